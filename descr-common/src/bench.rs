@@ -0,0 +1,303 @@
+//! Benchmarks of generated parsers, used by
+//! the bench crate generated with --bench.
+//! Gives throughput over sample files,
+//! allocations when CountingAlloc is the
+//! global allocator, and time per rule for
+//! parsers generated with tracing:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOC: CountingAlloc = CountingAlloc;
+//!
+//! bench::run(&args, "samples", parse, Some(parse_traced));
+//! ```
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use trace;
+
+/// System allocator counting allocations
+pub struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        if new_size > layout.size() {
+            ALLOCATED.fetch_add(new_size - layout.size(), Ordering::Relaxed);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Allocations so far, zero unless
+/// CountingAlloc is the global allocator
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Allocations {
+    pub count: usize,
+    pub bytes: usize,
+}
+
+pub fn allocations() -> Allocations {
+    Allocations {
+        count: ALLOCATIONS.load(Ordering::Relaxed),
+        bytes: ALLOCATED.load(Ordering::Relaxed),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub name: String,
+    pub input: Vec<u8>,
+}
+
+/// Files, and files in dirs, sorted by name
+pub fn read_samples(paths: &[String]) -> io::Result<Vec<Sample>> {
+    let mut samples = Vec::new();
+    for path in paths {
+        let path = Path::new(path);
+        if path.is_dir() {
+            let mut files = Vec::new();
+            for entry in fs::read_dir(path)? {
+                let file = entry?.path();
+                if file.is_file() {
+                    files.push(file);
+                }
+            }
+            files.sort();
+            for file in files {
+                samples.push(Sample {
+                    name: file.display().to_string(),
+                    input: fs::read(&file)?,
+                });
+            }
+        } else {
+            samples.push(Sample {
+                name: path.display().to_string(),
+                input: fs::read(path)?,
+            });
+        }
+    }
+    Ok(samples)
+}
+
+/// Rounds of parsing all samples
+#[derive(Debug, Clone)]
+pub struct Measure {
+    pub bytes: usize,
+    pub rounds: usize,
+    pub elapsed: Duration,
+    pub allocations: Allocations,
+}
+impl Measure {
+    pub fn mb_per_sec(&self) -> f64 {
+        (self.bytes * self.rounds) as f64 / secs(self.elapsed) / 1e6
+    }
+}
+
+pub fn measure<F>(samples: &[Sample], rounds: usize, mut parse: F) -> Measure
+where
+    F: FnMut(&[u8]),
+{
+    let before = allocations();
+    let start = Instant::now();
+    for _ in 0..rounds {
+        for sample in samples {
+            parse(&sample.input);
+        }
+    }
+    let elapsed = start.elapsed();
+    let after = allocations();
+    Measure {
+        bytes: samples.iter().map(|sample| sample.input.len()).sum(),
+        rounds,
+        elapsed,
+        allocations: Allocations {
+            count: after.count - before.count,
+            bytes: after.bytes - before.bytes,
+        },
+    }
+}
+
+/// Time of a rule, alternative or token.
+/// Total is counted for the outermost
+/// call when the rule is recursive.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleTime {
+    pub calls: usize,
+    pub total: Duration,
+    pub self_time: Duration,
+}
+
+/// Times attempts recorded by trace_wrap!,
+/// through trace::profile
+#[derive(Debug, Default)]
+pub struct Profiler {
+    open: Vec<(&'static str, Instant, Duration)>,
+    depths: HashMap<&'static str, usize>,
+    pub rules: HashMap<&'static str, RuleTime>,
+}
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    pub fn enter(&mut self, name: &'static str) {
+        *self.depths.entry(name).or_insert(0) += 1;
+        self.open.push((name, Instant::now(), Duration::from_secs(0)));
+    }
+
+    pub fn exit(&mut self) {
+        if let Some((name, start, children)) = self.open.pop() {
+            let elapsed = start.elapsed();
+            if let Some(parent) = self.open.last_mut() {
+                parent.2 += elapsed;
+            }
+            let depth = self.depths.get_mut(name).unwrap();
+            *depth -= 1;
+            let time = self.rules.entry(name).or_default();
+            time.calls += 1;
+            if *depth == 0 {
+                time.total += elapsed;
+            }
+            time.self_time += elapsed.checked_sub(children).unwrap_or_default();
+        }
+    }
+
+    /// Rules by self time, at most limit
+    pub fn to_text(&self, limit: usize) -> String {
+        let mut rules = self.rules.iter().collect::<Vec<_>>();
+        rules.sort_by(|a, b| match b.1.self_time.cmp(&a.1.self_time) {
+            CmpOrdering::Equal => a.0.cmp(b.0),
+            ordering => ordering,
+        });
+        let self_sum = secs(self.rules.values().map(|time| time.self_time).sum());
+        let mut s = format!(
+            "{:<30} {:>10} {:>10} {:>10} {:>7}\n",
+            "rule", "calls", "total ms", "self ms", "self %"
+        );
+        for (name, time) in rules.into_iter().take(limit) {
+            s += &format!(
+                "{:<30} {:>10} {:>10.2} {:>10.2} {:>7.1}\n",
+                name,
+                time.calls,
+                secs(time.total) * 1e3,
+                secs(time.self_time) * 1e3,
+                if self_sum > 0.0 { secs(time.self_time) / self_sum * 100.0 } else { 0.0 }
+            );
+        }
+        s
+    }
+}
+
+fn secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1e9
+}
+
+/// Runs the bench on the files and dirs in args,
+/// or the samples dir. Parse gives whether
+/// input parsed, traced is used for rule times.
+/// `--rounds n` sets the number of rounds.
+pub fn run(args: &[String], samples_dir: &str, parse: fn(&[u8]) -> bool, traced: Option<fn(&[u8]) -> bool>) {
+    let mut rounds = 20;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--rounds" {
+            rounds = match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) => n,
+                None => exit_with("Usage: bench [files or dirs..] [--rounds n]"),
+            };
+        } else {
+            paths.push(arg.clone());
+        }
+    }
+    if paths.is_empty() {
+        paths.push(samples_dir.to_string());
+    }
+    let samples = match read_samples(&paths) {
+        Ok(samples) => samples,
+        Err(err) => exit_with(&format!("Could not read samples: {}", err)),
+    };
+    let failed = samples.iter().filter(|sample| !parse(&sample.input)).collect::<Vec<_>>();
+    for sample in &failed {
+        eprintln!("Did not parse: {}", sample.name);
+    }
+    let res = measure(&samples, rounds, |input| {
+        parse(input);
+    });
+    println!(
+        "{} samples, {} bytes, {} did not parse",
+        samples.len(),
+        res.bytes,
+        failed.len()
+    );
+    println!("{:.2} MB/s over {} rounds", res.mb_per_sec(), rounds);
+    if res.allocations.count > 0 {
+        let per_round = res.allocations.count / rounds.max(1);
+        println!(
+            "{} allocations, {} bytes per round, {:.1} allocations per KB",
+            per_round,
+            res.allocations.bytes / rounds.max(1),
+            per_round as f64 * 1000.0 / res.bytes.max(1) as f64
+        );
+    } else {
+        println!("Allocations not counted, CountingAlloc is not the global allocator");
+    }
+    if let Some(traced) = traced {
+        let mut profiler = Profiler::new();
+        for sample in &samples {
+            trace::profile(&mut profiler, || traced(&sample.input));
+        }
+        println!();
+        print!("{}", profiler.to_text(30));
+    }
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_nested_rules() {
+        let mut profiler = Profiler::new();
+        profiler.enter("Expr");
+        profiler.enter("Expr");
+        profiler.enter("INT");
+        profiler.exit();
+        profiler.exit();
+        profiler.enter("PLUS");
+        profiler.exit();
+        profiler.exit();
+        let expr = &profiler.rules["Expr"];
+        assert_eq!(expr.calls, 2);
+        assert!(expr.total >= profiler.rules["INT"].total + profiler.rules["PLUS"].total);
+        assert!(expr.self_time <= expr.total);
+        let text = profiler.to_text(2);
+        assert!(text.starts_with("rule "));
+        assert_eq!(text.lines().count(), 3);
+    }
+}
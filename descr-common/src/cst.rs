@@ -0,0 +1,421 @@
+//! Lossless syntax trees, used by generated
+//! cst.rs files.
+//!
+//! Green nodes hold kinds, text and children,
+//! and can be shared. Syntax nodes are views
+//! over green nodes with offsets and parents.
+//! All input is kept in tokens, including
+//! whitespace, so the text of a tree is
+//! the parsed input.
+//!
+//! Children can have a label, which is the
+//! member name of the part in the rule, so
+//! typed views can find members.
+use std::fmt;
+use std::rc::Rc;
+use cursor::{self, State};
+
+/// Member name of child
+pub type Label = Option<&'static str>;
+
+/// Kinds are generated per grammar
+pub trait CstKind: Copy + PartialEq + fmt::Debug {
+    fn root() -> Self;
+    fn whitespace() -> Self;
+    /// Text collected by until (!) parts
+    fn text() -> Self;
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GreenToken<K> {
+    pub kind: K,
+    pub text: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GreenNode<K> {
+    pub kind: K,
+    pub len: usize,
+    pub children: Vec<GreenChild<K>>,
+}
+impl<K: CstKind> GreenNode<K> {
+    pub fn write_text(&self, s: &mut String) {
+        for child in &self.children {
+            match child.element {
+                GreenElement::Node(ref node) => node.write_text(s),
+                GreenElement::Token(ref token) => s.push_str(&token.text),
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GreenElement<K> {
+    Node(Rc<GreenNode<K>>),
+    Token(Rc<GreenToken<K>>),
+}
+impl<K> GreenElement<K> {
+    pub fn len(&self) -> usize {
+        match self {
+            &GreenElement::Node(ref node) => node.len,
+            &GreenElement::Token(ref token) => token.text.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GreenChild<K> {
+    pub label: Label,
+    pub element: GreenElement<K>,
+}
+
+/// Node with position in the tree
+#[derive(Debug, Clone)]
+pub struct SyntaxNode<K> {
+    green: Rc<GreenNode<K>>,
+    offset: usize,
+    parent: Option<Rc<SyntaxNode<K>>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntaxToken<K> {
+    green: Rc<GreenToken<K>>,
+    offset: usize,
+    parent: Rc<SyntaxNode<K>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SyntaxElement<K> {
+    Node(SyntaxNode<K>),
+    Token(SyntaxToken<K>),
+}
+
+impl<K: CstKind> SyntaxNode<K> {
+    pub fn new_root(green: Rc<GreenNode<K>>) -> SyntaxNode<K> {
+        SyntaxNode {
+            green,
+            offset: 0,
+            parent: None,
+        }
+    }
+
+    pub fn kind(&self) -> K {
+        self.green.kind
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode<K>> {
+        &self.green
+    }
+
+    /// Start and end offset in the input
+    pub fn range(&self) -> (usize, usize) {
+        (self.offset, self.offset + self.green.len)
+    }
+
+    pub fn parent(&self) -> Option<&SyntaxNode<K>> {
+        self.parent.as_ref().map(|parent| &**parent)
+    }
+
+    /// Children with their labels
+    pub fn labeled_children(&self) -> Vec<(Label, SyntaxElement<K>)> {
+        let parent = Rc::new(self.clone());
+        let mut offset = self.offset;
+        let mut children = Vec::with_capacity(self.green.children.len());
+        for child in &self.green.children {
+            let element = match child.element {
+                GreenElement::Node(ref node) => SyntaxElement::Node(SyntaxNode {
+                    green: node.clone(),
+                    offset,
+                    parent: Some(parent.clone()),
+                }),
+                GreenElement::Token(ref token) => SyntaxElement::Token(SyntaxToken {
+                    green: token.clone(),
+                    offset,
+                    parent: parent.clone(),
+                }),
+            };
+            offset += child.element.len();
+            children.push((child.label, element));
+        }
+        children
+    }
+
+    pub fn children(&self) -> Vec<SyntaxElement<K>> {
+        self.labeled_children()
+            .into_iter()
+            .map(|(_, element)| element)
+            .collect()
+    }
+
+    pub fn child_nodes(&self) -> Vec<SyntaxNode<K>> {
+        self.children()
+            .into_iter()
+            .filter_map(|element| match element {
+                SyntaxElement::Node(node) => Some(node),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// First child with label
+    pub fn child(&self, label: &str) -> Option<SyntaxElement<K>> {
+        self.labeled_children()
+            .into_iter()
+            .find(|&(child_label, _)| child_label == Some(label))
+            .map(|(_, element)| element)
+    }
+
+    pub fn child_node(&self, label: &str) -> Option<SyntaxNode<K>> {
+        match self.child(label) {
+            Some(SyntaxElement::Node(node)) => Some(node),
+            _ => None,
+        }
+    }
+
+    pub fn child_token(&self, label: &str) -> Option<SyntaxToken<K>> {
+        match self.child(label) {
+            Some(SyntaxElement::Token(token)) => Some(token),
+            _ => None,
+        }
+    }
+
+    /// Source text of the node, the same
+    /// as the parsed input
+    pub fn text(&self) -> String {
+        let mut s = String::with_capacity(self.green.len);
+        self.green.write_text(&mut s);
+        s
+    }
+
+    /// Indented tree of kinds, labels and ranges
+    pub fn debug_tree(&self) -> String {
+        let mut s = String::new();
+        self.add_debug_tree(&mut s, None, 0);
+        s
+    }
+
+    fn add_debug_tree(&self, s: &mut String, label: Label, indent: usize) {
+        let (start, end) = self.range();
+        add_debug_line(s, label, indent, &format!("{:?}@{}..{}", self.kind(), start, end));
+        for (label, child) in self.labeled_children() {
+            match child {
+                SyntaxElement::Node(node) => node.add_debug_tree(s, label, indent + 1),
+                SyntaxElement::Token(token) => {
+                    let (start, end) = token.range();
+                    add_debug_line(
+                        s,
+                        label,
+                        indent + 1,
+                        &format!("{:?}@{}..{} {:?}", token.kind(), start, end, token.text()),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn add_debug_line(s: &mut String, label: Label, indent: usize, line: &str) {
+    for _ in 0..indent {
+        s.push_str("  ");
+    }
+    if let Some(label) = label {
+        s.push_str(label);
+        s.push_str(": ");
+    }
+    s.push_str(line);
+    s.push('\n');
+}
+
+impl<K: CstKind> fmt::Display for SyntaxNode<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text())
+    }
+}
+
+impl<K: CstKind> SyntaxToken<K> {
+    pub fn kind(&self) -> K {
+        self.green.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn range(&self) -> (usize, usize) {
+        (self.offset, self.offset + self.green.text.len())
+    }
+
+    pub fn parent(&self) -> &SyntaxNode<K> {
+        &self.parent
+    }
+}
+
+impl<K: CstKind> SyntaxElement<K> {
+    pub fn kind(&self) -> K {
+        match self {
+            &SyntaxElement::Node(ref node) => node.kind(),
+            &SyntaxElement::Token(ref token) => token.kind(),
+        }
+    }
+}
+
+pub use cursor::{Fail, PResult};
+pub use cursor::ParseError as CstError;
+
+pub type Res = Result<(), Fail>;
+
+/// Children of nodes being built, as cursor
+/// state, so they are removed on backtrack
+pub struct Builder<K> {
+    children: Vec<GreenChild<K>>,
+}
+
+impl<K: CstKind> State for Builder<K> {
+    type Mark = usize;
+
+    fn mark(&self) -> usize {
+        self.children.len()
+    }
+
+    fn reset(&mut self, len: usize) {
+        self.children.truncate(len);
+    }
+
+    /// Whitespace token, so the
+    /// tree keeps all input
+    fn skipped(&mut self, whitespace: &[u8]) {
+        self.push_token(K::whitespace(), None, whitespace);
+    }
+}
+
+impl<K> Builder<K> {
+    fn push_token(&mut self, kind: K, label: Label, text: &[u8]) {
+        let text = String::from_utf8_lossy(text).into_owned();
+        self.children.push(GreenChild {
+            label,
+            element: GreenElement::Token(Rc::new(GreenToken { kind, text })),
+        });
+    }
+}
+
+/// Builds green nodes while parsing, with
+/// the combinators of the cursor module,
+/// so the trees have the same shape as
+/// the ast.
+pub type Cursor<'i, K> = cursor::Cursor<'i, Builder<K>>;
+
+impl<'i, K: CstKind> Cursor<'i, K> {
+    /// Node from children added by parser,
+    /// removed again when parser fails
+    pub fn node<T, F>(&mut self, kind: K, label: Label, parser: F) -> Res
+    where
+        F: FnOnce(&mut Cursor<'i, K>) -> PResult<T>,
+    {
+        let mark = self.mark();
+        let len = self.state.children.len();
+        if let Err(fail) = parser(self) {
+            self.reset(mark);
+            return Err(fail);
+        }
+        let children = self.state.children.split_off(len);
+        self.state.children.push(GreenChild {
+            label,
+            element: GreenElement::Node(Rc::new(GreenNode {
+                kind,
+                len: self.pos - mark.pos,
+                children,
+            })),
+        });
+        Ok(())
+    }
+
+    /// Token of the input consumed by parser
+    pub fn token<T, F>(&mut self, kind: K, label: Label, parser: F) -> Res
+    where
+        F: FnOnce(&mut Cursor<'i, K>) -> PResult<T>,
+    {
+        let start = self.pos;
+        parser(self)?;
+        let text = &self.input()[start..self.pos];
+        self.state.push_token(kind, label, text);
+        Ok(())
+    }
+}
+
+/// Lossless tree of input from the start
+/// parser, trailing whitespace is added
+/// to the root
+pub fn parse<'i, K, F>(input: &'i [u8], start: F) -> Result<SyntaxNode<K>, CstError>
+where
+    K: CstKind,
+    F: FnOnce(&mut Cursor<'i, K>) -> Res,
+{
+    let mut c = Cursor::with_state(input, Builder { children: Vec::new() });
+    let res = start(&mut c);
+    let ((), builder) = c.finish(res)?;
+    Ok(SyntaxNode::new_root(Rc::new(GreenNode {
+        kind: K::root(),
+        len: input.len(),
+        children: builder.children,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Kind {
+        Root,
+        Ws,
+        Text,
+        Pair,
+        Ident,
+        Int,
+    }
+    impl CstKind for Kind {
+        fn root() -> Kind {
+            Kind::Root
+        }
+        fn whitespace() -> Kind {
+            Kind::Ws
+        }
+        fn text() -> Kind {
+            Kind::Text
+        }
+    }
+
+    fn pair(c: &mut Cursor<Kind>, label: Label) -> Res {
+        c.node(Kind::Pair, label, |c| {
+            c.sp();
+            c.token(Kind::Ident, Some("key"), |c| c.ident())?;
+            c.sp();
+            c.token(Kind::Int, Some("val"), |c| c.int())
+        })
+    }
+
+    #[test]
+    fn drops_children_on_backtrack() {
+        let root = parse(b" a 1 b x ", |c| {
+            c.many0(|c| pair(c, None))?;
+            // Ident of the failed pair is dropped
+            c.attempt(|c| pair(c, None));
+            c.sp();
+            c.token(Kind::Text, None, |c| c.until(|c| c.tag(" ")))?;
+            c.sp();
+            c.token(Kind::Ident, None, |c| c.ident())
+        })
+        .unwrap();
+        assert_eq!(root.text(), " a 1 b x ");
+        assert_eq!(
+            root.debug_tree(),
+            "Root@0..9\n  Pair@0..4\n    Ws@0..1 \" \"\n    key: Ident@1..2 \"a\"\n    \
+             Ws@2..3 \" \"\n    val: Int@3..4 \"1\"\n  Ws@4..5 \" \"\n  Text@5..6 \"b\"\n  \
+             Ws@6..7 \" \"\n  Ident@7..8 \"x\"\n  Ws@8..9 \" \"\n"
+        );
+    }
+}
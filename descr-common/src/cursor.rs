@@ -0,0 +1,606 @@
+//! Input cursor with the combinators used by
+//! parsers that don't generate nom macros. The
+//! recursive descent backend calls it from plain
+//! functions, the cst parsers build green nodes
+//! with it, and the interpreter in descr-gen
+//! parses with it from lang data. The semantics
+//! are the same as the nom macros used by the nom
+//! backend. Backtracking is explicit, with mark
+//! and reset.
+//!
+//! Whitespace before parts is skipped with sp,
+//! and the last skip is remembered, so trying
+//! alternatives from the same position doesn't
+//! scan the whitespace again.
+//!
+//! The cursor carries state for the parse, like
+//! tables for memoized rules or nodes being
+//! built. State is marked and reset with the
+//! position when parsing backtracks.
+extern crate nom;
+use self::nom::IResult;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::str;
+use parsers::{ident, quoted_str};
+
+/// Parse failure, mirrors nom Error
+/// and Incomplete
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fail {
+    Error,
+    Incomplete,
+}
+
+pub type PResult<T> = Result<T, Fail>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// Furthest offset a token was tried
+    pub offset: usize,
+    pub incomplete: bool,
+    /// The start rule matched, but
+    /// input remains
+    pub trailing: bool,
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.incomplete {
+            write!(f, "Unexpected end of input at offset {}", self.offset)
+        } else if self.trailing {
+            write!(f, "Unexpected input at offset {}", self.offset)
+        } else {
+            write!(f, "Parse error at offset {}", self.offset)
+        }
+    }
+}
+
+/// State of a parse, reset with the
+/// position when parsing backtracks
+pub trait State {
+    type Mark: Copy;
+    fn mark(&self) -> Self::Mark;
+    fn reset(&mut self, mark: Self::Mark);
+    /// Whitespace skipped by sp
+    fn skipped(&mut self, _whitespace: &[u8]) {}
+}
+
+impl State for () {
+    type Mark = ();
+    fn mark(&self) {}
+    fn reset(&mut self, _mark: ()) {}
+}
+
+/// Results of a memoized rule by start
+/// position, with the end position
+pub type MemoTable<T> = HashMap<usize, Result<(T, usize), Fail>, BuildHasherDefault<PosHasher>>;
+
+/// Hasher for positions, cheaper than
+/// the default hasher
+#[derive(Default)]
+pub struct PosHasher {
+    hash: u64,
+}
+impl Hasher for PosHasher {
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.hash = (self.hash.rotate_left(5) ^ u64::from(*b)).wrapping_mul(0x517c_c1b7_2722_0a95);
+        }
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.hash = (self.hash.rotate_left(5) ^ n as u64).wrapping_mul(0x517c_c1b7_2722_0a95);
+    }
+}
+
+/// Position to backtrack to
+#[derive(Debug, Clone, Copy)]
+pub struct Mark<M> {
+    pub pos: usize,
+    state: M,
+}
+
+pub struct Cursor<'a, S = ()> {
+    input: &'a [u8],
+    pub pos: usize,
+    furthest: usize,
+    // Last whitespace skipped
+    sp_from: usize,
+    sp_to: usize,
+    pub state: S,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(input: &'a [u8]) -> Cursor<'a> {
+        Cursor::with_state(input, ())
+    }
+}
+
+/// Offset after whitespace from pos
+pub fn ws_end(input: &[u8], pos: usize) -> usize {
+    pos + input[pos..]
+        .iter()
+        .take_while(|c| match **c {
+            b' ' | b'\t' | b'\r' | b'\n' => true,
+            _ => false,
+        })
+        .count()
+}
+
+impl<'a, S: State> Cursor<'a, S> {
+    pub fn with_state(input: &'a [u8], state: S) -> Cursor<'a, S> {
+        Cursor {
+            input,
+            pos: 0,
+            furthest: 0,
+            sp_from: usize::MAX,
+            sp_to: 0,
+            state,
+        }
+    }
+
+    pub fn input(&self) -> &'a [u8] {
+        self.input
+    }
+
+    pub fn rest(&self) -> &'a [u8] {
+        &self.input[self.pos..]
+    }
+
+    /// Furthest position a token was tried
+    pub fn furthest(&self) -> usize {
+        self.furthest
+    }
+
+    pub fn mark(&self) -> Mark<S::Mark> {
+        Mark {
+            pos: self.pos,
+            state: self.state.mark(),
+        }
+    }
+
+    pub fn reset(&mut self, mark: Mark<S::Mark>) {
+        self.pos = mark.pos;
+        self.state.reset(mark.state);
+    }
+
+    /// Err(Fail::Error), recording the
+    /// furthest position tried
+    pub fn fail<T>(&mut self) -> PResult<T> {
+        self.fail_with(Fail::Error)
+    }
+
+    pub fn fail_with<T>(&mut self, fail: Fail) -> PResult<T> {
+        if self.pos > self.furthest {
+            self.furthest = self.pos;
+        }
+        Err(fail)
+    }
+
+    fn take(&mut self, len: usize) -> &'a [u8] {
+        let start = self.pos;
+        self.pos += len;
+        &self.input[start..self.pos]
+    }
+
+    /// sp, skips whitespace
+    pub fn sp(&mut self) {
+        if self.pos != self.sp_from {
+            self.sp_from = self.pos;
+            self.sp_to = ws_end(self.input, self.pos);
+        }
+        if self.sp_to > self.pos {
+            let whitespace = self.take(self.sp_to - self.pos);
+            self.state.skipped(whitespace);
+        }
+    }
+
+    /// WS part, the whitespace as value
+    pub fn ws(&mut self) -> PResult<&'a [u8]> {
+        let end = ws_end(self.input, self.pos);
+        Ok(self.take(end - self.pos))
+    }
+
+    /// char!
+    pub fn chr(&mut self, chr: char) -> PResult<char> {
+        match self.input.get(self.pos) {
+            None => self.fail_with(Fail::Incomplete),
+            Some(&c) if c as char == chr => {
+                self.pos += 1;
+                Ok(chr)
+            }
+            Some(_) => self.fail(),
+        }
+    }
+
+    /// tag!, Incomplete when input
+    /// is a prefix of the tag
+    pub fn tag(&mut self, tag: &str) -> PResult<&'a [u8]> {
+        let rest = &self.input[self.pos..];
+        let tag = tag.as_bytes();
+        let len = if rest.len() < tag.len() { rest.len() } else { tag.len() };
+        if rest[..len] != tag[..len] {
+            self.fail()
+        } else if len < tag.len() {
+            self.fail_with(Fail::Incomplete)
+        } else {
+            Ok(self.take(len))
+        }
+    }
+
+    pub fn ident(&mut self) -> PResult<&'a str> {
+        let res = ident(self.rest());
+        self.nom_result(res)
+    }
+
+    /// Quoted string, without the quotes
+    pub fn string(&mut self) -> PResult<&'a str> {
+        let res = quoted_str(self.rest());
+        self.nom_result(res)
+    }
+
+    /// Digits with optional sign, like parse_int,
+    /// but values that don't fit are errors
+    pub fn int(&mut self) -> PResult<u32> {
+        let rest = self.rest();
+        if rest.is_empty() {
+            return self.fail_with(Fail::Incomplete);
+        }
+        let mut len = match rest[0] {
+            b'0'..=b'9' => 1,
+            b'-' | b'+' if rest.len() > 1 && rest[1].is_ascii_digit() => 2,
+            _ => return self.fail(),
+        };
+        while len < rest.len() && rest[len].is_ascii_digit() {
+            len += 1;
+        }
+        match str::from_utf8(&rest[..len]).unwrap().parse::<u32>() {
+            Ok(value) => {
+                self.pos += len;
+                Ok(value)
+            }
+            Err(_) => self.fail(),
+        }
+    }
+
+    /// Nom parser, for functions in the grammar
+    pub fn nom<O, F>(&mut self, parser: F) -> PResult<O>
+    where
+        F: FnOnce(&'a [u8]) -> IResult<&'a [u8], O>,
+    {
+        let res = parser(self.rest());
+        self.nom_result(res)
+    }
+
+    fn nom_result<O>(&mut self, res: IResult<&'a [u8], O>) -> PResult<O> {
+        match res {
+            IResult::Done(rest, value) => {
+                self.pos = self.input.len() - rest.len();
+                Ok(value)
+            }
+            IResult::Error(_) => self.fail(),
+            IResult::Incomplete(_) => self.fail_with(Fail::Incomplete),
+        }
+    }
+
+    /// opt!, None when parser gives Error
+    pub fn opt<T, F>(&mut self, parser: F) -> PResult<Option<T>>
+    where
+        F: FnOnce(&mut Cursor<'a, S>) -> PResult<T>,
+    {
+        let mark = self.mark();
+        match parser(self) {
+            Ok(value) => Ok(Some(value)),
+            Err(Fail::Error) => {
+                self.reset(mark);
+                Ok(None)
+            }
+            Err(Fail::Incomplete) => {
+                self.reset(mark);
+                Err(Fail::Incomplete)
+            }
+        }
+    }
+
+    /// Alternative for alt_complete!, resets
+    /// and gives None when parser fails
+    pub fn attempt<T, F>(&mut self, parser: F) -> Option<T>
+    where
+        F: FnOnce(&mut Cursor<'a, S>) -> PResult<T>,
+    {
+        let mark = self.mark();
+        match parser(self) {
+            Ok(value) => Some(value),
+            Err(_) => {
+                self.reset(mark);
+                None
+            }
+        }
+    }
+
+    /// Parses with parser, or gives the result
+    /// in table from an earlier parse at the
+    /// same position
+    pub fn memo<T, G, F>(&mut self, table: G, parser: F) -> PResult<T>
+    where
+        T: Clone,
+        G: Fn(&mut S) -> &mut MemoTable<T>,
+        F: FnOnce(&mut Cursor<'a, S>) -> PResult<T>,
+    {
+        let start = self.pos;
+        if let Some(res) = table(&mut self.state).get(&start).cloned() {
+            return res.map(|(value, end)| {
+                self.pos = end;
+                value
+            });
+        }
+        let res = parser(self);
+        let entry = match res {
+            Ok(ref value) => Ok((value.clone(), self.pos)),
+            Err(fail) => Err(fail),
+        };
+        table(&mut self.state).insert(start, entry);
+        res
+    }
+
+    /// until_done_result!, input until parser
+    /// matches, Incomplete when it's empty
+    pub fn until<T, F>(&mut self, parser: F) -> PResult<&'a [u8]>
+    where
+        F: Fn(&mut Cursor<'a, S>) -> PResult<T>,
+    {
+        let mark = self.mark();
+        let mut index = 0;
+        let mut is_done = false;
+        loop {
+            self.pos = mark.pos + index;
+            let res = parser(self);
+            // Only peeks
+            self.reset(mark);
+            if res.is_ok() {
+                is_done = true;
+                break;
+            }
+            index += 1;
+            if mark.pos + index > self.input.len() {
+                break;
+            }
+        }
+        if is_done && index > 0 {
+            Ok(self.take(index))
+        } else {
+            self.fail_with(Fail::Incomplete)
+        }
+    }
+
+    /// many0!
+    pub fn many0<T, F>(&mut self, item: F) -> PResult<Vec<T>>
+    where
+        F: Fn(&mut Cursor<'a, S>) -> PResult<T>,
+    {
+        let mut items = Vec::new();
+        while self.pos < self.input.len() {
+            let mark = self.mark();
+            match item(self) {
+                Ok(value) => {
+                    if self.pos == mark.pos {
+                        self.reset(mark);
+                        return self.fail();
+                    }
+                    items.push(value);
+                }
+                Err(Fail::Error) => {
+                    self.reset(mark);
+                    break;
+                }
+                Err(Fail::Incomplete) => return Err(Fail::Incomplete),
+            }
+        }
+        Ok(items)
+    }
+
+    /// separated_list!, there is no
+    /// whitespace before separators
+    pub fn separated_list<P, T, U, F>(&mut self, sep: P, item: F) -> PResult<Vec<T>>
+    where
+        P: Fn(&mut Cursor<'a, S>) -> PResult<U>,
+        F: Fn(&mut Cursor<'a, S>) -> PResult<T>,
+    {
+        let mut items = Vec::new();
+        let mark = self.mark();
+        match item(self) {
+            Ok(value) => {
+                if self.pos == mark.pos {
+                    return self.fail();
+                }
+                items.push(value);
+            }
+            Err(Fail::Error) => {
+                self.reset(mark);
+                return Ok(items);
+            }
+            Err(Fail::Incomplete) => return Err(Fail::Incomplete),
+        }
+        loop {
+            let mark = self.mark();
+            match sep(self) {
+                Ok(_) => {
+                    if self.pos == mark.pos {
+                        self.reset(mark);
+                        break;
+                    }
+                }
+                Err(Fail::Error) => {
+                    self.reset(mark);
+                    break;
+                }
+                Err(Fail::Incomplete) => return Err(Fail::Incomplete),
+            }
+            let sep_end = self.pos;
+            match item(self) {
+                Ok(value) => {
+                    if self.pos == sep_end {
+                        self.reset(mark);
+                        break;
+                    }
+                    items.push(value);
+                }
+                Err(Fail::Error) => {
+                    self.reset(mark);
+                    break;
+                }
+                Err(Fail::Incomplete) => return Err(Fail::Incomplete),
+            }
+        }
+        Ok(items)
+    }
+
+    /// Result of the start parser and the state,
+    /// when only whitespace remains. Trailing
+    /// whitespace is skipped with sp.
+    pub fn finish<T>(mut self, res: PResult<T>) -> Result<(T, S), ParseError> {
+        match res {
+            Ok(value) => {
+                self.sp();
+                if self.pos < self.input.len() {
+                    return Err(ParseError {
+                        offset: if self.furthest > self.pos { self.furthest } else { self.pos },
+                        incomplete: false,
+                        trailing: true,
+                    });
+                }
+                Ok((value, self.state))
+            }
+            Err(fail) => Err(ParseError {
+                offset: self.furthest,
+                incomplete: fail == Fail::Incomplete,
+                trailing: false,
+            }),
+        }
+    }
+}
+
+/// Parses all of input with the start
+/// parser, trailing whitespace is allowed
+pub fn parse<'a, T, F>(input: &'a [u8], start: F) -> Result<T, ParseError>
+where
+    F: FnOnce(&mut Cursor<'a>) -> PResult<T>,
+{
+    parse_with(input, (), start)
+}
+
+/// parse, with state in the cursor
+pub fn parse_with<'a, S, T, F>(input: &'a [u8], state: S, start: F) -> Result<T, ParseError>
+where
+    S: State,
+    F: FnOnce(&mut Cursor<'a, S>) -> PResult<T>,
+{
+    let mut c = Cursor::with_state(input, state);
+    let res = start(&mut c);
+    c.finish(res).map(|(value, _)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair<'a>(c: &mut Cursor<'a>) -> PResult<(&'a str, u32)> {
+        c.sp();
+        let key = c.ident()?;
+        c.sp();
+        c.chr('=')?;
+        c.sp();
+        let val = c.int()?;
+        Ok((key, val))
+    }
+
+    #[test]
+    fn parses_with_backtracking() {
+        let res = parse(b" a = 1, b=2 ,c = 3; \n", |c| {
+            let pairs = c.separated_list(
+                |c| {
+                    c.sp();
+                    c.chr(',')
+                },
+                pair,
+            )?;
+            c.sp();
+            c.chr(';')?;
+            Ok(pairs)
+        });
+        assert_eq!(res, Ok(vec![("a", 1), ("b", 2), ("c", 3)]));
+        let res = parse(b"x = 1 y", |c| {
+            let first = c.attempt(|c| {
+                let pair = pair(c)?;
+                c.sp();
+                c.tag("z")?;
+                Ok(pair)
+            });
+            assert_eq!(first, None);
+            pair(c)
+        });
+        assert_eq!(res.unwrap_err(), ParseError {
+            offset: 6,
+            incomplete: false,
+            trailing: true,
+        });
+        let res = parse(b"(* note *)", |c| {
+            c.tag("(*")?;
+            let text = c.until(|c| c.tag("*)"))?;
+            c.tag("*)")?;
+            Ok(text)
+        });
+        assert_eq!(res, Ok(&b" note "[..]));
+        let res = parse(b"a = ", |c| c.many0(pair));
+        assert!(res.unwrap_err().incomplete);
+    }
+
+    #[derive(Default)]
+    struct Memo<'a> {
+        calls: usize,
+        pair: MemoTable<(&'a str, u32)>,
+    }
+    impl<'a> State for Memo<'a> {
+        type Mark = ();
+        fn mark(&self) {}
+        fn reset(&mut self, _mark: ()) {}
+    }
+
+    fn memo_pair<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<(&'a str, u32)> {
+        c.memo(|m| &mut m.pair, |c| {
+            c.state.calls += 1;
+            c.sp();
+            let key = c.ident()?;
+            c.sp();
+            c.chr('=')?;
+            c.sp();
+            let val = c.int()?;
+            Ok((key, val))
+        })
+    }
+
+    #[test]
+    fn memoizes_by_position() {
+        let mut c = Cursor::with_state(b"a = 1 b = 2", Memo::default());
+        let first = c.attempt(|c| {
+            memo_pair(c)?;
+            c.sp();
+            c.chr(';')
+        });
+        assert_eq!(first, None);
+        assert_eq!(memo_pair(&mut c), Ok(("a", 1)));
+        assert_eq!(c.pos, 5);
+        assert_eq!(memo_pair(&mut c), Ok(("b", 2)));
+        assert_eq!(c.state.calls, 2);
+        c.pos = 1;
+        assert_eq!(memo_pair(&mut c), Err(Fail::Error));
+        c.pos = 1;
+        assert_eq!(memo_pair(&mut c), Err(Fail::Error));
+        assert_eq!(c.state.calls, 3);
+    }
+}
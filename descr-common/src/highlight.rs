@@ -0,0 +1,357 @@
+//! Highlighting from parsed source. Tokens
+//! are spans with a TextMate like scope name,
+//! given by generated highlight.rs files or
+//! the interpreter, and rendered as html
+//! spans or as 24-bit ansi colors.
+//!
+//! Themes map scope prefixes to styles, one
+//! per line, where the longest prefix wins:
+//!
+//! ```text
+//! # comment
+//! keyword          = #c678dd bold
+//! constant.numeric = #d19a66
+//! string           = #98c379 italic
+//! ```
+use std::error::Error;
+use std::fmt;
+use cst::{CstKind, Label, SyntaxElement, SyntaxNode};
+
+/// Byte range of input with its scope
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighlightToken {
+    pub start: usize,
+    pub end: usize,
+    pub scope: String,
+}
+
+/// Tokens of tree in order, with scopes
+/// by parent kind, label and token kind
+pub fn cst_tokens<K, F>(root: &SyntaxNode<K>, scope: F) -> Vec<HighlightToken>
+where
+    K: CstKind,
+    F: Fn(K, Label, K) -> Option<&'static str>,
+{
+    let mut tokens = Vec::new();
+    add_cst_tokens(root, &scope, &mut tokens);
+    tokens
+}
+
+fn add_cst_tokens<K, F>(node: &SyntaxNode<K>, scope: &F, tokens: &mut Vec<HighlightToken>)
+where
+    K: CstKind,
+    F: Fn(K, Label, K) -> Option<&'static str>,
+{
+    for (label, child) in node.labeled_children() {
+        match child {
+            SyntaxElement::Node(ref child) => add_cst_tokens(child, scope, tokens),
+            SyntaxElement::Token(ref token) => {
+                let (start, end) = token.range();
+                if start == end {
+                    continue;
+                }
+                if let Some(scope) = scope(node.kind(), label, token.kind()) {
+                    tokens.push(HighlightToken {
+                        start,
+                        end,
+                        scope: scope.to_string(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    pub color: Option<(u8, u8, u8)>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+impl Style {
+    /// Sgr parameters, like 1;38;2;r;g;b
+    fn ansi_params(&self) -> String {
+        let mut params = Vec::new();
+        if self.bold {
+            params.push("1".to_string());
+        }
+        if self.italic {
+            params.push("3".to_string());
+        }
+        if self.underline {
+            params.push("4".to_string());
+        }
+        if let Some((r, g, b)) = self.color {
+            params.push(format!("38;2;{};{};{}", r, g, b));
+        }
+        params.join(";")
+    }
+
+    fn css(&self) -> String {
+        let mut s = String::new();
+        if let Some((r, g, b)) = self.color {
+            s += &format!(" color: #{:02x}{:02x}{:02x};", r, g, b);
+        }
+        if self.bold {
+            s += " font-weight: bold;";
+        }
+        if self.italic {
+            s += " font-style: italic;";
+        }
+        if self.underline {
+            s += " text-decoration: underline;";
+        }
+        s
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeError {
+    /// Line in theme, starting at 1
+    pub line: usize,
+    pub message: String,
+}
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Theme error at line {}: {}", self.line, self.message)
+    }
+}
+impl Error for ThemeError {}
+
+const DEFAULT_THEME: &str = "\
+comment          = #7f848e italic
+keyword          = #c678dd
+keyword.operator = #56b6c2
+storage          = #c678dd
+constant         = #d19a66
+string           = #98c379
+variable         = #e06c75
+entity.name      = #61afef
+support          = #e5c07b
+punctuation      = #abb2bf
+";
+
+/// Styles by scope prefix
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub styles: Vec<(String, Style)>,
+}
+impl Theme {
+    pub fn parse(source: &str) -> Result<Theme, ThemeError> {
+        let mut styles = Vec::new();
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| ThemeError { line: i + 1, message };
+            let eq = match line.find('=') {
+                Some(eq) => eq,
+                None => return Err(error("Expected scope = style".to_string())),
+            };
+            let scope = line[..eq].trim();
+            if scope.is_empty() {
+                return Err(error("Missing scope".to_string()));
+            }
+            let mut style = Style::default();
+            for word in line[eq + 1..].split_whitespace() {
+                match word {
+                    "bold" => style.bold = true,
+                    "italic" => style.italic = true,
+                    "underline" => style.underline = true,
+                    _ if word.starts_with('#') => match parse_color(&word[1..]) {
+                        Some(color) => style.color = Some(color),
+                        None => return Err(error(format!("Invalid color: {}", word))),
+                    },
+                    _ => return Err(error(format!("Unknown style: {}", word))),
+                }
+            }
+            styles.push((scope.to_string(), style));
+        }
+        Ok(Theme { styles })
+    }
+
+    /// Style of longest matching prefix,
+    /// matched by whole dot separated parts
+    pub fn style(&self, scope: &str) -> Option<&Style> {
+        self.styles
+            .iter()
+            .filter(|&&(ref prefix, _)| {
+                scope == prefix || (scope.starts_with(prefix.as_str()) && scope[prefix.len()..].starts_with('.'))
+            })
+            .max_by_key(|&&(ref prefix, _)| prefix.len())
+            .map(|&(_, ref style)| style)
+    }
+
+    /// Stylesheet for html from to_html.
+    /// More parts gives a more specific
+    /// selector, like the longest prefix.
+    pub fn to_css(&self) -> String {
+        let mut s = String::new();
+        for &(ref scope, ref style) in &self.styles {
+            s += ".highlight .";
+            s += scope;
+            s += " {";
+            s += &style.css();
+            s += " }\n";
+        }
+        s
+    }
+}
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::parse(DEFAULT_THEME).unwrap()
+    }
+}
+
+fn parse_color(hex: &str) -> Option<(u8, u8, u8)> {
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Ordered, non overlapping tokens within
+/// text, and on char boundaries
+fn valid_tokens<'t>(text: &str, tokens: &'t [HighlightToken]) -> Vec<&'t HighlightToken> {
+    let mut sorted = tokens
+        .iter()
+        .filter(|token| {
+            token.start < token.end
+                && token.end <= text.len()
+                && text.is_char_boundary(token.start)
+                && text.is_char_boundary(token.end)
+        })
+        .collect::<Vec<_>>();
+    sorted.sort_by_key(|token| token.start);
+    let mut valid = Vec::with_capacity(sorted.len());
+    let mut pos = 0;
+    for token in sorted {
+        if token.start >= pos {
+            pos = token.end;
+            valid.push(token);
+        }
+    }
+    valid
+}
+
+/// Text with html special chars escaped
+pub fn html_escape(s: &mut String, text: &str) {
+    for chr in text.chars() {
+        match chr {
+            '&' => s.push_str("&amp;"),
+            '<' => s.push_str("&lt;"),
+            '>' => s.push_str("&gt;"),
+            '"' => s.push_str("&quot;"),
+            other => s.push(other),
+        }
+    }
+}
+
+/// Pre element with a span for each token,
+/// with the scope parts as classes
+pub fn to_html(text: &str, tokens: &[HighlightToken]) -> String {
+    let mut s = String::with_capacity(text.len() * 2);
+    s += "<pre class=\"highlight\">";
+    let mut pos = 0;
+    for token in valid_tokens(text, tokens) {
+        html_escape(&mut s, &text[pos..token.start]);
+        s += "<span class=\"";
+        html_escape(&mut s, &token.scope.replace('.', " "));
+        s += "\">";
+        html_escape(&mut s, &text[token.start..token.end]);
+        s += "</span>";
+        pos = token.end;
+    }
+    html_escape(&mut s, &text[pos..]);
+    s += "</pre>\n";
+    s
+}
+
+/// Text with 24-bit ansi colors from theme.
+/// Styles are reset at line ends, so lines
+/// can be shown on their own.
+pub fn to_ansi(text: &str, tokens: &[HighlightToken], theme: &Theme) -> String {
+    let mut s = String::with_capacity(text.len() * 2);
+    let mut pos = 0;
+    for token in valid_tokens(text, tokens) {
+        s += &text[pos..token.start];
+        let params = match theme.style(&token.scope) {
+            Some(style) => style.ansi_params(),
+            None => String::new(),
+        };
+        let token_text = &text[token.start..token.end];
+        if params.is_empty() {
+            s += token_text;
+        } else {
+            for (i, line) in token_text.split('\n').enumerate() {
+                if i > 0 {
+                    s.push('\n');
+                }
+                if !line.is_empty() {
+                    s += "\x1b[";
+                    s += &params;
+                    s += "m";
+                    s += line;
+                    s += "\x1b[0m";
+                }
+            }
+        }
+        pos = token.end;
+    }
+    s += &text[pos..];
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(start: usize, end: usize, scope: &str) -> HighlightToken {
+        HighlightToken {
+            start,
+            end,
+            scope: scope.to_string(),
+        }
+    }
+
+    #[test]
+    fn theme_picks_longest_prefix() {
+        let theme = Theme::parse("# test\nkeyword = #ff0000 bold\nkeyword.operator = #00ff00\n").unwrap();
+        assert_eq!(theme.style("keyword.control").unwrap().color, Some((255, 0, 0)));
+        assert_eq!(theme.style("keyword.operator.new").unwrap().color, Some((0, 255, 0)));
+        assert!(theme.style("keywords").is_none());
+        assert_eq!(theme.to_css().lines().next(), Some(".highlight .keyword { color: #ff0000; font-weight: bold; }"));
+        let err = Theme::parse("string = #12345x\n").unwrap_err();
+        assert_eq!(err.line, 1);
+        assert!(Theme::parse("string italic").is_err());
+    }
+
+    #[test]
+    fn renders_html_and_ansi() {
+        let text = "say \"<hi>\"\nsay 2";
+        let tokens = vec![
+            token(0, 3, "keyword.other"),
+            token(4, 10, "string.quoted"),
+            token(11, 14, "keyword.other"),
+            // Overlapping tokens are skipped
+            token(12, 13, "variable"),
+            token(15, 16, "constant.numeric"),
+        ];
+        assert_eq!(
+            to_html(text, &tokens),
+            "<pre class=\"highlight\"><span class=\"keyword other\">say</span> \
+             <span class=\"string quoted\">&quot;&lt;hi&gt;&quot;</span>\n\
+             <span class=\"keyword other\">say</span> \
+             <span class=\"constant numeric\">2</span></pre>\n"
+        );
+        let theme = Theme::parse("keyword = #010203 bold\nconstant = #0a0b0c\n").unwrap();
+        assert_eq!(
+            to_ansi(text, &tokens, &theme),
+            "\x1b[1;38;2;1;2;3msay\x1b[0m \"<hi>\"\n\
+             \x1b[1;38;2;1;2;3msay\x1b[0m \x1b[38;2;10;11;12m2\x1b[0m"
+        );
+    }
+}
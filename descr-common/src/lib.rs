@@ -2,6 +2,13 @@
 pub mod util;
 #[macro_use]
 pub mod parsers;
+pub mod cst;
+pub mod cursor;
+pub mod highlight;
+pub mod json;
+#[macro_use]
+pub mod trace;
+pub mod bench;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,419 @@
+//! Parse traces, a tree of the rules,
+//! alternatives and tokens that were tried,
+//! with where they started and what they
+//! consumed. Failed attempts are kept, so
+//! the trace shows why an alternative failed.
+//!
+//! Generated parsers record with trace_wrap!
+//! when generated with tracing, into a
+//! recorder for the current thread:
+//!
+//! ```ignore
+//! let (res, trace) = trace::record(input, || parsers::start(input));
+//! println!("{}", trace.to_text());
+//! ```
+use std::cell::RefCell;
+use std::mem;
+use bench::Profiler;
+use highlight::html_escape;
+use json::json_str;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Done,
+    Error,
+    Incomplete,
+}
+impl Outcome {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Outcome::Done => "done",
+            Outcome::Error => "error",
+            Outcome::Incomplete => "incomplete",
+        }
+    }
+}
+
+/// Attempt of a rule, alternative or token.
+/// For failures, end is start.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceNode {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    pub outcome: Outcome,
+    pub children: Vec<TraceNode>,
+}
+
+/// Attempts in order, with the input
+/// the offsets are in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    pub input: String,
+    pub nodes: Vec<TraceNode>,
+}
+
+/// Builds the tree from enter and
+/// exit around each attempt
+#[derive(Debug, Default)]
+pub struct Recorder {
+    open: Vec<TraceNode>,
+    nodes: Vec<TraceNode>,
+}
+impl Recorder {
+    pub fn new() -> Recorder {
+        Recorder::default()
+    }
+
+    pub fn enter(&mut self, name: &str, start: usize) {
+        self.open.push(TraceNode {
+            name: name.to_string(),
+            start,
+            end: start,
+            outcome: Outcome::Error,
+            children: Vec::new(),
+        });
+    }
+
+    /// Closes the last entered attempt,
+    /// end is used when done
+    pub fn exit(&mut self, outcome: Outcome, end: usize) {
+        if let Some(mut node) = self.open.pop() {
+            if outcome == Outcome::Done {
+                node.end = end;
+            }
+            node.outcome = outcome;
+            match self.open.last_mut() {
+                Some(parent) => parent.children.push(node),
+                None => self.nodes.push(node),
+            }
+        }
+    }
+
+    pub fn finish(mut self, input: &[u8]) -> Trace {
+        // Attempts that didn't exit,
+        // by a panic for example
+        while !self.open.is_empty() {
+            self.exit(Outcome::Error, 0);
+        }
+        Trace {
+            input: String::from_utf8_lossy(input).into_owned(),
+            nodes: self.nodes,
+        }
+    }
+}
+
+enum Sink {
+    Tree(Recorder),
+    Profile(Profiler),
+}
+
+struct ThreadRecorder {
+    input_len: usize,
+    sink: Sink,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<ThreadRecorder>> = const { RefCell::new(None) };
+}
+
+/// Starts recording generated parsers
+/// on this thread, parsing input
+pub fn start(input: &[u8]) {
+    RECORDER.with(|cell| {
+        *cell.borrow_mut() = Some(ThreadRecorder {
+            input_len: input.len(),
+            sink: Sink::Tree(Recorder::new()),
+        });
+    });
+}
+
+/// Stops recording, giving the trace
+/// since start
+pub fn finish(input: &[u8]) -> Option<Trace> {
+    RECORDER.with(|cell| match cell.borrow_mut().take() {
+        Some(ThreadRecorder {
+            sink: Sink::Tree(recorder),
+            ..
+        }) => Some(recorder.finish(input)),
+        _ => None,
+    })
+}
+
+/// Records the parse of input
+pub fn record<T, F>(input: &[u8], parse: F) -> (T, Trace)
+where
+    F: FnOnce() -> T,
+{
+    start(input);
+    let res = parse();
+    (res, finish(input).unwrap())
+}
+
+/// Times the attempts of parse into
+/// profiler, instead of recording a trace
+pub fn profile<T, F>(profiler: &mut Profiler, parse: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    RECORDER.with(|cell| {
+        *cell.borrow_mut() = Some(ThreadRecorder {
+            input_len: 0,
+            sink: Sink::Profile(mem::replace(profiler, Profiler::new())),
+        });
+    });
+    let res = parse();
+    RECORDER.with(|cell| {
+        if let Some(ThreadRecorder {
+            sink: Sink::Profile(used),
+            ..
+        }) = cell.borrow_mut().take()
+        {
+            *profiler = used;
+        }
+    });
+    res
+}
+
+/// Enter with the length of the remaining
+/// input, like nom parsers have
+pub fn enter_rest(name: &'static str, rest_len: usize) {
+    RECORDER.with(|cell| {
+        if let Some(ref mut thread) = *cell.borrow_mut() {
+            match thread.sink {
+                Sink::Tree(ref mut recorder) => recorder.enter(name, thread.input_len - rest_len),
+                Sink::Profile(ref mut profiler) => profiler.enter(name),
+            }
+        }
+    });
+}
+
+pub fn exit_rest(outcome: Outcome, rest_len: usize) {
+    RECORDER.with(|cell| {
+        if let Some(ref mut thread) = *cell.borrow_mut() {
+            match thread.sink {
+                Sink::Tree(ref mut recorder) => recorder.exit(outcome, thread.input_len - rest_len),
+                Sink::Profile(ref mut profiler) => profiler.exit(),
+            }
+        }
+    });
+}
+
+/// Records nom parser, with name in
+/// the trace
+#[macro_export]
+macro_rules! trace_wrap (
+    ($i:expr, $name:expr, $submac:ident!( $($args:tt)* )) => ({
+        let input = $i;
+        $crate::trace::enter_rest($name, input.len());
+        let result = $submac!(input, $($args)*);
+        match result {
+            IResult::Done(ref rest, _) => $crate::trace::exit_rest($crate::trace::Outcome::Done, rest.len()),
+            IResult::Error(_) => $crate::trace::exit_rest($crate::trace::Outcome::Error, input.len()),
+            IResult::Incomplete(_) => $crate::trace::exit_rest($crate::trace::Outcome::Incomplete, input.len()),
+        }
+        result
+    });
+    ($i:expr, $name:expr, $f:expr) => (
+        trace_wrap!($i, $name, call!($f))
+    );
+);
+
+impl Trace {
+    /// Indented tree, with the consumed text,
+    /// or the input where a failure started
+    pub fn to_text(&self) -> String {
+        let mut s = String::new();
+        for node in &self.nodes {
+            self.node_text(&mut s, node, 0);
+        }
+        s
+    }
+
+    fn node_text(&self, s: &mut String, node: &TraceNode, level: usize) {
+        indent(s, level);
+        s.push_str(&node.name);
+        s.push(' ');
+        s.push_str(&self.span(node));
+        s.push(' ');
+        s.push_str(node.outcome.name());
+        s.push_str(&format!(" {:?}\n", self.snippet(node)));
+        for child in &node.children {
+            self.node_text(s, child, level + 1);
+        }
+    }
+
+    fn span(&self, node: &TraceNode) -> String {
+        if node.outcome == Outcome::Done {
+            format!("{}..{}", node.start, node.end)
+        } else {
+            format!("{}", node.start)
+        }
+    }
+
+    /// Consumed text, or the input
+    /// following a failure
+    pub fn snippet(&self, node: &TraceNode) -> String {
+        let text = self.input.get(node.start..).unwrap_or("");
+        let len = if node.outcome == Outcome::Done { node.end - node.start } else { 20 };
+        let mut snippet = String::new();
+        for (i, c) in text.char_indices() {
+            if i >= len {
+                break;
+            }
+            if snippet.len() >= 40 {
+                snippet.push_str("...");
+                break;
+            }
+            snippet.push(c);
+        }
+        snippet
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut s = String::new();
+        s += "{\n";
+        s += &format!("  \"input_len\": {},\n", self.input.len());
+        s += "  \"nodes\": ";
+        self.nodes_json(&mut s, &self.nodes, 1);
+        s += "\n}\n";
+        s
+    }
+
+    fn nodes_json(&self, s: &mut String, nodes: &[TraceNode], level: usize) {
+        if nodes.is_empty() {
+            s.push_str("[]");
+            return;
+        }
+        s.push('[');
+        for (i, node) in nodes.iter().enumerate() {
+            s.push_str(if i > 0 { ",\n" } else { "\n" });
+            indent(s, level + 1);
+            s.push_str(&format!(
+                "{{ \"name\": {}, \"start\": {}, \"end\": {}, \"outcome\": \"{}\", \"children\": ",
+                json_str(&node.name),
+                node.start,
+                node.end,
+                node.outcome.name()
+            ));
+            self.nodes_json(s, &node.children, level + 1);
+            s.push_str(" }");
+        }
+        s.push('\n');
+        indent(s, level);
+        s.push(']');
+    }
+
+    /// Page with the tree as collapsible
+    /// elements. Attempts leading to the
+    /// furthest failure are open.
+    pub fn to_html(&self) -> String {
+        let furthest = self.furthest();
+        let mut s = String::new();
+        s += "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Parse trace</title>\n<style>\n";
+        s += "body { font-family: monospace; }\n";
+        s += "details { margin-left: 1.5em; }\n";
+        s += "summary { cursor: pointer; }\n";
+        s += ".leaf { margin-left: 2.6em; }\n";
+        s += ".done { color: #2a7f2a; }\n";
+        s += ".error { color: #b22222; }\n";
+        s += ".incomplete { color: #b8860b; }\n";
+        s += ".text { color: #666; }\n";
+        s += "</style>\n</head>\n<body>\n";
+        for node in &self.nodes {
+            self.node_html(&mut s, node, furthest);
+        }
+        s += "</body>\n</html>\n";
+        s
+    }
+
+    fn node_html(&self, s: &mut String, node: &TraceNode, furthest: usize) {
+        let mut line = String::new();
+        line.push_str("<span class=\"");
+        line.push_str(node.outcome.name());
+        line.push_str("\">");
+        html_escape(&mut line, &node.name);
+        line.push_str("</span> ");
+        line.push_str(&self.span(node));
+        line.push_str(" <span class=\"text\">");
+        html_escape(&mut line, &format!("{:?}", self.snippet(node)));
+        line.push_str("</span>");
+        if node.children.is_empty() {
+            s.push_str("<div class=\"leaf\">");
+            s.push_str(&line);
+            s.push_str("</div>\n");
+        } else {
+            if reaches(node, furthest) {
+                s.push_str("<details open>");
+            } else {
+                s.push_str("<details>");
+            }
+            s.push_str("<summary>");
+            s.push_str(&line);
+            s.push_str("</summary>\n");
+            for child in &node.children {
+                self.node_html(s, child, furthest);
+            }
+            s.push_str("</details>\n");
+        }
+    }
+
+    /// Furthest start of a failed attempt
+    pub fn furthest(&self) -> usize {
+        fn furthest(nodes: &[TraceNode]) -> usize {
+            nodes
+                .iter()
+                .map(|node| {
+                    let start = if node.outcome == Outcome::Done { 0 } else { node.start };
+                    let children = furthest(&node.children);
+                    if children > start { children } else { start }
+                })
+                .max()
+                .unwrap_or(0)
+        }
+        furthest(&self.nodes)
+    }
+}
+
+/// Whether node or a child failed at pos
+fn reaches(node: &TraceNode, pos: usize) -> bool {
+    (node.outcome != Outcome::Done && node.start == pos) || node.children.iter().any(|child| reaches(child, pos))
+}
+
+fn indent(s: &mut String, level: usize) {
+    for _ in 0..level {
+        s.push_str("  ");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_tree_and_exports() {
+        let mut recorder = Recorder::new();
+        recorder.enter("Greeting", 0);
+        recorder.enter("\"hello\"", 0);
+        recorder.exit(Outcome::Error, 0);
+        recorder.enter("\"hi\"", 0);
+        recorder.exit(Outcome::Done, 2);
+        recorder.exit(Outcome::Done, 2);
+        let trace = recorder.finish(b"hi <there>");
+        assert_eq!(
+            trace.to_text(),
+            "Greeting 0..2 done \"hi\"\n  \"hello\" 0 error \"hi <there>\"\n  \"hi\" 0..2 done \"hi\"\n"
+        );
+        assert!(trace.to_json().contains(
+            "{ \"name\": \"\\\"hi\\\"\", \"start\": 0, \"end\": 2, \"outcome\": \"done\", \"children\": [] }"
+        ));
+        assert!(trace.to_html().contains("<details open><summary><span class=\"done\">Greeting</span>"));
+        // Recording on this thread
+        let (_, trace) = record(b"abc", || {
+            enter_rest("abc", 3);
+            exit_rest(Outcome::Incomplete, 1);
+        });
+        assert_eq!(trace.to_text(), "abc 0 incomplete \"abc\"\n");
+        assert!(finish(b"").is_none());
+    }
+}
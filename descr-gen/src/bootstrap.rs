@@ -0,0 +1,232 @@
+//! Self-hosting check. descr-lang/src/gen is generated
+//! from descr.lang by a generator built on that code.
+//! Stage 1 is generated by this build. A copy of the
+//! crates is then built with stage 1 in descr-lang,
+//! and run to parse descr.lang and generate stage 2.
+//! At a fixpoint, stage 1 and stage 2 are the same.
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use diff::unified_diff;
+use generator::{GenError, GenOptions, Generator};
+use process::write_file;
+
+/// Crates of the generator, copied
+/// to build the next stage
+const CRATES: [&str; 5] = ["descr-common", "descr-lsp", "descr-lang", "descr-gen", "langs/json-descr"];
+
+/// Module dir of descr-lang
+pub const GEN_DIR: &str = "descr-lang/src/gen";
+
+/// Options of the descr-lang modules
+pub fn descr_lang_options() -> GenOptions {
+    let mut options = GenOptions::new("Descr");
+    options.syntax = false;
+    options.cst = true;
+    options.highlight = true;
+    options.lsp = true;
+    options
+}
+
+/// Modules of descr-lang generated from
+/// descr.lang, without the server crate.
+/// The lsp module needs descr-lsp, and is
+/// built with the lsp feature of descr-lang.
+pub fn descr_lang_outputs(source: &[u8]) -> Result<BTreeMap<String, String>, GenError> {
+    let mut outputs = Generator::new(descr_lang_options()).generate(source)?;
+    outputs.retain(|name, _| !name.starts_with("lsp/"));
+    if let Some(mod_s) = outputs.get_mut("mod.rs") {
+        *mod_s = mod_s.replace("pub mod lsp;", "#[cfg(feature = \"lsp\")]\npub mod lsp;");
+    }
+    Ok(outputs)
+}
+
+pub struct Stages {
+    pub stage1: BTreeMap<String, String>,
+    pub stage2: BTreeMap<String, String>,
+}
+impl Stages {
+    /// Diff from stage 1 to stage 2,
+    /// None at a fixpoint
+    pub fn diff(&self) -> Option<String> {
+        diff_outputs("stage1", &self.stage1, "stage2", &self.stage2)
+    }
+}
+
+/// Diff of outputs by name, where missing
+/// outputs are diffed as empty
+pub fn diff_outputs(
+    old_label: &str,
+    old: &BTreeMap<String, String>,
+    new_label: &str,
+    new: &BTreeMap<String, String>,
+) -> Option<String> {
+    let mut s = String::new();
+    for name in old.keys().chain(new.keys().filter(|name| !old.contains_key(*name))) {
+        let old_content = old.get(name).map(|s| s.as_str()).unwrap_or("");
+        let new_content = new.get(name).map(|s| s.as_str()).unwrap_or("");
+        let diff = unified_diff(
+            &format!("{}/{}", old_label, name),
+            &format!("{}/{}", new_label, name),
+            old_content,
+            new_content,
+        );
+        if let Some(diff) = diff {
+            s += &diff;
+        }
+    }
+    if s.is_empty() {
+        None
+    } else {
+        Some(s)
+    }
+}
+
+/// Modules in descr-lang/src/gen of root
+pub fn checked_in(root: &Path) -> io::Result<BTreeMap<String, String>> {
+    let mut outputs = BTreeMap::new();
+    for entry in fs::read_dir(root.join(GEN_DIR))? {
+        let path = entry?.path();
+        if path.is_file() {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            outputs.insert(name, fs::read_to_string(&path)?);
+        }
+    }
+    Ok(outputs)
+}
+
+/// Generates both stages from the descr checkout
+/// in root, building stage 1 in work dir
+pub fn run(root: &Path, work_dir: &Path) -> Result<Stages, String> {
+    let grammar_path = root.join("descr.lang");
+    let source = fs::read(&grammar_path).map_err(|err| format!("Could not read {}: {}", grammar_path.display(), err))?;
+    let stage1 = descr_lang_outputs(&source).map_err(|err| format!("descr.lang: {}", err))?;
+    let tree = work_dir.join("tree");
+    copy_crates(root, &tree, &stage1).map_err(|err| format!("Could not copy crates to {}: {}", tree.display(), err))?;
+    let stage2_dir = work_dir.join("stage2");
+    let _ = fs::remove_dir_all(&stage2_dir);
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let output = Command::new(cargo)
+        .arg("run")
+        .arg("--quiet")
+        .arg("--manifest-path")
+        .arg(tree.join("stage/Cargo.toml"))
+        .arg("--")
+        .arg(&grammar_path)
+        .arg(&stage2_dir)
+        .env("CARGO_TARGET_DIR", work_dir.join("target"))
+        .output()
+        .map_err(|err| format!("Could not run cargo: {}", err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Stage 1 failed:\n{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    let mut stage2 = BTreeMap::new();
+    for name in stage1.keys() {
+        if let Ok(content) = fs::read_to_string(stage2_dir.join(name)) {
+            stage2.insert(name.clone(), content);
+        }
+    }
+    Ok(Stages { stage1, stage2 })
+}
+
+/// Copies the crates with stage 1 as descr-lang
+/// modules, and a stage crate generating with them
+fn copy_crates(root: &Path, tree: &Path, stage1: &BTreeMap<String, String>) -> io::Result<()> {
+    let _ = fs::remove_dir_all(tree);
+    for krate in &CRATES {
+        copy_dir(&root.join(krate), &tree.join(krate))?;
+    }
+    let gen_dir = tree.join(GEN_DIR);
+    fs::remove_dir_all(&gen_dir)?;
+    let gen_path = gen_dir.to_string_lossy().into_owned();
+    for (name, content) in stage1 {
+        write_file(&gen_path, name, content)?;
+    }
+    let stage = tree.join("stage");
+    fs::create_dir_all(stage.join("src"))?;
+    fs::write(stage.join("Cargo.toml"), STAGE_CARGO_TOML)?;
+    fs::write(stage.join("src/main.rs"), STAGE_MAIN)?;
+    // Same dependency versions as descr-gen
+    if let Ok(lock) = fs::read(root.join("descr-gen/Cargo.lock")) {
+        fs::write(stage.join("Cargo.lock"), lock)?;
+    }
+    Ok(())
+}
+
+/// Copies sources, without build outputs
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap();
+        let target: PathBuf = to.join(name);
+        if path.is_dir() {
+            if name != "target" {
+                copy_dir(&path, &target)?;
+            }
+        } else if name != "Cargo.lock" {
+            fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
+const STAGE_CARGO_TOML: &str = "[package]
+name = \"descr-stage\"
+version = \"0.1.0\"
+
+[dependencies]
+descr-gen = { path = \"../descr-gen\" }
+";
+
+const STAGE_MAIN: &str = "extern crate descr_gen;
+use std::env;
+use std::fs;
+use std::process;
+use descr_gen::bootstrap::descr_lang_outputs;
+use descr_gen::process::write_file;
+
+fn main() {
+    let args = env::args().collect::<Vec<_>>();
+    let source = fs::read(&args[1]).expect(\"Could not read descr.lang\");
+    match descr_lang_outputs(&source) {
+        Ok(outputs) => {
+            for (name, content) in outputs {
+                write_file(&args[2], &name, &content).expect(\"Could not write stage 2\");
+            }
+        }
+        Err(err) => {
+            eprintln!(\"Stage 1 could not generate descr.lang: {}\", err);
+            process::exit(1);
+        }
+    }
+}
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_outputs_by_name() {
+        let mut old = BTreeMap::new();
+        old.insert("a.rs".to_string(), "a\n".to_string());
+        old.insert("b.rs".to_string(), "b\n".to_string());
+        let mut new = old.clone();
+        assert_eq!(diff_outputs("stage1", &old, "stage2", &new), None);
+        new.remove("b.rs");
+        new.insert("c.rs".to_string(), "c\n".to_string());
+        assert_eq!(
+            diff_outputs("stage1", &old, "stage2", &new).unwrap(),
+            "--- stage1/b.rs\n+++ stage2/b.rs\n@@ -1 +0,0 @@\n-b\n\
+             --- stage1/c.rs\n+++ stage2/c.rs\n@@ -0,0 +1 @@\n+c\n"
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Line diffs in unified format, for comparing
+//! generated outputs with the files on disk.
+//! Lines are matched with Myers' algorithm,
+//! after the common start and end are taken
+//! out. Files with very many differences are
+//! shown as all lines removed and added.
+//! Lines keep their line break, so a missing
+//! newline at the end is a change, marked
+//! like diff does.
+
+/// Edits give indexes into old and new lines
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    Same(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Above this many edits, lines are
+/// not matched further
+const MAX_EDITS: usize = 2000;
+
+const CONTEXT: usize = 3;
+
+/// Unified diff from old to new, or
+/// None when they are the same
+pub fn unified_diff(old_name: &str, new_name: &str, old: &str, new: &str) -> Option<String> {
+    if old == new {
+        return None;
+    }
+    let old_lines = old.split_inclusive('\n').collect::<Vec<_>>();
+    let new_lines = new.split_inclusive('\n').collect::<Vec<_>>();
+    let edits = edits(&old_lines, &new_lines);
+    let mut s = String::new();
+    s += &format!("--- {}\n+++ {}\n", old_name, new_name);
+    // Hunks are changes with context lines
+    // around, joined when they overlap
+    let changed = edits
+        .iter()
+        .enumerate()
+        .filter(|&(_, edit)| !matches_same(edit))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for i in changed {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(edits.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    for (start, end) in hunks {
+        let old_pos = edits[..start].iter().filter(|edit| !is_insert(edit)).count();
+        let new_pos = edits[..start].iter().filter(|edit| !is_delete(edit)).count();
+        let hunk = &edits[start..end];
+        let old_count = hunk.iter().filter(|edit| !is_insert(edit)).count();
+        let new_count = hunk.iter().filter(|edit| !is_delete(edit)).count();
+        s += &format!(
+            "@@ -{} +{} @@\n",
+            range(old_pos, old_count),
+            range(new_pos, new_count)
+        );
+        for edit in hunk {
+            let line = match *edit {
+                Edit::Same(i, _) => {
+                    s.push(' ');
+                    old_lines[i]
+                }
+                Edit::Delete(i) => {
+                    s.push('-');
+                    old_lines[i]
+                }
+                Edit::Insert(j) => {
+                    s.push('+');
+                    new_lines[j]
+                }
+            };
+            s += line;
+            if !line.ends_with('\n') {
+                s += "\n\\ No newline at end of file\n";
+            }
+        }
+    }
+    Some(s)
+}
+
+fn matches_same(edit: &Edit) -> bool {
+    match *edit {
+        Edit::Same(..) => true,
+        _ => false,
+    }
+}
+
+fn is_insert(edit: &Edit) -> bool {
+    match *edit {
+        Edit::Insert(_) => true,
+        _ => false,
+    }
+}
+
+fn is_delete(edit: &Edit) -> bool {
+    match *edit {
+        Edit::Delete(_) => true,
+        _ => false,
+    }
+}
+
+/// Hunk range, where an empty range
+/// gives the line before it
+fn range(pos: usize, count: usize) -> String {
+    if count == 0 {
+        format!("{},0", pos)
+    } else if count == 1 {
+        format!("{}", pos + 1)
+    } else {
+        format!("{},{}", pos + 1, count)
+    }
+}
+
+fn edits(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|&(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|&(a, b)| a == b)
+        .count();
+    let mut edits = (0..prefix).map(|i| Edit::Same(i, i)).collect::<Vec<_>>();
+    let middle = myers(&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+    edits.extend(middle.into_iter().map(|edit| match edit {
+        Edit::Same(i, j) => Edit::Same(prefix + i, prefix + j),
+        Edit::Delete(i) => Edit::Delete(prefix + i),
+        Edit::Insert(j) => Edit::Insert(prefix + j),
+    }));
+    let old_end = old.len() - suffix;
+    let new_end = new.len() - suffix;
+    edits.extend((0..suffix).map(|i| Edit::Same(old_end + i, new_end + i)));
+    edits
+}
+
+/// Shortest edits from a to b. The furthest
+/// x of each diagonal k is kept for each step
+/// d, for k in -d..=d, to walk back from the end.
+fn myers(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m) as usize;
+    if max == 0 {
+        return Vec::new();
+    }
+    let offset = max as isize;
+    let mut v = vec![0isize; 2 * max + 2];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    for d in 0..(max as isize + 1) {
+        if d as usize > MAX_EDITS {
+            return replace_all(a.len(), b.len());
+        }
+        trace.push(v[(offset - d) as usize..(offset + d + 1) as usize].to_vec());
+        let mut k = -d;
+        while k <= d {
+            let i = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                return backtrack(&trace, n, m);
+            }
+            k += 2;
+        }
+    }
+    replace_all(a.len(), b.len())
+}
+
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (0..trace.len() as isize).rev() {
+        let (prev_x, prev_y) = if d == 0 {
+            (0, 0)
+        } else {
+            // Window of step d is k in -d..=d
+            let v = &trace[d as usize];
+            let at = |k: isize| v[(k + d) as usize];
+            let k = x - y;
+            let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+                k + 1
+            } else {
+                k - 1
+            };
+            let prev_x = at(prev_k);
+            (prev_x, prev_x - prev_k)
+        };
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Same((x - 1) as usize, (y - 1) as usize));
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == prev_x {
+                edits.push(Edit::Insert((y - 1) as usize));
+            } else {
+                edits.push(Edit::Delete((x - 1) as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    edits
+}
+
+fn replace_all(n: usize, m: usize) -> Vec<Edit> {
+    (0..n).map(Edit::Delete).chain((0..m).map(Edit::Insert)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_lines_with_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nk\nl\n";
+        assert_eq!(
+            unified_diff("a/x", "b/x", old, new).unwrap(),
+            "--- a/x\n+++ b/x\n@@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+             @@ -7,5 +7,5 @@\n g\n h\n i\n-j\n k\n+l\n"
+        );
+        assert_eq!(unified_diff("a/x", "b/x", old, old), None);
+        assert_eq!(
+            unified_diff("/dev/null", "b/x", "", "a\n").unwrap(),
+            "--- /dev/null\n+++ b/x\n@@ -0,0 +1 @@\n+a\n"
+        );
+    }
+
+    #[test]
+    fn marks_missing_newline_at_end() {
+        assert_eq!(
+            unified_diff("a/x", "b/x", "a\nb", "a\nb\n").unwrap(),
+            "--- a/x\n+++ b/x\n@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+b\n"
+        );
+        assert_eq!(
+            unified_diff("a/x", "b/x", "a\n", "a").unwrap(),
+            "--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+a\n\\ No newline at end of file\n"
+        );
+        // Unchanged last line without newline
+        assert_eq!(
+            unified_diff("a/x", "b/x", "a\nb", "A\nb").unwrap(),
+            "--- a/x\n+++ b/x\n@@ -1,2 +1,2 @@\n-a\n+A\n b\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn finds_shortest_edits() {
+        let old = "a b c a b b a".split(' ').collect::<Vec<_>>();
+        let new = "c b a b a c".split(' ').collect::<Vec<_>>();
+        let edits = edits(&old, &new);
+        let changes = edits.iter().filter(|edit| !matches_same(edit)).count();
+        assert_eq!(changes, 5);
+        // Edits give new from old
+        let mut result = Vec::new();
+        for edit in &edits {
+            match *edit {
+                Edit::Same(i, _) => result.push(old[i]),
+                Edit::Insert(j) => result.push(new[j]),
+                Edit::Delete(_) => {}
+            }
+        }
+        assert_eq!(result, new);
+    }
+}
@@ -0,0 +1,312 @@
+//! Grammar formatting. Lines are kept as
+//! written, and indented by the brackets
+//! open at their start, four spaces a level.
+//! Runs of spaces become one, trailing space
+//! and repeated blank lines are removed.
+//! Strings and comments are kept as is.
+//! Source written on one line, like from
+//! descr-lang's ToSource, is first laid out
+//! on lines by `layout`.
+use generator::{parse_grammar, GenError};
+
+#[derive(Clone, Copy)]
+enum Item<'s> {
+    Newline,
+    Space,
+    Open(&'s str),
+    Close(&'s str),
+    Text(&'s str),
+}
+
+/// Formatted grammar, or an error
+/// when the grammar doesn't parse
+pub fn format_grammar(source: &[u8]) -> Result<String, GenError> {
+    parse_grammar(source)?;
+    let source = String::from_utf8_lossy(source);
+    let mut s = String::with_capacity(source.len());
+    let mut depth = 0usize;
+    let mut newlines = 0;
+    let mut line_start = true;
+    let mut space = false;
+    for item in items(&source) {
+        let text = match item {
+            Item::Newline => {
+                newlines += 1;
+                line_start = true;
+                space = false;
+                continue;
+            }
+            Item::Space => {
+                space = !line_start;
+                continue;
+            }
+            Item::Open(text) | Item::Close(text) | Item::Text(text) => text,
+        };
+        if line_start {
+            if !s.is_empty() {
+                s += if newlines > 1 { "\n\n" } else { "\n" };
+            }
+            let level = match item {
+                Item::Close(_) => depth.saturating_sub(1),
+                _ => depth,
+            };
+            for _ in 0..level {
+                s += "    ";
+            }
+        } else if space {
+            s.push(' ');
+        }
+        s += text;
+        match item {
+            Item::Open(_) => depth += 1,
+            Item::Close(_) => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        newlines = 0;
+        line_start = false;
+        space = false;
+    }
+    s.push('\n');
+    Ok(s)
+}
+
+/// Lays out grammar rules, and the items of enums
+/// and lists, on lines of their own, and takes out
+/// spaces inside brackets and around `:`, `!`, `?`,
+/// `@` and `=`. Lines are not indented, that is
+/// done by format_grammar.
+pub fn layout(source: &str) -> String {
+    let mut s = String::with_capacity(source.len() + source.len() / 4);
+    let mut open: Vec<&str> = Vec::new();
+    let mut prev: Option<Item> = None;
+    // Depth of the annotation in, if any
+    let mut annotation: Option<usize> = None;
+    // Parts after [] of a list at top level,
+    // where ListSingle ends after two
+    let mut list_parts: Option<usize> = None;
+    for item in items(source) {
+        let text = match item {
+            Item::Newline => {
+                s.push('\n');
+                prev = None;
+                continue;
+            }
+            Item::Space => continue,
+            Item::Open(text) | Item::Close(text) | Item::Text(text) => text,
+        };
+        let top_level = open.is_empty();
+        match item {
+            Item::Close("}") => break_line(&mut s, false),
+            Item::Text("@") if top_level => break_line(&mut s, true),
+            Item::Text(text) if top_level && text.starts_with("(*") => break_line(&mut s, true),
+            _ => {}
+        }
+        if s.is_empty() || s.ends_with('\n') {
+            prev = None;
+        }
+        let space = match (&prev, &item) {
+            (&None, _) => false,
+            (&Some(Item::Open(bracket)), _) => bracket == "{",
+            (_, &Item::Close(bracket)) => bracket == "}",
+            (_, &Item::Text(",")) | (_, &Item::Text(":")) | (_, &Item::Text("?")) | (_, &Item::Text("=")) => false,
+            (&Some(Item::Text(":")), _)
+            | (&Some(Item::Text("!")), _)
+            | (&Some(Item::Text("@")), _)
+            | (&Some(Item::Text("=")), _) => false,
+            // Brackets of rules, lists and annotations,
+            // like Name(..), key:Type[] and @name(..).
+            // In token lists, ( starts a group.
+            (_, &Item::Open("(")) | (_, &Item::Open("[")) => !top_level && annotation.is_none(),
+            _ => true,
+        };
+        if space {
+            s.push(' ');
+        }
+        s += text;
+        prev = Some(item);
+        match item {
+            Item::Open(text) => {
+                open.push(text);
+                list_parts = None;
+                if text == "{" {
+                    break_line(&mut s, false);
+                }
+            }
+            Item::Close(text) => {
+                open.pop();
+                if annotation == Some(open.len()) {
+                    annotation = None;
+                    if open.is_empty() {
+                        break_line(&mut s, false);
+                    }
+                } else if open.is_empty() {
+                    if text == "]" {
+                        list_parts = Some(0);
+                    } else {
+                        break_line(&mut s, true);
+                    }
+                }
+            }
+            Item::Text(",") if open.last() == Some(&"{") => break_line(&mut s, false),
+            Item::Text("@") => annotation = Some(open.len()),
+            Item::Text(text) if top_level => {
+                if text.starts_with("(*") {
+                    break_line(&mut s, true);
+                } else if let Some(parts) = list_parts {
+                    list_parts = Some(parts + 1);
+                    if parts + 1 == 2 {
+                        list_parts = None;
+                        break_line(&mut s, true);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    while s.ends_with('\n') {
+        s.pop();
+    }
+    s.push('\n');
+    s
+}
+
+/// Ends the line unless at the start of
+/// one, with a blank line when given
+fn break_line(s: &mut String, blank: bool) {
+    while s.ends_with(' ') {
+        s.pop();
+    }
+    if s.is_empty() {
+        return;
+    }
+    if !s.ends_with('\n') {
+        s.push('\n');
+    }
+    if blank && !s.ends_with("\n\n") {
+        s.push('\n');
+    }
+}
+
+/// Splits source into brackets, spaces, newlines
+/// and text, where strings and comments are text
+fn items<'s>(source: &'s str) -> Vec<Item<'s>> {
+    let bytes = source.as_bytes();
+    let mut items = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let start = pos;
+        let item = match bytes[pos] {
+            b'\n' => {
+                pos += 1;
+                Item::Newline
+            }
+            b' ' | b'\t' | b'\r' => {
+                while pos < bytes.len() && (bytes[pos] == b' ' || bytes[pos] == b'\t' || bytes[pos] == b'\r') {
+                    pos += 1;
+                }
+                Item::Space
+            }
+            b'(' if bytes.get(pos + 1) == Some(&b'*') => {
+                pos = match source[pos + 2..].find("*)") {
+                    Some(end) => pos + 2 + end + 2,
+                    None => bytes.len(),
+                };
+                Item::Text(&source[start..pos])
+            }
+            b'"' => {
+                pos += 1;
+                while pos < bytes.len() && bytes[pos] != b'"' {
+                    pos += if bytes[pos] == b'\\' { 2 } else { 1 };
+                }
+                pos = (pos + 1).min(bytes.len());
+                Item::Text(&source[start..pos])
+            }
+            b'(' | b'{' | b'[' => {
+                pos += 1;
+                Item::Open(&source[start..pos])
+            }
+            b',' => {
+                pos += 1;
+                Item::Text(&source[start..pos])
+            }
+            b')' | b'}' | b']' => {
+                pos += 1;
+                Item::Close(&source[start..pos])
+            }
+            _ => {
+                while pos < bytes.len() && !b" \t\r\n\"(){}[],".contains(&bytes[pos]) {
+                    pos += 1;
+                }
+                Item::Text(&source[start..pos])
+            }
+        };
+        items.push(item);
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use descr_lang::gen::ast;
+    use descr_lang::gen::to_source::ToSource;
+
+    fn assert_same_grammar(source: &[u8]) {
+        let formatted = format_grammar(source).unwrap();
+        assert_eq!(
+            format!("{:?}", parse_grammar(source).unwrap()),
+            format!("{:?}", parse_grammar(formatted.as_bytes()).unwrap())
+        );
+        assert_eq!(format_grammar(formatted.as_bytes()).unwrap(), formatted);
+    }
+
+    #[test]
+    fn indents_by_brackets() {
+        let source = b"\n\nSource (items)  \n\n\n  items:Item[] WS {\nSay(\"say  \"   string),\n       Num(\n   int\n   )\n  }\n  (* a  comment\n  over lines *)\n";
+        assert_eq!(
+            format_grammar(source).unwrap(),
+            "Source (items)\n\nitems:Item[] WS {\n    Say(\"say  \" string),\n    Num(\n        int\n    )\n}\n(* a  comment\n  over lines *)\n"
+        );
+        assert_same_grammar(source);
+        assert!(format_grammar(b"Source(").is_err());
+    }
+
+    #[test]
+    fn keeps_grammars() {
+        assert_same_grammar(include_bytes!("../../descr.lang"));
+        assert_same_grammar(include_bytes!("../../langs/json-descr/json.lang"));
+        assert_same_grammar(include_bytes!("../../langs/rust-descr/rust.lang"));
+    }
+
+    #[test]
+    fn lays_out_to_source() {
+        // In descr.lang, annotations of the first
+        // token in enum items are written by
+        // ToSource like annotations of the item
+        let grammars: [&[u8]; 2] = [
+            include_bytes!("../../langs/json-descr/json.lang"),
+            include_bytes!("../../langs/rust-descr/rust.lang"),
+        ];
+        // ToSource puts spaces in comments
+        let rules = |ast: ast::Source| {
+            ast.items
+                .iter()
+                .filter(|item| match item {
+                    &&ast::SourceItem::CommentItem(_) => false,
+                    _ => true,
+                })
+                .map(|item| format!("{:?}", item))
+                .collect::<Vec<_>>()
+        };
+        for source in grammars.iter() {
+            let ast = parse_grammar(source).unwrap();
+            let laid_out = layout(&ToSource::source(String::new(), &ast));
+            assert_eq!(rules(ast), rules(parse_grammar(laid_out.as_bytes()).unwrap()));
+            assert_same_grammar(laid_out.as_bytes());
+        }
+        assert_eq!(
+            layout(" Source (  items :  sourceItems  )   items : Item [ ] WS {   Say ,  \"num\"  int  => Num  }   names [ ] COMMA Name "),
+            "Source(items:sourceItems)\n\nitems:Item[] WS {\nSay,\n\"num\" int => Num\n}\n\nnames[] COMMA Name\n"
+        );
+    }
+}
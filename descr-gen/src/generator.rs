@@ -0,0 +1,511 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use nom::IResult;
+use descr_lang::gen::ast;
+use descr_lang::gen::cst;
+use descr_lang::gen::parsers;
+use descr_lang::gen::visitor::Visitor;
+use lang_data::data::*;
+use process::register_keys::RegisterKeys;
+use process::check_keys::CheckKeys;
+use process::get_tokens::GetTokens;
+use process::build_parsers::BuildParsers;
+use process::build_ast::BuildAst;
+use process::codegen_ast::CodegenAst;
+use process::codegen_parsers::CodegenParsers;
+use process::codegen_rd::{memo_keys, CodegenRd};
+use process::codegen_visitor::CodegenVisitor;
+use process::codegen_tosource::CodegenToSource;
+use process::codegen_syntax::CodegenSyntax;
+use process::codegen_cst::CodegenCst;
+use process::codegen_highlight::CodegenHighlight;
+use process::codegen_tree_sitter::CodegenTreeSitter;
+use process::codegen_ebnf::CodegenEbnf;
+use process::codegen_railroad::CodegenRailroad;
+use process::codegen_lsp::CodegenLsp;
+use process::codegen_vscode::CodegenVscode;
+use process::codegen_bench::CodegenBench;
+
+/// Options for a generator run.
+/// Each output can be turned off.
+#[derive(Debug, Clone)]
+pub struct GenOptions {
+    /// Language name, used for syntax output
+    pub name: String,
+    /// Record parse traces with trace_wrap!,
+    /// see descr_common::trace
+    pub trace: bool,
+    pub ast: bool,
+    pub parsers: bool,
+    /// Parsers as plain functions over
+    /// descr_common::cursor, instead of nom
+    /// macros. The ast is the same.
+    pub recursive_descent: bool,
+    /// Memoize all rules of recursive descent
+    /// parsers, not only those annotated
+    /// with @memo
+    pub memoize: bool,
+    pub visitor: bool,
+    pub to_source: bool,
+    pub syntax: bool,
+    /// Syntax also as plist .tmLanguage and
+    /// .sublime-syntax
+    pub sublime: bool,
+    /// Lossless syntax tree, off by default
+    pub cst: bool,
+    /// Highlighter module highlight.rs,
+    /// parsing with the cst. Implies cst.
+    pub highlight: bool,
+    /// Tree-sitter grammar.js and highlights.scm
+    pub tree_sitter: bool,
+    /// Ebnf, railroad diagrams and index.html in docs/
+    pub docs: bool,
+    /// Language server module lsp.rs, and a
+    /// server crate in lsp/. Implies cst.
+    pub lsp: bool,
+    /// Bench crate in bench/, measuring the
+    /// parsers on samples of the grammar
+    pub bench: bool,
+    /// Descr checkout, for path dependencies
+    /// of the server and bench crates. Version
+    /// dependencies are used when not given.
+    pub descr_path: Option<String>,
+    /// VS Code extension in vscode/, with
+    /// the syntax as grammar
+    pub vscode: bool,
+    /// mod.rs including the generated modules
+    pub module: bool,
+}
+impl GenOptions {
+    pub fn new<S: Into<String>>(name: S) -> GenOptions {
+        GenOptions {
+            name: name.into(),
+            trace: false,
+            ast: true,
+            parsers: true,
+            recursive_descent: false,
+            memoize: false,
+            visitor: true,
+            to_source: true,
+            syntax: true,
+            sublime: false,
+            cst: false,
+            highlight: false,
+            tree_sitter: false,
+            docs: false,
+            lsp: false,
+            bench: false,
+            descr_path: None,
+            vscode: false,
+            module: true,
+        }
+    }
+}
+
+/// Errors in the grammar source.
+/// Positions are byte offsets into the source,
+/// with line and column starting at 1.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenError {
+    /// Grammar could not be parsed from position
+    Parse { pos: SourcePos },
+    /// Grammar ended in the middle of a rule
+    Incomplete { pos: SourcePos },
+    /// Token key that is not a rule or builtin token
+    UnknownToken { key: String, pos: SourcePos },
+    /// Key without an ast type, like a token
+    /// as enum item or list item
+    UnresolvedType { key: String, pos: SourcePos },
+    /// Enum with itself as an item
+    RecursiveEnum { key: String, pos: SourcePos },
+    /// Member given by several parts of a rule,
+    /// at the second part
+    DuplicateMember { key: String, pos: SourcePos },
+    /// Key of both an ast and a list rule
+    DuplicateRule { key: String, pos: SourcePos },
+    /// Grammar has no rules
+    NoStartRule,
+}
+impl fmt::Display for GenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &GenError::Parse { ref pos } => {
+                write!(f, "Could not parse grammar at {}", pos)
+            }
+            &GenError::Incomplete { ref pos } => {
+                write!(f, "Unexpected end of grammar at {}", pos)
+            }
+            &GenError::UnknownToken { ref key, ref pos } => {
+                write!(f, "Could not find token: {} at {}", key, pos)
+            }
+            &GenError::UnresolvedType { ref key, ref pos } => {
+                write!(f, "Could not resolve type of {} at {}", key, pos)
+            }
+            &GenError::RecursiveEnum { ref key, ref pos } => {
+                write!(f, "Enum {} has itself as item at {}", key, pos)
+            }
+            &GenError::DuplicateMember { ref key, ref pos } => {
+                write!(f, "Several parts resolve to the same member key: {} at {}", key, pos)
+            }
+            &GenError::DuplicateRule { ref key, ref pos } => {
+                write!(f, "Rule {} is both an ast and a list at {}", key, pos)
+            }
+            &GenError::NoStartRule => write!(f, "Grammar has no rules"),
+        }
+    }
+}
+impl Error for GenError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourcePos {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+impl SourcePos {
+    pub fn from_offset(source: &[u8], offset: usize) -> SourcePos {
+        let before = &source[..offset];
+        let line = before.iter().filter(|c| **c == b'\n').count() + 1;
+        let column = match before.iter().rposition(|c| *c == b'\n') {
+            Some(pos) => offset - pos,
+            None => offset + 1,
+        };
+        SourcePos {
+            offset,
+            line,
+            column,
+        }
+    }
+
+    /// Position of a slice of source
+    pub fn from_slice(source: &[u8], part: &str) -> SourcePos {
+        let offset = part.as_ptr() as usize - source.as_ptr() as usize;
+        SourcePos::from_offset(source, offset)
+    }
+}
+impl fmt::Display for SourcePos {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Generates source from grammars in memory.
+/// Nothing is written or printed, the result
+/// is a map of output names to contents,
+/// like "ast.rs" or "json.tmLanguage.json".
+pub struct Generator {
+    pub options: GenOptions,
+}
+impl Generator {
+    pub fn new(options: GenOptions) -> Generator {
+        Generator { options }
+    }
+
+    pub fn generate(&self, source: &[u8]) -> Result<BTreeMap<String, String>, GenError> {
+        let ast = parse_grammar(source)?;
+        let mut data = LangData::new(self.options.trace, self.options.name.clone());
+        build_data(source, &ast, &mut data)?;
+        let mut outputs = self.gen_outputs(&data);
+        let traced_bench = self.options.bench && self.options.parsers && !self.options.recursive_descent;
+        if traced_bench {
+            // Bench times rules with a copy of
+            // the parsers generated with tracing
+            let mut traced = LangData::new(true, self.options.name.clone());
+            build_data(source, &ast, &mut traced)?;
+            outputs.insert("bench/src/traced.rs".to_string(), CodegenParsers::new(&traced).gen());
+        }
+        Ok(outputs)
+    }
+
+    /// Outputs from built data, except the traced
+    /// parsers of the bench crate, which needs
+    /// data built with tracing
+    pub fn gen_outputs(&self, data: &LangData) -> BTreeMap<String, String> {
+        let mut outputs = BTreeMap::new();
+        let mut mod_s = String::new();
+        let memoized = self.options.parsers
+            && self.options.recursive_descent
+            && !memo_keys(data, self.options.memoize).is_empty();
+        if self.options.ast {
+            let codegen_ast = CodegenAst::new(data);
+            let codegen_ast = if memoized { codegen_ast.derive_clone() } else { codegen_ast };
+            outputs.insert("ast.rs".to_string(), codegen_ast.gen());
+            mod_s += "pub mod ast;\n";
+        }
+        if self.options.parsers {
+            let parsers = if self.options.recursive_descent {
+                CodegenRd::new(data, self.options.memoize).gen()
+            } else {
+                CodegenParsers::new(data).gen()
+            };
+            outputs.insert("parsers.rs".to_string(), parsers);
+            mod_s += "pub mod parsers;\n";
+        }
+        if self.options.visitor {
+            outputs.insert("visitor.rs".to_string(), CodegenVisitor::new(data).gen());
+            mod_s += "pub mod visitor;\n";
+        }
+        if self.options.to_source {
+            outputs.insert("to_source.rs".to_string(), CodegenToSource::new(data).gen());
+            mod_s += "pub mod to_source;\n";
+        }
+        if self.options.syntax || self.options.vscode {
+            let syntax = CodegenSyntax::new(data).gen();
+            if self.options.vscode {
+                let vscode = CodegenVscode::new(data);
+                outputs.insert("vscode/package.json".to_string(), vscode.gen_package());
+                outputs.insert(
+                    "vscode/language-configuration.json".to_string(),
+                    vscode.gen_language_configuration(),
+                );
+                outputs.insert(format!("vscode/{}", vscode.grammar_path()), syntax.clone());
+            }
+            if self.options.syntax {
+                outputs.insert(format!("{}.tmLanguage.json", data.lang_id()), syntax);
+            }
+        }
+        if self.options.sublime {
+            let syntax = CodegenSyntax::new(data);
+            outputs.insert(format!("{}.tmLanguage", data.lang_id()), syntax.gen_plist());
+            outputs.insert(format!("{}.sublime-syntax", data.lang_id()), syntax.gen_sublime_syntax());
+        }
+        if self.options.cst || self.options.highlight || self.options.lsp {
+            outputs.insert("cst.rs".to_string(), CodegenCst::new(data).gen());
+            mod_s += "pub mod cst;\n";
+        }
+        if self.options.highlight {
+            outputs.insert("highlight.rs".to_string(), CodegenHighlight::new(data).gen());
+            mod_s += "pub mod highlight;\n";
+        }
+        if self.options.tree_sitter {
+            let tree_sitter = CodegenTreeSitter::new(data);
+            outputs.insert("grammar.js".to_string(), tree_sitter.gen_grammar());
+            outputs.insert("highlights.scm".to_string(), tree_sitter.gen_highlights());
+        }
+        if self.options.docs {
+            outputs.insert("docs/grammar.ebnf".to_string(), CodegenEbnf::new(data).gen());
+            let railroad = CodegenRailroad::new(data);
+            for (name, svg) in railroad.gen_svgs() {
+                outputs.insert(format!("docs/{}", name), svg);
+            }
+            outputs.insert("docs/index.html".to_string(), railroad.gen_index());
+        }
+        if self.options.lsp {
+            let lsp = CodegenLsp::new(data);
+            outputs.insert("lsp.rs".to_string(), lsp.gen_module());
+            outputs.insert("lsp/Cargo.toml".to_string(), lsp.gen_cargo_toml(self.options.descr_path.as_ref().map(|path| path.as_str())));
+            outputs.insert("lsp/src/main.rs".to_string(), lsp.gen_main());
+            mod_s += "pub mod lsp;\n";
+        }
+        if self.options.bench && self.options.parsers {
+            let bench = CodegenBench::new(data, self.options.recursive_descent, self.options.lsp);
+            outputs.insert("bench/Cargo.toml".to_string(), bench.gen_cargo_toml(self.options.descr_path.as_ref().map(|path| path.as_str())));
+            outputs.insert("bench/src/main.rs".to_string(), bench.gen_main());
+            for (name, sample) in bench.gen_samples(100) {
+                outputs.insert(format!("bench/samples/{}", name), sample);
+            }
+        }
+        if self.options.module {
+            mod_s += "\n";
+            outputs.insert("mod.rs".to_string(), mod_s);
+        }
+        outputs
+    }
+}
+
+/// Parse grammar source into descr ast
+pub fn parse_grammar<'s>(source: &'s [u8]) -> Result<ast::Source<'s>, GenError> {
+    match parsers::source(source) {
+        IResult::Done(rest, ast) => {
+            match rest.iter().position(|c| !(*c as char).is_whitespace()) {
+                Some(pos) => Err(GenError::Parse {
+                    pos: furthest_pos(source, source.len() - rest.len() + pos),
+                }),
+                None => Ok(ast),
+            }
+        }
+        IResult::Error(_) => Err(GenError::Parse {
+            pos: furthest_pos(source, 0),
+        }),
+        IResult::Incomplete(_) => Err(GenError::Incomplete {
+            pos: SourcePos::from_offset(source, source.len()),
+        }),
+    }
+}
+
+/// Position of the token that failed, found
+/// with the cst parser, since nom errors only
+/// tell where the failing rule started
+fn furthest_pos(source: &[u8], from: usize) -> SourcePos {
+    let offset = match cst::parse(source) {
+        Err(err) if err.offset > from => err.offset,
+        _ => from,
+    };
+    SourcePos::from_offset(source, offset)
+}
+
+/// Run the passes that builds lang data
+/// from parsed grammar
+pub fn build_data<'d>(
+    source: &[u8],
+    ast: &'d ast::Source<'d>,
+    data: &mut LangData<'d>,
+) -> Result<(), GenError> {
+    {
+        let mut register_keys = RegisterKeys::new(data);
+        register_keys.visit_source(ast);
+    }
+    if data.start_key.is_none() {
+        return Err(GenError::NoStartRule);
+    }
+    {
+        let mut check_keys = CheckKeys::new(data);
+        check_keys.visit_source(ast);
+        if let Some(key) = check_keys.unknown.first() {
+            return Err(GenError::UnknownToken {
+                key: key.to_string(),
+                pos: SourcePos::from_slice(source, key),
+            });
+        }
+    }
+    {
+        let mut get_tokens = GetTokens::new(data);
+        get_tokens.visit_source(ast);
+        if let Some(&err) = get_tokens.errors.first() {
+            return Err(key_error(source, err));
+        }
+    }
+    {
+        let mut build_parsers = BuildParsers::new(data);
+        build_parsers.visit_source(ast);
+    }
+    {
+        let mut build_ast = BuildAst::new(data);
+        build_ast.build_ast().map_err(|err| key_error(source, err))?;
+    }
+    Ok(())
+}
+
+fn key_error(source: &[u8], err: KeyError) -> GenError {
+    match err {
+        KeyError::Unresolved(key) => GenError::UnresolvedType {
+            key: key.to_string(),
+            pos: SourcePos::from_slice(source, key),
+        },
+        KeyError::RecursiveEnum(key) => GenError::RecursiveEnum {
+            key: key.to_string(),
+            pos: SourcePos::from_slice(source, key),
+        },
+        KeyError::DuplicateMember(key) => GenError::DuplicateMember {
+            key: key.to_string(),
+            pos: SourcePos::from_slice(source, key),
+        },
+        KeyError::DuplicateRule(key) => GenError::DuplicateRule {
+            key: key.to_string(),
+            pos: SourcePos::from_slice(source, key),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use process::test_util::*;
+
+    #[test]
+    fn generates_outputs() {
+        let outputs = outputs("Source(LPAREN ident RPAREN)", |_| {});
+        let names = outputs.keys().map(|k| k.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["ast.rs", "mod.rs", "parsers.rs", "test.tmLanguage.json", "to_source.rs", "visitor.rs"]
+        );
+        assert!(outputs["ast.rs"].contains("pub struct Source<'a>"));
+    }
+
+    #[test]
+    fn reports_unknown_token() {
+        let res = Generator::new(GenOptions::new("Test")).generate(b"Source(\n  Missing)");
+        assert_eq!(
+            res,
+            Err(GenError::UnknownToken {
+                key: "Missing".to_string(),
+                pos: SourcePos {
+                    offset: 10,
+                    line: 2,
+                    column: 3,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn reports_unresolved_keys() {
+        let generator = Generator::new(GenOptions::new("Test"));
+        let err = |source: &str| generator.generate(source.as_bytes()).unwrap_err().to_string();
+        assert_eq!(err("Source { Missing }"), "Could not find token: Missing at 1:10");
+        assert_eq!(err("Source { LPAREN }"), "Could not resolve type of LPAREN at 1:10");
+        assert_eq!(
+            err("Source(items)\nitems[] WS ident"),
+            "Could not resolve type of ident at 2:12"
+        );
+        assert_eq!(
+            err("Source(items)\nitems[] WS items"),
+            "Could not resolve type of items at 1:8"
+        );
+        assert_eq!(err("A { B }\nB { A }"), "Enum A has itself as item at 1:1");
+        assert_eq!(err("Source { }"), "Could not resolve type of Source at 1:1");
+        assert_eq!(err("items[] WS int"), "Could not resolve type of int at 1:12");
+        assert_eq!(err("Source(A)\nA(ident)\nA[] WS Source"), "Rule A is both an ast and a list at 3:1");
+        assert_eq!(
+            err("Source(ident ident)"),
+            "Several parts resolve to the same member key: ident at 1:14"
+        );
+        assert_eq!(
+            err("Source { IfElse(\"if\" ident \"else\" ident) }"),
+            "Several parts resolve to the same member key: ident at 1:35"
+        );
+        assert_eq!(
+            err("Source(a:ident (LPAREN a:int)?)"),
+            "Several parts resolve to the same member key: a at 1:24"
+        );
+    }
+
+    #[test]
+    fn reports_parse_error_at_token() {
+        let generator = Generator::new(GenOptions::new("Test"));
+        let pos = |source: &[u8]| match generator.generate(source) {
+            Err(GenError::Parse { pos }) => (pos.line, pos.column),
+            other => panic!("Expected parse error, got {:?}", other),
+        };
+        assert_eq!(pos(b"Source(ident %)"), (1, 14));
+        assert_eq!(pos(b"Source(ident)\nOther {\n    ident => A,\n    ] \n}"), (4, 5));
+    }
+
+    #[test]
+    fn generates_valid_outputs_for_repo_grammars() {
+        for &(path, name) in &REPO_GRAMMARS {
+            let grammar = repo_grammar(path);
+            let mut options = GenOptions::new(name);
+            options.cst = true;
+            options.highlight = true;
+            options.tree_sitter = true;
+            options.docs = true;
+            options.lsp = true;
+            options.vscode = true;
+            options.sublime = true;
+            let outputs = Generator::new(options).generate(grammar.as_bytes()).unwrap();
+            for (file, output) in &outputs {
+                let file = format!("{}: {}", path, file);
+                if file.ends_with(".rs") {
+                    assert_rust(&file, output);
+                } else if file.ends_with(".json") {
+                    json(&file, output);
+                } else if file.ends_with(".svg") || file.ends_with(".tmLanguage") {
+                    assert_xml(&file, output);
+                }
+            }
+        }
+    }
+}
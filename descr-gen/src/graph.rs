@@ -0,0 +1,112 @@
+//! Rule graph, with an edge from each rule to
+//! the rules it uses, and optionally to the
+//! tokens. Given as text or as a graphviz
+//! dot graph.
+use std::collections::{BTreeMap, BTreeSet};
+use lang_data::data::LangData;
+use lang_data::typed_part::TypedPart;
+
+pub struct RuleGraph<'d> {
+    pub start_key: Option<&'d str>,
+    /// Rules and the keys they use
+    pub edges: BTreeMap<&'d str, BTreeSet<&'d str>>,
+    /// Token keys, when included
+    pub tokens: BTreeSet<&'d str>,
+}
+impl<'d> RuleGraph<'d> {
+    pub fn new(data: &LangData<'d>, with_tokens: bool) -> RuleGraph<'d> {
+        let mut edges = BTreeMap::new();
+        let mut tokens = BTreeSet::new();
+        for key in data.ast_data.keys().chain(data.list_data.keys()) {
+            let mut refs = BTreeSet::new();
+            for used in data.rule_refs(key) {
+                if is_rule(data, used) {
+                    refs.insert(used);
+                } else if with_tokens {
+                    tokens.insert(used);
+                    refs.insert(used);
+                }
+            }
+            edges.insert(*key, refs);
+        }
+        RuleGraph {
+            start_key: data.start_key,
+            edges,
+            tokens,
+        }
+    }
+
+    /// Rule per line, with the keys it uses
+    pub fn to_text(&self) -> String {
+        let mut s = String::new();
+        for (key, refs) in &self.edges {
+            s += key;
+            if !refs.is_empty() {
+                s += " -> ";
+                s += &refs.iter().cloned().collect::<Vec<_>>().join(", ");
+            }
+            s += "\n";
+        }
+        s
+    }
+
+    /// Rules as ellipses, the start rule
+    /// bold, and tokens as boxes
+    pub fn to_dot(&self) -> String {
+        let mut s = String::new();
+        s += "digraph grammar {\n";
+        s += "    node [shape=ellipse];\n";
+        for key in self.edges.keys() {
+            if Some(*key) == self.start_key {
+                append!(s 1, "\"" key "\" [style=bold];\n");
+            } else {
+                append!(s 1, "\"" key "\";\n");
+            }
+        }
+        for key in &self.tokens {
+            append!(s 1, "\"" key "\" [shape=box];\n");
+        }
+        for (key, refs) in &self.edges {
+            for used in refs {
+                append!(s 1, "\"" key "\" -> \"" used "\";\n");
+            }
+        }
+        s += "}\n";
+        s
+    }
+}
+
+/// Rules are ast and list keys, where
+/// parts are typed as ast or list parts
+fn is_rule(data: &LangData, key: &str) -> bool {
+    match data.typed_parts.get(key) {
+        Some(&TypedPart::AstPart { .. }) | Some(&TypedPart::ListPart { .. }) => true,
+        Some(_) => false,
+        None => data.ast_data.contains_key(key) || data.list_data.contains_key(key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generator::{build_data, parse_grammar};
+
+    #[test]
+    fn gives_rule_edges() {
+        let grammar = b"Source(items)
+            items:Item[] WS {
+                Say(\"say\" string),
+                Group(LPAREN items RPAREN)
+            }
+            Unused(int)";
+        let ast = parse_grammar(grammar).unwrap();
+        let mut data = LangData::new(false, "Test".to_string());
+        build_data(grammar, &ast, &mut data).unwrap();
+        let graph = RuleGraph::new(&data, false);
+        assert_eq!(graph.to_text(), "Source -> items\nUnused\nitems -> items\n");
+        let dot = RuleGraph::new(&data, true).to_dot();
+        assert!(dot.contains("    \"Source\" [style=bold];\n"));
+        assert!(dot.contains("    \"LPAREN\" [shape=box];\n"));
+        assert!(dot.contains("    \"items\" -> \"WS\";\n"));
+    }
+}
@@ -0,0 +1,545 @@
+//! ANTLR4 grammars, parsed to rules. Actions,
+//! predicates, options, rule arguments and
+//! exception handlers are skipped. The lexer
+//! commands skip and channel mark rules as
+//! skipped, modes, imports and other commands
+//! are reported.
+use super::{Alternative, Cursor, Diagnostic, Expr, Grammar, ImportError, Level, Rule};
+
+pub fn parse(source: &str) -> Result<Grammar, ImportError> {
+    let parser = Parser {
+        cursor: Cursor::new(source),
+        grammar: Grammar::default(),
+        lexer_grammar: false,
+        rule: String::new(),
+    };
+    parser.parse()
+}
+
+struct Parser<'s> {
+    cursor: Cursor<'s>,
+    grammar: Grammar,
+    /// All rules are lexer rules
+    lexer_grammar: bool,
+    /// Rule being parsed
+    rule: String,
+}
+impl<'s> Parser<'s> {
+    fn parse(mut self) -> Result<Grammar, ImportError> {
+        self.ws();
+        if self.keyword("lexer") {
+            self.lexer_grammar = true;
+        } else {
+            self.keyword("parser");
+        }
+        if self.keyword("grammar") {
+            let name = self.ident("grammar name")?;
+            self.grammar.name = Some(name.to_string());
+            self.expect(";")?;
+        }
+        loop {
+            self.ws();
+            if self.cursor.at_end() {
+                break;
+            }
+            let line = self.cursor.line();
+            if self.keyword("options") || self.keyword("channels") {
+                self.action()?;
+            } else if self.keyword("tokens") {
+                self.tokens()?;
+            } else if self.keyword("import") {
+                self.cursor.skip_past(";");
+                self.report(Level::Warning, line, "import", "of other grammars is not supported");
+            } else if self.keyword("mode") {
+                let mode = self.ident("mode name")?;
+                self.expect(";")?;
+                self.report(
+                    Level::Warning,
+                    line,
+                    &format!("mode {}", mode),
+                    "is not supported, its rules are imported like the others",
+                );
+            } else if self.cursor.eat("@") {
+                // Named action, like @header or @parser::members
+                self.ident("action name")?;
+                if self.cursor.eat("::") {
+                    self.ident("action name")?;
+                }
+                self.ws();
+                self.action()?;
+            } else {
+                self.rule()?;
+            }
+        }
+        Ok(self.grammar)
+    }
+
+    fn rule(&mut self) -> Result<(), ImportError> {
+        let line = self.cursor.line();
+        let fragment = self.keyword("fragment");
+        let name = self.ident("rule name")?.to_string();
+        self.rule = name.clone();
+        let lexer = fragment || self.lexer_grammar || name.starts_with(char::is_uppercase);
+        loop {
+            self.ws();
+            if self.cursor.peek() == Some('[') {
+                self.brackets()?;
+            } else if self.keyword("returns") || self.keyword("locals") {
+                self.brackets()?;
+            } else if self.keyword("throws") {
+                self.ident("exception")?;
+                while self.cursor.eat(",") {
+                    self.ws();
+                    self.ident("exception")?;
+                }
+            } else if self.keyword("options") {
+                self.action()?;
+            } else if self.cursor.eat("@") {
+                self.ident("action name")?;
+                self.ws();
+                self.action()?;
+            } else {
+                break;
+            }
+        }
+        self.expect(":")?;
+        let mut skip = false;
+        let alts = self.alternatives(&mut skip)?;
+        self.expect(";")?;
+        loop {
+            self.ws();
+            if self.keyword("catch") {
+                self.brackets()?;
+                self.ws();
+                self.action()?;
+            } else if self.keyword("finally") {
+                self.action()?;
+            } else {
+                break;
+            }
+        }
+        self.grammar.rules.push(Rule {
+            name,
+            lexer,
+            skip,
+            alts,
+            line,
+        });
+        Ok(())
+    }
+
+    /// Tokens declared in tokens { A, B },
+    /// as lexer rules without alternatives
+    fn tokens(&mut self) -> Result<(), ImportError> {
+        self.expect("{")?;
+        loop {
+            self.ws();
+            if self.cursor.eat("}") {
+                return Ok(());
+            }
+            let line = self.cursor.line();
+            let name = self.ident("token name")?.to_string();
+            self.grammar.rules.push(Rule {
+                name,
+                lexer: true,
+                skip: false,
+                alts: Vec::new(),
+                line,
+            });
+            self.ws();
+            self.cursor.eat(",");
+        }
+    }
+
+    fn alternatives(&mut self, skip: &mut bool) -> Result<Vec<Alternative>, ImportError> {
+        let mut alts = Vec::new();
+        loop {
+            alts.push(self.alternative(skip)?);
+            self.ws();
+            if !self.cursor.eat("|") {
+                return Ok(alts);
+            }
+        }
+    }
+
+    fn alternative(&mut self, skip: &mut bool) -> Result<Alternative, ImportError> {
+        let mut elements = Vec::new();
+        loop {
+            self.ws();
+            match self.cursor.peek() {
+                None | Some('|') | Some(')') | Some(';') | Some('#') => break,
+                _ if self.cursor.rest().starts_with("->") => break,
+                _ => {}
+            }
+            if let Some(element) = self.element()? {
+                elements.push(element);
+            }
+        }
+        let label = if self.cursor.eat("#") {
+            self.ws();
+            Some(self.ident("alternative label")?.to_string())
+        } else {
+            None
+        };
+        self.ws();
+        if self.cursor.eat("->") {
+            self.commands(skip)?;
+        }
+        let expr = match elements.len() {
+            0 => Expr::Empty,
+            1 => elements.pop().unwrap(),
+            _ => Expr::Seq(elements),
+        };
+        Ok(Alternative { expr, label })
+    }
+
+    /// Lexer commands, like -> skip
+    fn commands(&mut self, skip: &mut bool) -> Result<(), ImportError> {
+        loop {
+            self.ws();
+            let line = self.cursor.line();
+            let command = self.ident("lexer command")?;
+            self.ws();
+            if self.cursor.eat("(") {
+                self.cursor.skip_past(")");
+            }
+            match command {
+                "skip" | "channel" => *skip = true,
+                _ => {
+                    let rule = self.rule.clone();
+                    let message = format!("has lexer command {}, which is ignored", command);
+                    self.report(Level::Warning, line, &rule, &message);
+                }
+            }
+            self.ws();
+            if !self.cursor.eat(",") {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Element with its label and suffix, or
+    /// None for actions and predicates
+    fn element(&mut self) -> Result<Option<Expr>, ImportError> {
+        let start = self.cursor.pos;
+        if let Some(label) = self.cursor.ident() {
+            self.ws();
+            if self.cursor.eat("+=") || self.cursor.eat("=") {
+                self.ws();
+                return Ok(match self.atom()? {
+                    Some(atom) => Some(Expr::Named(label.to_string(), Box::new(self.suffix(atom)))),
+                    None => None,
+                });
+            }
+            self.cursor.pos = start;
+        }
+        Ok(match self.atom()? {
+            Some(atom) => Some(self.suffix(atom)),
+            None => None,
+        })
+    }
+
+    fn suffix(&mut self, atom: Expr) -> Expr {
+        self.ws();
+        let expr = match self.cursor.peek() {
+            Some('?') => Expr::Opt(Box::new(atom)),
+            Some('*') => Expr::Star(Box::new(atom)),
+            Some('+') if !self.cursor.rest().starts_with("+=") => Expr::Plus(Box::new(atom)),
+            _ => return atom,
+        };
+        self.cursor.bump();
+        // Non greedy
+        self.cursor.eat("?");
+        expr
+    }
+
+    fn atom(&mut self) -> Result<Option<Expr>, ImportError> {
+        let atom = match self.cursor.peek() {
+            Some('\'') => {
+                let from = self.literal()?;
+                self.ws();
+                if self.cursor.eat("..") {
+                    self.ws();
+                    let to = self.literal()?;
+                    match (single_char(&from), single_char(&to)) {
+                        (Some(from), Some(to)) => Expr::CharSet {
+                            ranges: vec![(from, to)],
+                            negated: false,
+                        },
+                        _ => return Err(self.cursor.error("Expected single characters in range")),
+                    }
+                } else {
+                    Expr::Literal(from)
+                }
+            }
+            Some('[') => self.char_set()?,
+            Some('(') => {
+                self.cursor.bump();
+                self.ws();
+                if self.keyword("options") {
+                    self.action()?;
+                    self.expect(":")?;
+                }
+                let mut skip = false;
+                let mut alts = self.alternatives(&mut skip)?;
+                self.expect(")")?;
+                if alts.len() == 1 {
+                    alts.pop().unwrap().expr
+                } else {
+                    Expr::Alt(alts.into_iter().map(|alt| alt.expr).collect())
+                }
+            }
+            Some('~') => {
+                self.cursor.bump();
+                self.ws();
+                match self.atom()? {
+                    Some(atom) => Expr::Not(Box::new(atom)),
+                    None => return Err(self.cursor.error("Expected set after ~")),
+                }
+            }
+            Some('.') => {
+                self.cursor.bump();
+                Expr::Any
+            }
+            Some('{') => {
+                // Action, or predicate with ?
+                self.action()?;
+                self.cursor.eat("?");
+                return Ok(None);
+            }
+            Some('<') => {
+                // Element options, like <assoc=right>
+                self.cursor.skip_past(">");
+                return Ok(None);
+            }
+            _ => match self.cursor.ident() {
+                Some(name) => Expr::Ref(name.to_string()),
+                None => return Err(self.cursor.error("Unexpected character in rule")),
+            },
+        };
+        Ok(Some(atom))
+    }
+
+    /// Quoted literal, with escapes
+    fn literal(&mut self) -> Result<String, ImportError> {
+        self.expect("'")?;
+        let mut literal = String::new();
+        loop {
+            match self.cursor.bump() {
+                Some('\'') => return Ok(literal),
+                Some('\\') => literal.push(self.escape()?),
+                Some(c) => literal.push(c),
+                None => return Err(self.cursor.error("Unterminated literal")),
+            }
+        }
+    }
+
+    /// Set like [a-zA-Z_]
+    fn char_set(&mut self) -> Result<Expr, ImportError> {
+        self.expect("[")?;
+        let mut ranges = Vec::new();
+        loop {
+            let from = match self.cursor.bump() {
+                Some(']') => {
+                    return Ok(Expr::CharSet {
+                        ranges,
+                        negated: false,
+                    })
+                }
+                Some('\\') => self.escape()?,
+                Some(c) => c,
+                None => return Err(self.cursor.error("Unterminated set")),
+            };
+            let to = if self.cursor.peek() == Some('-') && self.cursor.peek_nth(1) != Some(']') {
+                self.cursor.bump();
+                match self.cursor.bump() {
+                    Some('\\') => self.escape()?,
+                    Some(c) => c,
+                    None => return Err(self.cursor.error("Unterminated set")),
+                }
+            } else {
+                from
+            };
+            ranges.push((from, to));
+        }
+    }
+
+    /// Character after a backslash
+    fn escape(&mut self) -> Result<char, ImportError> {
+        let c = match self.cursor.bump() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('u') => {
+                let braced = self.cursor.eat("{");
+                let start = self.cursor.pos;
+                while self.cursor.peek().map_or(false, |c| c.is_ascii_hexdigit()) && (braced || self.cursor.pos - start < 4) {
+                    self.cursor.bump();
+                }
+                let hex = &self.cursor.source[start..self.cursor.pos];
+                if braced {
+                    self.expect("}")?;
+                }
+                match u32::from_str_radix(hex, 16).ok().and_then(::std::char::from_u32) {
+                    Some(c) => c,
+                    None => return Err(self.cursor.error("Invalid unicode escape")),
+                }
+            }
+            Some(c) => c,
+            None => return Err(self.cursor.error("Unterminated escape")),
+        };
+        Ok(c)
+    }
+
+    /// Skips a block in braces, like actions and
+    /// options, with nested braces and strings
+    fn action(&mut self) -> Result<(), ImportError> {
+        self.skip_block('{', '}')
+    }
+
+    /// Skips arguments and return values in [ ]
+    fn brackets(&mut self) -> Result<(), ImportError> {
+        self.skip_block('[', ']')
+    }
+
+    fn skip_block(&mut self, open: char, close: char) -> Result<(), ImportError> {
+        self.expect(&open.to_string())?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.cursor.bump() {
+                Some(c) if c == open => depth += 1,
+                Some(c) if c == close => depth -= 1,
+                Some(quote) if quote == '"' || quote == '\'' => loop {
+                    match self.cursor.bump() {
+                        Some('\\') => {
+                            self.cursor.bump();
+                        }
+                        Some(c) if c == quote => break,
+                        Some(_) => {}
+                        None => break,
+                    }
+                },
+                Some(_) => {}
+                None => return Err(self.cursor.error(&format!("Expected {}", close))),
+            }
+        }
+        self.ws();
+        Ok(())
+    }
+
+    /// Skips whitespace and comments
+    fn ws(&mut self) {
+        loop {
+            match self.cursor.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.cursor.bump();
+                }
+                Some('/') if self.cursor.eat("//") => {
+                    self.cursor.skip_past("\n");
+                }
+                Some('/') if self.cursor.eat("/*") => {
+                    self.cursor.skip_past("*/");
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// Eats the word if it is next, and
+    /// the whitespace after it
+    fn keyword(&mut self, word: &str) -> bool {
+        let rest = self.cursor.rest();
+        let is_next = rest.starts_with(word)
+            && !rest[word.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_');
+        if is_next {
+            self.cursor.pos += word.len();
+            self.ws();
+        }
+        is_next
+    }
+
+    fn ident(&mut self, what: &str) -> Result<&'s str, ImportError> {
+        match self.cursor.ident() {
+            Some(ident) => Ok(ident),
+            None => Err(self.cursor.error(&format!("Expected {}", what))),
+        }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), ImportError> {
+        self.ws();
+        if self.cursor.eat(s) {
+            Ok(())
+        } else {
+            Err(self.cursor.error(&format!("Expected {}", s)))
+        }
+    }
+
+    fn report(&mut self, level: Level, line: usize, key: &str, message: &str) {
+        self.grammar.diagnostics.push(Diagnostic {
+            level,
+            line,
+            key: key.to_string(),
+            message: message.to_string(),
+        });
+    }
+}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rules() {
+        let grammar = parse(
+            "grammar Expr; // Comment
+            options { language = Java; }
+            @header { package x; }
+            expr: left=expr op=('*' | '/') right=expr # Mul
+                | INT {action();}           # Int
+                ;
+            INT: [0-9]+ ;
+            fragment LETTER: 'a'..'z' | ~[\\]x] ;
+            WS: [ \\t\\r\\n]+ -> channel(HIDDEN);",
+        )
+        .unwrap();
+        assert_eq!(grammar.name, Some("Expr".to_string()));
+        assert_eq!(grammar.rules.len(), 4);
+        let expr = &grammar.rules[0];
+        assert!(!expr.lexer);
+        assert_eq!(expr.alts[1].label, Some("Int".to_string()));
+        assert_eq!(expr.alts[1].expr, Expr::Ref("INT".to_string()));
+        assert_eq!(
+            expr.alts[0].expr,
+            Expr::Seq(vec![
+                Expr::Named("left".to_string(), Box::new(Expr::Ref("expr".to_string()))),
+                Expr::Named(
+                    "op".to_string(),
+                    Box::new(Expr::Alt(vec![
+                        Expr::Literal("*".to_string()),
+                        Expr::Literal("/".to_string()),
+                    ]))
+                ),
+                Expr::Named("right".to_string(), Box::new(Expr::Ref("expr".to_string()))),
+            ])
+        );
+        assert_eq!(
+            grammar.rules[2].alts[1].expr,
+            Expr::Not(Box::new(Expr::CharSet {
+                ranges: vec![(']', ']'), ('x', 'x')],
+                negated: false,
+            }))
+        );
+        assert!(grammar.rules[3].lexer && grammar.rules[3].skip);
+        assert!(parse("grammar X; a: 'b").is_err());
+    }
+}
@@ -0,0 +1,345 @@
+//! EBNF grammars, parsed to rules. ISO 14977 style
+//! is read with `=`, `,` and [ ] and { } for options
+//! and repetitions. Grammars defining rules with
+//! `::=` are read in W3C style, where [a-z] is a
+//! set and rules may end without a terminator.
+//! The suffixes ? * + are read in both. Special
+//! sequences are kept to be reported.
+use super::{Alternative, Cursor, Expr, Grammar, ImportError, Rule};
+
+pub fn parse(source: &str) -> Result<Grammar, ImportError> {
+    let mut parser = Parser {
+        cursor: Cursor::new(source),
+        w3c: false,
+    };
+    let mut grammar = Grammar::default();
+    parser.ws();
+    while !parser.cursor.at_end() {
+        let line = parser.cursor.line();
+        let name = match parser.name() {
+            Some(name) => name,
+            None => return Err(parser.cursor.error("Expected rule name")),
+        };
+        parser.ws();
+        if parser.cursor.eat("::=") {
+            parser.w3c = true;
+        } else if !(parser.cursor.eat(":=") || parser.cursor.eat("=")) {
+            return Err(parser.cursor.error("Expected = or ::= after rule name"));
+        }
+        let alts = parser.alternatives()?;
+        parser.ws();
+        if parser.cursor.eat(";") || parser.cursor.eat(".") {
+            parser.ws();
+        }
+        grammar.rules.push(Rule {
+            name,
+            lexer: false,
+            skip: false,
+            alts: alts.into_iter().map(|expr| Alternative { expr, label: None }).collect(),
+            line,
+        });
+    }
+    Ok(grammar)
+}
+
+struct Parser<'s> {
+    cursor: Cursor<'s>,
+    /// Rules are defined with ::=
+    w3c: bool,
+}
+impl<'s> Parser<'s> {
+    fn alternatives(&mut self) -> Result<Vec<Expr>, ImportError> {
+        let mut alts = vec![self.sequence()?];
+        loop {
+            self.ws();
+            if self.cursor.eat("|") || (!self.w3c && self.cursor.eat("/")) {
+                alts.push(self.sequence()?);
+            } else {
+                return Ok(alts);
+            }
+        }
+    }
+
+    fn sequence(&mut self) -> Result<Expr, ImportError> {
+        let mut items = Vec::new();
+        loop {
+            self.ws();
+            match self.cursor.peek() {
+                None | Some(';') | Some('.') | Some('|') | Some(')') | Some(']') | Some('}') => break,
+                Some('/') if !self.w3c => break,
+                Some(',') => {
+                    self.cursor.bump();
+                    continue;
+                }
+                _ => {}
+            }
+            if self.at_rule_start() {
+                break;
+            }
+            items.push(self.term()?);
+        }
+        Ok(match items.len() {
+            0 => Expr::Empty,
+            1 => items.pop().unwrap(),
+            _ => Expr::Seq(items),
+        })
+    }
+
+    /// Factor with an exception, like a - b
+    fn term(&mut self) -> Result<Expr, ImportError> {
+        let factor = self.factor()?;
+        self.ws();
+        if self.cursor.eat("-") {
+            self.ws();
+            let except = self.factor()?;
+            return Ok(Expr::Except(Box::new(factor), Box::new(except)));
+        }
+        Ok(factor)
+    }
+
+    fn factor(&mut self) -> Result<Expr, ImportError> {
+        // Repeated a number of times, like 3 * digit
+        if self.cursor.peek().map_or(false, |c| c.is_ascii_digit()) {
+            let start = self.cursor.pos;
+            while self.cursor.peek().map_or(false, |c| c.is_ascii_digit()) {
+                self.cursor.bump();
+            }
+            let count = self.cursor.source[start..self.cursor.pos].parse::<usize>().unwrap_or(0);
+            self.ws();
+            if !self.cursor.eat("*") {
+                return Err(self.cursor.error("Expected * after repetition count"));
+            }
+            self.ws();
+            let primary = self.primary()?;
+            return Ok(Expr::Seq(vec![primary; count]));
+        }
+        let mut expr = self.primary()?;
+        loop {
+            expr = match self.cursor.peek() {
+                Some('?') => Expr::Opt(Box::new(expr)),
+                Some('*') if !self.cursor.rest().starts_with("*)") => Expr::Star(Box::new(expr)),
+                Some('+') => Expr::Plus(Box::new(expr)),
+                _ => return Ok(expr),
+            };
+            self.cursor.bump();
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, ImportError> {
+        let expr = match self.cursor.peek() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                self.cursor.bump();
+                let start = self.cursor.pos;
+                if !self.cursor.skip_past(&quote.to_string()) {
+                    return Err(self.cursor.error("Unterminated literal"));
+                }
+                Expr::Literal(self.cursor.source[start..self.cursor.pos - 1].to_string())
+            }
+            Some('(') => {
+                self.cursor.bump();
+                let alts = self.alternatives()?;
+                self.expect(")")?;
+                alternatives(alts)
+            }
+            Some('[') if self.w3c => self.char_set()?,
+            Some('[') => {
+                self.cursor.bump();
+                let alts = self.alternatives()?;
+                self.expect("]")?;
+                Expr::Opt(Box::new(alternatives(alts)))
+            }
+            Some('{') => {
+                self.cursor.bump();
+                let alts = self.alternatives()?;
+                self.expect("}")?;
+                Expr::Star(Box::new(alternatives(alts)))
+            }
+            Some('?') if !self.w3c => {
+                self.cursor.bump();
+                let start = self.cursor.pos;
+                if !self.cursor.skip_past("?") {
+                    return Err(self.cursor.error("Unterminated special sequence"));
+                }
+                let text = self.cursor.source[start..self.cursor.pos - 1].trim();
+                Expr::Unsupported(format!("special sequence ? {} ?", text))
+            }
+            Some('#') if self.w3c => {
+                let c = self.char_code()?;
+                Expr::Literal(c.to_string())
+            }
+            _ => match self.name() {
+                Some(name) => Expr::Ref(name),
+                None => return Err(self.cursor.error("Unexpected character in rule")),
+            },
+        };
+        Ok(expr)
+    }
+
+    /// W3C set, like [a-zA-Z] or [^"]
+    fn char_set(&mut self) -> Result<Expr, ImportError> {
+        self.expect("[")?;
+        let negated = self.cursor.eat("^");
+        let mut ranges = Vec::new();
+        loop {
+            if self.cursor.eat("]") {
+                return Ok(Expr::CharSet { ranges, negated });
+            }
+            let from = self.set_char()?;
+            let to = if self.cursor.peek() == Some('-') && self.cursor.peek_nth(1) != Some(']') {
+                self.cursor.bump();
+                self.set_char()?
+            } else {
+                from
+            };
+            ranges.push((from, to));
+        }
+    }
+
+    fn set_char(&mut self) -> Result<char, ImportError> {
+        if self.cursor.peek() == Some('#') {
+            return self.char_code();
+        }
+        match self.cursor.bump() {
+            Some(c) => Ok(c),
+            None => Err(self.cursor.error("Unterminated set")),
+        }
+    }
+
+    /// Character by code, like #x20
+    fn char_code(&mut self) -> Result<char, ImportError> {
+        if !self.cursor.eat("#x") {
+            return Err(self.cursor.error("Expected #x"));
+        }
+        let start = self.cursor.pos;
+        while self.cursor.peek().map_or(false, |c| c.is_ascii_hexdigit()) {
+            self.cursor.bump();
+        }
+        let hex = &self.cursor.source[start..self.cursor.pos];
+        match u32::from_str_radix(hex, 16).ok().and_then(::std::char::from_u32) {
+            Some(c) => Ok(c),
+            None => Err(self.cursor.error("Invalid character code")),
+        }
+    }
+
+    /// Rule name, with - inside, or in < >
+    fn name(&mut self) -> Option<String> {
+        if self.cursor.eat("<") {
+            let start = self.cursor.pos;
+            self.cursor.skip_past(">");
+            return Some(self.cursor.source[start..self.cursor.pos - 1].trim().to_string());
+        }
+        let mut name = self.cursor.ident()?.to_string();
+        while self.cursor.peek() == Some('-') && self.cursor.peek_nth(1).map_or(false, char::is_alphanumeric) {
+            self.cursor.bump();
+            name.push('-');
+            name += self.cursor.ident().unwrap_or("");
+        }
+        Some(name)
+    }
+
+    /// Whether the next rule starts here, for
+    /// rules without a terminator
+    fn at_rule_start(&mut self) -> bool {
+        let start = self.cursor.pos;
+        let at_start = self.name().is_some() && {
+            self.ws();
+            let rest = self.cursor.rest();
+            rest.starts_with("::=") || rest.starts_with(":=") || (rest.starts_with('=') && !self.w3c)
+        };
+        self.cursor.pos = start;
+        at_start
+    }
+
+    /// Skips whitespace and comments
+    fn ws(&mut self) {
+        loop {
+            match self.cursor.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.cursor.bump();
+                }
+                Some('(') if self.cursor.eat("(*") => {
+                    self.cursor.skip_past("*)");
+                }
+                Some('/') if self.cursor.eat("/*") => {
+                    self.cursor.skip_past("*/");
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), ImportError> {
+        self.ws();
+        if self.cursor.eat(s) {
+            Ok(())
+        } else {
+            Err(self.cursor.error(&format!("Expected {}", s)))
+        }
+    }
+}
+
+fn alternatives(mut alts: Vec<Expr>) -> Expr {
+    if alts.len() == 1 {
+        alts.pop().unwrap()
+    } else {
+        Expr::Alt(alts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_iso_and_w3c_rules() {
+        let grammar = parse(
+            "(* ISO style *)
+            list = '(', [ item, { ',', item } ], ')' ;
+            item = 'a' | \"b\" | 2 * digit | ? any ? ;
+            digit = '0' | '1' .",
+        )
+        .unwrap();
+        assert_eq!(grammar.rules.len(), 3);
+        assert_eq!(
+            grammar.rules[0].alts[0].expr,
+            Expr::Seq(vec![
+                Expr::Literal("(".to_string()),
+                Expr::Opt(Box::new(Expr::Seq(vec![
+                    Expr::Ref("item".to_string()),
+                    Expr::Star(Box::new(Expr::Seq(vec![
+                        Expr::Literal(",".to_string()),
+                        Expr::Ref("item".to_string()),
+                    ]))),
+                ]))),
+                Expr::Literal(")".to_string()),
+            ])
+        );
+        assert_eq!(grammar.rules[1].alts.len(), 4);
+        assert_eq!(
+            grammar.rules[1].alts[2].expr,
+            Expr::Seq(vec![Expr::Ref("digit".to_string()), Expr::Ref("digit".to_string())])
+        );
+        assert_eq!(
+            grammar.rules[1].alts[3].expr,
+            Expr::Unsupported("special sequence ? any ?".to_string())
+        );
+
+        let grammar = parse(
+            "/* W3C style */
+            Name ::= [a-zA-Z_] [a-zA-Z0-9_-]*
+            Char ::= [^\"#x20]
+            List ::= Name (',' Name)+",
+        )
+        .unwrap();
+        assert_eq!(grammar.rules.len(), 3);
+        assert_eq!(
+            grammar.rules[1].alts[0].expr,
+            Expr::CharSet {
+                ranges: vec![('"', '"'), (' ', ' ')],
+                negated: true,
+            }
+        );
+        assert!(parse("a = 'b").is_err());
+        assert!(parse("a b").is_err());
+    }
+}
@@ -0,0 +1,341 @@
+//! Importers of grammars in other formats. EBNF and
+//! ANTLR4 grammars are parsed to the rules here, and
+//! translated to descr rules by translate. These are
+//! written with descr-lang's ToSource, then laid out
+//! and formatted. What can't be translated is given
+//! as diagnostics along with the grammar.
+use std::fmt;
+use descr_lang::gen::ast;
+use descr_lang::gen::to_source::ToSource;
+use format::{format_grammar, layout};
+use generator::SourcePos;
+
+pub mod antlr4;
+pub mod ebnf;
+mod translate;
+
+use self::translate::{Item, List, Tok, TokKind, Variant};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Ebnf,
+    Antlr4,
+}
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "ebnf" => Some(Format::Ebnf),
+            "antlr4" | "antlr" => Some(Format::Antlr4),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            &Format::Ebnf => "EBNF",
+            &Format::Antlr4 => "ANTLR4",
+        }
+    }
+}
+
+/// Expression of a rule
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Seq(Vec<Expr>),
+    Alt(Vec<Expr>),
+    Opt(Box<Expr>),
+    Star(Box<Expr>),
+    Plus(Box<Expr>),
+    Literal(String),
+    Ref(String),
+    /// ANTLR4 element label, like left=expr
+    Named(String, Box<Expr>),
+    /// Character ranges, like [a-z] or 'a'..'z'
+    CharSet { ranges: Vec<(char, char)>, negated: bool },
+    /// Any character
+    Any,
+    /// Anything but the characters, ANTLR4's ~
+    Not(Box<Expr>),
+    /// First without the second, EBNF's -
+    Except(Box<Expr>, Box<Expr>),
+    Empty,
+    /// Construct without a translation, described
+    Unsupported(String),
+}
+
+/// Alternative of a rule, with its
+/// ANTLR4 label if any
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alternative {
+    pub expr: Expr,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub name: String,
+    /// ANTLR4 lexer rule or fragment
+    pub lexer: bool,
+    /// Skipped or on a hidden channel
+    pub skip: bool,
+    pub alts: Vec<Alternative>,
+    pub line: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Grammar {
+    pub name: Option<String>,
+    pub rules: Vec<Rule>,
+    /// Parts of the grammar that are left out
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Level {
+    Note,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub level: Level,
+    /// Line in the imported grammar
+    pub line: usize,
+    /// Rule or construct
+    pub key: String,
+    pub message: String,
+}
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let level = match self.level {
+            Level::Note => "note",
+            Level::Warning => "warning",
+            Level::Error => "error",
+        };
+        write!(f, "{}: {} {}", level, self.key, self.message)
+    }
+}
+
+/// Grammar that could not be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportError {
+    pub pos: SourcePos,
+    pub message: String,
+}
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at {}", self.message, self.pos)
+    }
+}
+
+pub struct Imported {
+    /// Grammar in descr syntax
+    pub grammar: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+impl Imported {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|d| d.level == Level::Error)
+    }
+}
+
+/// Parses the grammar and translates it to descr
+pub fn import(format: Format, source: &str) -> Result<Imported, ImportError> {
+    let grammar = match format {
+        Format::Ebnf => ebnf::parse(source)?,
+        Format::Antlr4 => antlr4::parse(source)?,
+    };
+    let (mut items, mut diagnostics) = translate::translate(&grammar, format);
+    let header = match grammar.name {
+        Some(ref name) => format!("Imported from {} grammar {}", format.name(), name),
+        None => format!("Imported from {} grammar", format.name()),
+    };
+    items.insert(0, Item::Comment(header));
+    let written = layout(&ToSource::source(String::new(), &source_ast(&items)));
+    let grammar = match format_grammar(written.as_bytes()) {
+        Ok(formatted) => formatted,
+        Err(err) => {
+            diagnostics.push(Diagnostic {
+                level: Level::Error,
+                line: 0,
+                key: "grammar".to_string(),
+                message: format!("translates to invalid descr: {}", err),
+            });
+            written
+        }
+    };
+    Ok(Imported { grammar, diagnostics })
+}
+
+fn source_ast<'a>(items: &'a [Item]) -> ast::Source<'a> {
+    let items = items
+        .iter()
+        .map(|item| match item {
+            &Item::Comment(ref comment) => ast::SourceItem::comment(comment),
+            &Item::Struct(ref name, ref toks) => ast::SourceItem::ast_single(Vec::new(), name, tokens(toks)),
+            &Item::Enum(ref name, ref variants) => {
+                ast::SourceItem::ast_many(Vec::new(), name, variants.iter().map(ast_item).collect())
+            }
+            &Item::List(ref list) => ast::SourceItem::ListItem(list_ast(list)),
+        })
+        .collect();
+    ast::Source::new(items)
+}
+
+fn list_ast<'a>(list: &'a List) -> ast::List<'a> {
+    match list.ast_type {
+        Some(ref ast_type) => ast::List::list_many(
+            Vec::new(),
+            &list.key,
+            ast_type,
+            Some(&list.sep),
+            list.items.iter().map(|item| ast::ListItem::new(ast_item(item), None)).collect(),
+        ),
+        None => {
+            let reference = match list.items.first() {
+                Some(&Variant::Ref(ref reference)) => reference.as_str(),
+                _ => "",
+            };
+            ast::List::list_single(Vec::new(), &list.key, &list.sep, reference)
+        }
+    }
+}
+
+fn ast_item<'a>(variant: &'a Variant) -> ast::AstItem<'a> {
+    match variant {
+        &Variant::Def(ref name, ref toks) => ast::AstItem::ast_def(Vec::new(), tokens(toks), Some(name)),
+        &Variant::Ref(ref name) => ast::AstItem::ast_ref(name),
+    }
+}
+
+fn tokens<'a>(toks: &'a [Tok]) -> Vec<ast::Token<'a>> {
+    toks.iter()
+        .map(|tok| {
+            let token_type = match tok.kind {
+                TokKind::Key(ref key) => ast::TokenType::key_token(key),
+                TokKind::Literal(ref literal) => ast::TokenType::quoted(literal),
+                TokKind::Group(ref toks) => {
+                    return ast::Token::token_group(Vec::new(), false, tokens(toks), tok.optional);
+                }
+            };
+            match tok.name {
+                Some(ref name) => ast::Token::named_token(Vec::new(), name, false, token_type, tok.optional),
+                None => ast::Token::simple_token(Vec::new(), false, token_type, tok.optional),
+            }
+        })
+        .collect()
+}
+
+/// Position in the source of a grammar,
+/// shared by the parsers
+struct Cursor<'s> {
+    source: &'s str,
+    pos: usize,
+}
+impl<'s> Cursor<'s> {
+    fn new(source: &'s str) -> Cursor<'s> {
+        Cursor { source, pos: 0 }
+    }
+
+    fn rest(&self) -> &'s str {
+        &self.source[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.source.len()
+    }
+
+    /// Advances past s if the rest starts with it
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Advances past end, or to the
+    /// end of source if not found
+    fn skip_past(&mut self, end: &str) -> bool {
+        match self.rest().find(end) {
+            Some(i) => {
+                self.pos += i + end.len();
+                true
+            }
+            None => {
+                self.pos = self.source.len();
+                false
+            }
+        }
+    }
+
+    /// Letters, digits and _, from a letter or _
+    fn ident(&mut self) -> Option<&'s str> {
+        match self.peek() {
+            Some(c) if c.is_alphabetic() || c == '_' => {}
+            _ => return None,
+        }
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || c == '_' {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        Some(&self.source[start..self.pos])
+    }
+
+    fn line(&self) -> usize {
+        self.source[..self.pos].matches('\n').count() + 1
+    }
+
+    fn error(&self, message: &str) -> ImportError {
+        ImportError {
+            pos: SourcePos::from_offset(self.source.as_bytes(), self.pos),
+            message: message.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generator::{GenOptions, Generator};
+
+    #[test]
+    fn imports_to_grammar_that_generates() {
+        let source = "grammar Calc;
+            prog: stat+ EOF;
+            stat: 'let' ID '=' expr ';' # Let
+                | expr ';' # Print
+                ;
+            expr: term (op=('+' | '-') term)*;
+            term: INT | ID | '(' expr ')';
+            ID: [a-zA-Z_] [a-zA-Z_0-9]*;
+            INT: [0-9]+;
+            WS: [ \\t\\r\\n]+ -> skip;
+        ";
+        let imported = import(Format::Antlr4, source).unwrap();
+        assert!(!imported.has_errors(), "{:?}", imported.diagnostics);
+        Generator::new(GenOptions::new("Calc"))
+            .generate(imported.grammar.as_bytes())
+            .unwrap();
+    }
+}
@@ -0,0 +1,1055 @@
+//! Translation of imported rules to descr rules.
+//! Parser rules become structs, or enums when they
+//! have alternatives. Repetitions become lists,
+//! separated by a builtin token like COMMA when
+//! written as `X (SEP X)*`, else by whitespace.
+//! Lexer rules become the builtin token they match,
+//! like int, ident and string, or literals. Sets
+//! of literals become enums where they are used.
+use std::collections::{HashMap, HashSet};
+use lang_data::data::LangData;
+use lang_data::typed_part::TypedPart;
+use super::{Alternative, Diagnostic, Expr, Format, Grammar, Level, Rule};
+
+pub enum Item {
+    Comment(String),
+    Struct(String, Vec<Tok>),
+    Enum(String, Vec<Variant>),
+    List(List),
+}
+
+/// List of the ast type, or of the
+/// one reference when it is None
+pub struct List {
+    pub key: String,
+    pub ast_type: Option<String>,
+    pub sep: String,
+    pub items: Vec<Variant>,
+}
+
+pub enum Variant {
+    Def(String, Vec<Tok>),
+    Ref(String),
+}
+
+pub struct Tok {
+    pub name: Option<String>,
+    pub optional: bool,
+    pub kind: TokKind,
+}
+impl Tok {
+    fn new(kind: TokKind) -> Tok {
+        Tok {
+            name: None,
+            optional: false,
+            kind,
+        }
+    }
+}
+
+pub enum TokKind {
+    Key(String),
+    Literal(String),
+    Group(Vec<Tok>),
+}
+
+/// What a lexer rule is written as
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Builtin token, like int
+    Key(&'static str),
+    Literal(String),
+    /// Written as an enum of the literals
+    Literals(Vec<String>),
+    /// Single characters, written as an
+    /// enum of literals
+    Chars(Vec<(char, char)>),
+    Skip,
+    /// Reported where used, the rule
+    /// name is kept as key
+    Unresolved,
+}
+
+/// Builtin tokens of one or two characters
+const CHAR_KEYS: [&str; 19] = [
+    "LPAREN", "RPAREN", "LBRACE", "RBRACE", "LBRACKET", "RBRACKET", "COMMA", "COLON", "SEMICOLON", "EQUAL", "LT",
+    "GT", "LTE", "GTE", "STAR", "EXCL", "DOT", "QUESTION", "QUOTE",
+];
+
+/// Names taken by the generated Rust code
+const RESERVED: [&str; 10] = ["Box", "Option", "Vec", "String", "Self", "Some", "None", "Ok", "Err", "Result"];
+
+/// Above this many characters, a
+/// set is not written as literals
+const MAX_CHARS: usize = 16;
+
+pub fn translate(grammar: &Grammar, format: Format) -> (Vec<Item>, Vec<Diagnostic>) {
+    let mut translator = Translator::new(grammar, format);
+    translator.translate();
+    (translator.items, translator.diagnostics)
+}
+
+struct Translator {
+    format: Format,
+    rules: Vec<Rule>,
+    tokens: HashMap<String, Token>,
+    /// Type names of parser rules
+    types: HashMap<String, String>,
+    /// Rules with an empty alternative
+    nullable: HashSet<String>,
+    /// Rules with only empty alternatives
+    empty: HashSet<String>,
+    /// Type names and list keys
+    names: HashSet<String>,
+    /// Lists of references, by key,
+    /// type and separator
+    lists: Vec<(String, String, String)>,
+    /// Enums of lexer rules, by rule name
+    token_enums: HashMap<String, String>,
+    /// Lexer rules reported where used
+    reported: HashSet<String>,
+    items: Vec<Item>,
+    /// Items made for the current rule
+    pending: Vec<Item>,
+    token_items: Vec<Item>,
+    diagnostics: Vec<Diagnostic>,
+    /// Current rule and its type
+    rule: String,
+    line: usize,
+    current_type: String,
+}
+impl Translator {
+    fn new(grammar: &Grammar, format: Format) -> Translator {
+        let mut translator = Translator {
+            format,
+            rules: Vec::new(),
+            tokens: HashMap::new(),
+            types: HashMap::new(),
+            nullable: HashSet::new(),
+            empty: HashSet::new(),
+            names: HashSet::new(),
+            lists: Vec::new(),
+            token_enums: HashMap::new(),
+            reported: HashSet::new(),
+            items: Vec::new(),
+            pending: Vec::new(),
+            token_items: Vec::new(),
+            diagnostics: grammar.diagnostics.clone(),
+            rule: String::new(),
+            line: 0,
+            current_type: String::new(),
+        };
+        // Rules defined again in EBNF
+        // have their alternatives joined
+        for rule in &grammar.rules {
+            match translator.rules.iter().position(|r| r.name == rule.name) {
+                Some(i) => {
+                    translator.report_at(
+                        Level::Warning,
+                        rule.line,
+                        &rule.name,
+                        "is defined again, the alternatives are joined",
+                    );
+                    translator.rules[i].alts.extend(rule.alts.iter().cloned());
+                }
+                None => translator.rules.push(rule.clone()),
+            }
+        }
+        translator
+    }
+
+    fn translate(&mut self) {
+        self.classify();
+        let parser_rules = self
+            .rules
+            .iter()
+            .filter(|rule| !self.tokens.contains_key(&rule.name))
+            .cloned()
+            .collect::<Vec<_>>();
+        if parser_rules.is_empty() {
+            self.report_at(Level::Error, 0, "grammar", "has no parser rules to translate");
+            return;
+        }
+        for rule in &parser_rules {
+            let empty_alts = rule.alts.iter().filter(|alt| is_empty(&alt.expr)).count();
+            if empty_alts == rule.alts.len() {
+                self.report_at(Level::Error, rule.line, &rule.name, "matches only empty input, it is left out");
+                self.empty.insert(rule.name.clone());
+                continue;
+            }
+            if empty_alts > 0 {
+                self.report_at(
+                    Level::Note,
+                    rule.line,
+                    &rule.name,
+                    "has an empty alternative, it is optional where used instead",
+                );
+                self.nullable.insert(rule.name.clone());
+            }
+            let type_name = self.unique(camel(&rule.name));
+            self.types.insert(rule.name.clone(), type_name);
+        }
+        self.left_recursion(&parser_rules);
+        for rule in &parser_rules {
+            if self.types.contains_key(&rule.name) {
+                self.parser_rule(rule);
+            }
+        }
+        let token_items = self.token_items.drain(..).collect::<Vec<_>>();
+        self.items.extend(token_items);
+    }
+
+    /// Finds what lexer rules, and EBNF rules
+    /// matching tokens, are written as
+    fn classify(&mut self) {
+        for i in 0..self.rules.len() {
+            let rule = self.rules[i].clone();
+            let expr = rule_expr(&rule);
+            let token = if rule.skip {
+                let chars = match expr {
+                    Expr::Plus(ref inner) | Expr::Star(ref inner) => self.class(inner, 0),
+                    _ => self.class(&expr, 0),
+                };
+                match chars {
+                    Some(ref set) if is_whitespace(set) => self.report_at(
+                        Level::Note,
+                        rule.line,
+                        &rule.name,
+                        "is skipped, like whitespace between tokens in descr",
+                    ),
+                    _ => self.report_at(
+                        Level::Warning,
+                        rule.line,
+                        &rule.name,
+                        "is skipped, but descr skips only whitespace between tokens",
+                    ),
+                }
+                Token::Skip
+            } else if rule.lexer {
+                if rule.alts.is_empty() {
+                    Token::Unresolved
+                } else {
+                    self.token_of(&expr, 0).unwrap_or(Token::Unresolved)
+                }
+            } else {
+                match self.token_of(&expr, 0) {
+                    Some(token) => token,
+                    None if is_lexical(&expr) => Token::Unresolved,
+                    None => continue,
+                }
+            };
+            self.tokens.insert(rule.name.clone(), token);
+        }
+    }
+
+    fn token_of(&self, expr: &Expr, depth: usize) -> Option<Token> {
+        if depth > 8 {
+            return None;
+        }
+        if let Some(set) = self.class(expr, depth) {
+            return Some(match set.as_slice() {
+                &[(from, to)] if from == to => Token::Literal(from.to_string()),
+                _ => Token::Chars(set),
+            });
+        }
+        match expr {
+            &Expr::Literal(ref literal) => Some(Token::Literal(literal.clone())),
+            &Expr::Alt(ref alts) => {
+                let literals = alts
+                    .iter()
+                    .map(|alt| match unnamed(alt) {
+                        &Expr::Literal(ref literal) => Some(literal.clone()),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(Token::Literals(literals))
+            }
+            &Expr::Ref(ref name) => {
+                let rule = self.rules.iter().find(|rule| &rule.name == name)?;
+                self.token_of(&rule_expr(rule), depth + 1)
+            }
+            &Expr::Named(_, ref inner) => self.token_of(inner, depth),
+            &Expr::Plus(ref inner) => {
+                let set = self.class(inner, depth)?;
+                if is_digits(&set) {
+                    Some(Token::Key("int"))
+                } else if is_letters(&set) {
+                    Some(Token::Key("ident"))
+                } else {
+                    None
+                }
+            }
+            &Expr::Seq(ref exprs) => {
+                let exprs = exprs.iter().map(unnamed).collect::<Vec<_>>();
+                let quote = Expr::Literal("\"".to_string());
+                if exprs.len() >= 2 && *exprs[0] == quote && *exprs[exprs.len() - 1] == quote {
+                    return Some(Token::Key("string"));
+                }
+                if exprs.len() == 1 {
+                    return self.token_of(exprs[0], depth);
+                }
+                if exprs.len() != 2 {
+                    return None;
+                }
+                let first = self.class(exprs[0], depth)?;
+                let rest = match exprs[1] {
+                    &Expr::Star(ref inner) | &Expr::Plus(ref inner) => self.class(inner, depth)?,
+                    _ => return None,
+                };
+                if is_digits(&first) && is_digits(&rest) {
+                    Some(Token::Key("int"))
+                } else if is_letters(&first) && is_word(&rest) {
+                    Some(Token::Key("ident"))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Characters matched by expr, when
+    /// it matches a single character
+    fn class(&self, expr: &Expr, depth: usize) -> Option<Vec<(char, char)>> {
+        if depth > 8 {
+            return None;
+        }
+        match expr {
+            &Expr::Literal(ref literal) if literal.chars().count() == 1 => {
+                let c = literal.chars().next().unwrap();
+                Some(vec![(c, c)])
+            }
+            &Expr::CharSet { ref ranges, negated: false } => Some(ranges.clone()),
+            &Expr::Alt(ref alts) => {
+                let mut set = Vec::new();
+                for alt in alts {
+                    set.extend(self.class(alt, depth)?);
+                }
+                Some(set)
+            }
+            &Expr::Ref(ref name) => {
+                let rule = self.rules.iter().find(|rule| &rule.name == name)?;
+                if self.format == Format::Antlr4 && !rule.lexer {
+                    return None;
+                }
+                self.class(&rule_expr(rule), depth + 1)
+            }
+            &Expr::Named(_, ref inner) => self.class(inner, depth),
+            _ => None,
+        }
+    }
+
+    fn parser_rule(&mut self, rule: &Rule) {
+        self.rule = rule.name.clone();
+        self.line = rule.line;
+        let type_name = self.types[&rule.name].clone();
+        self.current_type = type_name.clone();
+        let alts = rule
+            .alts
+            .iter()
+            .filter(|alt| !is_empty(&alt.expr))
+            .cloned()
+            .collect::<Vec<_>>();
+        let item = if alts.len() == 1 && alts[0].label.is_none() {
+            let toks = self.tokens(&mut HashSet::new(), &alts[0].expr);
+            if toks.is_empty() {
+                self.report(Level::Error, "matches nothing descr can parse, it is left out");
+                return;
+            }
+            Item::Struct(type_name, toks)
+        } else {
+            Item::Enum(type_name.clone(), self.variants(&type_name, &alts))
+        };
+        self.items.push(item);
+        let pending = self.pending.drain(..).collect::<Vec<_>>();
+        self.items.extend(pending);
+    }
+
+    /// Items of an enum, named by label, by a
+    /// starting keyword, or by position
+    fn variants(&mut self, enum_name: &str, alts: &[Alternative]) -> Vec<Variant> {
+        let mut variants = Vec::new();
+        for (i, alt) in alts.iter().enumerate() {
+            if alt.label.is_none() {
+                if let Expr::Ref(ref name) = alt.expr {
+                    if let Some(type_name) = self.types.get(name) {
+                        if !self.nullable.contains(name) {
+                            variants.push(Variant::Ref(type_name.clone()));
+                            continue;
+                        }
+                    }
+                }
+            }
+            let name = match alt.label {
+                Some(ref label) => camel(label),
+                None => variant_name(enum_name, &alt.expr, i),
+            };
+            let toks = self.tokens(&mut HashSet::new(), &alt.expr);
+            if toks.is_empty() {
+                let message = format!("has alternative {} matching nothing descr can parse, it is left out", i + 1);
+                self.report(Level::Error, &message);
+                continue;
+            }
+            let name = self.unique(name);
+            variants.push(Variant::Def(name, toks));
+        }
+        variants
+    }
+
+    fn tokens(&mut self, members: &mut HashSet<String>, expr: &Expr) -> Vec<Tok> {
+        match expr {
+            &Expr::Seq(ref exprs) => self.seq(members, exprs),
+            _ => self.seq(members, ::std::slice::from_ref(expr)),
+        }
+    }
+
+    fn seq(&mut self, members: &mut HashSet<String>, exprs: &[Expr]) -> Vec<Tok> {
+        let mut toks = Vec::new();
+        let mut i = 0;
+        while i < exprs.len() {
+            // X (SEP X)* as a separated list
+            if let Some(&Expr::Star(ref inner)) = exprs.get(i + 1) {
+                if let Some(sep) = self.separator(&exprs[i], inner) {
+                    if let Some(tok) = self.repeat(&exprs[i], sep, false) {
+                        toks.push(tok);
+                    }
+                    i += 2;
+                    continue;
+                }
+            }
+            if let Some(tok) = self.expr(members, &exprs[i]) {
+                toks.push(tok);
+            }
+            i += 1;
+        }
+        toks
+    }
+
+    /// Builtin key of SEP, when inner is
+    /// SEP followed by item
+    fn separator(&self, item: &Expr, inner: &Expr) -> Option<&'static str> {
+        let exprs = match inner {
+            &Expr::Seq(ref exprs) if exprs.len() == 2 => exprs,
+            _ => return None,
+        };
+        if unnamed(&exprs[1]) != unnamed(item) {
+            return None;
+        }
+        match unnamed(&exprs[0]) {
+            &Expr::Literal(ref literal) => builtin_key(literal),
+            &Expr::Ref(ref name) => match self.tokens.get(name) {
+                Some(&Token::Literal(ref literal)) => builtin_key(literal),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn expr(&mut self, members: &mut HashSet<String>, expr: &Expr) -> Option<Tok> {
+        let tok = match expr {
+            &Expr::Named(ref label, ref inner) => {
+                let mut tok = self.expr_tok(members, inner)?;
+                tok.name = Some(label.clone());
+                tok
+            }
+            _ => self.expr_tok(members, expr)?,
+        };
+        Some(self.member(members, tok))
+    }
+
+    fn expr_tok(&mut self, members: &mut HashSet<String>, expr: &Expr) -> Option<Tok> {
+        match expr {
+            &Expr::Seq(ref exprs) => {
+                let mut toks = self.seq(members, exprs);
+                match toks.len() {
+                    0 => None,
+                    1 => toks.pop(),
+                    _ => Some(Tok::new(TokKind::Group(toks))),
+                }
+            }
+            &Expr::Alt(ref alts) => self.alternatives(members, alts),
+            &Expr::Opt(ref inner) => {
+                let mut tok = self.expr(members, inner)?;
+                // Lists can be empty already
+                if !self.is_list(&tok) {
+                    tok.optional = true;
+                }
+                Some(tok)
+            }
+            &Expr::Star(ref inner) => self.repeat(inner, "WS", false),
+            &Expr::Plus(ref inner) => self.repeat(inner, "WS", true),
+            &Expr::Literal(ref literal) => self.literal(literal),
+            &Expr::Ref(ref name) => self.reference(name),
+            &Expr::Named(_, ref inner) => self.expr_tok(members, inner),
+            &Expr::CharSet { .. } | &Expr::Any | &Expr::Not(_) | &Expr::Except(..) => {
+                let message = format!("has {}, which can only be translated in lexer rules", describe(expr));
+                self.report(Level::Error, &message);
+                None
+            }
+            &Expr::Unsupported(ref what) => {
+                let message = format!("has {}, which can't be translated", what);
+                self.report(Level::Error, &message);
+                None
+            }
+            &Expr::Empty => None,
+        }
+    }
+
+    /// Alternatives in a rule, as a new enum
+    fn alternatives(&mut self, members: &mut HashSet<String>, alts: &[Expr]) -> Option<Tok> {
+        let optional = alts.iter().any(is_empty);
+        let alts = alts
+            .iter()
+            .filter(|alt| !is_empty(alt))
+            .map(|alt| Alternative {
+                expr: alt.clone(),
+                label: None,
+            })
+            .collect::<Vec<_>>();
+        if alts.is_empty() {
+            return None;
+        }
+        let mut tok = if alts.len() == 1 {
+            self.expr(members, &alts[0].expr)?
+        } else {
+            let literals = alts.iter().all(|alt| match alt.expr {
+                Expr::Literal(_) => true,
+                _ => false,
+            });
+            let suffix = if literals { "Op" } else { "Alt" };
+            let enum_name = self.unique(format!("{}{}", self.current_type, suffix));
+            let variants = self.variants(&enum_name, &alts);
+            self.pending.push(Item::Enum(enum_name.clone(), variants));
+            Tok::new(TokKind::Key(enum_name))
+        };
+        if optional && !self.is_list(&tok) {
+            tok.optional = true;
+        }
+        Some(tok)
+    }
+
+    /// List of item, reusing lists of the
+    /// same reference and separator
+    fn repeat(&mut self, item: &Expr, sep: &str, plus: bool) -> Option<Tok> {
+        if plus {
+            self.report(Level::Note, "has one or more repetitions, written as lists which can also be empty");
+        }
+        if let Some(type_name) = self.item_type(item) {
+            let existing = self
+                .lists
+                .iter()
+                .find(|list| list.1 == type_name && list.2 == sep)
+                .map(|list| list.0.clone());
+            let key = match existing {
+                Some(key) => key,
+                None => {
+                    let key = self.unique(format!("{}s", lower_camel(&type_name)));
+                    self.lists.push((key.clone(), type_name.clone(), sep.to_string()));
+                    self.pending.push(Item::List(List {
+                        key: key.clone(),
+                        ast_type: None,
+                        sep: sep.to_string(),
+                        items: vec![Variant::Ref(type_name)],
+                    }));
+                    key
+                }
+            };
+            return Some(Tok::new(TokKind::Key(key)));
+        }
+        let ast_type = self.unique(format!("{}Item", self.current_type));
+        let key = self.unique(format!("{}s", lower_camel(&ast_type)));
+        let items = match unnamed(item) {
+            &Expr::Alt(ref alts) => {
+                let alts = alts
+                    .iter()
+                    .filter(|alt| !is_empty(alt))
+                    .map(|alt| Alternative {
+                        expr: alt.clone(),
+                        label: None,
+                    })
+                    .collect::<Vec<_>>();
+                self.variants(&ast_type, &alts)
+            }
+            _ => {
+                let toks = self.tokens(&mut HashSet::new(), item);
+                if toks.is_empty() {
+                    return None;
+                }
+                vec![Variant::Def(ast_type.clone(), toks)]
+            }
+        };
+        if items.is_empty() {
+            return None;
+        }
+        self.pending.push(Item::List(List {
+            key: key.clone(),
+            ast_type: Some(ast_type),
+            sep: sep.to_string(),
+            items,
+        }));
+        Some(Tok::new(TokKind::Key(key)))
+    }
+
+    /// Type of item if it is a single
+    /// reference to a rule with a type
+    fn item_type(&mut self, item: &Expr) -> Option<String> {
+        let name = match unnamed(item) {
+            &Expr::Ref(ref name) => name,
+            _ => return None,
+        };
+        if let Some(type_name) = self.types.get(name) {
+            if self.nullable.contains(name) {
+                return None;
+            }
+            return Some(type_name.clone());
+        }
+        match self.tokens.get(name) {
+            Some(&Token::Literals(_)) | Some(&Token::Chars(_)) => self.token_enum(name),
+            _ => None,
+        }
+    }
+
+    fn literal(&mut self, literal: &str) -> Option<Tok> {
+        if literal.is_empty() {
+            return None;
+        }
+        if let Some(key) = builtin_key(literal) {
+            return Some(Tok::new(TokKind::Key(key.to_string())));
+        }
+        if literal.contains('"') || literal.contains('\\') || literal.chars().any(char::is_control) {
+            let message = format!("has literal {:?}, which can't be written in descr, it is left out", literal);
+            self.report(Level::Error, &message);
+            return None;
+        }
+        Some(Tok::new(TokKind::Literal(literal.to_string())))
+    }
+
+    fn reference(&mut self, name: &str) -> Option<Tok> {
+        if name == "EOF" && self.format == Format::Antlr4 {
+            return None;
+        }
+        if let Some(type_name) = self.types.get(name) {
+            let mut tok = Tok::new(TokKind::Key(type_name.clone()));
+            tok.optional = self.nullable.contains(name);
+            return Some(tok);
+        }
+        if self.empty.contains(name) {
+            return None;
+        }
+        let token = match self.tokens.get(name) {
+            Some(token) => token.clone(),
+            None => {
+                let message = format!("refers to {}, which is not defined", name);
+                self.report(Level::Error, &message);
+                return Some(Tok::new(TokKind::Key(name.to_string())));
+            }
+        };
+        match token {
+            Token::Key(key) => Some(Tok::new(TokKind::Key(key.to_string()))),
+            Token::Literal(ref literal) => self.literal(literal),
+            Token::Literals(_) | Token::Chars(_) => {
+                let enum_name = self.token_enum(name)?;
+                Some(Tok::new(TokKind::Key(enum_name)))
+            }
+            Token::Skip => {
+                let message = format!("refers to {}, which is skipped, it is left out", name);
+                self.report(Level::Warning, &message);
+                None
+            }
+            Token::Unresolved => {
+                if self.reported.insert(name.to_string()) {
+                    let rule = self.rules.iter().find(|rule| rule.name == name);
+                    let line = rule.map_or(0, |rule| rule.line);
+                    let message = match rule {
+                        Some(rule) if rule.alts.is_empty() => "is declared without a rule, the key is kept",
+                        _ => "can't be written with descr tokens, which are int, ident, string and literals, the key is kept",
+                    };
+                    self.report_at(Level::Error, line, name, message);
+                }
+                Some(Tok::new(TokKind::Key(name.to_string())))
+            }
+        }
+    }
+
+    /// Enum of the literals of a lexer rule,
+    /// added with the first reference
+    fn token_enum(&mut self, name: &str) -> Option<String> {
+        if let Some(enum_name) = self.token_enums.get(name) {
+            return Some(enum_name.clone());
+        }
+        let literals = match self.tokens.get(name) {
+            Some(&Token::Literals(ref literals)) => literals.clone(),
+            Some(&Token::Chars(ref set)) => {
+                let count = set.iter().map(|&(from, to)| (to as u32).saturating_sub(from as u32) as usize + 1).sum::<usize>();
+                if count > MAX_CHARS {
+                    if self.reported.insert(name.to_string()) {
+                        let message = format!(
+                            "matches any of {} characters, descr has no character sets, it is left out",
+                            count
+                        );
+                        let line = self.rules.iter().find(|rule| rule.name == name).map(|rule| rule.line).unwrap_or(0);
+                        self.report_at(Level::Error, line, name, &message);
+                    }
+                    return None;
+                }
+                let mut literals = Vec::new();
+                for &(from, to) in set {
+                    for c in (from as u32)..(to as u32 + 1) {
+                        if let Some(c) = ::std::char::from_u32(c) {
+                            literals.push(c.to_string());
+                        }
+                    }
+                }
+                literals
+            }
+            _ => return None,
+        };
+        let enum_name = self.unique(camel(name));
+        self.token_enums.insert(name.to_string(), enum_name.clone());
+        let mut variants = Vec::new();
+        for (i, literal) in literals.iter().enumerate() {
+            if let Some(tok) = self.literal(literal) {
+                let variant_name = literal_name(literal).unwrap_or_else(|| format!("{}{}", enum_name, i + 1));
+                let variant_name = self.unique(variant_name);
+                variants.push(Variant::Def(variant_name, vec![tok]));
+            }
+        }
+        self.token_items.push(Item::Enum(enum_name.clone(), variants));
+        Some(enum_name)
+    }
+
+    /// Tokens with member keys that are already used
+    /// in the struct get a name, like expr2:Expr
+    fn member(&self, members: &mut HashSet<String>, mut tok: Tok) -> Tok {
+        let key = match tok.kind {
+            TokKind::Key(ref key) if is_member(key) => key.clone(),
+            _ => return tok,
+        };
+        if let Some(name) = tok.name.clone() {
+            if members.insert(member_norm(&name)) {
+                return tok;
+            }
+        } else if members.insert(member_norm(&key)) {
+            return tok;
+        }
+        let base = lower_camel(&key);
+        let mut n = 2;
+        while members.contains(&member_norm(&format!("{}{}", base, n))) {
+            n += 1;
+        }
+        let name = format!("{}{}", base, n);
+        members.insert(member_norm(&name));
+        tok.name = Some(name);
+        tok
+    }
+
+    fn is_list(&self, tok: &Tok) -> bool {
+        match tok.kind {
+            TokKind::Key(ref key) => self.items.iter().chain(self.pending.iter()).any(|item| match item {
+                &Item::List(ref list) => &list.key == key,
+                _ => false,
+            }),
+            _ => false,
+        }
+    }
+
+    /// Name not used by other types, lists
+    /// or builtin tokens, numbered if needed
+    fn unique(&mut self, name: String) -> String {
+        let name = if name.is_empty() { "Rule".to_string() } else { name };
+        let mut candidate = name.clone();
+        let mut n = 2;
+        while self.names.contains(&candidate)
+            || LangData::builtin_part(&candidate).is_some()
+            || RESERVED.contains(&candidate.as_str())
+        {
+            candidate = format!("{}{}", name, n);
+            n += 1;
+        }
+        self.names.insert(candidate.clone());
+        candidate
+    }
+
+    /// Reports parser rules that can start with
+    /// themselves, where descr parsers would
+    /// recurse without end
+    fn left_recursion(&mut self, rules: &[Rule]) {
+        let mut leading = HashMap::new();
+        for rule in rules {
+            let mut refs = Vec::new();
+            for alt in &rule.alts {
+                self.leading(&alt.expr, &mut refs);
+            }
+            leading.insert(rule.name.clone(), refs);
+        }
+        for rule in rules {
+            let mut seen = HashSet::new();
+            let mut stack = leading[&rule.name].clone();
+            let mut recursive = false;
+            while let Some(name) = stack.pop() {
+                if name == rule.name {
+                    recursive = true;
+                    break;
+                }
+                if seen.insert(name.clone()) {
+                    if let Some(refs) = leading.get(&name) {
+                        stack.extend(refs.iter().cloned());
+                    }
+                }
+            }
+            if recursive {
+                self.report_at(
+                    Level::Error,
+                    rule.line,
+                    &rule.name,
+                    "is left recursive, which descr parsers can't parse, write it with a list like `term (op term)*`",
+                );
+            }
+        }
+    }
+
+    /// Rules expr can start with, gives
+    /// whether expr can match empty input
+    fn leading(&self, expr: &Expr, refs: &mut Vec<String>) -> bool {
+        match expr {
+            &Expr::Seq(ref exprs) => exprs.iter().all(|expr| self.leading(expr, refs)),
+            &Expr::Alt(ref alts) => alts.iter().fold(false, |nullable, alt| self.leading(alt, refs) || nullable),
+            &Expr::Opt(ref inner) | &Expr::Star(ref inner) => {
+                self.leading(inner, refs);
+                true
+            }
+            &Expr::Plus(ref inner) | &Expr::Named(_, ref inner) => self.leading(inner, refs),
+            &Expr::Ref(ref name) => {
+                refs.push(name.clone());
+                self.nullable.contains(name) || self.empty.contains(name) || name == "EOF"
+            }
+            &Expr::Empty => true,
+            _ => false,
+        }
+    }
+
+    fn report(&mut self, level: Level, message: &str) {
+        let (line, rule) = (self.line, self.rule.clone());
+        self.report_at(level, line, &rule, message);
+    }
+
+    fn report_at(&mut self, level: Level, line: usize, key: &str, message: &str) {
+        let diagnostic = Diagnostic {
+            level,
+            line,
+            key: key.to_string(),
+            message: message.to_string(),
+        };
+        // Same message for a rule once
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+}
+
+fn rule_expr(rule: &Rule) -> Expr {
+    if rule.alts.len() == 1 {
+        rule.alts[0].expr.clone()
+    } else {
+        Expr::Alt(rule.alts.iter().map(|alt| alt.expr.clone()).collect())
+    }
+}
+
+fn unnamed(expr: &Expr) -> &Expr {
+    match expr {
+        &Expr::Named(_, ref inner) => unnamed(inner),
+        _ => expr,
+    }
+}
+
+fn is_empty(expr: &Expr) -> bool {
+    match expr {
+        &Expr::Empty => true,
+        &Expr::Seq(ref exprs) => exprs.iter().all(is_empty),
+        _ => false,
+    }
+}
+
+/// Whether expr has constructs of lexer rules
+fn is_lexical(expr: &Expr) -> bool {
+    match expr {
+        &Expr::CharSet { .. } | &Expr::Any | &Expr::Not(_) | &Expr::Except(..) => true,
+        &Expr::Seq(ref exprs) | &Expr::Alt(ref exprs) => exprs.iter().any(is_lexical),
+        &Expr::Opt(ref inner) | &Expr::Star(ref inner) | &Expr::Plus(ref inner) | &Expr::Named(_, ref inner) => {
+            is_lexical(inner)
+        }
+        _ => false,
+    }
+}
+
+fn describe(expr: &Expr) -> &'static str {
+    match expr {
+        &Expr::CharSet { .. } => "a character set",
+        &Expr::Any => "a wildcard",
+        &Expr::Not(_) => "a negation",
+        &Expr::Except(..) => "an exception",
+        _ => "an expression",
+    }
+}
+
+fn is_digits(set: &[(char, char)]) -> bool {
+    set.iter().all(|&(from, to)| from >= '0' && to <= '9')
+}
+
+fn is_letters(set: &[(char, char)]) -> bool {
+    set.iter().all(|&(from, to)| {
+        (from >= 'a' && to <= 'z') || (from >= 'A' && to <= 'Z') || (from == to && (from.is_alphabetic() || from == '_'))
+    })
+}
+
+fn is_word(set: &[(char, char)]) -> bool {
+    set.iter()
+        .all(|&(from, to)| is_letters(&[(from, to)]) || is_digits(&[(from, to)]))
+}
+
+fn is_whitespace(set: &[(char, char)]) -> bool {
+    set.iter().all(|&(from, to)| {
+        (from as u32..to as u32 + 1).all(|c| [' ', '\t', '\r', '\n', '\x0c'].iter().any(|ws| *ws as u32 == c))
+    })
+}
+
+/// Builtin token of the literal, like COMMA
+fn builtin_key(literal: &str) -> Option<&'static str> {
+    CHAR_KEYS.iter().cloned().find(|key| match LangData::builtin_part(key) {
+        Some(TypedPart::CharPart { chr, .. }) => literal.len() == chr.len_utf8() && literal.starts_with(chr),
+        Some(TypedPart::TagPart { tag, .. }) => literal == tag,
+        _ => false,
+    })
+}
+
+/// Whether a key token is a member of
+/// its struct, other than single tokens
+/// like COMMA and whitespace
+fn is_member(key: &str) -> bool {
+    match LangData::builtin_part(key) {
+        Some(TypedPart::CharPart { .. }) | Some(TypedPart::TagPart { .. }) | Some(TypedPart::WSPart) => false,
+        _ => true,
+    }
+}
+
+/// Member keys are compared as the
+/// snake cased field names they become
+fn member_norm(key: &str) -> String {
+    key.to_lowercase().replace('_', "")
+}
+
+/// Variant named by a starting keyword, like
+/// IfStmt, or by the position in the enum
+fn variant_name(enum_name: &str, expr: &Expr, i: usize) -> String {
+    let exprs = match unnamed(expr) {
+        &Expr::Seq(ref exprs) => exprs.iter().map(unnamed).collect::<Vec<_>>(),
+        expr => vec![expr],
+    };
+    match exprs.first() {
+        Some(&&Expr::Literal(ref literal)) if exprs.len() == 1 => {
+            if let Some(name) = literal_name(literal) {
+                return name;
+            }
+        }
+        Some(&&Expr::Literal(ref literal)) if is_word(&literal.chars().map(|c| (c, c)).collect::<Vec<_>>()) => {
+            return format!("{}{}", camel(literal), enum_name);
+        }
+        Some(&&Expr::Ref(ref name)) if exprs.len() == 1 => return format!("{}{}", camel(name), enum_name),
+        _ => {}
+    }
+    format!("{}{}", enum_name, i + 1)
+}
+
+/// Name of a keyword, or of the
+/// symbols in it, like LtEq for <=
+fn literal_name(literal: &str) -> Option<String> {
+    if literal.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        let name = camel(literal);
+        return if name.is_empty() { None } else { Some(name) };
+    }
+    let mut name = String::new();
+    for c in literal.chars() {
+        name += match c {
+            '+' => "Plus",
+            '-' => "Minus",
+            '*' => "Star",
+            '/' => "Slash",
+            '%' => "Percent",
+            '=' => "Eq",
+            '<' => "Lt",
+            '>' => "Gt",
+            '!' => "Excl",
+            '&' => "Amp",
+            '|' => "Pipe",
+            '^' => "Caret",
+            '~' => "Tilde",
+            '?' => "Question",
+            ':' => "Colon",
+            ';' => "Semi",
+            ',' => "Comma",
+            '.' => "Dot",
+            '(' => "LParen",
+            ')' => "RParen",
+            '[' => "LBracket",
+            ']' => "RBracket",
+            '{' => "LBrace",
+            '}' => "RBrace",
+            '@' => "At",
+            '#' => "Hash",
+            '$' => "Dollar",
+            '\'' => "Apos",
+            _ => return None,
+        };
+    }
+    Some(name)
+}
+
+/// Type name of a rule, like ExprList
+/// for expr_list and Id for ID
+pub fn camel(name: &str) -> String {
+    let mut s = String::new();
+    for part in name.split(|c: char| !c.is_alphanumeric()).filter(|part| !part.is_empty()) {
+        let upper = part.chars().all(|c| !c.is_lowercase());
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            s.extend(first.to_uppercase());
+        }
+        if upper {
+            s.extend(chars.flat_map(char::to_lowercase));
+        } else {
+            s.extend(chars);
+        }
+    }
+    if s.starts_with(|c: char| c.is_numeric()) {
+        s.insert(0, 'N');
+    }
+    s
+}
+
+fn lower_camel(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_rules_and_literals() {
+        assert_eq!(camel("expr_list"), "ExprList");
+        assert_eq!(camel("exprList"), "ExprList");
+        assert_eq!(camel("BOOL_LIT"), "BoolLit");
+        assert_eq!(camel("<digit>"), "Digit");
+        assert_eq!(lower_camel("ExprList"), "exprList");
+        assert_eq!(literal_name("<="), Some("LtEq".to_string()));
+        assert_eq!(literal_name("true"), Some("True".to_string()));
+        assert_eq!(literal_name("\u{2192}"), None);
+        assert_eq!(builtin_key(","), Some("COMMA"));
+        assert_eq!(builtin_key(">="), Some("GTE"));
+        assert_eq!(builtin_key("+"), None);
+    }
+}
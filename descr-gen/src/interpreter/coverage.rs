@@ -0,0 +1,403 @@
+//! Grammar coverage over parsed inputs.
+//! Points are the rules, the alternatives of
+//! enums and lists, optional parts and list
+//! separators in lang data. The interpreter
+//! records hits for points that match, and
+//! drops them when parsing backtracks.
+//!
+//! ```ignore
+//! let interpreter = Interpreter::new(&data).with_coverage();
+//! let mut coverage = Coverage::new(&data);
+//! for input in inputs {
+//!     interpreter.parse_complete(input)?;
+//!     coverage.add(&interpreter.hits());
+//! }
+//! println!("{}", coverage.to_text());
+//! ```
+use std::collections::HashMap;
+use std::fmt;
+use lang_data::data::*;
+use lang_data::rule::*;
+use lang_data::typed_part::TypedPart;
+use descr_common::util::*;
+use descr_common::json::json_str;
+
+/// Point matched while parsing. Rules
+/// and parts are known by their address
+/// in lang data, which doesn't move while
+/// the interpreter borrows it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Hit {
+    Rule(usize),
+    Alternative(usize),
+    Optional(usize),
+    Separator(usize),
+}
+
+pub fn address<T>(item: &T) -> usize {
+    item as *const T as usize
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointKind {
+    Rule,
+    Alternative,
+    Optional,
+    Separator,
+}
+impl PointKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            &PointKind::Rule => "rule",
+            &PointKind::Alternative => "alternative",
+            &PointKind::Optional => "optional",
+            &PointKind::Separator => "separator",
+        }
+    }
+}
+
+/// Point with the ast_data or list_data key
+/// it is in. Detail is the alternative name,
+/// the optional part, or the separator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoveragePoint {
+    pub kind: PointKind,
+    pub key: String,
+    pub detail: String,
+    pub hits: usize,
+}
+impl fmt::Display for CoveragePoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:<12} {}", self.kind.name(), self.key)?;
+        if !self.detail.is_empty() {
+            write!(f, ": {}", self.detail)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Coverage {
+    pub points: Vec<CoveragePoint>,
+    index: HashMap<Hit, usize>,
+    /// Inputs added
+    pub inputs: usize,
+    /// Inputs that didn't parse, with
+    /// the error
+    pub failures: Vec<(String, String)>,
+}
+
+impl Coverage {
+    /// Points of lang data, in key order
+    pub fn new(data: &LangData) -> Coverage {
+        let mut coverage = Coverage {
+            points: Vec::new(),
+            index: HashMap::new(),
+            inputs: 0,
+            failures: Vec::new(),
+        };
+        for (key, ast_data) in data.ast_data.sorted_iter() {
+            coverage.add_point(Hit::Rule(address(ast_data)), PointKind::Rule, key, String::new());
+            // Single rules are covered by the rule
+            let alternatives = ast_data.rules.len() > 1;
+            for rule in &ast_data.rules {
+                coverage.add_rule_points(key, rule, alternatives);
+            }
+        }
+        for (key, list_data) in data.list_data.sorted_iter() {
+            coverage.add_point(Hit::Rule(address(list_data)), PointKind::Rule, key, String::new());
+            // Empty lists match, so items are
+            // alternatives even when alone
+            for rule in &list_data.rules {
+                coverage.add_rule_points(key, &rule.ast_rule, true);
+            }
+            if let Some(sep) = list_data.sep {
+                match data.typed_parts.get(sep) {
+                    Some(&TypedPart::WSPart) | None => {}
+                    Some(_) => {
+                        coverage.add_point(
+                            Hit::Separator(address(list_data)),
+                            PointKind::Separator,
+                            key,
+                            sep.to_string(),
+                        );
+                    }
+                }
+            }
+        }
+        coverage
+    }
+
+    fn add_point(&mut self, hit: Hit, kind: PointKind, key: &str, detail: String) {
+        self.index.insert(hit, self.points.len());
+        self.points.push(CoveragePoint {
+            kind,
+            key: key.to_string(),
+            detail,
+            hits: 0,
+        });
+    }
+
+    fn add_rule_points(&mut self, key: &str, rule: &AstRule, alternative: bool) {
+        match rule {
+            &AstRule::RefRule(rule_ref) => {
+                if alternative {
+                    self.add_point(Hit::Alternative(address(rule)), PointKind::Alternative, key, rule_ref.to_string());
+                }
+            }
+            &AstRule::PartsRule(ref parts_rule) => {
+                if alternative {
+                    self.add_point(
+                        Hit::Alternative(address(rule)),
+                        PointKind::Alternative,
+                        key,
+                        parts_rule.ast_type.to_string(),
+                    );
+                }
+                self.add_optional_points(key, parts_rule.ast_type, &parts_rule.parts);
+            }
+        }
+    }
+
+    fn add_optional_points(&mut self, key: &str, ast_type: &str, parts: &[AstRulePart]) {
+        for part in parts {
+            if part.optional && !part.not {
+                let detail = format!("{} {}", ast_type, describe_part(part));
+                self.add_point(Hit::Optional(address(part)), PointKind::Optional, key, detail);
+            }
+            if let AstRuleToken::Group(ref parts) = part.token {
+                self.add_optional_points(key, ast_type, parts);
+            }
+        }
+    }
+
+    /// Hits of a parsed input
+    pub fn add(&mut self, hits: &[Hit]) {
+        self.inputs += 1;
+        for hit in hits {
+            if let Some(&i) = self.index.get(hit) {
+                self.points[i].hits += 1;
+            }
+        }
+    }
+
+    pub fn add_failure(&mut self, input: String, error: String) {
+        self.failures.push((input, error));
+    }
+
+    pub fn covered(&self) -> usize {
+        self.points.iter().filter(|point| point.hits > 0).count()
+    }
+
+    pub fn unexercised(&self) -> Vec<&CoveragePoint> {
+        self.points.iter().filter(|point| point.hits == 0).collect()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut s = String::new();
+        let total = self.points.len();
+        let percent = if total == 0 { 100 } else { self.covered() * 100 / total };
+        s += &format!(
+            "Coverage: {} of {} points ({}%) over {} inputs\n",
+            self.covered(),
+            total,
+            percent,
+            self.inputs
+        );
+        let unexercised = self.unexercised();
+        if !unexercised.is_empty() {
+            s += "\nNot exercised:\n";
+            for point in unexercised {
+                s += &format!("  {}\n", point);
+            }
+        }
+        if !self.failures.is_empty() {
+            s += "\nFailed to parse:\n";
+            for &(ref input, ref error) in &self.failures {
+                s += &format!("  {}: {}\n", input, error);
+            }
+        }
+        s
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut s = String::new();
+        s += "{\n";
+        s += &format!("  \"inputs\": {},\n", self.inputs);
+        s += &format!("  \"total\": {},\n", self.points.len());
+        s += &format!("  \"covered\": {},\n", self.covered());
+        s += "  \"points\": [";
+        for (i, point) in self.points.iter().enumerate() {
+            s += if i > 0 { ",\n" } else { "\n" };
+            s += &format!(
+                "    {{ \"kind\": \"{}\", \"key\": {}, \"detail\": {}, \"hits\": {} }}",
+                point.kind.name(),
+                json_str(&point.key),
+                json_str(&point.detail),
+                point.hits
+            );
+        }
+        s += if self.points.is_empty() { "],\n" } else { "\n  ],\n" };
+        s += "  \"failures\": [";
+        for (i, &(ref input, ref error)) in self.failures.iter().enumerate() {
+            s += if i > 0 { ",\n" } else { "\n" };
+            s += &format!("    {{ \"input\": {}, \"error\": {} }}", json_str(input), json_str(error));
+        }
+        s += if self.failures.is_empty() { "]\n" } else { "\n  ]\n" };
+        s += "}\n";
+        s
+    }
+}
+
+/// Part like it is written in the grammar
+fn describe_part(part: &AstRulePart) -> String {
+    let mut s = String::new();
+    if let Some(member_key) = part.member_key {
+        s += member_key;
+        s += ":";
+    }
+    if part.not {
+        s += "!";
+    }
+    match part.token {
+        AstRuleToken::Key(key) => s += key,
+        AstRuleToken::Tag(tag) => s += &format!("\"{}\"", tag),
+        AstRuleToken::Func(fnc, _) => s += &format!("{}(..)", fnc),
+        AstRuleToken::Group(ref parts) => {
+            let parts = parts.iter().map(describe_part).collect::<Vec<_>>();
+            s += &format!("({})", parts.join(" "));
+        }
+    }
+    if part.optional {
+        s += "?";
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use interpreter::Interpreter;
+    use process::test_util::{json, repo_grammar, with_data};
+
+    const GRAMMAR: &'static str = "
+        Source(items:items)
+        items:Item[] WS {
+            Say(\"say\" string (\"to\" who:ident)?),
+            Gray(\"color\" LPAREN Num RPAREN \"gray\"),
+            Color(\"color\" LPAREN ints RPAREN),
+            Comment(\"(*\" comment:!\"*)\" \"*)\")
+        }
+        ints[] COMMA Num
+        Num(int)
+    ";
+
+    /// Coverage of inputs, which must parse
+    fn coverage_of(data: &LangData, inputs: &[&str]) -> Coverage {
+        let interpreter = Interpreter::new(data).with_coverage();
+        let mut coverage = Coverage::new(data);
+        for input in inputs {
+            interpreter.parse_complete(input.as_bytes()).unwrap();
+            coverage.add(&interpreter.hits());
+        }
+        coverage
+    }
+
+    #[test]
+    fn reports_coverage() {
+        with_data(GRAMMAR, |data| {
+            let mut coverage = coverage_of(data, &["say \"hi\" color(1)", "color(1, 2) say 'x' to bob (* a *)"]);
+            let hits = |detail: &str| {
+                coverage.points.iter().find(|point| point.detail == detail).unwrap().hits
+            };
+            // Nums in Gray are dropped when it backtracks
+            assert_eq!(coverage.points[0].to_string(), "rule         Num");
+            assert_eq!(coverage.points[0].hits, 3);
+            assert_eq!(hits("COMMA"), 1);
+            assert_eq!(hits("Say (\"to\" who:ident)?"), 1);
+            let unexercised = coverage.unexercised();
+            assert_eq!(unexercised.len(), 1);
+            assert_eq!(unexercised[0].to_string(), "alternative  items: Gray");
+            coverage.add_failure("bad.test".to_string(), "Parse error at 1:1".to_string());
+            assert_eq!(
+                coverage.to_text(),
+                "Coverage: 10 of 11 points (90%) over 2 inputs\n\n\
+                 Not exercised:\n  alternative  items: Gray\n\n\
+                 Failed to parse:\n  bad.test: Parse error at 1:1\n"
+            );
+        })
+    }
+
+    #[test]
+    fn has_points_of_rules_alternatives_and_parts() {
+        with_data(GRAMMAR, |data| {
+            let coverage = Coverage::new(data);
+            let points = coverage.points.iter().map(|point| point.to_string()).collect::<Vec<_>>();
+            // Num has a single rule, and WS
+            // separators are not points
+            assert_eq!(
+                points,
+                vec![
+                    "rule         Num",
+                    "rule         Source",
+                    "rule         ints",
+                    "alternative  ints: Num",
+                    "separator    ints: COMMA",
+                    "rule         items",
+                    "alternative  items: Say",
+                    "optional     items: Say (\"to\" who:ident)?",
+                    "alternative  items: Gray",
+                    "alternative  items: Color",
+                    "alternative  items: Comment",
+                ]
+            );
+            assert_eq!(coverage.to_text(), "Coverage: 0 of 11 points (0%) over 0 inputs\n\nNot exercised:\n".to_string()
+                + &points.iter().map(|point| format!("  {}\n", point)).collect::<String>());
+        })
+    }
+
+    #[test]
+    fn writes_json_of_points_and_failures() {
+        with_data(GRAMMAR, |data| {
+            let mut coverage = coverage_of(data, &["color(1, 2)"]);
+            coverage.add_failure("tab\t\"quoted\".test".to_string(), "Expected \\ at\n1:1\u{1}".to_string());
+            let value = json("coverage.json", &coverage.to_json());
+            assert_eq!(value["inputs"], 1);
+            assert_eq!(value["total"], coverage.points.len());
+            assert_eq!(value["covered"], coverage.covered());
+            let points = value["points"].as_array().unwrap();
+            assert_eq!(points.len(), coverage.points.len());
+            for (point, value) in coverage.points.iter().zip(points) {
+                assert_eq!(value["kind"], point.kind.name());
+                assert_eq!(value["key"], point.key.as_str());
+                assert_eq!(value["detail"], point.detail.as_str());
+                assert_eq!(value["hits"], point.hits);
+            }
+            assert_eq!(value["failures"][0]["input"], "tab\t\"quoted\".test");
+            assert_eq!(value["failures"][0]["error"], "Expected \\ at\n1:1\u{1}");
+        });
+        with_data("Source(int)", |data| {
+            let coverage = Coverage::new(data);
+            let value = json("empty.json", &coverage.to_json());
+            assert_eq!(value["failures"].as_array().unwrap().len(), 0);
+        })
+    }
+
+    #[test]
+    fn repo_grammar_covers_itself() {
+        let descr = repo_grammar("descr.lang");
+        with_data(&descr, |data| {
+            let coverage = coverage_of(data, &[&descr]);
+            let value = json("coverage.json", &coverage.to_json());
+            assert_eq!(value["total"], coverage.points.len());
+            // Optional parts come with the rule
+            // they are in
+            for point in &coverage.points {
+                if point.hits > 0 && point.kind == PointKind::Optional {
+                    let rule = coverage.points.iter().find(|rule| rule.kind == PointKind::Rule && rule.key == point.key);
+                    assert!(rule.unwrap().hits > 0, "{}", point);
+                }
+            }
+            assert!(coverage.covered() * 2 > coverage.points.len(), "{}", coverage.to_text());
+        })
+    }
+}
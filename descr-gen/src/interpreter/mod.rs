@@ -0,0 +1,811 @@
+//! Parses input directly from lang data,
+//! without generating code. Rules are
+//! interpreted with the same semantics as
+//! the generated nom parsers, so it can be
+//! used to try grammars, and as a reference
+//! for the generated code.
+//!
+//! ```ignore
+//! let ast = parse_grammar(grammar)?;
+//! let mut data = LangData::new(false, "Lang".to_string());
+//! build_data(grammar, &ast, &mut data)?;
+//! let node = Interpreter::new(&data).parse_complete(input)?;
+//! println!("{}", node);
+//! ```
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::str;
+use descr_common::cursor::{ws_end, Cursor, Fail, PResult, State};
+use descr_common::trace::{Outcome, Recorder, Trace};
+use lang_data::data::*;
+use lang_data::rule::*;
+use lang_data::typed_part::TypedPart;
+use generator::SourcePos;
+
+pub mod tree;
+pub mod to_source;
+pub mod coverage;
+pub use self::tree::{Node, Span, Value};
+pub use self::to_source::DynToSource;
+pub use self::coverage::{Coverage, Hit};
+use self::coverage::address;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseErrorKind {
+    /// No rule matched
+    Error,
+    /// Input ended in the middle of a rule
+    Incomplete,
+    /// Start rule matched, but input remains
+    Trailing,
+}
+
+/// Parse error at the furthest position
+/// a token was tried, with the tokens
+/// that were expected there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub pos: SourcePos,
+    pub expected: Vec<String>,
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ParseErrorKind::Error => write!(f, "Parse error at {}", self.pos)?,
+            ParseErrorKind::Incomplete => write!(f, "Unexpected end of input at {}", self.pos)?,
+            ParseErrorKind::Trailing => write!(f, "Unexpected input at {}", self.pos)?,
+        }
+        if !self.expected.is_empty() {
+            write!(f, ", expected {}", self.expected.join(" or "))?;
+        }
+        Ok(())
+    }
+}
+impl Error for ParseError {}
+
+/// Result of the start rule, and the
+/// offset where it ended. Like the generated
+/// `start` parser, input may remain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parsed<'i> {
+    pub value: Value<'i>,
+    pub end: usize,
+}
+
+/// Token matched by a part, with the scope
+/// it would get in the generated syntax, from
+/// the syntax annotation or the token type
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub span: Span,
+    pub scope: String,
+}
+
+/// Tokens and coverage hits, recorded as
+/// parts match, so they are reset when
+/// parsing backtracks
+struct Recording {
+    tokens: Vec<Token>,
+    /// Recorded when coverage is on
+    hits: Option<Vec<Hit>>,
+}
+impl State for Recording {
+    type Mark = (usize, usize);
+
+    fn mark(&self) -> (usize, usize) {
+        (self.tokens.len(), self.hits.as_ref().map(|hits| hits.len()).unwrap_or(0))
+    }
+
+    fn reset(&mut self, (tokens, hits): (usize, usize)) {
+        self.tokens.truncate(tokens);
+        if let Some(ref mut recorded) = self.hits {
+            recorded.truncate(hits);
+        }
+    }
+}
+impl Recording {
+    fn hit(&mut self, hit: Hit) {
+        if let Some(ref mut hits) = self.hits {
+            hits.push(hit);
+        }
+    }
+}
+
+type Cur<'i> = Cursor<'i, Recording>;
+
+struct Furthest {
+    offset: usize,
+    expected: Vec<String>,
+}
+
+/// Parses with the combinators of
+/// descr_common::cursor, like the
+/// generated recursive descent parsers
+pub struct Interpreter<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+    furthest: RefCell<Furthest>,
+    tokens: RefCell<Vec<Token>>,
+    /// Recorded when coverage is on
+    hits: RefCell<Option<Vec<Hit>>>,
+    /// Recorded when tracing is on
+    recorder: RefCell<Option<Recorder>>,
+}
+
+impl<'a, 'd> Interpreter<'a, 'd> {
+    pub fn new(data: &'a LangData<'d>) -> Interpreter<'a, 'd> {
+        Interpreter {
+            data,
+            furthest: RefCell::new(Furthest {
+                offset: 0,
+                expected: Vec::new(),
+            }),
+            tokens: RefCell::new(Vec::new()),
+            hits: RefCell::new(None),
+            recorder: RefCell::new(None),
+        }
+    }
+
+    /// Record rules, alternatives, optional
+    /// parts and separators that match
+    pub fn with_coverage(self) -> Interpreter<'a, 'd> {
+        *self.hits.borrow_mut() = Some(Vec::new());
+        self
+    }
+
+    /// Coverage hits from the last parse,
+    /// add them to a Coverage
+    pub fn hits(&self) -> Vec<Hit> {
+        self.hits.borrow().clone().unwrap_or_default()
+    }
+
+    /// Record a trace of the rules, alternatives
+    /// and tokens tried, like generated parsers
+    /// with tracing
+    pub fn with_trace(self) -> Interpreter<'a, 'd> {
+        *self.recorder.borrow_mut() = Some(Recorder::new());
+        self
+    }
+
+    /// Trace of the last parse of input
+    pub fn trace(&self, input: &[u8]) -> Option<Trace> {
+        self.recorder
+            .borrow_mut()
+            .as_mut()
+            .map(|recorder| mem::replace(recorder, Recorder::new()).finish(input))
+    }
+
+    /// Tokens from the last parse, in order
+    pub fn tokens(&self) -> Vec<Token> {
+        self.tokens.borrow().clone()
+    }
+
+    /// Parse from the start rule
+    pub fn parse<'i>(&self, input: &'i [u8]) -> Result<Parsed<'i>, ParseError> {
+        match self.data.start_key {
+            Some(start_key) => self.parse_rule(start_key, input),
+            None => Err(ParseError {
+                kind: ParseErrorKind::Error,
+                pos: SourcePos::from_offset(input, 0),
+                expected: Vec::new(),
+            }),
+        }
+    }
+
+    /// Parse from rule with given key
+    pub fn parse_rule<'i>(&self, key: &str, input: &'i [u8]) -> Result<Parsed<'i>, ParseError> {
+        {
+            let mut furthest = self.furthest.borrow_mut();
+            furthest.offset = 0;
+            furthest.expected.clear();
+        }
+        if let Some(ref mut recorder) = *self.recorder.borrow_mut() {
+            *recorder = Recorder::new();
+        }
+        let recording = Recording {
+            tokens: Vec::new(),
+            hits: self.hits.borrow().as_ref().map(|_| Vec::new()),
+        };
+        let mut c = Cursor::with_state(input, recording);
+        let res = self.parse_key(key, &mut c);
+        *self.tokens.borrow_mut() = c.state.tokens;
+        *self.hits.borrow_mut() = c.state.hits;
+        match res {
+            Ok(value) => Ok(Parsed { value, end: c.pos }),
+            Err(Fail::Error) => Err(self.error(ParseErrorKind::Error, input)),
+            Err(Fail::Incomplete) => Err(self.error(ParseErrorKind::Incomplete, input)),
+        }
+    }
+
+    /// Parse from the start rule, and require
+    /// that only whitespace remains. The start
+    /// rule is expected to give a node.
+    pub fn parse_complete<'i>(&self, input: &'i [u8]) -> Result<Node<'i>, ParseError> {
+        let parsed = self.parse(input)?;
+        let rest = ws_end(input, parsed.end);
+        if rest < input.len() {
+            let furthest_offset = self.furthest.borrow().offset;
+            return Err(if furthest_offset >= rest {
+                self.error(ParseErrorKind::Trailing, input)
+            } else {
+                ParseError {
+                    kind: ParseErrorKind::Trailing,
+                    pos: SourcePos::from_offset(input, rest),
+                    expected: Vec::new(),
+                }
+            });
+        }
+        match parsed.value {
+            Value::Node(node) => Ok(node),
+            value => Ok(Node {
+                type_name: self.data.start_key.unwrap_or("").to_string(),
+                span: Span {
+                    start: 0,
+                    end: parsed.end,
+                },
+                members: vec![("items".to_string(), value)],
+            }),
+        }
+    }
+
+    fn error(&self, kind: ParseErrorKind, input: &[u8]) -> ParseError {
+        let furthest = self.furthest.borrow();
+        ParseError {
+            kind,
+            pos: SourcePos::from_offset(input, furthest.offset),
+            expected: furthest.expected.clone(),
+        }
+    }
+
+    /// Record token expected at offset
+    fn expect(&self, offset: usize, expected: String) {
+        let mut furthest = self.furthest.borrow_mut();
+        if offset > furthest.offset {
+            furthest.offset = offset;
+            furthest.expected.clear();
+        }
+        if offset == furthest.offset && !furthest.expected.contains(&expected) {
+            furthest.expected.push(expected);
+        }
+    }
+
+    /// Token parser, recording what was
+    /// expected when it fails
+    fn expecting<'i, T, F>(&self, c: &mut Cur<'i>, expected: &str, parser: F) -> PResult<T>
+    where
+        F: FnOnce(&mut Cur<'i>) -> PResult<T>,
+    {
+        let pos = c.pos;
+        let res = parser(c);
+        if res.is_err() {
+            self.expect(pos, expected.to_string());
+        }
+        res
+    }
+
+    /// Records the attempt when tracing
+    fn traced<'i, T, F>(&self, name: &str, c: &mut Cur<'i>, parser: F) -> PResult<T>
+    where
+        F: FnOnce(&mut Cur<'i>) -> PResult<T>,
+    {
+        if self.recorder.borrow().is_none() {
+            return parser(c);
+        }
+        let pos = c.pos;
+        if let Some(ref mut recorder) = *self.recorder.borrow_mut() {
+            recorder.enter(name, pos);
+        }
+        let res = parser(c);
+        if let Some(ref mut recorder) = *self.recorder.borrow_mut() {
+            match res {
+                Ok(_) => recorder.exit(Outcome::Done, c.pos),
+                Err(Fail::Error) => recorder.exit(Outcome::Error, pos),
+                Err(Fail::Incomplete) => recorder.exit(Outcome::Incomplete, pos),
+            }
+        }
+        res
+    }
+
+    /// Rules with alternatives and lists are
+    /// traced as a whole, alternatives and
+    /// tokens by themselves
+    fn parse_key<'i>(&self, key: &str, c: &mut Cur<'i>) -> PResult<Value<'i>> {
+        if let Some(ast_data) = self.data.ast_data.get(key) {
+            let value = match ast_data.rules.len() {
+                0 => return Err(Fail::Error),
+                1 => self.parse_ast_rule(&ast_data.rules[0], c)?,
+                _ => self.traced(key, c, |c| self.parse_alt(ast_data.rules.iter(), c))?,
+            };
+            c.state.hit(Hit::Rule(address(ast_data)));
+            Ok(value)
+        } else if let Some(list_data) = self.data.list_data.get(key) {
+            let value = self.traced(key, c, |c| self.parse_list(list_data, c))?;
+            c.state.hit(Hit::Rule(address(list_data)));
+            Ok(value)
+        } else {
+            Err(Fail::Error)
+        }
+    }
+
+    /// alt_complete!, Incomplete is
+    /// counted as Error
+    fn parse_alt<'i, 'r, I>(&self, rules: I, c: &mut Cur<'i>) -> PResult<Value<'i>>
+    where
+        I: Iterator<Item = &'r AstRule<'d>>,
+        'd: 'r,
+    {
+        for rule in rules {
+            if let Some(value) = c.attempt(|c| self.parse_ast_rule(rule, c)) {
+                return Ok(value);
+            }
+        }
+        Err(Fail::Error)
+    }
+
+    fn parse_list_item<'i>(&self, list_data: &ListData<'d>, c: &mut Cur<'i>) -> PResult<Value<'i>> {
+        if list_data.rules.len() == 1 {
+            self.parse_ast_rule(&list_data.rules[0].ast_rule, c)
+        } else {
+            self.parse_alt(list_data.rules.iter().map(|rule| &rule.ast_rule), c)
+        }
+    }
+
+    fn parse_list<'i>(&self, list_data: &ListData<'d>, c: &mut Cur<'i>) -> PResult<Value<'i>> {
+        let sep = match list_data.sep {
+            Some(sep) => match self.data.typed_parts.get(sep) {
+                Some(&TypedPart::WSPart) => None,
+                Some(typed_part) => Some(typed_part),
+                None => return Err(Fail::Error),
+            },
+            None => None,
+        };
+        let items = match sep {
+            Some(sep) => {
+                let items = c.separated_list(
+                    |c| self.parse_typed_part(sep, c),
+                    |c| self.parse_list_item(list_data, c),
+                )?;
+                for _ in 1..items.len() {
+                    c.state.hit(Hit::Separator(address(list_data)));
+                }
+                items
+            }
+            None => c.many0(|c| self.parse_list_item(list_data, c))?,
+        };
+        Ok(Value::List(items))
+    }
+
+    fn parse_ast_rule<'i>(&self, rule: &AstRule<'d>, c: &mut Cur<'i>) -> PResult<Value<'i>> {
+        let value = match rule {
+            &AstRule::RefRule(rule_ref) => self.parse_key(rule_ref, c)?,
+            &AstRule::PartsRule(ref parts_rule) => {
+                let pos = c.pos;
+                let mut members = Vec::new();
+                self.traced(parts_rule.ast_type, c, |c| {
+                    self.parse_parts(&parts_rule.parts, c, &mut members)
+                })?;
+                Value::Node(Node {
+                    type_name: parts_rule.ast_type.to_string(),
+                    span: Span {
+                        start: ws_end(&c.input()[..c.pos], pos),
+                        end: c.pos,
+                    },
+                    members,
+                })
+            }
+        };
+        c.state.hit(Hit::Alternative(address(rule)));
+        Ok(value)
+    }
+
+    /// Parts in sequence, like do_parse!
+    fn parse_parts<'i>(
+        &self,
+        parts: &[AstRulePart<'d>],
+        c: &mut Cur<'i>,
+        members: &mut Vec<(String, Value<'i>)>,
+    ) -> PResult<()> {
+        for part in parts {
+            self.parse_part(part, c, members)?;
+        }
+        Ok(())
+    }
+
+    fn parse_part<'i>(
+        &self,
+        part: &AstRulePart<'d>,
+        c: &mut Cur<'i>,
+        members: &mut Vec<(String, Value<'i>)>,
+    ) -> PResult<()> {
+        if let &AstRuleToken::Group(ref parts) = &part.token {
+            if part.not {
+                let text = c.until(|c| self.parse_parts(parts, c, &mut Vec::new()))?;
+                let value = self.text_value(c, text)?;
+                if let Some(member_key) = part.member_key {
+                    self.push_member(members, member_key, value);
+                }
+            } else if part.optional {
+                let mut group_members = Vec::new();
+                match c.opt(|c| self.parse_parts(parts, c, &mut group_members))? {
+                    Some(()) => {
+                        c.state.hit(Hit::Optional(address(part)));
+                        members.append(&mut group_members);
+                    }
+                    None => {
+                        for group_part in parts {
+                            if let Some(member_key) = group_part.member_key {
+                                members.push((self.data.sc(member_key).to_string(), Value::None));
+                            }
+                        }
+                    }
+                }
+            } else {
+                self.parse_parts(parts, c, members)?;
+            }
+            return Ok(());
+        }
+        if !part.optional && !part.not {
+            c.sp();
+        }
+        let pos = c.pos;
+        let value = if part.not {
+            let text = c.until(|c| {
+                if part.optional {
+                    self.parse_opt(part, c)
+                } else {
+                    self.parse_token(&part.token, c)
+                }
+            })?;
+            self.text_value(c, text)?
+        } else if part.optional {
+            self.parse_opt(part, c)?
+        } else {
+            self.parse_token(&part.token, c)?
+        };
+        if part.optional && !part.not && value != Value::None {
+            c.state.hit(Hit::Optional(address(part)));
+        }
+        let start = ws_end(c.input(), pos);
+        if !part.not && c.pos > start {
+            if let Some(scope) = self.part_scope(part) {
+                c.state.tokens.push(Token {
+                    span: Span { start, end: c.pos },
+                    scope: scope.to_string(),
+                });
+            }
+        }
+        if let Some(member_key) = part.member_key {
+            let value = self.member_value(part, value);
+            self.push_member(members, member_key, value);
+        }
+        Ok(())
+    }
+
+    /// opt!(do_parse!(sp >> res: X >> (res))),
+    /// None when not matched
+    fn parse_opt<'i>(&self, part: &AstRulePart<'d>, c: &mut Cur<'i>) -> PResult<Value<'i>> {
+        let value = c.opt(|c| {
+            c.sp();
+            self.parse_token(&part.token, c)
+        })?;
+        Ok(value.unwrap_or(Value::None))
+    }
+
+    /// Text of until (!) parts
+    fn text_value<'i>(&self, c: &mut Cur<'i>, text: &'i [u8]) -> PResult<Value<'i>> {
+        match str::from_utf8(text) {
+            Ok(s) => Ok(Value::Str(s)),
+            Err(_) => c.fail(),
+        }
+    }
+
+    /// Scope from syntax annotation, with
+    /// defaults like the generated syntax
+    fn part_scope(&self, part: &AstRulePart<'d>) -> Option<&'d str> {
+        let typed_part = match &part.token {
+            &AstRuleToken::Key(key) => self.data.typed_parts.get(key),
+            _ => None,
+        };
+        match typed_part {
+            // Rules give their own tokens
+            Some(&TypedPart::AstPart { .. }) | Some(&TypedPart::ListPart { .. }) => return None,
+            _ => {}
+        }
+        if let Some(name) = part.annots.get_quoted("syntax", "name") {
+            return Some(name);
+        }
+        match &part.token {
+            &AstRuleToken::Key(_) => match typed_part {
+                Some(&TypedPart::TagPart { .. }) => Some("keyword.other"),
+                Some(&TypedPart::IntPart { .. }) => Some("constant.numeric"),
+                Some(&TypedPart::IdentPart { .. }) => Some("variable.other"),
+                Some(&TypedPart::StringPart { .. }) | Some(&TypedPart::StrPart { .. }) => {
+                    Some("string.quoted")
+                }
+                _ => None,
+            },
+            &AstRuleToken::Tag(..) => Some("keyword.other"),
+            _ => None,
+        }
+    }
+
+    /// Value assigned to the member of the
+    /// generated struct
+    fn member_value<'i>(&self, part: &AstRulePart<'d>, value: Value<'i>) -> Value<'i> {
+        if part.not {
+            return value;
+        }
+        let is_flag = match &part.token {
+            &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                Some(&TypedPart::CharPart { .. }) | Some(&TypedPart::TagPart { .. }) => true,
+                _ => false,
+            },
+            &AstRuleToken::Tag(..) => true,
+            _ => false,
+        };
+        if is_flag {
+            if part.optional {
+                Value::Bool(value != Value::None)
+            } else {
+                Value::Bool(true)
+            }
+        } else {
+            value
+        }
+    }
+
+    fn push_member<'i>(&self, members: &mut Vec<(String, Value<'i>)>, member_key: &str, value: Value<'i>) {
+        members.push((self.data.sc(member_key).to_string(), value));
+    }
+
+    fn parse_token<'i>(&self, token: &AstRuleToken<'d>, c: &mut Cur<'i>) -> PResult<Value<'i>> {
+        match token {
+            &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                Some(typed_part) => self.parse_typed_part(typed_part, c),
+                None => Err(Fail::Error),
+            },
+            &AstRuleToken::Tag(tag) => self.traced(&format!("{:?}", tag), c, |c| self.parse_tag(tag, c)),
+            &AstRuleToken::Func(fnc, _) => self.traced(&format!("{}!", fnc), c, |c| {
+                // Functions are nom macros in the
+                // generated code, and can't be called
+                self.expect(c.pos, format!("{}! (not interpreted)", fnc));
+                Err(Fail::Error)
+            }),
+            &AstRuleToken::Group(ref parts) => {
+                self.parse_parts(parts, c, &mut Vec::new())?;
+                Ok(Value::None)
+            }
+        }
+    }
+
+    fn parse_typed_part<'i>(&self, typed_part: &TypedPart<'d>, c: &mut Cur<'i>) -> PResult<Value<'i>> {
+        if typed_part.is_token() {
+            self.traced(typed_part.trace_name(), c, |c| self.parse_typed_token(typed_part, c))
+        } else {
+            self.parse_typed_token(typed_part, c)
+        }
+    }
+
+    fn parse_typed_token<'i>(&self, typed_part: &TypedPart<'d>, c: &mut Cur<'i>) -> PResult<Value<'i>> {
+        use lang_data::typed_part::TypedPart::*;
+        match typed_part {
+            &AstPart { key } | &ListPart { key } => self.parse_key(key, c),
+            &CharPart { chr, .. } => {
+                self.expecting(c, &format!("'{}'", chr), |c| c.chr(chr))?;
+                Ok(Value::Bool(true))
+            }
+            &TagPart { tag, .. } => self.parse_tag(tag, c),
+            &IntPart { .. } => self.expecting(c, "int", |c| c.int()).map(Value::Int),
+            &IdentPart { .. } => self.expecting(c, "ident", |c| c.ident()).map(Value::Str),
+            &StringPart { .. } | &StrPart { .. } => {
+                self.expecting(c, "string", |c| c.string()).map(Value::Str)
+            }
+            &FnPart { fnc, .. } => {
+                self.expect(c.pos, format!("{} (not interpreted)", fnc));
+                Err(Fail::Error)
+            }
+            &WSPart => {
+                let text = c.ws()?;
+                self.text_value(c, text)
+            }
+        }
+    }
+
+    fn parse_tag<'i>(&self, tag: &str, c: &mut Cur<'i>) -> PResult<Value<'i>> {
+        self.expecting(c, &format!("{:?}", tag), |c| c.tag(tag))?;
+        Ok(Value::Bool(true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use descr_common::util::*;
+    use nom::IResult;
+    use descr_lang::gen::parsers;
+    use generator::{build_data, parse_grammar};
+
+    const GRAMMAR: &'static [u8] = b"
+        Source(items:items)
+        items:Item[] WS {
+            Say(\"say\" string),
+            Color(\"color\" LPAREN r:int COMMA g:int COMMA b:int RPAREN),
+            Comment(\"(*\" comment:!\"*)\" \"*)\")
+        }
+    ";
+
+    fn with_interpreter<F: Fn(&Interpreter)>(grammar: &[u8], f: F) {
+        let ast = parse_grammar(grammar).unwrap();
+        let mut data = LangData::new(false, "Test".to_string());
+        build_data(grammar, &ast, &mut data).unwrap();
+        f(&Interpreter::new(&data));
+    }
+
+    #[test]
+    fn parses_dynamic_tree() {
+        with_interpreter(GRAMMAR, |interpreter| {
+            let node = interpreter
+                .parse_complete(b"say \"hi\"\n(* note *) color(1, 2, 3)")
+                .unwrap();
+            let items = node.get("items").unwrap().as_list().unwrap();
+            assert_eq!(items.len(), 3);
+            let say = items[0].as_node().unwrap();
+            assert_eq!(say.type_name, "Say");
+            assert_eq!(say.members, vec![("string".to_string(), Value::Str("hi"))]);
+            let comment = items[1].as_node().unwrap();
+            assert_eq!(comment.get("comment"), Some(&Value::Str(" note ")));
+            let color = items[2].as_node().unwrap();
+            assert_eq!(color.span, Span { start: 20, end: 34 });
+            assert_eq!(color.get("g"), Some(&Value::Int(2)));
+        });
+    }
+
+    #[test]
+    fn gives_tokens_and_source() {
+        with_interpreter(GRAMMAR, |interpreter| {
+            let node = interpreter.parse_complete(b"say 'hi' color(1,2,3)").unwrap();
+            let scopes = interpreter
+                .tokens()
+                .into_iter()
+                .map(|token| (token.span.start, token.scope))
+                .collect::<Vec<_>>();
+            assert_eq!(scopes[..3].to_vec(), vec![
+                (0, "keyword.other".to_string()),
+                (4, "string.quoted".to_string()),
+                (9, "keyword.other".to_string()),
+            ]);
+            assert_eq!(scopes.len(), 6);
+            let data = interpreter.data;
+            assert_eq!(
+                DynToSource::new(data).to_source(&node),
+                "  say \"hi\"  color ( 1 , 2 , 3 )"
+            );
+        });
+    }
+
+    #[test]
+    fn reports_furthest_error() {
+        with_interpreter(GRAMMAR, |interpreter| {
+            let err = interpreter.parse_complete(b"say \"hi\"\ncolor(1, x").unwrap_err();
+            assert_eq!(err.kind, ParseErrorKind::Trailing);
+            assert_eq!((err.pos.line, err.pos.column), (2, 10));
+            assert_eq!(err.expected, vec!["int".to_string()]);
+        });
+    }
+
+    #[test]
+    fn records_trace() {
+        let ast = parse_grammar(GRAMMAR).unwrap();
+        let mut data = LangData::new(false, "Test".to_string());
+        build_data(GRAMMAR, &ast, &mut data).unwrap();
+        let interpreter = Interpreter::new(&data).with_trace();
+        let input = b"say 'hi' color(1, x";
+        assert!(interpreter.parse_complete(input).is_err());
+        let trace = interpreter.trace(input).unwrap();
+        let text = trace.to_text();
+        assert!(text.contains("\n    Say 0..8 done \"say 'hi'\"\n"));
+        assert!(text.contains("\n    Say 8 error \" color(1, x\"\n"));
+        assert!(text.contains("\n      COMMA 16..17 done \",\"\n      int 18 error \"x\"\n"));
+        assert_eq!(trace.furthest(), 18);
+        // Trace is for the last parse
+        assert!(interpreter.parse_complete(b"say 'hi'").is_ok());
+        assert!(!interpreter.trace(b"say 'hi'").unwrap().to_text().contains("Color"));
+        assert!(Interpreter::new(&data).trace(input).is_none());
+    }
+
+    /// Node written like the derived Debug of
+    /// the generated ast. Nodes of enum types are
+    /// in their item variant, optional members in
+    /// Some.
+    fn generated_debug(node: &Node, data: &LangData) -> String {
+        /// Enum of the member type, without
+        /// wrappers and lifetime
+        fn member_enum<'a>(type_name: &str, data: &'a LangData) -> Option<&'a str> {
+            let inner = type_name
+                .trim_start_matches("Option<")
+                .trim_start_matches("Vec<")
+                .trim_start_matches("Box<");
+            let inner = &inner[..inner.find(|c| c == '<' || c == '>').unwrap_or(inner.len())];
+            data.ast_enums.get(inner).map(|ast_enum| ast_enum.name)
+        }
+        /// Item of the enum down to the node type,
+        /// through enums that are items
+        fn in_enum(enum_name: &str, node: &Node, data: &LangData) -> Option<String> {
+            let ast_enum = data.ast_enums.get(enum_name)?;
+            for item in &ast_enum.items {
+                if *item == node.type_name {
+                    return Some(if data.simple_enums.contains(enum_name) {
+                        node.type_name.clone()
+                    } else {
+                        format!("{}Item({})", item, generated_debug(node, data))
+                    });
+                }
+                if let Some(inner) = in_enum(item, node, data) {
+                    return Some(format!("{}Item({})", item, inner));
+                }
+            }
+            None
+        }
+        fn value_debug(value: &Value, enum_name: Option<&str>, data: &LangData) -> String {
+            match value {
+                &Value::Node(ref node) => enum_name
+                    .and_then(|enum_name| in_enum(enum_name, node, data))
+                    .unwrap_or_else(|| generated_debug(node, data)),
+                &Value::List(ref items) => format!(
+                    "[{}]",
+                    items.iter().map(|item| value_debug(item, enum_name, data)).collect::<Vec<_>>().join(", ")
+                ),
+                &Value::Str(s) => format!("{:?}", s),
+                &Value::Int(i) => format!("{:?}", i),
+                &Value::Bool(b) => format!("{:?}", b),
+                &Value::None => "None".to_string(),
+            }
+        }
+        let ast_struct = match data.ast_structs.get(node.type_name.as_str()) {
+            Some(ast_struct) => ast_struct,
+            None => return node.type_name.clone(),
+        };
+        if ast_struct.members.is_empty() {
+            return node.type_name.clone();
+        }
+        // Fields are in key order
+        let members = ast_struct
+            .members
+            .sorted_iter()
+            .map(|(_, member)| {
+                let type_name = member.tpe.add_type(String::new(), data);
+                let value = match node.get(&member.sc()) {
+                    Some(&Value::None) | None => "None".to_string(),
+                    Some(value) => {
+                        let debug = value_debug(value, member_enum(&type_name, data), data);
+                        if member.tpe.is_option(member, data) {
+                            format!("Some({})", debug)
+                        } else {
+                            debug
+                        }
+                    }
+                };
+                format!("{}: {}", member.sc(), value)
+            })
+            .collect::<Vec<_>>();
+        format!("{} {{ {} }}", node.type_name, members.join(", "))
+    }
+
+    /// Descr grammar parsed by the interpreter
+    /// and by the generated descr parser
+    #[test]
+    fn matches_generated_parser() {
+        let grammar = include_bytes!("../../../descr.lang");
+        with_interpreter(grammar, |interpreter| {
+            let node = interpreter.parse_complete(grammar).unwrap();
+            let generated = match parsers::start(grammar) {
+                IResult::Done(_, source) => source,
+                _ => panic!("Generated parser failed"),
+            };
+            let debug = generated_debug(&node, interpreter.data);
+            assert_eq!(debug, format!("{:?}", generated));
+        });
+    }
+}
@@ -0,0 +1,330 @@
+use lang_data::data::*;
+use lang_data::rule::*;
+use lang_data::typed_part::TypedPart;
+use interpreter::tree::{Node, Value};
+use descr_common::util::*;
+
+/// Source from an interpreted tree, the
+/// same as the generated ToSource gives
+/// for the generated ast. Parts of a rule
+/// are separated by a space, except around
+/// not parts.
+pub struct DynToSource<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+}
+impl<'a, 'd> DynToSource<'a, 'd> {
+    pub fn new(data: &'a LangData<'d>) -> DynToSource<'a, 'd> {
+        DynToSource { data }
+    }
+
+    pub fn to_source(&self, node: &Node) -> String {
+        self.add_node(String::new(), node)
+    }
+
+    /// Rule for the node, the first matching
+    /// its members as in the generated ToSource.
+    /// Rules an earlier rule reads instead are
+    /// left out.
+    fn find_rule(&self, node: &Node) -> Option<&'a AstPartsRule<'d>> {
+        let ast_rules = self.data
+            .ast_data
+            .sorted_iter()
+            .flat_map(|(_, ast_data)| ast_data.rules.iter());
+        let list_rules = self.data
+            .list_data
+            .sorted_iter()
+            .flat_map(|(_, list_data)| list_data.rules.iter().map(|rule| &rule.ast_rule));
+        let all_rules = ast_rules
+            .chain(list_rules)
+            .filter_map(|rule| match rule {
+                &AstRule::PartsRule(ref parts_rule) if parts_rule.ast_type == node.type_name => Some(parts_rule),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let rules = all_rules
+            .iter()
+            .enumerate()
+            .filter(|&(i, rule)| !all_rules[..i].iter().any(|earlier| rule.is_read_by(earlier, self.data)))
+            .map(|(_, rule)| *rule)
+            .collect::<Vec<_>>();
+        let len = rules.len();
+        rules
+            .iter()
+            .enumerate()
+            .find(|&(i, rule)| i == len - 1 || self.fits(node, rule))
+            .map(|(_, rule)| *rule)
+    }
+
+    /// Members the rule doesn't have are empty,
+    /// and optional members it requires are set.
+    /// The first item of a list after a leading
+    /// list has its part of that list empty.
+    fn fits(&self, node: &Node, rule: &AstPartsRule) -> bool {
+        fn is_empty(value: Option<&Value>) -> bool {
+            match value {
+                None | Some(&Value::None) | Some(&Value::Bool(false)) => true,
+                Some(&Value::List(ref items)) => items.is_empty(),
+                _ => false,
+            }
+        }
+        let (present, required) = rule.members();
+        let members_fit = match self.data.ast_structs.get(rule.ast_type) {
+            Some(ast_struct) => ast_struct.members_ordered.iter().all(|member_name| {
+                let value = node.get(self.data.sc(member_name));
+                if !present.contains(member_name) {
+                    is_empty(value)
+                } else if required.contains(member_name) {
+                    value.and_then(Value::as_list).is_some() || !is_empty(value)
+                } else {
+                    true
+                }
+            }),
+            None => true,
+        };
+        members_fit && match rule.leading_list(self.data) {
+            Some(leading) => {
+                let first = node
+                    .get(self.data.sc(leading.member))
+                    .and_then(Value::as_list)
+                    .and_then(|items| items.first())
+                    .and_then(Value::as_node);
+                match first {
+                    Some(item) => leading
+                        .items
+                        .iter()
+                        .filter(|&&(item_type, _)| item_type == item.type_name)
+                        .all(|&(_, member)| is_empty(item.get(self.data.sc(member)))),
+                    None => true,
+                }
+            }
+            None => true,
+        }
+    }
+
+    fn add_node(&self, mut s: String, node: &Node) -> String {
+        match self.find_rule(node) {
+            Some(rule) => {
+                for (i, part) in rule.parts.iter().enumerate() {
+                    if space_before(&rule.parts, i) {
+                        s += " ";
+                    }
+                    s = self.add_part(s, part, node, false);
+                }
+                s
+            }
+            // List as start rule
+            None => match node.members.first() {
+                Some(&(_, ref value)) => self.add_value(s, value, None),
+                None => s,
+            },
+        }
+    }
+
+    fn member<'n, 'i>(&self, node: &'n Node<'i>, part: &AstRulePart) -> Option<&'n Value<'i>> {
+        match part.member_key {
+            Some(member_key) => match node.get(self.data.sc(member_key)) {
+                Some(&Value::None) | None => None,
+                Some(value) => Some(value),
+            },
+            None => None,
+        }
+    }
+
+    fn add_part(&self, mut s: String, part: &AstRulePart, node: &Node, parent_opt: bool) -> String {
+        if part.not {
+            if let Some(&Value::Str(not_part)) = self.member(node, part) {
+                s += not_part;
+            }
+            return s;
+        }
+        let has_member = part.member_key.is_some();
+        match &part.token {
+            &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                Some(&TypedPart::AstPart { key }) | Some(&TypedPart::ListPart { key }) => {
+                    if let Some(value) = self.member(node, part) {
+                        let sep = self.data
+                            .list_data
+                            .get(key)
+                            .and_then(|list_data| list_data.sep)
+                            .and_then(|sep| self.data.typed_parts.get(sep));
+                        s = self.add_value(s, value, sep);
+                    }
+                }
+                Some(&TypedPart::IntPart { .. }) => {
+                    if let Some(&Value::Int(val)) = self.member(node, part) {
+                        s += &val.to_string();
+                    }
+                }
+                Some(&TypedPart::StringPart { .. }) | Some(&TypedPart::StrPart { .. }) => {
+                    if let Some(&Value::Str(val)) = self.member(node, part) {
+                        s += "\"";
+                        s += val;
+                        s += "\"";
+                    }
+                }
+                Some(&TypedPart::IdentPart { .. }) | Some(&TypedPart::FnPart { .. }) => {
+                    if let Some(&Value::Str(val)) = self.member(node, part) {
+                        s += val;
+                    }
+                }
+                Some(typed_part) => {
+                    if !has_member || self.member(node, part) == Some(&Value::Bool(true)) {
+                        s = self.add_sep(s, typed_part);
+                    }
+                }
+                None => {}
+            },
+            &AstRuleToken::Tag(tag) => {
+                if part.optional || parent_opt {
+                    if self.member(node, part) == Some(&Value::Bool(true)) {
+                        s += tag;
+                    } else if !part.optional && !has_member {
+                        // In an optional group that is set
+                        s += tag;
+                    }
+                } else {
+                    s += tag;
+                }
+            }
+            &AstRuleToken::Func(..) => {}
+            &AstRuleToken::Group(ref parts) => {
+                if part.optional {
+                    let is_some = parts
+                        .iter()
+                        .any(|part| part.member_key.is_some() && self.member(node, part).is_some());
+                    if is_some {
+                        s = self.add_group(s, parts, node, true);
+                    }
+                } else {
+                    s = self.add_group(s, parts, node, parent_opt);
+                }
+            }
+        }
+        s
+    }
+
+    fn add_group(&self, mut s: String, parts: &[AstRulePart], node: &Node, parent_opt: bool) -> String {
+        for (i, part) in parts.iter().enumerate() {
+            if i > 0 && space_before(parts, i) {
+                s += " ";
+            }
+            s = self.add_part(s, part, node, parent_opt);
+        }
+        s
+    }
+
+    fn add_value(&self, mut s: String, value: &Value, sep: Option<&TypedPart>) -> String {
+        match value {
+            &Value::Node(ref node) => self.add_node(s, node),
+            &Value::List(ref items) => {
+                let len = items.len();
+                for (i, item) in items.iter().enumerate() {
+                    s = self.add_value(s, item, None);
+                    if let Some(sep) = sep {
+                        if i < len - 1 {
+                            // Parsers don't skip whitespace
+                            // before separators
+                            let end = s.trim_end().len();
+                            s.truncate(end);
+                            s = self.add_sep(s, sep);
+                        }
+                    }
+                }
+                s
+            }
+            _ => s,
+        }
+    }
+
+    /// Tokens without value
+    fn add_sep(&self, mut s: String, typed_part: &TypedPart) -> String {
+        match typed_part {
+            &TypedPart::CharPart { chr, .. } => s.push(chr),
+            &TypedPart::TagPart { tag, .. } => s += tag,
+            &TypedPart::WSPart => s += " ",
+            _ => {}
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use interpreter::tree::Span;
+    use interpreter::Interpreter;
+    use ir;
+    use process::test_util::*;
+
+    const GRAMMAR: &str = "Source(items:items)
+        items:Item[] WS {
+            Say(\"say\" string (\"to\" who:ident)?),
+            Color(\"color\" LPAREN ints RPAREN),
+            Flag(\"flag\" \"on\"?),
+            Comment(\"(*\" comment:!\"*)\" \"*)\")
+        }
+        ints[] COMMA Num
+        Num(int)";
+
+    /// Node with empty spans, which differ in
+    /// the printed source, and members by name,
+    /// as rules of a type can order them apart
+    fn without_spans<'i>(node: &Node<'i>) -> Node<'i> {
+        fn value_without_spans<'i>(value: &Value<'i>) -> Value<'i> {
+            match value {
+                &Value::Node(ref node) => Value::Node(without_spans(node)),
+                &Value::List(ref items) => Value::List(items.iter().map(value_without_spans).collect()),
+                other => other.clone(),
+            }
+        }
+        let mut members = node
+            .members
+            .iter()
+            .map(|&(ref name, ref member)| (name.clone(), value_without_spans(member)))
+            .collect::<Vec<_>>();
+        members.sort_by(|a, b| a.0.cmp(&b.0));
+        Node {
+            type_name: node.type_name.clone(),
+            span: Span { start: 0, end: 0 },
+            members,
+        }
+    }
+
+    /// Panics unless the source of input
+    /// parses to the same tree
+    fn assert_round_trip(grammar: &str, input: &str) -> String {
+        with_data(grammar, |data| {
+            let interpreter = Interpreter::new(data);
+            let node = interpreter.parse_complete(input.as_bytes()).unwrap();
+            let source = DynToSource::new(data).to_source(&node);
+            match interpreter.parse_complete(source.as_bytes()) {
+                Ok(reparsed) => assert_eq!(without_spans(&reparsed), without_spans(&node), "{}", source),
+                Err(err) => panic!("{}\n{}", err, source),
+            }
+            source
+        })
+    }
+
+    #[test]
+    fn prints_parts_of_rules() {
+        let source = assert_round_trip(GRAMMAR, "say 'hi' to bob color(1,2) (* a *)");
+        // Not parts keep their text, separators
+        // follow list items
+        assert_eq!(source, "  say \"hi\" to bob  color (  1, 2 )  (* a *)");
+        // Optional groups are left out, like
+        // optional tags without a member
+        assert_eq!(assert_round_trip(GRAMMAR, "flag on say 'x' (* *)"), "  flag  say \"x\"  (* *)");
+    }
+
+    #[test]
+    fn repo_grammars_round_trip() {
+        // The reprint of descr.lang is the same grammar
+        let descr = repo_grammar("descr.lang");
+        let source = assert_round_trip(&descr, &descr);
+        assert_eq!(with_data(&source, ir::to_json), with_data(&descr, ir::to_json));
+        assert_round_trip(
+            &repo_grammar("langs/json-descr/json.lang"),
+            "{\"a\": [1, \"b\", {\"c\": []}], \"d\": {}}",
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+
+/// Byte range in the input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Node parsed from a parts rule.
+/// The type name is the struct name the
+/// generated ast would use, and members
+/// are in rule order with snake cased names.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node<'i> {
+    pub type_name: String,
+    pub span: Span,
+    pub members: Vec<(String, Value<'i>)>,
+}
+impl<'i> Node<'i> {
+    pub fn get(&self, name: &str) -> Option<&Value<'i>> {
+        self.members
+            .iter()
+            .find(|&&(ref member_name, _)| member_name == name)
+            .map(|&(_, ref value)| value)
+    }
+
+    /// Child nodes, including nodes in lists
+    pub fn children(&self) -> Vec<&Node<'i>> {
+        let mut children = Vec::new();
+        for &(_, ref value) in &self.members {
+            value.collect_nodes(&mut children);
+        }
+        children
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        write!(f, "{} [{}..{}]", self.type_name, self.span.start, self.span.end)?;
+        for &(ref name, ref value) in &self.members {
+            writeln!(f)?;
+            write!(f, "{:1$}{2}: ", "", (indent + 1) * 2, name)?;
+            value.fmt_indented(f, indent + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Value of a member, or the result
+/// of a rule. Optional members that
+/// did not match are `None`, otherwise
+/// the value is given directly.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<'i> {
+    Node(Node<'i>),
+    List(Vec<Value<'i>>),
+    Str(&'i str),
+    Int(u32),
+    Bool(bool),
+    None,
+}
+impl<'i> Value<'i> {
+    pub fn as_node(&self) -> Option<&Node<'i>> {
+        match self {
+            &Value::Node(ref node) => Some(node),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&Vec<Value<'i>>> {
+        match self {
+            &Value::List(ref list) => Some(list),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'i str> {
+        match self {
+            &Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn collect_nodes<'v>(&'v self, nodes: &mut Vec<&'v Node<'i>>) {
+        match self {
+            &Value::Node(ref node) => nodes.push(node),
+            &Value::List(ref list) => {
+                for item in list {
+                    item.collect_nodes(nodes);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        match self {
+            &Value::Node(ref node) => node.fmt_indented(f, indent),
+            &Value::List(ref list) => {
+                if list.is_empty() {
+                    return write!(f, "[]");
+                }
+                writeln!(f, "[")?;
+                for item in list {
+                    write!(f, "{:1$}", "", (indent + 1) * 2)?;
+                    item.fmt_indented(f, indent + 1)?;
+                    writeln!(f)?;
+                }
+                write!(f, "{:1$}]", "", indent * 2)
+            }
+            &Value::Str(s) => write!(f, "{:?}", s),
+            &Value::Int(i) => write!(f, "{}", i),
+            &Value::Bool(b) => write!(f, "{}", b),
+            &Value::None => write!(f, "None"),
+        }
+    }
+}
+
+/// Indented tree, one member per line
+impl<'i> fmt::Display for Node<'i> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+impl<'i> fmt::Display for Value<'i> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node<'i>(type_name: &str, start: usize, end: usize, members: Vec<(&str, Value<'i>)>) -> Node<'i> {
+        Node {
+            type_name: type_name.to_string(),
+            span: Span { start, end },
+            members: members
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        }
+    }
+
+    fn tree() -> Node<'static> {
+        node("Source", 0, 14, vec![
+            ("items", Value::List(vec![
+                Value::Node(node("Say", 0, 8, vec![("string", Value::Str("hi"))])),
+                Value::List(vec![Value::Node(node("Num", 9, 10, vec![("int", Value::Int(1))]))]),
+                Value::Str("text"),
+            ])),
+            ("empty", Value::List(Vec::new())),
+            ("name", Value::None),
+            ("last", Value::Node(node("Num", 12, 14, vec![("int", Value::Int(23))]))),
+            ("flag", Value::Bool(true)),
+        ])
+    }
+
+    #[test]
+    fn gets_members_and_children() {
+        let tree = tree();
+        assert_eq!(tree.get("name"), Some(&Value::None));
+        assert_eq!(tree.get("flag"), Some(&Value::Bool(true)));
+        assert_eq!(tree.get("missing"), None);
+        // Nodes in nested lists, in member order
+        let children = tree
+            .children()
+            .iter()
+            .map(|child| (child.type_name.as_str(), child.span.start))
+            .collect::<Vec<_>>();
+        assert_eq!(children, [("Say", 0), ("Num", 9), ("Num", 12)]);
+        assert!(tree.children()[0].children().is_empty());
+    }
+
+    #[test]
+    fn accesses_values() {
+        let tree = tree();
+        let items = tree.get("items").unwrap().as_list().unwrap();
+        assert_eq!(items[0].as_node().unwrap().get("string").unwrap().as_str(), Some("hi"));
+        assert_eq!(items[2].as_str(), Some("text"));
+        assert_eq!(items[2].as_node(), None);
+        assert_eq!(items[0].as_list(), None);
+        assert_eq!(Value::Int(1).as_str(), None);
+    }
+
+    #[test]
+    fn displays_indented_tree() {
+        assert_eq!(
+            tree().to_string(),
+            "Source [0..14]\n  \
+             items: [\n    \
+             Say [0..8]\n      string: \"hi\"\n    \
+             [\n      Num [9..10]\n        int: 1\n    ]\n    \
+             \"text\"\n  ]\n  \
+             empty: []\n  \
+             name: None\n  \
+             last: Num [12..14]\n    int: 23\n  \
+             flag: true"
+        );
+        assert_eq!(Value::Str("a\"b").to_string(), "\"a\\\"b\"");
+    }
+}
@@ -0,0 +1,314 @@
+//! Grammar IR, the analysed grammar as JSON for
+//! other tools. Gives the rules with their parts,
+//! the typed tokens, and the resolved structs and
+//! enums with member types, optionality, boxing
+//! and annotations. Maps are in key order and
+//! rules, parts and members in grammar order.
+//! The version is raised when the shape changes.
+use lang_data::annotations::{AnArgVal, AnnotList};
+use lang_data::ast::{AstMemberType, AstStructMember, RuleType};
+use lang_data::data::LangData;
+use lang_data::rule::{AstRule, AstRulePart, AstRuleToken, RuleFuncArg};
+use lang_data::typed_part::TypedPart;
+use descr_common::json::{json_str, Json};
+use descr_common::util::SortedHashMap;
+
+pub const IR_VERSION: u32 = 1;
+
+/// Objects and arrays over lines, unless
+/// they only hold scalars
+fn write(json: &Json, mut s: String, indent: usize) -> String {
+    match *json {
+        Json::Array(ref items) if !items.iter().all(is_scalar) => {
+            s += "[\n";
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    s += ",\n";
+                }
+                indent!(s indent + 1);
+                s = write(item, s, indent + 1);
+            }
+            s += "\n";
+            indent!(s indent);
+            s += "]";
+        }
+        Json::Array(ref items) if !items.is_empty() => {
+            s += "[";
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    s += ", ";
+                }
+                s = write(item, s, indent);
+            }
+            s += "]";
+        }
+        Json::Object(ref fields) if !fields.iter().all(|&(_, ref value)| is_scalar(value)) => {
+            s += "{\n";
+            for (i, &(ref key, ref value)) in fields.iter().enumerate() {
+                if i > 0 {
+                    s += ",\n";
+                }
+                indent!(s indent + 1);
+                s += &json_str(key);
+                s += ": ";
+                s = write(value, s, indent + 1);
+            }
+            s += "\n";
+            indent!(s indent);
+            s += "}";
+        }
+        Json::Object(ref fields) => {
+            s += "{ ";
+            for (i, &(ref key, ref value)) in fields.iter().enumerate() {
+                if i > 0 {
+                    s += ", ";
+                }
+                s += &json_str(key);
+                s += ": ";
+                s = write(value, s, indent);
+            }
+            s += " }";
+        }
+        ref scalar => s += &scalar.to_string(),
+    }
+    s
+}
+
+fn is_scalar(json: &Json) -> bool {
+    match *json {
+        Json::Array(ref items) => items.is_empty(),
+        Json::Object(ref fields) => fields.is_empty(),
+        _ => true,
+    }
+}
+
+fn opt(text: Option<&str>) -> Json {
+    text.map(Json::from).unwrap_or(Json::Null)
+}
+
+/// The IR of data as JSON
+pub fn to_json(data: &LangData) -> String {
+    let ir = Json::object(vec![
+        ("version", Json::from(IR_VERSION as usize)),
+        ("name", Json::from(data.name.as_str())),
+        ("start", opt(data.start_key)),
+        ("ast", Json::Array(data.ast_data.sorted_iter().map(|(_, ast_data)| {
+            Json::object(vec![
+                ("key", Json::from(ast_data.key)),
+                ("type", Json::from(ast_data.ast_type)),
+                ("rule_type", rule_type(data, ast_data.key)),
+                ("rules", Json::Array(ast_data.rules.iter().map(rule).collect())),
+                ("annotations", annots(&ast_data.annots)),
+            ])
+        }).collect())),
+        ("lists", Json::Array(data.list_data.sorted_iter().map(|(_, list_data)| {
+            Json::object(vec![
+                ("key", Json::from(list_data.key)),
+                ("type", opt(list_data.ast_type)),
+                ("sep", opt(list_data.sep)),
+                ("rule_type", rule_type(data, list_data.key)),
+                ("rules", Json::Array(list_data.rules.iter().map(|list_rule| rule(&list_rule.ast_rule)).collect())),
+                ("annotations", annots(&list_data.annots)),
+            ])
+        }).collect())),
+        ("tokens", Json::Array(data.typed_parts.sorted_iter().map(|(key, part)| {
+            let mut fields = vec![("key", Json::from(*key))];
+            fields.extend(typed_part(part));
+            Json::object(fields)
+        }).collect())),
+        ("structs", Json::Array(data.ast_structs.sorted_iter().map(|(_, ast_struct)| {
+            Json::object(vec![
+                ("name", Json::from(ast_struct.name)),
+                ("members", Json::Array(ast_struct.members_ordered.iter().map(|name| {
+                    member(data, &ast_struct.members[name])
+                }).collect())),
+            ])
+        }).collect())),
+        ("enums", Json::Array(data.ast_enums.sorted_iter().map(|(key, ast_enum)| {
+            Json::object(vec![
+                ("name", Json::from(ast_enum.name)),
+                // Simple enums have unit items
+                ("simple", Json::Bool(data.simple_enums.contains(key))),
+                ("items", Json::Array(ast_enum.items.iter().map(|item| {
+                    Json::object(vec![
+                        ("name", Json::from(*item)),
+                        ("boxed", Json::Bool(ast_enum.boxed_items.contains(item))),
+                    ])
+                }).collect())),
+            ])
+        }).collect())),
+    ]);
+    let mut s = write(&ir, String::new(), 0);
+    s.push('\n');
+    s
+}
+
+/// Type of a rule key, single for a struct
+/// or a ref, many for an enum
+fn rule_type(data: &LangData, key: &str) -> Json {
+    match data.rule_types.get(key) {
+        Some(&RuleType::SingleType(name)) => Json::object(vec![
+            ("kind", Json::from("single")),
+            ("name", Json::from(name)),
+        ]),
+        Some(&RuleType::ManyType(name)) => Json::object(vec![
+            ("kind", Json::from("many")),
+            ("name", Json::from(name)),
+        ]),
+        None => Json::Null,
+    }
+}
+
+fn rule(rule: &AstRule) -> Json {
+    match *rule {
+        AstRule::PartsRule(ref parts_rule) => Json::object(vec![
+            ("kind", Json::from("parts")),
+            ("type", Json::from(parts_rule.ast_type)),
+            ("parts", Json::Array(parts_rule.parts.iter().map(part).collect())),
+            ("annotations", annots(&parts_rule.annots)),
+        ]),
+        AstRule::RefRule(key) => Json::object(vec![
+            ("kind", Json::from("ref")),
+            ("key", Json::from(key)),
+        ]),
+    }
+}
+
+fn part(part: &AstRulePart) -> Json {
+    let token = match part.token {
+        AstRuleToken::Key(key) => Json::object(vec![
+            ("kind", Json::from("key")),
+            ("key", Json::from(key)),
+        ]),
+        AstRuleToken::Tag(tag) => Json::object(vec![
+            ("kind", Json::from("tag")),
+            ("tag", Json::from(tag)),
+        ]),
+        AstRuleToken::Func(name, ref args) => Json::object(vec![
+            ("kind", Json::from("func")),
+            ("name", Json::from(name)),
+            ("args", Json::Array(args.iter().map(|arg| match *arg {
+                RuleFuncArg::Quoted(text) => Json::from(text),
+            }).collect())),
+        ]),
+        AstRuleToken::Group(ref parts) => Json::object(vec![
+            ("kind", Json::from("group")),
+            ("parts", Json::Array(parts.iter().map(self::part).collect())),
+        ]),
+    };
+    Json::object(vec![
+        ("member", opt(part.member_key)),
+        ("optional", Json::Bool(part.optional)),
+        ("not", Json::Bool(part.not)),
+        ("token", token),
+        ("annotations", annots(&part.annots)),
+    ])
+}
+
+/// Kind of a typed part, with its value
+fn typed_part(part: &TypedPart) -> Vec<(&'static str, Json)> {
+    match *part {
+        TypedPart::AstPart { .. } => vec![("kind", Json::from("ast"))],
+        TypedPart::ListPart { .. } => vec![("kind", Json::from("list"))],
+        TypedPart::CharPart { chr, .. } => vec![
+            ("kind", Json::from("char")),
+            ("char", Json::String(chr.to_string())),
+        ],
+        TypedPart::TagPart { tag, .. } => vec![
+            ("kind", Json::from("tag")),
+            ("tag", Json::from(tag)),
+        ],
+        TypedPart::IntPart { .. } => vec![("kind", Json::from("int"))],
+        TypedPart::IdentPart { .. } => vec![("kind", Json::from("ident"))],
+        TypedPart::FnPart { fnc, tpe, .. } => vec![
+            ("kind", Json::from("fn")),
+            ("fn", Json::from(fnc)),
+            ("type", Json::from(tpe)),
+        ],
+        TypedPart::StringPart { .. } => vec![("kind", Json::from("string"))],
+        TypedPart::StrPart { .. } => vec![("kind", Json::from("str"))],
+        TypedPart::WSPart => vec![("kind", Json::from("ws"))],
+    }
+}
+
+/// Member with its type, where ast and list
+/// types give the resolved struct or enum
+fn member(data: &LangData, member: &AstStructMember) -> Json {
+    let mut tpe = match member.tpe {
+        AstMemberType::KeyedToken(key) => match data.typed_parts.get(key) {
+            Some(part) => {
+                let mut fields = typed_part(part);
+                if let Some(rule_type) = data.rule_types.get(key) {
+                    fields.push(("name", Json::from(rule_type.get_type_name(data))));
+                }
+                fields
+            }
+            None => vec![("kind", Json::Null)],
+        },
+        AstMemberType::TagBool(_) => vec![("kind", Json::from("bool"))],
+        AstMemberType::NotString => vec![("kind", Json::from("not"))],
+    };
+    tpe.push(("rust", Json::String(member.tpe.add_type(String::new(), data))));
+    Json::object(vec![
+        ("name", Json::from(member.name)),
+        ("key", Json::from(member.part_key)),
+        ("type", Json::object(tpe)),
+        ("optional", Json::Bool(member.tpe.is_option(member, data))),
+        ("boxed", Json::Bool(member.boxed)),
+    ])
+}
+
+/// Annotations in key order, with
+/// arguments of their kind
+fn annots(annots: &AnnotList) -> Json {
+    Json::Array(annots.items.sorted_iter().map(|(_, annot)| {
+        Json::object(vec![
+            ("name", Json::from(annot.ident)),
+            ("args", Json::Array(annot.args.sorted_iter().map(|(key, val)| {
+                let (kind, value) = match *val {
+                    AnArgVal::Quoted(text) => ("quoted", Json::from(text)),
+                    AnArgVal::Ident(ident) => ("ident", Json::from(ident)),
+                    AnArgVal::IntConst(int) => ("int", Json::from(int as usize)),
+                };
+                Json::object(vec![
+                    ("key", Json::from(*key)),
+                    ("kind", Json::from(kind)),
+                    ("value", value),
+                ])
+            }).collect())),
+        ])
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generator::{build_data, parse_grammar};
+
+    #[test]
+    fn exports_rules_and_types() {
+        let grammar = b"Source(items)
+            items:Item[] WS {
+                @syntax(name=\"keyword\")
+                Say(\"say\" string),
+                Group(LPAREN items RPAREN),
+                Num(int label:ident?)
+            }";
+        let ast = parse_grammar(grammar).unwrap();
+        let mut data = LangData::new(false, "Test".to_string());
+        build_data(grammar, &ast, &mut data).unwrap();
+        let json = to_json(&data);
+        assert!(json.starts_with("{\n    \"version\": 1,\n    \"name\": \"Test\",\n    \"start\": \"Source\",\n"));
+        assert!(json.contains("{ \"key\": \"LPAREN\", \"kind\": \"char\", \"char\": \"(\" }"));
+        assert!(json.contains("\"type\": { \"kind\": \"list\", \"name\": \"Item\", \"rust\": \"Vec<Item<'a>>\" }"));
+        assert!(json.contains(
+            "\"name\": \"label\",
+                    \"key\": \"ident\",
+                    \"type\": { \"kind\": \"ident\", \"rust\": \"&'a str\" },
+                    \"optional\": true,"
+        ));
+        assert!(json.contains("{ \"key\": \"name\", \"kind\": \"quoted\", \"value\": \"keyword\" }"));
+        assert!(json.contains("\"rule_type\": { \"kind\": \"many\", \"name\": \"Item\" }"));
+        assert_eq!(json, to_json(&data));
+    }
+}
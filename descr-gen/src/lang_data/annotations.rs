@@ -5,6 +5,31 @@ use descr_lang::gen::ast::*;
 pub struct AnnotList<'a> {
     pub items: HashMap<&'a str, Annot<'a>>
 }
+impl<'a> AnnotList<'a> {
+    /// Quoted argument of annotation, like
+    /// name in @syntax(name="keyword")
+    pub fn get_quoted(&self, ident: &str, arg: &str) -> Option<&'a str> {
+        match self.items.get(ident) {
+            Some(annot) => match annot.args.get(arg) {
+                Some(&AnArgVal::Quoted(val)) => Some(val),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
+    /// Ident or quoted argument, like
+    /// kind in @symbol(kind=function)
+    pub fn get_ident(&self, ident: &str, arg: &str) -> Option<&'a str> {
+        match self.items.get(ident) {
+            Some(annot) => match annot.args.get(arg) {
+                Some(&AnArgVal::Ident(val)) | Some(&AnArgVal::Quoted(val)) => Some(val),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+}
 #[derive(Debug)]
 pub struct Annot<'a> {
     pub ident: &'a str,
@@ -1,5 +1,7 @@
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use descr_common::util::SortedHashMap;
 use lang_data::ast::*;
 use lang_data::rule::*;
 use lang_data::typed_part::*;
@@ -82,14 +84,31 @@ impl<'a> SnakeCased<'a> {
         self.cache.get(key).unwrap().clone()
     }
 
-    pub fn get_str(&self, key: &str) -> &str {
+    /// Snake cased key, keys of the grammar are
+    /// registered when their parts are resolved
+    pub fn get_str<'k>(&'k self, key: &'k str) -> &'k str {
         match self.cache.get(key) {
             Some(ref s) => s.as_str(),
-            None => panic!("Could not find snake cased for: {}", key),
+            None => key,
         }
     }
 }
 
+/// Key of the grammar that can't be built,
+/// a slice of the source
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyError<'a> {
+    /// Key without an ast type, like a token,
+    /// or a list referring to itself
+    Unresolved(&'a str),
+    /// Enum with itself as an item
+    RecursiveEnum(&'a str),
+    /// Member given by several parts of a rule
+    DuplicateMember(&'a str),
+    /// Key of both an ast and a list rule
+    DuplicateRule(&'a str),
+}
+
 pub enum ResolvedType<'a> {
     ResolvedStruct(&'a str),
     ResolvedEnum(&'a str),
@@ -139,7 +158,9 @@ pub struct LangData<'a> {
     // possibly key to enum is needed
     // sometime.
     pub simple_structs: HashSet<&'a str>,
-    pub debug: bool,
+    /// Generated parsers record traces
+    /// with descr_common::trace
+    pub trace: bool,
     // Key: enum/struct name, Set: Parents - can be from
     // member to owning struct/enum, or from
     // struct/enum to another where it is a member
@@ -151,17 +172,17 @@ pub struct LangData<'a> {
 }
 #[derive(Debug)]
 pub struct ParentRefs<'a> {
-    pub refs: HashMap<&'a str, HashSet<ParentRef<'a>>>,
+    pub refs: HashMap<&'a str, BTreeSet<ParentRef<'a>>>,
 }
 impl<'a> ParentRefs<'a> {
     pub fn add_ref(&mut self, key: &'a str, parent_ref: ParentRef<'a>) {
         if !self.refs.contains_key(key) {
-            self.refs.insert(key, HashSet::new());
+            self.refs.insert(key, BTreeSet::new());
         }
         self.refs.get_mut(key).unwrap().insert(parent_ref);
     }
 }
-#[derive(PartialEq, Eq, Hash, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub enum ParentRef<'a> {
     StructMember {
         struct_name: &'a str,
@@ -174,7 +195,7 @@ pub enum ParentRef<'a> {
 }
 
 impl<'a> LangData<'a> {
-    pub fn new(debug: bool, name: String) -> LangData<'a> {
+    pub fn new(trace: bool, name: String) -> LangData<'a> {
         LangData {
             typed_parts: HashMap::new(),
             ast_data: HashMap::new(),
@@ -188,7 +209,7 @@ impl<'a> LangData<'a> {
             start_key: None,
             simple_enums: HashSet::new(),
             simple_structs: HashSet::new(),
-            debug,
+            trace,
             parent_refs: ParentRefs {
                 refs: HashMap::new(),
             },
@@ -196,6 +217,38 @@ impl<'a> LangData<'a> {
         }
     }
 
+    /// Lowercase language id, like "json",
+    /// used for file names and scope names
+    pub fn lang_id(&self) -> String {
+        self.name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect()
+    }
+
+    /// Keys used by the parts, refs and
+    /// separator of a rule
+    pub fn rule_refs(&self, key: &str) -> Vec<&'a str> {
+        let mut refs = Vec::new();
+        if let Some(ast_data) = self.ast_data.get(key) {
+            for rule in &ast_data.rules {
+                rule_refs(rule, &mut refs);
+            }
+        } else if let Some(list_data) = self.list_data.get(key) {
+            refs.extend(list_data.sep);
+            for rule in &list_data.rules {
+                rule_refs(&rule.ast_rule, &mut refs);
+            }
+        }
+        refs
+    }
+
     // Gets ast key (struct/enum) from part_key
     pub fn get_ast_key(&self, key: &'a str) -> Option<&'a str> {
         if self.typed_parts.contains_key(key) {
@@ -212,40 +265,78 @@ impl<'a> LangData<'a> {
     /// Resolve key to enum or struct
     /// Key could be enum or struct key, or
     /// rule item key
-    pub fn resolve(&self, key: &'a str) -> ResolvedType<'a> {
+    pub fn try_resolve(&self, key: &'a str) -> Result<ResolvedType<'a>, KeyError<'a>> {
         if self.ast_enums.contains_key(key) {
-            ResolvedType::ResolvedEnum(key)
+            Ok(ResolvedType::ResolvedEnum(key))
         } else if self.ast_structs.contains_key(key) {
-            ResolvedType::ResolvedStruct(key)
+            Ok(ResolvedType::ResolvedStruct(key))
         } else {
             match self.rule_types.get(key) {
-                Some(rule_type) => match rule_type {
-                    &RuleType::SingleType(type_key) => {
-                        if key != type_key {
-                            self.resolve(type_key)
-                        } else {
-                            panic!("Could not find singletype key: {}", key);
-                        }
-                    }
-                    &RuleType::ManyType(type_key) => {
-                        if key != type_key {
-                            self.resolve(type_key)
-                        } else {
-                            panic!("Could not find manytype key: {}", key);
-                        }
-                    }
-                },
-                None => {
-                    println!("{:#?}", self.ast_enums.keys());
-                    println!("{:#?}", self.ast_structs.keys());
-                    println!("{:#?}", self.rule_types);
-                    panic!("Could not find key: {}", key);
+                Some(&RuleType::SingleType(type_key)) | Some(&RuleType::ManyType(type_key)) if key != type_key => {
+                    self.try_resolve(type_key)
                 }
+                _ => Err(KeyError::Unresolved(key)),
             }
         }
     }
 
-    pub fn sc(&self, key: &str) -> &str {
+    /// Resolve key checked by check_types
+    pub fn resolve(&self, key: &'a str) -> ResolvedType<'a> {
+        match self.try_resolve(key) {
+            Ok(resolved) => resolved,
+            Err(_) => unreachable!("Key is checked by check_types: {}", key),
+        }
+    }
+
+    /// Checks that rules, enum items and members
+    /// resolve to a type, and that enums don't
+    /// contain themselves. Runs when types are built,
+    /// before anything is resolved.
+    pub fn check_types(&self) -> Result<(), KeyError<'a>> {
+        for (_name, struct_data) in self.ast_structs.sorted_iter() {
+            for (_member_name, member) in struct_data.members.sorted_iter() {
+                if self.get_ast_key(member.part_key).is_some() {
+                    self.try_resolve(member.part_key)?;
+                }
+            }
+        }
+        for (name, enum_data) in self.ast_enums.sorted_iter() {
+            for item in &enum_data.items {
+                self.try_resolve(item)?;
+            }
+            if self.enum_contains(name, name, &mut HashSet::new()) {
+                return Err(KeyError::RecursiveEnum(name));
+            }
+        }
+        // Rules that are not referred to, like
+        // the start rule or an empty enum
+        for (key, _) in self.ast_data.sorted_iter() {
+            self.try_resolve(key)?;
+        }
+        for (key, _) in self.list_data.sorted_iter() {
+            if self.ast_data.contains_key(key) {
+                return Err(KeyError::DuplicateRule(key));
+            }
+            self.try_resolve(key)?;
+        }
+        Ok(())
+    }
+
+    /// Whether an enum has the key as item,
+    /// directly or through enum items
+    fn enum_contains(&self, name: &'a str, key: &str, visited: &mut HashSet<&'a str>) -> bool {
+        if !visited.insert(name) {
+            return false;
+        }
+        self.ast_enums[name].items.iter().any(|item| match self.try_resolve(item) {
+            Ok(ResolvedType::ResolvedEnum(item_enum)) => {
+                item_enum == key || self.enum_contains(item_enum, key, visited)
+            }
+            _ => false,
+        })
+    }
+
+    pub fn sc<'k>(&'k self, key: &'k str) -> &'k str {
         self.snake_cased.get_str(key)
     }
 
@@ -258,85 +349,95 @@ impl<'a> LangData<'a> {
         }
     }
 
-    fn add_tag_token(&mut self, key: &'a str, tag: &'a str) {
-        self.typed_parts
-            .insert(key, TypedPart::TagPart { key: key, tag: tag });
+    /// Resolve typed part assuming keys
+    /// are registered, or Err with the key
+    pub fn resolve_typed_part(&mut self, key: &'a str) -> Result<(), KeyError<'a>> {
+        let typed_part = if self.ast_data.contains_key(key) {
+            TypedPart::AstPart { key }
+        } else if self.list_data.contains_key(key) {
+            TypedPart::ListPart { key }
+        } else {
+            match Self::builtin_part(key) {
+                Some(typed_part) => typed_part,
+                None => return Err(KeyError::Unresolved(key)),
+            }
+        };
+        self.snake_cased.reg(key);
+        self.typed_parts.insert(key, typed_part);
+        Ok(())
+    }
+
+    /// Typed part of a key that was just
+    /// registered as ast or list
+    pub fn register_part(&mut self, key: &'a str) {
+        let typed_part = if self.list_data.contains_key(key) {
+            TypedPart::ListPart { key }
+        } else {
+            TypedPart::AstPart { key }
+        };
+        self.snake_cased.reg(key);
+        self.typed_parts.insert(key, typed_part);
     }
 
-    fn add_char_token(&mut self, key: &'a str, chr: char) {
-        self.typed_parts
-            .insert(key, TypedPart::CharPart { key, chr });
+    /// Whether key resolves to a registered
+    /// rule or a builtin token
+    pub fn is_known_token(&self, key: &str) -> bool {
+        self.ast_data.contains_key(key)
+            || self.list_data.contains_key(key)
+            || Self::builtin_part(key).is_some()
     }
 
-    /*
-    fn add_fn_token(&mut self, key: &'a str, fnc: &'a str, tpe: &'a str) {
-        self.typed_parts
-            .insert(key, TypedPart::FnPart { key, fnc, tpe });
+    /// Some hardcoded tokens, these could
+    /// be from some standard library later
+    pub fn builtin_part(key: &str) -> Option<TypedPart<'static>> {
+        use lang_data::typed_part::TypedPart::*;
+        let typed_part = match key {
+            "LPAREN" => CharPart { key: "LPAREN", chr: '(' },
+            "RPAREN" => CharPart { key: "RPAREN", chr: ')' },
+            "LBRACE" => CharPart { key: "LBRACE", chr: '{' },
+            "RBRACE" => CharPart { key: "RBRACE", chr: '}' },
+            "LBRACKET" => CharPart { key: "LBRACKET", chr: '[' },
+            "RBRACKET" => CharPart { key: "RBRACKET", chr: ']' },
+            "COMMA" => CharPart { key: "COMMA", chr: ',' },
+            "COLON" => CharPart { key: "COLON", chr: ':' },
+            "SEMICOLON" => CharPart { key: "SEMICOLON", chr: ';' },
+            "EQUAL" => CharPart { key: "EQUAL", chr: '=' },
+            "LT" => CharPart { key: "LT", chr: '<' },
+            "GT" => CharPart { key: "GT", chr: '>' },
+            "LTE" => TagPart { key: "LTE", tag: "<=" },
+            "GTE" => TagPart { key: "GTE", tag: ">=" },
+            "STAR" => CharPart { key: "STAR", chr: '*' },
+            "EXCL" => CharPart { key: "EXCL", chr: '!' },
+            "DOT" => CharPart { key: "DOT", chr: '.' },
+            "QUESTION" => CharPart { key: "QUESTION", chr: '?' },
+            "QUOTE" => CharPart { key: "QUOTE", chr: '"' },
+            "WS" => WSPart,
+            //"WS" => FnPart { key: "WS", fnc: "sp", tpe: "&'a str" },
+            "string" => StrPart { key: "string" },
+            "str" => StrPart { key: "str" },
+            // Uppercased first for non borrowed
+            "String" => StringPart { key: "String" },
+            "ident" => IdentPart { key: "ident" },
+            "int" => IntPart { key: "int" },
+            _ => return None
+        };
+        Some(typed_part)
     }
-    */
+}
 
-    /// Resolve typed part assuming keys
-    /// are registered
-    pub fn resolve_typed_part(&mut self, key: &'a str) {
-        if self.ast_data.contains_key(key) {
-            self.typed_parts.insert(key, TypedPart::AstPart { key });
-        } else if self.list_data.contains_key(key) {
-            self.typed_parts.insert(key, TypedPart::ListPart { key });
-        } else {
-            // Some hardcoded tokens, these could
-            // be from some standard library later
-            match key {
-                "LPAREN" => self.add_char_token("LPAREN", '('),
-                "RPAREN" => self.add_char_token("RPAREN", ')'),
-                "LBRACE" => self.add_char_token("LBRACE", '{'),
-                "RBRACE" => self.add_char_token("RBRACE", '}'),
-                "LBRACKET" => self.add_char_token("LBRACKET", '['),
-                "RBRACKET" => self.add_char_token("RBRACKET", ']'),
-                "COMMA" => self.add_char_token("COMMA", ','),
-                "COLON" => self.add_char_token("COLON", ':'),
-                "SEMICOLON" => self.add_char_token("SEMICOLON", ';'),
-                "EQUAL" => self.add_char_token("EQUAL", '='),
-                "LT" => self.add_char_token("LT", '<'),
-                "GT" => self.add_char_token("GT", '>'),
-                "LTE" => self.add_tag_token("LTE", "<="),
-                "GTE" => self.add_tag_token("GTE", ">="),
-                "STAR" => self.add_char_token("STAR", '*'),
-                "EXCL" => self.add_char_token("EXCL", '!'),
-                "DOT" => self.add_char_token("DOT", '.'),
-                "QUESTION" => self.add_char_token("QUESTION", '?'),
-                "QUOTE" => self.add_char_token("QUOTE", '"'),
-                "WS" => {
-                    self.typed_parts.insert("WS", TypedPart::WSPart);
-                },
-                //"WS" => self.add_fn_token("WS", "sp", "&'a str"),
-                "string" => {
-                    self.typed_parts
-                        .insert("string", TypedPart::StrPart { key });
-                },
-                "str" => {
-                    self.typed_parts
-                        .insert("str", TypedPart::StrPart { key });
-                },
-                // Uppercased first for non borrowed
-                "String" => {
-                    self.typed_parts
-                        .insert("String", TypedPart::StringPart { key });
-                }
-                "ident" => {
-                    self.typed_parts.insert(key, TypedPart::IdentPart { key });
-                },
-                "int" => {
-                    self.typed_parts.insert(key, TypedPart::IntPart { key });
-                },
-                _ => {
-                    println!("Could not find token: {}", key);
-                    println!("Structs: {:#?}", self.ast_structs.keys());
-                    println!("Enums: {:#?}", self.ast_enums.keys());
-                    println!("Ast rules: {:#?}", self.ast_data.keys());
-                    println!("List rules: {:#?}", self.list_data.keys());
-                    panic!("Could not find token: {}", key)
-                }
-            };
+fn rule_refs<'a>(rule: &AstRule<'a>, refs: &mut Vec<&'a str>) {
+    match rule {
+        &AstRule::RefRule(key) => refs.push(key),
+        &AstRule::PartsRule(ref parts_rule) => parts_refs(&parts_rule.parts, refs),
+    }
+}
+
+fn parts_refs<'a>(parts: &[AstRulePart<'a>], refs: &mut Vec<&'a str>) {
+    for part in parts {
+        match part.token {
+            AstRuleToken::Key(key) => refs.push(key),
+            AstRuleToken::Group(ref parts) => parts_refs(parts, refs),
+            AstRuleToken::Tag(_) | AstRuleToken::Func(..) => {}
         }
     }
 }
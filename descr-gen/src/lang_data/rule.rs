@@ -4,7 +4,7 @@ use lang_data::ast::RuleType;
 use lang_data::ast::AstStruct;
 use lang_data::annotations::*;
 use descr_lang::gen::ast::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use process::codegen_syntax::{SyntaxData, SyntaxEntry};
 
 /// Parser "rule"
@@ -348,19 +348,7 @@ impl<'a: 's, 's> AstPartsRule<'a> {
                                syntax_data: &mut SyntaxData,
                                data: &LangData<'a>) -> CollectPartReturn
     {
-        // Todo: helper method
-        let annot_name = match part.annots.items.get("syntax") {
-            Some(ref annot) => {
-                match annot.args.get("name") {
-                    Some(name) => match name {
-                        &AnArgVal::Quoted(name) => Some(name),
-                        _ => None
-                    }
-                    None => None
-                }
-            },
-            None => None
-        };
+        let annot_name = part.annots.get_quoted("syntax", "name");
         let in_group = state.group_stack.len() > 0;
         match &part.token {
             &AstRuleToken::Key(key) => {
@@ -432,10 +420,10 @@ impl<'a: 's, 's> AstPartsRule<'a> {
                         panic!("Fn not implemented: {}", key);
                     },
                     &TypedPart::StringPart{..} => {
-                        state.add_regex(part.not, part.optional, "\"(?:[^\"\\\\]|\\.)*\"", annot_name.or(Some("string.quoted")), in_group);
+                        state.add_regex(part.not, part.optional, "\"(?:[^\"\\\\]|\\\\.)*\"", annot_name.or(Some("string.quoted")), in_group);
                     },
                     &TypedPart::StrPart{..} => {
-                        state.add_regex(part.not, part.optional, "\"(?:[^\"\\\\]|\\.)*\"", annot_name.or(Some("string.quoted")), in_group);
+                        state.add_regex(part.not, part.optional, "\"(?:[^\"\\\\]|\\\\.)*\"", annot_name.or(Some("string.quoted")), in_group);
                     },
                     &TypedPart::WSPart => {
                         state.add_regex(false, false, "\\s+", None, in_group);
@@ -635,6 +623,142 @@ impl<'a: 's, 's> AstPartsRule<'a> {
     }
 }
 
+/// List member of a rule whose items
+/// can start with the list before it
+pub struct LeadingList<'a> {
+    pub member: &'a str,
+    pub list_key: &'a str,
+    /// Item types with their member
+    /// of the leading list
+    pub items: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> AstPartsRule<'a> {
+    /// A list directly followed by a list whose
+    /// items start with the same list, like annots
+    /// before tokens. Printed, the part of the first
+    /// item is read as the rule's own.
+    pub fn leading_list(&self, data: &LangData<'a>) -> Option<LeadingList<'a>> {
+        let (first, second) = match (self.parts.first(), self.parts.get(1)) {
+            (Some(first), Some(second)) => (first, second),
+            _ => return None,
+        };
+        let (leading_key, list_key) = match (&first.token, &second.token) {
+            (&AstRuleToken::Key(leading_key), &AstRuleToken::Key(list_key)) => (leading_key, list_key),
+            _ => return None,
+        };
+        match (data.typed_parts.get(leading_key), data.list_data.get(list_key)) {
+            (Some(&TypedPart::ListPart { .. }), Some(list_data)) => {
+                let mut items = Vec::new();
+                for rule in &list_data.rules {
+                    if let &AstRule::PartsRule(ref item_rule) = &rule.ast_rule {
+                        if let Some(part) = item_rule.parts.first() {
+                            if let &AstRuleToken::Key(key) = &part.token {
+                                if key == leading_key {
+                                    items.push((item_rule.ast_type, part.member_key.unwrap_or(key)));
+                                }
+                            }
+                        }
+                    }
+                }
+                if items.is_empty() {
+                    None
+                } else {
+                    Some(LeadingList {
+                        member: second.member_key.unwrap_or(list_key),
+                        list_key,
+                        items,
+                    })
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Members the rule has, and those
+    /// of them it always sets
+    pub fn members(&self) -> (HashSet<&'a str>, HashSet<&'a str>) {
+        let mut present = HashSet::new();
+        let mut required = HashSet::new();
+        Self::collect_members(&self.parts, false, &mut present, &mut required);
+        (present, required)
+    }
+
+    fn collect_members(
+        parts: &[AstRulePart<'a>],
+        optional: bool,
+        present: &mut HashSet<&'a str>,
+        required: &mut HashSet<&'a str>,
+    ) {
+        for part in parts {
+            if let Some(member_key) = part.member_key {
+                present.insert(member_key);
+                if !optional && !part.optional {
+                    required.insert(member_key);
+                }
+            }
+            if let &AstRuleToken::Group(ref group) = &part.token {
+                Self::collect_members(group, optional || part.optional, present, required);
+            }
+        }
+    }
+
+    /// Whether an earlier rule reads the source
+    /// of this one. This rule has a list of the
+    /// earlier rule in the delimiters of an item
+    /// of the list, which the earlier rule reads
+    /// as a list of one item.
+    pub fn is_read_by(&self, earlier: &AstPartsRule<'a>, data: &LangData<'a>) -> bool {
+        if self.parts.len() != earlier.parts.len() + 2 {
+            return false;
+        }
+        for (i, part) in earlier.parts.iter().enumerate() {
+            let list_key = match &part.token {
+                &AstRuleToken::Key(key) => match data.typed_parts.get(key) {
+                    Some(&TypedPart::ListPart { .. }) => key,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            let list_data = match data.list_data.get(list_key) {
+                Some(list_data) => list_data,
+                None => continue,
+            };
+            let same_around = earlier.parts[..i]
+                .iter()
+                .zip(&self.parts[..i])
+                .chain(earlier.parts[i + 1..].iter().zip(&self.parts[i + 3..]))
+                .all(|(a, b)| a.is_same(b));
+            if !same_around || !part.is_same(&self.parts[i + 1]) {
+                continue;
+            }
+            let wrapped = &self.parts[i..i + 3];
+            let is_item = list_data.rules.iter().any(|rule| match &rule.ast_rule {
+                &AstRule::PartsRule(ref item_rule) => {
+                    // Parts that can't be left out,
+                    // other lists can be empty
+                    let required = item_rule
+                        .parts
+                        .iter()
+                        .filter(|part| {
+                            !part.optional && (!part.is_list(data) || part.token.is_same(&AstRuleToken::Key(list_key)))
+                        })
+                        .collect::<Vec<_>>();
+                    required.len() == 3
+                        && required[0].token.is_same(&wrapped[0].token)
+                        && required[1].token.is_same(&AstRuleToken::Key(list_key))
+                        && required[2].token.is_same(&wrapped[2].token)
+                }
+                _ => false,
+            });
+            if is_item {
+                return true;
+            }
+        }
+        false
+    }
+}
+
 pub fn to_regex(string: &str) -> String {
     let mut s = String::with_capacity(string.len() + 1);
     for chr in string.chars() {
@@ -685,6 +809,19 @@ impl<'a> AstRuleToken<'a> {
                 }).collect::<Vec<_>>()
         )
     }
+
+    /// Same key, tag or group of parts
+    pub fn is_same(&self, other: &AstRuleToken) -> bool {
+        match (self, other) {
+            (&AstRuleToken::Key(a), &AstRuleToken::Key(b)) => a == b,
+            (&AstRuleToken::Tag(a), &AstRuleToken::Tag(b)) => a == b,
+            (&AstRuleToken::Func(a, _), &AstRuleToken::Func(b, _)) => a == b,
+            (&AstRuleToken::Group(ref a), &AstRuleToken::Group(ref b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.is_same(b))
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -728,9 +865,72 @@ impl GenParserData {
     pub fn pop_group(&mut self) {
         let _ = self.group_stack.pop();
     }
+
+    /// Group for an optional group with members,
+    /// which are then referenced through the
+    /// group value, a tuple when several
+    pub fn push_opt_group(&mut self, member_names: &[&str]) -> String {
+        let group_name = self.push_group();
+        self.opt_group = true;
+        let is_tuple = member_names.len() > 1;
+        for (i, member_name) in member_names.iter().enumerate() {
+            let mut member_ref = group_name.clone();
+            if is_tuple {
+                let it_string = i.to_string();
+                append!(member_ref, "." it_string.as_ref());
+            }
+            self.member_map.insert(String::from(*member_name), (group_name.clone(), member_ref));
+        }
+        group_name
+    }
+}
+
+/// Value of an optional group with members
+pub fn gen_group_tuple(mut s: String, member_names: &[&str], data: &LangData) -> String {
+    let num_members = member_names.len();
+    let is_tuple = num_members > 1;
+    if is_tuple {
+        // Make tuple
+        s += "(";
+    }
+    for (i, member_name) in member_names.iter().enumerate() {
+        append!(s, data.sc(member_name) "_k");
+        if i < num_members - 1 {
+            s += ", ";
+        }
+    }
+    if is_tuple {
+        s += ")";
+    }
+    s
+}
+
+/// Whether to_source writes a space before
+/// part i of parts. Not parts keep their
+/// text, so there is no space around them.
+pub fn space_before(parts: &[AstRulePart], i: usize) -> bool {
+    !parts[i].not && (i == 0 || !parts[i - 1].not)
 }
 
 impl<'a, 'b> AstRulePart<'a> {
+    /// Same token, member and flags
+    pub fn is_same(&self, other: &AstRulePart) -> bool {
+        self.token.is_same(&other.token)
+            && self.member_key == other.member_key
+            && self.optional == other.optional
+            && self.not == other.not
+    }
+
+    pub fn is_list(&self, data: &LangData) -> bool {
+        match &self.token {
+            &AstRuleToken::Key(key) => match data.typed_parts.get(key) {
+                Some(&TypedPart::ListPart { .. }) => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
     pub fn collect_ast_member_data(&self,
                                    mut list: Vec<AstRulePartMemberData<'a>>, 
                                    parent_opt: bool,
@@ -846,40 +1046,17 @@ impl<'a, 'b> AstRulePart<'a> {
                 }
                 let num_members = member_names.len();
                 indent!(s 2);
-                let group_name = if num_members > 0 {
-                    let group_name = gen_data.push_group();
-                    gen_data.opt_group = true;
+                if num_members > 0 {
+                    let group_name = gen_data.push_opt_group(&member_names);
                     append!(s, &group_name ": ");
-                    Some(group_name)
-                } else {
-                    None
-                };
+                }
                 append!(s, "opt!(do_parse!(\n");
                 for part in parts {
                     s = part.gen_part_parser(s, data, gen_data);
                 }
                 if num_members > 0 {
                     append!(s 3, "(");
-                    let is_tuple = if num_members > 1 { true } else { false };
-                    if is_tuple {
-                        // Make tuple
-                        s += "(";
-                    }
-                    for (i, member_name) in member_names.iter().enumerate() {
-                        append!(s, member_name "_k");
-                        if i < num_members - 1 {
-                            s += ", ";
-                        }
-                        let mut member_ref = group_name.clone().unwrap();
-                        if is_tuple {
-                            let it_string = i.to_string();
-                            append!(member_ref, "." it_string.as_ref());
-                        }
-                        gen_data.member_map.insert(String::from(*member_name), (group_name.clone().unwrap(), member_ref));
-                    }
-                    if is_tuple {
-                        s += ")";
-                    }
+                    s = gen_group_tuple(s, &member_names, data);
                     s += ")";
                 }
                 append!(s, ")) >>\n");
@@ -919,18 +1096,18 @@ impl<'a, 'b> AstRulePart<'a> {
         match &self.token {
             &AstRuleToken::Key(key) => data.typed_parts.get(key).unwrap().gen_parser(s, data),
             &AstRuleToken::Tag(string) => {
-                if data.debug {
-                    s += "debug_wrap!(";
+                if data.trace {
+                    append!(s, "trace_wrap!(" format!("{:?}", format!("{:?}", string)).as_str() ", ");
                 }
                 append!(s, "tag!(\"" string "\")");
-                if data.debug {
+                if data.trace {
                     s += ")";
                 }
                 s
             }
             &AstRuleToken::Func(ident, ref args) => {
-                if data.debug {
-                    s += "debug_wrap!(";
+                if data.trace {
+                    append!(s, "trace_wrap!(\"" ident "!\", ");
                 }
                 append!(s, ident "!(");
                 let num_args = args.len();
@@ -945,7 +1122,7 @@ impl<'a, 'b> AstRulePart<'a> {
                     }
                 }
                 s += ")";
-                if data.debug {
+                if data.trace {
                     s += ")";
                 }
                 s
@@ -981,12 +1158,12 @@ impl<'a, 'b> AstRulePart<'a> {
             // Check if there is a member ref from grouped
             let (member_ref, group_key, opt_group) = if let Some(ref member_mapped) = gen_data.member_map.get(member_key) {
                 (
-                    String::from(member_mapped.1.as_ref()),
-                    Some(String::from(member_mapped.0.as_ref())),
+                    String::from(member_mapped.1.as_str()),
+                    Some(String::from(member_mapped.0.as_str())),
                     gen_data.opt_group
                 )
             } else {
-                let mut member_ref = String::from(self.member_key.unwrap());
+                let mut member_ref = String::from(data.sc(member_key));
                 member_ref.push_str("_k");
                 (member_ref, None, false)
             };
@@ -1056,6 +1233,9 @@ impl<'a, 'b> AstRulePart<'a> {
                     if self.optional || parent_opt {
                         if let Some(member_key) = self.member_key {
                             append!(s 2, "if node." data.sc(member_key) " { s += \"" quoted "\"; }\n");
+                        } else if !self.optional {
+                            // In an optional group that is set
+                            append!(s 2, "s += \"" quoted "\";\n");
                         }
                     } else {
                         append!(s 2, "s += \"" quoted "\";\n");
@@ -1078,19 +1258,24 @@ impl<'a, 'b> AstRulePart<'a> {
                                 if let Some(member_key) = part.member_key {
                                     append!(s, "node." member_key ".is_some()");
                                     if i < mlen - 1 {
-                                        s += ", ";
+                                        s += " || ";
                                     }
                                 }
                             }
                             append!(s, " {\n");
-                            for part in parts {
+                            for (i, part) in parts.iter().enumerate() {
+                                if i > 0 && space_before(parts, i) {
+                                    append!(s 2, "s += \" \";\n");
+                                }
                                 s = part.add_to_source(s, data, true);
                             }
                             append!(s 2, "}\n");
                         }
                     } else {
-                        // Just forward to parts for now
-                        for part in parts {
+                        for (i, part) in parts.iter().enumerate() {
+                            if i > 0 && space_before(parts, i) {
+                                append!(s 2, "s += \" \";\n");
+                            }
                             s = part.add_to_source(s, data, parent_opt);
                         }
                     }
@@ -1114,6 +1299,57 @@ impl<'a> AstRule<'a> {
         data: &LangData,
         rule_type: &RuleType<'a>,
         resolved: &ResolvedType<'a>,
+    ) -> String {
+        let is_many = match rule_type {
+            &RuleType::SingleType(..) => false,
+            &RuleType::ManyType(..) => true,
+        };
+        let mut gen_data = GenParserData::new();
+        match self {
+            &AstRule::RefRule(rule_ref) => {
+                // When is_many, an enum is assumed generated for
+                // the rule
+                if is_many {
+                    append!(s, "map!(" data.sc(rule_ref) ", |node| { ");
+                    s = self.gen_rule_value(s, data, rule_type, resolved, &mut gen_data);
+                    s += " })";
+                } else {
+                    s += data.sc(rule_ref);
+                }
+            }
+            &AstRule::PartsRule(ref parts_rule) => {
+                if data.trace {
+                    append!(s, "trace_wrap!(\"" parts_rule.ast_type "\", ");
+                }
+                s += "do_parse!(\n";
+                for part in &parts_rule.parts {
+                    s = part.gen_part_parser(s, data, &mut gen_data);
+                }
+                s += "        (";
+                s = self.gen_rule_value(s, data, rule_type, resolved, &mut gen_data);
+                if is_many && resolved.is_simple(data) {
+                    s += "        ))";
+                } else {
+                    s += "))";
+                }
+                if data.trace {
+                    s += ")";
+                }
+            }
+        }
+        s
+    }
+
+    /// Ast value of rule, from the parsed
+    /// members in gen_data, or from `node`
+    /// for ref rules
+    pub fn gen_rule_value(
+        &self,
+        mut s: String,
+        data: &LangData,
+        rule_type: &RuleType<'a>,
+        resolved: &ResolvedType<'a>,
+        gen_data: &mut GenParserData,
     ) -> String {
         let (is_many, type_name) = match rule_type {
             &RuleType::SingleType(tn) => (false, tn),
@@ -1123,11 +1359,10 @@ impl<'a> AstRule<'a> {
             &ResolvedType::ResolvedEnum(key) => (None, data.ast_enums.get(key)),
             &ResolvedType::ResolvedStruct(key) => (data.ast_structs.get(key), None),
         };
-        let mut gen_data = GenParserData::new();
-        match self {
+        let parts_rule = match self {
+            &AstRule::PartsRule(ref parts_rule) => parts_rule,
             &AstRule::RefRule(rule_ref) => {
-                // When is_many, an enum is assumed generated for
-                // the rule
+                // Enum item from node, when is_many
                 if is_many {
                     let node_expr = match enum_data {
                         Some(enum_data) => {
@@ -1139,62 +1374,46 @@ impl<'a> AstRule<'a> {
                         }
                         _ => "node",
                     };
-                    append!(s, "map!(" data.sc(rule_ref) ", |node| { ");
-                    append!(s, type_name "::" rule_ref "Item(" node_expr ") })");
+                    append!(s, type_name "::" rule_ref "Item(" node_expr ")");
                 } else {
-                    s += data.sc(rule_ref);
+                    s += "node";
                 }
+                return s;
             }
-            &AstRule::PartsRule(ref parts_rule) => {
-                s += "do_parse!(\n";
-                for part in &parts_rule.parts {
-                    s = part.gen_part_parser(s, data, &mut gen_data);
-                }
-                s += "        (";
-                // There could also be "simple enum" here
-                // which is enums without data
-                let is_simple = is_many && resolved.is_simple(data);
-                let is_boxed_item = match enum_data {
-                    Some(enum_data) => {
-                        if enum_data.boxed_items.contains(parts_rule.ast_type) {
-                            true
-                        } else {
-                            false
-                        }
-                    }
-                    _ => false,
-                };
-                if is_many {
-                    // Could "resolved" be used instead?
-                    if is_simple {
-                        append!(s, type_name "::" parts_rule.ast_type);
-                    } else {
-                        append!(s, type_name "::" parts_rule.ast_type "Item(");
-                        if is_boxed_item {
-                            append!(s, "Box::new(" parts_rule.ast_type "");
-                        } else {
-                            s += parts_rule.ast_type;
-                        }
-                        s += " {\n";
-                    }
+        };
+        // There could also be "simple enum" here
+        // which is enums without data
+        let is_simple = is_many && resolved.is_simple(data);
+        let is_boxed_item = match enum_data {
+            Some(enum_data) => enum_data.boxed_items.contains(parts_rule.ast_type),
+            _ => false,
+        };
+        if is_many {
+            // Could "resolved" be used instead?
+            if is_simple {
+                append!(s, type_name "::" parts_rule.ast_type);
+            } else {
+                append!(s, type_name "::" parts_rule.ast_type "Item(");
+                if is_boxed_item {
+                    append!(s, "Box::new(" parts_rule.ast_type "");
                 } else {
                     s += parts_rule.ast_type;
-                    s += " {\n";
                 }
-                if is_simple {
-                    s += "        ))";
-                } else {
-                    for part in &parts_rule.parts {
-                        s = part.gen_parser_struct_assign(s, struct_data, data, &mut gen_data);
-                    }
-                    if is_many {
-                        s += "        })))";
-                        if is_boxed_item {
-                            s += ")";
-                        }
-                    } else {
-                        s += "        }))";
-                    }
+                s += " {\n";
+            }
+        } else {
+            s += parts_rule.ast_type;
+            s += " {\n";
+        }
+        if !is_simple {
+            for part in &parts_rule.parts {
+                s = part.gen_parser_struct_assign(s, struct_data, data, gen_data);
+            }
+            s += "        }";
+            if is_many {
+                s += ")";
+                if is_boxed_item {
+                    s += ")";
                 }
             }
         }
@@ -51,10 +51,38 @@ impl<'a> TypedPart<'a> {
         }
     }
 
+    /// Name in parse traces, the key
+    /// in the grammar
+    pub fn trace_name(&self) -> &'a str {
+        use lang_data::typed_part::TypedPart::*;
+        match self {
+            &AstPart { key }
+            | &ListPart { key }
+            | &CharPart { key, .. }
+            | &TagPart { key, .. }
+            | &IntPart { key }
+            | &IdentPart { key }
+            | &FnPart { key, .. }
+            | &StringPart { key }
+            | &StrPart { key } => key,
+            &WSPart => "WS",
+        }
+    }
+
+    /// Whether the part is a token, and not
+    /// a rule, rules are traced by themselves
+    pub fn is_token(&self) -> bool {
+        match self {
+            &TypedPart::AstPart { .. } | &TypedPart::ListPart { .. } => false,
+            _ => true,
+        }
+    }
+
     pub fn gen_parser(&self, mut s: String, data: &LangData) -> String {
         use lang_data::typed_part::TypedPart::*;
-        if data.debug {
-            s += "debug_wrap!(";
+        let traced = data.trace && self.is_token();
+        if traced {
+            append!(s, "trace_wrap!(" format!("{:?}", self.trace_name()).as_str() ", ");
         }
         match self {
             &AstPart { key } => {
@@ -88,7 +116,7 @@ impl<'a> TypedPart<'a> {
             }
             &WSPart => s += "sp",
         }
-        if data.debug {
+        if traced {
             s += ")";
         }
         s
@@ -266,9 +294,12 @@ impl<'a> TypedPart<'a> {
                     s += ".iter().enumerate() {\n";
                     append!(s 3, "s = Self::" data.sc(ast_type) "(s, item);\n");
                     if let Some(sep_part) = sep_part {
-                        append!(s 3, "if i < len - 1 { ");
+                        // Parsers don't skip whitespace
+                        // before separators
+                        append!(s 3, "if i < len - 1 {\n");
+                        append!(s 4, "s.truncate(s.trim_end().len());\n");
                         s = sep_part.add_to_source(s, None, false, data);
-                        s += " }\n";
+                        append!(s 3, "}\n");
                     }
                     append!(s 2, "}\n");
                     if optional {
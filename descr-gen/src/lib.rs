@@ -1,12 +1,27 @@
 #[macro_use]
 extern crate descr_common;
 extern crate descr_lang;
-extern crate elapsed;
 extern crate nom;
 //#[macro_use]
 extern crate itertools;
+#[cfg(test)]
+extern crate syn;
+#[cfg(test)]
+extern crate serde_json;
+#[cfg(test)]
+extern crate fancy_regex;
 pub mod lang_data;
 pub mod process;
+pub mod generator;
+pub mod interpreter;
+pub mod samples;
+pub mod lint;
+pub mod graph;
+pub mod format;
+pub mod diff;
+pub mod bootstrap;
+pub mod ir;
+pub mod import;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,570 @@
+//! Checks on lang data for grammars that build,
+//! but doesn't parse like they read. Alternatives
+//! are tried in order, like alt_complete!, so an
+//! alternative that matches a prefix of a later
+//! one shadows it. FIRST sets and nullability are
+//! computed over the rules to find these, along
+//! with rules and tokens that are never used, and
+//! list items that could loop in many0!.
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use lang_data::data::*;
+use lang_data::rule::*;
+use lang_data::typed_part::TypedPart;
+use descr_common::util::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintKind {
+    /// Never tried, an earlier alternative
+    /// matches a prefix of it, or empty input
+    ShadowedAlternative,
+    /// Starts with a token an earlier
+    /// alternative can also start with
+    OverlappingAlternatives,
+    UnreachableRule,
+    NullableListItem,
+    UnusedToken,
+}
+impl LintKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            &LintKind::ShadowedAlternative => "shadowed-alternative",
+            &LintKind::OverlappingAlternatives => "overlapping-alternatives",
+            &LintKind::UnreachableRule => "unreachable-rule",
+            &LintKind::NullableListItem => "nullable-list-item",
+            &LintKind::UnusedToken => "unused-token",
+        }
+    }
+
+    /// Overlaps are often intended, like a
+    /// longer alternative before a shorter one
+    pub fn is_note(&self) -> bool {
+        *self == LintKind::OverlappingAlternatives
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintWarning {
+    pub kind: LintKind,
+    /// Rule or token key
+    pub key: String,
+    pub message: String,
+}
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let level = if self.kind.is_note() { "note" } else { "warning" };
+        write!(f, "{}[{}] {}: {}", level, self.kind.name(), self.key, self.message)
+    }
+}
+
+/// Token that can start a match
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Terminal<'d> {
+    Tag(String),
+    Ident,
+    Int,
+    Str,
+    Fn(&'d str),
+    /// Not parts, anything up to the part
+    Any,
+}
+impl<'d> Terminal<'d> {
+    /// Whether some input can start
+    /// with both
+    pub fn overlaps(&self, other: &Terminal) -> bool {
+        use self::Terminal::*;
+        match (self, other) {
+            (&Any, _) | (_, &Any) => true,
+            (&Tag(ref a), &Tag(ref b)) => a.starts_with(b.as_str()) || b.starts_with(a.as_str()),
+            (&Tag(ref tag), term) | (term, &Tag(ref tag)) => match tag.bytes().next() {
+                Some(first) => match term {
+                    &Ident => first.is_ascii_alphabetic() || first == b'_',
+                    &Int => first.is_ascii_digit() || first == b'-' || first == b'+',
+                    &Str => first == b'"',
+                    _ => false,
+                },
+                None => false,
+            },
+            (a, b) => a == b,
+        }
+    }
+}
+impl<'d> fmt::Display for Terminal<'d> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &Terminal::Tag(ref tag) => write!(f, "\"{}\"", tag),
+            &Terminal::Ident => write!(f, "ident"),
+            &Terminal::Int => write!(f, "int"),
+            &Terminal::Str => write!(f, "string"),
+            &Terminal::Fn(fnc) => write!(f, "{}(..)", fnc),
+            &Terminal::Any => write!(f, "any"),
+        }
+    }
+}
+
+/// Tokens a match can start with, and
+/// whether it can match empty input
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct First<'d> {
+    pub terminals: BTreeSet<Terminal<'d>>,
+    pub nullable: bool,
+}
+
+/// Part compared for prefixes, rule keys
+/// and terminals, without member names
+#[derive(Debug, PartialEq)]
+enum Symbol<'d> {
+    Rule(&'d str),
+    Terminal(Terminal<'d>),
+    Group(Vec<(Symbol<'d>, bool, bool)>),
+    /// Not compared
+    Opaque,
+}
+
+pub struct Linter<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+    firsts: HashMap<&'d str, First<'d>>,
+}
+
+impl<'a, 'd> Linter<'a, 'd> {
+    /// FIRST sets of the rules in lang data,
+    /// iterated until they don't change
+    pub fn new(data: &'a LangData<'d>) -> Linter<'a, 'd> {
+        let mut linter = Linter {
+            data,
+            firsts: HashMap::new(),
+        };
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (key, ast_data) in data.ast_data.sorted_iter() {
+                let mut first = First::default();
+                for rule in &ast_data.rules {
+                    let rule_first = linter.rule_first(rule);
+                    first.terminals.extend(rule_first.terminals);
+                    first.nullable |= rule_first.nullable;
+                }
+                changed |= linter.update(key, first);
+            }
+            for (key, list_data) in data.list_data.sorted_iter() {
+                // Zero items always match
+                let mut first = First {
+                    terminals: BTreeSet::new(),
+                    nullable: true,
+                };
+                for rule in &list_data.rules {
+                    first.terminals.extend(linter.rule_first(&rule.ast_rule).terminals);
+                }
+                changed |= linter.update(key, first);
+            }
+        }
+        linter
+    }
+
+    fn update(&mut self, key: &'d str, first: First<'d>) -> bool {
+        if self.firsts.get(key) == Some(&first) {
+            false
+        } else {
+            self.firsts.insert(key, first);
+            true
+        }
+    }
+
+    /// FIRST set of an ast or list key
+    pub fn first(&self, key: &str) -> Option<&First<'d>> {
+        self.firsts.get(key)
+    }
+
+    fn rule_first(&self, rule: &AstRule<'d>) -> First<'d> {
+        match rule {
+            &AstRule::RefRule(key) => self.firsts.get(key).cloned().unwrap_or_default(),
+            &AstRule::PartsRule(ref parts_rule) => self.parts_first(&parts_rule.parts),
+        }
+    }
+
+    fn parts_first(&self, parts: &[AstRulePart<'d>]) -> First<'d> {
+        let mut first = First {
+            terminals: BTreeSet::new(),
+            nullable: true,
+        };
+        for part in parts {
+            let part_first = self.part_first(part);
+            first.terminals.extend(part_first.terminals);
+            if !part_first.nullable {
+                first.nullable = false;
+                break;
+            }
+        }
+        first
+    }
+
+    fn part_first(&self, part: &AstRulePart<'d>) -> First<'d> {
+        // until_done! needs to consume something
+        if part.not {
+            let mut terminals = BTreeSet::new();
+            terminals.insert(Terminal::Any);
+            return First {
+                terminals,
+                nullable: false,
+            };
+        }
+        let mut first = match part.token {
+            AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                Some(&TypedPart::AstPart { key }) | Some(&TypedPart::ListPart { key }) => {
+                    self.firsts.get(key).cloned().unwrap_or_default()
+                }
+                Some(&TypedPart::WSPart) => First {
+                    terminals: BTreeSet::new(),
+                    nullable: true,
+                },
+                Some(typed_part) => terminal_first(self.typed_terminal(typed_part)),
+                None => First::default(),
+            },
+            AstRuleToken::Tag(tag) => terminal_first(Terminal::Tag(tag.to_string())),
+            AstRuleToken::Func(fnc, _) => terminal_first(Terminal::Fn(fnc)),
+            AstRuleToken::Group(ref parts) => self.parts_first(parts),
+        };
+        first.nullable |= part.optional;
+        first
+    }
+
+    fn typed_terminal(&self, typed_part: &TypedPart<'d>) -> Terminal<'d> {
+        match typed_part {
+            &TypedPart::CharPart { chr, .. } => Terminal::Tag(chr.to_string()),
+            &TypedPart::TagPart { tag, .. } => Terminal::Tag(tag.to_string()),
+            &TypedPart::IntPart { .. } => Terminal::Int,
+            &TypedPart::IdentPart { .. } => Terminal::Ident,
+            &TypedPart::StringPart { .. } | &TypedPart::StrPart { .. } => Terminal::Str,
+            &TypedPart::FnPart { fnc, .. } => Terminal::Fn(fnc),
+            &TypedPart::AstPart { .. } | &TypedPart::ListPart { .. } | &TypedPart::WSPart => Terminal::Any,
+        }
+    }
+
+    /// Warnings for unused keys, then
+    /// for rules in key order
+    pub fn lint(&self) -> Vec<LintWarning> {
+        let mut warnings = Vec::new();
+        self.lint_unused(&mut warnings);
+        for (key, ast_data) in self.data.ast_data.sorted_iter() {
+            let rules = ast_data.rules.iter().collect::<Vec<_>>();
+            self.lint_alternatives(key, &rules, &mut warnings);
+        }
+        for (key, list_data) in self.data.list_data.sorted_iter() {
+            let rules = list_data.rules.iter().map(|rule| &rule.ast_rule).collect::<Vec<_>>();
+            self.lint_alternatives(key, &rules, &mut warnings);
+            self.lint_list_items(key, list_data, &rules, &mut warnings);
+        }
+        warnings
+    }
+
+    /// Rules not reachable from the start key,
+    /// and tokens only used by those
+    fn lint_unused(&self, warnings: &mut Vec<LintWarning>) {
+        let start_key = match self.data.start_key {
+            Some(start_key) => start_key,
+            None => return,
+        };
+        let mut reachable = HashSet::new();
+        let mut used = HashSet::new();
+        let mut stack = vec![start_key];
+        while let Some(key) = stack.pop() {
+            if !reachable.insert(key) {
+                continue;
+            }
+            for key in self.data.rule_refs(key) {
+                used.insert(key);
+                stack.push(key);
+            }
+        }
+        // Keys referenced from anywhere, to tell
+        // unused rules from unreachable ones
+        let mut rule_keys = self.data.ast_data.keys().chain(self.data.list_data.keys()).collect::<Vec<_>>();
+        let referenced = rule_keys
+            .iter()
+            .flat_map(|key| self.data.rule_refs(key))
+            .collect::<HashSet<_>>();
+        rule_keys.sort();
+        for key in rule_keys {
+            if reachable.contains(key) {
+                continue;
+            }
+            let message = if referenced.contains(key) {
+                format!("not reachable from {}", start_key)
+            } else {
+                "not used by any rule".to_string()
+            };
+            warnings.push(LintWarning {
+                kind: LintKind::UnreachableRule,
+                key: key.to_string(),
+                message,
+            });
+        }
+        for (key, typed_part) in self.data.typed_parts.sorted_iter() {
+            match typed_part {
+                &TypedPart::AstPart { .. } | &TypedPart::ListPart { .. } => {}
+                _ => {
+                    if !used.contains(key) {
+                        warnings.push(LintWarning {
+                            kind: LintKind::UnusedToken,
+                            key: key.to_string(),
+                            message: format!("only used by rules not reachable from {}", start_key),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Alternatives are tried in order, the
+    /// first match wins
+    fn lint_alternatives(&self, key: &str, rules: &[&AstRule<'d>], warnings: &mut Vec<LintWarning>) {
+        if rules.len() < 2 {
+            return;
+        }
+        let symbols = rules.iter().map(|rule| self.rule_symbols(rule)).collect::<Vec<_>>();
+        let firsts = rules.iter().map(|rule| self.rule_first(rule)).collect::<Vec<_>>();
+        // Alternatives after one that can match
+        // empty input are reported with it
+        let mut tried = rules.len();
+        if let Some(i) = firsts.iter().position(|first| first.nullable) {
+            if i + 1 < rules.len() {
+                warnings.push(LintWarning {
+                    kind: LintKind::ShadowedAlternative,
+                    key: key.to_string(),
+                    message: format!(
+                        "{} can match empty input, alternatives after it are never tried",
+                        alternative_name(rules, i)
+                    ),
+                });
+            }
+            tried = i + 1;
+        }
+        for j in 1..tried {
+            let shadowed_by = (0..j).find(|&i| {
+                symbols[i].len() <= symbols[j].len() && symbols[i].iter().zip(&symbols[j]).all(|(a, b)| same_symbol(a, b))
+            });
+            if let Some(i) = shadowed_by {
+                let how = if symbols[i].len() == symbols[j].len() {
+                    "matches the same parts"
+                } else {
+                    "matches a prefix of it"
+                };
+                warnings.push(LintWarning {
+                    kind: LintKind::ShadowedAlternative,
+                    key: key.to_string(),
+                    message: format!(
+                        "{} is shadowed by {}, which {}",
+                        alternative_name(rules, j),
+                        alternative_name(rules, i),
+                        how
+                    ),
+                });
+                continue;
+            }
+            for i in 0..j {
+                let overlap = firsts[j]
+                    .terminals
+                    .iter()
+                    .filter(|term| firsts[i].terminals.iter().any(|other| term.overlaps(other)))
+                    .map(|term| term.to_string())
+                    .collect::<Vec<_>>();
+                if !overlap.is_empty() {
+                    warnings.push(LintWarning {
+                        kind: LintKind::OverlappingAlternatives,
+                        key: key.to_string(),
+                        message: format!(
+                            "{} and {} can both start with {}, and {} is tried first",
+                            alternative_name(rules, i),
+                            alternative_name(rules, j),
+                            overlap.join(", "),
+                            alternative_name(rules, i)
+                        ),
+                    });
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Items without a separator are parsed
+    /// with many0!, which loops on empty matches
+    fn lint_list_items(
+        &self,
+        key: &str,
+        list_data: &ListData<'d>,
+        rules: &[&AstRule<'d>],
+        warnings: &mut Vec<LintWarning>,
+    ) {
+        let separated = match list_data.sep {
+            Some(sep) => match self.data.typed_parts.get(sep) {
+                Some(&TypedPart::WSPart) | None => false,
+                Some(_) => true,
+            },
+            None => false,
+        };
+        if separated {
+            return;
+        }
+        for (i, rule) in rules.iter().enumerate() {
+            if self.rule_first(rule).nullable {
+                warnings.push(LintWarning {
+                    kind: LintKind::NullableListItem,
+                    key: key.to_string(),
+                    message: format!(
+                        "item {} can match empty input, and would loop in many0!",
+                        alternative_name(rules, i)
+                    ),
+                });
+            }
+        }
+    }
+
+    fn rule_symbols(&self, rule: &AstRule<'d>) -> Vec<(Symbol<'d>, bool, bool)> {
+        match rule {
+            &AstRule::RefRule(key) => vec![(Symbol::Rule(key), false, false)],
+            &AstRule::PartsRule(ref parts_rule) => self.parts_symbols(&parts_rule.parts),
+        }
+    }
+
+    /// Symbols with optional and not flags
+    fn parts_symbols(&self, parts: &[AstRulePart<'d>]) -> Vec<(Symbol<'d>, bool, bool)> {
+        parts
+            .iter()
+            .map(|part| {
+                let symbol = match part.token {
+                    AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                        Some(&TypedPart::AstPart { key }) | Some(&TypedPart::ListPart { key }) => Symbol::Rule(key),
+                        Some(&TypedPart::WSPart) => Symbol::Rule(key),
+                        Some(typed_part) => Symbol::Terminal(self.typed_terminal(typed_part)),
+                        None => Symbol::Opaque,
+                    },
+                    AstRuleToken::Tag(tag) => Symbol::Terminal(Terminal::Tag(tag.to_string())),
+                    AstRuleToken::Func(..) => Symbol::Opaque,
+                    AstRuleToken::Group(ref parts) => Symbol::Group(self.parts_symbols(parts)),
+                };
+                (symbol, part.optional, part.not)
+            })
+            .collect()
+    }
+}
+
+fn terminal_first(terminal: Terminal) -> First {
+    let mut terminals = BTreeSet::new();
+    terminals.insert(terminal);
+    First {
+        terminals,
+        nullable: false,
+    }
+}
+
+fn same_symbol(a: &(Symbol, bool, bool), b: &(Symbol, bool, bool)) -> bool {
+    if a.1 != b.1 || a.2 != b.2 {
+        return false;
+    }
+    match (&a.0, &b.0) {
+        (&Symbol::Opaque, _) | (_, &Symbol::Opaque) => false,
+        (&Symbol::Group(ref a), &Symbol::Group(ref b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_symbol(a, b))
+        }
+        (a, b) => a == b,
+    }
+}
+
+/// Position and name, as alternatives
+/// can share the ast type
+fn alternative_name(rules: &[&AstRule], i: usize) -> String {
+    let name = match rules[i] {
+        &AstRule::RefRule(key) => key,
+        &AstRule::PartsRule(ref parts_rule) => parts_rule.ast_type,
+    };
+    format!("alternative {} ({})", i + 1, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generator::{build_data, parse_grammar};
+
+    fn lint(grammar: &[u8]) -> Vec<String> {
+        let ast = parse_grammar(grammar).unwrap();
+        let mut data = LangData::new(false, "Test".to_string());
+        build_data(grammar, &ast, &mut data).unwrap();
+        Linter::new(&data).lint().iter().map(|warning| warning.to_string()).collect()
+    }
+
+    #[test]
+    fn warns_on_shadowed_alternatives() {
+        let warnings = lint(b"
+            Source(stmts:stmts)
+            stmts:Stmt[] WS {
+                If(\"if\" cond:Expr LBRACE RBRACE),
+                IfElse(\"if\" cond:Expr LBRACE RBRACE \"else\" LBRACE RBRACE),
+                Call(ident LPAREN RPAREN),
+                Assign(ident EQUAL Expr)
+            }
+            Expr {
+                Var(ident),
+                Num(int),
+                Neg(\"-\" Expr)
+            }
+            Unused(ident)
+        ");
+        assert_eq!(
+            warnings,
+            vec![
+                "warning[unreachable-rule] Unused: not used by any rule",
+                "note[overlapping-alternatives] Expr: alternative 2 (Num) and alternative 3 (Neg) \
+                 can both start with \"-\", and alternative 2 (Num) is tried first",
+                "warning[shadowed-alternative] stmts: alternative 2 (IfElse) is shadowed by alternative 1 (If), \
+                 which matches a prefix of it",
+                "note[overlapping-alternatives] stmts: alternative 1 (If) and alternative 3 (Call) \
+                 can both start with ident, and alternative 1 (If) is tried first",
+                "note[overlapping-alternatives] stmts: alternative 1 (If) and alternative 4 (Assign) \
+                 can both start with ident, and alternative 1 (If) is tried first",
+            ]
+        );
+        // Prefixes are checked up to a last
+        // alternative that can match empty input
+        let warnings = lint(b"
+            Source(stmts:stmts)
+            stmts:Stmt[] WS {
+                If(\"if\" cond:ident),
+                IfElse(\"if\" cond:ident \"else\" els:ident),
+                Loop(opts)
+            }
+            opts[] WS Opt
+            Opt(ident?)
+        ");
+        assert_eq!(
+            warnings,
+            vec![
+                "warning[nullable-list-item] opts: item alternative 1 (Opt) can match empty input, \
+                 and would loop in many0!",
+                "warning[shadowed-alternative] stmts: alternative 2 (IfElse) is shadowed by alternative 1 (If), \
+                 which matches a prefix of it",
+                "note[overlapping-alternatives] stmts: alternative 1 (If) and alternative 3 (Loop) \
+                 can both start with ident, and alternative 1 (If) is tried first",
+                "warning[nullable-list-item] stmts: item alternative 3 (Loop) can match empty input, \
+                 and would loop in many0!",
+            ]
+        );
+    }
+
+    #[test]
+    fn warns_on_nullable_items() {
+        let warnings = lint(b"
+            Source(items:items)
+            items:Item[] WS {
+                Word(ident),
+                Maybe(int?)
+            }
+        ");
+        assert_eq!(
+            warnings,
+            vec![
+                "warning[nullable-list-item] items: item alternative 2 (Maybe) can match empty input, \
+                 and would loop in many0!",
+            ]
+        );
+    }
+}
@@ -2,6 +2,7 @@ use lang_data::data::*;
 use lang_data::ast::*;
 use lang_data::typed_part::*;
 use lang_data::rule::*;
+use descr_common::util::SortedHashMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
@@ -68,20 +69,18 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
     }
 
     fn process_parts_rule(
-        rule: &'a AstPartsRule<'d>,
+        rule: &AstPartsRule<'d>,
         struct_data: &mut HashMap<&'d str, AstStruct<'d>>,
         typed_parts: &HashMap<&'d str, TypedPart<'d>>,
         snake_cased: &mut SnakeCased<'d>,
-    ) {
+    ) -> Result<(), KeyError<'d>> {
         let mut current_members = HashSet::new();
         let mut list = Vec::new();
         for part in &rule.parts {
             list = part.collect_ast_member_data(list, false, typed_parts);
             for mdata in &list {
                 if current_members.contains(mdata.member_name) {
-                    panic!("Several parts resolve to the same member key: {}, in {}",
-                            mdata.member_name, 
-                            rule.ast_type);
+                    return Err(KeyError::DuplicateMember(mdata.member_name));
                 }
                 current_members.insert(mdata.member_name);
                 Self::reg_struct_member(
@@ -97,6 +96,7 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
             }
             list.clear();
         }
+        Ok(())
     }
 
     fn build_from_ast_data(
@@ -106,8 +106,8 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
         typed_parts: &HashMap<&'d str, TypedPart<'d>>,
         rule_types: &mut HashMap<&'d str, RuleType<'d>>,
         snake_cased: &mut SnakeCased<'d>,
-    ) {
-        for (_key, ast_data) in ast_data {
+    ) -> Result<(), KeyError<'d>> {
+        for (_key, ast_data) in ast_data.sorted_iter() {
             // Collect types to check if
             // this should be an enum
             let mut types = HashSet::new();
@@ -121,7 +121,7 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
                     &AstRule::PartsRule(ref rule) => {
                         types.insert(rule.ast_type);
                         Self::reg_struct(struct_data, rule.ast_type, snake_cased);
-                        Self::process_parts_rule(rule, struct_data, typed_parts, snake_cased);
+                        Self::process_parts_rule(rule, struct_data, typed_parts, snake_cased)?;
                     }
                 }
             }
@@ -149,6 +149,7 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
                 }
             }
         }
+        Ok(())
     }
 
     fn build_from_list_data(
@@ -158,8 +159,8 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
         typed_parts: &HashMap<&'d str, TypedPart<'d>>,
         rule_types: &mut HashMap<&'d str, RuleType<'d>>,
         snake_cased: &mut SnakeCased<'d>,
-    ) {
-        for (key, list_data) in list_data {
+    ) -> Result<(), KeyError<'d>> {
+        for (key, list_data) in list_data.sorted_iter() {
             snake_cased.reg(key);
             let mut types = HashSet::new();
             list_data.ast_type.map(|t| {
@@ -174,7 +175,7 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
                     &AstRule::PartsRule(ref rule) => {
                         types.insert(rule.ast_type);
                         Self::reg_struct(struct_data, rule.ast_type, snake_cased);
-                        Self::process_parts_rule(rule, struct_data, typed_parts, snake_cased);
+                        Self::process_parts_rule(rule, struct_data, typed_parts, snake_cased)?;
                     }
                 }
             }
@@ -207,6 +208,7 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
                 }
             }
         }
+        Ok(())
     }
 
     pub fn check_simple(&mut self) {
@@ -215,7 +217,7 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
         // it makes the result a bit
         // unpredictable. Maybe some
         // annotations later
-        for (key, enum_data) in &self.data.ast_enums {
+        for (key, enum_data) in self.data.ast_enums.sorted_iter() {
             if enum_data.is_simple(self.data) {
                 self.data.simple_enums.insert(key);
                 for item in &enum_data.items {
@@ -226,7 +228,7 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
         }
     }
 
-    pub fn build_ast(&mut self) {
+    pub fn build_ast(&mut self) -> Result<(), KeyError<'d>> {
         Self::build_from_ast_data(
             &self.data.ast_data,
             &mut self.data.ast_structs,
@@ -234,7 +236,7 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
             &self.data.typed_parts,
             &mut self.data.rule_types,
             &mut self.data.snake_cased,
-        );
+        )?;
         Self::build_from_list_data(
             &self.data.list_data,
             &mut self.data.ast_structs,
@@ -242,12 +244,13 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
             &self.data.typed_parts,
             &mut self.data.rule_types,
             &mut self.data.snake_cased,
-        );
+        )?;
+        self.data.check_types()?;
         self.check_simple();
         // Build parent refs
         // Set member type
-        for (struct_name, struct_data) in &self.data.ast_structs {
-            for (member_name, member) in &struct_data.members {
+        for (struct_name, struct_data) in self.data.ast_structs.sorted_iter() {
+            for (member_name, member) in struct_data.members.sorted_iter() {
                 match self.data.get_ast_key(member.part_key) {
                     Some(_ast_key) => {
                         // Insert struct/enum members
@@ -276,7 +279,7 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
                 }
             }
         }
-        for (enum_name, enum_data) in &self.data.ast_enums {
+        for (enum_name, enum_data) in self.data.ast_enums.sorted_iter() {
             if enum_data.is_simple(self.data) {
                 continue;
             }
@@ -311,8 +314,8 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
         let mut to_box = Vec::new();
         let mut visited = HashMap::new();
         use std::collections::HashSet;
-        for (struct_name, struct_data) in &self.data.ast_structs {
-            for (_member_name, member) in &struct_data.members {
+        for (struct_name, struct_data) in self.data.ast_structs.sorted_iter() {
+            for (_member_name, member) in struct_data.members.sorted_iter() {
                 match self.data.get_ast_key(member.part_key) {
                     Some(ast_key) => {
                         if !visited.contains_key(ast_key) {
@@ -345,11 +348,11 @@ impl<'a, 'd: 'a> BuildAst<'a, 'd> {
                     item_name,
                 } => {
                     let enum_data = self.data.ast_enums.get_mut(enum_name).unwrap();
-                    println!("Added enum box: {}, {}", enum_name, item_name);
                     enum_data.boxed_items.insert(item_name);
                 }
             }
         }
+        Ok(())
     }
 
     fn set_boxed(
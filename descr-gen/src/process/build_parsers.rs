@@ -14,7 +14,7 @@ impl<'a, 'd: 'a> BuildParsers<'a, 'd> {
         BuildParsers { data }
     }
 
-    pub fn process_token(token: &Token<'d>, typed_parts: &'a HashMap<&'d str, TypedPart<'d>>) -> AstRulePart<'d> {
+    pub fn process_token(token: &Token<'d>, typed_parts: &HashMap<&'d str, TypedPart<'d>>) -> AstRulePart<'d> {
         use self::Token::*;
         match token {
             &SimpleTokenItem(ref simple_token) => {
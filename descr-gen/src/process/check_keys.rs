@@ -0,0 +1,55 @@
+use lang_data::data::*;
+use descr_lang::gen::ast::*;
+use descr_lang::gen::visitor::Visitor;
+
+/// Collects token keys that does not
+/// resolve to a rule or builtin token.
+/// Should run after RegisterKeys, and can
+/// be used before GetTokens to report
+/// unknown keys instead of panicing.
+/// The keys are slices of the source,
+/// so their position can be found
+/// from the source buffer.
+pub struct CheckKeys<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+    pub unknown: Vec<&'d str>,
+}
+
+impl<'a, 'd> CheckKeys<'a, 'd> {
+    pub fn new(data: &'a LangData<'d>) -> CheckKeys<'a, 'd> {
+        CheckKeys {
+            data,
+            unknown: Vec::new(),
+        }
+    }
+
+    fn check(&mut self, key: &'d str) {
+        if !self.data.is_known_token(key) {
+            self.unknown.push(key);
+        }
+    }
+}
+
+impl<'a, 'd> Visitor<'d> for CheckKeys<'a, 'd> {
+    fn visit_key_token(&mut self, node: &'d KeyToken) {
+        self.check(node.key);
+    }
+
+    fn visit_list_single(&mut self, node: &'d ListSingle) {
+        self.check(node.sep);
+        self.check(node.reference);
+    }
+
+    fn visit_list_many(&mut self, node: &'d ListMany) {
+        if let Some(sep) = node.sep {
+            self.check(sep);
+        }
+        for item in &node.items {
+            self.visit_list_item(item);
+        }
+    }
+
+    fn visit_ast_ref(&mut self, node: &'d AstRef) {
+        self.check(node.ident);
+    }
+}
@@ -4,10 +4,21 @@ use std::collections::HashSet;
 
 pub struct CodegenAst<'a, 'd: 'a> {
     data: &'a LangData<'d>,
+    derives: &'static str,
 }
 impl<'a, 'd> CodegenAst<'a, 'd> {
     pub fn new(data: &'a LangData<'d>) -> CodegenAst<'a, 'd> {
-        CodegenAst { data }
+        CodegenAst {
+            data,
+            derives: "#[derive(Debug)]\n",
+        }
+    }
+
+    /// Derive Clone for all types, needed
+    /// by memoized parsers
+    pub fn derive_clone(mut self) -> CodegenAst<'a, 'd> {
+        self.derives = "#[derive(Debug, Clone)]\n";
+        self
     }
 
     pub fn gen(&self) -> String {
@@ -19,7 +30,7 @@ impl<'a, 'd> CodegenAst<'a, 'd> {
             if self.data.simple_structs.contains(key) {
                 continue;
             }
-            append!(s, "#[derive(Debug)]\n");
+            s += self.derives;
             append!(s, "pub struct ");
             s = ast_struct.add_type(s, self.data);
             s += " {\n";
@@ -128,7 +139,7 @@ impl<'a, 'd> CodegenAst<'a, 'd> {
         }
         for (key, enum_data) in self.data.ast_enums.sorted_iter() {
             let is_simple = self.data.simple_enums.contains(key);
-            append!(s, "#[derive(Debug)]\n");
+            s += self.derives;
             append!(s, "pub enum ");
             s = enum_data.add_type(s, self.data);
             s += " {\n";
@@ -217,3 +228,47 @@ impl<'a, 'd> CodegenAst<'a, 'd> {
         s
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use process::test_util::*;
+
+    /// Panics unless ast is valid Rust with
+    /// the types given by the parsers
+    fn assert_types(name: &str, outputs: &BTreeMap<String, String>) {
+        let ast = &outputs["ast.rs"];
+        assert_rust(name, ast);
+        let types = item_names(ast);
+        for line in outputs["parsers.rs"].lines().filter(|line| line.starts_with("named!(pub ")) {
+            let type_name = &line[line.find('<').unwrap() + 1..line.find(">,").unwrap()];
+            let type_name = type_name.trim_start_matches("Vec<").trim_end_matches('>');
+            assert!(types.iter().any(|existing| existing == type_name), "{}: no type {}", name, type_name);
+        }
+    }
+
+    #[test]
+    fn defines_types_of_rules() {
+        let outputs = outputs(
+            "Source(items:sourceItems)
+            sourceItems:SourceItem[] WS {
+                Say(\"say\" string?),
+                Group(LPAREN sourceItems RPAREN)
+            }",
+            |_| {},
+        );
+        let ast = &outputs["ast.rs"];
+        assert_types("Test", &outputs);
+        assert_eq!(item_names(ast), ["Group", "Say", "Source", "SourceItem"]);
+        assert!(ast.contains("pub struct Say<'a> {\n    pub string: Option<&'a str>,\n}"));
+        assert!(ast.contains("pub struct Group<'a> {\n    pub source_items: Vec<SourceItem<'a>>,\n}"));
+        assert!(ast.contains("#[derive(Debug)]\npub enum SourceItem<'a> {"));
+    }
+
+    #[test]
+    fn repo_grammars_define_parser_types() {
+        for &(path, _) in &REPO_GRAMMARS {
+            assert_types(path, &outputs(&repo_grammar(path), |_| {}));
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use lang_data::data::*;
+use samples::{SampleOptions, Sampler};
+use process::descr_dependency;
+
+/// Generates a bench crate in bench/, running
+/// descr_common::bench over the generated
+/// parsers, with samples from the grammar in
+/// bench/samples/. Rule times use a copy of
+/// the nom parsers generated with tracing,
+/// in bench/src/traced.rs, recursive descent
+/// parsers are not timed by rule.
+pub struct CodegenBench<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+    recursive_descent: bool,
+    lsp: bool,
+}
+impl<'a, 'd> CodegenBench<'a, 'd> {
+    pub fn new(data: &'a LangData<'d>, recursive_descent: bool, lsp: bool) -> CodegenBench<'a, 'd> {
+        CodegenBench {
+            data,
+            recursive_descent,
+            lsp,
+        }
+    }
+
+    /// Cargo.toml of bench crate, with descr
+    /// crates by path if given. The included
+    /// modules need descr-lsp with the lsp module.
+    pub fn gen_cargo_toml(&self, descr_path: Option<&str>) -> String {
+        let mut s = String::new();
+        s += "[package]\n";
+        append!(s, "name = \"" self.data.lang_id().as_str() "-bench\"\n");
+        s += "version = \"0.1.0\"\n\n";
+        s += "[dependencies]\n";
+        s += "nom = \"^3.2\"\n";
+        s += &descr_dependency("descr-common", descr_path);
+        if self.lsp {
+            s += &descr_dependency("descr-lsp", descr_path);
+        }
+        s += "\n";
+        s += "[profile.release]\n";
+        s += "debug = true\n\n";
+        s += "[workspace]\n";
+        s
+    }
+
+    /// Bench main, includes the generated
+    /// modules from the parent dir
+    pub fn gen_main(&self) -> String {
+        let mut s = String::new();
+        s += "#[allow(unused_imports)]\n";
+        s += "#[macro_use]\n";
+        s += "extern crate descr_common;\n";
+        if self.lsp {
+            s += "extern crate descr_lsp;\n";
+        }
+        s += "extern crate nom;\n\n";
+        s += "#[path = \"../../mod.rs\"]\n";
+        s += "#[allow(dead_code)]\n";
+        s += "mod lang;\n";
+        if !self.recursive_descent {
+            s += "#[allow(dead_code)]\n";
+            s += "mod traced;\n\n";
+            s += "use lang::ast;\n";
+        } else {
+            s += "\n";
+        }
+        s += "use descr_common::bench::{self, CountingAlloc};\n\n";
+        s += "#[global_allocator]\n";
+        s += "static ALLOC: CountingAlloc = CountingAlloc;\n\n";
+        s += "fn parse(input: &[u8]) -> bool {\n";
+        if self.recursive_descent {
+            s += "    lang::parsers::parse(input).is_ok()\n";
+        } else {
+            s += "    done(lang::parsers::start(input))\n";
+        }
+        s += "}\n\n";
+        if !self.recursive_descent {
+            s += "fn parse_traced(input: &[u8]) -> bool {\n";
+            s += "    done(traced::start(input))\n";
+            s += "}\n\n";
+            s += "fn done<T>(res: nom::IResult<&[u8], T>) -> bool {\n";
+            s += "    match res {\n";
+            s += "        nom::IResult::Done(rest, _) => rest.iter().all(|c| (*c as char).is_whitespace()),\n";
+            s += "        _ => false,\n";
+            s += "    }\n";
+            s += "}\n\n";
+        }
+        s += "fn main() {\n";
+        s += "    let args = std::env::args().skip(1).collect::<Vec<_>>();\n";
+        s += "    let samples = concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/samples\");\n";
+        if !self.recursive_descent {
+            s += "    bench::run(&args, samples, parse, Some(parse_traced));\n";
+        } else {
+            s += "    bench::run(&args, samples, parse, None);\n";
+        }
+        s += "}\n";
+        s
+    }
+
+    /// Sample files by name, the same
+    /// for each run
+    pub fn gen_samples(&self, count: usize) -> Vec<(String, String)> {
+        let mut options = SampleOptions::new();
+        options.max_list_len = 8;
+        Sampler::new(self.data, options)
+            .samples(count)
+            .into_iter()
+            .enumerate()
+            .map(|(i, sample)| (format!("sample-{:03}.{}", i + 1, self.data.lang_id()), sample))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use interpreter::Interpreter;
+    use process::test_util::*;
+
+    const GRAMMAR: &str = "Source(items)
+        items:Item[] {
+            Say(\"say\" string),
+            Num(int)
+        }";
+
+    #[test]
+    fn generates_bench_crate() {
+        let bench = outputs(GRAMMAR, |options| options.bench = true);
+        let main = &bench["bench/src/main.rs"];
+        assert_rust("main.rs", main);
+        assert_eq!(item_names(main), ["lang", "traced", "parse", "parse_traced", "done", "main"]);
+        assert!(main.contains("static ALLOC: CountingAlloc = CountingAlloc;"));
+        assert!(main.contains("bench::run(&args, samples, parse, Some(parse_traced));"));
+        // Rule times come from a traced copy of the parsers
+        let traced = outputs(GRAMMAR, |options| options.trace = true);
+        assert_eq!(bench["bench/src/traced.rs"], traced["parsers.rs"]);
+        assert!(!bench["parsers.rs"].contains("trace_wrap!"));
+        assert!(bench["bench/Cargo.toml"].contains("name = \"test-bench\""));
+        // Version dependency without a checkout
+        assert!(bench["bench/Cargo.toml"].contains("\ndescr-common = \"0.1.0\"\n"));
+        // No rule times for recursive descent
+        let rd = outputs(GRAMMAR, |options| {
+            options.bench = true;
+            options.recursive_descent = true;
+        });
+        assert_rust("main.rs", &rd["bench/src/main.rs"]);
+        assert!(rd["bench/src/main.rs"].contains("bench::run(&args, samples, parse, None);"));
+        assert!(!rd.contains_key("bench/src/traced.rs"));
+    }
+
+    #[test]
+    fn depends_on_descr_lsp_with_lsp_module() {
+        // The bench includes mod.rs, which has the lsp module
+        let bench = outputs(GRAMMAR, |options| {
+            options.bench = true;
+            options.lsp = true;
+            options.descr_path = Some("../descr".to_string());
+        });
+        assert!(bench["mod.rs"].contains("pub mod lsp;"));
+        assert!(bench["bench/Cargo.toml"].contains("\ndescr-lsp = { path = \"../descr/descr-lsp\" }\n"));
+        let main = &bench["bench/src/main.rs"];
+        assert_rust("main.rs", main);
+        assert!(main.contains("extern crate descr_lsp;\n"));
+        let bench = outputs(GRAMMAR, |options| options.bench = true);
+        assert!(!bench["bench/Cargo.toml"].contains("descr-lsp"));
+        assert!(!bench["bench/src/main.rs"].contains("descr_lsp"));
+    }
+
+    /// Panics unless the bench samples of
+    /// grammar parse with the interpreter
+    fn assert_samples_parse(name: &str, grammar: &str) {
+        let outputs = outputs(grammar, |options| options.bench = true);
+        let samples = outputs
+            .iter()
+            .filter(|&(file, _)| file.starts_with("bench/samples/"))
+            .collect::<Vec<_>>();
+        assert_eq!(samples.len(), 100);
+        with_data(grammar, |data| {
+            let interpreter = Interpreter::new(data);
+            for (file, sample) in samples {
+                if let Err(err) = interpreter.parse_complete(sample.as_bytes()) {
+                    panic!("{}: {} doesn't parse: {}\n{}", name, file, err, sample);
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn samples_parse_with_the_grammar() {
+        assert_samples_parse("Test", GRAMMAR);
+        for &(path, _) in &REPO_GRAMMARS {
+            assert_samples_parse(path, &repo_grammar(path));
+        }
+    }
+}
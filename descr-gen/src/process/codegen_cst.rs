@@ -0,0 +1,694 @@
+use lang_data::data::*;
+use lang_data::rule::*;
+use lang_data::ast::*;
+use lang_data::typed_part::TypedPart;
+use descr_common::util::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Generates a lossless syntax tree layer,
+/// with a SyntaxKind enum, parsers building
+/// green nodes with descr_common::cst, and
+/// typed views over syntax nodes.
+/// The parsers follow the generated nom
+/// parsers, but keep whitespace and token
+/// text, so the tree prints the input.
+pub struct CodegenCst<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+    // Token text to kind name
+    literals: BTreeMap<String, String>,
+}
+impl<'a, 'd> CodegenCst<'a, 'd> {
+    pub fn new(data: &'a LangData<'d>) -> CodegenCst<'a, 'd> {
+        let mut gen = CodegenCst {
+            data,
+            literals: BTreeMap::new(),
+        };
+        gen.collect_literals();
+        gen
+    }
+
+    /// Kind for rules and ast types
+    pub fn kind_name(key: &str) -> String {
+        let mut chars = key.chars();
+        match chars.next() {
+            Some(c) => c.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }
+
+    /// Kind for char and tag tokens, keywords
+    /// get _KW, other tokens are named by chars
+    fn literal_name(text: &str) -> String {
+        if text.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return text.to_uppercase() + "_KW";
+        }
+        let mut parts = Vec::new();
+        let mut word = String::new();
+        for c in text.chars() {
+            if c.is_alphanumeric() {
+                word.push(c);
+                continue;
+            }
+            if !word.is_empty() {
+                parts.push(word.to_uppercase());
+                word.clear();
+            }
+            parts.push(match c {
+                '(' => "LPAREN".to_string(),
+                ')' => "RPAREN".to_string(),
+                '{' => "LBRACE".to_string(),
+                '}' => "RBRACE".to_string(),
+                '[' => "LBRACKET".to_string(),
+                ']' => "RBRACKET".to_string(),
+                ',' => "COMMA".to_string(),
+                ':' => "COLON".to_string(),
+                ';' => "SEMICOLON".to_string(),
+                '=' => "EQUAL".to_string(),
+                '<' => "LT".to_string(),
+                '>' => "GT".to_string(),
+                '*' => "STAR".to_string(),
+                '!' => "EXCL".to_string(),
+                '.' => "DOT".to_string(),
+                '?' => "QUESTION".to_string(),
+                '"' => "QUOTE".to_string(),
+                '\'' => "SQUOTE".to_string(),
+                '+' => "PLUS".to_string(),
+                '-' => "MINUS".to_string(),
+                '/' => "SLASH".to_string(),
+                '\\' => "BACKSLASH".to_string(),
+                '@' => "AT".to_string(),
+                '#' => "HASH".to_string(),
+                '$' => "DOLLAR".to_string(),
+                '%' => "PERCENT".to_string(),
+                '&' => "AMP".to_string(),
+                '|' => "PIPE".to_string(),
+                '^' => "CARET".to_string(),
+                '~' => "TILDE".to_string(),
+                '`' => "BACKTICK".to_string(),
+                '_' => "UNDERSCORE".to_string(),
+                other => format!("U{:X}", other as u32),
+            });
+        }
+        if !word.is_empty() {
+            parts.push(word.to_uppercase());
+        }
+        parts.join("_")
+    }
+
+    fn add_literal(&mut self, text: &str) {
+        if !self.literals.contains_key(text) {
+            let name = Self::literal_name(text);
+            let mut unique = name.clone();
+            let mut i = 2;
+            while self.literals.values().any(|existing| *existing == unique) {
+                unique = format!("{}{}", name, i);
+                i += 1;
+            }
+            self.literals.insert(text.to_string(), unique);
+        }
+    }
+
+    fn collect_literals(&mut self) {
+        let data = self.data;
+        let mut texts = Vec::new();
+        for ast_data in data.ast_data.values() {
+            for rule in &ast_data.rules {
+                Self::collect_rule_literals(rule, data, &mut texts);
+            }
+        }
+        for list_data in data.list_data.values() {
+            for rule in &list_data.rules {
+                Self::collect_rule_literals(&rule.ast_rule, data, &mut texts);
+            }
+            if let Some(sep) = list_data.sep {
+                Self::collect_key_literal(sep, data, &mut texts);
+            }
+        }
+        texts.sort();
+        for text in texts {
+            self.add_literal(&text);
+        }
+    }
+
+    fn collect_rule_literals(rule: &AstRule, data: &LangData, texts: &mut Vec<String>) {
+        if let &AstRule::PartsRule(ref parts_rule) = rule {
+            Self::collect_part_literals(&parts_rule.parts, data, texts);
+        }
+    }
+
+    fn collect_part_literals(parts: &[AstRulePart], data: &LangData, texts: &mut Vec<String>) {
+        for part in parts {
+            match &part.token {
+                &AstRuleToken::Key(key) => Self::collect_key_literal(key, data, texts),
+                &AstRuleToken::Tag(tag) => texts.push(tag.to_string()),
+                &AstRuleToken::Group(ref parts) => Self::collect_part_literals(parts, data, texts),
+                &AstRuleToken::Func(..) => {}
+            }
+        }
+    }
+
+    fn collect_key_literal(key: &str, data: &LangData, texts: &mut Vec<String>) {
+        match data.typed_parts.get(key) {
+            Some(&TypedPart::CharPart { chr, .. }) => texts.push(chr.to_string()),
+            Some(&TypedPart::TagPart { tag, .. }) => texts.push(tag.to_string()),
+            _ => {}
+        }
+    }
+
+    pub fn literal_kind(&self, text: &str) -> &str {
+        self.literals.get(text).map(|name| name.as_str()).unwrap_or("TEXT")
+    }
+
+    /// Token text to kind name
+    pub fn literals(&self) -> &BTreeMap<String, String> {
+        &self.literals
+    }
+
+    /// Parts rules of ast data and list
+    /// items, which have kinds of their own
+    pub fn parts_rules(&self) -> Vec<&'a AstPartsRule<'d>> {
+        let mut rules = Vec::new();
+        for (_, ast_data) in self.data.ast_data.sorted_iter() {
+            for rule in &ast_data.rules {
+                if let &AstRule::PartsRule(ref parts_rule) = rule {
+                    rules.push(parts_rule);
+                }
+            }
+        }
+        for (_, list_data) in self.data.list_data.sorted_iter() {
+            for rule in &list_data.rules {
+                if let &AstRule::PartsRule(ref parts_rule) = &rule.ast_rule {
+                    rules.push(parts_rule);
+                }
+            }
+        }
+        rules
+    }
+
+    /// Rule keys and ast types
+    fn rule_kinds(&self) -> BTreeSet<String> {
+        let mut kinds = BTreeSet::new();
+        for (key, ast_data) in &self.data.ast_data {
+            kinds.insert(Self::kind_name(key));
+            for rule in &ast_data.rules {
+                if let &AstRule::PartsRule(ref parts_rule) = rule {
+                    kinds.insert(Self::kind_name(parts_rule.ast_type));
+                }
+            }
+        }
+        for (key, list_data) in &self.data.list_data {
+            kinds.insert(Self::kind_name(key));
+            for rule in &list_data.rules {
+                if let &AstRule::PartsRule(ref parts_rule) = &rule.ast_rule {
+                    kinds.insert(Self::kind_name(parts_rule.ast_type));
+                }
+            }
+        }
+        kinds
+    }
+
+    pub fn gen(&self) -> String {
+        let mut s = String::with_capacity(
+            self.data.ast_data.len() * 300 + self.data.list_data.len() * 300,
+        );
+        s += "#[allow(unused_imports)]\n";
+        s += "use descr_common::cst::*;\n\n";
+        s = self.gen_kinds(s);
+        s += "pub type Node = SyntaxNode<SyntaxKind>;\n\n";
+        if let Some(start_key) = self.data.start_key {
+            s += "/// Lossless tree of input. The root has the\n";
+            s += "/// start node and trailing whitespace.\n";
+            s += "pub fn parse(input: &[u8]) -> Result<Node, CstError> {\n";
+            append!(s, "    ::descr_common::cst::parse(input, |c| " self.data.sc(start_key) "(c, None))\n");
+            s += "}\n\n";
+        }
+        s = self.gen_parsers(s);
+        s = self.gen_views(s);
+        s
+    }
+
+    fn gen_kinds(&self, mut s: String) -> String {
+        s += "#[allow(non_camel_case_types)]\n";
+        s += "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n";
+        s += "pub enum SyntaxKind {\n";
+        for builtin in &["ROOT", "WHITESPACE", "TEXT", "IDENT", "INT", "STRING"] {
+            append!(s 1, builtin ",\n");
+        }
+        for (text, name) in &self.literals {
+            append!(s 1, "/// " text "\n");
+            append!(s 1, name ",\n");
+        }
+        for kind in self.rule_kinds() {
+            append!(s 1, &kind ",\n");
+        }
+        s += "}\n\n";
+        s += "impl CstKind for SyntaxKind {\n";
+        s += "    fn root() -> SyntaxKind {\n        SyntaxKind::ROOT\n    }\n";
+        s += "    fn whitespace() -> SyntaxKind {\n        SyntaxKind::WHITESPACE\n    }\n";
+        s += "    fn text() -> SyntaxKind {\n        SyntaxKind::TEXT\n    }\n";
+        s += "}\n\n";
+        s
+    }
+
+    fn gen_parsers(&self, mut s: String) -> String {
+        for (key, ast_data) in self.data.ast_data.sorted_iter() {
+            append!(s, "pub fn " self.data.sc(key) "(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {\n");
+            s = self.gen_alt(s, ast_data.rules.iter().collect());
+            s += "}\n\n";
+        }
+        for (key, list_data) in self.data.list_data.sorted_iter() {
+            let sc = self.data.sc(key);
+            append!(s, "pub fn " sc "(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {\n");
+            append!(s 1, "c.node(SyntaxKind::" Self::kind_name(key).as_str() ", label, |c| ");
+            let sep = list_data
+                .sep
+                .and_then(|sep| self.data.typed_parts.get(sep))
+                .and_then(|typed_part| match typed_part {
+                    &TypedPart::WSPart => None,
+                    typed_part => Some(typed_part),
+                });
+            match sep {
+                Some(sep) => {
+                    s += "c.separated_list(|c| ";
+                    s = self.gen_typed_part(s, sep, "None");
+                    append!(s, ", |c| " sc "_item(c, None)))\n");
+                }
+                None => {
+                    append!(s, "c.many0(|c| " sc "_item(c, None)))\n");
+                }
+            }
+            s += "}\n\n";
+            append!(s, "fn " sc "_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {\n");
+            s = self.gen_alt(s, list_data.rules.iter().map(|rule| &rule.ast_rule).collect());
+            s += "}\n\n";
+        }
+        s
+    }
+
+    /// Function body, alt_complete! when
+    /// there are several rules
+    fn gen_alt(&self, mut s: String, rules: Vec<&AstRule<'d>>) -> String {
+        if rules.len() == 1 {
+            indent!(s 1);
+            s = self.gen_rule(s, rules[0], 1);
+            s += "\n";
+        } else {
+            for rule in rules {
+                append!(s 1, "if c.attempt(|c| ");
+                s = self.gen_rule(s, rule, 1);
+                s += ").is_some() {\n";
+                append!(s 2, "return Ok(());\n");
+                append!(s 1, "}\n");
+            }
+            append!(s 1, "Err(Fail::Error)\n");
+        }
+        s
+    }
+
+    fn gen_rule(&self, mut s: String, rule: &AstRule<'d>, level: usize) -> String {
+        match rule {
+            &AstRule::RefRule(rule_ref) => {
+                append!(s, self.data.sc(rule_ref) "(c, label)");
+            }
+            &AstRule::PartsRule(ref parts_rule) => {
+                append!(s, "c.node(SyntaxKind::" Self::kind_name(parts_rule.ast_type).as_str() ", label, |c| {\n");
+                for part in &parts_rule.parts {
+                    s = self.gen_part(s, part, level + 1);
+                }
+                indent!(s level + 1);
+                s += "Ok(())\n";
+                indent!(s level);
+                s += "})";
+            }
+        }
+        s
+    }
+
+    /// Label argument for member key
+    pub fn label(&self, member_key: Option<&str>) -> String {
+        match member_key {
+            Some(member_key) => format!("Some(\"{}\")", self.data.sc(member_key)),
+            None => "None".to_string(),
+        }
+    }
+
+    /// Statement for part, mirrors gen_part_parser
+    fn gen_part(&self, mut s: String, part: &AstRulePart<'d>, level: usize) -> String {
+        if let &AstRuleToken::Group(ref parts) = &part.token {
+            if part.not || part.optional {
+                indent!(s level);
+                if part.not {
+                    s += "c.token(SyntaxKind::TEXT, None, |c| c.until(|c| {\n";
+                } else {
+                    s += "c.opt(|c| {\n";
+                }
+                for part in parts {
+                    s = self.gen_part(s, part, level + 1);
+                }
+                indent!(s level + 1);
+                s += "Ok(())\n";
+                indent!(s level);
+                if part.not {
+                    s += "}))?;\n";
+                } else {
+                    s += "})?;\n";
+                }
+            } else {
+                for part in parts {
+                    s = self.gen_part(s, part, level);
+                }
+            }
+            return s;
+        }
+        let label = self.label(part.member_key);
+        indent!(s level);
+        if !part.optional && !part.not {
+            s += "c.sp();\n";
+            indent!(s level);
+        }
+        if part.not {
+            append!(s, "c.token(SyntaxKind::TEXT, " label.as_str() ", |c| c.until(|c| ");
+            if part.optional {
+                s += "c.opt(|c| {\n";
+                indent!(s level + 1);
+                s += "c.sp();\n";
+                indent!(s level + 1);
+                s = self.gen_token(s, &part.token, "None", level + 1);
+                s += "\n";
+                indent!(s level);
+                s += "})";
+            } else {
+                s = self.gen_token(s, &part.token, "None", level);
+            }
+            s += "))?;\n";
+        } else if part.optional {
+            s += "c.opt(|c| {\n";
+            indent!(s level + 1);
+            s += "c.sp();\n";
+            indent!(s level + 1);
+            s = self.gen_token(s, &part.token, &label, level + 1);
+            s += "\n";
+            indent!(s level);
+            s += "})?;\n";
+        } else {
+            s = self.gen_token(s, &part.token, &label, level);
+            s += "?;\n";
+        }
+        s
+    }
+
+    /// Expression giving Res
+    fn gen_token(&self, mut s: String, token: &AstRuleToken<'d>, label: &str, level: usize) -> String {
+        match token {
+            &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                Some(typed_part) => self.gen_typed_part(s, typed_part, label),
+                None => s + "Res::Err(Fail::Error)",
+            },
+            &AstRuleToken::Tag(tag) => {
+                append!(s, "c.token(SyntaxKind::" self.literal_kind(tag) ", " label ", |c| c.tag(\"" tag "\"))");
+                s
+            }
+            // Functions are nom macros, which
+            // can't build tokens
+            &AstRuleToken::Func(..) => s + "Res::Err(Fail::Error)",
+            &AstRuleToken::Group(ref parts) => {
+                s += "{\n";
+                for part in parts {
+                    s = self.gen_part(s, part, level + 1);
+                }
+                indent!(s level + 1);
+                s += "Ok(())\n";
+                indent!(s level);
+                s += "}";
+                s
+            }
+        }
+    }
+
+    fn gen_typed_part(&self, mut s: String, typed_part: &TypedPart, label: &str) -> String {
+        use lang_data::typed_part::TypedPart::*;
+        match typed_part {
+            &AstPart { key } | &ListPart { key } => {
+                append!(s, self.data.sc(key) "(c, " label ")");
+            }
+            &CharPart { chr, .. } => {
+                let text = chr.to_string();
+                append!(s, "c.token(SyntaxKind::" self.literal_kind(&text) ", " label ", |c| c.chr(" format!("{:?}", chr).as_str() "))");
+            }
+            &TagPart { tag, .. } => {
+                append!(s, "c.token(SyntaxKind::" self.literal_kind(tag) ", " label ", |c| c.tag(\"" tag "\"))");
+            }
+            &IntPart { .. } => {
+                append!(s, "c.token(SyntaxKind::INT, " label ", |c| c.int())");
+            }
+            &IdentPart { .. } => {
+                append!(s, "c.token(SyntaxKind::IDENT, " label ", |c| c.ident())");
+            }
+            &StringPart { .. } | &StrPart { .. } => {
+                append!(s, "c.token(SyntaxKind::STRING, " label ", |c| c.string())");
+            }
+            &FnPart { .. } => s += "Res::Err(Fail::Error)",
+            &WSPart => {
+                append!(s, "c.token(SyntaxKind::WHITESPACE, " label ", |c| c.ws())");
+            }
+        }
+        s
+    }
+
+    fn view_name(name: &str) -> String {
+        Self::kind_name(name) + "Node"
+    }
+
+    fn gen_views(&self, mut s: String) -> String {
+        // Enum items without members are
+        // not in ast structs
+        let mut struct_names = BTreeSet::new();
+        for (key, _) in self.data.ast_structs.sorted_iter() {
+            struct_names.insert(*key);
+        }
+        for (_, ast_enum) in self.data.ast_enums.sorted_iter() {
+            for item in &ast_enum.items {
+                if !self.data.ast_enums.contains_key(item) {
+                    struct_names.insert(*item);
+                }
+            }
+        }
+        for name in struct_names {
+            s = self.gen_struct_view(s, name, self.data.ast_structs.get(name));
+        }
+        for (_, ast_enum) in self.data.ast_enums.sorted_iter() {
+            s = self.gen_enum_view(s, ast_enum);
+        }
+        s
+    }
+
+    fn gen_struct_view(&self, mut s: String, name: &str, ast_struct: Option<&AstStruct<'d>>) -> String {
+        let view = Self::view_name(name);
+        s += "#[derive(Debug, Clone)]\n";
+        append!(s, "pub struct " view.as_str() " {\n");
+        s += "    pub syntax: Node,\n";
+        s += "}\n\n";
+        s += "#[allow(dead_code)]\n";
+        append!(s, "impl " view.as_str() " {\n");
+        append!(s 1, "pub fn cast(syntax: Node) -> Option<" view.as_str() "> {\n");
+        append!(s 2, "if syntax.kind() == SyntaxKind::" Self::kind_name(name).as_str() " {\n");
+        append!(s 3, "Some(" view.as_str() " { syntax })\n");
+        append!(s 2, "} else {\n");
+        append!(s 3, "None\n");
+        append!(s 2, "}\n");
+        append!(s 1, "}\n\n");
+        append!(s 1, "pub fn syntax(&self) -> &Node {\n");
+        append!(s 2, "&self.syntax\n");
+        append!(s 1, "}\n");
+        if let Some(ast_struct) = ast_struct {
+            for member_name in &ast_struct.members_ordered {
+                let member = ast_struct.members.get(member_name).expect("Could not find struct member");
+                s += "\n";
+                s = self.gen_accessor(s, member);
+            }
+        }
+        s += "}\n\n";
+        s
+    }
+
+    fn gen_accessor(&self, mut s: String, member: &AstStructMember<'d>) -> String {
+        let sc = member.sc();
+        match member.tpe {
+            AstMemberType::KeyedToken(part_key) => match self.data.typed_parts.get(part_key) {
+                Some(&TypedPart::AstPart { key }) => {
+                    let view = Self::view_name(self.type_name(key));
+                    append!(s 1, "pub fn " sc "(&self) -> Option<" view.as_str() "> {\n");
+                    append!(s 2, "self.syntax.child_node(\"" sc "\").and_then(" view.as_str() "::cast)\n");
+                    append!(s 1, "}\n");
+                }
+                Some(&TypedPart::ListPart { key }) => {
+                    let view = Self::view_name(self.type_name(key));
+                    append!(s 1, "pub fn " sc "(&self) -> Vec<" view.as_str() "> {\n");
+                    append!(s 2, "match self.syntax.child_node(\"" sc "\") {\n");
+                    append!(s 3, "Some(list) => list.child_nodes().into_iter().filter_map(" view.as_str() "::cast).collect(),\n");
+                    append!(s 3, "None => Vec::new(),\n");
+                    append!(s 2, "}\n");
+                    append!(s 1, "}\n");
+                }
+                Some(&TypedPart::IntPart { .. }) => {
+                    append!(s 1, "pub fn " sc "(&self) -> Option<u32> {\n");
+                    append!(s 2, "self.syntax.child_token(\"" sc "\").and_then(|token| token.text().parse().ok())\n");
+                    append!(s 1, "}\n");
+                }
+                Some(&TypedPart::StringPart { .. }) | Some(&TypedPart::StrPart { .. }) => {
+                    append!(s 1, "/// String without quotes\n");
+                    append!(s 1, "pub fn " sc "(&self) -> Option<String> {\n");
+                    append!(s 2, "self.syntax.child_token(\"" sc "\").map(|token| {\n");
+                    append!(s 3, "let text = token.text();\n");
+                    append!(s 3, "text[1..text.len() - 1].to_string()\n");
+                    append!(s 2, "})\n");
+                    append!(s 1, "}\n");
+                }
+                Some(&TypedPart::CharPart { .. }) | Some(&TypedPart::TagPart { .. }) => {
+                    s = Self::gen_bool_accessor(s, sc);
+                }
+                _ => {
+                    s = Self::gen_text_accessor(s, sc);
+                }
+            },
+            AstMemberType::TagBool(..) => {
+                s = Self::gen_bool_accessor(s, sc);
+            }
+            AstMemberType::NotString => {
+                s = Self::gen_text_accessor(s, sc);
+            }
+        }
+        s
+    }
+
+    fn gen_bool_accessor(mut s: String, sc: &str) -> String {
+        append!(s 1, "pub fn " sc "(&self) -> bool {\n");
+        append!(s 2, "self.syntax.child(\"" sc "\").is_some()\n");
+        append!(s 1, "}\n");
+        s
+    }
+
+    fn gen_text_accessor(mut s: String, sc: &str) -> String {
+        append!(s 1, "pub fn " sc "(&self) -> Option<String> {\n");
+        append!(s 2, "self.syntax.child_token(\"" sc "\").map(|token| token.text().to_string())\n");
+        append!(s 1, "}\n");
+        s
+    }
+
+    fn type_name<'k>(&'k self, key: &'k str) -> &'k str {
+        match self.data.rule_types.get(key) {
+            Some(rule_type) => rule_type.get_type_name(self.data),
+            None => key,
+        }
+    }
+
+    fn gen_enum_view(&self, mut s: String, ast_enum: &AstEnum<'d>) -> String {
+        let view = Self::view_name(ast_enum.name);
+        s += "#[derive(Debug, Clone)]\n";
+        append!(s, "pub enum " view.as_str() " {\n");
+        for item in &ast_enum.items {
+            append!(s 1, item "(" Self::view_name(item).as_str() "),\n");
+        }
+        s += "}\n\n";
+        s += "#[allow(dead_code)]\n";
+        append!(s, "impl " view.as_str() " {\n");
+        append!(s 1, "pub fn cast(syntax: Node) -> Option<" view.as_str() "> {\n");
+        for item in &ast_enum.items {
+            append!(s 2, "if let Some(node) = " Self::view_name(item).as_str() "::cast(syntax.clone()) {\n");
+            append!(s 3, "return Some(" view.as_str() "::" item "(node));\n");
+            append!(s 2, "}\n");
+        }
+        append!(s 2, "None\n");
+        append!(s 1, "}\n\n");
+        append!(s 1, "pub fn syntax(&self) -> &Node {\n");
+        append!(s 2, "match self {\n");
+        for item in &ast_enum.items {
+            append!(s 3, "&" view.as_str() "::" item "(ref node) => node.syntax(),\n");
+        }
+        append!(s 2, "}\n");
+        append!(s 1, "}\n");
+        s += "}\n\n";
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use process::test_util::*;
+
+    /// Variants of SyntaxKind in cst
+    fn kinds(cst: &str) -> Vec<String> {
+        let start = cst.find("pub enum SyntaxKind {\n").unwrap();
+        let end = start + cst[start..].find("}\n").unwrap();
+        cst[start..end]
+            .lines()
+            .skip(1)
+            .map(|line| line.trim())
+            .filter(|line| !line.starts_with("///"))
+            .map(|line| line.trim_end_matches(',').to_string())
+            .collect()
+    }
+
+    /// Panics unless cst is valid Rust with a
+    /// parser per rule and distinct kinds
+    fn assert_cst(name: &str, cst: &str, rules: &[&str]) {
+        assert_rust(name, cst);
+        let kinds = kinds(cst);
+        let mut distinct = kinds.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(kinds.len(), distinct.len(), "{}: duplicate kinds", name);
+        assert_kinds_defined(name, cst, cst);
+        let items = item_names(cst);
+        for rule in rules {
+            assert!(items.iter().any(|item| item == rule), "{}: no parser {}", name, rule);
+        }
+    }
+
+    #[test]
+    fn names_literals() {
+        assert_eq!(CodegenCst::literal_name("let"), "LET_KW");
+        assert_eq!(CodegenCst::literal_name("(*"), "LPAREN_STAR");
+        assert_eq!(CodegenCst::literal_name("->"), "MINUS_GT");
+        assert_eq!(CodegenCst::literal_name("a-b"), "A_MINUS_B");
+        assert_eq!(CodegenCst::literal_name("\u{e9}\u{a7}"), "\u{c9}_UA7");
+        assert_eq!(CodegenCst::kind_name("sourceItems"), "SourceItems");
+    }
+
+    #[test]
+    fn generates_kinds_parsers_and_views() {
+        let outputs = outputs(
+            "Source(items:sourceItems)
+            sourceItems:SourceItem[] WS {
+                Let(\"let\" name:ident EQUAL int),
+                Arrow(\"->\" int),
+                Gt(\"-gt\" int)
+            }",
+            |options| options.cst = true,
+        );
+        let cst = &outputs["cst.rs"];
+        assert_cst("cst.rs", cst, &["parse", "source", "source_items"]);
+        let kinds = kinds(cst);
+        assert_eq!(&kinds[..6], ["ROOT", "WHITESPACE", "TEXT", "IDENT", "INT", "STRING"]);
+        // Same names from a tag and from chars are made distinct
+        assert!(cst.contains("    /// ->\n    MINUS_GT,\n"));
+        assert!(cst.contains("    /// -gt\n    MINUS_GT2,\n"));
+        for kind in &["LET_KW", "EQUAL", "Source", "SourceItems", "Let", "Arrow"] {
+            assert!(kinds.contains(&kind.to_string()), "No kind {}", kind);
+        }
+        assert!(cst.contains("pub fn parse(input: &[u8]) -> Result<Node, CstError> {"));
+        // Typed views over nodes
+        let items = item_names(cst);
+        assert!(items.contains(&"LetNode".to_string()) && items.contains(&"SourceItemNode".to_string()));
+    }
+
+    #[test]
+    fn repo_grammars_have_distinct_kinds() {
+        for &(path, _) in &REPO_GRAMMARS {
+            let outputs = outputs(&repo_grammar(path), |options| options.cst = true);
+            assert_cst(path, &outputs["cst.rs"], &["parse"]);
+            assert_modules(&outputs);
+        }
+    }
+}
@@ -0,0 +1,526 @@
+use lang_data::data::*;
+use lang_data::rule::*;
+use lang_data::typed_part::TypedPart;
+use descr_common::util::*;
+
+/// Rule expression, shared by the
+/// ebnf and railroad outputs
+#[derive(Debug, Clone, PartialEq)]
+pub enum GrammarExpr {
+    Terminal(String),
+    NonTerminal(String),
+    /// Described in words, ebnf ? special ?
+    Special(String),
+    Sequence(Vec<GrammarExpr>),
+    Choice(Vec<GrammarExpr>),
+    Optional(Box<GrammarExpr>),
+    /// Zero or more, with optional separator
+    Repeat(Box<GrammarExpr>, Option<Box<GrammarExpr>>),
+    /// Any text not containing expression,
+    /// from not parts
+    Until(Box<GrammarExpr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrammarRule {
+    pub name: String,
+    pub expr: GrammarExpr,
+}
+
+/// Collects rules from lang data, with builtin
+/// tokens last, and writes them as ISO 14977 ebnf.
+/// Rules keep their names from the grammar.
+/// Ast types given by several rules, like
+/// enum items, become a choice.
+pub struct CodegenEbnf<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+}
+impl<'a, 'd> CodegenEbnf<'a, 'd> {
+    pub fn new(data: &'a LangData<'d>) -> CodegenEbnf<'a, 'd> {
+        CodegenEbnf { data }
+    }
+
+    /// Rule keys, start rule first
+    fn rule_keys(&self) -> Vec<&'d str> {
+        let mut keys = Vec::new();
+        if let Some(start_key) = self.data.start_key {
+            keys.push(start_key);
+        }
+        for (key, _) in self.data.ast_data.sorted_iter() {
+            if !keys.contains(key) {
+                keys.push(*key);
+            }
+        }
+        for (key, _) in self.data.list_data.sorted_iter() {
+            if !keys.contains(key) {
+                keys.push(*key);
+            }
+        }
+        keys
+    }
+
+    pub fn rules(&self) -> Vec<GrammarRule> {
+        let mut rules = Vec::new();
+        let mut builtins = Vec::new();
+        for key in self.rule_keys() {
+            self.add_rules(&mut rules, key, &mut builtins);
+        }
+        for builtin in Self::builtin_rules(&builtins) {
+            if !rules.iter().any(|rule| rule.name == builtin.name) {
+                rules.push(builtin);
+            }
+        }
+        // Character is only used by until and strings
+        let uses_character = rules
+            .iter()
+            .any(|rule| Self::has_until(&rule.expr) || Self::refers(&rule.expr, "character"));
+        if !uses_character {
+            rules.retain(|rule| rule.name != "character");
+        }
+        rules
+    }
+
+    fn add_rule(rules: &mut Vec<GrammarRule>, name: &str, expr: GrammarExpr) {
+        match rules.iter().position(|rule| rule.name == name) {
+            Some(i) => {
+                let rule = &mut rules[i];
+                match rule.expr {
+                    GrammarExpr::Choice(ref mut items) => {
+                        if !items.contains(&expr) {
+                            items.push(expr);
+                        }
+                        return;
+                    }
+                    ref existing if *existing == expr => return,
+                    _ => {}
+                }
+                let existing = rule.expr.clone();
+                rule.expr = GrammarExpr::Choice(vec![existing, expr]);
+            }
+            None => rules.push(GrammarRule {
+                name: name.to_string(),
+                expr,
+            }),
+        }
+    }
+
+    fn add_rules(&self, rules: &mut Vec<GrammarRule>, key: &'d str, builtins: &mut Vec<&'static str>) {
+        let item_rules = if let Some(ast_data) = self.data.ast_data.get(key) {
+            let item_rules = ast_data.rules.iter().collect::<Vec<_>>();
+            match ast_data.rules.first() {
+                Some(&AstRule::PartsRule(ref parts_rule))
+                    if ast_data.rules.len() == 1 && parts_rule.ast_type == key => {}
+                // Enum alternatives
+                _ => Self::add_rule(rules, key, self.rule_choice(&item_rules)),
+            }
+            item_rules
+        } else if let Some(list_data) = self.data.list_data.get(key) {
+            let item_rules = list_data
+                .rules
+                .iter()
+                .map(|rule| &rule.ast_rule)
+                .collect::<Vec<_>>();
+            let sep = list_data
+                .sep
+                .and_then(|sep| match self.data.typed_parts.get(sep) {
+                    Some(&TypedPart::WSPart) | None => None,
+                    Some(typed_part) => Some(Box::new(self.typed_part_expr(typed_part, builtins))),
+                });
+            let item = self.rule_choice(&item_rules);
+            Self::add_rule(rules, key, GrammarExpr::Repeat(Box::new(item), sep));
+            item_rules
+        } else {
+            return;
+        };
+        for rule in item_rules {
+            if let &AstRule::PartsRule(ref parts_rule) = rule {
+                let expr = self.parts_expr(&parts_rule.parts, builtins);
+                Self::add_rule(rules, parts_rule.ast_type, expr);
+            }
+        }
+    }
+
+    fn rule_choice(&self, rules: &[&AstRule<'d>]) -> GrammarExpr {
+        let mut items = Vec::new();
+        for rule in rules {
+            let name = match rule {
+                &&AstRule::RefRule(rule_ref) => rule_ref,
+                &&AstRule::PartsRule(ref parts_rule) => parts_rule.ast_type,
+            };
+            let item = GrammarExpr::NonTerminal(name.to_string());
+            if !items.contains(&item) {
+                items.push(item);
+            }
+        }
+        if items.len() == 1 {
+            items.remove(0)
+        } else {
+            GrammarExpr::Choice(items)
+        }
+    }
+
+    fn parts_expr(&self, parts: &[AstRulePart<'d>], builtins: &mut Vec<&'static str>) -> GrammarExpr {
+        let mut items = parts
+            .iter()
+            .filter_map(|part| self.part_expr(part, builtins))
+            .collect::<Vec<_>>();
+        if items.len() == 1 {
+            items.remove(0)
+        } else {
+            GrammarExpr::Sequence(items)
+        }
+    }
+
+    fn part_expr(&self, part: &AstRulePart<'d>, builtins: &mut Vec<&'static str>) -> Option<GrammarExpr> {
+        let mut expr = match &part.token {
+            &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                // Whitespace is implicit between tokens
+                Some(&TypedPart::WSPart) if !part.not => return None,
+                Some(typed_part) => self.typed_part_expr(typed_part, builtins),
+                None => return None,
+            },
+            &AstRuleToken::Tag(tag) => GrammarExpr::Terminal(tag.to_string()),
+            &AstRuleToken::Func(fnc, _) => GrammarExpr::Special(format!("{} function", fnc)),
+            &AstRuleToken::Group(ref parts) => self.parts_expr(parts, builtins),
+        };
+        if part.not {
+            expr = GrammarExpr::Until(Box::new(expr));
+        }
+        if part.optional {
+            expr = GrammarExpr::Optional(Box::new(expr));
+        }
+        Some(expr)
+    }
+
+    fn typed_part_expr(&self, typed_part: &TypedPart, builtins: &mut Vec<&'static str>) -> GrammarExpr {
+        let mut builtin = |name: &'static str| {
+            if !builtins.contains(&name) {
+                builtins.push(name);
+            }
+            GrammarExpr::NonTerminal(name.to_string())
+        };
+        match typed_part {
+            &TypedPart::AstPart { key } | &TypedPart::ListPart { key } => {
+                GrammarExpr::NonTerminal(key.to_string())
+            }
+            &TypedPart::CharPart { chr, .. } => GrammarExpr::Terminal(chr.to_string()),
+            &TypedPart::TagPart { tag, .. } => GrammarExpr::Terminal(tag.to_string()),
+            &TypedPart::IntPart { .. } => builtin("int"),
+            &TypedPart::IdentPart { .. } => builtin("ident"),
+            &TypedPart::StringPart { .. } | &TypedPart::StrPart { .. } => builtin("string"),
+            &TypedPart::FnPart { fnc, .. } => GrammarExpr::Special(format!("{} function", fnc)),
+            &TypedPart::WSPart => GrammarExpr::Special("whitespace".to_string()),
+        }
+    }
+
+    /// Builtin tokens, as in descr_common::parsers
+    fn builtin_rules(builtins: &[&'static str]) -> Vec<GrammarRule> {
+        use self::GrammarExpr::*;
+        let t = |s: &str| Terminal(s.to_string());
+        let n = |s: &str| NonTerminal(s.to_string());
+        let many = |e: GrammarExpr| Repeat(Box::new(e), None);
+        let rule = |name: &str, expr: GrammarExpr| GrammarRule {
+            name: name.to_string(),
+            expr,
+        };
+        let mut rules = Vec::new();
+        let mut chars = Vec::new();
+        if builtins.contains(&"ident") {
+            rules.push(rule(
+                "ident",
+                Sequence(vec![
+                    Choice(vec![n("letter"), t("_")]),
+                    many(Choice(vec![n("letter"), n("digit"), t("_")])),
+                ]),
+            ));
+            chars.push("letter");
+            chars.push("digit");
+        }
+        if builtins.contains(&"int") {
+            rules.push(rule(
+                "int",
+                Sequence(vec![
+                    Optional(Box::new(Choice(vec![t("-"), t("+")]))),
+                    n("digit"),
+                    many(n("digit")),
+                ]),
+            ));
+            if !chars.contains(&"digit") {
+                chars.push("digit");
+            }
+        }
+        if builtins.contains(&"string") {
+            let quoted = |quote: &str| {
+                Sequence(vec![
+                    t(quote),
+                    many(Choice(vec![
+                        Special(format!("character except {} and \\", quote)),
+                        Sequence(vec![t("\\"), n("character")]),
+                    ])),
+                    t(quote),
+                ])
+            };
+            rules.push(rule("string", Choice(vec![quoted("\""), quoted("'")])));
+        }
+        for name in chars {
+            let desc = match name {
+                "letter" => "ASCII letter",
+                _ => "decimal digit",
+            };
+            rules.push(rule(name, Special(desc.to_string())));
+        }
+        // Used by until and strings
+        rules.push(rule("character", Special("any character".to_string())));
+        rules
+    }
+
+    pub fn gen(&self) -> String {
+        let rules = self.rules();
+        let mut s = String::new();
+        append!(s, "(* " self.data.name.as_str() " grammar, whitespace is allowed between tokens *)\n\n");
+        for rule in &rules {
+            append!(s, rule.name.as_str() " = ");
+            s = Self::add_expr(s, &rule.expr, 0);
+            s += " ;\n";
+        }
+        s
+    }
+
+    fn has_until(expr: &GrammarExpr) -> bool {
+        Self::any(expr, &|expr| match expr {
+            &GrammarExpr::Until(..) => true,
+            _ => false,
+        })
+    }
+
+    fn refers(expr: &GrammarExpr, name: &str) -> bool {
+        Self::any(expr, &|expr| match expr {
+            &GrammarExpr::NonTerminal(ref n) => n == name,
+            _ => false,
+        })
+    }
+
+    fn any(expr: &GrammarExpr, f: &dyn Fn(&GrammarExpr) -> bool) -> bool {
+        use self::GrammarExpr::*;
+        if f(expr) {
+            return true;
+        }
+        match expr {
+            &Sequence(ref items) | &Choice(ref items) => items.iter().any(|item| Self::any(item, f)),
+            &Optional(ref item) | &Until(ref item) => Self::any(item, f),
+            &Repeat(ref item, ref sep) => {
+                Self::any(item, f) || sep.as_ref().map(|sep| Self::any(sep, f)).unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+
+    /// Terminal in quotes, single quotes
+    /// when it contains a double quote
+    pub fn terminal(text: &str) -> String {
+        if text.contains('"') {
+            format!("'{}'", text)
+        } else {
+            format!("\"{}\"", text)
+        }
+    }
+
+    /// Ebnf for expression. Level 0 is a
+    /// definitions list, 1 a single definition
+    /// and 2 a term, which groups the others.
+    pub fn add_expr(mut s: String, expr: &GrammarExpr, level: usize) -> String {
+        use self::GrammarExpr::*;
+        match expr {
+            &Terminal(ref text) => s += &Self::terminal(text),
+            &NonTerminal(ref name) => s += name,
+            &Special(ref text) => {
+                append!(s, "? " text.as_str() " ?");
+            }
+            &Sequence(ref items) => {
+                if items.is_empty() {
+                    // Empty sequence
+                    s += "\"\"";
+                    return s;
+                }
+                if level > 1 && items.len() > 1 {
+                    s += "( ";
+                }
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        s += " , ";
+                    }
+                    s = Self::add_expr(s, item, 2);
+                }
+                if level > 1 && items.len() > 1 {
+                    s += " )";
+                }
+            }
+            &Choice(ref items) => {
+                if level > 0 {
+                    s += "( ";
+                }
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        s += " | ";
+                    }
+                    s = Self::add_expr(s, item, 1);
+                }
+                if level > 0 {
+                    s += " )";
+                }
+            }
+            &Optional(ref item) => {
+                s += "[ ";
+                s = Self::add_expr(s, item, 0);
+                s += " ]";
+            }
+            &Repeat(ref item, None) => {
+                s += "{ ";
+                s = Self::add_expr(s, item, 0);
+                s += " }";
+            }
+            &Repeat(ref item, Some(ref sep)) => {
+                s += "[ ";
+                s = Self::add_expr(s, item, 1);
+                s += " , { ";
+                s = Self::add_expr(s, sep, 1);
+                s += " , ";
+                s = Self::add_expr(s, item, 1);
+                s += " } ]";
+            }
+            &Until(ref item) => {
+                // Text not containing item
+                s += "( { character } - ( { character } , ";
+                s = Self::add_expr(s, item, 2);
+                s += " , { character } ) )";
+            }
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use import::{ebnf, Expr};
+    use process::test_util::*;
+
+    /// Rule names and terminals used by expr
+    #[derive(Debug, Default, PartialEq)]
+    struct Used {
+        names: BTreeSet<String>,
+        terminals: BTreeSet<String>,
+    }
+
+    fn grammar_used(expr: &GrammarExpr, used: &mut Used) {
+        use self::GrammarExpr::*;
+        match expr {
+            &Terminal(ref text) => {
+                used.terminals.insert(text.clone());
+            }
+            &NonTerminal(ref name) => {
+                used.names.insert(name.clone());
+            }
+            &Special(_) => {}
+            &Sequence(ref items) | &Choice(ref items) => {
+                for item in items {
+                    grammar_used(item, used);
+                }
+            }
+            &Optional(ref item) | &Repeat(ref item, None) => grammar_used(item, used),
+            &Repeat(ref item, Some(ref sep)) => {
+                grammar_used(item, used);
+                grammar_used(sep, used);
+            }
+            &Until(ref item) => {
+                used.names.insert("character".to_string());
+                grammar_used(item, used);
+            }
+        }
+    }
+
+    fn imported_used(expr: &Expr, used: &mut Used) {
+        match expr {
+            &Expr::Literal(ref text) => {
+                used.terminals.insert(text.clone());
+            }
+            &Expr::Ref(ref name) => {
+                used.names.insert(name.clone());
+            }
+            &Expr::Seq(ref items) | &Expr::Alt(ref items) => {
+                for item in items {
+                    imported_used(item, used);
+                }
+            }
+            &Expr::Opt(ref item) | &Expr::Star(ref item) | &Expr::Plus(ref item) => imported_used(item, used),
+            &Expr::Except(ref a, ref b) => {
+                imported_used(a, used);
+                imported_used(b, used);
+            }
+            &Expr::Unsupported(_) => {}
+            other => panic!("Unexpected {:?}", other),
+        }
+    }
+
+    /// Panics unless the ebnf of grammar is read
+    /// back by the ebnf importer with the same
+    /// rules, using the same names and terminals
+    fn assert_round_trip(name: &str, grammar: &str) {
+        let (ebnf, rules) = with_data(grammar, |data| {
+            let codegen = CodegenEbnf::new(data);
+            (codegen.gen(), codegen.rules())
+        });
+        let imported = match ebnf::parse(&ebnf) {
+            Ok(imported) => imported,
+            Err(err) => panic!("{}: {}\n{}", name, err, ebnf),
+        };
+        let names = |rules: Vec<&str>| rules.into_iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            names(imported.rules.iter().map(|rule| rule.name.as_str()).collect()),
+            names(rules.iter().map(|rule| rule.name.as_str()).collect())
+        );
+        for (rule, imported) in rules.iter().zip(&imported.rules) {
+            let mut expected = Used::default();
+            grammar_used(&rule.expr, &mut expected);
+            let mut used = Used::default();
+            for alt in &imported.alts {
+                imported_used(&alt.expr, &mut used);
+            }
+            assert_eq!(used, expected, "{}: {}", name, rule.name);
+        }
+    }
+
+    #[test]
+    fn writes_iso_ebnf() {
+        let outputs = outputs(
+            "Source(items:sourceItems)
+            sourceItems:SourceItem[] WS {
+                Say(\"say\" string?),
+                Comment(\"(*\" comment:!\"*)\" \"*)\")
+            }
+            Pair(LPAREN values RPAREN)
+            values:Value[] COMMA {
+                Num(int)
+            }",
+            |options| options.docs = true,
+        );
+        let ebnf = &outputs["docs/grammar.ebnf"];
+        assert!(ebnf.starts_with("(* Test grammar, whitespace is allowed between tokens *)\n\n"));
+        assert!(ebnf.contains("\nSource = sourceItems ;\n"));
+        assert!(ebnf.contains("\nsourceItems = { Say | Comment } ;\n"));
+        assert!(ebnf.contains("\nSay = \"say\" , [ string ] ;\n"));
+        assert!(ebnf.contains(
+            "\nComment = \"(*\" , ( { character } - ( { character } , \"*)\" , { character } ) ) , \"*)\" ;\n"
+        ));
+        assert!(ebnf.contains("\nvalues = [ Num , { \",\" , Num } ] ;\n"));
+        assert_round_trip("Test", "Source(LPAREN quote:'\"' string RPAREN)");
+    }
+
+    #[test]
+    fn repo_grammars_read_back() {
+        for &(path, name) in &REPO_GRAMMARS {
+            assert_round_trip(name, &repo_grammar(path));
+        }
+    }
+}
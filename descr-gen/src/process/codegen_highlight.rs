@@ -0,0 +1,154 @@
+use lang_data::data::*;
+use lang_data::rule::*;
+use process::codegen_cst::CodegenCst;
+
+/// Generates a highlight.rs module, which
+/// parses with the cst.rs parsers and gives
+/// descr_common::highlight tokens. Scopes come
+/// from @syntax names on tokens, by the rule
+/// and member the token is in, with defaults
+/// for ints, strings and keywords.
+pub struct CodegenHighlight<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+    cst: CodegenCst<'a, 'd>,
+}
+impl<'a, 'd> CodegenHighlight<'a, 'd> {
+    pub fn new(data: &'a LangData<'d>) -> CodegenHighlight<'a, 'd> {
+        CodegenHighlight {
+            data,
+            cst: CodegenCst::new(data),
+        }
+    }
+
+    /// Kind of token made by part,
+    /// None for nodes
+    fn token_kind(&self, part: &AstRulePart<'d>) -> Option<String> {
+        use lang_data::typed_part::TypedPart::*;
+        if part.not {
+            return Some("TEXT".to_string());
+        }
+        match &part.token {
+            &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                Some(&CharPart { chr, .. }) => Some(self.cst.literal_kind(&chr.to_string()).to_string()),
+                Some(&TagPart { tag, .. }) => Some(self.cst.literal_kind(tag).to_string()),
+                Some(&IntPart { .. }) => Some("INT".to_string()),
+                Some(&IdentPart { .. }) => Some("IDENT".to_string()),
+                Some(&StringPart { .. }) | Some(&StrPart { .. }) => Some("STRING".to_string()),
+                Some(&WSPart) => Some("WHITESPACE".to_string()),
+                _ => None,
+            },
+            &AstRuleToken::Tag(tag) => Some(self.cst.literal_kind(tag).to_string()),
+            _ => None,
+        }
+    }
+
+    /// Match patterns to scope names, from
+    /// @syntax on tokens
+    fn add_scopes(&self, scopes: &mut Vec<(String, &'d str)>, kind: &str, parts: &[AstRulePart<'d>]) {
+        for part in parts {
+            if let &AstRuleToken::Group(ref parts) = &part.token {
+                self.add_scopes(scopes, kind, parts);
+                continue;
+            }
+            let name = match part.annots.get_quoted("syntax", "name") {
+                Some(name) => name,
+                None => continue,
+            };
+            if let Some(token_kind) = self.token_kind(part) {
+                let pattern = format!(
+                    "(SyntaxKind::{}, {}, SyntaxKind::{})",
+                    kind,
+                    self.cst.label(part.member_key),
+                    token_kind
+                );
+                if !scopes.iter().any(|&(ref existing, _)| *existing == pattern) {
+                    scopes.push((pattern, name));
+                }
+            }
+        }
+    }
+
+    /// Arms matching (parent, label, kind)
+    /// to a scope, without the fallback arm
+    pub fn add_scope_arms(&self, mut s: String, level: usize) -> String {
+        let mut scopes = Vec::new();
+        for parts_rule in self.cst.parts_rules() {
+            let kind = CodegenCst::kind_name(parts_rule.ast_type);
+            self.add_scopes(&mut scopes, &kind, &parts_rule.parts);
+        }
+        for (pattern, name) in scopes {
+            indent!(s level);
+            append!(s, pattern.as_str() " => Some(\"" name "\"),\n");
+        }
+        indent!(s level);
+        append!(s, "(_, _, SyntaxKind::INT) => Some(\"constant.numeric\"),\n");
+        indent!(s level);
+        append!(s, "(_, _, SyntaxKind::STRING) => Some(\"string.quoted.double\"),\n");
+        for (text, kind) in self.cst.literals() {
+            if text.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                indent!(s level);
+                append!(s, "(_, _, SyntaxKind::" kind.as_str() ") => Some(\"keyword.other\"),\n");
+            }
+        }
+        s
+    }
+
+    pub fn gen(&self) -> String {
+        let mut s = String::with_capacity(2000);
+        s += "use descr_common::cst::{CstError, Label};\n";
+        s += "use descr_common::highlight::{self, HighlightToken};\n";
+        s += "use super::cst::{self, SyntaxKind};\n\n";
+        s += "/// Scope of token by the kind of its parent,\n";
+        s += "/// its label and its kind\n";
+        s += "pub fn scope(parent: SyntaxKind, label: Label, kind: SyntaxKind) -> Option<&'static str> {\n";
+        s += "    match (parent, label, kind) {\n";
+        s = self.add_scope_arms(s, 2);
+        s += "        _ => None,\n";
+        s += "    }\n";
+        s += "}\n\n";
+        s += "/// Highlighted tokens of input, render with\n";
+        s += "/// descr_common::highlight::{to_html, to_ansi}\n";
+        s += "pub fn highlight(input: &[u8]) -> Result<Vec<HighlightToken>, CstError> {\n";
+        s += "    let root = cst::parse(input)?;\n";
+        s += "    Ok(highlight::cst_tokens(&root, scope))\n";
+        s += "}\n";
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use process::test_util::*;
+
+    #[test]
+    fn generates_scopes_of_tokens() {
+        let outputs = outputs(
+            "Source(items:sourceItems)
+            sourceItems:SourceItem[] WS {
+                Let(\"let\" @syntax(name=\"variable.other\") name:ident EQUAL int)
+            }",
+            |options| options.highlight = true,
+        );
+        let highlight = &outputs["highlight.rs"];
+        assert_rust("highlight.rs", highlight);
+        assert_eq!(item_names(highlight), ["scope", "highlight"]);
+        assert!(highlight.contains(
+            "(SyntaxKind::Let, Some(\"name\"), SyntaxKind::IDENT) => Some(\"variable.other\"),"
+        ));
+        assert!(highlight.contains("(_, _, SyntaxKind::LET_KW) => Some(\"keyword.other\"),"));
+        // Punctuation has no default scope
+        assert!(!highlight.contains("SyntaxKind::EQUAL"));
+        assert_kinds_defined("highlight.rs", highlight, &outputs["cst.rs"]);
+        assert_modules(&outputs);
+    }
+
+    #[test]
+    fn repo_grammars_highlight_cst_kinds() {
+        for &(path, _) in &REPO_GRAMMARS {
+            let outputs = outputs(&repo_grammar(path), |options| options.highlight = true);
+            assert_rust(path, &outputs["highlight.rs"]);
+            assert_kinds_defined(path, &outputs["highlight.rs"], &outputs["cst.rs"]);
+            assert_modules(&outputs);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use lang_data::data::*;
+use lang_data::rule::*;
+use lang_data::annotations::AnnotList;
+use lang_data::typed_part::TypedPart;
+use process::codegen_cst::CodegenCst;
+use process::codegen_highlight::CodegenHighlight;
+use process::descr_dependency;
+use descr_common::util::*;
+use std::collections::BTreeMap;
+
+/// Generates an lsp.rs module implementing
+/// descr_lsp::LspLanguage over the cst.rs tree,
+/// and a crate running it as a language server.
+/// Document symbols come from rules with
+/// @symbol(kind=function, name=ident), semantic
+/// tokens from @syntax names on tokens.
+pub struct CodegenLsp<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+    cst: CodegenCst<'a, 'd>,
+}
+impl<'a, 'd> CodegenLsp<'a, 'd> {
+    pub fn new(data: &'a LangData<'d>) -> CodegenLsp<'a, 'd> {
+        CodegenLsp {
+            data,
+            cst: CodegenCst::new(data),
+        }
+    }
+
+    /// Kind to symbol kind and name label,
+    /// first annotation of a kind wins
+    fn symbols(&self) -> BTreeMap<String, (&'d str, String)> {
+        let mut symbols = BTreeMap::new();
+        let mut add = |kind: String, annots: &AnnotList<'d>, parts: &[AstRulePart<'d>]| {
+            if let Some(symbol_kind) = annots.get_ident("symbol", "kind") {
+                let name = match annots.get_ident("symbol", "name") {
+                    Some(name) => self.cst.label(Some(name)),
+                    None => self.cst.label(self.name_member(parts)),
+                };
+                symbols.entry(kind).or_insert((symbol_kind, name));
+            }
+        };
+        for parts_rule in self.cst.parts_rules() {
+            add(
+                CodegenCst::kind_name(parts_rule.ast_type),
+                &parts_rule.annots,
+                &parts_rule.parts,
+            );
+        }
+        // Lists are nodes too, enums are not
+        for (key, list_data) in self.data.list_data.sorted_iter() {
+            add(CodegenCst::kind_name(key), &list_data.annots, &[]);
+        }
+        symbols
+    }
+
+    /// First ident or string member of rule
+    fn name_member(&self, parts: &[AstRulePart<'d>]) -> Option<&'d str> {
+        for part in parts {
+            match &part.token {
+                &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                    Some(&TypedPart::IdentPart { .. })
+                    | Some(&TypedPart::StringPart { .. })
+                    | Some(&TypedPart::StrPart { .. }) => {
+                        if part.member_key.is_some() && !part.not {
+                            return part.member_key;
+                        }
+                    }
+                    _ => {}
+                },
+                &AstRuleToken::Group(ref parts) => {
+                    if let Some(member_key) = self.name_member(parts) {
+                        return Some(member_key);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    pub fn gen_module(&self) -> String {
+        let mut s = String::with_capacity(2000);
+        s += "use descr_lsp::LspLanguage;\n";
+        s += "use descr_common::cst::{CstError, Label};\n";
+        s += "use super::cst::{self, Node, SyntaxKind};\n\n";
+        s += "/// Language server features from the grammar,\n";
+        s += "/// serve with descr_lsp::run_stdio(Lsp)\n";
+        s += "pub struct Lsp;\n\n";
+        s += "impl LspLanguage for Lsp {\n";
+        s += "    type Kind = SyntaxKind;\n\n";
+        s += "    fn name(&self) -> &'static str {\n";
+        append!(s 2, "\"" self.data.lang_id().as_str() "\"\n");
+        s += "    }\n\n";
+        s += "    fn parse(&self, input: &[u8]) -> Result<Node, CstError> {\n";
+        s += "        cst::parse(input)\n";
+        s += "    }\n\n";
+        s += "    fn symbol(&self, kind: SyntaxKind) -> Option<(&'static str, Label)> {\n";
+        s += "        match kind {\n";
+        for (kind, (symbol_kind, name)) in self.symbols() {
+            append!(s 3, "SyntaxKind::" kind.as_str() " => Some((\"" symbol_kind "\", " name.as_str() ")),\n");
+        }
+        s += "            _ => None,\n";
+        s += "        }\n";
+        s += "    }\n\n";
+        s += "    fn scope(&self, parent: SyntaxKind, label: Label, kind: SyntaxKind) -> Option<&'static str> {\n";
+        s += "        match (parent, label, kind) {\n";
+        s = CodegenHighlight::new(self.data).add_scope_arms(s, 3);
+        s += "            _ => None,\n";
+        s += "        }\n";
+        s += "    }\n";
+        s += "}\n";
+        s
+    }
+
+    /// Cargo.toml of server crate in lsp/,
+    /// with descr crates by path if given
+    pub fn gen_cargo_toml(&self, descr_path: Option<&str>) -> String {
+        let mut s = String::new();
+        s += "[package]\n";
+        append!(s, "name = \"" self.data.lang_id().as_str() "-language-server\"\n");
+        s += "version = \"0.1.0\"\n\n";
+        s += "[dependencies]\n";
+        s += "nom = \"^3.2\"\n";
+        s += &descr_dependency("descr-common", descr_path);
+        s += &descr_dependency("descr-lsp", descr_path);
+        s += "\n";
+        s += "[workspace]\n";
+        s
+    }
+
+    /// Server main, includes the generated
+    /// modules from the parent dir
+    pub fn gen_main(&self) -> String {
+        let mut s = String::new();
+        s += "#[allow(unused_imports)]\n";
+        s += "#[macro_use]\n";
+        s += "extern crate descr_common;\n";
+        s += "extern crate descr_lsp;\n";
+        s += "extern crate nom;\n\n";
+        s += "#[path = \"../../mod.rs\"]\n";
+        s += "#[allow(dead_code)]\n";
+        s += "mod lang;\n\n";
+        s += "fn main() {\n";
+        s += "    descr_lsp::run_stdio(lang::lsp::Lsp);\n";
+        s += "}\n";
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use process::test_util::*;
+
+    #[test]
+    fn generates_server_crate() {
+        let outputs = outputs(
+            "Source(items:sourceItems)
+            sourceItems:SourceItem[] WS {
+                @symbol(kind=function)
+                Fn(\"fn\" @syntax(name=\"entity.name.function\") name:ident LBRACE RBRACE),
+                @symbol(kind=constant, name=value)
+                Const(\"const\" key:ident EQUAL value:string),
+                Say(\"say\" string?)
+            }",
+            |options| {
+                options.lsp = true;
+                options.descr_path = Some("../descr".to_string());
+            },
+        );
+        let lsp = &outputs["lsp.rs"];
+        assert_rust("lsp.rs", lsp);
+        assert_eq!(item_names(lsp), ["Lsp"]);
+        // Named by the first ident or by the annotation
+        assert!(lsp.contains("SyntaxKind::Fn => Some((\"function\", Some(\"name\"))),"));
+        assert!(lsp.contains("SyntaxKind::Const => Some((\"constant\", Some(\"value\"))),"));
+        assert!(!lsp.contains("SyntaxKind::Say =>"));
+        assert!(lsp.contains(
+            "(SyntaxKind::Fn, Some(\"name\"), SyntaxKind::IDENT) => Some(\"entity.name.function\"),"
+        ));
+        assert!(lsp.contains("(_, _, SyntaxKind::SAY_KW) => Some(\"keyword.other\"),"));
+        assert_kinds_defined("lsp.rs", lsp, &outputs["cst.rs"]);
+        assert_modules(&outputs);
+        assert!(outputs["lsp/Cargo.toml"].contains("descr-lsp = { path = \"../descr/descr-lsp\" }"));
+        // The crate includes the modules two dirs up
+        let main = &outputs["lsp/src/main.rs"];
+        assert_rust("main.rs", main);
+        assert!(main.contains("#[path = \"../../mod.rs\"]"));
+        assert!(main.contains("descr_lsp::run_stdio(lang::lsp::Lsp);"));
+    }
+
+    #[test]
+    fn depends_on_released_crates_without_checkout() {
+        let outputs = outputs("Source(int)", |options| options.lsp = true);
+        let version = env!("CARGO_PKG_VERSION");
+        assert!(outputs["lsp/Cargo.toml"].contains(&format!("descr-lsp = \"{}\"\n", version)));
+        assert!(outputs["lsp/Cargo.toml"].contains(&format!("descr-common = \"{}\"\n", version)));
+    }
+
+    #[test]
+    fn repo_grammars_use_cst_kinds() {
+        for &(path, _) in &REPO_GRAMMARS {
+            let outputs = outputs(&repo_grammar(path), |options| options.lsp = true);
+            assert_rust(path, &outputs["lsp.rs"]);
+            assert_kinds_defined(path, &outputs["lsp.rs"], &outputs["cst.rs"]);
+            assert_modules(&outputs);
+        }
+    }
+}
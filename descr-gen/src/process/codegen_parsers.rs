@@ -44,14 +44,18 @@ impl<'a, 'd> CodegenParsers<'a, 'd> {
                 }
                 len => {
                     // Alt rule
-                    append!(s, "named!(pub " self.data.sc(ast_data.ast_type) "<" ast_data.ast_type ">, alt_complete!(\n    ");
+                    append!(s, "named!(pub " self.data.sc(ast_data.ast_type) "<" ast_data.ast_type ">, ");
+                    s = self.trace_open(s, key);
+                    s += "alt_complete!(\n    ";
                     for (i, rule) in ast_data.rules.iter().enumerate() {
                         s = rule.gen_rule(s, self.data, rule_type, resolved);
                         if i < len - 1 {
                             s += "\n    | ";
                         }
                     }
-                    s += "\n));\n\n";
+                    s += "\n)";
+                    s = self.trace_close(s);
+                    s += ");\n\n";
                 }
             }
         }
@@ -70,6 +74,7 @@ impl<'a, 'd> CodegenParsers<'a, 'd> {
                         "<Vec<"
                         self.data.rule_types.get(list_data.key).unwrap().get_type_name(self.data)
                         ">>, ");
+                    s = self.trace_open(s, key);
                     match list_data.sep {
                         Some(sep) => {
                             let tp = self.data.typed_parts.get(sep).unwrap();
@@ -89,7 +94,9 @@ impl<'a, 'd> CodegenParsers<'a, 'd> {
                         }
                     }
                     s = rule.ast_rule.gen_rule(s, self.data, rule_type, resolved);
-                    s += "\n));\n\n";
+                    s += "\n)";
+                    s = self.trace_close(s);
+                    s += ");\n\n";
                 }
                 len => {
                     // Alt rule
@@ -98,6 +105,7 @@ impl<'a, 'd> CodegenParsers<'a, 'd> {
                         "<Vec<"
                         self.data.rule_types.get(list_data.key).unwrap().get_type_name(self.data)
                         ">>, ");
+                    s = self.trace_open(s, key);
                     match list_data.sep {
                         Some(sep) => {
                             let tp = self.data.typed_parts.get(sep).unwrap();
@@ -123,10 +131,139 @@ impl<'a, 'd> CodegenParsers<'a, 'd> {
                             s += "\n    | ";
                         }
                     }
-                    s += "\n)));\n\n";
+                    s += "\n))";
+                    s = self.trace_close(s);
+                    s += ");\n\n";
                 }
             }
         }
         s
     }
+
+    /// Rules with alternatives and lists are
+    /// traced as a whole, alternatives and
+    /// tokens by themselves
+    fn trace_open(&self, mut s: String, key: &str) -> String {
+        if self.data.trace {
+            append!(s, "trace_wrap!(\"" key "\", ");
+        }
+        s
+    }
+
+    fn trace_close(&self, mut s: String) -> String {
+        if self.data.trace {
+            s += ")";
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use process::test_util::*;
+
+    const GRAMMAR: &str = "Source(items)
+        items:Item[] {
+            Say(\"say\" string),
+            Paren(\"(\" ident? ')'),
+            Num(int)
+        }";
+
+    /// Parsers without trace_wrap! and the
+    /// whitespace outside of literals
+    fn untraced(parsers: &str) -> String {
+        const WRAP: &str = "trace_wrap!(";
+        let mut s = String::new();
+        let mut depth = 0;
+        let mut wraps = Vec::new();
+        let mut chars = parsers.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                't' if parsers[i..].starts_with(WRAP) => {
+                    for _ in 1..WRAP.len() {
+                        chars.next();
+                    }
+                    // Name literal and comma
+                    let mut quotes = 0;
+                    while let Some((_, c)) = chars.next() {
+                        match c {
+                            '\\' => {
+                                chars.next();
+                            }
+                            '"' => quotes += 1,
+                            ',' if quotes == 2 => break,
+                            _ => {}
+                        }
+                    }
+                    wraps.push(depth);
+                    depth += 1;
+                }
+                '"' => {
+                    s.push(c);
+                    while let Some((_, c)) = chars.next() {
+                        s.push(c);
+                        match c {
+                            '\\' => s.push(chars.next().unwrap().1),
+                            '"' => break,
+                            _ => {}
+                        }
+                    }
+                }
+                '\'' => {
+                    s.push(c);
+                    let (_, next) = chars.next().unwrap();
+                    s.push(next);
+                    if next == '\\' {
+                        s.push(chars.next().unwrap().1);
+                    }
+                    s.push(chars.next().unwrap().1);
+                }
+                '(' => {
+                    depth += 1;
+                    s.push(c);
+                }
+                ')' => {
+                    depth -= 1;
+                    if wraps.last() == Some(&depth) {
+                        wraps.pop();
+                    } else {
+                        s.push(c);
+                    }
+                }
+                c if c.is_whitespace() => {}
+                c => s.push(c),
+            }
+        }
+        assert_eq!(depth, 0);
+        s
+    }
+
+    /// Panics unless traced parsers are the
+    /// parsers wrapped in trace_wrap!
+    fn assert_traced(name: &str, grammar: &str) {
+        let parsers = &outputs(grammar, |_| {})["parsers.rs"];
+        let traced = &outputs(grammar, |options| options.trace = true)["parsers.rs"];
+        assert_rust(name, parsers);
+        assert_rust(name, traced);
+        assert!(!parsers.contains("trace_wrap!"));
+        assert!(traced.contains("trace_wrap!"));
+        assert_eq!(untraced(traced), untraced(parsers), "{}", name);
+    }
+
+    #[test]
+    fn wraps_rules_and_tokens() {
+        let parsers = &outputs(GRAMMAR, |options| options.trace = true)["parsers.rs"];
+        assert!(parsers.contains("trace_wrap!(\"Source\", do_parse!("));
+        assert!(parsers.contains("trace_wrap!(\"Say\", do_parse!(\n        sp >> trace_wrap!(\"\\\"say\\\"\", tag!(\"say\")) >>"));
+        assert!(parsers.contains("trace_wrap!(\"items\", many0!(alt_complete!("));
+        assert!(parsers.contains("trace_wrap!(\"int\", parse_int)"));
+        assert_traced("Test", GRAMMAR);
+    }
+
+    #[test]
+    fn repo_grammars_trace_same_parsers() {
+        for &(path, _) in &REPO_GRAMMARS {
+            assert_traced(path, &repo_grammar(path));
+        }
+    }
 }
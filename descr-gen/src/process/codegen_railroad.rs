@@ -0,0 +1,444 @@
+use lang_data::data::*;
+use process::codegen_ebnf::{CodegenEbnf, GrammarExpr, GrammarRule};
+use descr_common::highlight::html_escape;
+
+const ARC: i32 = 10;
+const GAP: i32 = 10;
+const VSPACE: i32 = 8;
+const BOX_HEIGHT: i32 = 22;
+const CHAR_WIDTH: i32 = 8;
+const PADDING: i32 = 20;
+
+const STYLE: &str = "<style>\
+path { stroke: #333; stroke-width: 2; fill: none; } \
+rect { stroke: #333; stroke-width: 2; } \
+rect.terminal { fill: #ffffd0; } \
+rect.nonterminal { fill: #dff0ff; } \
+rect.special { fill: #eee; stroke-dasharray: 4 2; } \
+text { font: 13px monospace; text-anchor: middle; } \
+a text { fill: #036; text-decoration: underline; }\
+</style>";
+
+/// Size of a laid out expression. Up and
+/// down are from the line it's entered on.
+#[derive(Debug, Clone, Copy)]
+struct Size {
+    width: i32,
+    up: i32,
+    down: i32,
+}
+
+/// Railroad diagrams as standalone svg,
+/// one per rule, and an html index with
+/// ebnf and diagrams for all rules.
+/// Rule references link to their diagram.
+pub struct CodegenRailroad<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+    rules: Vec<GrammarRule>,
+}
+impl<'a, 'd> CodegenRailroad<'a, 'd> {
+    pub fn new(data: &'a LangData<'d>) -> CodegenRailroad<'a, 'd> {
+        CodegenRailroad {
+            data,
+            rules: CodegenEbnf::new(data).rules(),
+        }
+    }
+
+    /// Output name to svg for each rule
+    pub fn gen_svgs(&self) -> Vec<(String, String)> {
+        self.rules
+            .iter()
+            .map(|rule| {
+                let svg = self.gen_svg(&rule.expr, &|name| format!("{}.svg", name));
+                (format!("{}.svg", rule.name), svg)
+            })
+            .collect()
+    }
+
+    pub fn gen_index(&self) -> String {
+        let mut s = String::new();
+        let title = format!("{} grammar", self.data.name);
+        s += "<!DOCTYPE html>\n";
+        s += "<html>\n<head>\n<meta charset=\"utf-8\">\n";
+        append!(s, "<title>" escape(&title).as_str() "</title>\n");
+        s += "<style>body { font-family: sans-serif; margin: 2em; } ";
+        s += "pre { background: #f6f6f6; padding: 0.5em; white-space: pre-wrap; }</style>\n";
+        s += "</head>\n<body>\n";
+        append!(s, "<h1>" escape(&title).as_str() "</h1>\n");
+        s += "<ul>\n";
+        for rule in &self.rules {
+            append!(s, "<li><a href=\"#" escape(&rule.name).as_str() "\">" escape(&rule.name).as_str() "</a></li>\n");
+        }
+        s += "</ul>\n";
+        for rule in &self.rules {
+            let mut ebnf = String::new();
+            append!(ebnf, rule.name.as_str() " = ");
+            ebnf = CodegenEbnf::add_expr(ebnf, &rule.expr, 0);
+            ebnf += " ;";
+            append!(s, "<h2 id=\"" escape(&rule.name).as_str() "\">" escape(&rule.name).as_str() "</h2>\n");
+            append!(s, "<pre>" escape(&ebnf).as_str() "</pre>\n");
+            s += &self.gen_svg(&rule.expr, &|name| format!("#{}", name));
+            s += "\n";
+        }
+        s += "</body>\n</html>\n";
+        s
+    }
+
+    fn gen_svg(&self, expr: &GrammarExpr, link: &dyn Fn(&str) -> String) -> String {
+        let size = Self::size(expr);
+        // Start and end marks, with a line to the expression
+        let width = PADDING * 2 + GAP * 2 + size.width;
+        let height = PADDING * 2 + size.up + size.down;
+        let y = PADDING + size.up;
+        let mut s = String::new();
+        s += &format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+            width, height
+        );
+        s += STYLE;
+        s += "\n";
+        s += &format!("<path d=\"M{0} {1}v20M{2} {1}v20\"/>\n", PADDING, y - 10, PADDING + 4);
+        s += &format!("<path d=\"M{} {}h{}\"/>\n", PADDING, y, GAP);
+        s = self.render(s, expr, PADDING + GAP, y, link);
+        let end = PADDING + GAP + size.width;
+        s += &format!("<path d=\"M{} {}h{}\"/>\n", end, y, GAP);
+        s += &format!("<path d=\"M{0} {1}v20M{2} {1}v20\"/>\n", end + GAP - 4, y - 10, end + GAP);
+        s += "</svg>\n";
+        s
+    }
+
+    fn label(expr: &GrammarExpr) -> Option<(String, &'static str)> {
+        match expr {
+            &GrammarExpr::Terminal(ref text) => Some((text.clone(), "terminal")),
+            &GrammarExpr::NonTerminal(ref name) => Some((name.clone(), "nonterminal")),
+            &GrammarExpr::Special(ref text) => Some((text.clone(), "special")),
+            &GrammarExpr::Until(ref item) => {
+                let item = CodegenEbnf::add_expr(String::new(), item, 2);
+                Some((format!("text until {}", item), "special"))
+            }
+            _ => None,
+        }
+    }
+
+    fn size(expr: &GrammarExpr) -> Size {
+        use self::GrammarExpr::*;
+        if let Some((text, _)) = Self::label(expr) {
+            return Size {
+                width: text.chars().count() as i32 * CHAR_WIDTH + 20,
+                up: BOX_HEIGHT / 2,
+                down: BOX_HEIGHT / 2,
+            };
+        }
+        match expr {
+            &Sequence(ref items) => {
+                let sizes = items.iter().map(Self::size).collect::<Vec<_>>();
+                Size {
+                    width: sizes.iter().map(|size| size.width).sum::<i32>()
+                        + GAP * (sizes.len() as i32 - 1).max(0),
+                    up: sizes.iter().map(|size| size.up).max().unwrap_or(0),
+                    down: sizes.iter().map(|size| size.down).max().unwrap_or(0),
+                }
+            }
+            &Choice(ref items) => {
+                let sizes = items.iter().map(Self::size).collect::<Vec<_>>();
+                let offsets = Self::choice_offsets(&sizes);
+                let last = sizes.len() - 1;
+                Size {
+                    width: sizes.iter().map(|size| size.width).max().unwrap_or(0) + ARC * 4,
+                    up: sizes[0].up,
+                    down: offsets[last] + sizes[last].down,
+                }
+            }
+            &Optional(ref item) => {
+                let size = Self::size(item);
+                Size {
+                    width: size.width + ARC * 4,
+                    up: Self::skip_offset(size),
+                    down: size.down,
+                }
+            }
+            &Repeat(ref item, ref sep) => {
+                // Optional one or more
+                let size = Self::one_or_more_size(item, sep);
+                Size {
+                    width: size.width + ARC * 4,
+                    up: Self::skip_offset(size),
+                    down: size.down,
+                }
+            }
+            _ => Size {
+                width: 0,
+                up: 0,
+                down: 0,
+            },
+        }
+    }
+
+    /// Offset down to each alternative
+    fn choice_offsets(sizes: &[Size]) -> Vec<i32> {
+        let mut offsets = vec![0];
+        for i in 1..sizes.len() {
+            let offset = offsets[i - 1] + sizes[i - 1].down + VSPACE + sizes[i].up;
+            offsets.push(offset.max(offsets[i - 1] + ARC * 2));
+        }
+        offsets
+    }
+
+    /// Offset up to skip line of optional
+    fn skip_offset(size: Size) -> i32 {
+        (size.up + VSPACE).max(ARC * 2)
+    }
+
+    /// Offset down to return line of repeat
+    fn return_offset(item: Size, sep: Option<Size>) -> i32 {
+        let sep_up = sep.map(|sep| sep.up).unwrap_or(0);
+        (item.down + VSPACE + sep_up).max(ARC * 2)
+    }
+
+    fn one_or_more_size(item: &GrammarExpr, sep: &Option<Box<GrammarExpr>>) -> Size {
+        let item = Self::size(item);
+        let sep = sep.as_ref().map(|sep| Self::size(sep));
+        let offset = Self::return_offset(item, sep);
+        Size {
+            width: item.width.max(sep.map(|sep| sep.width).unwrap_or(0)) + ARC * 4,
+            up: item.up,
+            down: offset + sep.map(|sep| sep.down).unwrap_or(0),
+        }
+    }
+
+    fn line(s: &mut String, x: i32, y: i32, to_x: i32) {
+        if to_x != x {
+            *s += &format!("<path d=\"M{} {}H{}\"/>\n", x, y, to_x);
+        }
+    }
+
+    fn render(&self, mut s: String, expr: &GrammarExpr, x: i32, y: i32, link: &dyn Fn(&str) -> String) -> String {
+        use self::GrammarExpr::*;
+        if let Some((text, class)) = Self::label(expr) {
+            let size = Self::size(expr);
+            let rx = if class == "terminal" { 10 } else { 0 };
+            let linked = match expr {
+                &NonTerminal(ref name) if self.rules.iter().any(|rule| rule.name == *name) => {
+                    Some(link(name))
+                }
+                _ => None,
+            };
+            if let Some(ref href) = linked {
+                append!(s, "<a href=\"" escape(href).as_str() "\">");
+            }
+            s += &format!(
+                "<rect class=\"{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\"/>",
+                class,
+                x,
+                y - BOX_HEIGHT / 2,
+                size.width,
+                BOX_HEIGHT,
+                rx
+            );
+            s += &format!("<text x=\"{}\" y=\"{}\">{}</text>", x + size.width / 2, y + 4, escape(&text));
+            if linked.is_some() {
+                s += "</a>";
+            }
+            s += "\n";
+            return s;
+        }
+        match expr {
+            &Sequence(ref items) => {
+                let mut cur = x;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        Self::line(&mut s, cur, y, cur + GAP);
+                        cur += GAP;
+                    }
+                    s = self.render(s, item, cur, y, link);
+                    cur += Self::size(item).width;
+                }
+            }
+            &Choice(ref items) => {
+                let size = Self::size(expr);
+                let sizes = items.iter().map(Self::size).collect::<Vec<_>>();
+                let offsets = Self::choice_offsets(&sizes);
+                let end = x + size.width;
+                for (i, item) in items.iter().enumerate() {
+                    let item_y = y + offsets[i];
+                    if i == 0 {
+                        Self::line(&mut s, x, y, x + ARC * 2);
+                    } else {
+                        let drop = offsets[i] - ARC * 2;
+                        s += &format!(
+                            "<path d=\"M{} {}a{2} {2} 0 0 1 {2} {2}v{3}a{2} {2} 0 0 0 {2} {2}\"/>\n",
+                            x, y, ARC, drop
+                        );
+                        s += &format!(
+                            "<path d=\"M{} {}a{2} {2} 0 0 0 {2} -{2}v-{3}a{2} {2} 0 0 1 {2} -{2}\"/>\n",
+                            end - ARC * 2,
+                            item_y,
+                            ARC,
+                            drop
+                        );
+                    }
+                    s = self.render(s, item, x + ARC * 2, item_y, link);
+                    Self::line(&mut s, x + ARC * 2 + sizes[i].width, item_y, end - ARC * 2);
+                    if i == 0 {
+                        Self::line(&mut s, end - ARC * 2, y, end);
+                    }
+                }
+            }
+            &Optional(ref item) => {
+                let size = Self::size(item);
+                s = self.render_skip(s, x, y, size);
+                s = self.render(s, item, x + ARC * 2, y, link);
+                Self::line(&mut s, x + ARC * 2 + size.width, y, x + size.width + ARC * 4);
+            }
+            &Repeat(ref item, ref sep) => {
+                let size = Self::one_or_more_size(item, sep);
+                s = self.render_skip(s, x, y, size);
+                s = self.render_one_or_more(s, item, sep, x + ARC * 2, y, link);
+                Self::line(&mut s, x + ARC * 2 + size.width, y, x + size.width + ARC * 4);
+            }
+            _ => {}
+        }
+        s
+    }
+
+    /// Line over an optional part, also
+    /// draws the line in to the part
+    fn render_skip(&self, mut s: String, x: i32, y: i32, size: Size) -> String {
+        let rise = Self::skip_offset(size) - ARC * 2;
+        let end = x + size.width + ARC * 4;
+        Self::line(&mut s, x, y, x + ARC * 2);
+        s += &format!(
+            "<path d=\"M{} {}a{2} {2} 0 0 0 {2} -{2}v-{3}a{2} {2} 0 0 1 {2} -{2}H{4}a{2} {2} 0 0 1 {2} {2}v{3}a{2} {2} 0 0 0 {2} {2}\"/>\n",
+            x,
+            y,
+            ARC,
+            rise,
+            end - ARC * 2
+        );
+        s
+    }
+
+    /// Item on the line, with separator on
+    /// the way back
+    fn render_one_or_more(
+        &self,
+        mut s: String,
+        item: &GrammarExpr,
+        sep: &Option<Box<GrammarExpr>>,
+        x: i32,
+        y: i32,
+        link: &dyn Fn(&str) -> String,
+    ) -> String {
+        let size = Self::one_or_more_size(item, sep);
+        let item_size = Self::size(item);
+        let sep_size = sep.as_ref().map(|sep| Self::size(sep));
+        let offset = Self::return_offset(item_size, sep_size);
+        let drop = offset - ARC * 2;
+        let end = x + size.width;
+        let inner = size.width - ARC * 4;
+        Self::line(&mut s, x, y, x + ARC * 2);
+        s = self.render(s, item, x + ARC * 2, y, link);
+        Self::line(&mut s, x + ARC * 2 + item_size.width, y, end);
+        // Return loop
+        s += &format!(
+            "<path d=\"M{} {}a{2} {2} 0 0 1 {2} {2}v{3}a{2} {2} 0 0 1 -{2} {2}\"/>\n",
+            end - ARC * 2,
+            y,
+            ARC,
+            drop
+        );
+        s += &format!(
+            "<path d=\"M{} {}a{2} {2} 0 0 1 -{2} -{2}v-{3}a{2} {2} 0 0 1 {2} -{2}\"/>\n",
+            x + ARC * 2,
+            y + offset,
+            ARC,
+            drop
+        );
+        match (sep, sep_size) {
+            (&Some(ref sep), Some(sep_size)) => {
+                let sep_x = x + ARC * 2 + (inner - sep_size.width) / 2;
+                Self::line(&mut s, x + ARC * 2, y + offset, sep_x);
+                s = self.render(s, sep, sep_x, y + offset, link);
+                Self::line(&mut s, sep_x + sep_size.width, y + offset, end - ARC * 2);
+            }
+            _ => Self::line(&mut s, x + ARC * 2, y + offset, end - ARC * 2),
+        }
+        s
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut s = String::with_capacity(text.len());
+    html_escape(&mut s, text);
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use process::test_util::*;
+
+    /// Values of attr in source
+    fn attrs<'s>(source: &'s str, attr: &str) -> Vec<&'s str> {
+        let prefix = format!("{}=\"", attr);
+        source
+            .split(prefix.as_str())
+            .skip(1)
+            .map(|rest| &rest[..rest.find('"').unwrap()])
+            .collect()
+    }
+
+    /// Panics unless the diagrams and index are
+    /// well formed and their links resolve
+    fn assert_linked(name: &str, outputs: &BTreeMap<String, String>) {
+        let svgs = outputs
+            .iter()
+            .filter(|&(file, _)| file.ends_with(".svg"))
+            .collect::<Vec<_>>();
+        assert!(!svgs.is_empty());
+        for (file, svg) in svgs {
+            assert_xml(file, svg);
+            for size in attrs(svg, "width").into_iter().chain(attrs(svg, "height")) {
+                assert!(size.parse::<i32>().unwrap() > 0, "{}: {} size {}", name, file, size);
+            }
+            for href in attrs(svg, "href") {
+                assert!(outputs.contains_key(&format!("docs/{}", href)), "{}: {} links {}", name, file, href);
+            }
+        }
+        let index = outputs["docs/index.html"].replace("<meta charset=\"utf-8\">", "");
+        assert_xml("index.html", &index);
+        let ids = attrs(&index, "id");
+        for href in attrs(&index, "href") {
+            assert!(ids.contains(&&href[1..]), "{}: index links {}", name, href);
+        }
+        assert_eq!(ids.len(), outputs.keys().filter(|file| file.ends_with(".svg")).count());
+    }
+
+    #[test]
+    fn links_diagrams_of_rules() {
+        let outputs = outputs(
+            "Source(items:sourceItems)
+            sourceItems:SourceItem[] WS {
+                Say(\"say\" string?),
+                Comment(\"(*\" comment:!\"*)\" \"*)\")
+            }
+            Pair(LPAREN values RPAREN)
+            values:Value[] COMMA {
+                Num(int)
+            }",
+            |options| options.docs = true,
+        );
+        assert!(outputs["docs/Say.svg"].contains("<a href=\"string.svg\">"));
+        assert!(outputs["docs/Say.svg"].contains("<text x="));
+        assert!(outputs["docs/index.html"].contains("<h2 id=\"Pair\">Pair</h2>"));
+        assert!(outputs["docs/index.html"].contains("<pre>Say = &quot;say&quot; , [ string ] ;</pre>"));
+        assert_linked("Test", &outputs);
+    }
+
+    #[test]
+    fn repo_grammars_are_linked() {
+        for &(path, _) in &REPO_GRAMMARS {
+            assert_linked(path, &outputs(&repo_grammar(path), |options| options.docs = true));
+        }
+    }
+}
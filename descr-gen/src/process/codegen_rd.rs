@@ -0,0 +1,671 @@
+use lang_data::data::*;
+use lang_data::rule::*;
+use lang_data::ast::RuleType;
+use lang_data::typed_part::TypedPart;
+use descr_common::util::*;
+use std::collections::{BTreeSet, HashSet};
+
+/// Generates parsers as plain functions over
+/// descr_common::cursor, an alternative to the
+/// nom macros of CodegenParsers. The functions
+/// give the same ast, using the struct values
+/// of the nom backend, with parsed members
+/// bound to the same names.
+///
+/// Memoized rules keep their results by
+/// position in a Memo struct carried by the
+/// cursor, so alternatives that backtrack
+/// don't parse them again.
+pub struct CodegenRd<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+    memo_keys: BTreeSet<&'d str>,
+}
+impl<'a, 'd> CodegenRd<'a, 'd> {
+    /// With memoize, all rules are memoized,
+    /// otherwise rules annotated with @memo
+    pub fn new(data: &'a LangData<'d>, memoize: bool) -> CodegenRd<'a, 'd> {
+        CodegenRd {
+            data,
+            memo_keys: memo_keys(data, memoize),
+        }
+    }
+
+    /// Cursor type of parser functions
+    fn cursor(&self) -> &'static str {
+        if self.memo_keys.is_empty() {
+            "Cursor<'a>"
+        } else {
+            "Cursor<'a, Memo<'a>>"
+        }
+    }
+
+    pub fn gen(&self) -> String {
+        let mut s = String::with_capacity(
+            self.data.ast_data.len() * 200 + self.data.list_data.len() * 200,
+        );
+        // Members are bound also when
+        // the value is not used
+        s += "#![allow(unused_variables)]\n";
+        s += "#[allow(unused_imports)]\n";
+        s += "use descr_common::cursor::*;\n";
+        if self.uses_nom() {
+            s += "#[allow(unused_imports)]\n";
+            s += "use descr_common::parsers::*;\n";
+            s += "extern crate nom;\n";
+            s += "use self::nom::*;\n";
+        }
+        s += "use super::ast::*;\n\n";
+        let cursor = self.cursor();
+        if !self.memo_keys.is_empty() {
+            s += "/// Results of memoized rules by position\n";
+            s += "#[derive(Default)]\n";
+            s += "pub struct Memo<'a> {\n";
+            for key in &self.memo_keys {
+                append!(s 1, "pub " self.data.sc(key) ": MemoTable<");
+                if self.data.list_data.contains_key(key) {
+                    append!(s, "Vec<" self.type_name(key).as_str() ">>,\n");
+                } else {
+                    append!(s, self.type_name(key).as_str() ">,\n");
+                }
+            }
+            append!(s 1, "input: ::std::marker::PhantomData<&'a [u8]>,\n");
+            s += "}\n\n";
+            s += "/// Results are kept on backtrack,\n";
+            s += "/// they don't depend on the path\n";
+            s += "impl<'a> State for Memo<'a> {\n";
+            s += "    type Mark = ();\n";
+            s += "    fn mark(&self) {}\n";
+            s += "    fn reset(&mut self, _mark: ()) {}\n";
+            s += "}\n\n";
+        }
+        if let Some(start_key) = self.data.start_key {
+            let type_name = self.type_name(start_key);
+            s += "/// Parses all of input, trailing\n";
+            s += "/// whitespace is allowed\n";
+            append!(s, "pub fn parse<'a>(input: &'a [u8]) -> Result<" type_name.as_str() ", ParseError> {\n");
+            if self.memo_keys.is_empty() {
+                append!(s 1, "::descr_common::cursor::parse(input, start)\n");
+            } else {
+                append!(s 1, "::descr_common::cursor::parse_with(input, Memo::default(), start)\n");
+            }
+            s += "}\n\n";
+            append!(s, "pub fn start<'a>(c: &mut " cursor ") -> PResult<" type_name.as_str() "> {\n");
+            append!(s 1, self.data.sc(start_key) "(c)\n");
+            s += "}\n\n";
+        }
+        for (key, ast_data) in self.data.ast_data.sorted_iter() {
+            if ast_data.rules.is_empty() {
+                continue;
+            }
+            let type_name = self.type_name(key);
+            append!(s, "pub fn " self.data.sc(key) "<'a>(c: &mut " cursor ") -> PResult<" type_name.as_str() "> {\n");
+            if self.memo_keys.contains(key) {
+                s = self.gen_memo(s, key, |s| self.gen_alt(s, key, ast_data.rules.iter().collect(), 2));
+            } else {
+                s = self.gen_alt(s, key, ast_data.rules.iter().collect(), 1);
+            }
+            s += "}\n\n";
+        }
+        for (key, list_data) in self.data.list_data.sorted_iter() {
+            if list_data.rules.is_empty() {
+                continue;
+            }
+            let sc = self.data.sc(key);
+            let type_name = self.type_name(key);
+            append!(s, "pub fn " sc "<'a>(c: &mut " cursor ") -> PResult<Vec<" type_name.as_str() ">> {\n");
+            if self.memo_keys.contains(key) {
+                s = self.gen_memo(s, key, |s| self.gen_list(s, list_data, 2));
+            } else {
+                s = self.gen_list(s, list_data, 1);
+            }
+            s += "}\n\n";
+            append!(s, "fn " sc "_item<'a>(c: &mut " cursor ") -> PResult<" type_name.as_str() "> {\n");
+            s = self.gen_alt(s, key, list_data.rules.iter().map(|rule| &rule.ast_rule).collect(), 1);
+            s += "}\n\n";
+        }
+        s
+    }
+
+    /// Function body parsing with c.memo
+    fn gen_memo<F>(&self, mut s: String, key: &str, gen_body: F) -> String
+    where
+        F: FnOnce(String) -> String,
+    {
+        append!(s 1, "c.memo(|m| &mut m." self.data.sc(key) ", |c| {\n");
+        s = gen_body(s);
+        append!(s 1, "})\n");
+        s
+    }
+
+    /// List items with separator, or many0
+    /// when there is none or it is WS
+    fn gen_list(&self, mut s: String, list_data: &ListData<'d>, level: usize) -> String {
+        let sc = self.data.sc(list_data.key);
+        let sep = list_data
+            .sep
+            .and_then(|sep| self.data.typed_parts.get(sep))
+            .and_then(|typed_part| match typed_part {
+                &TypedPart::WSPart => None,
+                typed_part => Some(typed_part),
+            });
+        indent!(s level);
+        match sep {
+            Some(sep) => {
+                s += "c.separated_list(|c| ";
+                s = self.gen_typed_part(s, sep);
+                append!(s, ", " sc "_item)\n");
+            }
+            None => {
+                append!(s, "c.many0(" sc "_item)\n");
+            }
+        }
+        s
+    }
+
+    /// Whether functions or nom tokens
+    /// are used in the grammar
+    fn uses_nom(&self) -> bool {
+        let fn_part = self.data.typed_parts.values().any(|typed_part| match typed_part {
+            &TypedPart::FnPart { .. } => true,
+            _ => false,
+        });
+        fn_part
+            || self.data.ast_data.values().any(|ast_data| ast_data.rules.iter().any(rule_has_func))
+            || self
+                .data
+                .list_data
+                .values()
+                .any(|list_data| list_data.rules.iter().any(|rule| rule_has_func(&rule.ast_rule)))
+    }
+
+    /// Ast type of rule, with lifetime
+    /// when it has borrowed members
+    fn type_name(&self, key: &str) -> String {
+        let rule_type = self.data.rule_types.get(key).unwrap();
+        let mut type_name = rule_type.get_type_name(self.data).to_string();
+        if rule_type.needs_lifetime(self.data, &mut HashSet::new()) {
+            type_name += "<'a>";
+        }
+        type_name
+    }
+
+    /// Function body, alternatives are tried
+    /// in order like alt_complete!
+    fn gen_alt(&self, mut s: String, key: &str, rules: Vec<&AstRule<'d>>, level: usize) -> String {
+        let rule_type = self.data.rule_types.get(key).unwrap();
+        let resolved = self.data.resolve(key);
+        if rules.len() == 1 {
+            s = self.gen_rule_body(s, rules[0], rule_type, &resolved, level);
+        } else {
+            for rule in rules {
+                indent!(s level);
+                s += "if let Some(res) = c.attempt(|c| ";
+                s = self.gen_rule(s, rule, rule_type, &resolved, level);
+                s += ") {\n";
+                indent!(s level + 1);
+                s += "return Ok(res);\n";
+                indent!(s level);
+                s += "}\n";
+            }
+            indent!(s level);
+            s += "c.fail()\n";
+        }
+        s
+    }
+
+    /// Rule as statements ending with the value
+    fn gen_rule_body(
+        &self,
+        mut s: String,
+        rule: &AstRule<'d>,
+        rule_type: &RuleType<'d>,
+        resolved: &ResolvedType<'d>,
+        level: usize,
+    ) -> String {
+        match rule {
+            &AstRule::RefRule(..) => {
+                indent!(s level);
+                s = self.gen_rule(s, rule, rule_type, resolved, level);
+                s += "\n";
+            }
+            &AstRule::PartsRule(ref parts_rule) => {
+                let mut gen_data = GenParserData::new();
+                for part in &parts_rule.parts {
+                    s = self.gen_part(s, part, level, &mut gen_data, true);
+                }
+                indent!(s level);
+                s += "Ok(";
+                let value = rule.gen_rule_value(String::new(), self.data, rule_type, resolved, &mut gen_data);
+                s = reindent(s, &value, level);
+                s += ")\n";
+            }
+        }
+        s
+    }
+
+    /// Expression giving the rule value
+    fn gen_rule(
+        &self,
+        mut s: String,
+        rule: &AstRule<'d>,
+        rule_type: &RuleType<'d>,
+        resolved: &ResolvedType<'d>,
+        level: usize,
+    ) -> String {
+        match rule {
+            &AstRule::RefRule(rule_ref) => {
+                append!(s, self.data.sc(rule_ref) "(c)");
+                if let &RuleType::ManyType(..) = rule_type {
+                    s += ".map(|node| ";
+                    s = rule.gen_rule_value(s, self.data, rule_type, resolved, &mut GenParserData::new());
+                    s += ")";
+                }
+            }
+            &AstRule::PartsRule(..) => {
+                s += "{\n";
+                s = self.gen_rule_body(s, rule, rule_type, resolved, level + 1);
+                indent!(s level);
+                s += "}";
+            }
+        }
+        s
+    }
+
+    /// Statements for part, mirrors gen_part_parser.
+    /// Members are bound to <member>_k, or in a
+    /// tuple for optional groups.
+    fn gen_part(
+        &self,
+        mut s: String,
+        part: &AstRulePart<'d>,
+        level: usize,
+        gen_data: &mut GenParserData,
+        bind: bool,
+    ) -> String {
+        if let &AstRuleToken::Group(ref parts) = &part.token {
+            if part.not {
+                indent!(s level);
+                if let Some(member_key) = part.member_key {
+                    if bind {
+                        append!(s, "let " self.data.sc(member_key) "_k = ");
+                    }
+                }
+                s += "c.until(|c| {\n";
+                for part in parts {
+                    s = self.gen_part(s, part, level + 1, gen_data, false);
+                }
+                indent!(s level + 1);
+                s += "Ok(())\n";
+                indent!(s level);
+                s += "})?;\n";
+            } else if part.optional {
+                let member_names = parts.iter().filter_map(|part| part.member_key).collect::<Vec<_>>();
+                indent!(s level);
+                if !member_names.is_empty() && bind {
+                    let group_name = gen_data.push_opt_group(&member_names);
+                    append!(s, "let " group_name.as_str() " = ");
+                }
+                s += "c.opt(|c| {\n";
+                for part in parts {
+                    s = self.gen_part(s, part, level + 1, gen_data, bind);
+                }
+                indent!(s level + 1);
+                if member_names.is_empty() || !bind {
+                    s += "Ok(())\n";
+                } else {
+                    s += "Ok(";
+                    s = gen_group_tuple(s, &member_names, self.data);
+                    s += ")\n";
+                }
+                indent!(s level);
+                s += "})?;\n";
+            } else {
+                // Forwarded to parts
+                for part in parts {
+                    s = self.gen_part(s, part, level, gen_data, bind);
+                }
+            }
+            return s;
+        }
+        if !part.optional && !part.not {
+            indent!(s level);
+            s += "c.sp();\n";
+        }
+        indent!(s level);
+        if let Some(member_key) = part.member_key {
+            if bind {
+                append!(s, "let " self.data.sc(member_key) "_k = ");
+            }
+        }
+        if part.not {
+            s += "c.until(|c| ";
+            if part.optional {
+                s += "c.opt(|c| {\n";
+                indent!(s level + 1);
+                s += "c.sp();\n";
+                indent!(s level + 1);
+                s = self.gen_token(s, &part.token, level + 1);
+                s += "\n";
+                indent!(s level);
+                s += "})";
+            } else {
+                s = self.gen_token(s, &part.token, level);
+            }
+            s += ")?;\n";
+        } else if part.optional {
+            s += "c.opt(|c| {\n";
+            indent!(s level + 1);
+            s += "c.sp();\n";
+            indent!(s level + 1);
+            s = self.gen_token(s, &part.token, level + 1);
+            s += "\n";
+            indent!(s level);
+            s += "})?;\n";
+        } else {
+            s = self.gen_token(s, &part.token, level);
+            s += "?;\n";
+        }
+        s
+    }
+
+    /// Expression giving PResult
+    fn gen_token(&self, mut s: String, token: &AstRuleToken<'d>, level: usize) -> String {
+        match token {
+            &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                Some(typed_part) => self.gen_typed_part(s, typed_part),
+                None => s + "c.fail::<()>()",
+            },
+            &AstRuleToken::Tag(tag) => {
+                append!(s, "c.tag(\"" tag "\")");
+                s
+            }
+            &AstRuleToken::Func(fnc, ref args) => {
+                // Functions are nom macros
+                append!(s, "c.nom(|i| " fnc "!(i");
+                for arg in args {
+                    match arg {
+                        &RuleFuncArg::Quoted(string) => {
+                            append!(s, ", \"" string "\"");
+                        }
+                    }
+                }
+                s += "))";
+                s
+            }
+            &AstRuleToken::Group(ref parts) => {
+                s += "{\n";
+                let mut gen_data = GenParserData::new();
+                for part in parts {
+                    s = self.gen_part(s, part, level + 1, &mut gen_data, false);
+                }
+                indent!(s level + 1);
+                s += "Ok(())\n";
+                indent!(s level);
+                s += "}";
+                s
+            }
+        }
+    }
+
+    fn gen_typed_part(&self, mut s: String, typed_part: &TypedPart) -> String {
+        use lang_data::typed_part::TypedPart::*;
+        match typed_part {
+            &AstPart { key } | &ListPart { key } => {
+                append!(s, self.data.sc(key) "(c)");
+            }
+            &CharPart { chr, .. } => {
+                append!(s, "c.chr(" format!("{:?}", chr).as_str() ")");
+            }
+            &TagPart { tag, .. } => {
+                append!(s, "c.tag(\"" tag "\")");
+            }
+            &IntPart { .. } => s += "c.int()",
+            &IdentPart { .. } => s += "c.ident()",
+            &StringPart { .. } | &StrPart { .. } => s += "c.string()",
+            &FnPart { fnc, .. } => {
+                append!(s, "c.nom(" fnc ")");
+            }
+            &WSPart => s += "c.ws()",
+        }
+        s
+    }
+}
+
+/// Keys of rules to memoize, all ast and list
+/// rules with all, or those annotated with @memo
+pub fn memo_keys<'d>(data: &LangData<'d>, all: bool) -> BTreeSet<&'d str> {
+    let ast_keys = data
+        .ast_data
+        .values()
+        .filter(|ast_data| !ast_data.rules.is_empty() && (all || ast_data.annots.items.contains_key("memo")))
+        .map(|ast_data| ast_data.key);
+    let list_keys = data
+        .list_data
+        .values()
+        .filter(|list_data| !list_data.rules.is_empty() && (all || list_data.annots.items.contains_key("memo")))
+        .map(|list_data| list_data.key);
+    ast_keys.chain(list_keys).collect()
+}
+
+/// Value lines are indented for do_parse!,
+/// at level 2
+fn reindent(mut s: String, value: &str, level: usize) -> String {
+    for (i, line) in value.split('\n').enumerate() {
+        if i > 0 {
+            s += "\n";
+            indent!(s level);
+            s += if line.starts_with("        ") { &line[8..] } else { line.trim_start() };
+        } else {
+            s += line;
+        }
+    }
+    s
+}
+
+fn rule_has_func(rule: &AstRule) -> bool {
+    match rule {
+        &AstRule::RefRule(..) => false,
+        &AstRule::PartsRule(ref parts_rule) => parts_have_func(&parts_rule.parts),
+    }
+}
+
+fn parts_have_func(parts: &[AstRulePart]) -> bool {
+    parts.iter().any(|part| match part.token {
+        AstRuleToken::Func(..) => true,
+        AstRuleToken::Group(ref parts) => parts_have_func(parts),
+        _ => false,
+    })
+}
+
+// Parsers of the repo grammars from each
+// backend, as compared by the parser bench
+#[cfg(test)]
+#[allow(warnings, clippy::all)]
+#[path = "../../../examples/parser_bench/json/mod.rs"]
+mod bench_json;
+#[cfg(test)]
+#[allow(warnings, clippy::all)]
+#[path = "../../../examples/parser_bench/rust/mod.rs"]
+mod bench_rust;
+#[cfg(test)]
+#[allow(warnings, clippy::all)]
+#[path = "../../../examples/parser_bench/descr/mod.rs"]
+mod bench_descr;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use nom::IResult;
+    use process::test_util::*;
+    use samples::{SampleOptions, Sampler};
+    use super::{bench_descr, bench_json, bench_rust};
+
+    const GRAMMAR: &str = "Source(items:sourceItems)
+        sourceItems:SourceItem[] WS {
+            Say(\"say\" string? SEMICOLON),
+            Items(LPAREN values RPAREN)
+        }
+        values:Value[] COMMA {
+            Num(int)
+        }";
+
+    /// Public parsers with their result types,
+    /// without lifetimes
+    fn signatures(parsers: &str) -> BTreeSet<(String, String)> {
+        parsers
+            .lines()
+            .filter_map(|line| {
+                if line.starts_with("named!(pub ") {
+                    let rest = &line["named!(pub ".len()..];
+                    let open = rest.find('<').unwrap();
+                    let close = rest.find(">,").unwrap();
+                    Some((rest[..open].to_string(), rest[open + 1..close].to_string()))
+                } else if line.starts_with("pub fn ") && line.contains("-> PResult<") {
+                    let name = &line["pub fn ".len()..line.find("<'a>").unwrap()];
+                    let result = &line[line.find("-> PResult<").unwrap() + "-> PResult<".len()..line.len() - "> {".len()];
+                    Some((name.to_string(), result.replace("<'a>", "")))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Panics unless the recursive descent parsers
+    /// are valid Rust giving the nom parser types
+    fn assert_like_nom(name: &str, grammar: &str, memoize: bool) {
+        let nom = outputs(grammar, |_| {});
+        let rd = outputs(grammar, |options| {
+            options.recursive_descent = true;
+            options.memoize = memoize;
+        });
+        assert_rust(name, &rd["parsers.rs"]);
+        assert!(!rd["parsers.rs"].contains("named!"));
+        let expected = signatures(&nom["parsers.rs"]);
+        assert!(!expected.is_empty());
+        assert_eq!(signatures(&rd["parsers.rs"]), expected, "{}", name);
+        if !memoize {
+            assert_eq!(rd["ast.rs"], nom["ast.rs"], "{}", name);
+        }
+    }
+
+    #[test]
+    fn generates_cursor_functions() {
+        let outputs = outputs(GRAMMAR, |options| options.recursive_descent = true);
+        let parsers = &outputs["parsers.rs"];
+        assert!(parsers.contains("use descr_common::cursor::*;"));
+        assert!(parsers.contains("c.many0(source_items_item)"));
+        assert!(parsers.contains("c.separated_list(|c| c.chr(','), values_item)"));
+        assert!(parsers.contains("        let string_k = c.opt(|c| {\n            c.sp();\n            c.string()\n        })?;\n"));
+        assert!(parsers.contains("    c.fail()\n"));
+        assert_like_nom("Test", GRAMMAR, false);
+    }
+
+    #[test]
+    fn memoizes_annotated_rules() {
+        let grammar = "Source(Expr)
+            Expr {
+                Call(Atom LPAREN RPAREN),
+                Atom
+            }
+            @memo
+            Atom {
+                Paren(LPAREN Expr RPAREN),
+                Num(int)
+            }";
+        let annotated = outputs(grammar, |options| options.recursive_descent = true);
+        let parsers = &annotated["parsers.rs"];
+        assert_rust("parsers.rs", parsers);
+        assert!(parsers.contains("pub struct Memo<'a> {\n    pub atom: MemoTable<Atom>,\n"));
+        assert!(parsers.contains("::descr_common::cursor::parse_with(input, Memo::default(), start)"));
+        assert!(parsers.contains("pub fn atom<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Atom> {\n    c.memo(|m| &mut m.atom, |c| {\n"));
+        assert!(!parsers.contains("m.expr"));
+        // Memoized results are cloned
+        assert!(annotated["ast.rs"].contains("#[derive(Debug, Clone)]\npub enum Atom {"));
+        assert_like_nom("Memo", grammar, true);
+        // All rules
+        let all = outputs(grammar, |options| {
+            options.recursive_descent = true;
+            options.memoize = true;
+        });
+        assert!(all["parsers.rs"].contains("c.memo(|m| &mut m.expr, |c| {"));
+        // Annotation is ignored by nom parsers
+        let nom = outputs(grammar, |options| options.memoize = true);
+        assert!(!nom["parsers.rs"].contains("Memo"));
+        assert!(!nom["ast.rs"].contains("Clone"));
+    }
+
+    #[test]
+    fn repo_grammars_parse_like_nom() {
+        for &(path, _) in &REPO_GRAMMARS {
+            assert_like_nom(path, &repo_grammar(path), false);
+            assert_like_nom(path, &repo_grammar(path), true);
+        }
+    }
+
+    /// Samples of a repo grammar, with
+    /// the grammar itself for descr.lang
+    fn samples(path: &str) -> Vec<String> {
+        let grammar = repo_grammar(path);
+        let mut samples = with_data(&grammar, |data| {
+            let mut options = SampleOptions::new();
+            options.seed = 7;
+            Sampler::new(data, options).samples(200)
+        });
+        if path == "descr.lang" {
+            samples.push(grammar);
+        }
+        samples
+    }
+
+    /// Panics unless the backends give the same
+    /// tree for each sample, or all fail
+    macro_rules! assert_same_trees {
+        ($path:expr, $lang:ident) => {{
+            let mut parsed = 0;
+            for sample in samples($path) {
+                let nom_res = match $lang::parsers::start(sample.as_bytes()) {
+                    IResult::Done(_, res) => Some(format!("{:?}", res)),
+                    _ => None,
+                };
+                let rd_res = $lang::rd_parsers::parse(sample.as_bytes())
+                    .ok()
+                    .map(|res| format!("{:?}", res));
+                let memo_res = $lang::memo_parsers::parse(sample.as_bytes())
+                    .ok()
+                    .map(|res| format!("{:?}", res));
+                assert_eq!(rd_res, nom_res, "{}: backends differ on:\n{}", $path, sample);
+                assert_eq!(memo_res, nom_res, "{}: memo differs on:\n{}", $path, sample);
+                if nom_res.is_some() {
+                    parsed += 1;
+                }
+            }
+            assert!(parsed > 100, "{}: {} samples parsed", $path, parsed);
+        }};
+    }
+
+    #[test]
+    fn repo_grammar_samples_parse_the_same() {
+        // The compared parsers are the current outputs
+        for &(path, module) in &[
+            ("langs/json-descr/json.lang", "json"),
+            ("langs/rust-descr/rust.lang", "rust"),
+            ("descr.lang", "descr"),
+        ] {
+            let grammar = repo_grammar(path);
+            let dir = format!("{}/../examples/parser_bench/{}", env!("CARGO_MANIFEST_DIR"), module);
+            let read = |name: &str| ::std::fs::read_to_string(format!("{}/{}", dir, name)).unwrap();
+            assert_eq!(read("parsers.rs"), outputs(&grammar, |_| {})["parsers.rs"], "{}", path);
+            let rd = outputs(&grammar, |options| options.recursive_descent = true);
+            assert_eq!(read("rd_parsers.rs"), rd["parsers.rs"], "{}", path);
+            let memo = outputs(&grammar, |options| {
+                options.recursive_descent = true;
+                options.memoize = true;
+            });
+            assert_eq!(read("memo_parsers.rs"), memo["parsers.rs"], "{}", path);
+        }
+        assert_same_trees!("langs/json-descr/json.lang", bench_json);
+        assert_same_trees!("langs/rust-descr/rust.lang", bench_rust);
+        assert_same_trees!("descr.lang", bench_descr);
+    }
+}
@@ -6,6 +6,7 @@ use std::collections::hash_map::Entry::{Vacant, Occupied};
 use self::json_descr::lang::ast::*;
 use itertools::Itertools;
 use descr_common::util::SortedHashMap;
+use process::syntax_formats;
 
 #[derive(Debug)]
 pub struct SyntaxData {
@@ -40,13 +41,17 @@ impl SyntaxData {
             .collect::<Vec<_>>()
     }
 
-    // Recursively get merged parent entries
+    // Recursively get merged parent entries,
+    // once each as rules can refer to themselves
     pub fn get_parent_entries<S: Into<String>>(&self, parent: S, mut v: Vec<String>) -> Vec<String> {
         let parent = parent.into();
         match self.parent_entries.get(&parent) {
             None => {},
             Some(entries) => {
                 for entry in entries {
+                    if v.contains(entry) {
+                        continue;
+                    }
                     v.push(entry.clone());
                     v = self.get_parent_entries(entry.clone(), v);
                 }
@@ -74,6 +79,10 @@ impl SyntaxEntry {
             match chr {
                 '"' => s.push_str("\\\""),
                 '\\' => s.push_str("\\\\"),
+                '\n' => s.push_str("\\n"),
+                '\r' => s.push_str("\\r"),
+                '\t' => s.push_str("\\t"),
+                other if (other as u32) < 0x20 => s.push_str(&format!("\\u{:04x}", other as u32)),
                 other => s.push(other)
             }
         }
@@ -81,7 +90,8 @@ impl SyntaxEntry {
     }
 
     // Chain in parent entries and create
-    // vec of JsObjects with includes
+    // vec of JsObjects with includes, of
+    // the entries in the repository
     fn collect_pattern_includes(items: &Vec<&str>, syntax_data: &SyntaxData) -> JsVal {
         JsVal::array_val(items
             .iter()
@@ -91,6 +101,7 @@ impl SyntaxEntry {
             .chain(items.iter().map(|e| { e.to_string() }))
             //.chain(items.iter().map(|i| { String::from(*i) }).collect::<Vec<_>>())
             .unique()
+            .filter(|item| syntax_data.entries.contains_key(item))
             .map(|item| {
                 let mut key_ref = String::with_capacity(item.len() + 1);
                 key_ref.push('#');
@@ -191,12 +202,26 @@ impl<'a, 'd: 'a> CodegenSyntax<'a, 'd> {
         CodegenSyntax { data }
     }
 
-    pub fn gen(&self) {
+    pub fn gen(&self) -> String {
         use self::json_descr::lang::to_source::ToSource;
         let syntax_data = self.gen_syntax_data();
         let root = self.gen_js_object(syntax_data);
-        let s = String::new();
-        println!("Js source: {}", ToSource::js_object(s, &root));
+        ToSource::js_object(String::new(), &root)
+    }
+
+    /// Same grammar as plist xml (.tmLanguage)
+    pub fn gen_plist(&self) -> String {
+        let syntax_data = self.gen_syntax_data();
+        syntax_formats::to_plist(&self.gen_js_object(syntax_data))
+    }
+
+    /// Same grammar as .sublime-syntax
+    pub fn gen_sublime_syntax(&self) -> String {
+        let syntax_data = self.gen_syntax_data();
+        syntax_formats::to_sublime_syntax(
+            &self.gen_js_object(syntax_data),
+            &[self.data.lang_id()],
+        )
     }
 
     pub fn gen_syntax_data(&self) -> SyntaxData {
@@ -385,7 +410,7 @@ impl<'a, 'd: 'a> CodegenSyntax<'a, 'd> {
                                         end: ref inner_end
                                     } => {
                                         // Combine start (patterns) end + entry
-                                        let mut new_begin = inner_begin.clone();
+                                        let new_begin = inner_begin.clone();
                                         let mut new_end = inner_end.clone();
                                         new_end.append(collect);
                                         SyntaxEntry::BeginEnd {
@@ -470,7 +495,7 @@ impl<'a, 'd: 'a> CodegenSyntax<'a, 'd> {
             JsVal::string_val(self.data.name.clone())
         ));
         let mut scope_name = String::from("source.");
-        scope_name.push_str(&self.data.name);
+        scope_name.push_str(&self.data.lang_id());
         root.items.push(ObjectPair::new(
             "scopeName".to_string(),
             JsVal::string_val(scope_name)
@@ -503,4 +528,75 @@ impl<'a, 'd: 'a> CodegenSyntax<'a, 'd> {
         root
     }
     
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use process::test_util::*;
+
+    /// Includes in the patterns of value
+    fn includes(value: &Value, found: &mut Vec<String>) {
+        match *value {
+            Value::Object(ref object) => for (key, value) in object {
+                match value.as_str() {
+                    Some(include) if key == "include" => found.push(include.to_string()),
+                    _ => includes(value, found),
+                }
+            },
+            Value::Array(ref values) => for value in values {
+                includes(value, found);
+            },
+            _ => {}
+        }
+    }
+
+    /// Panics unless the grammar is json where
+    /// each include is in the repository
+    fn assert_includes_resolve(name: &str, grammar: &str) -> Value {
+        let outputs = outputs(grammar, |_| {});
+        let file = outputs.keys().find(|file| file.ends_with(".tmLanguage.json")).unwrap();
+        let tm_language = json(name, &outputs[file]);
+        let mut found = Vec::new();
+        includes(&tm_language, &mut found);
+        assert!(!found.is_empty());
+        for include in found {
+            assert!(include.starts_with('#'), "{}: {}", name, include);
+            assert!(tm_language["repository"].get(&include[1..]).is_some(), "{}: no {}", name, include);
+        }
+        tm_language
+    }
+
+    #[test]
+    fn scopes_rules_and_tokens() {
+        let tm_language = assert_includes_resolve(
+            "Test",
+            "Source(items:sourceItems)
+            sourceItems:SourceItem[] WS {
+                Less(@syntax(name=\"keyword.operator\") LT int),
+                Group(LPAREN sourceItems RPAREN)
+            }",
+        );
+        assert_eq!(tm_language["scopeName"], "source.test");
+        let less = &tm_language["repository"]["Less"];
+        assert_eq!(less["match"], "\\s*(<)\\s*([-\\+]?[1-9]+)");
+        assert_eq!(less["captures"]["1"]["name"], "keyword.operator");
+        assert_eq!(less["captures"]["2"]["name"], "constant.numeric");
+        // Recursive rules begin and end
+        let group = &tm_language["repository"]["Group"];
+        assert_eq!(group["begin"], "\\s*\\(");
+        assert_eq!(group["end"], "\\s*\\)");
+    }
+
+    #[test]
+    fn includes_rules_that_refer_to_each_other() {
+        // A and B are each other's parents
+        assert_includes_resolve("Test", "Source { A }\nA(B)\nB { A }");
+    }
+
+    #[test]
+    fn repo_grammars_include_repository_rules() {
+        for &(path, _) in &REPO_GRAMMARS {
+            assert_includes_resolve(path, &repo_grammar(path));
+        }
+    }
+}
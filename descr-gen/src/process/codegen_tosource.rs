@@ -1,13 +1,13 @@
 use lang_data::data::*;
 use lang_data::rule::*;
 use descr_common::util::*;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 pub struct CodegenToSource<'a, 'd: 'a> {
     data: &'a LangData<'d>
 }
 struct AstRules<'a, 'd: 'a> {
-    pub rules: HashMap<&'a str, Vec<&'a AstPartsRule<'d>>>
+    pub rules: BTreeMap<&'a str, Vec<&'a AstPartsRule<'d>>>
 }
 impl<'a, 'd: 'a> AstRules<'a, 'd> {
     pub fn insert(&mut self, ast_type: &'d str, rule: &'a AstPartsRule<'d>) {
@@ -23,16 +23,16 @@ impl<'a, 'd> CodegenToSource<'a, 'd> {
     }
 
     fn collect_rules(&self) -> AstRules<'a, 'd> {
-        let mut rules = AstRules { rules: HashMap::new() };
+        let mut rules = AstRules { rules: BTreeMap::new() };
         // Add rules by ast key
-        for (_key, ast_data) in &self.data.ast_data {
+        for (_key, ast_data) in self.data.ast_data.sorted_iter() {
             for rule in &ast_data.rules {
                 if let &AstRule::PartsRule(ref parts_rule) = rule {
                     rules.insert(parts_rule.ast_type, parts_rule);
                 }
             }
         }
-        for (_key, list_data) in &self.data.list_data {
+        for (_key, list_data) in self.data.list_data.sorted_iter() {
             for rule in &list_data.rules {
                 if let &AstRule::PartsRule(ref parts_rule) = &rule.ast_rule {
                     rules.insert(parts_rule.ast_type, parts_rule);
@@ -42,6 +42,87 @@ impl<'a, 'd> CodegenToSource<'a, 'd> {
         rules
     }
 
+    /// Required parts of optional members
+    /// are written like optional parts
+    fn add_rule(&self, mut s: String, ast_type: &str, rule: &AstPartsRule<'d>) -> String {
+        let ast_struct = self.data.ast_structs.get(ast_type);
+        for (i, part) in rule.parts.iter().enumerate() {
+            let is_option = match (ast_struct, part.member_key) {
+                (Some(ast_struct), Some(member_key)) => match ast_struct.members.get(member_key) {
+                    Some(member) => member.tpe.is_option(member, self.data),
+                    None => false,
+                },
+                _ => false,
+            };
+            if space_before(&rule.parts, i) {
+                s += "        s += \" \";\n";
+            }
+            s = part.add_to_source(s, self.data, is_option);
+        }
+        s
+    }
+
+    /// Conditions for writing a node with rule.
+    /// Members the rule doesn't have must be
+    /// empty, and optional members it requires
+    /// must be set.
+    fn rule_conditions(&self, ast_type: &str, rule: &AstPartsRule<'d>) -> Vec<String> {
+        let mut conditions = Vec::new();
+        let ast_struct = match self.data.ast_structs.get(ast_type) {
+            Some(ast_struct) => ast_struct,
+            None => return conditions,
+        };
+        let (present, required) = rule.members();
+        for member_name in &ast_struct.members_ordered {
+            let member = ast_struct.members.get(member_name).expect("Could not find struct member");
+            let sc = member.sc();
+            let type_name = member.tpe.add_type(String::new(), self.data);
+            if member.tpe.is_option(member, self.data) {
+                if !present.contains(member_name) {
+                    conditions.push(format!("node.{}.is_none()", sc));
+                } else if required.contains(member_name) {
+                    conditions.push(format!("node.{}.is_some()", sc));
+                }
+            } else if type_name == "bool" {
+                if !present.contains(member_name) {
+                    conditions.push(format!("!node.{}", sc));
+                } else if required.contains(member_name) {
+                    conditions.push(format!("node.{}", sc));
+                }
+            } else if type_name.starts_with("Vec<") && !present.contains(member_name) {
+                conditions.push(format!("node.{}.is_empty()", sc));
+            }
+        }
+        conditions
+    }
+
+    /// The first item of the list after a leading
+    /// list must have its part of that list empty
+    fn leading_condition(&self, leading: &LeadingList) -> String {
+        let mut s = String::new();
+        append!(s, "node." self.data.sc(leading.member) ".first().map_or(true, |item| ");
+        let list_type = self.data.list_data.get(leading.list_key).and_then(|list_data| list_data.ast_type);
+        match list_type.and_then(|list_type| self.data.ast_enums.get(list_type)) {
+            Some(ast_enum) => {
+                s += "match item { ";
+                for enum_item in &ast_enum.items {
+                    append!(s, "&" ast_enum.name "::" enum_item "Item(ref item) => ");
+                    match leading.items.iter().find(|&&(item_type, _)| item_type == *enum_item) {
+                        Some(&(_, member)) => {
+                            append!(s, "item." self.data.sc(member) ".is_empty(), ");
+                        }
+                        None => s += "true, ",
+                    }
+                }
+                s += "})";
+            }
+            None => {
+                append!(s, "item." self.data.sc(leading.items[0].1) ".is_empty())");
+            }
+        }
+        s
+    }
+
     pub fn gen(&self) -> String {
         let mut s = String::with_capacity(
             self.data.ast_data.len() * 100
@@ -59,15 +140,36 @@ impl<'a, 'd> CodegenToSource<'a, 'd> {
                 continue;
             }
             append!(s, "    pub fn " self.data.sc(ast_type) "(mut s: String, node: &'a " ast_type ") -> String {\n");
-            for rule in rules {
-                // Todo: Possibly create if statement
-                // if there are several rules for the
-                // same type, comparing optional
-                // members at least
-                for part in &rule.parts {
-                    s += "        s += \" \";\n";
-                    s = part.add_to_source(s, self.data, false);
+            // With several rules, the first rule
+            // matching the members of the node.
+            // Rules an earlier rule reads instead
+            // are left out.
+            let rules = rules
+                .iter()
+                .enumerate()
+                .filter(|&(i, rule)| !rules[..i].iter().any(|earlier| rule.is_read_by(earlier, self.data)))
+                .map(|(_, rule)| *rule)
+                .collect::<Vec<_>>();
+            let mut is_if = false;
+            for (i, rule) in rules.iter().enumerate() {
+                let mut conditions = self.rule_conditions(ast_type, rule);
+                if let Some(leading) = rule.leading_list(self.data) {
+                    conditions.push(self.leading_condition(&leading));
                 }
+                if conditions.is_empty() || i == rules.len() - 1 {
+                    if is_if {
+                        s += "        } else {\n";
+                    }
+                    s = self.add_rule(s, ast_type, rule);
+                    break;
+                }
+                s += if is_if { "        } else if " } else { "        if " };
+                append!(s, &conditions.join(" && ") " {\n");
+                s = self.add_rule(s, ast_type, rule);
+                is_if = true;
+            }
+            if is_if {
+                s += "        }\n";
             }
             s += "        s\n";
             s += "    }\n\n";
@@ -85,12 +187,12 @@ impl<'a, 'd> CodegenToSource<'a, 'd> {
                 for enum_item in &ast_enum.items {
                     append!(s 3, "&" ast_enum.name "::" enum_item " => {\n");
                     indent!(s 4);
+                    // Items without members are
+                    // written with the first rule
                     let rules = ast_rules.rules.get(enum_item).unwrap();
-                    for rule in rules {
-                        for part in &rule.parts {
-                            s += "s += \" \";\n";
-                            s = part.add_to_source(s, self.data, false);
-                        }
+                    for part in &rules[0].parts {
+                        s += "s += \" \";\n";
+                        s = part.add_to_source(s, self.data, false);
                     }
                     append!(s 3, "},\n");
                 }
@@ -108,4 +210,77 @@ impl<'a, 'd> CodegenToSource<'a, 'd> {
         s += "}";
         s
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generator::{build_data, parse_grammar};
+
+    fn to_source(grammar: &[u8]) -> String {
+        let ast = parse_grammar(grammar).unwrap();
+        let mut data = LangData::new(false, "Test".to_string());
+        build_data(grammar, &ast, &mut data).unwrap();
+        CodegenToSource::new(&data).gen()
+    }
+
+    #[test]
+    fn writes_with_rule_matching_members() {
+        let s = to_source(b"
+            Source(items:items)
+            items:Stmt[] WS {
+                Cond(\"if\" cond:ident \"then\" body:ident \"else\" other:ident),
+                Cond(\"if\" cond:ident \"then\" body:ident \"end\" other:ident?),
+                Print(\"print\" ident),
+                Print(\"say\" ident)
+            }
+        ");
+        let cond_fn = &s[s.find("pub fn cond(").unwrap()..];
+        let cond_fn = &cond_fn[..cond_fn.find("\n    }\n").unwrap()];
+        let if_else = cond_fn.find("if node.other.is_some() {").unwrap();
+        let else_tag = cond_fn.find("s += \"else\";").unwrap();
+        let if_end = cond_fn.find("} else {").unwrap();
+        let end_tag = cond_fn.find("s += \"end\";").unwrap();
+        assert!(if_else < else_tag && else_tag < if_end && if_end < end_tag);
+        assert_eq!(cond_fn.matches("if let Some(some_val) = node.other").count(), 2);
+        // Same members, the first rule is used
+        let print_fn = &s[s.find("pub fn print(").unwrap()..];
+        let print_fn = &print_fn[..print_fn.find("\n    }\n").unwrap()];
+        assert!(print_fn.contains("s += \"print\";"));
+        assert!(!print_fn.contains("say") && !print_fn.contains("if "));
+    }
+
+    #[test]
+    fn writes_annotated_first_item_in_parens() {
+        let s = to_source(b"
+            Source(defs:defs)
+            defs[] WS Def
+            Def {
+                Rule(annots parts \"=>\" ident?),
+                Rule(annots LPAREN parts RPAREN \"=>\" ident?),
+                Rule(annots ident? LPAREN parts RPAREN)
+            }
+            annots[] WS Annot
+            Annot(\"@\" ident)
+            parts:Part[] WS {
+                Named(annots name:ident),
+                Group(annots LPAREN parts RPAREN)
+            }
+        ");
+        let rule_fn = &s[s.find("pub fn rule(").unwrap()..];
+        let rule_fn = &rule_fn[..rule_fn.find("\n    }\n").unwrap()];
+        // Short form unless the annotations of the first
+        // part would be read as the rule's, then the form
+        // with the list in parens after the name
+        assert!(rule_fn.contains(
+            "if node.parts.first().map_or(true, |item| match item { \
+             &Part::NamedItem(ref item) => item.annots.is_empty(), \
+             &Part::GroupItem(ref item) => item.annots.is_empty(), }) {"
+        ));
+        let else_pos = rule_fn.find("} else {").unwrap();
+        let ident_pos = rule_fn.rfind("if let Some(some_val) = node.ident").unwrap();
+        let paren_pos = rule_fn.rfind("s.push('(');").unwrap();
+        assert!(else_pos < ident_pos && ident_pos < paren_pos);
+        assert_eq!(rule_fn.matches("s.push('(');").count(), 1);
+    }
+}
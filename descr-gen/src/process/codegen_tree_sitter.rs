@@ -0,0 +1,624 @@
+use descr_common::util::*;
+use lang_data::data::*;
+use lang_data::rule::*;
+use lang_data::typed_part::TypedPart;
+use std::collections::BTreeSet;
+
+/// Generates a tree-sitter grammar.js, and
+/// highlights.scm from syntax annotations.
+/// Rules are named by snake cased keys, and
+/// members become fields. Whitespace is left
+/// to extras, so WS tokens are skipped.
+pub struct CodegenTreeSitter<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+}
+impl<'a, 'd> CodegenTreeSitter<'a, 'd> {
+    pub fn new(data: &'a LangData<'d>) -> CodegenTreeSitter<'a, 'd> {
+        CodegenTreeSitter { data }
+    }
+
+    /// Grammar name, lowercase identifier
+    fn grammar_name(&self) -> String {
+        self.data
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+
+    /// Rules in grammar order, start rule first
+    /// since tree-sitter uses the first rule as root
+    fn rule_keys(&self) -> Vec<&'d str> {
+        let mut keys = Vec::new();
+        if let Some(start_key) = self.data.start_key {
+            keys.push(start_key);
+        }
+        for (key, _) in self.data.ast_data.sorted_iter() {
+            if !keys.contains(key) {
+                keys.push(*key);
+            }
+        }
+        for (key, _) in self.data.list_data.sorted_iter() {
+            if !keys.contains(key) {
+                keys.push(*key);
+            }
+        }
+        keys
+    }
+
+    /// Builtin tokens used by the grammar,
+    /// named so they don't clash with rules
+    fn builtin_name(&self, name: &str) -> String {
+        let mut name = name.to_string();
+        while self
+            .data
+            .ast_data
+            .keys()
+            .any(|key| self.data.sc(key) == name)
+            || self
+                .data
+                .list_data
+                .keys()
+                .any(|key| self.data.sc(key) == name)
+        {
+            name += "_token";
+        }
+        name
+    }
+
+    pub fn gen_grammar(&self) -> String {
+        let mut s = String::with_capacity(self.data.ast_data.len() * 200);
+        let mut builtins = BTreeSet::new();
+        let mut rules = Vec::new();
+        for key in self.rule_keys() {
+            self.add_rules(&mut rules, key, &mut builtins);
+        }
+        s += "module.exports = grammar({\n";
+        append!(s 1, "name: '" self.grammar_name().as_str() "',\n\n");
+        s += "    extras: $ => [/\\s/],\n\n";
+        if builtins.contains("ident") {
+            append!(s 1, "word: $ => $." self.builtin_name("identifier").as_str() ",\n\n");
+        }
+        s += "    rules: {\n";
+        for &(ref name, ref bodies) in &rules {
+            append!(s 2, name.as_str() ": $ => ");
+            if bodies.len() == 1 {
+                s += &bodies[0];
+            } else {
+                append!(s, "choice(" bodies.join(", ").as_str() ")");
+            }
+            s += ",\n\n";
+        }
+        for builtin in &builtins {
+            s = self.gen_builtin(s, builtin);
+        }
+        s.pop();
+        s += "    }\n";
+        s += "});\n\n";
+        s += "function sepBy1(sep, rule) {\n";
+        s += "    return seq(rule, repeat(seq(sep, rule)));\n";
+        s += "}\n";
+        s
+    }
+
+    fn gen_builtin(&self, mut s: String, builtin: &str) -> String {
+        let (name, regex) = match builtin {
+            "ident" => ("identifier", "/[_]*[a-zA-Z][a-zA-Z0-9_]*/"),
+            "int" => ("integer", "/[-+]?[0-9]+/"),
+            "string" => ("string", "/\"(?:[^\"\\\\]|\\\\.)*\"/"),
+            _ => ("function", "/[^\\s]+/"),
+        };
+        append!(s 2, self.builtin_name(name).as_str() ": $ => " regex ",\n\n");
+        s
+    }
+
+    /// Adds body to rule by name. Ast types
+    /// can be given by several rules, which
+    /// becomes a choice between the bodies.
+    fn add_body(rules: &mut Vec<(String, Vec<String>)>, name: &str, body: String) {
+        match rules.iter().position(|&(ref existing, _)| existing == name) {
+            Some(i) => {
+                if !rules[i].1.contains(&body) {
+                    rules[i].1.push(body);
+                }
+            }
+            None => rules.push((name.to_string(), vec![body])),
+        }
+    }
+
+    fn add_rules(
+        &self,
+        rules: &mut Vec<(String, Vec<String>)>,
+        key: &'d str,
+        builtins: &mut BTreeSet<&'static str>,
+    ) {
+        let is_start = self.data.start_key == Some(key);
+        let (item_rules, body) = if let Some(ast_data) = self.data.ast_data.get(key) {
+            let item_rules = ast_data.rules.iter().collect::<Vec<_>>();
+            match ast_data.rules.first() {
+                Some(&AstRule::PartsRule(ref parts_rule))
+                    if ast_data.rules.len() == 1 && parts_rule.ast_type == key =>
+                {
+                    (item_rules, None)
+                }
+                // Enum, items become choice
+                _ => {
+                    let body = self.gen_choice(String::new(), &item_rules);
+                    (item_rules, Some(body))
+                }
+            }
+        } else if let Some(list_data) = self.data.list_data.get(key) {
+            let item_rules = list_data
+                .rules
+                .iter()
+                .map(|rule| &rule.ast_rule)
+                .collect::<Vec<_>>();
+            let sep = list_data
+                .sep
+                .and_then(|sep| match self.data.typed_parts.get(sep) {
+                    Some(&TypedPart::WSPart) | None => None,
+                    Some(typed_part) => Some(typed_part),
+                });
+            let item = self.gen_choice(String::new(), &item_rules);
+            // Only the start rule may match empty
+            // input, other lists are optional where used
+            let mut s = String::new();
+            match sep {
+                Some(sep) => {
+                    if is_start {
+                        s += "optional(";
+                    }
+                    s += "sepBy1(";
+                    s = self.gen_typed_part(s, sep, builtins);
+                    append!(s, ", " item.as_str() ")");
+                    if is_start {
+                        s += ")";
+                    }
+                }
+                None => {
+                    if is_start {
+                        append!(s, "repeat(" item.as_str() ")");
+                    } else {
+                        append!(s, "repeat1(" item.as_str() ")");
+                    }
+                }
+            }
+            (item_rules, Some(s))
+        } else {
+            return;
+        };
+        if let Some(body) = body {
+            Self::add_body(rules, self.data.sc(key), body);
+        }
+        for rule in item_rules {
+            if let &AstRule::PartsRule(ref parts_rule) = rule {
+                let body = self.gen_seq(String::new(), &parts_rule.parts, builtins);
+                Self::add_body(rules, self.data.sc(parts_rule.ast_type), body);
+            }
+        }
+    }
+
+    /// Choice between rules, or the rule
+    /// itself when there is one
+    fn gen_choice(&self, mut s: String, rules: &[&AstRule<'d>]) -> String {
+        let mut refs = Vec::new();
+        for rule in rules {
+            let rule_ref = self.gen_rule_ref(String::new(), rule);
+            if !refs.contains(&rule_ref) {
+                refs.push(rule_ref);
+            }
+        }
+        if refs.len() == 1 {
+            s += &refs[0];
+        } else {
+            append!(s, "choice(" refs.join(", ").as_str() ")");
+        }
+        s
+    }
+
+    fn gen_rule_ref(&self, mut s: String, rule: &AstRule<'d>) -> String {
+        match rule {
+            &AstRule::RefRule(rule_ref) => {
+                append!(s, "$." self.data.sc(rule_ref));
+            }
+            &AstRule::PartsRule(ref parts_rule) => {
+                append!(s, "$." self.data.sc(parts_rule.ast_type));
+            }
+        }
+        s
+    }
+
+    fn gen_seq(
+        &self,
+        mut s: String,
+        parts: &[AstRulePart<'d>],
+        builtins: &mut BTreeSet<&'static str>,
+    ) -> String {
+        let mut exprs = Vec::new();
+        for part in parts {
+            if let Some(expr) = self.gen_part(part, builtins) {
+                exprs.push(expr);
+            }
+        }
+        match exprs.len() {
+            // Tree-sitter has no empty rule
+            0 => s += "blank()",
+            1 => s += &exprs[0],
+            _ => {
+                s += "seq(";
+                s += &exprs.join(", ");
+                s += ")";
+            }
+        }
+        s
+    }
+
+    fn gen_part(
+        &self,
+        part: &AstRulePart<'d>,
+        builtins: &mut BTreeSet<&'static str>,
+    ) -> Option<String> {
+        let mut expr = if part.not {
+            self.gen_until(&part.token)
+        } else {
+            match &part.token {
+                &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                    Some(&TypedPart::WSPart) => return None,
+                    Some(&TypedPart::ListPart { key }) => {
+                        // Lists only match empty as start
+                        let list = format!("$.{}", self.data.sc(key));
+                        if part.optional {
+                            list
+                        } else {
+                            format!("optional({})", list)
+                        }
+                    }
+                    Some(typed_part) => self.gen_typed_part(String::new(), typed_part, builtins),
+                    None => return None,
+                },
+                &AstRuleToken::Tag(tag) => Self::literal(tag),
+                &AstRuleToken::Func(..) => {
+                    builtins.insert("fn");
+                    format!("$.{}", self.builtin_name("function"))
+                }
+                &AstRuleToken::Group(ref parts) => self.gen_seq(String::new(), parts, builtins),
+            }
+        };
+        if let Some(member_key) = part.member_key {
+            expr = format!("field('{}', {})", self.data.sc(member_key), expr);
+        }
+        if part.optional {
+            expr = format!("optional({})", expr);
+        }
+        Some(expr)
+    }
+
+    fn gen_typed_part(
+        &self,
+        mut s: String,
+        typed_part: &TypedPart,
+        builtins: &mut BTreeSet<&'static str>,
+    ) -> String {
+        match typed_part {
+            &TypedPart::AstPart { key } | &TypedPart::ListPart { key } => {
+                append!(s, "$." self.data.sc(key));
+            }
+            &TypedPart::CharPart { chr, .. } => s += &Self::literal(&chr.to_string()),
+            &TypedPart::TagPart { tag, .. } => s += &Self::literal(tag),
+            &TypedPart::IntPart { .. } => {
+                builtins.insert("int");
+                append!(s, "$." self.builtin_name("integer").as_str());
+            }
+            &TypedPart::IdentPart { .. } => {
+                builtins.insert("ident");
+                append!(s, "$." self.builtin_name("identifier").as_str());
+            }
+            &TypedPart::StringPart { .. } | &TypedPart::StrPart { .. } => {
+                builtins.insert("string");
+                append!(s, "$." self.builtin_name("string").as_str());
+            }
+            &TypedPart::FnPart { .. } => {
+                builtins.insert("fn");
+                append!(s, "$." self.builtin_name("function").as_str());
+            }
+            &TypedPart::WSPart => s += "/\\s+/",
+        }
+        s
+    }
+
+    /// Text up to a token, for not parts.
+    /// Tree-sitter regexes have no lookahead, so
+    /// this is exact for chars and two char tags,
+    /// and only checks two chars of longer tags.
+    fn gen_until(&self, token: &AstRuleToken) -> String {
+        let until = match token {
+            &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                Some(&TypedPart::CharPart { chr, .. }) => Some(chr.to_string()),
+                Some(&TypedPart::TagPart { tag, .. }) => Some(tag.to_string()),
+                _ => None,
+            },
+            &AstRuleToken::Tag(tag) => Some(tag.to_string()),
+            &AstRuleToken::Group(ref parts) => match parts.first() {
+                Some(part) => return self.gen_until(&part.token),
+                None => None,
+            },
+            _ => None,
+        };
+        let mut chars = match until {
+            Some(ref until) => until.chars(),
+            None => return "token(prec(-1, /[^\\n]+/))".to_string(),
+        };
+        let first = chars.next().map(Self::regex_char).unwrap_or_default();
+        match chars.next() {
+            None => format!("token(prec(-1, /[^{}]+/))", first),
+            Some(second) => format!(
+                "token(prec(-1, /([^{0}]|{0}+[^{0}{1}])+/))",
+                first,
+                Self::regex_char(second)
+            ),
+        }
+    }
+
+    fn regex_char(c: char) -> String {
+        match c {
+            '\\' | '/' | '^' | '$' | '.' | '|' | '?' | '*' | '+' | '(' | ')' | '[' | ']' | '{'
+            | '}' | '-' => format!("\\{}", c),
+            c => c.to_string(),
+        }
+    }
+
+    /// Js string literal
+    fn literal(text: &str) -> String {
+        let mut s = String::with_capacity(text.len() + 2);
+        s.push('\'');
+        for c in text.chars() {
+            match c {
+                '\'' => s += "\\'",
+                '\\' => s += "\\\\",
+                c => s.push(c),
+            }
+        }
+        s.push('\'');
+        s
+    }
+
+    /// Query file, with patterns for syntax
+    /// annotations first, then default scopes
+    /// for tokens like the generated syntax
+    pub fn gen_highlights(&self) -> String {
+        let mut s = String::new();
+        let mut keywords = BTreeSet::new();
+        let mut builtins = BTreeSet::new();
+        for key in self.rule_keys() {
+            let rules = match self.data.ast_data.get(key) {
+                Some(ast_data) => ast_data.rules.iter().collect::<Vec<_>>(),
+                None => match self.data.list_data.get(key) {
+                    Some(list_data) => list_data.rules.iter().map(|rule| &rule.ast_rule).collect(),
+                    None => Vec::new(),
+                },
+            };
+            for rule in rules {
+                if let &AstRule::PartsRule(ref parts_rule) = rule {
+                    let node = self.data.sc(parts_rule.ast_type);
+                    s = self.gen_part_highlights(
+                        s,
+                        node,
+                        &parts_rule.parts,
+                        &mut keywords,
+                        &mut builtins,
+                    );
+                }
+            }
+        }
+        if !keywords.is_empty() {
+            s += "[\n";
+            for keyword in &keywords {
+                append!(s 1, Self::query_string(keyword).as_str() "\n");
+            }
+            s += "] @keyword.other\n";
+        }
+        for builtin in &builtins {
+            let (name, scope) = match *builtin {
+                "ident" => ("identifier", "variable.other"),
+                "int" => ("integer", "constant.numeric"),
+                _ => ("string", "string.quoted"),
+            };
+            append!(s, "(" self.builtin_name(name).as_str() ") @" scope "\n");
+        }
+        s
+    }
+
+    fn gen_part_highlights(
+        &self,
+        mut s: String,
+        node: &str,
+        parts: &[AstRulePart<'d>],
+        keywords: &mut BTreeSet<String>,
+        builtins: &mut BTreeSet<&'static str>,
+    ) -> String {
+        for part in parts {
+            let typed_part = match &part.token {
+                &AstRuleToken::Key(key) => self.data.typed_parts.get(key),
+                _ => None,
+            };
+            if let &AstRuleToken::Group(ref parts) = &part.token {
+                s = self.gen_part_highlights(s, node, parts, keywords, builtins);
+                continue;
+            }
+            if !part.not {
+                match typed_part {
+                    Some(&TypedPart::IntPart { .. }) => {
+                        builtins.insert("int");
+                    }
+                    Some(&TypedPart::IdentPart { .. }) => {
+                        builtins.insert("ident");
+                    }
+                    Some(&TypedPart::StringPart { .. }) | Some(&TypedPart::StrPart { .. }) => {
+                        builtins.insert("string");
+                    }
+                    Some(&TypedPart::TagPart { tag, .. }) => {
+                        keywords.insert(tag.to_string());
+                    }
+                    _ => {}
+                }
+                if let &AstRuleToken::Tag(tag) = &part.token {
+                    keywords.insert(tag.to_string());
+                }
+            }
+            let scope = match part.annots.get_quoted("syntax", "name") {
+                Some(scope) => scope,
+                None => continue,
+            };
+            let target = match (part.member_key, &part.token, typed_part) {
+                (Some(member_key), ..) => format!("{}: _", self.data.sc(member_key)),
+                (None, &AstRuleToken::Tag(tag), _) => Self::query_string(tag),
+                (None, _, Some(&TypedPart::TagPart { tag, .. })) => Self::query_string(tag),
+                (None, _, Some(&TypedPart::CharPart { chr, .. })) => {
+                    Self::query_string(&chr.to_string())
+                }
+                (None, _, Some(&TypedPart::AstPart { key }))
+                | (None, _, Some(&TypedPart::ListPart { key })) => {
+                    format!("({})", self.data.sc(key))
+                }
+                _ => continue,
+            };
+            append!(s, "(" node " " target.as_str() " @" scope ")\n");
+        }
+        s
+    }
+
+    fn query_string(text: &str) -> String {
+        let mut s = String::with_capacity(text.len() + 2);
+        s.push('"');
+        for c in text.chars() {
+            match c {
+                '"' => s += "\\\"",
+                '\\' => s += "\\\\",
+                c => s.push(c),
+            }
+        }
+        s.push('"');
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use process::test_util::*;
+
+    const GRAMMAR: &str = "
+        Source(items:sourceItems)
+        sourceItems:SourceItem[] WS {
+            Say(@syntax(name=\"keyword.control\") \"say\" string),
+            Items(LPAREN values RPAREN)
+        }
+        values:Value[] COMMA {
+            Num(int)
+        }";
+
+    /// Bodies of the rules in grammar.js, in order
+    fn rules(grammar: &str) -> Vec<(String, String)> {
+        grammar
+            .lines()
+            .skip_while(|line| line.trim() != "rules: {")
+            .filter_map(|line| {
+                let line = line.trim();
+                line.find(": $ => ")
+                    .map(|pos| (line[..pos].to_string(), line[pos + 7..].to_string()))
+            })
+            .collect()
+    }
+
+    /// Names after $. in body
+    fn references(body: &str) -> Vec<&str> {
+        body.split("$.")
+            .skip(1)
+            .map(|rest| rest.split(|c: char| !c.is_alphanumeric() && c != '_').next().unwrap())
+            .collect()
+    }
+
+    /// Panics unless the grammar references only
+    /// its rules, starts with the start rule, and
+    /// highlights query fields and literals of
+    /// the rules
+    fn assert_consistent(name: &str, grammar: &str, highlights: &str, start: &str) {
+        let rules = rules(grammar).into_iter().collect::<Vec<_>>();
+        assert_eq!(rules[0].0, start, "{}: first rule", name);
+        let bodies = rules.iter().cloned().collect::<BTreeMap<_, _>>();
+        assert_eq!(bodies.len(), rules.len(), "{}: duplicate rules", name);
+        for (rule, body) in &rules {
+            for reference in references(body) {
+                assert!(bodies.contains_key(reference), "{}: {} uses undefined {}", name, rule, reference);
+            }
+        }
+        for query in highlights.lines().filter(|line| line.starts_with('(')) {
+            let mut words = query[1..].split_whitespace();
+            let node = words.next().unwrap().trim_end_matches(')');
+            let body = match bodies.get(node) {
+                Some(body) => body,
+                None => panic!("{}: {} queries undefined {}", name, query, node),
+            };
+            match words.next() {
+                Some(field) if field.ends_with(':') => {
+                    let field = format!("field('{}'", field.trim_end_matches(':'));
+                    assert!(body.contains(&field), "{}: {} has no {}", name, node, field);
+                }
+                Some(literal) if literal.starts_with('"') => {
+                    let literal = format!("'{}'", literal.trim_matches('"'));
+                    assert!(body.contains(&literal), "{}: {} has no {}", name, node, literal);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn generates_rules_and_highlights() {
+        let outputs = outputs(GRAMMAR, |options| options.tree_sitter = true);
+        let grammar = &outputs["grammar.js"];
+        assert!(grammar.contains("name: 'test'"));
+        assert_eq!(
+            rules(grammar),
+            vec![
+                ("source", "field('items', optional($.source_items)),"),
+                ("source_items", "repeat1(choice($.say, $.items)),"),
+                ("say", "seq('say', field('string', $.string)),"),
+                ("items", "seq('(', field('values', optional($.values)), ')'),"),
+                ("values", "sepBy1(',', $.num),"),
+                ("num", "field('int', $.integer),"),
+                ("integer", "/[-+]?[0-9]+/,"),
+                ("string", "/\"(?:[^\"\\\\]|\\\\.)*\"/,"),
+            ]
+            .into_iter()
+            .map(|(rule, body)| (rule.to_string(), body.to_string()))
+            .collect::<Vec<_>>()
+        );
+        let highlights = &outputs["highlights.scm"];
+        assert!(highlights.contains("(say \"say\" @keyword.control)"));
+        assert!(highlights.contains("(integer) @constant.numeric"));
+        assert_consistent("test", grammar, highlights, "source");
+    }
+
+    #[test]
+    fn repo_grammars_are_consistent() {
+        for &(path, name) in &REPO_GRAMMARS {
+            let outputs = outputs(&repo_grammar(path), |options| {
+                options.name = name.to_string();
+                options.tree_sitter = true;
+            });
+            // Parsed by the start parser
+            let parsers = &outputs["parsers.rs"];
+            let start = &parsers[parsers.find("pub start<").unwrap()..];
+            let start = &start[start.find("res: ").unwrap() + 5..];
+            let start = &start[..start.find(' ').unwrap()];
+            assert_consistent(path, &outputs["grammar.js"], &outputs["highlights.scm"], start);
+        }
+    }
+}
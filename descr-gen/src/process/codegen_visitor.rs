@@ -41,3 +41,60 @@ impl<'a, 'd> CodegenVisitor<'a, 'd> {
         s
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use syn;
+    use process::test_util::*;
+
+    /// Panics unless the visitor has a method
+    /// per ast type, and calls only those
+    fn assert_visits(name: &str, outputs: &BTreeMap<String, String>) {
+        let visitor = &outputs["visitor.rs"];
+        assert_rust(name, visitor);
+        let file = syn::parse_file(visitor).unwrap();
+        let methods = file
+            .items
+            .iter()
+            .filter_map(|item| match *item {
+                syn::Item::Trait(ref item) => Some(item),
+                _ => None,
+            })
+            .flat_map(|item| item.items.iter())
+            .filter_map(|item| match *item {
+                syn::TraitItem::Fn(ref method) => Some(method.sig.ident.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let types = item_names(&outputs["ast.rs"]);
+        assert_eq!(methods.len(), types.len(), "{}: {:?} for {:?}", name, methods, types);
+        for called in visitor.split("self.").skip(1) {
+            let called = &called[..called.find('(').unwrap()];
+            assert!(methods.iter().any(|method| method == called), "{}: no {}", name, called);
+        }
+    }
+
+    #[test]
+    fn visits_children() {
+        let outputs = outputs(
+            "Source(items:sourceItems)
+            sourceItems:SourceItem[] WS {
+                Say(\"say\" string?),
+                Group(LPAREN sourceItems RPAREN)
+            }",
+            |_| {},
+        );
+        let visitor = &outputs["visitor.rs"];
+        assert_visits("Test", &outputs);
+        assert!(visitor.contains("    fn visit_group(&mut self, node: &'a Group) {\n        for item in &node.source_items {\n            self.visit_source_item(item);\n"));
+        assert!(visitor.contains("&SourceItem::SayItem(ref inner) => self.visit_say(inner),"));
+    }
+
+    #[test]
+    fn repo_grammars_visit_all_types() {
+        for &(path, _) in &REPO_GRAMMARS {
+            assert_visits(path, &outputs(&repo_grammar(path), |_| {}));
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use lang_data::data::*;
+use lang_data::rule::*;
+use lang_data::typed_part::TypedPart;
+use process::codegen_cst::CodegenCst;
+use descr_common::util::*;
+use descr_common::json::json_str;
+
+/// Bracket pairs recognized among char tokens
+const BRACKETS: &[(&str, &str)] = &[("{", "}"), ("[", "]"), ("(", ")")];
+
+/// Generates a VS Code extension folder, with
+/// package.json contributing the language and
+/// grammar, and language-configuration.json
+/// with comments, brackets and auto closing
+/// pairs found in the grammar.
+pub struct CodegenVscode<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+}
+impl<'a, 'd> CodegenVscode<'a, 'd> {
+    pub fn new(data: &'a LangData<'d>) -> CodegenVscode<'a, 'd> {
+        CodegenVscode { data }
+    }
+
+    /// Path of grammar in extension folder
+    pub fn grammar_path(&self) -> String {
+        format!("syntaxes/{}.tmLanguage.json", self.data.lang_id())
+    }
+
+    /// Text of tag or char token
+    fn token_text(&self, token: &AstRuleToken<'d>) -> Option<String> {
+        match token {
+            &AstRuleToken::Tag(tag) => Some(tag.to_string()),
+            &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                Some(&TypedPart::CharPart { chr, .. }) => Some(chr.to_string()),
+                Some(&TypedPart::TagPart { tag, .. }) => Some(tag.to_string()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Line and block comments, from rules named
+    /// like comments with a start token, a not
+    /// part and its end token. A newline end
+    /// makes a line comment.
+    fn comments(&self) -> (Option<String>, Option<(String, String)>) {
+        let mut line = None;
+        let mut block = None;
+        let mut rules = Vec::new();
+        for (_, ast_data) in self.data.ast_data.sorted_iter() {
+            rules.extend(ast_data.rules.iter());
+        }
+        for (_, list_data) in self.data.list_data.sorted_iter() {
+            rules.extend(list_data.rules.iter().map(|rule| &rule.ast_rule));
+        }
+        for rule in rules {
+            let parts_rule = match rule {
+                &AstRule::PartsRule(ref parts_rule) => parts_rule,
+                _ => continue,
+            };
+            if !parts_rule.ast_type.to_lowercase().contains("comment") {
+                continue;
+            }
+            for window in parts_rule.parts.windows(2) {
+                if window[0].not || !window[1].not {
+                    continue;
+                }
+                let start = self.token_text(&window[0].token);
+                let end = self.token_text(&window[1].token);
+                match (start, end) {
+                    (Some(start), Some(ref end)) if end == "\n" => {
+                        line = line.or(Some(start));
+                    }
+                    (Some(start), Some(end)) => {
+                        block = block.or(Some((start, end)));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        (line, block)
+    }
+
+    /// Bracket pairs with both chars used
+    fn brackets(&self) -> Vec<(&'static str, &'static str)> {
+        let cst = CodegenCst::new(self.data);
+        let literals = cst.literals();
+        BRACKETS
+            .iter()
+            .filter(|&&(open, close)| literals.contains_key(open) && literals.contains_key(close))
+            .cloned()
+            .collect()
+    }
+
+    fn has_strings(&self) -> bool {
+        self.data.typed_parts.values().any(|typed_part| match typed_part {
+            &TypedPart::StringPart { .. } | &TypedPart::StrPart { .. } => true,
+            _ => false,
+        })
+    }
+
+    pub fn gen_package(&self) -> String {
+        let id = json_str(&self.data.lang_id());
+        let name = json_str(&self.data.name);
+        let mut s = String::with_capacity(1000);
+        s += "{\n";
+        append!(s 1, "\"name\": " id.as_str() ",\n");
+        append!(s 1, "\"displayName\": " name.as_str() ",\n");
+        append!(s 1, "\"description\": " json_str(&format!("{} language support", self.data.name)).as_str() ",\n");
+        append!(s 1, "\"version\": \"0.1.0\",\n");
+        append!(s 1, "\"engines\": {\n");
+        append!(s 2, "\"vscode\": \"^1.30.0\"\n");
+        append!(s 1, "},\n");
+        append!(s 1, "\"categories\": [\n");
+        append!(s 2, "\"Programming Languages\"\n");
+        append!(s 1, "],\n");
+        append!(s 1, "\"contributes\": {\n");
+        append!(s 2, "\"languages\": [\n");
+        append!(s 3, "{\n");
+        append!(s 4, "\"id\": " id.as_str() ",\n");
+        append!(s 4, "\"aliases\": [" name.as_str() ", " id.as_str() "],\n");
+        append!(s 4, "\"extensions\": [" json_str(&format!(".{}", self.data.lang_id())).as_str() "],\n");
+        append!(s 4, "\"configuration\": \"./language-configuration.json\"\n");
+        append!(s 3, "}\n");
+        append!(s 2, "],\n");
+        append!(s 2, "\"grammars\": [\n");
+        append!(s 3, "{\n");
+        append!(s 4, "\"language\": " id.as_str() ",\n");
+        append!(s 4, "\"scopeName\": " json_str(&format!("source.{}", self.data.lang_id())).as_str() ",\n");
+        append!(s 4, "\"path\": " json_str(&format!("./{}", self.grammar_path())).as_str() "\n");
+        append!(s 3, "}\n");
+        append!(s 2, "]\n");
+        append!(s 1, "}\n");
+        s += "}\n";
+        s
+    }
+
+    pub fn gen_language_configuration(&self) -> String {
+        let mut s = String::with_capacity(500);
+        s += "{\n";
+        let (line, block) = self.comments();
+        if line.is_some() || block.is_some() {
+            append!(s 1, "\"comments\": {\n");
+            if let Some(ref line) = line {
+                append!(s 2, "\"lineComment\": " json_str(line).as_str());
+                s += if block.is_some() { ",\n" } else { "\n" };
+            }
+            if let Some((ref start, ref end)) = block {
+                append!(s 2, "\"blockComment\": [" json_str(start).as_str() ", " json_str(end).as_str() "]\n");
+            }
+            append!(s 1, "},\n");
+        }
+        let brackets = self.brackets();
+        let mut pairs = brackets
+            .iter()
+            .map(|&(open, close)| (json_str(open), json_str(close), false))
+            .collect::<Vec<_>>();
+        if self.has_strings() {
+            pairs.push(("\"\\\"\"".to_string(), "\"\\\"\"".to_string(), true));
+        }
+        append!(s 1, "\"brackets\": [");
+        for (i, &(open, close)) in brackets.iter().enumerate() {
+            if i > 0 {
+                s += ", ";
+            }
+            append!(s, "[" json_str(open).as_str() ", " json_str(close).as_str() "]");
+        }
+        s += "],\n";
+        append!(s 1, "\"autoClosingPairs\": [");
+        for (i, &(ref open, ref close, is_string)) in pairs.iter().enumerate() {
+            s += if i > 0 { ",\n" } else { "\n" };
+            append!(s 2, "{ \"open\": " open.as_str() ", \"close\": " close.as_str());
+            if is_string {
+                s += ", \"notIn\": [\"string\"]";
+            }
+            s += " }";
+        }
+        if !pairs.is_empty() {
+            s += "\n";
+            indent!(s 1);
+        }
+        s += "],\n";
+        append!(s 1, "\"surroundingPairs\": [");
+        for (i, &(ref open, ref close, _)) in pairs.iter().enumerate() {
+            if i > 0 {
+                s += ", ";
+            }
+            append!(s, "[" open.as_str() ", " close.as_str() "]");
+        }
+        s += "]\n";
+        s += "}\n";
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use fancy_regex::Regex;
+    use process::test_util::*;
+
+    /// Panics unless the extension points at its
+    /// outputs and the grammar regexes compile
+    fn assert_extension(name: &str, outputs: &BTreeMap<String, String>) {
+        let package = json("package.json", &outputs["vscode/package.json"]);
+        let grammar = &package["contributes"]["grammars"][0];
+        let path = format!("vscode/{}", grammar["path"].as_str().unwrap().trim_start_matches("./"));
+        assert!(outputs.contains_key(&path), "{}: no {}", name, path);
+        let tm_language = json(&path, &outputs[&path]);
+        assert_eq!(grammar["scopeName"], tm_language["scopeName"]);
+        assert_eq!(grammar["language"], package["contributes"]["languages"][0]["id"]);
+        let configuration = package["contributes"]["languages"][0]["configuration"].as_str().unwrap();
+        let configuration = format!("vscode/{}", configuration.trim_start_matches("./"));
+        json(&configuration, &outputs[&configuration]);
+        let found = tm_regexes(&tm_language);
+        assert!(!found.is_empty());
+        for regex in found {
+            if let Err(err) = Regex::new(&regex) {
+                panic!("{}: {} doesn't compile: {}", name, regex, err);
+            }
+        }
+    }
+
+    #[test]
+    fn writes_extension() {
+        let outputs = outputs(
+            "Source(items:sourceItems)
+            sourceItems:SourceItem[] WS {
+                Block(LBRACE string RBRACE),
+                LineComment(\"#\" comment:!\"\n\" \"\n\"),
+                Comment(\"(*\" comment:!\"*)\" \"*)\")
+            }",
+            |options| options.vscode = true,
+        );
+        assert_eq!(outputs["vscode/syntaxes/test.tmLanguage.json"], outputs["test.tmLanguage.json"]);
+        assert_extension("Test", &outputs);
+        let tm_language = json("test.tmLanguage.json", &outputs["test.tmLanguage.json"]);
+        assert_eq!(tm_language["scopeName"], "source.test");
+        let block = Regex::new(tm_language["repository"]["Block"]["match"].as_str().unwrap()).unwrap();
+        let captures = block.captures(" { \"a \\\" b\" }").unwrap().unwrap();
+        assert_eq!(&captures[1], "\"a \\\" b\"");
+        let config = json("language-configuration.json", &outputs["vscode/language-configuration.json"]);
+        assert_eq!(config["comments"]["lineComment"], "#");
+        assert_eq!(config["comments"]["blockComment"], json("", "[\"(*\", \"*)\"]"));
+        assert_eq!(config["brackets"], json("", "[[\"{\", \"}\"]]"));
+        assert_eq!(config["autoClosingPairs"][1], json("", "{\"open\": \"\\\"\", \"close\": \"\\\"\", \"notIn\": [\"string\"]}"));
+        assert_eq!(config["surroundingPairs"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn repo_grammars_have_extensions() {
+        for &(path, _) in &REPO_GRAMMARS {
+            assert_extension(path, &outputs(&repo_grammar(path), |options| options.vscode = true));
+        }
+    }
+}
@@ -4,28 +4,38 @@ use descr_lang::gen::visitor::Visitor;
 
 pub struct GetTokens<'a, 'd: 'a> {
     data: &'a mut LangData<'d>,
+    /// Keys that could not be resolved
+    pub errors: Vec<KeyError<'d>>,
 }
 
 impl<'a, 'd> GetTokens<'a, 'd> {
     pub fn new(data: &'a mut LangData<'d>) -> GetTokens<'a, 'd> {
-        GetTokens { data }
+        GetTokens {
+            data,
+            errors: Vec::new(),
+        }
+    }
+
+    fn resolve(&mut self, key: &'d str) {
+        if let Err(err) = self.data.resolve_typed_part(key) {
+            self.errors.push(err);
+        }
     }
 }
 
 impl<'a, 'd> Visitor<'d> for GetTokens<'a, 'd> {
     fn visit_key_token(&mut self, node: &'d KeyToken) {
-        self.data.resolve_typed_part(node.key);
+        self.resolve(node.key);
     }
 
     fn visit_list_single(&mut self, node: &'d ListSingle) {
-        self.data.resolve_typed_part(node.sep);
+        self.resolve(node.sep);
     }
 
     fn visit_list_many(&mut self, node: &'d ListMany) {
-        match node.sep {
-            Some(sep) => self.data.resolve_typed_part(sep),
-            None => {}
-        };
+        if let Some(sep) = node.sep {
+            self.resolve(sep);
+        }
         for item in &node.items {
             self.visit_list_item(item);
         }
@@ -1,154 +1,244 @@
 pub mod register_keys;
+pub mod check_keys;
 pub mod get_tokens;
 pub mod build_parsers;
 pub mod build_ast;
 pub mod codegen_ast;
 pub mod codegen_parsers;
+pub mod codegen_rd;
 pub mod codegen_visitor;
 pub mod codegen_tosource;
 pub mod codegen_syntax;
+pub mod syntax_formats;
+pub mod codegen_cst;
+pub mod codegen_highlight;
+pub mod codegen_tree_sitter;
+pub mod codegen_ebnf;
+pub mod codegen_railroad;
+pub mod codegen_lsp;
+pub mod codegen_vscode;
+pub mod codegen_bench;
 
-use std::fs::File;
 use std::fs;
-use std::io::Write;
-use elapsed::measure_time;
-use self::register_keys::RegisterKeys;
-use self::get_tokens::GetTokens;
-use self::build_parsers::BuildParsers;
-use self::build_ast::BuildAst;
-use self::codegen_ast::CodegenAst;
-use self::codegen_parsers::CodegenParsers;
-use self::codegen_visitor::CodegenVisitor;
-use self::codegen_tosource::CodegenToSource;
-use self::codegen_syntax::CodegenSyntax;
-use descr_lang::gen::ast;
-use lang_data::data::*;
+use std::io;
 use std::path::Path;
-use descr_lang::gen::visitor::Visitor;
-
-pub fn process<'a : 'd, 'b, 'c, 'd>(res: &'a ast::Source, data: &'d mut LangData<'d>, path: &'c str) {
-    let (elapsed, ()) = measure_time(|| {
-        {
-            measure!("Register keys", {
-                let mut register_keys = RegisterKeys::new(data);
-                register_keys.visit_source(res);
-            });
-        }
-        {
-            measure!("Get tokens", {
-                let mut get_tokens = GetTokens::new(data);
-                get_tokens.visit_source(res);
-            });
-        }
-        {
-            measure!("Build parsers", {
-                let mut build_parsers = BuildParsers::new(data);
-                build_parsers.visit_source(res);
-            });
+
+/// Dependency line of a descr crate in generated
+/// Cargo.toml files, by path in the descr checkout
+/// when given, or by version
+pub fn descr_dependency(name: &str, descr_path: Option<&str>) -> String {
+    match descr_path {
+        Some(descr_path) => format!("{} = {{ path = \"{}/{}\" }}\n", name, descr_path, name),
+        None => format!("{} = \"{}\"\n", name, env!("CARGO_PKG_VERSION")),
+    }
+}
+
+/// Writes content to name in the path dir, when
+/// it differs from the file there. The content
+/// goes to a temp file that is renamed over the
+/// file, so it is never left half written.
+/// Gives whether the file was written.
+pub fn write_file(path: &str, name: &str, content: &str) -> io::Result<bool> {
+    let p = Path::new(path).join(name);
+    if fs::read(&p).map(|old| old == content.as_bytes()).unwrap_or(false) {
+        return Ok(false);
+    }
+    // Outputs can be in sub dirs, like docs/
+    let dir = match p.parent() {
+        Some(parent) => parent.to_path_buf(),
+        None => Path::new(path).to_path_buf(),
+    };
+    fs::create_dir_all(&dir)?;
+    let file_name = p.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let tmp = dir.join(format!(".{}.tmp", file_name));
+    fs::write(&tmp, content)?;
+    if let Err(err) = fs::rename(&tmp, &p) {
+        let _ = fs::remove_file(&tmp);
+        return Err(err);
+    }
+    Ok(true)
+}
+
+/// Helpers of the codegen tests, checking
+/// that outputs parse in their format
+#[cfg(test)]
+pub mod test_util {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::Path;
+    use generator::{build_data, parse_grammar, GenOptions, Generator};
+    use lang_data::data::LangData;
+    use serde_json;
+    use syn;
+
+    /// Grammars of the checkout, with their names
+    pub const REPO_GRAMMARS: [(&str, &str); 3] = [
+        ("descr.lang", "Descr"),
+        ("langs/json-descr/json.lang", "Json"),
+        ("langs/rust-descr/rust.lang", "Rust"),
+    ];
+
+    /// Outputs of grammar, with the options
+    /// changed by configure
+    pub fn outputs<F>(grammar: &str, configure: F) -> BTreeMap<String, String>
+    where
+        F: FnOnce(&mut GenOptions),
+    {
+        let mut options = GenOptions::new("Test");
+        configure(&mut options);
+        Generator::new(options).generate(grammar.as_bytes()).unwrap()
+    }
+
+    /// Result of f with the data of grammar
+    pub fn with_data<T, F>(grammar: &str, f: F) -> T
+    where
+        F: for<'d> FnOnce(&LangData<'d>) -> T,
+    {
+        let ast = parse_grammar(grammar.as_bytes()).unwrap();
+        let mut data = LangData::new(false, "Test".to_string());
+        build_data(grammar.as_bytes(), &ast, &mut data).unwrap();
+        f(&data)
+    }
+
+    /// Grammar of the checkout by path
+    pub fn repo_grammar(path: &str) -> String {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+        fs::read_to_string(root.join(path)).unwrap()
+    }
+
+    /// Panics unless source parses as Rust
+    pub fn assert_rust(name: &str, source: &str) {
+        if let Err(err) = syn::parse_file(source) {
+            panic!("{} is not valid Rust: {}\n{}", name, err, source);
         }
-        {
-            measure!("Build ast", {
-                let mut build_ast = BuildAst::new(data);
-                build_ast.build_ast();
+    }
+
+    /// Names of the fns, structs, enums
+    /// and mods at the top of source
+    pub fn item_names(source: &str) -> Vec<String> {
+        syn::parse_file(source)
+            .unwrap()
+            .items
+            .iter()
+            .filter_map(|item| match *item {
+                syn::Item::Fn(ref item) => Some(item.sig.ident.to_string()),
+                syn::Item::Struct(ref item) => Some(item.ident.to_string()),
+                syn::Item::Enum(ref item) => Some(item.ident.to_string()),
+                syn::Item::Mod(ref item) => Some(item.ident.to_string()),
+                _ => None,
             })
+            .collect()
+    }
+
+    /// Panics unless each mod of mod.rs is
+    /// an output, and each .rs output a mod
+    pub fn assert_modules(outputs: &BTreeMap<String, String>) {
+        let mods = item_names(&outputs["mod.rs"]);
+        for module in &mods {
+            assert!(outputs.contains_key(&format!("{}.rs", module)), "No {}.rs", module);
         }
-        {
-            measure!("Codegen ast", {
-                let codegen_ast = CodegenAst::new(data);
-                write_file(path, "ast.rs", codegen_ast.gen());
-            });
+        for name in outputs.keys().filter(|name| name.ends_with(".rs") && !name.contains('/')) {
+            assert!(name == "mod.rs" || mods.contains(&name[..name.len() - 3].to_string()), "No mod for {}", name);
         }
-        {
-            measure!("Codegen parsers", {
-                let codegen_parsers = CodegenParsers::new(data);
-                write_file(path, "parsers.rs", codegen_parsers.gen());
-            });
+    }
+
+    /// Panics unless the SyntaxKind variants
+    /// used in source are defined in cst.rs
+    pub fn assert_kinds_defined(name: &str, source: &str, cst: &str) {
+        let file = syn::parse_file(cst).unwrap();
+        let variants = file
+            .items
+            .iter()
+            .filter_map(|item| match *item {
+                syn::Item::Enum(ref item) if item.ident == "SyntaxKind" => {
+                    Some(item.variants.iter().map(|variant| variant.ident.to_string()).collect::<Vec<_>>())
+                }
+                _ => None,
+            })
+            .next()
+            .expect("No SyntaxKind in cst.rs");
+        for used in source.split("SyntaxKind::").skip(1) {
+            let used = used
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .next()
+                .unwrap();
+            assert!(variants.iter().any(|variant| variant == used), "{}: no SyntaxKind::{}", name, used);
         }
-        {
-            measure!("Codegen visitor", {
-                let codegen_visitor = CodegenVisitor::new(data);
-                write_file(path, "visitor.rs", codegen_visitor.gen());
-            });
+    }
+
+    /// Parsed json, panics when invalid
+    pub fn json(name: &str, source: &str) -> serde_json::Value {
+        match serde_json::from_str(source) {
+            Ok(value) => value,
+            Err(err) => panic!("{} is not valid json: {}\n{}", name, err, source),
         }
-        {
-            measure!("Codegen tosource", {
-                let codegen_tosource = CodegenToSource::new(data);
-                write_file(path, "to_source.rs", codegen_tosource.gen());
-            });
+    }
+
+    /// Regexes of the match, begin and end
+    /// keys in a tmLanguage grammar
+    pub fn tm_regexes(value: &serde_json::Value) -> Vec<String> {
+        fn add(value: &serde_json::Value, found: &mut Vec<String>) {
+            match *value {
+                serde_json::Value::Object(ref object) => for (key, value) in object {
+                    match (key.as_str(), value.as_str()) {
+                        ("match", Some(regex)) | ("begin", Some(regex)) | ("end", Some(regex)) => {
+                            found.push(regex.to_string())
+                        }
+                        _ => add(value, found),
+                    }
+                },
+                serde_json::Value::Array(ref values) => for value in values {
+                    add(value, found);
+                },
+                _ => {}
+            }
         }
-        {
-            measure!("Codegen syntax", {
-                let codegen_syntax = CodegenSyntax::new(data);
-                codegen_syntax.gen();
-                //write_file(path, "to_source.rs", codegen_tosource.gen());
-            });
+        let mut found = Vec::new();
+        add(value, &mut found);
+        found
+    }
+
+    /// Panics unless the tags of the xml
+    /// source are balanced
+    pub fn assert_xml(name: &str, source: &str) {
+        let mut open = Vec::new();
+        let mut rest = source;
+        while let Some(start) = rest.find('<') {
+            let end = start + rest[start..].find('>').expect("Unclosed tag");
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+            if tag.starts_with('?') || tag.starts_with('!') || tag.ends_with('/') {
+                continue;
+            }
+            let tag_name = tag.trim_start_matches('/').split_whitespace().next().unwrap_or("");
+            if tag.starts_with('/') {
+                match open.pop() {
+                    Some(ref open_name) if open_name == tag_name => {}
+                    other => panic!("{}: </{}> closes {:?}", name, tag_name, other),
+                }
+            } else {
+                open.push(tag_name.to_string());
+            }
         }
-        // Generate mod file
-        gen_mod(path, data);
-    });
-    println!("Process: {}", elapsed);
+        assert!(open.is_empty(), "{}: unclosed {:?}", name, open);
+    }
 }
 
-pub fn gen_mod<'a, 'd>(path: &str, _data: &'a LangData<'d>) {
-    let s = "pub mod ast;\npub mod parsers;\npub mod visitor;\npub mod to_source;\n\n".to_string();
-    /*
-    use std::collections::HashSet;
-    let start_key = data.start_key.expect("Start key not found");
-    let start_part = data.typed_parts
-                .get(start_key)
-                .expect(&format!("Could not find token for start key: {}", start_key));
-    let needs_lifetime = start_part.needs_lifetime(data, &mut HashSet::new());
-    s += "extern crate descr_common;\n";
-    s += "extern crate nom;\n";
-    s += "use std::result::Result;\n";
-    s += "use self::ast::";
-    s += data.rule_types
-        .get(start_key)
-        .expect(&format!("Coult not get ast {}", start_key))
-        .get_type_name(data);
-    s += ";\n";
-    // Parse file
-    s += "pub fn parse_file";
-    if needs_lifetime { s += "<'a>"; }
-    s += "(filename: &str) -> Result<";
-    s = start_part.add_type(s, data);
-    s += ", &str> {\n";
-    s += "    let buf = descr_common::util::load_file(filename);\n";
-    s += "    parse_bytes(&buf[..])\n";
-    s += "}\n\n";
-    // Parse str
-    s += "pub fn parse_str";
-    if needs_lifetime { s += "<'a>"; }
-    s += "(string: &str) -> Result<";
-    s = start_part.add_type(s, data);
-    s += ", &str> {\n";
-    s += "    parse_bytes(string.as_bytes())\n";
-    s += "}\n\n";
-    // Parse bytes
-    s += "pub fn parse_bytes";
-    if needs_lifetime { s += "<'a>"; }
-    s += "(bytes: &[u8]) -> Result<";
-    s = start_part.add_type(s, data);
-    s += ", &str> {\n";
-    s += "    let res = self::parsers::start(bytes);\n";
-    s += "    match res {\n";
-    s += "        nom::IResult::Done(i, o) => Result::Ok(o),\n";
-    s += "        nom::IResult::Error(ref e) => Result::Err(e.description()),\n";
-    s += "    }\n";
-    s += "}\n\n";
-    */
-    write_file(path, "mod.rs", s);
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
 
-pub fn write_file<'a, 'b>(path: &'a str, name: &'b str, content: String) {
-    let p = Path::new(path).join(name);
-    if p.exists() {
-        // Backup file (for now)
-        let backup_p = Path::new(path).join("backup").join(name);
-        fs::copy(&p, backup_p).expect("Could not backup file");
+    #[test]
+    fn writes_changed_files() {
+        let dir = env::temp_dir().join(format!("descr-write-file-{}", std::process::id()));
+        let path = dir.to_string_lossy().into_owned();
+        assert!(write_file(&path, "docs/a.md", "a").unwrap());
+        assert!(!write_file(&path, "docs/a.md", "a").unwrap());
+        assert!(write_file(&path, "docs/a.md", "b").unwrap());
+        assert_eq!(fs::read_to_string(dir.join("docs/a.md")).unwrap(), "b");
+        let names = fs::read_dir(dir.join("docs")).unwrap().count();
+        assert_eq!(names, 1);
+        fs::remove_dir_all(&dir).unwrap();
     }
-    let mut file =
-        File::create(p).expect(format!("Could not open path: {}, file: {}", path, name).as_str());
-    file.write_all(content.as_bytes())
-        .expect(format!("Could not write to path: {}, file: {}", path, name).as_str());
 }
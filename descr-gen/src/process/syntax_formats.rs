@@ -0,0 +1,418 @@
+extern crate json_descr;
+use self::json_descr::lang::ast::*;
+
+/// Serializers of the TextMate grammar built by
+/// CodegenSyntax::gen_js_object, for consumers
+/// that don't read the json form.
+/// String values in the object are escaped for
+/// json, so they are unescaped before written.
+
+fn unescape(string: &str) -> String {
+    let mut s = String::with_capacity(string.len());
+    let mut chars = string.chars();
+    while let Some(chr) = chars.next() {
+        if chr != '\\' {
+            s.push(chr);
+            continue;
+        }
+        match chars.next() {
+            Some('b') => s.push('\u{8}'),
+            Some('f') => s.push('\u{c}'),
+            Some('n') => s.push('\n'),
+            Some('r') => s.push('\r'),
+            Some('t') => s.push('\t'),
+            Some('u') => s.push(unescape_unicode(&mut chars)),
+            // Quotes, slashes and backslashes
+            Some(escaped) => s.push(escaped),
+            None => s.push('\\'),
+        }
+    }
+    s
+}
+
+/// Char of a \u escape, after the u. A high
+/// surrogate is joined with the low surrogate
+/// escaped after it, unpaired surrogates and
+/// invalid hex give the replacement char.
+fn unescape_unicode(chars: &mut ::std::str::Chars) -> char {
+    fn hex4(chars: &mut ::std::str::Chars) -> Option<u32> {
+        let digits = chars.as_str().get(..4)?;
+        let code = u32::from_str_radix(digits, 16).ok()?;
+        *chars = chars.as_str()[4..].chars();
+        Some(code)
+    }
+    let high = match hex4(chars) {
+        Some(code) => code,
+        None => return '\u{fffd}',
+    };
+    if !(0xd800..=0xdfff).contains(&high) {
+        return ::std::char::from_u32(high).unwrap_or('\u{fffd}');
+    }
+    if high < 0xdc00 && chars.as_str().starts_with("\\u") {
+        let mut after = chars.as_str()[2..].chars();
+        if let Some(low) = hex4(&mut after) {
+            if (0xdc00..=0xdfff).contains(&low) {
+                *chars = after;
+                let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                return ::std::char::from_u32(code).unwrap_or('\u{fffd}');
+            }
+        }
+    }
+    '\u{fffd}'
+}
+
+fn xml_escape(string: &str) -> String {
+    let mut s = String::with_capacity(string.len());
+    for chr in string.chars() {
+        match chr {
+            '&' => s.push_str("&amp;"),
+            '<' => s.push_str("&lt;"),
+            '>' => s.push_str("&gt;"),
+            other => s.push(other),
+        }
+    }
+    s
+}
+
+/// Yaml scalar, single quoted where only quotes
+/// are escaped. Those fold line breaks, so strings
+/// with control characters are double quoted.
+fn yaml_str(string: &str) -> String {
+    if !string.chars().any(|chr| chr < ' ') {
+        return format!("'{}'", string.replace('\'', "''"));
+    }
+    let mut s = String::with_capacity(string.len() + 10);
+    s.push('"');
+    for chr in string.chars() {
+        match chr {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\t' => s.push_str("\\t"),
+            other if other < ' ' => s.push_str(&format!("\\u{:04x}", other as u32)),
+            other => s.push(other),
+        }
+    }
+    s.push('"');
+    s
+}
+
+/// Yaml nests by two spaces
+fn yaml_indent(s: &mut String, level: usize) {
+    for _ in 0..level {
+        s.push_str("  ");
+    }
+}
+
+fn get<'o>(object: &'o JsObject, key: &str) -> Option<&'o JsVal> {
+    object
+        .items
+        .iter()
+        .find(|pair| pair.key == key)
+        .map(|pair| &pair.val)
+}
+
+fn get_str(object: &JsObject, key: &str) -> Option<String> {
+    match get(object, key) {
+        Some(&JsVal::StringValItem(ref val)) => Some(unescape(&val.string)),
+        _ => None,
+    }
+}
+
+fn get_object<'o>(object: &'o JsObject, key: &str) -> Option<&'o JsObject> {
+    match get(object, key) {
+        Some(&JsVal::JsObjectItem(ref val)) => Some(val),
+        _ => None,
+    }
+}
+
+/// Plist xml, the .tmLanguage format.
+/// The json $schema is left out.
+pub fn to_plist(root: &JsObject) -> String {
+    let mut s = String::with_capacity(4096);
+    s += "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+    s += "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n";
+    s += "<plist version=\"1.0\">\n";
+    let items = root
+        .items
+        .iter()
+        .filter(|pair| pair.key != "$schema")
+        .collect::<Vec<_>>();
+    s = add_plist_dict(s, &items, 0);
+    s += "</plist>\n";
+    s
+}
+
+fn add_plist_dict(mut s: String, items: &[&ObjectPair], level: usize) -> String {
+    indent!(s level);
+    s += "<dict>\n";
+    for pair in items {
+        indent!(s level + 1);
+        append!(s, "<key>" xml_escape(&pair.key).as_str() "</key>\n");
+        s = add_plist_val(s, &pair.val, level + 1);
+    }
+    indent!(s level);
+    s += "</dict>\n";
+    s
+}
+
+fn add_plist_val(mut s: String, val: &JsVal, level: usize) -> String {
+    match val {
+        &JsVal::IntItem(ref int) => {
+            indent!(s level);
+            append!(s, "<integer>" int.int.to_string().as_str() "</integer>\n");
+        }
+        &JsVal::StringValItem(ref string) => {
+            indent!(s level);
+            append!(s, "<string>" xml_escape(&unescape(&string.string)).as_str() "</string>\n");
+        }
+        &JsVal::ArrayValItem(ref array) => {
+            indent!(s level);
+            s += "<array>\n";
+            for item in &array.items {
+                s = add_plist_val(s, item, level + 1);
+            }
+            indent!(s level);
+            s += "</array>\n";
+        }
+        &JsVal::JsObjectItem(ref object) => {
+            s = add_plist_dict(s, &object.items.iter().collect::<Vec<_>>(), level);
+        }
+    }
+    s
+}
+
+/// Sublime syntax yaml. Root patterns become the
+/// main context, and repository items contexts.
+/// Begin/end patterns push an anonymous context
+/// with the name as meta scope, which the end
+/// pattern pops.
+pub fn to_sublime_syntax(root: &JsObject, file_extensions: &[String]) -> String {
+    let mut s = String::with_capacity(4096);
+    s += "%YAML 1.2\n";
+    s += "---\n";
+    if let Some(name) = get_str(root, "name") {
+        append!(s, "name: " yaml_str(&name).as_str() "\n");
+    }
+    if !file_extensions.is_empty() {
+        s += "file_extensions:\n";
+        for extension in file_extensions {
+            yaml_indent(&mut s, 1);
+            append!(s, "- " yaml_str(extension).as_str() "\n");
+        }
+    }
+    if let Some(scope) = get_str(root, "scopeName") {
+        append!(s, "scope: " yaml_str(&scope).as_str() "\n");
+    }
+    s += "contexts:\n";
+    s += "  main:\n";
+    s = add_sublime_patterns(s, get(root, "patterns"), 2);
+    if let Some(repository) = get_object(root, "repository") {
+        for pair in &repository.items {
+            yaml_indent(&mut s, 1);
+            append!(s, yaml_str(&pair.key).as_str() ":\n");
+            if let JsVal::JsObjectItem(ref rule) = pair.val {
+                s = add_sublime_rule(s, rule, 2);
+            }
+        }
+    }
+    s
+}
+
+/// List items of patterns array
+fn add_sublime_patterns(mut s: String, patterns: Option<&JsVal>, level: usize) -> String {
+    let mut any = false;
+    if let Some(&JsVal::ArrayValItem(ref patterns)) = patterns {
+        for pattern in &patterns.items {
+            if let &JsVal::JsObjectItem(ref rule) = pattern {
+                s = add_sublime_rule(s, rule, level);
+                any = true;
+            }
+        }
+    }
+    if !any {
+        yaml_indent(&mut s, level);
+        s += "[]\n";
+    }
+    s
+}
+
+fn add_sublime_rule(mut s: String, rule: &JsObject, level: usize) -> String {
+    if let Some(include) = get_str(rule, "include") {
+        let include = if include.starts_with('#') { &include[1..] } else { include.as_str() };
+        yaml_indent(&mut s, level);
+        append!(s, "- include: " yaml_str(include).as_str() "\n");
+        return s;
+    }
+    let name = get_str(rule, "name");
+    if let Some(regex) = get_str(rule, "match") {
+        yaml_indent(&mut s, level);
+        append!(s, "- match: " yaml_str(&regex).as_str() "\n");
+        if let Some(ref name) = name {
+            yaml_indent(&mut s, level + 1);
+            append!(s, "scope: " yaml_str(name).as_str() "\n");
+        }
+        s = add_sublime_captures(s, get_object(rule, "captures"), level + 1);
+    } else if let Some(begin) = get_str(rule, "begin") {
+        yaml_indent(&mut s, level);
+        append!(s, "- match: " yaml_str(&begin).as_str() "\n");
+        s = add_sublime_captures(s, get_object(rule, "beginCaptures"), level + 1);
+        yaml_indent(&mut s, level + 1);
+        s += "push:\n";
+        if let Some(ref name) = name {
+            yaml_indent(&mut s, level + 2);
+            append!(s, "- meta_scope: " yaml_str(name).as_str() "\n");
+        }
+        if let Some(end) = get_str(rule, "end") {
+            yaml_indent(&mut s, level + 2);
+            append!(s, "- match: " yaml_str(&end).as_str() "\n");
+            s = add_sublime_captures(s, get_object(rule, "endCaptures"), level + 3);
+            yaml_indent(&mut s, level + 3);
+            s += "pop: true\n";
+        }
+        if let Some(&JsVal::ArrayValItem(ref patterns)) = get(rule, "patterns") {
+            for pattern in &patterns.items {
+                if let &JsVal::JsObjectItem(ref pattern) = pattern {
+                    s = add_sublime_rule(s, pattern, level + 2);
+                }
+            }
+        }
+    }
+    s
+}
+
+fn add_sublime_captures(mut s: String, captures: Option<&JsObject>, level: usize) -> String {
+    let captures = match captures {
+        Some(captures) if !captures.items.is_empty() => captures,
+        _ => return s,
+    };
+    yaml_indent(&mut s, level);
+    s += "captures:\n";
+    for pair in &captures.items {
+        if let JsVal::JsObjectItem(ref capture) = pair.val {
+            if let Some(name) = get_str(capture, "name") {
+                yaml_indent(&mut s, level + 1);
+                append!(s, pair.key.as_str() ": " yaml_str(&name).as_str() "\n");
+            }
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use process::test_util::*;
+
+    /// Regexes of the plist, in the strings
+    /// after match, begin and end keys
+    fn plist_regexes(plist: &str) -> Vec<String> {
+        let mut found = Vec::new();
+        for key in &["<key>match</key>", "<key>begin</key>", "<key>end</key>"] {
+            for after in plist.split(key).skip(1) {
+                let start = after.find("<string>").unwrap() + "<string>".len();
+                let string = &after[start..after.find("</string>").unwrap()];
+                found.push(string.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&"));
+            }
+        }
+        found
+    }
+
+    /// Regexes of the sublime syntax, in
+    /// single or double quoted scalars
+    fn sublime_regexes(sublime: &str) -> Vec<String> {
+        sublime
+            .lines()
+            .filter_map(|line| line.trim().splitn(2, "- match: ").nth(1))
+            .map(|scalar| {
+                let quoted = &scalar[1..scalar.len() - 1];
+                if scalar.starts_with('\'') {
+                    quoted.replace("''", "'")
+                } else {
+                    unescape(quoted)
+                }
+            })
+            .collect()
+    }
+
+    /// Panics unless the plist and sublime syntax
+    /// have the regexes of the json grammar
+    fn assert_equivalent(name: &str, outputs: &BTreeMap<String, String>) {
+        let file = |ext: &str| {
+            outputs
+                .iter()
+                .find(|&(file, _)| file.ends_with(ext) && !file.contains('/'))
+                .map(|(_, content)| content.as_str())
+                .unwrap()
+        };
+        let plist = file(".tmLanguage");
+        assert_xml(name, plist);
+        let mut expected = tm_regexes(&json(name, file(".tmLanguage.json")));
+        expected.sort();
+        let mut plist_found = plist_regexes(plist);
+        plist_found.sort();
+        assert_eq!(plist_found, expected, "{}: plist regexes", name);
+        let mut sublime_found = sublime_regexes(file(".sublime-syntax"));
+        sublime_found.sort();
+        assert_eq!(sublime_found, expected, "{}: sublime regexes", name);
+    }
+
+    #[test]
+    fn writes_equivalent_formats() {
+        let outputs = outputs(
+            "Source(items:sourceItems)
+            sourceItems:SourceItem[] WS {
+                Block(LBRACE @syntax(name=\"string.quoted\") string RBRACE),
+                Less(@syntax(name=\"keyword.operator\") LT int),
+                Group(LPAREN sourceItems RPAREN),
+                LineComment(\"#\" comment:!\"\n\" \"\n\")
+            }",
+            |options| options.sublime = true,
+        );
+        assert_equivalent("Test", &outputs);
+        let plist = &outputs["test.tmLanguage"];
+        let sublime = &outputs["test.sublime-syntax"];
+        // Regexes are xml escaped and yaml quoted
+        assert!(plist.contains(r#"<string>\s*(&lt;)\s*([-\+]?[1-9]+)</string>"#));
+        assert!(sublime.contains(r#"- match: '\s*\{\s*("(?:[^"\\]|\\.)*")\s*\}'"#));
+        assert!(sublime.contains(r#"- match: "\\s*(#)((?:(?!\n).)*)\\s*(\n)""#));
+        // Same rules, scopes and includes
+        for key in &["Block", "Less", "Group", "Source"] {
+            assert!(plist.contains(&format!("<key>{}</key>", key)));
+            assert!(sublime.contains(&format!("\n  '{}':\n", key)));
+        }
+        assert!(plist.contains("<key>scopeName</key>\n    <string>source.test</string>"));
+        assert!(sublime.contains("scope: 'source.test'"));
+        assert!(plist.contains("<string>#Less</string>"));
+        assert!(sublime.contains("    - include: 'Less'\n"));
+        assert!(sublime.contains(
+            "      captures:\n        1: 'keyword.operator'\n        2: 'constant.numeric'\n"
+        ));
+        // Begin and end push and pop a context
+        assert!(sublime.contains(
+            "  'Group':\n    - match: '\\s*\\('\n      push:\n        - meta_scope: 'Group'\n        - match: '\\s*\\)'\n          pop: true\n"
+        ));
+        assert!(!plist.contains("$schema"));
+    }
+
+    #[test]
+    fn repo_grammars_have_equivalent_formats() {
+        for &(path, _) in &REPO_GRAMMARS {
+            assert_equivalent(path, &outputs(&repo_grammar(path), |options| options.sublime = true));
+        }
+    }
+
+    #[test]
+    fn unescapes_json_strings() {
+        assert_eq!(unescape(r#"a\nb\tc\r"#), "a\nb\tc\r");
+        assert_eq!(unescape(r#"\"q\" \\ \/"#), "\"q\" \\ /");
+        assert_eq!(unescape(r#"\b\f"#), "\u{8}\u{c}");
+        assert_eq!(unescape(r#"\u0041\u00e9"#), "A\u{e9}");
+        assert_eq!(unescape(r#"\ud83d\ude00"#), "\u{1f600}");
+        assert_eq!(unescape(r#"\ud83d x"#), "\u{fffd} x");
+        assert_eq!(unescape(r#"\u12"#), "\u{fffd}12");
+        assert_eq!(unescape(r#"end\"#), "end\\");
+    }
+}
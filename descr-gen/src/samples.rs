@@ -0,0 +1,496 @@
+//! Random valid inputs from lang data, for
+//! fuzzing parsers and as test corpus.
+//!
+//! Rules are walked from the start key, with
+//! random enum alternatives, list lengths and
+//! optional parts. Past the depth limit, the
+//! rules that end soonest are picked and lists
+//! are left empty. Tokens get plausible values,
+//! and not parts get text without their token.
+//! Each sample is checked with the interpreter,
+//! and generated again if it doesn't parse.
+//!
+//! ```ignore
+//! let mut sampler = Sampler::new(&data, SampleOptions::new());
+//! for sample in sampler.samples(100) {
+//!     println!("{}", sample);
+//! }
+//! ```
+use std::collections::HashMap;
+use lang_data::data::*;
+use lang_data::rule::*;
+use lang_data::typed_part::TypedPart;
+use interpreter::Interpreter;
+
+const IDENTS: &[&str] = &[
+    "foo", "bar", "baz", "item", "value", "name", "count", "x", "y", "data", "node", "next",
+];
+const WORDS: &[&str] = &[
+    "lorem", "ipsum", "dolor", "sit", "amet", "hello", "world", "some", "text", "here",
+];
+
+/// Options for a sampler
+#[derive(Debug, Clone)]
+pub struct SampleOptions {
+    /// Same seed gives the same samples
+    pub seed: u64,
+    /// Rules deeper than this are ended
+    /// as soon as possible
+    pub max_depth: usize,
+    pub max_list_len: usize,
+    /// Tries for each sample before
+    /// giving up
+    pub attempts: usize,
+}
+impl SampleOptions {
+    pub fn new() -> SampleOptions {
+        SampleOptions {
+            seed: 1,
+            max_depth: 8,
+            max_list_len: 4,
+            attempts: 20,
+        }
+    }
+}
+impl Default for SampleOptions {
+    fn default() -> SampleOptions {
+        SampleOptions::new()
+    }
+}
+
+/// Xorshift64*, enough for picking
+/// alternatives without a dependency
+struct Rng(u64);
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        // Zero state stays zero
+        Rng(seed ^ 0x9e37_79b9_7f4a_7c15)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Number in 0..n
+    fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            0
+        } else {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    fn chance(&mut self) -> bool {
+        self.below(2) == 0
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+/// Depth not reachable, like rules
+/// that always recurse
+const UNREACHABLE: usize = usize::MAX;
+
+pub struct Sampler<'a, 'd: 'a> {
+    data: &'a LangData<'d>,
+    options: SampleOptions,
+    rng: Rng,
+    /// Least depth to end each rule key
+    min_depths: HashMap<&'d str, usize>,
+    interpreter: Interpreter<'a, 'd>,
+}
+
+impl<'a, 'd> Sampler<'a, 'd> {
+    pub fn new(data: &'a LangData<'d>, options: SampleOptions) -> Sampler<'a, 'd> {
+        let mut sampler = Sampler {
+            data,
+            rng: Rng::new(options.seed),
+            options,
+            min_depths: HashMap::new(),
+            interpreter: Interpreter::new(data),
+        };
+        sampler.collect_min_depths();
+        sampler
+    }
+
+    /// Depths of keys are found by repeating
+    /// until they don't get lower
+    fn collect_min_depths(&mut self) {
+        for key in self.data.ast_data.keys().chain(self.data.list_data.keys()) {
+            self.min_depths.insert(key, UNREACHABLE);
+        }
+        loop {
+            let mut changed = false;
+            for (key, ast_data) in &self.data.ast_data {
+                let depth = ast_data
+                    .rules
+                    .iter()
+                    .map(|rule| self.rule_depth(rule))
+                    .min()
+                    .unwrap_or(UNREACHABLE);
+                changed |= self.lower_depth(key, depth);
+            }
+            for key in self.data.list_data.keys() {
+                // Lists can be empty
+                changed |= self.lower_depth(key, 1);
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    fn lower_depth(&mut self, key: &'d str, depth: usize) -> bool {
+        let current = self.min_depths.get(key).cloned().unwrap_or(UNREACHABLE);
+        if depth < current {
+            self.min_depths.insert(key, depth);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn key_depth(&self, key: &str) -> usize {
+        self.min_depths.get(key).cloned().unwrap_or(0)
+    }
+
+    fn rule_depth(&self, rule: &AstRule<'d>) -> usize {
+        match rule {
+            &AstRule::RefRule(key) => self.key_depth(key),
+            &AstRule::PartsRule(ref parts_rule) => match self.parts_depth(&parts_rule.parts) {
+                UNREACHABLE => UNREACHABLE,
+                depth => depth + 1,
+            },
+        }
+    }
+
+    /// Deepest of required parts
+    fn parts_depth(&self, parts: &[AstRulePart<'d>]) -> usize {
+        parts
+            .iter()
+            .filter(|part| !part.optional && !part.not)
+            .map(|part| self.token_depth(&part.token))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn token_depth(&self, token: &AstRuleToken<'d>) -> usize {
+        match token {
+            &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                Some(&TypedPart::AstPart { key }) | Some(&TypedPart::ListPart { key }) => {
+                    self.key_depth(key)
+                }
+                _ => 0,
+            },
+            &AstRuleToken::Group(ref parts) => self.parts_depth(parts),
+            _ => 0,
+        }
+    }
+
+    /// Sample that parses with the interpreter,
+    /// None when no attempt parsed
+    pub fn sample(&mut self) -> Option<String> {
+        let start_key = self.data.start_key?;
+        for _ in 0..self.options.attempts {
+            let mut s = String::new();
+            if !self.gen_key(&mut s, start_key, 0) {
+                continue;
+            }
+            // Idents and ints are incomplete
+            // at the end of input
+            s.push('\n');
+            if self.interpreter.parse_complete(s.as_bytes()).is_ok() {
+                return Some(s);
+            }
+        }
+        None
+    }
+
+    /// Up to n samples, fewer when some
+    /// could not be made
+    pub fn samples(&mut self, n: usize) -> Vec<String> {
+        (0..n).filter_map(|_| self.sample()).collect()
+    }
+
+    /// Rules within the depth left, or
+    /// the shallowest when none are
+    fn pick_rule<'r>(&mut self, rules: &[&'r AstRule<'d>], depth: usize) -> Option<&'r AstRule<'d>> {
+        let depths = rules.iter().map(|rule| self.rule_depth(rule)).collect::<Vec<_>>();
+        let left = self.options.max_depth.saturating_sub(depth);
+        let mut candidates = rules
+            .iter()
+            .zip(&depths)
+            .filter(|&(_, &rule_depth)| rule_depth <= left)
+            .map(|(rule, _)| *rule)
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            let least = depths.iter().cloned().min().unwrap_or(UNREACHABLE);
+            if least == UNREACHABLE {
+                return None;
+            }
+            candidates = rules
+                .iter()
+                .zip(&depths)
+                .filter(|&(_, &rule_depth)| rule_depth == least)
+                .map(|(rule, _)| *rule)
+                .collect();
+        }
+        Some(*self.rng.pick(&candidates))
+    }
+
+    fn gen_key(&mut self, s: &mut String, key: &str, depth: usize) -> bool {
+        let data = self.data;
+        if let Some(ast_data) = data.ast_data.get(key) {
+            let rules = ast_data.rules.iter().collect::<Vec<_>>();
+            match self.pick_rule(&rules, depth) {
+                Some(rule) => self.gen_rule(s, rule, depth),
+                None => false,
+            }
+        } else if let Some(list_data) = data.list_data.get(key) {
+            self.gen_list(s, list_data, depth)
+        } else {
+            false
+        }
+    }
+
+    fn gen_list(&mut self, s: &mut String, list_data: &ListData<'d>, depth: usize) -> bool {
+        let len = if depth < self.options.max_depth {
+            self.rng.below(self.options.max_list_len + 1)
+        } else {
+            0
+        };
+        let sep = match list_data.sep.and_then(|sep| self.data.typed_parts.get(sep)) {
+            Some(&TypedPart::CharPart { chr, .. }) => Some(chr.to_string()),
+            Some(&TypedPart::TagPart { tag, .. }) => Some(tag.to_string()),
+            _ => None,
+        };
+        let rules = list_data.rules.iter().map(|rule| &rule.ast_rule).collect::<Vec<_>>();
+        for i in 0..len {
+            if i > 0 {
+                match sep {
+                    // Separator follows the item
+                    // directly, like separated_list!
+                    Some(ref sep) => {
+                        s.push_str(sep);
+                        s.push(' ');
+                    }
+                    None => s.push('\n'),
+                }
+            }
+            let rule = match self.pick_rule(&rules, depth + 1) {
+                Some(rule) => rule,
+                None => return false,
+            };
+            if !self.gen_rule(s, rule, depth + 1) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn gen_rule(&mut self, s: &mut String, rule: &AstRule<'d>, depth: usize) -> bool {
+        match rule {
+            &AstRule::RefRule(key) => self.gen_key(s, key, depth),
+            &AstRule::PartsRule(ref parts_rule) => self.gen_parts(s, &parts_rule.parts, depth + 1),
+        }
+    }
+
+    fn gen_parts(&mut self, s: &mut String, parts: &[AstRulePart<'d>], depth: usize) -> bool {
+        for (i, part) in parts.iter().enumerate() {
+            if part.not {
+                let end = parts.get(i + 1).and_then(|next| self.token_text(&next.token));
+                let avoid = self.token_text(&part.token);
+                self.gen_not_text(s, avoid.as_ref().map(|avoid| avoid.as_str()));
+                // Until parsers stop before the token,
+                // give it when the rule doesn't
+                if end.is_none() || end != avoid {
+                    if let Some(ref avoid) = avoid {
+                        s.push_str(avoid);
+                    }
+                }
+                continue;
+            }
+            if part.optional {
+                let deep = self.token_depth(&part.token);
+                if depth.saturating_add(deep) > self.options.max_depth || self.rng.chance() {
+                    continue;
+                }
+            }
+            if !self.gen_token(s, &part.token, depth) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Literal text of char and tag tokens
+    fn token_text(&self, token: &AstRuleToken<'d>) -> Option<String> {
+        match token {
+            &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                Some(&TypedPart::CharPart { chr, .. }) => Some(chr.to_string()),
+                Some(&TypedPart::TagPart { tag, .. }) => Some(tag.to_string()),
+                _ => None,
+            },
+            &AstRuleToken::Tag(tag) => Some(tag.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Words not containing the token
+    /// the until parser looks for
+    fn gen_not_text(&mut self, s: &mut String, avoid: Option<&str>) {
+        let len = 1 + self.rng.below(4);
+        let mut text = String::new();
+        for _ in 0..len {
+            let word = *self.rng.pick(WORDS);
+            let mut next = text.clone();
+            next.push(' ');
+            next.push_str(word);
+            if let Some(avoid) = avoid {
+                if next.contains(avoid) {
+                    continue;
+                }
+            }
+            text = next;
+        }
+        if text.is_empty() {
+            text.push(' ');
+        }
+        s.push_str(&text);
+    }
+
+    /// Space between tokens, parsers
+    /// skip whitespace before them
+    fn push_token(s: &mut String, text: &str) {
+        if !s.is_empty() && !s.ends_with(char::is_whitespace) {
+            s.push(' ');
+        }
+        s.push_str(text);
+    }
+
+    fn gen_token(&mut self, s: &mut String, token: &AstRuleToken<'d>, depth: usize) -> bool {
+        match token {
+            &AstRuleToken::Key(key) => match self.data.typed_parts.get(key) {
+                Some(typed_part) => self.gen_typed_part(s, typed_part, depth),
+                None => false,
+            },
+            &AstRuleToken::Tag(tag) => {
+                Self::push_token(s, tag);
+                true
+            }
+            // Nom macros can't be sampled
+            &AstRuleToken::Func(..) => false,
+            &AstRuleToken::Group(ref parts) => self.gen_parts(s, parts, depth),
+        }
+    }
+
+    fn gen_typed_part(&mut self, s: &mut String, typed_part: &TypedPart<'d>, depth: usize) -> bool {
+        use lang_data::typed_part::TypedPart::*;
+        match typed_part {
+            &AstPart { key } | &ListPart { key } => self.gen_key(s, key, depth),
+            &CharPart { chr, .. } => {
+                Self::push_token(s, &chr.to_string());
+                true
+            }
+            &TagPart { tag, .. } => {
+                Self::push_token(s, tag);
+                true
+            }
+            &IntPart { .. } => {
+                let int = match self.rng.below(4) {
+                    0 => self.rng.below(10),
+                    1 => self.rng.below(100),
+                    _ => self.rng.below(10000),
+                };
+                Self::push_token(s, &int.to_string());
+                true
+            }
+            &IdentPart { .. } => {
+                let ident = *self.rng.pick(IDENTS);
+                match self.rng.below(4) {
+                    0 => Self::push_token(s, &format!("{}{}", ident, self.rng.below(10))),
+                    _ => Self::push_token(s, ident),
+                }
+                true
+            }
+            &StringPart { .. } | &StrPart { .. } => {
+                let len = 1 + self.rng.below(3);
+                let words = (0..len).map(|_| *self.rng.pick(WORDS)).collect::<Vec<_>>();
+                Self::push_token(s, &format!("\"{}\"", words.join(" ")));
+                true
+            }
+            &WSPart => {
+                s.push(' ');
+                true
+            }
+            &FnPart { .. } => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generator::{build_data, parse_grammar};
+
+    const GRAMMAR: &'static [u8] = b"
+        Source(items:items)
+        items:Item[] WS {
+            Say(\"say\" string),
+            Assign(\"let\" name:ident EQUAL expr:Expr SEMICOLON),
+            Comment(\"(*\" comment:!\"*)\" \"*)\")
+        }
+        Expr {
+            Num(int),
+            Call(ident LPAREN args:args RPAREN),
+            Var(ident),
+            Group(LPAREN Expr RPAREN)
+        }
+        args[] COMMA Expr
+    ";
+
+    fn with_data<F: Fn(&LangData)>(grammar: &[u8], f: F) {
+        let ast = parse_grammar(grammar).unwrap();
+        let mut data = LangData::new(false, "Test".to_string());
+        build_data(grammar, &ast, &mut data).unwrap();
+        f(&data);
+    }
+
+    #[test]
+    fn samples_parse() {
+        with_data(GRAMMAR, |data| {
+            let mut sampler = Sampler::new(data, SampleOptions::new());
+            let samples = sampler.samples(50);
+            assert_eq!(samples.len(), 50);
+            let interpreter = Interpreter::new(data);
+            for sample in &samples {
+                assert!(interpreter.parse_complete(sample.as_bytes()).is_ok(), "{}", sample);
+            }
+            let all = samples.concat();
+            for expected in &["say \"", "let ", "(*", "*)", "(", ","] {
+                assert!(all.contains(expected), "No {} in samples", expected);
+            }
+        });
+    }
+
+    #[test]
+    fn samples_are_seeded_and_limited() {
+        with_data(GRAMMAR, |data| {
+            let mut options = SampleOptions::new();
+            options.seed = 42;
+            options.max_depth = 3;
+            let first = Sampler::new(data, options.clone()).samples(20);
+            let second = Sampler::new(data, options).samples(20);
+            assert_eq!(first, second);
+            // Nesting stops past the depth limit
+            for sample in &first {
+                assert!(!sample.contains("((("), "{}", sample);
+            }
+        });
+    }
+}
@@ -0,0 +1,20 @@
+//! Builds the generator with the descr-lang modules
+//! it generates, which should generate the same
+//! modules again. Builds in target/bootstrap.
+extern crate descr_gen;
+
+use std::path::Path;
+use descr_gen::bootstrap::{self, diff_outputs};
+
+#[test]
+fn descr_lang_is_a_fixpoint() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let stages = bootstrap::run(root, &root.join("target/bootstrap")).unwrap_or_else(|err| panic!("{}", err));
+    if let Some(diff) = stages.diff() {
+        panic!("Stage 2 differs from stage 1:\n{}", diff);
+    }
+    let checked_in = bootstrap::checked_in(root).unwrap();
+    if let Some(diff) = diff_outputs(bootstrap::GEN_DIR, &checked_in, "stage1", &stages.stage1) {
+        panic!("{} is not generated from descr.lang, run descr bootstrap-check --write:\n{}", bootstrap::GEN_DIR, diff);
+    }
+}
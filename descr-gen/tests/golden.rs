@@ -0,0 +1,104 @@
+//! Golden tests, generating the repo grammars and
+//! comparing with the outputs in tests/golden.
+//! Run with UPDATE_GOLDEN=1 to write the outputs
+//! after an intended change.
+extern crate descr_gen;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use descr_gen::diff::unified_diff;
+use descr_gen::generator::{GenOptions, Generator};
+
+fn root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+/// Outputs of the grammar, with the rd
+/// parsers under rd/. Crates with paths
+/// of this checkout are left out.
+fn outputs(grammar: &str, name: &str) -> BTreeMap<String, String> {
+    let source = fs::read(root().join(grammar)).unwrap();
+    let mut options = GenOptions::new(name);
+    options.cst = true;
+    options.highlight = true;
+    options.tree_sitter = true;
+    let mut outputs = Generator::new(options.clone()).generate(&source).unwrap();
+    options = GenOptions::new(name);
+    options.ast = false;
+    options.visitor = false;
+    options.to_source = false;
+    options.syntax = false;
+    options.module = false;
+    options.recursive_descent = true;
+    for (file, content) in Generator::new(options).generate(&source).unwrap() {
+        outputs.insert(format!("rd/{}", file), content);
+    }
+    outputs
+}
+
+/// Files in dir, by path relative to it
+fn read_dir(dir: &Path, prefix: &str, files: &mut BTreeMap<String, String>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries {
+        let path = entry.unwrap().path();
+        let name = format!("{}{}", prefix, path.file_name().unwrap().to_string_lossy());
+        if path.is_dir() {
+            read_dir(&path, &format!("{}/", name), files);
+        } else {
+            files.insert(name, fs::read_to_string(&path).unwrap());
+        }
+    }
+}
+
+fn check_golden(grammar: &str, name: &str, dir: &str) {
+    let golden_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(dir);
+    let outputs = outputs(grammar, name);
+    if env::var("UPDATE_GOLDEN").is_ok() {
+        let _ = fs::remove_dir_all(&golden_dir);
+        for (file, content) in &outputs {
+            let path = golden_dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        return;
+    }
+    let mut golden = BTreeMap::new();
+    read_dir(&golden_dir, "", &mut golden);
+    let mut failures = String::new();
+    for (file, content) in &outputs {
+        let expected = golden.get(file).map(|s| s.as_str()).unwrap_or("");
+        let label = format!("{}/{}", dir, file);
+        if let Some(diff) = unified_diff(&format!("golden/{}", label), &label, expected, content) {
+            failures += &diff;
+        }
+    }
+    for file in golden.keys().filter(|file| !outputs.contains_key(*file)) {
+        failures += &format!("{}/{} is no longer generated\n", dir, file);
+    }
+    if !failures.is_empty() {
+        panic!(
+            "Outputs of {} differ from tests/golden/{}, run with UPDATE_GOLDEN=1 if intended:\n{}",
+            grammar, dir, failures
+        );
+    }
+}
+
+#[test]
+fn descr_lang() {
+    check_golden("descr.lang", "Descr", "descr");
+}
+
+#[test]
+fn json_lang() {
+    check_golden("langs/json-descr/json.lang", "Json", "json");
+}
+
+#[test]
+fn rust_lang() {
+    check_golden("langs/rust-descr/rust.lang", "Rust", "rust");
+}
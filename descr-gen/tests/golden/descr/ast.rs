@@ -325,6 +325,10 @@ impl<'a> Quoted<'a> {
         }
     }
 
+    pub fn as_annot_arg_val(self) -> AnnotArgVal<'a> {
+        AnnotArgVal::QuotedItem(self)
+    }
+
     pub fn as_func_arg(self) -> FuncArg<'a> {
         FuncArg::QuotedItem(self)
     }
@@ -332,10 +336,6 @@ impl<'a> Quoted<'a> {
     pub fn as_token_type(self) -> TokenType<'a> {
         TokenType::QuotedItem(self)
     }
-
-    pub fn as_annot_arg_val(self) -> AnnotArgVal<'a> {
-        AnnotArgVal::QuotedItem(self)
-    }
 }
 
 #[derive(Debug)]
@@ -401,13 +401,13 @@ impl<'a> TokenGroup<'a> {
 }
 
 #[derive(Debug)]
-#[allow(dead_code)]
 pub enum AnnotArgVal<'a> {
     QuotedItem(Quoted<'a>),
     IdentItem(Ident<'a>),
     IntConstItem(IntConst),
 }
 
+#[allow(dead_code)]
 impl<'a> AnnotArgVal<'a> {
     pub fn quoted(string: &'a str) -> AnnotArgVal<'a> {
         AnnotArgVal::QuotedItem(Quoted::new(string))
@@ -423,12 +423,12 @@ impl<'a> AnnotArgVal<'a> {
 }
 
 #[derive(Debug)]
-#[allow(dead_code)]
 pub enum AstItem<'a> {
     AstDefItem(AstDef<'a>),
     AstRefItem(AstRef<'a>),
 }
 
+#[allow(dead_code)]
 impl<'a> AstItem<'a> {
     pub fn ast_def(annots: Vec<Annotation<'a>>, tokens: Vec<Token<'a>>, ident: Option<&'a str>) -> AstItem<'a> {
         AstItem::AstDefItem(AstDef::new(annots, tokens, ident))
@@ -440,11 +440,11 @@ impl<'a> AstItem<'a> {
 }
 
 #[derive(Debug)]
-#[allow(dead_code)]
 pub enum FuncArg<'a> {
     QuotedItem(Quoted<'a>),
 }
 
+#[allow(dead_code)]
 impl<'a> FuncArg<'a> {
     pub fn quoted(string: &'a str) -> FuncArg<'a> {
         FuncArg::QuotedItem(Quoted::new(string))
@@ -452,12 +452,12 @@ impl<'a> FuncArg<'a> {
 }
 
 #[derive(Debug)]
-#[allow(dead_code)]
 pub enum List<'a> {
     ListSingleItem(ListSingle<'a>),
     ListManyItem(ListMany<'a>),
 }
 
+#[allow(dead_code)]
 impl<'a> List<'a> {
     pub fn list_single(annots: Vec<Annotation<'a>>, ident: &'a str, sep: &'a str, reference: &'a str) -> List<'a> {
         List::ListSingleItem(ListSingle::new(annots, ident, sep, reference))
@@ -469,7 +469,6 @@ impl<'a> List<'a> {
 }
 
 #[derive(Debug)]
-#[allow(dead_code)]
 pub enum SourceItem<'a> {
     AstSingleItem(AstSingle<'a>),
     AstManyItem(AstMany<'a>),
@@ -477,6 +476,7 @@ pub enum SourceItem<'a> {
     CommentItem(Comment<'a>),
 }
 
+#[allow(dead_code)]
 impl<'a> SourceItem<'a> {
     pub fn ast_single(annots: Vec<Annotation<'a>>, ident: &'a str, tokens: Vec<Token<'a>>) -> SourceItem<'a> {
         SourceItem::AstSingleItem(AstSingle::new(annots, ident, tokens))
@@ -492,13 +492,13 @@ impl<'a> SourceItem<'a> {
 }
 
 #[derive(Debug)]
-#[allow(dead_code)]
 pub enum Token<'a> {
     NamedTokenItem(NamedToken<'a>),
     SimpleTokenItem(SimpleToken<'a>),
     TokenGroupItem(TokenGroup<'a>),
 }
 
+#[allow(dead_code)]
 impl<'a> Token<'a> {
     pub fn named_token(annots: Vec<Annotation<'a>>, name: &'a str, not: bool, token_type: TokenType<'a>, optional: bool) -> Token<'a> {
         Token::NamedTokenItem(NamedToken::new(annots, name, not, token_type, optional))
@@ -514,13 +514,13 @@ impl<'a> Token<'a> {
 }
 
 #[derive(Debug)]
-#[allow(dead_code)]
 pub enum TokenType<'a> {
     FuncTokenItem(FuncToken<'a>),
     KeyTokenItem(KeyToken<'a>),
     QuotedItem(Quoted<'a>),
 }
 
+#[allow(dead_code)]
 impl<'a> TokenType<'a> {
     pub fn func_token(ident: &'a str, fn_args: Vec<FuncArg<'a>>) -> TokenType<'a> {
         TokenType::FuncTokenItem(FuncToken::new(ident, fn_args))
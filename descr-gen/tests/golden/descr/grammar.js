@@ -0,0 +1,81 @@
+module.exports = grammar({
+    name: 'descr',
+
+    extras: $ => [/\s/],
+
+    word: $ => $.identifier,
+
+    rules: {
+        source: $ => field('items', optional($.source_items)),
+
+        annot_arg_val: $ => choice($.quoted, $.ident, $.int_const),
+
+        quoted: $ => field('string', $.string),
+
+        ident: $ => field('ident', $.identifier),
+
+        int_const: $ => field('int', $.integer),
+
+        annot_args: $ => seq('(', field('annot_arg_list', optional($.annot_arg_list)), ')'),
+
+        annotation: $ => seq('@', field('ident', $.identifier), optional(field('annot_args', $.annot_args))),
+
+        ast_item: $ => choice($.ast_def, $.ast_ref),
+
+        ast_def: $ => choice(seq(field('annots', optional($.annots)), field('tokens', optional($.token_list)), '=>', optional(field('ident', $.identifier))), seq(field('annots', optional($.annots)), '(', field('tokens', optional($.token_list)), ')', '=>', optional(field('ident', $.identifier))), seq(field('annots', optional($.annots)), optional(field('ident', $.identifier)), '(', field('tokens', optional($.token_list)), ')')),
+
+        ast_ref: $ => field('ident', $.identifier),
+
+        ast_many: $ => seq(field('annots', optional($.annots)), field('ident', $.identifier), '{', field('items', optional($.ast_items)), '}'),
+
+        ast_single: $ => seq(field('annots', optional($.annots)), field('ident', $.identifier), '(', field('tokens', optional($.token_list)), ')'),
+
+        comment: $ => seq('(*', field('comment', token(prec(-1, /([^\*]|\*+[^\*\)])+/))), '*)'),
+
+        list: $ => choice($.list_single, $.list_many),
+
+        list_single: $ => seq(field('annots', optional($.annots)), field('ident', $.identifier), '[', ']', field('sep', $.identifier), field('reference', $.identifier)),
+
+        list_many: $ => seq(field('annots', optional($.annots)), field('ident', $.identifier), ':', field('ast_type', $.identifier), '[', ']', optional(field('sep', $.identifier)), '{', field('items', optional($.list_items)), '}'),
+
+        list_item: $ => seq(field('ast_item', $.ast_item), optional(field('sep', $.identifier))),
+
+        token_type: $ => choice($.func_token, $.key_token, $.quoted),
+
+        func_token: $ => seq(field('ident', $.identifier), '(', field('fn_args', optional($.fn_args)), ')'),
+
+        key_token: $ => field('key', $.identifier),
+
+        annot_arg_list: $ => sepBy1(',', $.annot_arg),
+
+        annot_arg: $ => seq(field('key', $.identifier), '=', field('annot_arg_val', $.annot_arg_val)),
+
+        annots: $ => repeat1($.annotation),
+
+        ast_items: $ => sepBy1(',', $.ast_item),
+
+        fn_args: $ => sepBy1(',', $.quoted),
+
+        list_items: $ => sepBy1(',', $.list_item),
+
+        source_items: $ => repeat1(choice($.ast_single, $.ast_many, $.list, $.comment)),
+
+        token_list: $ => repeat1(choice($.named_token, $.simple_token, $.token_group)),
+
+        named_token: $ => seq(field('annots', optional($.annots)), field('name', $.identifier), ':', optional(field('not', '!')), field('token_type', $.token_type), optional(field('optional', '?'))),
+
+        simple_token: $ => seq(field('annots', optional($.annots)), optional(field('not', '!')), field('token_type', $.token_type), optional(field('optional', '?'))),
+
+        token_group: $ => seq(field('annots', optional($.annots)), optional(field('not', '!')), '(', field('token_list', optional($.token_list)), ')', optional(field('optional', '?'))),
+
+        identifier: $ => /[_]*[a-zA-Z][a-zA-Z0-9_]*/,
+
+        integer: $ => /[-+]?[0-9]+/,
+
+        string: $ => /"(?:[^"\\]|\\.)*"/,
+    }
+});
+
+function sepBy1(sep, rule) {
+    return seq(rule, repeat(seq(sep, rule)));
+}
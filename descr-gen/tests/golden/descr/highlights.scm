@@ -0,0 +1,25 @@
+(annotation "@" @keyword.control)
+(annotation ident: _ @entity.name.function)
+(ast_def ident: _ @entity.name.type)
+(ast_many ident: _ @entity.name.type)
+(ast_single ident: _ @entity.name.type)
+(list_single ident: _ @entity.name.type)
+(list_many ident: _ @entity.name.function)
+(list_many ast_type: _ @entity.name.type)
+(key_token key: _ @storage.type)
+(named_token name: _ @variable.name)
+(named_token not: _ @keyword.control)
+(named_token optional: _ @keyword.control)
+(simple_token not: _ @keyword.control)
+(simple_token optional: _ @keyword.control)
+(token_group not: _ @keyword.control)
+(token_group optional: _ @keyword.control)
+[
+    "(*"
+    "*)"
+    "=>"
+    "@"
+] @keyword.other
+(identifier) @variable.other
+(integer) @constant.numeric
+(string) @string.quoted
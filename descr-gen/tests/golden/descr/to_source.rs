@@ -3,6 +3,16 @@ use super::ast::*;
 pub struct ToSource;
 #[allow(unused_variables,dead_code)]
 impl<'a> ToSource {
+    pub fn annot_arg(mut s: String, node: &'a AnnotArg) -> String {
+        s += " ";
+        s += node.key;
+        s += " ";
+        s.push('=');
+        s += " ";
+        s = Self::annot_arg_val(s, &node.annot_arg_val);
+        s
+    }
+
     pub fn annot_args(mut s: String, node: &'a AnnotArgs) -> String {
         s += " ";
         s.push('(');
@@ -10,37 +20,75 @@ impl<'a> ToSource {
         let len = node.annot_arg_list.len();
         for (i, item) in node.annot_arg_list.iter().enumerate() {
             s = Self::annot_arg(s, item);
-            if i < len - 1 {         s.push(',');
- }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s.push(',');
+            }
         }
         s += " ";
         s.push(')');
         s
     }
 
-    pub fn list_item(mut s: String, node: &'a ListItem) -> String {
-        s += " ";
-        s = Self::ast_item(s, &node.ast_item);
+    pub fn annotation(mut s: String, node: &'a Annotation) -> String {
         s += " ";
-        if let Some(some_val) = node.sep {
-                s += some_val;
-        }        s
-    }
-
-    pub fn ident(mut s: String, node: &'a Ident) -> String {
+        s += "@";
         s += " ";
         s += node.ident;
+        s += " ";
+        if let Some(ref some_val) = node.annot_args {
+            s = Self::annot_args(s, some_val);
+        }
         s
     }
 
-    pub fn annotation(mut s: String, node: &'a Annotation) -> String {
+    pub fn ast_def(mut s: String, node: &'a AstDef) -> String {
+        if node.tokens.first().map_or(true, |item| match item { &Token::NamedTokenItem(ref item) => item.annots.is_empty(), &Token::SimpleTokenItem(ref item) => item.annots.is_empty(), &Token::TokenGroupItem(ref item) => item.annots.is_empty(), }) {
         s += " ";
-        s += "@";
+        let len = node.annots.len();
+        for (i, item) in node.annots.iter().enumerate() {
+            s = Self::annotation(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
+        }
         s += " ";
-        s += node.ident;
+        let len = node.tokens.len();
+        for (i, item) in node.tokens.iter().enumerate() {
+            s = Self::token(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
+        }
         s += " ";
-        if let Some(ref some_val) = node.annot_args {
-            s = Self::annot_args(s, some_val);
+        s += "=>";
+        s += " ";
+        if let Some(some_val) = node.ident {
+                s += some_val;
+        }        } else {
+        s += " ";
+        let len = node.annots.len();
+        for (i, item) in node.annots.iter().enumerate() {
+            s = Self::annotation(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
+        }
+        s += " ";
+        if let Some(some_val) = node.ident {
+                s += some_val;
+        }        s += " ";
+        s.push('(');
+        s += " ";
+        let len = node.tokens.len();
+        for (i, item) in node.tokens.iter().enumerate() {
+            s = Self::token(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
+        }
+        s += " ";
+        s.push(')');
         }
         s
     }
@@ -50,7 +98,9 @@ impl<'a> ToSource {
         let len = node.annots.len();
         for (i, item) in node.annots.iter().enumerate() {
             s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
         s += node.ident;
@@ -60,123 +110,137 @@ impl<'a> ToSource {
         let len = node.items.len();
         for (i, item) in node.items.iter().enumerate() {
             s = Self::ast_item(s, item);
-            if i < len - 1 {         s.push(',');
- }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s.push(',');
+            }
         }
         s += " ";
         s.push('}');
         s
     }
 
-    pub fn quoted(mut s: String, node: &'a Quoted) -> String {
-        s += " ";
-        s += "\"";
-        s += node.string;
-        s += "\"";
-        s += " ";
-        s += "\"";
-        s += node.string;
-        s += "\"";
-        s += " ";
-        s += "\"";
-        s += node.string;
-        s += "\"";
-        s
-    }
-
-    pub fn int_const(mut s: String, node: &'a IntConst) -> String {
+    pub fn ast_ref(mut s: String, node: &'a AstRef) -> String {
         s += " ";
-        s += &node.int.to_string();
+        s += node.ident;
         s
     }
 
-    pub fn token_group(mut s: String, node: &'a TokenGroup) -> String {
+    pub fn ast_single(mut s: String, node: &'a AstSingle) -> String {
         s += " ";
         let len = node.annots.len();
         for (i, item) in node.annots.iter().enumerate() {
             s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
-        if node.not {
-                s.push('!');
-    }        s += " ";
+        s += node.ident;
+        s += " ";
         s.push('(');
         s += " ";
-        let len = node.token_list.len();
-        for (i, item) in node.token_list.iter().enumerate() {
+        let len = node.tokens.len();
+        for (i, item) in node.tokens.iter().enumerate() {
             s = Self::token(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
         s.push(')');
-        s += " ";
-        if node.optional {
-                s.push('?');
-    }        s
+        s
     }
 
-    pub fn ast_single(mut s: String, node: &'a AstSingle) -> String {
+    pub fn comment(mut s: String, node: &'a Comment) -> String {
         s += " ";
-        let len = node.annots.len();
-        for (i, item) in node.annots.iter().enumerate() {
-            s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
-        }
+        s += "(*";
+        s += node.comment;
+        s += "*)";
+        s
+    }
+
+    pub fn func_token(mut s: String, node: &'a FuncToken) -> String {
         s += " ";
         s += node.ident;
         s += " ";
         s.push('(');
         s += " ";
-        let len = node.tokens.len();
-        for (i, item) in node.tokens.iter().enumerate() {
-            s = Self::token(s, item);
-            if i < len - 1 {         s += " " }
+        let len = node.fn_args.len();
+        for (i, item) in node.fn_args.iter().enumerate() {
+            s = Self::func_arg(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s.push(',');
+            }
         }
         s += " ";
         s.push(')');
         s
     }
 
-    pub fn source(mut s: String, node: &'a Source) -> String {
+    pub fn ident(mut s: String, node: &'a Ident) -> String {
         s += " ";
-        let len = node.items.len();
-        for (i, item) in node.items.iter().enumerate() {
-            s = Self::source_item(s, item);
-            if i < len - 1 {         s += " " }
-        }
+        s += node.ident;
         s
     }
 
-    pub fn named_token(mut s: String, node: &'a NamedToken) -> String {
+    pub fn int_const(mut s: String, node: &'a IntConst) -> String {
+        s += " ";
+        s += &node.int.to_string();
+        s
+    }
+
+    pub fn key_token(mut s: String, node: &'a KeyToken) -> String {
+        s += " ";
+        s += node.key;
+        s
+    }
+
+    pub fn list_item(mut s: String, node: &'a ListItem) -> String {
+        s += " ";
+        s = Self::ast_item(s, &node.ast_item);
+        s += " ";
+        if let Some(some_val) = node.sep {
+                s += some_val;
+        }        s
+    }
+
+    pub fn list_many(mut s: String, node: &'a ListMany) -> String {
         s += " ";
         let len = node.annots.len();
         for (i, item) in node.annots.iter().enumerate() {
             s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
-        s += node.name;
+        s += node.ident;
         s += " ";
         s.push(':');
         s += " ";
-        if node.not {
-                s.push('!');
-    }        s += " ";
-        s = Self::token_type(s, &node.token_type);
+        s += node.ast_type;
         s += " ";
-        if node.optional {
-                s.push('?');
-    }        s
-    }
-
-    pub fn comment(mut s: String, node: &'a Comment) -> String {
+        s.push('[');
         s += " ";
-        s += "(*";
+        s.push(']');
         s += " ";
-        s += node.comment;
+        if let Some(some_val) = node.sep {
+                s += some_val;
+        }        s += " ";
+        s.push('{');
         s += " ";
-        s += "*)";
+        let len = node.items.len();
+        for (i, item) in node.items.iter().enumerate() {
+            s = Self::list_item(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s.push(',');
+            }
+        }
+        s += " ";
+        s.push('}');
         s
     }
 
@@ -185,7 +249,9 @@ impl<'a> ToSource {
         let len = node.annots.len();
         for (i, item) in node.annots.iter().enumerate() {
             s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
         s += node.ident;
@@ -200,64 +266,35 @@ impl<'a> ToSource {
         s
     }
 
-    pub fn ast_def(mut s: String, node: &'a AstDef) -> String {
+    pub fn named_token(mut s: String, node: &'a NamedToken) -> String {
         s += " ";
         let len = node.annots.len();
         for (i, item) in node.annots.iter().enumerate() {
             s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
-        let len = node.tokens.len();
-        for (i, item) in node.tokens.iter().enumerate() {
-            s = Self::token(s, item);
-            if i < len - 1 {         s += " " }
-        }
-        s += " ";
-        s += "=>";
-        s += " ";
-        if let Some(some_val) = node.ident {
-                s += some_val;
-        }        s += " ";
-        let len = node.annots.len();
-        for (i, item) in node.annots.iter().enumerate() {
-            s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
-        }
-        s += " ";
-        s.push('(');
-        s += " ";
-        let len = node.tokens.len();
-        for (i, item) in node.tokens.iter().enumerate() {
-            s = Self::token(s, item);
-            if i < len - 1 {         s += " " }
-        }
-        s += " ";
-        s.push(')');
-        s += " ";
-        s += "=>";
+        s += node.name;
         s += " ";
-        if let Some(some_val) = node.ident {
-                s += some_val;
-        }        s += " ";
-        let len = node.annots.len();
-        for (i, item) in node.annots.iter().enumerate() {
-            s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
-        }
+        s.push(':');
         s += " ";
-        if let Some(some_val) = node.ident {
-                s += some_val;
-        }        s += " ";
-        s.push('(');
+        if node.not {
+                s.push('!');
+    }        s += " ";
+        s = Self::token_type(s, &node.token_type);
         s += " ";
-        let len = node.tokens.len();
-        for (i, item) in node.tokens.iter().enumerate() {
-            s = Self::token(s, item);
-            if i < len - 1 {         s += " " }
-        }
+        if node.optional {
+                s.push('?');
+    }        s
+    }
+
+    pub fn quoted(mut s: String, node: &'a Quoted) -> String {
         s += " ";
-        s.push(')');
+        s += "\"";
+        s += node.string;
+        s += "\"";
         s
     }
 
@@ -266,7 +303,9 @@ impl<'a> ToSource {
         let len = node.annots.len();
         for (i, item) in node.annots.iter().enumerate() {
             s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
         if node.not {
@@ -279,77 +318,46 @@ impl<'a> ToSource {
     }        s
     }
 
-    pub fn func_token(mut s: String, node: &'a FuncToken) -> String {
-        s += " ";
-        s += node.ident;
-        s += " ";
-        s.push('(');
+    pub fn source(mut s: String, node: &'a Source) -> String {
         s += " ";
-        let len = node.fn_args.len();
-        for (i, item) in node.fn_args.iter().enumerate() {
-            s = Self::func_arg(s, item);
-            if i < len - 1 {         s.push(',');
- }
+        let len = node.items.len();
+        for (i, item) in node.items.iter().enumerate() {
+            s = Self::source_item(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
-        s += " ";
-        s.push(')');
         s
     }
 
-    pub fn list_many(mut s: String, node: &'a ListMany) -> String {
+    pub fn token_group(mut s: String, node: &'a TokenGroup) -> String {
         s += " ";
         let len = node.annots.len();
         for (i, item) in node.annots.iter().enumerate() {
             s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
-        s += node.ident;
-        s += " ";
-        s.push(':');
-        s += " ";
-        s += node.ast_type;
-        s += " ";
-        s.push('[');
-        s += " ";
-        s.push(']');
-        s += " ";
-        if let Some(some_val) = node.sep {
-                s += some_val;
-        }        s += " ";
-        s.push('{');
+        if node.not {
+                s.push('!');
+    }        s += " ";
+        s.push('(');
         s += " ";
-        let len = node.items.len();
-        for (i, item) in node.items.iter().enumerate() {
-            s = Self::list_item(s, item);
-            if i < len - 1 {         s.push(',');
- }
+        let len = node.token_list.len();
+        for (i, item) in node.token_list.iter().enumerate() {
+            s = Self::token(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
-        s.push('}');
-        s
-    }
-
-    pub fn ast_ref(mut s: String, node: &'a AstRef) -> String {
-        s += " ";
-        s += node.ident;
-        s
-    }
-
-    pub fn key_token(mut s: String, node: &'a KeyToken) -> String {
-        s += " ";
-        s += node.key;
-        s
-    }
-
-    pub fn annot_arg(mut s: String, node: &'a AnnotArg) -> String {
-        s += " ";
-        s += node.key;
-        s += " ";
-        s.push('=');
+        s.push(')');
         s += " ";
-        s = Self::annot_arg_val(s, &node.annot_arg_val);
-        s
+        if node.optional {
+                s.push('?');
+    }        s
     }
 
     pub fn annot_arg_val(s: String, node: &'a AnnotArgVal) -> String {
@@ -3,6 +3,7 @@ pub struct ArrayVal {
     pub items: Vec<JsVal>,
 }
 
+#[allow(dead_code)]
 impl ArrayVal {
     pub fn new(items: Vec<JsVal>) -> ArrayVal {
         ArrayVal {
@@ -20,6 +21,7 @@ pub struct Int {
     pub int: u32,
 }
 
+#[allow(dead_code)]
 impl Int {
     pub fn new(int: u32) -> Int {
         Int {
@@ -37,6 +39,7 @@ pub struct JsObject {
     pub items: Vec<ObjectPair>,
 }
 
+#[allow(dead_code)]
 impl JsObject {
     pub fn new(items: Vec<ObjectPair>) -> JsObject {
         JsObject {
@@ -55,6 +58,7 @@ pub struct ObjectPair {
     pub val: JsVal,
 }
 
+#[allow(dead_code)]
 impl ObjectPair {
     pub fn new(key: String, val: JsVal) -> ObjectPair {
         ObjectPair {
@@ -69,6 +73,7 @@ pub struct StringVal {
     pub string: String,
 }
 
+#[allow(dead_code)]
 impl StringVal {
     pub fn new(string: String) -> StringVal {
         StringVal {
@@ -89,6 +94,7 @@ pub enum JsVal {
     JsObjectItem(Box<JsObject>),
 }
 
+#[allow(dead_code)]
 impl JsVal {
     pub fn int(int: u32) -> JsVal {
         JsVal::IntItem(Int::new(int))
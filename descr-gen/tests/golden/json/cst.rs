@@ -0,0 +1,296 @@
+#[allow(unused_imports)]
+use descr_common::cst::*;
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    ROOT,
+    WHITESPACE,
+    TEXT,
+    IDENT,
+    INT,
+    STRING,
+    /// ,
+    COMMA,
+    /// :
+    COLON,
+    /// [
+    LBRACKET,
+    /// ]
+    RBRACKET,
+    /// {
+    LBRACE,
+    /// }
+    RBRACE,
+    ArrayVal,
+    ArrayVals,
+    Int,
+    JsObject,
+    JsVal,
+    ObjectPair,
+    ObjectPairs,
+    StringVal,
+}
+
+impl CstKind for SyntaxKind {
+    fn root() -> SyntaxKind {
+        SyntaxKind::ROOT
+    }
+    fn whitespace() -> SyntaxKind {
+        SyntaxKind::WHITESPACE
+    }
+    fn text() -> SyntaxKind {
+        SyntaxKind::TEXT
+    }
+}
+
+pub type Node = SyntaxNode<SyntaxKind>;
+
+/// Lossless tree of input. The root has the
+/// start node and trailing whitespace.
+pub fn parse(input: &[u8]) -> Result<Node, CstError> {
+    ::descr_common::cst::parse(input, |c| js_object(c, None))
+}
+
+pub fn js_object(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::JsObject, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::LBRACE, None, |c| c.chr('{'))?;
+        c.sp();
+        object_pairs(c, Some("items"))?;
+        c.sp();
+        c.token(SyntaxKind::RBRACE, None, |c| c.chr('}'))?;
+        Ok(())
+    })
+}
+
+pub fn js_val(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    if c.attempt(|c| c.node(SyntaxKind::Int, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::INT, Some("int"), |c| c.int())?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::StringVal, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::STRING, Some("string"), |c| c.string())?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::ArrayVal, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::LBRACKET, None, |c| c.chr('['))?;
+        c.sp();
+        array_vals(c, Some("items"))?;
+        c.sp();
+        c.token(SyntaxKind::RBRACKET, None, |c| c.chr(']'))?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| js_object(c, label)).is_some() {
+        return Ok(());
+    }
+    Err(Fail::Error)
+}
+
+pub fn array_vals(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::ArrayVals, label, |c| c.separated_list(|c| c.token(SyntaxKind::COMMA, None, |c| c.chr(',')), |c| array_vals_item(c, None)))
+}
+
+fn array_vals_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    js_val(c, label)
+}
+
+pub fn object_pairs(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::ObjectPairs, label, |c| c.separated_list(|c| c.token(SyntaxKind::COMMA, None, |c| c.chr(',')), |c| object_pairs_item(c, None)))
+}
+
+fn object_pairs_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::ObjectPair, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::STRING, Some("key"), |c| c.string())?;
+        c.sp();
+        c.token(SyntaxKind::COLON, None, |c| c.chr(':'))?;
+        c.sp();
+        js_val(c, Some("val"))?;
+        Ok(())
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct ArrayValNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl ArrayValNode {
+    pub fn cast(syntax: Node) -> Option<ArrayValNode> {
+        if syntax.kind() == SyntaxKind::ArrayVal {
+            Some(ArrayValNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn items(&self) -> Vec<JsValNode> {
+        match self.syntax.child_node("items") {
+            Some(list) => list.child_nodes().into_iter().filter_map(JsValNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IntNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl IntNode {
+    pub fn cast(syntax: Node) -> Option<IntNode> {
+        if syntax.kind() == SyntaxKind::Int {
+            Some(IntNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn int(&self) -> Option<u32> {
+        self.syntax.child_token("int").and_then(|token| token.text().parse().ok())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JsObjectNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl JsObjectNode {
+    pub fn cast(syntax: Node) -> Option<JsObjectNode> {
+        if syntax.kind() == SyntaxKind::JsObject {
+            Some(JsObjectNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn items(&self) -> Vec<ObjectPairNode> {
+        match self.syntax.child_node("items") {
+            Some(list) => list.child_nodes().into_iter().filter_map(ObjectPairNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjectPairNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl ObjectPairNode {
+    pub fn cast(syntax: Node) -> Option<ObjectPairNode> {
+        if syntax.kind() == SyntaxKind::ObjectPair {
+            Some(ObjectPairNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    /// String without quotes
+    pub fn key(&self) -> Option<String> {
+        self.syntax.child_token("key").map(|token| {
+            let text = token.text();
+            text[1..text.len() - 1].to_string()
+        })
+    }
+
+    pub fn val(&self) -> Option<JsValNode> {
+        self.syntax.child_node("val").and_then(JsValNode::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StringValNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl StringValNode {
+    pub fn cast(syntax: Node) -> Option<StringValNode> {
+        if syntax.kind() == SyntaxKind::StringVal {
+            Some(StringValNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    /// String without quotes
+    pub fn string(&self) -> Option<String> {
+        self.syntax.child_token("string").map(|token| {
+            let text = token.text();
+            text[1..text.len() - 1].to_string()
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum JsValNode {
+    Int(IntNode),
+    StringVal(StringValNode),
+    ArrayVal(ArrayValNode),
+    JsObject(JsObjectNode),
+}
+
+#[allow(dead_code)]
+impl JsValNode {
+    pub fn cast(syntax: Node) -> Option<JsValNode> {
+        if let Some(node) = IntNode::cast(syntax.clone()) {
+            return Some(JsValNode::Int(node));
+        }
+        if let Some(node) = StringValNode::cast(syntax.clone()) {
+            return Some(JsValNode::StringVal(node));
+        }
+        if let Some(node) = ArrayValNode::cast(syntax.clone()) {
+            return Some(JsValNode::ArrayVal(node));
+        }
+        if let Some(node) = JsObjectNode::cast(syntax.clone()) {
+            return Some(JsValNode::JsObject(node));
+        }
+        None
+    }
+
+    pub fn syntax(&self) -> &Node {
+        match self {
+            &JsValNode::Int(ref node) => node.syntax(),
+            &JsValNode::StringVal(ref node) => node.syntax(),
+            &JsValNode::ArrayVal(ref node) => node.syntax(),
+            &JsValNode::JsObject(ref node) => node.syntax(),
+        }
+    }
+}
+
@@ -0,0 +1,31 @@
+module.exports = grammar({
+    name: 'json',
+
+    extras: $ => [/\s/],
+
+    rules: {
+        js_object: $ => seq('{', field('items', optional($.object_pairs)), '}'),
+
+        js_val: $ => choice($.int, $.string_val, $.array_val, $.js_object),
+
+        int: $ => field('int', $.integer),
+
+        string_val: $ => field('string', $.string),
+
+        array_val: $ => seq('[', field('items', optional($.array_vals)), ']'),
+
+        array_vals: $ => sepBy1(',', $.js_val),
+
+        object_pairs: $ => sepBy1(',', $.object_pair),
+
+        object_pair: $ => seq(field('key', $.string), ':', field('val', $.js_val)),
+
+        integer: $ => /[-+]?[0-9]+/,
+
+        string: $ => /"(?:[^"\\]|\\.)*"/,
+    }
+});
+
+function sepBy1(sep, rule) {
+    return seq(rule, repeat(seq(sep, rule)));
+}
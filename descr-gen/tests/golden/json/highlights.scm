@@ -0,0 +1,2 @@
+(integer) @constant.numeric
+(string) @string.quoted
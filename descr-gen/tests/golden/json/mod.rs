@@ -2,4 +2,6 @@ pub mod ast;
 pub mod parsers;
 pub mod visitor;
 pub mod to_source;
+pub mod cst;
+pub mod highlight;
 
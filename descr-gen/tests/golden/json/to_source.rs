@@ -3,11 +3,20 @@ use super::ast::*;
 pub struct ToSource;
 #[allow(unused_variables,dead_code)]
 impl<'a> ToSource {
-    pub fn string_val(mut s: String, node: &'a StringVal) -> String {
+    pub fn array_val(mut s: String, node: &'a ArrayVal) -> String {
         s += " ";
-        s += "\"";
-        s += node.string.as_str();
-        s += "\"";
+        s.push('[');
+        s += " ";
+        let len = node.items.len();
+        for (i, item) in node.items.iter().enumerate() {
+            s = Self::js_val(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s.push(',');
+            }
+        }
+        s += " ";
+        s.push(']');
         s
     }
 
@@ -24,8 +33,10 @@ impl<'a> ToSource {
         let len = node.items.len();
         for (i, item) in node.items.iter().enumerate() {
             s = Self::object_pair(s, item);
-            if i < len - 1 {         s.push(',');
- }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s.push(',');
+            }
         }
         s += " ";
         s.push('}');
@@ -44,18 +55,11 @@ impl<'a> ToSource {
         s
     }
 
-    pub fn array_val(mut s: String, node: &'a ArrayVal) -> String {
-        s += " ";
-        s.push('[');
-        s += " ";
-        let len = node.items.len();
-        for (i, item) in node.items.iter().enumerate() {
-            s = Self::js_val(s, item);
-            if i < len - 1 {         s.push(',');
- }
-        }
+    pub fn string_val(mut s: String, node: &'a StringVal) -> String {
         s += " ";
-        s.push(']');
+        s += "\"";
+        s += node.string.as_str();
+        s += "\"";
         s
     }
 
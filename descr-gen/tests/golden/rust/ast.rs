@@ -0,0 +1,226 @@
+#[derive(Debug)]
+pub struct EnumItem<'a> {
+    pub ident: &'a str,
+}
+
+#[allow(dead_code)]
+impl<'a> EnumItem<'a> {
+    pub fn new(ident: &'a str) -> EnumItem<'a> {
+        EnumItem {
+            ident
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GenType<'a> {
+    pub ident: &'a str,
+}
+
+#[allow(dead_code)]
+impl<'a> GenType<'a> {
+    pub fn new(ident: &'a str) -> GenType<'a> {
+        GenType {
+            ident
+        }
+    }
+
+    pub fn as_generic_item(self) -> GenericItem<'a> {
+        GenericItem::GenTypeItem(self)
+    }
+}
+
+#[derive(Debug)]
+pub struct Generic<'a> {
+    pub generic_items: Vec<GenericItem<'a>>,
+}
+
+#[allow(dead_code)]
+impl<'a> Generic<'a> {
+    pub fn new(generic_items: Vec<GenericItem<'a>>) -> Generic<'a> {
+        Generic {
+            generic_items
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LifeTime<'a> {
+    pub ident: &'a str,
+}
+
+#[allow(dead_code)]
+impl<'a> LifeTime<'a> {
+    pub fn new(ident: &'a str) -> LifeTime<'a> {
+        LifeTime {
+            ident
+        }
+    }
+
+    pub fn as_generic_item(self) -> GenericItem<'a> {
+        GenericItem::LifeTimeItem(self)
+    }
+}
+
+#[derive(Debug)]
+pub struct RsEnum<'a> {
+    pub enum_items: Vec<EnumItem<'a>>,
+    pub ident: &'a str,
+    pub public: bool,
+}
+
+#[allow(dead_code)]
+impl<'a> RsEnum<'a> {
+    pub fn new(public: bool, ident: &'a str, enum_items: Vec<EnumItem<'a>>) -> RsEnum<'a> {
+        RsEnum {
+            public,
+            ident,
+            enum_items
+        }
+    }
+
+    pub fn as_source_item(self) -> SourceItem<'a> {
+        SourceItem::RsEnumItem(self)
+    }
+}
+
+#[derive(Debug)]
+pub struct RsStruct<'a> {
+    pub generic: Option<Generic<'a>>,
+    pub ident: &'a str,
+    pub public: bool,
+    pub struct_members: Vec<StructMember<'a>>,
+}
+
+#[allow(dead_code)]
+impl<'a> RsStruct<'a> {
+    pub fn new(public: bool, ident: &'a str, generic: Option<Generic<'a>>, struct_members: Vec<StructMember<'a>>) -> RsStruct<'a> {
+        RsStruct {
+            public,
+            ident,
+            generic,
+            struct_members
+        }
+    }
+
+    pub fn as_source_item(self) -> SourceItem<'a> {
+        SourceItem::RsStructItem(self)
+    }
+}
+
+#[derive(Debug)]
+pub struct RsTrait<'a> {
+    pub ident: &'a str,
+    pub public: bool,
+}
+
+#[allow(dead_code)]
+impl<'a> RsTrait<'a> {
+    pub fn new(public: bool, ident: &'a str) -> RsTrait<'a> {
+        RsTrait {
+            public,
+            ident
+        }
+    }
+
+    pub fn as_source_item(self) -> SourceItem<'a> {
+        SourceItem::RsTraitItem(self)
+    }
+}
+
+#[derive(Debug)]
+pub struct Source<'a> {
+    pub source_items: Vec<SourceItem<'a>>,
+}
+
+#[allow(dead_code)]
+impl<'a> Source<'a> {
+    pub fn new(source_items: Vec<SourceItem<'a>>) -> Source<'a> {
+        Source {
+            source_items
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct StructMember<'a> {
+    pub tpe_spes: TpeSpes<'a>,
+    pub ident: &'a str,
+    pub public: bool,
+}
+
+#[allow(dead_code)]
+impl<'a> StructMember<'a> {
+    pub fn new(public: bool, ident: &'a str, tpe_spes: TpeSpes<'a>) -> StructMember<'a> {
+        StructMember {
+            public,
+            ident,
+            tpe_spes
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TpeSpes<'a> {
+    pub generic_item: Option<GenericItem<'a>>,
+    pub tpe: Tpe,
+}
+
+#[allow(dead_code)]
+impl<'a> TpeSpes<'a> {
+    pub fn new(tpe: Tpe, generic_item: Option<GenericItem<'a>>) -> TpeSpes<'a> {
+        TpeSpes {
+            tpe,
+            generic_item
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GenericItem<'a> {
+    LifeTimeItem(LifeTime<'a>),
+    GenTypeItem(GenType<'a>),
+}
+
+#[allow(dead_code)]
+impl<'a> GenericItem<'a> {
+    pub fn life_time(ident: &'a str) -> GenericItem<'a> {
+        GenericItem::LifeTimeItem(LifeTime::new(ident))
+    }
+
+    pub fn gen_type(ident: &'a str) -> GenericItem<'a> {
+        GenericItem::GenTypeItem(GenType::new(ident))
+    }
+}
+
+#[derive(Debug)]
+pub enum SourceItem<'a> {
+    RsStructItem(RsStruct<'a>),
+    RsEnumItem(RsEnum<'a>),
+    RsTraitItem(RsTrait<'a>),
+}
+
+#[allow(dead_code)]
+impl<'a> SourceItem<'a> {
+    pub fn rs_struct(public: bool, ident: &'a str, generic: Option<Generic<'a>>, struct_members: Vec<StructMember<'a>>) -> SourceItem<'a> {
+        SourceItem::RsStructItem(RsStruct::new(public, ident, generic, struct_members))
+    }
+
+    pub fn rs_enum(public: bool, ident: &'a str, enum_items: Vec<EnumItem<'a>>) -> SourceItem<'a> {
+        SourceItem::RsEnumItem(RsEnum::new(public, ident, enum_items))
+    }
+
+    pub fn rs_trait(public: bool, ident: &'a str) -> SourceItem<'a> {
+        SourceItem::RsTraitItem(RsTrait::new(public, ident))
+    }
+}
+
+#[derive(Debug)]
+pub enum Tpe {
+    RsU32,
+    RsI32,
+    RsString,
+    RsStr,
+    Bool,
+}
+
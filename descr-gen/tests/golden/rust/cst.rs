@@ -0,0 +1,787 @@
+#[allow(unused_imports)]
+use descr_common::cst::*;
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    ROOT,
+    WHITESPACE,
+    TEXT,
+    IDENT,
+    INT,
+    STRING,
+    /// '
+    SQUOTE,
+    /// ,
+    COMMA,
+    /// :
+    COLON,
+    /// <
+    LT,
+    /// >
+    GT,
+    /// String
+    STRING_KW,
+    /// bool
+    BOOL_KW,
+    /// enum
+    ENUM_KW,
+    /// i32
+    I32_KW,
+    /// pub
+    PUB_KW,
+    /// str
+    STR_KW,
+    /// struct
+    STRUCT_KW,
+    /// trait
+    TRAIT_KW,
+    /// u32
+    U32_KW,
+    /// {
+    LBRACE,
+    /// }
+    RBRACE,
+    Bool,
+    EnumItem,
+    EnumItems,
+    GenType,
+    Generic,
+    GenericItem,
+    GenericItems,
+    LifeTime,
+    RsEnum,
+    RsI32,
+    RsStr,
+    RsString,
+    RsStruct,
+    RsTrait,
+    RsU32,
+    Source,
+    SourceItems,
+    StructMember,
+    StructMembers,
+    Tpe,
+    TpeSpes,
+}
+
+impl CstKind for SyntaxKind {
+    fn root() -> SyntaxKind {
+        SyntaxKind::ROOT
+    }
+    fn whitespace() -> SyntaxKind {
+        SyntaxKind::WHITESPACE
+    }
+    fn text() -> SyntaxKind {
+        SyntaxKind::TEXT
+    }
+}
+
+pub type Node = SyntaxNode<SyntaxKind>;
+
+/// Lossless tree of input. The root has the
+/// start node and trailing whitespace.
+pub fn parse(input: &[u8]) -> Result<Node, CstError> {
+    ::descr_common::cst::parse(input, |c| source(c, None))
+}
+
+pub fn generic(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::Generic, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::LT, None, |c| c.tag("<"))?;
+        c.sp();
+        generic_items(c, Some("generic_items"))?;
+        c.sp();
+        c.token(SyntaxKind::GT, None, |c| c.tag(">"))?;
+        Ok(())
+    })
+}
+
+pub fn generic_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    if c.attempt(|c| life_time(c, label)).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::GenType, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    Err(Fail::Error)
+}
+
+pub fn life_time(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::LifeTime, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::SQUOTE, None, |c| c.tag("'"))?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        Ok(())
+    })
+}
+
+pub fn rs_enum(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::RsEnum, label, |c| {
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::PUB_KW, Some("public"), |c| c.tag("pub"))
+        })?;
+        c.sp();
+        c.token(SyntaxKind::ENUM_KW, None, |c| c.tag("enum"))?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        c.sp();
+        c.token(SyntaxKind::LBRACE, None, |c| c.chr('{'))?;
+        c.sp();
+        enum_items(c, Some("enum_items"))?;
+        c.sp();
+        c.token(SyntaxKind::RBRACE, None, |c| c.chr('}'))?;
+        Ok(())
+    })
+}
+
+pub fn rs_struct(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::RsStruct, label, |c| {
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::PUB_KW, Some("public"), |c| c.tag("pub"))
+        })?;
+        c.sp();
+        c.token(SyntaxKind::STRUCT_KW, None, |c| c.tag("struct"))?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        c.opt(|c| {
+            c.sp();
+            generic(c, Some("generic"))
+        })?;
+        c.sp();
+        c.token(SyntaxKind::LBRACE, None, |c| c.chr('{'))?;
+        c.sp();
+        struct_members(c, Some("struct_members"))?;
+        c.sp();
+        c.token(SyntaxKind::RBRACE, None, |c| c.chr('}'))?;
+        Ok(())
+    })
+}
+
+pub fn rs_trait(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::RsTrait, label, |c| {
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::PUB_KW, Some("public"), |c| c.tag("pub"))
+        })?;
+        c.sp();
+        c.token(SyntaxKind::TRAIT_KW, None, |c| c.tag("trait"))?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        c.sp();
+        c.token(SyntaxKind::LBRACE, None, |c| c.chr('{'))?;
+        c.sp();
+        c.token(SyntaxKind::RBRACE, None, |c| c.chr('}'))?;
+        Ok(())
+    })
+}
+
+pub fn source(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::Source, label, |c| {
+        c.sp();
+        source_items(c, Some("source_items"))?;
+        Ok(())
+    })
+}
+
+pub fn tpe(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    if c.attempt(|c| c.node(SyntaxKind::RsU32, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::U32_KW, None, |c| c.tag("u32"))?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::RsI32, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::I32_KW, None, |c| c.tag("i32"))?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::RsString, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::STRING_KW, None, |c| c.tag("String"))?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::RsStr, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::STR_KW, None, |c| c.tag("str"))?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::Bool, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::BOOL_KW, None, |c| c.tag("bool"))?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    Err(Fail::Error)
+}
+
+pub fn tpe_spes(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::TpeSpes, label, |c| {
+        c.sp();
+        tpe(c, Some("tpe"))?;
+        c.opt(|c| {
+            c.sp();
+            generic_item(c, Some("generic_item"))
+        })?;
+        Ok(())
+    })
+}
+
+pub fn enum_items(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::EnumItems, label, |c| c.separated_list(|c| c.token(SyntaxKind::COMMA, None, |c| c.chr(',')), |c| enum_items_item(c, None)))
+}
+
+fn enum_items_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::EnumItem, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        Ok(())
+    })
+}
+
+pub fn generic_items(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::GenericItems, label, |c| c.separated_list(|c| c.token(SyntaxKind::COMMA, None, |c| c.chr(',')), |c| generic_items_item(c, None)))
+}
+
+fn generic_items_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    generic_item(c, label)
+}
+
+pub fn source_items(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::SourceItems, label, |c| c.many0(|c| source_items_item(c, None)))
+}
+
+fn source_items_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    if c.attempt(|c| rs_struct(c, label)).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| rs_enum(c, label)).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| rs_trait(c, label)).is_some() {
+        return Ok(());
+    }
+    Err(Fail::Error)
+}
+
+pub fn struct_members(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::StructMembers, label, |c| c.separated_list(|c| c.token(SyntaxKind::COMMA, None, |c| c.chr(',')), |c| struct_members_item(c, None)))
+}
+
+fn struct_members_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::StructMember, label, |c| {
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::PUB_KW, Some("public"), |c| c.tag("pub"))
+        })?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        c.sp();
+        c.token(SyntaxKind::COLON, None, |c| c.chr(':'))?;
+        c.sp();
+        tpe_spes(c, Some("tpe_spes"))?;
+        Ok(())
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct BoolNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl BoolNode {
+    pub fn cast(syntax: Node) -> Option<BoolNode> {
+        if syntax.kind() == SyntaxKind::Bool {
+            Some(BoolNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumItemNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl EnumItemNode {
+    pub fn cast(syntax: Node) -> Option<EnumItemNode> {
+        if syntax.kind() == SyntaxKind::EnumItem {
+            Some(EnumItemNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GenTypeNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl GenTypeNode {
+    pub fn cast(syntax: Node) -> Option<GenTypeNode> {
+        if syntax.kind() == SyntaxKind::GenType {
+            Some(GenTypeNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GenericNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl GenericNode {
+    pub fn cast(syntax: Node) -> Option<GenericNode> {
+        if syntax.kind() == SyntaxKind::Generic {
+            Some(GenericNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn generic_items(&self) -> Vec<GenericItemNode> {
+        match self.syntax.child_node("generic_items") {
+            Some(list) => list.child_nodes().into_iter().filter_map(GenericItemNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LifeTimeNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl LifeTimeNode {
+    pub fn cast(syntax: Node) -> Option<LifeTimeNode> {
+        if syntax.kind() == SyntaxKind::LifeTime {
+            Some(LifeTimeNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RsEnumNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl RsEnumNode {
+    pub fn cast(syntax: Node) -> Option<RsEnumNode> {
+        if syntax.kind() == SyntaxKind::RsEnum {
+            Some(RsEnumNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn public(&self) -> bool {
+        self.syntax.child("public").is_some()
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+
+    pub fn enum_items(&self) -> Vec<EnumItemNode> {
+        match self.syntax.child_node("enum_items") {
+            Some(list) => list.child_nodes().into_iter().filter_map(EnumItemNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RsI32Node {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl RsI32Node {
+    pub fn cast(syntax: Node) -> Option<RsI32Node> {
+        if syntax.kind() == SyntaxKind::RsI32 {
+            Some(RsI32Node { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RsStrNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl RsStrNode {
+    pub fn cast(syntax: Node) -> Option<RsStrNode> {
+        if syntax.kind() == SyntaxKind::RsStr {
+            Some(RsStrNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RsStringNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl RsStringNode {
+    pub fn cast(syntax: Node) -> Option<RsStringNode> {
+        if syntax.kind() == SyntaxKind::RsString {
+            Some(RsStringNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RsStructNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl RsStructNode {
+    pub fn cast(syntax: Node) -> Option<RsStructNode> {
+        if syntax.kind() == SyntaxKind::RsStruct {
+            Some(RsStructNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn public(&self) -> bool {
+        self.syntax.child("public").is_some()
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+
+    pub fn generic(&self) -> Option<GenericNode> {
+        self.syntax.child_node("generic").and_then(GenericNode::cast)
+    }
+
+    pub fn struct_members(&self) -> Vec<StructMemberNode> {
+        match self.syntax.child_node("struct_members") {
+            Some(list) => list.child_nodes().into_iter().filter_map(StructMemberNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RsTraitNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl RsTraitNode {
+    pub fn cast(syntax: Node) -> Option<RsTraitNode> {
+        if syntax.kind() == SyntaxKind::RsTrait {
+            Some(RsTraitNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn public(&self) -> bool {
+        self.syntax.child("public").is_some()
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RsU32Node {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl RsU32Node {
+    pub fn cast(syntax: Node) -> Option<RsU32Node> {
+        if syntax.kind() == SyntaxKind::RsU32 {
+            Some(RsU32Node { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl SourceNode {
+    pub fn cast(syntax: Node) -> Option<SourceNode> {
+        if syntax.kind() == SyntaxKind::Source {
+            Some(SourceNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn source_items(&self) -> Vec<SourceItemNode> {
+        match self.syntax.child_node("source_items") {
+            Some(list) => list.child_nodes().into_iter().filter_map(SourceItemNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructMemberNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl StructMemberNode {
+    pub fn cast(syntax: Node) -> Option<StructMemberNode> {
+        if syntax.kind() == SyntaxKind::StructMember {
+            Some(StructMemberNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn public(&self) -> bool {
+        self.syntax.child("public").is_some()
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+
+    pub fn tpe_spes(&self) -> Option<TpeSpesNode> {
+        self.syntax.child_node("tpe_spes").and_then(TpeSpesNode::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TpeSpesNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl TpeSpesNode {
+    pub fn cast(syntax: Node) -> Option<TpeSpesNode> {
+        if syntax.kind() == SyntaxKind::TpeSpes {
+            Some(TpeSpesNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn tpe(&self) -> Option<TpeNode> {
+        self.syntax.child_node("tpe").and_then(TpeNode::cast)
+    }
+
+    pub fn generic_item(&self) -> Option<GenericItemNode> {
+        self.syntax.child_node("generic_item").and_then(GenericItemNode::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum GenericItemNode {
+    LifeTime(LifeTimeNode),
+    GenType(GenTypeNode),
+}
+
+#[allow(dead_code)]
+impl GenericItemNode {
+    pub fn cast(syntax: Node) -> Option<GenericItemNode> {
+        if let Some(node) = LifeTimeNode::cast(syntax.clone()) {
+            return Some(GenericItemNode::LifeTime(node));
+        }
+        if let Some(node) = GenTypeNode::cast(syntax.clone()) {
+            return Some(GenericItemNode::GenType(node));
+        }
+        None
+    }
+
+    pub fn syntax(&self) -> &Node {
+        match self {
+            &GenericItemNode::LifeTime(ref node) => node.syntax(),
+            &GenericItemNode::GenType(ref node) => node.syntax(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SourceItemNode {
+    RsStruct(RsStructNode),
+    RsEnum(RsEnumNode),
+    RsTrait(RsTraitNode),
+}
+
+#[allow(dead_code)]
+impl SourceItemNode {
+    pub fn cast(syntax: Node) -> Option<SourceItemNode> {
+        if let Some(node) = RsStructNode::cast(syntax.clone()) {
+            return Some(SourceItemNode::RsStruct(node));
+        }
+        if let Some(node) = RsEnumNode::cast(syntax.clone()) {
+            return Some(SourceItemNode::RsEnum(node));
+        }
+        if let Some(node) = RsTraitNode::cast(syntax.clone()) {
+            return Some(SourceItemNode::RsTrait(node));
+        }
+        None
+    }
+
+    pub fn syntax(&self) -> &Node {
+        match self {
+            &SourceItemNode::RsStruct(ref node) => node.syntax(),
+            &SourceItemNode::RsEnum(ref node) => node.syntax(),
+            &SourceItemNode::RsTrait(ref node) => node.syntax(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TpeNode {
+    RsU32(RsU32Node),
+    RsI32(RsI32Node),
+    RsString(RsStringNode),
+    RsStr(RsStrNode),
+    Bool(BoolNode),
+}
+
+#[allow(dead_code)]
+impl TpeNode {
+    pub fn cast(syntax: Node) -> Option<TpeNode> {
+        if let Some(node) = RsU32Node::cast(syntax.clone()) {
+            return Some(TpeNode::RsU32(node));
+        }
+        if let Some(node) = RsI32Node::cast(syntax.clone()) {
+            return Some(TpeNode::RsI32(node));
+        }
+        if let Some(node) = RsStringNode::cast(syntax.clone()) {
+            return Some(TpeNode::RsString(node));
+        }
+        if let Some(node) = RsStrNode::cast(syntax.clone()) {
+            return Some(TpeNode::RsStr(node));
+        }
+        if let Some(node) = BoolNode::cast(syntax.clone()) {
+            return Some(TpeNode::Bool(node));
+        }
+        None
+    }
+
+    pub fn syntax(&self) -> &Node {
+        match self {
+            &TpeNode::RsU32(ref node) => node.syntax(),
+            &TpeNode::RsI32(ref node) => node.syntax(),
+            &TpeNode::RsString(ref node) => node.syntax(),
+            &TpeNode::RsStr(ref node) => node.syntax(),
+            &TpeNode::Bool(ref node) => node.syntax(),
+        }
+    }
+}
+
@@ -0,0 +1,57 @@
+module.exports = grammar({
+    name: 'rust',
+
+    extras: $ => [/\s/],
+
+    word: $ => $.identifier,
+
+    rules: {
+        source: $ => field('source_items', optional($.source_items)),
+
+        generic: $ => seq('<', field('generic_items', optional($.generic_items)), '>'),
+
+        generic_item: $ => choice($.life_time, $.gen_type),
+
+        gen_type: $ => field('ident', $.identifier),
+
+        life_time: $ => seq('\'', field('ident', $.identifier)),
+
+        rs_enum: $ => seq(optional(field('public', 'pub')), 'enum', field('ident', $.identifier), '{', field('enum_items', optional($.enum_items)), '}'),
+
+        rs_struct: $ => seq(optional(field('public', 'pub')), 'struct', field('ident', $.identifier), optional(field('generic', $.generic)), '{', field('struct_members', optional($.struct_members)), '}'),
+
+        rs_trait: $ => seq(optional(field('public', 'pub')), 'trait', field('ident', $.identifier), '{', '}'),
+
+        tpe: $ => choice($.rs_u32, $.rs_i32, $.rs_string, $.rs_str, $.bool),
+
+        rs_u32: $ => 'u32',
+
+        rs_i32: $ => 'i32',
+
+        rs_string: $ => 'String',
+
+        rs_str: $ => 'str',
+
+        bool: $ => 'bool',
+
+        tpe_spes: $ => seq(field('tpe', $.tpe), optional(field('generic_item', $.generic_item))),
+
+        enum_items: $ => sepBy1(',', $.enum_item),
+
+        enum_item: $ => field('ident', $.identifier),
+
+        generic_items: $ => sepBy1(',', $.generic_item),
+
+        source_items: $ => repeat1(choice($.rs_struct, $.rs_enum, $.rs_trait)),
+
+        struct_members: $ => sepBy1(',', $.struct_member),
+
+        struct_member: $ => seq(optional(field('public', 'pub')), field('ident', $.identifier), ':', field('tpe_spes', $.tpe_spes)),
+
+        identifier: $ => /[_]*[a-zA-Z][a-zA-Z0-9_]*/,
+    }
+});
+
+function sepBy1(sep, rule) {
+    return seq(rule, repeat(seq(sep, rule)));
+}
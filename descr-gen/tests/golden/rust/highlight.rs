@@ -0,0 +1,29 @@
+use descr_common::cst::{CstError, Label};
+use descr_common::highlight::{self, HighlightToken};
+use super::cst::{self, SyntaxKind};
+
+/// Scope of token by the kind of its parent,
+/// its label and its kind
+pub fn scope(parent: SyntaxKind, label: Label, kind: SyntaxKind) -> Option<&'static str> {
+    match (parent, label, kind) {
+        (_, _, SyntaxKind::INT) => Some("constant.numeric"),
+        (_, _, SyntaxKind::STRING) => Some("string.quoted.double"),
+        (_, _, SyntaxKind::STRING_KW) => Some("keyword.other"),
+        (_, _, SyntaxKind::BOOL_KW) => Some("keyword.other"),
+        (_, _, SyntaxKind::ENUM_KW) => Some("keyword.other"),
+        (_, _, SyntaxKind::I32_KW) => Some("keyword.other"),
+        (_, _, SyntaxKind::PUB_KW) => Some("keyword.other"),
+        (_, _, SyntaxKind::STR_KW) => Some("keyword.other"),
+        (_, _, SyntaxKind::STRUCT_KW) => Some("keyword.other"),
+        (_, _, SyntaxKind::TRAIT_KW) => Some("keyword.other"),
+        (_, _, SyntaxKind::U32_KW) => Some("keyword.other"),
+        _ => None,
+    }
+}
+
+/// Highlighted tokens of input, render with
+/// descr_common::highlight::{to_html, to_ansi}
+pub fn highlight(input: &[u8]) -> Result<Vec<HighlightToken>, CstError> {
+    let root = cst::parse(input)?;
+    Ok(highlight::cst_tokens(&root, scope))
+}
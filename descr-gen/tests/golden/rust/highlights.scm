@@ -0,0 +1,15 @@
+[
+    "'"
+    "<"
+    ">"
+    "String"
+    "bool"
+    "enum"
+    "i32"
+    "pub"
+    "str"
+    "struct"
+    "trait"
+    "u32"
+] @keyword.other
+(identifier) @variable.other
@@ -0,0 +1,240 @@
+#![allow(unused_variables)]
+#[allow(unused_imports)]
+use descr_common::cursor::*;
+use super::ast::*;
+
+/// Parses all of input, trailing
+/// whitespace is allowed
+pub fn parse<'a>(input: &'a [u8]) -> Result<Source<'a>, ParseError> {
+    ::descr_common::cursor::parse(input, start)
+}
+
+pub fn start<'a>(c: &mut Cursor<'a>) -> PResult<Source<'a>> {
+    source(c)
+}
+
+pub fn generic<'a>(c: &mut Cursor<'a>) -> PResult<Generic<'a>> {
+    c.sp();
+    c.tag("<")?;
+    c.sp();
+    let generic_items_k = generic_items(c)?;
+    c.sp();
+    c.tag(">")?;
+    Ok(Generic {
+        generic_items: generic_items_k,
+    })
+}
+
+pub fn generic_item<'a>(c: &mut Cursor<'a>) -> PResult<GenericItem<'a>> {
+    if let Some(res) = c.attempt(|c| life_time(c).map(|node| GenericItem::LifeTimeItem(node))) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        let ident_k = c.ident()?;
+        Ok(GenericItem::GenTypeItem(GenType {
+            ident: ident_k,
+        }))
+    }) {
+        return Ok(res);
+    }
+    c.fail()
+}
+
+pub fn life_time<'a>(c: &mut Cursor<'a>) -> PResult<LifeTime<'a>> {
+    c.sp();
+    c.tag("'")?;
+    c.sp();
+    let ident_k = c.ident()?;
+    Ok(LifeTime {
+        ident: ident_k,
+    })
+}
+
+pub fn rs_enum<'a>(c: &mut Cursor<'a>) -> PResult<RsEnum<'a>> {
+    let public_k = c.opt(|c| {
+        c.sp();
+        c.tag("pub")
+    })?;
+    c.sp();
+    c.tag("enum")?;
+    c.sp();
+    let ident_k = c.ident()?;
+    c.sp();
+    c.chr('{')?;
+    c.sp();
+    let enum_items_k = enum_items(c)?;
+    c.sp();
+    c.chr('}')?;
+    Ok(RsEnum {
+        public: public_k.is_some(),
+        ident: ident_k,
+        enum_items: enum_items_k,
+    })
+}
+
+pub fn rs_struct<'a>(c: &mut Cursor<'a>) -> PResult<RsStruct<'a>> {
+    let public_k = c.opt(|c| {
+        c.sp();
+        c.tag("pub")
+    })?;
+    c.sp();
+    c.tag("struct")?;
+    c.sp();
+    let ident_k = c.ident()?;
+    let generic_k = c.opt(|c| {
+        c.sp();
+        generic(c)
+    })?;
+    c.sp();
+    c.chr('{')?;
+    c.sp();
+    let struct_members_k = struct_members(c)?;
+    c.sp();
+    c.chr('}')?;
+    Ok(RsStruct {
+        public: public_k.is_some(),
+        ident: ident_k,
+        generic: generic_k,
+        struct_members: struct_members_k,
+    })
+}
+
+pub fn rs_trait<'a>(c: &mut Cursor<'a>) -> PResult<RsTrait<'a>> {
+    let public_k = c.opt(|c| {
+        c.sp();
+        c.tag("pub")
+    })?;
+    c.sp();
+    c.tag("trait")?;
+    c.sp();
+    let ident_k = c.ident()?;
+    c.sp();
+    c.chr('{')?;
+    c.sp();
+    c.chr('}')?;
+    Ok(RsTrait {
+        public: public_k.is_some(),
+        ident: ident_k,
+    })
+}
+
+pub fn source<'a>(c: &mut Cursor<'a>) -> PResult<Source<'a>> {
+    c.sp();
+    let source_items_k = source_items(c)?;
+    Ok(Source {
+        source_items: source_items_k,
+    })
+}
+
+pub fn tpe<'a>(c: &mut Cursor<'a>) -> PResult<Tpe> {
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        c.tag("u32")?;
+        Ok(Tpe::RsU32)
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        c.tag("i32")?;
+        Ok(Tpe::RsI32)
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        c.tag("String")?;
+        Ok(Tpe::RsString)
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        c.tag("str")?;
+        Ok(Tpe::RsStr)
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        c.tag("bool")?;
+        Ok(Tpe::Bool)
+    }) {
+        return Ok(res);
+    }
+    c.fail()
+}
+
+pub fn tpe_spes<'a>(c: &mut Cursor<'a>) -> PResult<TpeSpes<'a>> {
+    c.sp();
+    let tpe_k = tpe(c)?;
+    let generic_item_k = c.opt(|c| {
+        c.sp();
+        generic_item(c)
+    })?;
+    Ok(TpeSpes {
+        tpe: tpe_k,
+        generic_item: generic_item_k,
+    })
+}
+
+pub fn enum_items<'a>(c: &mut Cursor<'a>) -> PResult<Vec<EnumItem<'a>>> {
+    c.separated_list(|c| c.chr(','), enum_items_item)
+}
+
+fn enum_items_item<'a>(c: &mut Cursor<'a>) -> PResult<EnumItem<'a>> {
+    c.sp();
+    let ident_k = c.ident()?;
+    Ok(EnumItem {
+        ident: ident_k,
+    })
+}
+
+pub fn generic_items<'a>(c: &mut Cursor<'a>) -> PResult<Vec<GenericItem<'a>>> {
+    c.separated_list(|c| c.chr(','), generic_items_item)
+}
+
+fn generic_items_item<'a>(c: &mut Cursor<'a>) -> PResult<GenericItem<'a>> {
+    generic_item(c)
+}
+
+pub fn source_items<'a>(c: &mut Cursor<'a>) -> PResult<Vec<SourceItem<'a>>> {
+    c.many0(source_items_item)
+}
+
+fn source_items_item<'a>(c: &mut Cursor<'a>) -> PResult<SourceItem<'a>> {
+    if let Some(res) = c.attempt(|c| rs_struct(c).map(|node| SourceItem::RsStructItem(node))) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| rs_enum(c).map(|node| SourceItem::RsEnumItem(node))) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| rs_trait(c).map(|node| SourceItem::RsTraitItem(node))) {
+        return Ok(res);
+    }
+    c.fail()
+}
+
+pub fn struct_members<'a>(c: &mut Cursor<'a>) -> PResult<Vec<StructMember<'a>>> {
+    c.separated_list(|c| c.chr(','), struct_members_item)
+}
+
+fn struct_members_item<'a>(c: &mut Cursor<'a>) -> PResult<StructMember<'a>> {
+    let public_k = c.opt(|c| {
+        c.sp();
+        c.tag("pub")
+    })?;
+    c.sp();
+    let ident_k = c.ident()?;
+    c.sp();
+    c.chr(':')?;
+    c.sp();
+    let tpe_spes_k = tpe_spes(c)?;
+    Ok(StructMember {
+        public: public_k.is_some(),
+        ident: ident_k,
+        tpe_spes: tpe_spes_k,
+    })
+}
+
@@ -0,0 +1,179 @@
+use super::ast::*;
+
+pub struct ToSource;
+#[allow(unused_variables,dead_code)]
+impl<'a> ToSource {
+    pub fn enum_item(mut s: String, node: &'a EnumItem) -> String {
+        s += " ";
+        s += node.ident;
+        s
+    }
+
+    pub fn gen_type(mut s: String, node: &'a GenType) -> String {
+        s += " ";
+        s += node.ident;
+        s
+    }
+
+    pub fn generic(mut s: String, node: &'a Generic) -> String {
+        s += " ";
+        s += "<";
+        s += " ";
+        let len = node.generic_items.len();
+        for (i, item) in node.generic_items.iter().enumerate() {
+            s = Self::generic_item(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s.push(',');
+            }
+        }
+        s += " ";
+        s += ">";
+        s
+    }
+
+    pub fn life_time(mut s: String, node: &'a LifeTime) -> String {
+        s += " ";
+        s += "'";
+        s += " ";
+        s += node.ident;
+        s
+    }
+
+    pub fn rs_enum(mut s: String, node: &'a RsEnum) -> String {
+        s += " ";
+        if node.public { s += "pub"; }
+        s += " ";
+        s += "enum";
+        s += " ";
+        s += node.ident;
+        s += " ";
+        s.push('{');
+        s += " ";
+        let len = node.enum_items.len();
+        for (i, item) in node.enum_items.iter().enumerate() {
+            s = Self::enum_item(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s.push(',');
+            }
+        }
+        s += " ";
+        s.push('}');
+        s
+    }
+
+    pub fn rs_struct(mut s: String, node: &'a RsStruct) -> String {
+        s += " ";
+        if node.public { s += "pub"; }
+        s += " ";
+        s += "struct";
+        s += " ";
+        s += node.ident;
+        s += " ";
+        if let Some(ref some_val) = node.generic {
+            s = Self::generic(s, some_val);
+        }
+        s += " ";
+        s.push('{');
+        s += " ";
+        let len = node.struct_members.len();
+        for (i, item) in node.struct_members.iter().enumerate() {
+            s = Self::struct_member(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s.push(',');
+            }
+        }
+        s += " ";
+        s.push('}');
+        s
+    }
+
+    pub fn rs_trait(mut s: String, node: &'a RsTrait) -> String {
+        s += " ";
+        if node.public { s += "pub"; }
+        s += " ";
+        s += "trait";
+        s += " ";
+        s += node.ident;
+        s += " ";
+        s.push('{');
+        s += " ";
+        s.push('}');
+        s
+    }
+
+    pub fn source(mut s: String, node: &'a Source) -> String {
+        s += " ";
+        let len = node.source_items.len();
+        for (i, item) in node.source_items.iter().enumerate() {
+            s = Self::source_item(s, item);
+        }
+        s
+    }
+
+    pub fn struct_member(mut s: String, node: &'a StructMember) -> String {
+        s += " ";
+        if node.public { s += "pub"; }
+        s += " ";
+        s += node.ident;
+        s += " ";
+        s.push(':');
+        s += " ";
+        s = Self::tpe_spes(s, &node.tpe_spes);
+        s
+    }
+
+    pub fn tpe_spes(mut s: String, node: &'a TpeSpes) -> String {
+        s += " ";
+        s = Self::tpe(s, &node.tpe);
+        s += " ";
+        if let Some(ref some_val) = node.generic_item {
+            s = Self::generic_item(s, some_val);
+        }
+        s
+    }
+
+    pub fn generic_item(s: String, node: &'a GenericItem) -> String {
+        match node {
+            &GenericItem::LifeTimeItem(ref inner) => Self::life_time(s, inner),
+            &GenericItem::GenTypeItem(ref inner) => Self::gen_type(s, inner),
+        }
+    }
+
+    pub fn source_item(s: String, node: &'a SourceItem) -> String {
+        match node {
+            &SourceItem::RsStructItem(ref inner) => Self::rs_struct(s, inner),
+            &SourceItem::RsEnumItem(ref inner) => Self::rs_enum(s, inner),
+            &SourceItem::RsTraitItem(ref inner) => Self::rs_trait(s, inner),
+        }
+    }
+
+    pub fn tpe(mut s: String, node: &'a Tpe) -> String {
+        match node {
+            &Tpe::RsU32 => {
+                s += " ";
+        s += "u32";
+            },
+            &Tpe::RsI32 => {
+                s += " ";
+        s += "i32";
+            },
+            &Tpe::RsString => {
+                s += " ";
+        s += "String";
+            },
+            &Tpe::RsStr => {
+                s += " ";
+        s += "str";
+            },
+            &Tpe::Bool => {
+                s += " ";
+        s += "bool";
+            },
+        }
+        s
+    }
+
+}
\ No newline at end of file
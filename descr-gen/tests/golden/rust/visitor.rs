@@ -1,5 +1,6 @@
 use super::ast::*;
 
+#[allow(unused_variables,dead_code)]
 pub trait Visitor<'a> {
     fn visit_enum_item(&mut self, node: &'a EnumItem) {
     }
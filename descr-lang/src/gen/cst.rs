@@ -0,0 +1,1331 @@
+#[allow(unused_imports)]
+use descr_common::cst::*;
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    ROOT,
+    WHITESPACE,
+    TEXT,
+    IDENT,
+    INT,
+    STRING,
+    /// !
+    EXCL,
+    /// (
+    LPAREN,
+    /// (*
+    LPAREN_STAR,
+    /// )
+    RPAREN,
+    /// *)
+    STAR_RPAREN,
+    /// ,
+    COMMA,
+    /// :
+    COLON,
+    /// =
+    EQUAL,
+    /// =>
+    EQUAL_GT,
+    /// ?
+    QUESTION,
+    /// @
+    AT,
+    /// [
+    LBRACKET,
+    /// ]
+    RBRACKET,
+    /// {
+    LBRACE,
+    /// }
+    RBRACE,
+    AnnotArg,
+    AnnotArgList,
+    AnnotArgVal,
+    AnnotArgs,
+    Annotation,
+    Annots,
+    AstDef,
+    AstItem,
+    AstItems,
+    AstMany,
+    AstRef,
+    AstSingle,
+    Comment,
+    FnArgs,
+    FuncToken,
+    Ident,
+    IntConst,
+    KeyToken,
+    List,
+    ListItem,
+    ListItems,
+    ListMany,
+    ListSingle,
+    NamedToken,
+    Quoted,
+    SimpleToken,
+    Source,
+    SourceItems,
+    TokenGroup,
+    TokenList,
+    TokenType,
+}
+
+impl CstKind for SyntaxKind {
+    fn root() -> SyntaxKind {
+        SyntaxKind::ROOT
+    }
+    fn whitespace() -> SyntaxKind {
+        SyntaxKind::WHITESPACE
+    }
+    fn text() -> SyntaxKind {
+        SyntaxKind::TEXT
+    }
+}
+
+pub type Node = SyntaxNode<SyntaxKind>;
+
+/// Lossless tree of input. The root has the
+/// start node and trailing whitespace.
+pub fn parse(input: &[u8]) -> Result<Node, CstError> {
+    ::descr_common::cst::parse(input, |c| source(c, None))
+}
+
+pub fn annot_arg_val(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    if c.attempt(|c| c.node(SyntaxKind::Quoted, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::STRING, Some("string"), |c| c.string())?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::Ident, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::IntConst, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::INT, Some("int"), |c| c.int())?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    Err(Fail::Error)
+}
+
+pub fn annot_args(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::AnnotArgs, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::LPAREN, None, |c| c.chr('('))?;
+        c.sp();
+        annot_arg_list(c, Some("annot_arg_list"))?;
+        c.sp();
+        c.token(SyntaxKind::RPAREN, None, |c| c.chr(')'))?;
+        Ok(())
+    })
+}
+
+pub fn annotation(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::Annotation, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::AT, None, |c| c.tag("@"))?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        c.opt(|c| {
+            c.sp();
+            annot_args(c, Some("annot_args"))
+        })?;
+        Ok(())
+    })
+}
+
+pub fn ast_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    if c.attempt(|c| c.node(SyntaxKind::AstDef, label, |c| {
+        c.sp();
+        annots(c, Some("annots"))?;
+        c.sp();
+        token_list(c, Some("tokens"))?;
+        c.sp();
+        c.token(SyntaxKind::EQUAL_GT, None, |c| c.tag("=>"))?;
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())
+        })?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::AstDef, label, |c| {
+        c.sp();
+        annots(c, Some("annots"))?;
+        c.sp();
+        c.token(SyntaxKind::LPAREN, None, |c| c.chr('('))?;
+        c.sp();
+        token_list(c, Some("tokens"))?;
+        c.sp();
+        c.token(SyntaxKind::RPAREN, None, |c| c.chr(')'))?;
+        c.sp();
+        c.token(SyntaxKind::EQUAL_GT, None, |c| c.tag("=>"))?;
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())
+        })?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::AstDef, label, |c| {
+        c.sp();
+        annots(c, Some("annots"))?;
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())
+        })?;
+        c.sp();
+        c.token(SyntaxKind::LPAREN, None, |c| c.chr('('))?;
+        c.sp();
+        token_list(c, Some("tokens"))?;
+        c.sp();
+        c.token(SyntaxKind::RPAREN, None, |c| c.chr(')'))?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::AstRef, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    Err(Fail::Error)
+}
+
+pub fn ast_many(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::AstMany, label, |c| {
+        c.sp();
+        annots(c, Some("annots"))?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        c.sp();
+        c.token(SyntaxKind::LBRACE, None, |c| c.chr('{'))?;
+        c.sp();
+        ast_items(c, Some("items"))?;
+        c.sp();
+        c.token(SyntaxKind::RBRACE, None, |c| c.chr('}'))?;
+        Ok(())
+    })
+}
+
+pub fn ast_single(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::AstSingle, label, |c| {
+        c.sp();
+        annots(c, Some("annots"))?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        c.sp();
+        c.token(SyntaxKind::LPAREN, None, |c| c.chr('('))?;
+        c.sp();
+        token_list(c, Some("tokens"))?;
+        c.sp();
+        c.token(SyntaxKind::RPAREN, None, |c| c.chr(')'))?;
+        Ok(())
+    })
+}
+
+pub fn comment(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::Comment, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::LPAREN_STAR, None, |c| c.tag("(*"))?;
+        c.token(SyntaxKind::TEXT, Some("comment"), |c| c.until(|c| c.token(SyntaxKind::STAR_RPAREN, None, |c| c.tag("*)"))))?;
+        c.sp();
+        c.token(SyntaxKind::STAR_RPAREN, None, |c| c.tag("*)"))?;
+        Ok(())
+    })
+}
+
+pub fn list(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    if c.attempt(|c| c.node(SyntaxKind::ListSingle, label, |c| {
+        c.sp();
+        annots(c, Some("annots"))?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        c.sp();
+        c.token(SyntaxKind::LBRACKET, None, |c| c.chr('['))?;
+        c.sp();
+        c.token(SyntaxKind::RBRACKET, None, |c| c.chr(']'))?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("sep"), |c| c.ident())?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("reference"), |c| c.ident())?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::ListMany, label, |c| {
+        c.sp();
+        annots(c, Some("annots"))?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        c.sp();
+        c.token(SyntaxKind::COLON, None, |c| c.chr(':'))?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ast_type"), |c| c.ident())?;
+        c.sp();
+        c.token(SyntaxKind::LBRACKET, None, |c| c.chr('['))?;
+        c.sp();
+        c.token(SyntaxKind::RBRACKET, None, |c| c.chr(']'))?;
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::IDENT, Some("sep"), |c| c.ident())
+        })?;
+        c.sp();
+        c.token(SyntaxKind::LBRACE, None, |c| c.chr('{'))?;
+        c.sp();
+        list_items(c, Some("items"))?;
+        c.sp();
+        c.token(SyntaxKind::RBRACE, None, |c| c.chr('}'))?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    Err(Fail::Error)
+}
+
+pub fn list_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::ListItem, label, |c| {
+        c.sp();
+        ast_item(c, Some("ast_item"))?;
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::IDENT, Some("sep"), |c| c.ident())
+        })?;
+        Ok(())
+    })
+}
+
+pub fn source(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::Source, label, |c| {
+        c.sp();
+        source_items(c, Some("items"))?;
+        Ok(())
+    })
+}
+
+pub fn token_type(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    if c.attempt(|c| c.node(SyntaxKind::FuncToken, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("ident"), |c| c.ident())?;
+        c.sp();
+        c.token(SyntaxKind::LPAREN, None, |c| c.chr('('))?;
+        c.sp();
+        fn_args(c, Some("fn_args"))?;
+        c.sp();
+        c.token(SyntaxKind::RPAREN, None, |c| c.chr(')'))?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::KeyToken, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("key"), |c| c.ident())?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::Quoted, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::STRING, Some("string"), |c| c.string())?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    Err(Fail::Error)
+}
+
+pub fn annot_arg_list(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::AnnotArgList, label, |c| c.separated_list(|c| c.token(SyntaxKind::COMMA, None, |c| c.chr(',')), |c| annot_arg_list_item(c, None)))
+}
+
+fn annot_arg_list_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::AnnotArg, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("key"), |c| c.ident())?;
+        c.sp();
+        c.token(SyntaxKind::EQUAL, None, |c| c.chr('='))?;
+        c.sp();
+        annot_arg_val(c, Some("annot_arg_val"))?;
+        Ok(())
+    })
+}
+
+pub fn annots(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::Annots, label, |c| c.many0(|c| annots_item(c, None)))
+}
+
+fn annots_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    annotation(c, label)
+}
+
+pub fn ast_items(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::AstItems, label, |c| c.separated_list(|c| c.token(SyntaxKind::COMMA, None, |c| c.chr(',')), |c| ast_items_item(c, None)))
+}
+
+fn ast_items_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    ast_item(c, label)
+}
+
+pub fn fn_args(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::FnArgs, label, |c| c.separated_list(|c| c.token(SyntaxKind::COMMA, None, |c| c.chr(',')), |c| fn_args_item(c, None)))
+}
+
+fn fn_args_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::Quoted, label, |c| {
+        c.sp();
+        c.token(SyntaxKind::STRING, Some("string"), |c| c.string())?;
+        Ok(())
+    })
+}
+
+pub fn list_items(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::ListItems, label, |c| c.separated_list(|c| c.token(SyntaxKind::COMMA, None, |c| c.chr(',')), |c| list_items_item(c, None)))
+}
+
+fn list_items_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    list_item(c, label)
+}
+
+pub fn source_items(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::SourceItems, label, |c| c.many0(|c| source_items_item(c, None)))
+}
+
+fn source_items_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    if c.attempt(|c| ast_single(c, label)).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| ast_many(c, label)).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| list(c, label)).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| comment(c, label)).is_some() {
+        return Ok(());
+    }
+    Err(Fail::Error)
+}
+
+pub fn token_list(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    c.node(SyntaxKind::TokenList, label, |c| c.many0(|c| token_list_item(c, None)))
+}
+
+fn token_list_item(c: &mut Cursor<SyntaxKind>, label: Label) -> Res {
+    if c.attempt(|c| c.node(SyntaxKind::NamedToken, label, |c| {
+        c.sp();
+        annots(c, Some("annots"))?;
+        c.sp();
+        c.token(SyntaxKind::IDENT, Some("name"), |c| c.ident())?;
+        c.sp();
+        c.token(SyntaxKind::COLON, None, |c| c.chr(':'))?;
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::EXCL, Some("not"), |c| c.chr('!'))
+        })?;
+        c.sp();
+        token_type(c, Some("token_type"))?;
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::QUESTION, Some("optional"), |c| c.chr('?'))
+        })?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::SimpleToken, label, |c| {
+        c.sp();
+        annots(c, Some("annots"))?;
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::EXCL, Some("not"), |c| c.chr('!'))
+        })?;
+        c.sp();
+        token_type(c, Some("token_type"))?;
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::QUESTION, Some("optional"), |c| c.chr('?'))
+        })?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    if c.attempt(|c| c.node(SyntaxKind::TokenGroup, label, |c| {
+        c.sp();
+        annots(c, Some("annots"))?;
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::EXCL, Some("not"), |c| c.chr('!'))
+        })?;
+        c.sp();
+        c.token(SyntaxKind::LPAREN, None, |c| c.chr('('))?;
+        c.sp();
+        token_list(c, Some("token_list"))?;
+        c.sp();
+        c.token(SyntaxKind::RPAREN, None, |c| c.chr(')'))?;
+        c.opt(|c| {
+            c.sp();
+            c.token(SyntaxKind::QUESTION, Some("optional"), |c| c.chr('?'))
+        })?;
+        Ok(())
+    })).is_some() {
+        return Ok(());
+    }
+    Err(Fail::Error)
+}
+
+#[derive(Debug, Clone)]
+pub struct AnnotArgNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl AnnotArgNode {
+    pub fn cast(syntax: Node) -> Option<AnnotArgNode> {
+        if syntax.kind() == SyntaxKind::AnnotArg {
+            Some(AnnotArgNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn key(&self) -> Option<String> {
+        self.syntax.child_token("key").map(|token| token.text().to_string())
+    }
+
+    pub fn annot_arg_val(&self) -> Option<AnnotArgValNode> {
+        self.syntax.child_node("annot_arg_val").and_then(AnnotArgValNode::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnnotArgsNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl AnnotArgsNode {
+    pub fn cast(syntax: Node) -> Option<AnnotArgsNode> {
+        if syntax.kind() == SyntaxKind::AnnotArgs {
+            Some(AnnotArgsNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn annot_arg_list(&self) -> Vec<AnnotArgNode> {
+        match self.syntax.child_node("annot_arg_list") {
+            Some(list) => list.child_nodes().into_iter().filter_map(AnnotArgNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnnotationNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl AnnotationNode {
+    pub fn cast(syntax: Node) -> Option<AnnotationNode> {
+        if syntax.kind() == SyntaxKind::Annotation {
+            Some(AnnotationNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+
+    pub fn annot_args(&self) -> Option<AnnotArgsNode> {
+        self.syntax.child_node("annot_args").and_then(AnnotArgsNode::cast)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AstDefNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl AstDefNode {
+    pub fn cast(syntax: Node) -> Option<AstDefNode> {
+        if syntax.kind() == SyntaxKind::AstDef {
+            Some(AstDefNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn annots(&self) -> Vec<AnnotationNode> {
+        match self.syntax.child_node("annots") {
+            Some(list) => list.child_nodes().into_iter().filter_map(AnnotationNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn tokens(&self) -> Vec<TokenNode> {
+        match self.syntax.child_node("tokens") {
+            Some(list) => list.child_nodes().into_iter().filter_map(TokenNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AstManyNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl AstManyNode {
+    pub fn cast(syntax: Node) -> Option<AstManyNode> {
+        if syntax.kind() == SyntaxKind::AstMany {
+            Some(AstManyNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn annots(&self) -> Vec<AnnotationNode> {
+        match self.syntax.child_node("annots") {
+            Some(list) => list.child_nodes().into_iter().filter_map(AnnotationNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+
+    pub fn items(&self) -> Vec<AstItemNode> {
+        match self.syntax.child_node("items") {
+            Some(list) => list.child_nodes().into_iter().filter_map(AstItemNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AstRefNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl AstRefNode {
+    pub fn cast(syntax: Node) -> Option<AstRefNode> {
+        if syntax.kind() == SyntaxKind::AstRef {
+            Some(AstRefNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AstSingleNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl AstSingleNode {
+    pub fn cast(syntax: Node) -> Option<AstSingleNode> {
+        if syntax.kind() == SyntaxKind::AstSingle {
+            Some(AstSingleNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn annots(&self) -> Vec<AnnotationNode> {
+        match self.syntax.child_node("annots") {
+            Some(list) => list.child_nodes().into_iter().filter_map(AnnotationNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+
+    pub fn tokens(&self) -> Vec<TokenNode> {
+        match self.syntax.child_node("tokens") {
+            Some(list) => list.child_nodes().into_iter().filter_map(TokenNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CommentNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl CommentNode {
+    pub fn cast(syntax: Node) -> Option<CommentNode> {
+        if syntax.kind() == SyntaxKind::Comment {
+            Some(CommentNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn comment(&self) -> Option<String> {
+        self.syntax.child_token("comment").map(|token| token.text().to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FuncTokenNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl FuncTokenNode {
+    pub fn cast(syntax: Node) -> Option<FuncTokenNode> {
+        if syntax.kind() == SyntaxKind::FuncToken {
+            Some(FuncTokenNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+
+    pub fn fn_args(&self) -> Vec<FuncArgNode> {
+        match self.syntax.child_node("fn_args") {
+            Some(list) => list.child_nodes().into_iter().filter_map(FuncArgNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IdentNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl IdentNode {
+    pub fn cast(syntax: Node) -> Option<IdentNode> {
+        if syntax.kind() == SyntaxKind::Ident {
+            Some(IdentNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IntConstNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl IntConstNode {
+    pub fn cast(syntax: Node) -> Option<IntConstNode> {
+        if syntax.kind() == SyntaxKind::IntConst {
+            Some(IntConstNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn int(&self) -> Option<u32> {
+        self.syntax.child_token("int").and_then(|token| token.text().parse().ok())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyTokenNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl KeyTokenNode {
+    pub fn cast(syntax: Node) -> Option<KeyTokenNode> {
+        if syntax.kind() == SyntaxKind::KeyToken {
+            Some(KeyTokenNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn key(&self) -> Option<String> {
+        self.syntax.child_token("key").map(|token| token.text().to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ListItemNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl ListItemNode {
+    pub fn cast(syntax: Node) -> Option<ListItemNode> {
+        if syntax.kind() == SyntaxKind::ListItem {
+            Some(ListItemNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn ast_item(&self) -> Option<AstItemNode> {
+        self.syntax.child_node("ast_item").and_then(AstItemNode::cast)
+    }
+
+    pub fn sep(&self) -> Option<String> {
+        self.syntax.child_token("sep").map(|token| token.text().to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ListManyNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl ListManyNode {
+    pub fn cast(syntax: Node) -> Option<ListManyNode> {
+        if syntax.kind() == SyntaxKind::ListMany {
+            Some(ListManyNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn annots(&self) -> Vec<AnnotationNode> {
+        match self.syntax.child_node("annots") {
+            Some(list) => list.child_nodes().into_iter().filter_map(AnnotationNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+
+    pub fn ast_type(&self) -> Option<String> {
+        self.syntax.child_token("ast_type").map(|token| token.text().to_string())
+    }
+
+    pub fn sep(&self) -> Option<String> {
+        self.syntax.child_token("sep").map(|token| token.text().to_string())
+    }
+
+    pub fn items(&self) -> Vec<ListItemNode> {
+        match self.syntax.child_node("items") {
+            Some(list) => list.child_nodes().into_iter().filter_map(ListItemNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ListSingleNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl ListSingleNode {
+    pub fn cast(syntax: Node) -> Option<ListSingleNode> {
+        if syntax.kind() == SyntaxKind::ListSingle {
+            Some(ListSingleNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn annots(&self) -> Vec<AnnotationNode> {
+        match self.syntax.child_node("annots") {
+            Some(list) => list.child_nodes().into_iter().filter_map(AnnotationNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn ident(&self) -> Option<String> {
+        self.syntax.child_token("ident").map(|token| token.text().to_string())
+    }
+
+    pub fn sep(&self) -> Option<String> {
+        self.syntax.child_token("sep").map(|token| token.text().to_string())
+    }
+
+    pub fn reference(&self) -> Option<String> {
+        self.syntax.child_token("reference").map(|token| token.text().to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NamedTokenNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl NamedTokenNode {
+    pub fn cast(syntax: Node) -> Option<NamedTokenNode> {
+        if syntax.kind() == SyntaxKind::NamedToken {
+            Some(NamedTokenNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn annots(&self) -> Vec<AnnotationNode> {
+        match self.syntax.child_node("annots") {
+            Some(list) => list.child_nodes().into_iter().filter_map(AnnotationNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.syntax.child_token("name").map(|token| token.text().to_string())
+    }
+
+    pub fn not(&self) -> bool {
+        self.syntax.child("not").is_some()
+    }
+
+    pub fn token_type(&self) -> Option<TokenTypeNode> {
+        self.syntax.child_node("token_type").and_then(TokenTypeNode::cast)
+    }
+
+    pub fn optional(&self) -> bool {
+        self.syntax.child("optional").is_some()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QuotedNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl QuotedNode {
+    pub fn cast(syntax: Node) -> Option<QuotedNode> {
+        if syntax.kind() == SyntaxKind::Quoted {
+            Some(QuotedNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    /// String without quotes
+    pub fn string(&self) -> Option<String> {
+        self.syntax.child_token("string").map(|token| {
+            let text = token.text();
+            text[1..text.len() - 1].to_string()
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimpleTokenNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl SimpleTokenNode {
+    pub fn cast(syntax: Node) -> Option<SimpleTokenNode> {
+        if syntax.kind() == SyntaxKind::SimpleToken {
+            Some(SimpleTokenNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn annots(&self) -> Vec<AnnotationNode> {
+        match self.syntax.child_node("annots") {
+            Some(list) => list.child_nodes().into_iter().filter_map(AnnotationNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn not(&self) -> bool {
+        self.syntax.child("not").is_some()
+    }
+
+    pub fn token_type(&self) -> Option<TokenTypeNode> {
+        self.syntax.child_node("token_type").and_then(TokenTypeNode::cast)
+    }
+
+    pub fn optional(&self) -> bool {
+        self.syntax.child("optional").is_some()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl SourceNode {
+    pub fn cast(syntax: Node) -> Option<SourceNode> {
+        if syntax.kind() == SyntaxKind::Source {
+            Some(SourceNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn items(&self) -> Vec<SourceItemNode> {
+        match self.syntax.child_node("items") {
+            Some(list) => list.child_nodes().into_iter().filter_map(SourceItemNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenGroupNode {
+    pub syntax: Node,
+}
+
+#[allow(dead_code)]
+impl TokenGroupNode {
+    pub fn cast(syntax: Node) -> Option<TokenGroupNode> {
+        if syntax.kind() == SyntaxKind::TokenGroup {
+            Some(TokenGroupNode { syntax })
+        } else {
+            None
+        }
+    }
+
+    pub fn syntax(&self) -> &Node {
+        &self.syntax
+    }
+
+    pub fn annots(&self) -> Vec<AnnotationNode> {
+        match self.syntax.child_node("annots") {
+            Some(list) => list.child_nodes().into_iter().filter_map(AnnotationNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn not(&self) -> bool {
+        self.syntax.child("not").is_some()
+    }
+
+    pub fn token_list(&self) -> Vec<TokenNode> {
+        match self.syntax.child_node("token_list") {
+            Some(list) => list.child_nodes().into_iter().filter_map(TokenNode::cast).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn optional(&self) -> bool {
+        self.syntax.child("optional").is_some()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AnnotArgValNode {
+    Quoted(QuotedNode),
+    Ident(IdentNode),
+    IntConst(IntConstNode),
+}
+
+#[allow(dead_code)]
+impl AnnotArgValNode {
+    pub fn cast(syntax: Node) -> Option<AnnotArgValNode> {
+        if let Some(node) = QuotedNode::cast(syntax.clone()) {
+            return Some(AnnotArgValNode::Quoted(node));
+        }
+        if let Some(node) = IdentNode::cast(syntax.clone()) {
+            return Some(AnnotArgValNode::Ident(node));
+        }
+        if let Some(node) = IntConstNode::cast(syntax.clone()) {
+            return Some(AnnotArgValNode::IntConst(node));
+        }
+        None
+    }
+
+    pub fn syntax(&self) -> &Node {
+        match self {
+            &AnnotArgValNode::Quoted(ref node) => node.syntax(),
+            &AnnotArgValNode::Ident(ref node) => node.syntax(),
+            &AnnotArgValNode::IntConst(ref node) => node.syntax(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AstItemNode {
+    AstDef(AstDefNode),
+    AstRef(AstRefNode),
+}
+
+#[allow(dead_code)]
+impl AstItemNode {
+    pub fn cast(syntax: Node) -> Option<AstItemNode> {
+        if let Some(node) = AstDefNode::cast(syntax.clone()) {
+            return Some(AstItemNode::AstDef(node));
+        }
+        if let Some(node) = AstRefNode::cast(syntax.clone()) {
+            return Some(AstItemNode::AstRef(node));
+        }
+        None
+    }
+
+    pub fn syntax(&self) -> &Node {
+        match self {
+            &AstItemNode::AstDef(ref node) => node.syntax(),
+            &AstItemNode::AstRef(ref node) => node.syntax(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum FuncArgNode {
+    Quoted(QuotedNode),
+}
+
+#[allow(dead_code)]
+impl FuncArgNode {
+    pub fn cast(syntax: Node) -> Option<FuncArgNode> {
+        if let Some(node) = QuotedNode::cast(syntax.clone()) {
+            return Some(FuncArgNode::Quoted(node));
+        }
+        None
+    }
+
+    pub fn syntax(&self) -> &Node {
+        match self {
+            &FuncArgNode::Quoted(ref node) => node.syntax(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ListNode {
+    ListSingle(ListSingleNode),
+    ListMany(ListManyNode),
+}
+
+#[allow(dead_code)]
+impl ListNode {
+    pub fn cast(syntax: Node) -> Option<ListNode> {
+        if let Some(node) = ListSingleNode::cast(syntax.clone()) {
+            return Some(ListNode::ListSingle(node));
+        }
+        if let Some(node) = ListManyNode::cast(syntax.clone()) {
+            return Some(ListNode::ListMany(node));
+        }
+        None
+    }
+
+    pub fn syntax(&self) -> &Node {
+        match self {
+            &ListNode::ListSingle(ref node) => node.syntax(),
+            &ListNode::ListMany(ref node) => node.syntax(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SourceItemNode {
+    AstSingle(AstSingleNode),
+    AstMany(AstManyNode),
+    List(ListNode),
+    Comment(CommentNode),
+}
+
+#[allow(dead_code)]
+impl SourceItemNode {
+    pub fn cast(syntax: Node) -> Option<SourceItemNode> {
+        if let Some(node) = AstSingleNode::cast(syntax.clone()) {
+            return Some(SourceItemNode::AstSingle(node));
+        }
+        if let Some(node) = AstManyNode::cast(syntax.clone()) {
+            return Some(SourceItemNode::AstMany(node));
+        }
+        if let Some(node) = ListNode::cast(syntax.clone()) {
+            return Some(SourceItemNode::List(node));
+        }
+        if let Some(node) = CommentNode::cast(syntax.clone()) {
+            return Some(SourceItemNode::Comment(node));
+        }
+        None
+    }
+
+    pub fn syntax(&self) -> &Node {
+        match self {
+            &SourceItemNode::AstSingle(ref node) => node.syntax(),
+            &SourceItemNode::AstMany(ref node) => node.syntax(),
+            &SourceItemNode::List(ref node) => node.syntax(),
+            &SourceItemNode::Comment(ref node) => node.syntax(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TokenNode {
+    NamedToken(NamedTokenNode),
+    SimpleToken(SimpleTokenNode),
+    TokenGroup(TokenGroupNode),
+}
+
+#[allow(dead_code)]
+impl TokenNode {
+    pub fn cast(syntax: Node) -> Option<TokenNode> {
+        if let Some(node) = NamedTokenNode::cast(syntax.clone()) {
+            return Some(TokenNode::NamedToken(node));
+        }
+        if let Some(node) = SimpleTokenNode::cast(syntax.clone()) {
+            return Some(TokenNode::SimpleToken(node));
+        }
+        if let Some(node) = TokenGroupNode::cast(syntax.clone()) {
+            return Some(TokenNode::TokenGroup(node));
+        }
+        None
+    }
+
+    pub fn syntax(&self) -> &Node {
+        match self {
+            &TokenNode::NamedToken(ref node) => node.syntax(),
+            &TokenNode::SimpleToken(ref node) => node.syntax(),
+            &TokenNode::TokenGroup(ref node) => node.syntax(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TokenTypeNode {
+    FuncToken(FuncTokenNode),
+    KeyToken(KeyTokenNode),
+    Quoted(QuotedNode),
+}
+
+#[allow(dead_code)]
+impl TokenTypeNode {
+    pub fn cast(syntax: Node) -> Option<TokenTypeNode> {
+        if let Some(node) = FuncTokenNode::cast(syntax.clone()) {
+            return Some(TokenTypeNode::FuncToken(node));
+        }
+        if let Some(node) = KeyTokenNode::cast(syntax.clone()) {
+            return Some(TokenTypeNode::KeyToken(node));
+        }
+        if let Some(node) = QuotedNode::cast(syntax.clone()) {
+            return Some(TokenTypeNode::Quoted(node));
+        }
+        None
+    }
+
+    pub fn syntax(&self) -> &Node {
+        match self {
+            &TokenTypeNode::FuncToken(ref node) => node.syntax(),
+            &TokenTypeNode::KeyToken(ref node) => node.syntax(),
+            &TokenTypeNode::Quoted(ref node) => node.syntax(),
+        }
+    }
+}
+
@@ -0,0 +1,36 @@
+use descr_common::cst::{CstError, Label};
+use descr_common::highlight::{self, HighlightToken};
+use super::cst::{self, SyntaxKind};
+
+/// Scope of token by the kind of its parent,
+/// its label and its kind
+pub fn scope(parent: SyntaxKind, label: Label, kind: SyntaxKind) -> Option<&'static str> {
+    match (parent, label, kind) {
+        (SyntaxKind::Annotation, None, SyntaxKind::AT) => Some("keyword.control"),
+        (SyntaxKind::Annotation, Some("ident"), SyntaxKind::IDENT) => Some("entity.name.function"),
+        (SyntaxKind::AstDef, Some("ident"), SyntaxKind::IDENT) => Some("entity.name.type"),
+        (SyntaxKind::AstMany, Some("ident"), SyntaxKind::IDENT) => Some("entity.name.type"),
+        (SyntaxKind::AstSingle, Some("ident"), SyntaxKind::IDENT) => Some("entity.name.type"),
+        (SyntaxKind::ListSingle, Some("ident"), SyntaxKind::IDENT) => Some("entity.name.type"),
+        (SyntaxKind::ListMany, Some("ident"), SyntaxKind::IDENT) => Some("entity.name.function"),
+        (SyntaxKind::ListMany, Some("ast_type"), SyntaxKind::IDENT) => Some("entity.name.type"),
+        (SyntaxKind::KeyToken, Some("key"), SyntaxKind::IDENT) => Some("storage.type"),
+        (SyntaxKind::NamedToken, Some("name"), SyntaxKind::IDENT) => Some("variable.name"),
+        (SyntaxKind::NamedToken, Some("not"), SyntaxKind::EXCL) => Some("keyword.control"),
+        (SyntaxKind::NamedToken, Some("optional"), SyntaxKind::QUESTION) => Some("keyword.control"),
+        (SyntaxKind::SimpleToken, Some("not"), SyntaxKind::EXCL) => Some("keyword.control"),
+        (SyntaxKind::SimpleToken, Some("optional"), SyntaxKind::QUESTION) => Some("keyword.control"),
+        (SyntaxKind::TokenGroup, Some("not"), SyntaxKind::EXCL) => Some("keyword.control"),
+        (SyntaxKind::TokenGroup, Some("optional"), SyntaxKind::QUESTION) => Some("keyword.control"),
+        (_, _, SyntaxKind::INT) => Some("constant.numeric"),
+        (_, _, SyntaxKind::STRING) => Some("string.quoted.double"),
+        _ => None,
+    }
+}
+
+/// Highlighted tokens of input, render with
+/// descr_common::highlight::{to_html, to_ansi}
+pub fn highlight(input: &[u8]) -> Result<Vec<HighlightToken>, CstError> {
+    let root = cst::parse(input)?;
+    Ok(highlight::cst_tokens(&root, scope))
+}
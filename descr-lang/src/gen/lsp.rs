@@ -0,0 +1,54 @@
+use descr_lsp::LspLanguage;
+use descr_common::cst::{CstError, Label};
+use super::cst::{self, Node, SyntaxKind};
+
+/// Language server features from the grammar,
+/// serve with descr_lsp::run_stdio(Lsp)
+pub struct Lsp;
+
+impl LspLanguage for Lsp {
+    type Kind = SyntaxKind;
+
+    fn name(&self) -> &'static str {
+        "descr"
+    }
+
+    fn parse(&self, input: &[u8]) -> Result<Node, CstError> {
+        cst::parse(input)
+    }
+
+    fn symbol(&self, kind: SyntaxKind) -> Option<(&'static str, Label)> {
+        match kind {
+            SyntaxKind::AstDef => Some(("enum_member", Some("ident"))),
+            SyntaxKind::AstMany => Some(("enum", Some("ident"))),
+            SyntaxKind::AstSingle => Some(("struct", Some("ident"))),
+            SyntaxKind::ListMany => Some(("array", Some("ident"))),
+            SyntaxKind::ListSingle => Some(("array", Some("ident"))),
+            _ => None,
+        }
+    }
+
+    fn scope(&self, parent: SyntaxKind, label: Label, kind: SyntaxKind) -> Option<&'static str> {
+        match (parent, label, kind) {
+            (SyntaxKind::Annotation, None, SyntaxKind::AT) => Some("keyword.control"),
+            (SyntaxKind::Annotation, Some("ident"), SyntaxKind::IDENT) => Some("entity.name.function"),
+            (SyntaxKind::AstDef, Some("ident"), SyntaxKind::IDENT) => Some("entity.name.type"),
+            (SyntaxKind::AstMany, Some("ident"), SyntaxKind::IDENT) => Some("entity.name.type"),
+            (SyntaxKind::AstSingle, Some("ident"), SyntaxKind::IDENT) => Some("entity.name.type"),
+            (SyntaxKind::ListSingle, Some("ident"), SyntaxKind::IDENT) => Some("entity.name.type"),
+            (SyntaxKind::ListMany, Some("ident"), SyntaxKind::IDENT) => Some("entity.name.function"),
+            (SyntaxKind::ListMany, Some("ast_type"), SyntaxKind::IDENT) => Some("entity.name.type"),
+            (SyntaxKind::KeyToken, Some("key"), SyntaxKind::IDENT) => Some("storage.type"),
+            (SyntaxKind::NamedToken, Some("name"), SyntaxKind::IDENT) => Some("variable.name"),
+            (SyntaxKind::NamedToken, Some("not"), SyntaxKind::EXCL) => Some("keyword.control"),
+            (SyntaxKind::NamedToken, Some("optional"), SyntaxKind::QUESTION) => Some("keyword.control"),
+            (SyntaxKind::SimpleToken, Some("not"), SyntaxKind::EXCL) => Some("keyword.control"),
+            (SyntaxKind::SimpleToken, Some("optional"), SyntaxKind::QUESTION) => Some("keyword.control"),
+            (SyntaxKind::TokenGroup, Some("not"), SyntaxKind::EXCL) => Some("keyword.control"),
+            (SyntaxKind::TokenGroup, Some("optional"), SyntaxKind::QUESTION) => Some("keyword.control"),
+            (_, _, SyntaxKind::INT) => Some("constant.numeric"),
+            (_, _, SyntaxKind::STRING) => Some("string.quoted.double"),
+            _ => None,
+        }
+    }
+}
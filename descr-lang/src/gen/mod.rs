@@ -2,4 +2,8 @@ pub mod ast;
 pub mod parsers;
 pub mod visitor;
 pub mod to_source;
+pub mod cst;
+pub mod highlight;
+#[cfg(feature = "lsp")]
+pub mod lsp;
 
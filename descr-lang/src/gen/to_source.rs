@@ -3,147 +3,176 @@ use super::ast::*;
 pub struct ToSource;
 #[allow(unused_variables,dead_code)]
 impl<'a> ToSource {
-    pub fn quoted(mut s: String, node: &'a Quoted) -> String {
+    pub fn annot_arg(mut s: String, node: &'a AnnotArg) -> String {
         s += " ";
-        s += "\"";
-        s += node.string;
-        s += "\"";
+        s += node.key;
         s += " ";
-        s += "\"";
-        s += node.string;
-        s += "\"";
+        s.push('=');
         s += " ";
-        s += "\"";
-        s += node.string;
-        s += "\"";
+        s = Self::annot_arg_val(s, &node.annot_arg_val);
         s
     }
 
-    pub fn list_many(mut s: String, node: &'a ListMany) -> String {
-        s += " ";
-        let len = node.annots.len();
-        for (i, item) in node.annots.iter().enumerate() {
-            s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
-        }
-        s += " ";
-        s += node.ident;
-        s += " ";
-        s.push(':');
-        s += " ";
-        s += node.ast_type;
-        s += " ";
-        s.push('[');
-        s += " ";
-        s.push(']');
+    pub fn annot_args(mut s: String, node: &'a AnnotArgs) -> String {
         s += " ";
-        if let Some(some_val) = node.sep {
-                s += some_val;
-        }        s += " ";
-        s.push('{');
+        s.push('(');
         s += " ";
-        let len = node.items.len();
-        for (i, item) in node.items.iter().enumerate() {
-            s = Self::list_item(s, item);
-            if i < len - 1 {         s.push(',');
- }
+        let len = node.annot_arg_list.len();
+        for (i, item) in node.annot_arg_list.iter().enumerate() {
+            s = Self::annot_arg(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s.push(',');
+            }
         }
         s += " ";
-        s.push('}');
+        s.push(')');
         s
     }
 
-    pub fn annot_arg(mut s: String, node: &'a AnnotArg) -> String {
+    pub fn annotation(mut s: String, node: &'a Annotation) -> String {
         s += " ";
-        s += node.key;
+        s += "@";
         s += " ";
-        s.push('=');
+        s += node.ident;
         s += " ";
-        s = Self::annot_arg_val(s, &node.annot_arg_val);
+        if let Some(ref some_val) = node.annot_args {
+            s = Self::annot_args(s, some_val);
+        }
         s
     }
 
-    pub fn simple_token(mut s: String, node: &'a SimpleToken) -> String {
+    pub fn ast_def(mut s: String, node: &'a AstDef) -> String {
+        if node.tokens.first().map_or(true, |item| match item { &Token::NamedTokenItem(ref item) => item.annots.is_empty(), &Token::SimpleTokenItem(ref item) => item.annots.is_empty(), &Token::TokenGroupItem(ref item) => item.annots.is_empty(), }) {
         s += " ";
         let len = node.annots.len();
         for (i, item) in node.annots.iter().enumerate() {
             s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
-        if node.not {
-                s.push('!');
-    }        s += " ";
-        s = Self::token_type(s, &node.token_type);
+        let len = node.tokens.len();
+        for (i, item) in node.tokens.iter().enumerate() {
+            s = Self::token(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
+        }
         s += " ";
-        if node.optional {
-                s.push('?');
-    }        s
-    }
-
-    pub fn annotation(mut s: String, node: &'a Annotation) -> String {
+        s += "=>";
         s += " ";
-        s += "@";
+        if let Some(some_val) = node.ident {
+                s += some_val;
+        }        } else {
         s += " ";
-        s += node.ident;
+        let len = node.annots.len();
+        for (i, item) in node.annots.iter().enumerate() {
+            s = Self::annotation(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
+        }
         s += " ";
-        if let Some(ref some_val) = node.annot_args {
-            s = Self::annot_args(s, some_val);
+        if let Some(some_val) = node.ident {
+                s += some_val;
+        }        s += " ";
+        s.push('(');
+        s += " ";
+        let len = node.tokens.len();
+        for (i, item) in node.tokens.iter().enumerate() {
+            s = Self::token(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
+        }
+        s += " ";
+        s.push(')');
         }
         s
     }
 
-    pub fn ast_ref(mut s: String, node: &'a AstRef) -> String {
+    pub fn ast_many(mut s: String, node: &'a AstMany) -> String {
+        s += " ";
+        let len = node.annots.len();
+        for (i, item) in node.annots.iter().enumerate() {
+            s = Self::annotation(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
+        }
         s += " ";
         s += node.ident;
+        s += " ";
+        s.push('{');
+        s += " ";
+        let len = node.items.len();
+        for (i, item) in node.items.iter().enumerate() {
+            s = Self::ast_item(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s.push(',');
+            }
+        }
+        s += " ";
+        s.push('}');
         s
     }
 
-    pub fn int_const(mut s: String, node: &'a IntConst) -> String {
+    pub fn ast_ref(mut s: String, node: &'a AstRef) -> String {
         s += " ";
-        s += &node.int.to_string();
+        s += node.ident;
         s
     }
 
-    pub fn list_single(mut s: String, node: &'a ListSingle) -> String {
+    pub fn ast_single(mut s: String, node: &'a AstSingle) -> String {
         s += " ";
         let len = node.annots.len();
         for (i, item) in node.annots.iter().enumerate() {
             s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
         s += node.ident;
         s += " ";
-        s.push('[');
-        s += " ";
-        s.push(']');
+        s.push('(');
         s += " ";
-        s += node.sep;
+        let len = node.tokens.len();
+        for (i, item) in node.tokens.iter().enumerate() {
+            s = Self::token(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
+        }
         s += " ";
-        s += node.reference;
+        s.push(')');
         s
     }
 
     pub fn comment(mut s: String, node: &'a Comment) -> String {
         s += " ";
         s += "(*";
-        s += " ";
         s += node.comment;
-        s += " ";
         s += "*)";
         s
     }
 
-    pub fn annot_args(mut s: String, node: &'a AnnotArgs) -> String {
+    pub fn func_token(mut s: String, node: &'a FuncToken) -> String {
+        s += " ";
+        s += node.ident;
         s += " ";
         s.push('(');
         s += " ";
-        let len = node.annot_arg_list.len();
-        for (i, item) in node.annot_arg_list.iter().enumerate() {
-            s = Self::annot_arg(s, item);
-            if i < len - 1 {         s.push(',');
- }
+        let len = node.fn_args.len();
+        for (i, item) in node.fn_args.iter().enumerate() {
+            s = Self::func_arg(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s.push(',');
+            }
         }
         s += " ";
         s.push(')');
@@ -156,42 +185,85 @@ impl<'a> ToSource {
         s
     }
 
+    pub fn int_const(mut s: String, node: &'a IntConst) -> String {
+        s += " ";
+        s += &node.int.to_string();
+        s
+    }
+
     pub fn key_token(mut s: String, node: &'a KeyToken) -> String {
         s += " ";
         s += node.key;
         s
     }
 
-    pub fn ast_many(mut s: String, node: &'a AstMany) -> String {
+    pub fn list_item(mut s: String, node: &'a ListItem) -> String {
+        s += " ";
+        s = Self::ast_item(s, &node.ast_item);
+        s += " ";
+        if let Some(some_val) = node.sep {
+                s += some_val;
+        }        s
+    }
+
+    pub fn list_many(mut s: String, node: &'a ListMany) -> String {
         s += " ";
         let len = node.annots.len();
         for (i, item) in node.annots.iter().enumerate() {
             s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
         s += node.ident;
         s += " ";
+        s.push(':');
+        s += " ";
+        s += node.ast_type;
+        s += " ";
+        s.push('[');
+        s += " ";
+        s.push(']');
+        s += " ";
+        if let Some(some_val) = node.sep {
+                s += some_val;
+        }        s += " ";
         s.push('{');
         s += " ";
         let len = node.items.len();
         for (i, item) in node.items.iter().enumerate() {
-            s = Self::ast_item(s, item);
-            if i < len - 1 {         s.push(',');
- }
+            s = Self::list_item(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s.push(',');
+            }
         }
         s += " ";
         s.push('}');
         s
     }
 
-    pub fn list_item(mut s: String, node: &'a ListItem) -> String {
+    pub fn list_single(mut s: String, node: &'a ListSingle) -> String {
         s += " ";
-        s = Self::ast_item(s, &node.ast_item);
+        let len = node.annots.len();
+        for (i, item) in node.annots.iter().enumerate() {
+            s = Self::annotation(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
+        }
         s += " ";
-        if let Some(some_val) = node.sep {
-                s += some_val;
-        }        s
+        s += node.ident;
+        s += " ";
+        s.push('[');
+        s += " ";
+        s.push(']');
+        s += " ";
+        s += node.sep;
+        s += " ";
+        s += node.reference;
+        s
     }
 
     pub fn named_token(mut s: String, node: &'a NamedToken) -> String {
@@ -199,7 +271,9 @@ impl<'a> ToSource {
         let len = node.annots.len();
         for (i, item) in node.annots.iter().enumerate() {
             s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
         s += node.name;
@@ -216,140 +290,74 @@ impl<'a> ToSource {
     }        s
     }
 
-    pub fn source(mut s: String, node: &'a Source) -> String {
-        s += " ";
-        let len = node.items.len();
-        for (i, item) in node.items.iter().enumerate() {
-            s = Self::source_item(s, item);
-            if i < len - 1 {         s += " " }
-        }
-        s
-    }
-
-    pub fn func_token(mut s: String, node: &'a FuncToken) -> String {
-        s += " ";
-        s += node.ident;
-        s += " ";
-        s.push('(');
-        s += " ";
-        let len = node.fn_args.len();
-        for (i, item) in node.fn_args.iter().enumerate() {
-            s = Self::func_arg(s, item);
-            if i < len - 1 {         s.push(',');
- }
-        }
+    pub fn quoted(mut s: String, node: &'a Quoted) -> String {
         s += " ";
-        s.push(')');
+        s += "\"";
+        s += node.string;
+        s += "\"";
         s
     }
 
-    pub fn token_group(mut s: String, node: &'a TokenGroup) -> String {
+    pub fn simple_token(mut s: String, node: &'a SimpleToken) -> String {
         s += " ";
         let len = node.annots.len();
         for (i, item) in node.annots.iter().enumerate() {
             s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
         if node.not {
                 s.push('!');
     }        s += " ";
-        s.push('(');
-        s += " ";
-        let len = node.token_list.len();
-        for (i, item) in node.token_list.iter().enumerate() {
-            s = Self::token(s, item);
-            if i < len - 1 {         s += " " }
-        }
-        s += " ";
-        s.push(')');
+        s = Self::token_type(s, &node.token_type);
         s += " ";
         if node.optional {
                 s.push('?');
     }        s
     }
 
-    pub fn ast_def(mut s: String, node: &'a AstDef) -> String {
-        s += " ";
-        let len = node.annots.len();
-        for (i, item) in node.annots.iter().enumerate() {
-            s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
-        }
-        s += " ";
-        let len = node.tokens.len();
-        for (i, item) in node.tokens.iter().enumerate() {
-            s = Self::token(s, item);
-            if i < len - 1 {         s += " " }
-        }
-        s += " ";
-        s += "=>";
-        s += " ";
-        if let Some(some_val) = node.ident {
-                s += some_val;
-        }        s += " ";
-        let len = node.annots.len();
-        for (i, item) in node.annots.iter().enumerate() {
-            s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
-        }
-        s += " ";
-        s.push('(');
-        s += " ";
-        let len = node.tokens.len();
-        for (i, item) in node.tokens.iter().enumerate() {
-            s = Self::token(s, item);
-            if i < len - 1 {         s += " " }
-        }
-        s += " ";
-        s.push(')');
-        s += " ";
-        s += "=>";
-        s += " ";
-        if let Some(some_val) = node.ident {
-                s += some_val;
-        }        s += " ";
-        let len = node.annots.len();
-        for (i, item) in node.annots.iter().enumerate() {
-            s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
-        }
-        s += " ";
-        if let Some(some_val) = node.ident {
-                s += some_val;
-        }        s += " ";
-        s.push('(');
+    pub fn source(mut s: String, node: &'a Source) -> String {
         s += " ";
-        let len = node.tokens.len();
-        for (i, item) in node.tokens.iter().enumerate() {
-            s = Self::token(s, item);
-            if i < len - 1 {         s += " " }
+        let len = node.items.len();
+        for (i, item) in node.items.iter().enumerate() {
+            s = Self::source_item(s, item);
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
-        s += " ";
-        s.push(')');
         s
     }
 
-    pub fn ast_single(mut s: String, node: &'a AstSingle) -> String {
+    pub fn token_group(mut s: String, node: &'a TokenGroup) -> String {
         s += " ";
         let len = node.annots.len();
         for (i, item) in node.annots.iter().enumerate() {
             s = Self::annotation(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
-        s += node.ident;
-        s += " ";
+        if node.not {
+                s.push('!');
+    }        s += " ";
         s.push('(');
         s += " ";
-        let len = node.tokens.len();
-        for (i, item) in node.tokens.iter().enumerate() {
+        let len = node.token_list.len();
+        for (i, item) in node.token_list.iter().enumerate() {
             s = Self::token(s, item);
-            if i < len - 1 {         s += " " }
+            if i < len - 1 {
+                s.truncate(s.trim_end().len());
+        s += " "            }
         }
         s += " ";
         s.push(')');
-        s
+        s += " ";
+        if node.optional {
+                s.push('?');
+    }        s
     }
 
     pub fn annot_arg_val(s: String, node: &'a AnnotArgVal) -> String {
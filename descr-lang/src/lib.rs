@@ -1,5 +1,7 @@
 #[macro_use]
 extern crate descr_common;
+#[cfg(feature = "lsp")]
+extern crate descr_lsp;
 pub mod gen;
 
 #[cfg(test)]
@@ -8,4 +10,137 @@ mod tests {
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn cst_reprints_input() {
+        use gen::cst::{self, SourceItemNode, SourceNode};
+        let input = include_bytes!("../../descr.lang");
+        let root = cst::parse(input).expect("Could not parse descr.lang");
+        assert_eq!(root.text().as_bytes(), &input[..]);
+        let source = root.child_nodes()
+            .into_iter()
+            .filter_map(SourceNode::cast)
+            .next()
+            .expect("No source node");
+        let items = source.items();
+        assert!(items.len() > 10);
+        let first_ast = items
+            .iter()
+            .filter_map(|item| match item {
+                &SourceItemNode::AstSingle(ref ast) => ast.ident(),
+                _ => None,
+            })
+            .next();
+        assert_eq!(first_ast, Some("Source".to_string()));
+    }
+
+    #[test]
+    fn to_source_reprints_descr_lang() {
+        use gen::ast::Source;
+        use gen::parsers::source;
+        use gen::to_source::ToSource;
+        fn parse(input: &[u8]) -> Source {
+            let (rest, node) = source(input).unwrap();
+            assert!(rest.iter().all(|c| c.is_ascii_whitespace()), "{}", String::from_utf8_lossy(input));
+            node
+        }
+        // The reprint parses to the same tree
+        let node = parse(include_bytes!("../../descr.lang"));
+        let printed = ToSource::source(String::new(), &node);
+        assert_eq!(format!("{:?}", parse(printed.as_bytes())), format!("{:?}", node), "{}", printed);
+    }
+
+    #[test]
+    fn highlights_descr_lang() {
+        use descr_common::highlight::to_html;
+        use gen::highlight::highlight;
+        let input = "Point(x:int y:int)\n\nColor {\n    \"red\" => Red\n}\n";
+        let tokens = highlight(input.as_bytes()).unwrap();
+        let scopes = tokens
+            .iter()
+            .map(|token| (&input[token.start..token.end], token.scope.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(scopes, vec![
+            ("Point", "entity.name.type"),
+            ("x", "variable.name"),
+            ("int", "storage.type"),
+            ("y", "variable.name"),
+            ("int", "storage.type"),
+            ("Color", "entity.name.type"),
+            ("\"red\"", "string.quoted.double"),
+            ("Red", "entity.name.type"),
+        ]);
+        assert!(to_html(input, &tokens).starts_with(
+            "<pre class=\"highlight\"><span class=\"entity name type\">Point</span>("
+        ));
+        assert!(highlight(b"Point(x:int").is_err());
+    }
+
+    #[cfg(feature = "lsp")]
+    #[test]
+    fn lsp_serves_descr_lang() {
+        use descr_lsp::InProcessClient;
+        use descr_lsp::json::Json;
+        use gen::lsp::Lsp;
+        let mut client = InProcessClient::new(Lsp);
+        let init = client.request("initialize", Json::object(vec![])).unwrap();
+        assert_eq!(
+            init.at(&["capabilities", "textDocumentSync"]).and_then(Json::as_i64),
+            Some(1)
+        );
+        let doc = |text: &str| {
+            Json::object(vec![(
+                "textDocument",
+                Json::object(vec![("uri", "file:///test.lang".into()), ("text", text.into())]),
+            )])
+        };
+        let diagnostics = |client: &mut InProcessClient<Lsp>| {
+            let notifications = client.take_notifications();
+            assert_eq!(notifications.len(), 1);
+            notifications[0].at(&["params", "diagnostics"]).and_then(Json::as_array).unwrap().len()
+        };
+        let text = "Point(x:int y:int)\n\nShape {\n    Circle(r:int),\n    Rect(w:int h:int)\n}\n";
+        client.notify("textDocument/didOpen", doc(text));
+        assert_eq!(diagnostics(&mut client), 0);
+
+        let symbols = client.request("textDocument/documentSymbol", doc("")).unwrap();
+        let symbols = symbols.as_array().unwrap();
+        let names = symbols
+            .iter()
+            .map(|symbol| symbol.get("name").and_then(Json::as_str).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["Point", "Shape"]);
+        assert_eq!(symbols[1].get("kind").and_then(Json::as_i64), Some(10));
+        let members = symbols[1].get("children").and_then(Json::as_array).unwrap();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[1].get("name").and_then(Json::as_str), Some("Rect"));
+        assert_eq!(
+            members[1].at(&["selectionRange", "start", "line"]).and_then(Json::as_i64),
+            Some(4)
+        );
+
+        // Type names are types, member names
+        // variables and int storage types
+        let tokens = client.request("textDocument/semanticTokens/full", doc("")).unwrap();
+        let data = tokens.get("data").and_then(Json::as_array).unwrap();
+        let data = data.iter().map(|n| n.as_i64().unwrap()).collect::<Vec<_>>();
+        assert_eq!(&data[..15], &[0, 0, 5, 2, 0, 0, 6, 1, 3, 0, 0, 2, 3, 2, 0]);
+        assert_eq!(data.len(), 14 * 5);
+
+        let folding = client.request("textDocument/foldingRange", doc("")).unwrap();
+        assert_eq!(folding.to_string(), r#"[{"startLine":2,"endLine":4}]"#);
+
+        let change = Json::object(vec![
+            ("textDocument", Json::object(vec![("uri", "file:///test.lang".into())])),
+            ("contentChanges", vec![Json::object(vec![("text", "Point(x:int".into())])].into()),
+        ]);
+        client.notify("textDocument/didChange", change);
+        assert_eq!(diagnostics(&mut client), 1);
+
+        let err = client.request("textDocument/hover", doc("")).unwrap_err();
+        assert_eq!(err.get("code").and_then(Json::as_i64), Some(-32601));
+        client.request("shutdown", Json::Null).unwrap();
+        client.notify("exit", Json::Null);
+        assert_eq!(client.exit_code(), Some(0));
+    }
 }
@@ -0,0 +1,362 @@
+//! Features computed from the lossless tree
+//! of a document. Positions are lines and
+//! utf-16 columns, as LSP expects.
+use descr_common::cst::*;
+use json::Json;
+
+/// Implemented by generated lsp.rs files
+pub trait LspLanguage {
+    type Kind: CstKind;
+    fn name(&self) -> &'static str;
+    fn parse(&self, input: &[u8]) -> Result<SyntaxNode<Self::Kind>, CstError>;
+    /// Symbol kind and label of name for
+    /// nodes from @symbol annotated rules
+    fn symbol(&self, kind: Self::Kind) -> Option<(&'static str, Label)>;
+    /// Scope name of token from @syntax,
+    /// like "entity.name.type"
+    fn scope(&self, parent: Self::Kind, label: Label, kind: Self::Kind) -> Option<&'static str>;
+}
+
+/// Legend of semantic token types, the index
+/// is sent as token type
+pub const TOKEN_TYPES: &[&str] = &[
+    "keyword",
+    "function",
+    "type",
+    "variable",
+    "parameter",
+    "property",
+    "string",
+    "number",
+    "comment",
+    "operator",
+];
+
+/// Token type for scope name, first matching
+/// prefix wins
+const SCOPE_TYPES: &[(&str, usize)] = &[
+    ("keyword.operator", 9),
+    ("keyword", 0),
+    ("storage.type", 2),
+    ("storage", 0),
+    ("entity.name.function", 1),
+    ("support.function", 1),
+    ("entity.name", 2),
+    ("support.type", 2),
+    ("variable.parameter", 4),
+    ("variable.other.member", 5),
+    ("entity.other.attribute", 5),
+    ("variable", 3),
+    ("string", 6),
+    ("constant.numeric", 7),
+    ("comment", 8),
+];
+
+pub fn token_type(scope: &str) -> Option<usize> {
+    SCOPE_TYPES
+        .iter()
+        .find(|&&(prefix, _)| {
+            scope == prefix || (scope.starts_with(prefix) && scope[prefix.len()..].starts_with('.'))
+        })
+        .map(|&(_, index)| index)
+}
+
+/// LSP number of symbol kind name
+pub fn symbol_kind(name: &str) -> usize {
+    match name {
+        "file" => 1,
+        "module" => 2,
+        "namespace" => 3,
+        "package" => 4,
+        "class" => 5,
+        "method" => 6,
+        "property" => 7,
+        "field" => 8,
+        "constructor" => 9,
+        "enum" => 10,
+        "interface" => 11,
+        "function" => 12,
+        "variable" => 13,
+        "constant" => 14,
+        "string" => 15,
+        "number" => 16,
+        "boolean" => 17,
+        "array" => 18,
+        "object" => 19,
+        "key" => 20,
+        "null" => 21,
+        "enum_member" => 22,
+        "struct" => 23,
+        "event" => 24,
+        "operator" => 25,
+        "type_parameter" => 26,
+        _ => 13,
+    }
+}
+
+/// Offsets to lines and utf-16 columns
+pub struct LineIndex {
+    text: String,
+    line_starts: Vec<usize>,
+}
+impl LineIndex {
+    pub fn new(text: &str) -> LineIndex {
+        let mut line_starts = vec![0];
+        for (i, b) in text.bytes().enumerate() {
+            if b == b'\n' {
+                line_starts.push(i + 1);
+            }
+        }
+        LineIndex {
+            text: text.to_string(),
+            line_starts,
+        }
+    }
+
+    /// Line and column of byte offset
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let mut offset = if offset > self.text.len() { self.text.len() } else { offset };
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        };
+        let col = self.text[self.line_starts[line]..offset].encode_utf16().count();
+        (line, col)
+    }
+
+    pub fn range(&self, start: usize, end: usize) -> Json {
+        Json::object(vec![
+            ("start", self.position_json(start)),
+            ("end", self.position_json(end)),
+        ])
+    }
+
+    fn position_json(&self, offset: usize) -> Json {
+        let (line, col) = self.position(offset);
+        Json::object(vec![("line", line.into()), ("character", col.into())])
+    }
+}
+
+/// Open document with its parse result
+pub struct Document<K: CstKind> {
+    pub text: String,
+    pub index: LineIndex,
+    pub tree: Result<SyntaxNode<K>, CstError>,
+}
+impl<K: CstKind> Document<K> {
+    pub fn new<L: LspLanguage<Kind = K>>(lang: &L, text: String) -> Document<K> {
+        let tree = lang.parse(text.as_bytes());
+        Document {
+            index: LineIndex::new(&text),
+            text,
+            tree,
+        }
+    }
+
+    /// Parse error as diagnostic. The tree is
+    /// all or nothing, so there is at most one.
+    pub fn diagnostics<L: LspLanguage<Kind = K>>(&self, lang: &L) -> Vec<Json> {
+        match self.tree {
+            Ok(_) => Vec::new(),
+            Err(ref err) => {
+                let end = self.text[err.offset..]
+                    .chars()
+                    .next()
+                    .map(|c| err.offset + c.len_utf8())
+                    .unwrap_or(err.offset);
+                vec![Json::object(vec![
+                    ("range", self.index.range(err.offset, end)),
+                    ("severity", 1usize.into()),
+                    ("source", lang.name().into()),
+                    ("message", err.to_string().into()),
+                ])]
+            }
+        }
+    }
+
+    /// Hierarchical DocumentSymbol list
+    pub fn symbols<L: LspLanguage<Kind = K>>(&self, lang: &L) -> Vec<Json> {
+        match self.tree {
+            Ok(ref root) => self.node_symbols(lang, root),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn node_symbols<L: LspLanguage<Kind = K>>(&self, lang: &L, node: &SyntaxNode<K>) -> Vec<Json> {
+        let mut symbols = Vec::new();
+        for child in node.child_nodes() {
+            let children = self.node_symbols(lang, &child);
+            match lang.symbol(child.kind()) {
+                Some((kind, label)) => {
+                    let name_range = label
+                        .and_then(|label| child.child(label))
+                        .or_else(|| {
+                            child
+                                .labeled_children()
+                                .into_iter()
+                                .find(|&(label, ref element)| {
+                                    label.is_some() && matches!(element, &SyntaxElement::Token(_))
+                                })
+                                .map(|(_, element)| element)
+                        })
+                        .map(|element| match element {
+                            SyntaxElement::Token(token) => token.range(),
+                            SyntaxElement::Node(node) => trimmed_range(&self.text, node.range()),
+                        });
+                    let (start, end) = trimmed_range(&self.text, child.range());
+                    let (name_start, name_end) = name_range.unwrap_or((start, start));
+                    let name = match &self.text[name_start..name_end] {
+                        "" => format!("{:?}", child.kind()),
+                        name => name.to_string(),
+                    };
+                    symbols.push(Json::object(vec![
+                        ("name", name.into()),
+                        ("kind", symbol_kind(kind).into()),
+                        ("range", self.index.range(start, end)),
+                        ("selectionRange", self.index.range(name_start, name_end)),
+                        ("children", children.into()),
+                    ]));
+                }
+                None => symbols.extend(children),
+            }
+        }
+        symbols
+    }
+
+    /// Delta encoded semantic tokens, split
+    /// at line ends
+    pub fn semantic_tokens<L: LspLanguage<Kind = K>>(&self, lang: &L) -> Vec<Json> {
+        let mut data = Vec::new();
+        let root = match self.tree {
+            Ok(ref root) => root,
+            Err(_) => return data,
+        };
+        let mut prev = (0, 0);
+        let mut add = |start: usize, end: usize, token_type: usize| {
+            let (line, col) = self.index.position(start);
+            let len = self.text[start..end].encode_utf16().count();
+            if len == 0 {
+                return;
+            }
+            let delta_col = if line == prev.0 { col - prev.1 } else { col };
+            data.extend(vec![
+                Json::from(line - prev.0),
+                Json::from(delta_col),
+                Json::from(len),
+                Json::from(token_type),
+                Json::from(0usize),
+            ]);
+            prev = (line, col);
+        };
+        walk_tokens(root, &mut |label, token| {
+            let scope = match lang.scope(token.parent().kind(), label, token.kind()) {
+                Some(scope) => scope,
+                None => return,
+            };
+            let token_type = match token_type(scope) {
+                Some(token_type) => token_type,
+                None => return,
+            };
+            let (start, end) = token.range();
+            let mut line_start = start;
+            for (i, b) in self.text[start..end].bytes().enumerate() {
+                if b == b'\n' {
+                    add(line_start, start + i, token_type);
+                    line_start = start + i + 1;
+                }
+            }
+            add(line_start, end, token_type);
+        });
+        data
+    }
+
+    /// Ranges between {} and [] pairs
+    /// on different lines
+    pub fn folding_ranges(&self) -> Vec<Json> {
+        let root = match self.tree {
+            Ok(ref root) => root,
+            Err(_) => return Vec::new(),
+        };
+        let mut ranges = Vec::new();
+        let mut open: Vec<(&str, usize)> = Vec::new();
+        walk_tokens(root, &mut |_, token| {
+            let (start, _) = token.range();
+            match token.text() {
+                "{" => open.push(("}", start)),
+                "[" => open.push(("]", start)),
+                close @ "}" | close @ "]" => {
+                    if let Some(pos) = open.iter().rposition(|&(expected, _)| expected == close) {
+                        let (_, open_start) = open[pos];
+                        open.truncate(pos);
+                        let (start_line, _) = self.index.position(open_start);
+                        let (end_line, _) = self.index.position(start);
+                        if end_line > start_line + 1 {
+                            ranges.push((start_line, end_line - 1));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        });
+        ranges.sort();
+        ranges
+            .into_iter()
+            .map(|(start_line, end_line)| {
+                Json::object(vec![
+                    ("startLine", start_line.into()),
+                    ("endLine", end_line.into()),
+                ])
+            })
+            .collect()
+    }
+}
+
+/// Tokens in order, with their labels
+fn walk_tokens<K: CstKind>(node: &SyntaxNode<K>, f: &mut dyn FnMut(Label, &SyntaxToken<K>)) {
+    for (label, child) in node.labeled_children() {
+        match child {
+            SyntaxElement::Node(node) => walk_tokens(&node, f),
+            SyntaxElement::Token(token) => f(label, &token),
+        }
+    }
+}
+
+/// Range without surrounding whitespace,
+/// which nodes include
+fn trimmed_range(text: &str, (start, end): (usize, usize)) -> (usize, usize) {
+    let slice = &text[start..end];
+    let trimmed_start = start + (slice.len() - slice.trim_start().len());
+    let trimmed_end = end - (slice.len() - slice.trim_end().len());
+    if trimmed_start > trimmed_end {
+        (start, start)
+    } else {
+        (trimmed_start, trimmed_end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_are_utf16() {
+        let index = LineIndex::new("ab\nö😀x\n");
+        assert_eq!(index.position(1), (0, 1));
+        assert_eq!(index.position(3), (1, 0));
+        assert_eq!(index.position(9), (1, 3));
+        assert_eq!(index.position(100), (2, 0));
+    }
+
+    #[test]
+    fn scopes_map_to_token_types() {
+        assert_eq!(token_type("keyword.control"), Some(0));
+        assert_eq!(token_type("keyword.operator.arithmetic"), Some(9));
+        assert_eq!(token_type("entity.name.function"), Some(1));
+        assert_eq!(token_type("entity.name.type"), Some(2));
+        assert_eq!(token_type("variables"), None);
+        assert_eq!(token_type("markup.bold"), None);
+    }
+}
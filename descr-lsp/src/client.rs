@@ -0,0 +1,125 @@
+//! Client driving a server in process,
+//! for tests. Messages go through the same
+//! framing and json as over stdio.
+use std::io::Cursor;
+use analysis::LspLanguage;
+use json::Json;
+use server::Server;
+use transport::{read_message, write_message};
+
+pub struct InProcessClient<L: LspLanguage> {
+    server: Server<L>,
+    next_id: i64,
+    notifications: Vec<Json>,
+}
+impl<L: LspLanguage> InProcessClient<L> {
+    pub fn new(lang: L) -> InProcessClient<L> {
+        InProcessClient {
+            server: Server::new(lang),
+            next_id: 1,
+            notifications: Vec::new(),
+        }
+    }
+
+    /// Result of request, or the error object
+    pub fn request(&mut self, method: &str, params: Json) -> Result<Json, Json> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let message = Json::object(vec![
+            ("jsonrpc", "2.0".into()),
+            ("id", id.into()),
+            ("method", method.into()),
+            ("params", params),
+        ]);
+        let mut response = None;
+        for message in self.send(&message) {
+            if message.get("id").and_then(Json::as_i64) == Some(id) {
+                response = Some(message);
+            } else {
+                self.notifications.push(message);
+            }
+        }
+        let response = response.expect("No response from server");
+        match response.get("error") {
+            Some(error) => Err(error.clone()),
+            None => Ok(response.get("result").cloned().unwrap_or(Json::Null)),
+        }
+    }
+
+    pub fn notify(&mut self, method: &str, params: Json) {
+        let message = Json::object(vec![
+            ("jsonrpc", "2.0".into()),
+            ("method", method.into()),
+            ("params", params),
+        ]);
+        let outgoing = self.send(&message);
+        self.notifications.extend(outgoing);
+    }
+
+    /// Notifications from the server since
+    /// last call
+    pub fn take_notifications(&mut self) -> Vec<Json> {
+        ::std::mem::take(&mut self.notifications)
+    }
+
+    pub fn exit_code(&self) -> Option<i32> {
+        self.server.exit_code()
+    }
+
+    fn send(&mut self, message: &Json) -> Vec<Json> {
+        let incoming = Self::roundtrip(message);
+        self.server
+            .handle(&incoming)
+            .iter()
+            .map(Self::roundtrip)
+            .collect()
+    }
+
+    /// Frames, reads and parses the message
+    fn roundtrip(message: &Json) -> Json {
+        let mut buf = Vec::new();
+        write_message(&mut buf, &message.to_string()).expect("Could not write message");
+        let content = read_message(&mut Cursor::new(buf))
+            .expect("Could not read message")
+            .expect("No message");
+        Json::parse(&content).expect("Invalid json")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::{doc, json, Lang, TEXT};
+
+    #[test]
+    fn answers_requests_by_id() {
+        let mut client = InProcessClient::new(Lang);
+        assert!(client.request("initialize", Json::Null).is_ok());
+        assert_eq!(client.next_id, 2);
+        let err = client.request("unknown", Json::Null).unwrap_err();
+        assert_eq!(err.get("code").and_then(Json::as_i64), Some(-32601));
+        assert_eq!(client.next_id, 3);
+        assert!(client.take_notifications().is_empty());
+    }
+
+    #[test]
+    fn keeps_notifications_until_taken() {
+        let mut client = InProcessClient::new(Lang);
+        client.notify("textDocument/didOpen", doc("file:///a", Some(TEXT)));
+        client.notify("textDocument/didOpen", doc("file:///b", Some("a {")));
+        client.notify("initialized", Json::object(vec![]));
+        let uris = client
+            .take_notifications()
+            .iter()
+            .map(|notification| notification.at(&["params", "uri"]).and_then(Json::as_str).unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(uris, vec!["file:///a", "file:///b"]);
+        assert!(client.take_notifications().is_empty());
+    }
+
+    #[test]
+    fn roundtrips_messages_through_framing() {
+        let message = json(r#"{"text": "é\n😀", "list": [1, -2.5, null, true]}"#);
+        assert_eq!(InProcessClient::<Lang>::roundtrip(&message), message);
+    }
+}
@@ -0,0 +1,149 @@
+//! Language server runtime for generated
+//! lsp.rs files. Speaks LSP over stdio with
+//! full document sync, and gives diagnostics,
+//! document symbols, semantic tokens and
+//! folding ranges from the lossless tree.
+extern crate descr_common;
+
+pub use descr_common::json;
+pub mod transport;
+pub mod analysis;
+pub mod server;
+pub mod client;
+
+pub use analysis::LspLanguage;
+pub use client::InProcessClient;
+pub use server::{run_stdio, Server};
+
+/// Language of blocks with fields and
+/// comments, served in tests
+#[cfg(test)]
+pub mod test_util {
+    use descr_common::cst::*;
+    use analysis::LspLanguage;
+    use json::Json;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Kind {
+        Root,
+        Ws,
+        Text,
+        Block,
+        Field,
+        Ident,
+        Int,
+        Colon,
+        LBrace,
+        RBrace,
+        Comment,
+    }
+    impl CstKind for Kind {
+        fn root() -> Kind {
+            Kind::Root
+        }
+        fn whitespace() -> Kind {
+            Kind::Ws
+        }
+        fn text() -> Kind {
+            Kind::Text
+        }
+    }
+
+    /// Two blocks on several lines, with a comment
+    /// over two lines
+    pub const TEXT: &str = "outer {\n  a: 1\n  (* note\n     more *)\n  inner {\n    b: 2\n  }\n}\n";
+
+    pub struct Lang;
+
+    impl LspLanguage for Lang {
+        type Kind = Kind;
+
+        fn name(&self) -> &'static str {
+            "blocks"
+        }
+
+        fn parse(&self, input: &[u8]) -> Result<SyntaxNode<Kind>, CstError> {
+            // Whitespace is skipped after blocks, as
+            // many0 only stops at the end of input
+            parse(input, |c| {
+                c.sp();
+                c.many0(|c| {
+                    block(c, None)?;
+                    c.sp();
+                    Ok(())
+                })?;
+                Ok(())
+            })
+        }
+
+        fn symbol(&self, kind: Kind) -> Option<(&'static str, Label)> {
+            match kind {
+                Kind::Block => Some(("class", Some("name"))),
+                Kind::Field => Some(("field", Some("key"))),
+                _ => None,
+            }
+        }
+
+        fn scope(&self, parent: Kind, label: Label, kind: Kind) -> Option<&'static str> {
+            match (parent, label, kind) {
+                (Kind::Block, Some("name"), Kind::Ident) => Some("entity.name.type"),
+                (Kind::Field, Some("key"), Kind::Ident) => Some("variable.other.member"),
+                (_, _, Kind::Int) => Some("constant.numeric"),
+                (_, _, Kind::Comment) => Some("comment"),
+                _ => None,
+            }
+        }
+    }
+
+    fn block(c: &mut Cursor<Kind>, label: Label) -> Res {
+        c.node(Kind::Block, label, |c| {
+            c.sp();
+            c.token(Kind::Ident, Some("name"), |c| c.ident())?;
+            c.sp();
+            c.token(Kind::LBrace, None, |c| c.chr('{'))?;
+            c.many0(|c| block_item(c, None))?;
+            c.sp();
+            c.token(Kind::RBrace, None, |c| c.chr('}'))
+        })
+    }
+
+    fn block_item(c: &mut Cursor<Kind>, label: Label) -> Res {
+        if c.attempt(|c| block(c, label)).is_some() {
+            return Ok(());
+        }
+        if c.attempt(|c| field(c, label)).is_some() {
+            return Ok(());
+        }
+        c.sp();
+        c.token(Kind::Comment, label, |c| {
+            c.tag("(*")?;
+            c.until(|c| c.tag("*)"))?;
+            c.tag("*)")
+        })
+    }
+
+    fn field(c: &mut Cursor<Kind>, label: Label) -> Res {
+        c.node(Kind::Field, label, |c| {
+            c.sp();
+            c.token(Kind::Ident, Some("key"), |c| c.ident())?;
+            c.sp();
+            c.token(Kind::Colon, None, |c| c.chr(':'))?;
+            c.sp();
+            c.token(Kind::Int, Some("val"), |c| c.int())
+        })
+    }
+
+    /// textDocument params of a document
+    /// with uri, and text if given
+    pub fn doc(uri: &str, text: Option<&str>) -> Json {
+        let mut doc = vec![("uri", uri.into())];
+        if let Some(text) = text {
+            doc.push(("text", text.into()));
+        }
+        Json::object(vec![("textDocument", Json::object(doc))])
+    }
+
+    pub fn json(source: &str) -> Json {
+        Json::parse(source).unwrap()
+    }
+}
@@ -0,0 +1,364 @@
+//! Message handling. The server keeps open
+//! documents and answers with json messages,
+//! run_stdio connects it to stdin and stdout.
+use std::collections::HashMap;
+use std::io::{self, BufReader};
+use analysis::{Document, LspLanguage, TOKEN_TYPES};
+use json::Json;
+use transport::{read_message, write_message};
+
+pub struct Server<L: LspLanguage> {
+    lang: L,
+    documents: HashMap<String, Document<L::Kind>>,
+    shutdown: bool,
+    exit: Option<i32>,
+}
+impl<L: LspLanguage> Server<L> {
+    pub fn new(lang: L) -> Server<L> {
+        Server {
+            lang,
+            documents: HashMap::new(),
+            shutdown: false,
+            exit: None,
+        }
+    }
+
+    /// Exit code, after exit notification
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit
+    }
+
+    /// Responses and notifications for
+    /// a message from the client
+    pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+        let method = match message.get("method").and_then(Json::as_str) {
+            Some(method) => method.to_string(),
+            // Responses to server requests
+            None => return Vec::new(),
+        };
+        let null = Json::Null;
+        let params = message.get("params").unwrap_or(&null);
+        let id = match message.get("id") {
+            Some(id) => id.clone(),
+            None => return self.handle_notification(&method, params),
+        };
+        let result = if self.shutdown && method != "exit" {
+            Err((-32600, "Server is shut down".to_string()))
+        } else {
+            self.handle_request(&method, params)
+        };
+        let response = match result {
+            Ok(result) => Json::object(vec![
+                ("jsonrpc", "2.0".into()),
+                ("id", id),
+                ("result", result),
+            ]),
+            Err((code, message)) => Json::object(vec![
+                ("jsonrpc", "2.0".into()),
+                ("id", id),
+                (
+                    "error",
+                    Json::object(vec![("code", (code as i64).into()), ("message", message.into())]),
+                ),
+            ]),
+        };
+        vec![response]
+    }
+
+    fn handle_request(&mut self, method: &str, params: &Json) -> Result<Json, (i32, String)> {
+        match method {
+            "initialize" => Ok(Json::object(vec![
+                ("capabilities", Self::capabilities()),
+                (
+                    "serverInfo",
+                    Json::object(vec![("name", format!("{}-language-server", self.lang.name()).into())]),
+                ),
+            ])),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Json::Null)
+            }
+            "textDocument/documentSymbol" => {
+                let doc = self.document(params)?;
+                Ok(doc.symbols(&self.lang).into())
+            }
+            "textDocument/semanticTokens/full" => {
+                let doc = self.document(params)?;
+                Ok(Json::object(vec![("data", doc.semantic_tokens(&self.lang).into())]))
+            }
+            "textDocument/foldingRange" => {
+                let doc = self.document(params)?;
+                Ok(doc.folding_ranges().into())
+            }
+            _ => Err((-32601, format!("Method not found: {}", method))),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Json) -> Vec<Json> {
+        let uri = params
+            .at(&["textDocument", "uri"])
+            .and_then(Json::as_str)
+            .map(|uri| uri.to_string());
+        match (method, uri) {
+            ("exit", _) => {
+                self.exit = Some(if self.shutdown { 0 } else { 1 });
+                Vec::new()
+            }
+            ("textDocument/didOpen", Some(uri)) => {
+                let text = params.at(&["textDocument", "text"]).and_then(Json::as_str).unwrap_or("");
+                self.open(uri, text.to_string())
+            }
+            // Full sync, the last change has
+            // the whole text
+            ("textDocument/didChange", Some(uri)) => {
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Json::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Json::as_str);
+                match text {
+                    Some(text) => self.open(uri, text.to_string()),
+                    None => Vec::new(),
+                }
+            }
+            ("textDocument/didClose", Some(uri)) => {
+                self.documents.remove(&uri);
+                vec![Self::publish_diagnostics(uri, Vec::new())]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn open(&mut self, uri: String, text: String) -> Vec<Json> {
+        let doc = Document::new(&self.lang, text);
+        let diagnostics = doc.diagnostics(&self.lang);
+        self.documents.insert(uri.clone(), doc);
+        vec![Self::publish_diagnostics(uri, diagnostics)]
+    }
+
+    fn document(&self, params: &Json) -> Result<&Document<L::Kind>, (i32, String)> {
+        let uri = params
+            .at(&["textDocument", "uri"])
+            .and_then(Json::as_str)
+            .ok_or((-32602, "Missing textDocument.uri".to_string()))?;
+        self.documents
+            .get(uri)
+            .ok_or((-32602, format!("Document not open: {}", uri)))
+    }
+
+    fn capabilities() -> Json {
+        Json::object(vec![
+            ("textDocumentSync", 1usize.into()),
+            ("documentSymbolProvider", true.into()),
+            ("foldingRangeProvider", true.into()),
+            (
+                "semanticTokensProvider",
+                Json::object(vec![
+                    (
+                        "legend",
+                        Json::object(vec![
+                            (
+                                "tokenTypes",
+                                TOKEN_TYPES.iter().map(|&name| Json::from(name)).collect::<Vec<_>>().into(),
+                            ),
+                            ("tokenModifiers", Vec::new().into()),
+                        ]),
+                    ),
+                    ("full", true.into()),
+                ]),
+            ),
+        ])
+    }
+
+    fn publish_diagnostics(uri: String, diagnostics: Vec<Json>) -> Json {
+        Json::object(vec![
+            ("jsonrpc", "2.0".into()),
+            ("method", "textDocument/publishDiagnostics".into()),
+            (
+                "params",
+                Json::object(vec![("uri", uri.into()), ("diagnostics", diagnostics.into())]),
+            ),
+        ])
+    }
+}
+
+/// Serves on stdin and stdout until exit,
+/// then exits the process
+pub fn run_stdio<L: LspLanguage>(lang: L) {
+    let mut server = Server::new(lang);
+    let stdin = io::stdin();
+    let mut reader = BufReader::new(stdin.lock());
+    let stdout = io::stdout();
+    let mut writer = stdout.lock();
+    while let Ok(Some(content)) = read_message(&mut reader) {
+        let outgoing = match Json::parse(&content) {
+            Ok(message) => server.handle(&message),
+            Err(err) => vec![Json::object(vec![
+                ("jsonrpc", "2.0".into()),
+                ("id", Json::Null),
+                (
+                    "error",
+                    Json::object(vec![("code", (-32700i64).into()), ("message", err.to_string().into())]),
+                ),
+            ])],
+        };
+        for message in outgoing {
+            if write_message(&mut writer, &message.to_string()).is_err() {
+                break;
+            }
+        }
+        if let Some(code) = server.exit_code() {
+            ::std::process::exit(code);
+        }
+    }
+    ::std::process::exit(if server.shutdown { 0 } else { 1 });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use client::InProcessClient;
+    use test_util::{doc, json, Lang, TEXT};
+
+    const URI: &str = "file:///a.blocks";
+
+    /// Client with TEXT open
+    fn client() -> InProcessClient<Lang> {
+        let mut client = InProcessClient::new(Lang);
+        client.request("initialize", Json::object(vec![])).unwrap();
+        client.notify("textDocument/didOpen", doc(URI, Some(TEXT)));
+        client.take_notifications();
+        client
+    }
+
+    /// Diagnostics published since last call,
+    /// which must be for URI
+    fn diagnostics(client: &mut InProcessClient<Lang>) -> Json {
+        let notifications = client.take_notifications();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].get("method").and_then(Json::as_str), Some("textDocument/publishDiagnostics"));
+        assert_eq!(notifications[0].at(&["params", "uri"]).and_then(Json::as_str), Some(URI));
+        notifications[0].at(&["params", "diagnostics"]).unwrap().clone()
+    }
+
+    #[test]
+    fn initializes_with_capabilities() {
+        let mut client = InProcessClient::new(Lang);
+        let init = client.request("initialize", Json::object(vec![])).unwrap();
+        assert_eq!(init.at(&["serverInfo", "name"]).and_then(Json::as_str), Some("blocks-language-server"));
+        assert_eq!(init.at(&["capabilities", "textDocumentSync"]).and_then(Json::as_i64), Some(1));
+        let legend = init.at(&["capabilities", "semanticTokensProvider", "legend", "tokenTypes"]).unwrap();
+        assert_eq!(legend.as_array().unwrap().len(), TOKEN_TYPES.len());
+    }
+
+    #[test]
+    fn publishes_diagnostics_on_sync() {
+        let mut client = InProcessClient::new(Lang);
+        client.notify("textDocument/didOpen", doc(URI, Some(TEXT)));
+        assert_eq!(diagnostics(&mut client), json("[]"));
+        let change = |text: &str| {
+            Json::object(vec![
+                ("textDocument", Json::object(vec![("uri", URI.into())])),
+                ("contentChanges", vec![Json::object(vec![("text", "ignored".into())]), Json::object(vec![("text", text.into())])].into()),
+            ])
+        };
+        // The last change has the whole text, the
+        // error is where the int is missing
+        client.notify("textDocument/didChange", change("outer {\n  a: 1\n  b:\n}\n"));
+        assert_eq!(
+            diagnostics(&mut client),
+            json(r#"[{
+                "range": {"start": {"line": 3, "character": 0}, "end": {"line": 3, "character": 1}},
+                "severity": 1,
+                "source": "blocks",
+                "message": "Unexpected input at offset 20"
+            }]"#)
+        );
+        let symbols = client.request("textDocument/documentSymbol", doc(URI, None)).unwrap();
+        assert_eq!(symbols, json("[]"));
+        client.notify("textDocument/didChange", change(TEXT));
+        assert_eq!(diagnostics(&mut client), json("[]"));
+        client.notify("textDocument/didClose", doc(URI, None));
+        assert_eq!(diagnostics(&mut client), json("[]"));
+        let err = client.request("textDocument/documentSymbol", doc(URI, None)).unwrap_err();
+        assert_eq!(err.get("code").and_then(Json::as_i64), Some(-32602));
+    }
+
+    #[test]
+    fn gives_document_symbols() {
+        let symbols = client().request("textDocument/documentSymbol", doc(URI, None)).unwrap();
+        let range = |start: (usize, usize), end: (usize, usize)| {
+            format!(
+                r#"{{"start": {{"line": {}, "character": {}}}, "end": {{"line": {}, "character": {}}}}}"#,
+                start.0, start.1, end.0, end.1
+            )
+        };
+        let symbol = |name: &str, kind: usize, range: String, selection: String, children: &str| {
+            format!(
+                r#"{{"name": "{}", "kind": {}, "range": {}, "selectionRange": {}, "children": [{}]}}"#,
+                name, kind, range, selection, children
+            )
+        };
+        let b = symbol("b", 8, range((5, 4), (5, 8)), range((5, 4), (5, 5)), "");
+        let inner = symbol("inner", 5, range((4, 2), (6, 3)), range((4, 2), (4, 7)), &b);
+        let a = symbol("a", 8, range((1, 2), (1, 6)), range((1, 2), (1, 3)), "");
+        let outer = symbol("outer", 5, range((0, 0), (7, 1)), range((0, 0), (0, 5)), &format!("{}, {}", a, inner));
+        assert_eq!(symbols, json(&format!("[{}]", outer)));
+    }
+
+    #[test]
+    fn gives_semantic_tokens() {
+        let tokens = client().request("textDocument/semanticTokens/full", doc(URI, None)).unwrap();
+        // Lines and columns are deltas, the
+        // comment is split at its line end
+        assert_eq!(
+            tokens,
+            json(r#"{"data": [
+                0, 0, 5, 2, 0,
+                1, 2, 1, 5, 0,
+                0, 3, 1, 7, 0,
+                1, 2, 7, 8, 0,
+                1, 0, 12, 8, 0,
+                1, 2, 5, 2, 0,
+                1, 4, 1, 5, 0,
+                0, 3, 1, 7, 0
+            ]}"#)
+        );
+    }
+
+    #[test]
+    fn gives_folding_ranges() {
+        let ranges = client().request("textDocument/foldingRange", doc(URI, None)).unwrap();
+        assert_eq!(
+            ranges,
+            json(r#"[{"startLine": 0, "endLine": 6}, {"startLine": 4, "endLine": 5}]"#)
+        );
+    }
+
+    #[test]
+    fn answers_errors_and_shuts_down() {
+        let mut client = client();
+        let err = client.request("textDocument/hover", doc(URI, None)).unwrap_err();
+        assert_eq!(err, json(r#"{"code": -32601, "message": "Method not found: textDocument/hover"}"#));
+        let err = client.request("textDocument/foldingRange", Json::object(vec![])).unwrap_err();
+        assert_eq!(err, json(r#"{"code": -32602, "message": "Missing textDocument.uri"}"#));
+        assert_eq!(client.request("shutdown", Json::Null), Ok(Json::Null));
+        let err = client.request("textDocument/foldingRange", doc(URI, None)).unwrap_err();
+        assert_eq!(err.get("code").and_then(Json::as_i64), Some(-32600));
+        assert_eq!(client.exit_code(), None);
+        client.notify("exit", Json::Null);
+        assert_eq!(client.exit_code(), Some(0));
+        assert!(client.take_notifications().is_empty());
+    }
+
+    #[test]
+    fn exits_with_1_without_shutdown() {
+        let mut server = Server::new(Lang);
+        // Responses to server requests are
+        // not answered
+        assert!(server.handle(&json(r#"{"jsonrpc": "2.0", "id": 1, "result": null}"#)).is_empty());
+        assert!(server.handle(&json(r#"{"jsonrpc": "2.0", "method": "exit"}"#)).is_empty());
+        assert_eq!(server.exit_code(), Some(1));
+    }
+}
@@ -0,0 +1,70 @@
+//! Base protocol framing, messages
+//! with a Content-Length header.
+use std::io::{self, BufRead, Write};
+
+/// Next message content, or None at
+/// end of input
+pub fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        let mut parts = line.splitn(2, ':');
+        let name = parts.next().unwrap_or("").trim();
+        let value = parts.next().unwrap_or("").trim();
+        if name.eq_ignore_ascii_case("Content-Length") {
+            content_length = value.parse::<usize>().ok();
+        }
+    }
+    let mut buf = vec![0; content_length.unwrap_or(0)];
+    reader.read_exact(&mut buf)?;
+    String::from_utf8(buf)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+pub fn write_message<W: Write>(writer: &mut W, content: &str) -> io::Result<()> {
+    write!(writer, "Content-Length: {}\r\n\r\n{}", content.len(), content)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn reads_written_messages() {
+        let mut buf = Vec::new();
+        write_message(&mut buf, "{\"id\":1}").unwrap();
+        write_message(&mut buf, "\"é\"").unwrap();
+        assert_eq!(String::from_utf8_lossy(&buf), "Content-Length: 8\r\n\r\n{\"id\":1}Content-Length: 4\r\n\r\n\"é\"");
+        let mut reader = Cursor::new(buf);
+        assert_eq!(read_message(&mut reader).unwrap(), Some("{\"id\":1}".to_string()));
+        // The length is in bytes
+        assert_eq!(read_message(&mut reader).unwrap(), Some("\"é\"".to_string()));
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn skips_other_headers_and_blank_lines() {
+        let input = "\r\ncontent-length: 2\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}";
+        let mut reader = Cursor::new(input.as_bytes());
+        assert_eq!(read_message(&mut reader).unwrap(), Some("{}".to_string()));
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+        // Content shorter than its length
+        let mut reader = Cursor::new(&b"Content-Length: 5\r\n\r\n{}"[..]);
+        assert!(read_message(&mut reader).is_err());
+        let mut reader = Cursor::new(&b"Content-Length: 1\r\n\r\n\xff"[..]);
+        assert_eq!(read_message(&mut reader).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
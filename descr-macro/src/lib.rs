@@ -0,0 +1,224 @@
+//! Inline grammars through the `descr!` macro.
+//!
+//! The macro accepts the same syntax as .lang files,
+//! and expands to the `ast`, `parsers`, `visitor` and
+//! `to_source` modules that would otherwise be written
+//! to disk by descr.
+//!
+//! ```ignore
+//! #[macro_use]
+//! extern crate nom;
+//! #[macro_use]
+//! extern crate descr_common;
+//! #[macro_use]
+//! extern crate descr_macro;
+//!
+//! mod lang {
+//!     descr! {
+//!         Source(items:sourceItems)
+//!         sourceItems:SourceItem[] WS {
+//!             Say("say" string)
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The generated code uses nom macros and descr_common
+//! parsers, so the crate needs those as dependencies with
+//! `#[macro_use]`, the same as for generated files.
+//!
+//! Since the grammar is read as rust tokens, comments
+//! and strings need to be valid rust tokens, for
+//! example a single quote can't be used in a comment.
+extern crate descr_gen;
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+use descr_gen::generator::{GenError, GenOptions, Generator};
+
+#[proc_macro]
+pub fn descr(input: TokenStream) -> TokenStream {
+    let source = GrammarSource::from_tokens(input);
+    match generate(&source) {
+        Ok(code) => match code.parse() {
+            Ok(stream) => stream,
+            Err(err) => compile_error(Span::call_site(), &format!("Could not parse generated code: {}", err)),
+        },
+        Err((span, msg)) => compile_error(span, &msg),
+    }
+}
+
+/// Grammar source rebuilt from the macro tokens.
+/// Tokens are placed at their line and column,
+/// so whitespace is the same as in the macro
+/// input. Each token is recorded with it's range
+/// in the text, so parse positions can be mapped
+/// back to a span.
+struct GrammarSource {
+    text: String,
+    spans: Vec<(usize, usize, Span)>,
+    line: usize,
+    column: usize,
+}
+impl GrammarSource {
+    fn from_tokens(input: TokenStream) -> GrammarSource {
+        let mut source = GrammarSource {
+            text: String::new(),
+            spans: Vec::new(),
+            line: 0,
+            column: 0,
+        };
+        source.push_stream(input);
+        source
+    }
+
+    fn push_stream(&mut self, stream: TokenStream) {
+        for tree in stream {
+            match tree {
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::None => ("", ""),
+                    };
+                    self.push_str(open, group.span_open());
+                    self.push_stream(group.stream());
+                    self.push_str(close, group.span_close());
+                }
+                TokenTree::Punct(punct) => {
+                    self.push_str(&punct.as_char().to_string(), punct.span());
+                }
+                TokenTree::Ident(ident) => {
+                    self.push_str(&ident.to_string(), ident.span());
+                }
+                TokenTree::Literal(literal) => {
+                    self.push_str(&literal.to_string(), literal.span());
+                }
+            }
+        }
+    }
+
+    fn push_str(&mut self, s: &str, span: Span) {
+        let (line, column) = (span.line(), span.column());
+        if self.spans.is_empty() {
+            self.line = line;
+            self.column = column;
+        } else if line > self.line {
+            for _ in self.line..line {
+                self.text.push('\n');
+            }
+            self.line = line;
+            self.column = 0;
+        }
+        if column > self.column {
+            for _ in self.column..column {
+                self.text.push(' ');
+            }
+            self.column = column;
+        } else if Self::is_word(self.text.chars().last()) && Self::is_word(s.chars().next()) {
+            // Spans without location, like from
+            // macro_rules expansion, keep words apart
+            self.text.push(' ');
+            self.column += 1;
+        }
+        let start = self.text.len();
+        self.text.push_str(s);
+        self.spans.push((start, self.text.len(), span));
+        match s.rfind('\n') {
+            Some(pos) => {
+                self.line += s.matches('\n').count();
+                self.column = s[pos + 1..].chars().count();
+            }
+            None => self.column += s.chars().count(),
+        }
+    }
+
+    fn is_word(c: Option<char>) -> bool {
+        match c {
+            Some(c) => c.is_alphanumeric() || c == '_' || c == '"',
+            None => false,
+        }
+    }
+
+    /// Span of the token at offset, or the
+    /// following token when offset is between tokens
+    fn span_at(&self, offset: usize) -> Span {
+        for &(_start, end, span) in &self.spans {
+            if end > offset {
+                return span;
+            }
+        }
+        match self.spans.last() {
+            Some(&(_, _, span)) => span,
+            None => Span::call_site(),
+        }
+    }
+
+}
+
+fn generate(source: &GrammarSource) -> Result<String, (Span, String)> {
+    let mut options = GenOptions::new("Inline");
+    options.syntax = false;
+    options.module = false;
+    let outputs = match Generator::new(options).generate(source.text.as_bytes()) {
+        Ok(outputs) => outputs,
+        Err(err) => {
+            let span = match err {
+                GenError::Parse { pos }
+                | GenError::Incomplete { pos }
+                | GenError::UnknownToken { pos, .. }
+                | GenError::UnresolvedType { pos, .. }
+                | GenError::RecursiveEnum { pos, .. }
+                | GenError::DuplicateMember { pos, .. }
+                | GenError::DuplicateRule { pos, .. } => source.span_at(pos.offset),
+                GenError::NoStartRule => Span::call_site(),
+            };
+            // Position is given by the span
+            let msg = match err {
+                GenError::Parse { .. } => "Could not parse grammar from here".to_string(),
+                GenError::UnknownToken { ref key, .. } => format!("Could not find token: {}", key),
+                GenError::UnresolvedType { ref key, .. } => format!("Could not resolve type of {}", key),
+                GenError::RecursiveEnum { ref key, .. } => format!("Enum {} has itself as item", key),
+                GenError::DuplicateMember { ref key, .. } => {
+                    format!("Several parts resolve to the same member key: {}", key)
+                }
+                GenError::DuplicateRule { ref key, .. } => format!("Rule {} is both an ast and a list", key),
+                ref other => other.to_string(),
+            };
+            return Err((span, msg));
+        }
+    };
+    let mut s = String::new();
+    for (name, content) in outputs {
+        append_module(&mut s, name.trim_end_matches(".rs"), &content);
+    }
+    Ok(s)
+}
+
+fn append_module(s: &mut String, name: &str, content: &str) {
+    s.push_str("pub mod ");
+    s.push_str(name);
+    s.push_str(" {\n");
+    s.push_str(content);
+    s.push_str("\n}\n\n");
+}
+
+fn compile_error(span: Span, msg: &str) -> TokenStream {
+    let ident = Ident::new("compile_error", span);
+    let mut bang = Punct::new('!', Spacing::Alone);
+    bang.set_span(span);
+    let mut literal = Literal::string(msg);
+    literal.set_span(span);
+    let mut group = Group::new(
+        Delimiter::Brace,
+        vec![TokenTree::Literal(literal)].into_iter().collect(),
+    );
+    group.set_span(span);
+    vec![
+        TokenTree::Ident(ident),
+        TokenTree::Punct(bang),
+        TokenTree::Group(group),
+    ].into_iter()
+        .collect()
+}
@@ -0,0 +1,9 @@
+extern crate trybuild;
+
+/// Grammar errors point at the token
+/// in the macro input
+#[test]
+fn reports_grammar_errors_at_span() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,39 @@
+extern crate descr_common;
+#[macro_use]
+extern crate descr_macro;
+#[macro_use]
+extern crate nom;
+
+mod lang {
+    descr! {
+        Source(items:sourceItems)
+
+        (* Statements *)
+        sourceItems:SourceItem[] WS {
+            Say("say" string),
+            BgColor("bg" Color)
+        }
+
+        Color {
+            Red("red"),
+            Green("green"),
+            Blue("blue")
+        }
+    }
+}
+
+#[test]
+fn parses_inline_grammar() {
+    use lang::ast::*;
+    let res = lang::parsers::start(b"bg blue\nsay \"Hello world\"");
+    match res {
+        nom::IResult::Done(_, source) => {
+            assert_eq!(source.items.len(), 2);
+            match source.items[1] {
+                SourceItem::SayItem(Say { string }) => assert_eq!(string, "Hello world"),
+                _ => panic!("Expected say item"),
+            }
+        }
+        _ => panic!("Could not parse source"),
+    }
+}
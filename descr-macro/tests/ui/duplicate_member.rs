@@ -0,0 +1,11 @@
+#[macro_use]
+extern crate descr_macro;
+
+descr! {
+    Source(items:sourceItems)
+    sourceItems:SourceItem[] WS {
+        IfElse("if" ident "else" ident)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+#[macro_use]
+extern crate descr_macro;
+
+descr! {
+    Source(LBRACE items:sourceItems RBRACE)
+    sourceItems:SourceItem[] COMMA {
+        Say("say" Missing)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,538 @@
+#![allow(unused_variables)]
+#[allow(unused_imports)]
+use descr_common::cursor::*;
+use super::ast::*;
+
+/// Results of memoized rules by position
+#[derive(Default)]
+pub struct Memo<'a> {
+    pub annot_arg_val: MemoTable<AnnotArgVal<'a>>,
+    pub annot_args: MemoTable<AnnotArgs<'a>>,
+    pub annotation: MemoTable<Annotation<'a>>,
+    pub ast_item: MemoTable<AstItem<'a>>,
+    pub ast_many: MemoTable<AstMany<'a>>,
+    pub ast_single: MemoTable<AstSingle<'a>>,
+    pub comment: MemoTable<Comment<'a>>,
+    pub list: MemoTable<List<'a>>,
+    pub list_item: MemoTable<ListItem<'a>>,
+    pub source: MemoTable<Source<'a>>,
+    pub token_type: MemoTable<TokenType<'a>>,
+    pub annot_arg_list: MemoTable<Vec<AnnotArg<'a>>>,
+    pub annots: MemoTable<Vec<Annotation<'a>>>,
+    pub ast_items: MemoTable<Vec<AstItem<'a>>>,
+    pub fn_args: MemoTable<Vec<FuncArg<'a>>>,
+    pub list_items: MemoTable<Vec<ListItem<'a>>>,
+    pub source_items: MemoTable<Vec<SourceItem<'a>>>,
+    pub token_list: MemoTable<Vec<Token<'a>>>,
+    input: ::std::marker::PhantomData<&'a [u8]>,
+}
+
+/// Results are kept on backtrack,
+/// they don't depend on the path
+impl<'a> State for Memo<'a> {
+    type Mark = ();
+    fn mark(&self) {}
+    fn reset(&mut self, _mark: ()) {}
+}
+
+/// Parses all of input, trailing
+/// whitespace is allowed
+pub fn parse<'a>(input: &'a [u8]) -> Result<Source<'a>, ParseError> {
+    ::descr_common::cursor::parse_with(input, Memo::default(), start)
+}
+
+pub fn start<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Source<'a>> {
+    source(c)
+}
+
+pub fn annot_arg_val<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<AnnotArgVal<'a>> {
+    c.memo(|m| &mut m.annot_arg_val, |c| {
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            let string_k = c.string()?;
+            Ok(AnnotArgVal::QuotedItem(Quoted {
+                string: string_k,
+            }))
+        }) {
+            return Ok(res);
+        }
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            let ident_k = c.ident()?;
+            Ok(AnnotArgVal::IdentItem(Ident {
+                ident: ident_k,
+            }))
+        }) {
+            return Ok(res);
+        }
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            let int_k = c.int()?;
+            Ok(AnnotArgVal::IntConstItem(IntConst {
+                int: int_k,
+            }))
+        }) {
+            return Ok(res);
+        }
+        c.fail()
+    })
+}
+
+pub fn annot_args<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<AnnotArgs<'a>> {
+    c.memo(|m| &mut m.annot_args, |c| {
+        c.sp();
+        c.chr('(')?;
+        c.sp();
+        let annot_arg_list_k = annot_arg_list(c)?;
+        c.sp();
+        c.chr(')')?;
+        Ok(AnnotArgs {
+            annot_arg_list: annot_arg_list_k,
+        })
+    })
+}
+
+pub fn annotation<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Annotation<'a>> {
+    c.memo(|m| &mut m.annotation, |c| {
+        c.sp();
+        c.tag("@")?;
+        c.sp();
+        let ident_k = c.ident()?;
+        let annot_args_k = c.opt(|c| {
+            c.sp();
+            annot_args(c)
+        })?;
+        Ok(Annotation {
+            ident: ident_k,
+            annot_args: annot_args_k,
+        })
+    })
+}
+
+pub fn ast_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<AstItem<'a>> {
+    c.memo(|m| &mut m.ast_item, |c| {
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            let annots_k = annots(c)?;
+            c.sp();
+            let tokens_k = token_list(c)?;
+            c.sp();
+            c.tag("=>")?;
+            let ident_k = c.opt(|c| {
+                c.sp();
+                c.ident()
+            })?;
+            Ok(AstItem::AstDefItem(AstDef {
+                annots: annots_k,
+                tokens: tokens_k,
+                ident: ident_k,
+            }))
+        }) {
+            return Ok(res);
+        }
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            let annots_k = annots(c)?;
+            c.sp();
+            c.chr('(')?;
+            c.sp();
+            let tokens_k = token_list(c)?;
+            c.sp();
+            c.chr(')')?;
+            c.sp();
+            c.tag("=>")?;
+            let ident_k = c.opt(|c| {
+                c.sp();
+                c.ident()
+            })?;
+            Ok(AstItem::AstDefItem(AstDef {
+                annots: annots_k,
+                tokens: tokens_k,
+                ident: ident_k,
+            }))
+        }) {
+            return Ok(res);
+        }
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            let annots_k = annots(c)?;
+            let ident_k = c.opt(|c| {
+                c.sp();
+                c.ident()
+            })?;
+            c.sp();
+            c.chr('(')?;
+            c.sp();
+            let tokens_k = token_list(c)?;
+            c.sp();
+            c.chr(')')?;
+            Ok(AstItem::AstDefItem(AstDef {
+                annots: annots_k,
+                ident: ident_k,
+                tokens: tokens_k,
+            }))
+        }) {
+            return Ok(res);
+        }
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            let ident_k = c.ident()?;
+            Ok(AstItem::AstRefItem(AstRef {
+                ident: ident_k,
+            }))
+        }) {
+            return Ok(res);
+        }
+        c.fail()
+    })
+}
+
+pub fn ast_many<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<AstMany<'a>> {
+    c.memo(|m| &mut m.ast_many, |c| {
+        c.sp();
+        let annots_k = annots(c)?;
+        c.sp();
+        let ident_k = c.ident()?;
+        c.sp();
+        c.chr('{')?;
+        c.sp();
+        let items_k = ast_items(c)?;
+        c.sp();
+        c.chr('}')?;
+        Ok(AstMany {
+            annots: annots_k,
+            ident: ident_k,
+            items: items_k,
+        })
+    })
+}
+
+pub fn ast_single<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<AstSingle<'a>> {
+    c.memo(|m| &mut m.ast_single, |c| {
+        c.sp();
+        let annots_k = annots(c)?;
+        c.sp();
+        let ident_k = c.ident()?;
+        c.sp();
+        c.chr('(')?;
+        c.sp();
+        let tokens_k = token_list(c)?;
+        c.sp();
+        c.chr(')')?;
+        Ok(AstSingle {
+            annots: annots_k,
+            ident: ident_k,
+            tokens: tokens_k,
+        })
+    })
+}
+
+pub fn comment<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Comment<'a>> {
+    c.memo(|m| &mut m.comment, |c| {
+        c.sp();
+        c.tag("(*")?;
+        let comment_k = c.until(|c| c.tag("*)"))?;
+        c.sp();
+        c.tag("*)")?;
+        Ok(Comment {
+            comment: std::str::from_utf8(comment_k).unwrap(),
+        })
+    })
+}
+
+pub fn list<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<List<'a>> {
+    c.memo(|m| &mut m.list, |c| {
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            let annots_k = annots(c)?;
+            c.sp();
+            let ident_k = c.ident()?;
+            c.sp();
+            c.chr('[')?;
+            c.sp();
+            c.chr(']')?;
+            c.sp();
+            let sep_k = c.ident()?;
+            c.sp();
+            let reference_k = c.ident()?;
+            Ok(List::ListSingleItem(ListSingle {
+                annots: annots_k,
+                ident: ident_k,
+                sep: sep_k,
+                reference: reference_k,
+            }))
+        }) {
+            return Ok(res);
+        }
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            let annots_k = annots(c)?;
+            c.sp();
+            let ident_k = c.ident()?;
+            c.sp();
+            c.chr(':')?;
+            c.sp();
+            let ast_type_k = c.ident()?;
+            c.sp();
+            c.chr('[')?;
+            c.sp();
+            c.chr(']')?;
+            let sep_k = c.opt(|c| {
+                c.sp();
+                c.ident()
+            })?;
+            c.sp();
+            c.chr('{')?;
+            c.sp();
+            let items_k = list_items(c)?;
+            c.sp();
+            c.chr('}')?;
+            Ok(List::ListManyItem(ListMany {
+                annots: annots_k,
+                ident: ident_k,
+                ast_type: ast_type_k,
+                sep: sep_k,
+                items: items_k,
+            }))
+        }) {
+            return Ok(res);
+        }
+        c.fail()
+    })
+}
+
+pub fn list_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<ListItem<'a>> {
+    c.memo(|m| &mut m.list_item, |c| {
+        c.sp();
+        let ast_item_k = ast_item(c)?;
+        let sep_k = c.opt(|c| {
+            c.sp();
+            c.ident()
+        })?;
+        Ok(ListItem {
+            ast_item: ast_item_k,
+            sep: sep_k,
+        })
+    })
+}
+
+pub fn source<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Source<'a>> {
+    c.memo(|m| &mut m.source, |c| {
+        c.sp();
+        let items_k = source_items(c)?;
+        Ok(Source {
+            items: items_k,
+        })
+    })
+}
+
+pub fn token_type<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<TokenType<'a>> {
+    c.memo(|m| &mut m.token_type, |c| {
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            let ident_k = c.ident()?;
+            c.sp();
+            c.chr('(')?;
+            c.sp();
+            let fn_args_k = fn_args(c)?;
+            c.sp();
+            c.chr(')')?;
+            Ok(TokenType::FuncTokenItem(FuncToken {
+                ident: ident_k,
+                fn_args: fn_args_k,
+            }))
+        }) {
+            return Ok(res);
+        }
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            let key_k = c.ident()?;
+            Ok(TokenType::KeyTokenItem(KeyToken {
+                key: key_k,
+            }))
+        }) {
+            return Ok(res);
+        }
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            let string_k = c.string()?;
+            Ok(TokenType::QuotedItem(Quoted {
+                string: string_k,
+            }))
+        }) {
+            return Ok(res);
+        }
+        c.fail()
+    })
+}
+
+pub fn annot_arg_list<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Vec<AnnotArg<'a>>> {
+    c.memo(|m| &mut m.annot_arg_list, |c| {
+        c.separated_list(|c| c.chr(','), annot_arg_list_item)
+    })
+}
+
+fn annot_arg_list_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<AnnotArg<'a>> {
+    c.sp();
+    let key_k = c.ident()?;
+    c.sp();
+    c.chr('=')?;
+    c.sp();
+    let annot_arg_val_k = annot_arg_val(c)?;
+    Ok(AnnotArg {
+        key: key_k,
+        annot_arg_val: annot_arg_val_k,
+    })
+}
+
+pub fn annots<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Vec<Annotation<'a>>> {
+    c.memo(|m| &mut m.annots, |c| {
+        c.many0(annots_item)
+    })
+}
+
+fn annots_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Annotation<'a>> {
+    annotation(c)
+}
+
+pub fn ast_items<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Vec<AstItem<'a>>> {
+    c.memo(|m| &mut m.ast_items, |c| {
+        c.separated_list(|c| c.chr(','), ast_items_item)
+    })
+}
+
+fn ast_items_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<AstItem<'a>> {
+    ast_item(c)
+}
+
+pub fn fn_args<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Vec<FuncArg<'a>>> {
+    c.memo(|m| &mut m.fn_args, |c| {
+        c.separated_list(|c| c.chr(','), fn_args_item)
+    })
+}
+
+fn fn_args_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<FuncArg<'a>> {
+    c.sp();
+    let string_k = c.string()?;
+    Ok(FuncArg::QuotedItem(Quoted {
+        string: string_k,
+    }))
+}
+
+pub fn list_items<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Vec<ListItem<'a>>> {
+    c.memo(|m| &mut m.list_items, |c| {
+        c.separated_list(|c| c.chr(','), list_items_item)
+    })
+}
+
+fn list_items_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<ListItem<'a>> {
+    list_item(c)
+}
+
+pub fn source_items<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Vec<SourceItem<'a>>> {
+    c.memo(|m| &mut m.source_items, |c| {
+        c.many0(source_items_item)
+    })
+}
+
+fn source_items_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<SourceItem<'a>> {
+    if let Some(res) = c.attempt(|c| ast_single(c).map(|node| SourceItem::AstSingleItem(node))) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| ast_many(c).map(|node| SourceItem::AstManyItem(node))) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| list(c).map(|node| SourceItem::ListItem(node))) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| comment(c).map(|node| SourceItem::CommentItem(node))) {
+        return Ok(res);
+    }
+    c.fail()
+}
+
+pub fn token_list<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Vec<Token<'a>>> {
+    c.memo(|m| &mut m.token_list, |c| {
+        c.many0(token_list_item)
+    })
+}
+
+fn token_list_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Token<'a>> {
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        let annots_k = annots(c)?;
+        c.sp();
+        let name_k = c.ident()?;
+        c.sp();
+        c.chr(':')?;
+        let not_k = c.opt(|c| {
+            c.sp();
+            c.chr('!')
+        })?;
+        c.sp();
+        let token_type_k = token_type(c)?;
+        let optional_k = c.opt(|c| {
+            c.sp();
+            c.chr('?')
+        })?;
+        Ok(Token::NamedTokenItem(NamedToken {
+            annots: annots_k,
+            name: name_k,
+            not: not_k.is_some(),
+            token_type: token_type_k,
+            optional: optional_k.is_some(),
+        }))
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        let annots_k = annots(c)?;
+        let not_k = c.opt(|c| {
+            c.sp();
+            c.chr('!')
+        })?;
+        c.sp();
+        let token_type_k = token_type(c)?;
+        let optional_k = c.opt(|c| {
+            c.sp();
+            c.chr('?')
+        })?;
+        Ok(Token::SimpleTokenItem(SimpleToken {
+            annots: annots_k,
+            not: not_k.is_some(),
+            token_type: token_type_k,
+            optional: optional_k.is_some(),
+        }))
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        let annots_k = annots(c)?;
+        let not_k = c.opt(|c| {
+            c.sp();
+            c.chr('!')
+        })?;
+        c.sp();
+        c.chr('(')?;
+        c.sp();
+        let token_list_k = token_list(c)?;
+        c.sp();
+        c.chr(')')?;
+        let optional_k = c.opt(|c| {
+            c.sp();
+            c.chr('?')
+        })?;
+        Ok(Token::TokenGroupItem(TokenGroup {
+            annots: annots_k,
+            not: not_k.is_some(),
+            token_list: token_list_k,
+            optional: optional_k.is_some(),
+        }))
+    }) {
+        return Ok(res);
+    }
+    c.fail()
+}
+
@@ -0,0 +1,173 @@
+#[derive(Debug, Clone)]
+pub struct Call<'a> {
+    pub atom: Atom<'a>,
+    pub args: Vec<Expr<'a>>,
+}
+
+#[allow(dead_code)]
+impl<'a> Call<'a> {
+    pub fn new(atom: Atom<'a>, args: Vec<Expr<'a>>) -> Call<'a> {
+        Call {
+            atom,
+            args
+        }
+    }
+
+    pub fn as_expr(self) -> Expr<'a> {
+        Expr::CallItem(Box::new(self))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Field<'a> {
+    pub atom: Atom<'a>,
+    pub ident: &'a str,
+}
+
+#[allow(dead_code)]
+impl<'a> Field<'a> {
+    pub fn new(atom: Atom<'a>, ident: &'a str) -> Field<'a> {
+        Field {
+            atom,
+            ident
+        }
+    }
+
+    pub fn as_expr(self) -> Expr<'a> {
+        Expr::FieldItem(Box::new(self))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Index<'a> {
+    pub atom: Atom<'a>,
+    pub expr: Expr<'a>,
+}
+
+#[allow(dead_code)]
+impl<'a> Index<'a> {
+    pub fn new(atom: Atom<'a>, expr: Expr<'a>) -> Index<'a> {
+        Index {
+            atom,
+            expr
+        }
+    }
+
+    pub fn as_expr(self) -> Expr<'a> {
+        Expr::IndexItem(Box::new(self))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Num {
+    pub int: u32,
+}
+
+#[allow(dead_code)]
+impl Num {
+    pub fn new(int: u32) -> Num {
+        Num {
+            int
+        }
+    }
+
+    pub fn as_atom<'a>(self) -> Atom<'a> {
+        Atom::NumItem(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Paren<'a> {
+    pub expr: Expr<'a>,
+}
+
+#[allow(dead_code)]
+impl<'a> Paren<'a> {
+    pub fn new(expr: Expr<'a>) -> Paren<'a> {
+        Paren {
+            expr
+        }
+    }
+
+    pub fn as_atom(self) -> Atom<'a> {
+        Atom::ParenItem(Box::new(self))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Source<'a> {
+    pub expr: Expr<'a>,
+}
+
+#[allow(dead_code)]
+impl<'a> Source<'a> {
+    pub fn new(expr: Expr<'a>) -> Source<'a> {
+        Source {
+            expr
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Var<'a> {
+    pub ident: &'a str,
+}
+
+#[allow(dead_code)]
+impl<'a> Var<'a> {
+    pub fn new(ident: &'a str) -> Var<'a> {
+        Var {
+            ident
+        }
+    }
+
+    pub fn as_atom(self) -> Atom<'a> {
+        Atom::VarItem(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Atom<'a> {
+    ParenItem(Box<Paren<'a>>),
+    NumItem(Num),
+    VarItem(Var<'a>),
+}
+
+#[allow(dead_code)]
+impl<'a> Atom<'a> {
+    pub fn paren(expr: Expr<'a>) -> Atom<'a> {
+        Atom::ParenItem(Box::new(Paren::new(expr)))
+    }
+
+    pub fn num(int: u32) -> Atom<'a> {
+        Atom::NumItem(Num::new(int))
+    }
+
+    pub fn var(ident: &'a str) -> Atom<'a> {
+        Atom::VarItem(Var::new(ident))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr<'a> {
+    CallItem(Box<Call<'a>>),
+    IndexItem(Box<Index<'a>>),
+    FieldItem(Box<Field<'a>>),
+    AtomItem(Box<Atom<'a>>),
+}
+
+#[allow(dead_code)]
+impl<'a> Expr<'a> {
+    pub fn call(atom: Atom<'a>, args: Vec<Expr<'a>>) -> Expr<'a> {
+        Expr::CallItem(Box::new(Call::new(atom, args)))
+    }
+
+    pub fn index(atom: Atom<'a>, expr: Expr<'a>) -> Expr<'a> {
+        Expr::IndexItem(Box::new(Index::new(atom, expr)))
+    }
+
+    pub fn field(atom: Atom<'a>, ident: &'a str) -> Expr<'a> {
+        Expr::FieldItem(Box::new(Field::new(atom, ident)))
+    }
+}
+
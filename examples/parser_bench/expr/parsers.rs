@@ -0,0 +1,72 @@
+#[allow(unused_imports)]
+use descr_common::parsers::*;
+extern crate nom;
+use self::nom::*;
+#[allow(unused_imports)]
+use std;
+use super::ast::*;
+
+named!(pub start<Source>, do_parse!(res: source >> (res)));
+
+named!(pub atom<Atom>, alt_complete!(
+    do_parse!(
+        sp >> char!('(') >>
+        sp >> expr_k: expr >>
+        sp >> char!(')') >>
+        (Atom::ParenItem(Box::new(Paren {
+            expr: expr_k,
+        }))))
+    | do_parse!(
+        sp >> int_k: parse_int >>
+        (Atom::NumItem(Num {
+            int: int_k,
+        })))
+    | do_parse!(
+        sp >> ident_k: ident >>
+        (Atom::VarItem(Var {
+            ident: ident_k,
+        })))
+));
+
+named!(pub expr<Expr>, alt_complete!(
+    do_parse!(
+        sp >> atom_k: atom >>
+        sp >> char!('(') >>
+        sp >> args_k: args >>
+        sp >> char!(')') >>
+        (Expr::CallItem(Box::new(Call {
+            atom: atom_k,
+            args: args_k,
+        }))))
+    | do_parse!(
+        sp >> atom_k: atom >>
+        sp >> char!('[') >>
+        sp >> expr_k: expr >>
+        sp >> char!(']') >>
+        (Expr::IndexItem(Box::new(Index {
+            atom: atom_k,
+            expr: expr_k,
+        }))))
+    | do_parse!(
+        sp >> atom_k: atom >>
+        sp >> char!('.') >>
+        sp >> ident_k: ident >>
+        (Expr::FieldItem(Box::new(Field {
+            atom: atom_k,
+            ident: ident_k,
+        }))))
+    | map!(atom, |node| { Expr::AtomItem(Box::new(node)) })
+));
+
+named!(pub source<Source>,
+    do_parse!(
+        sp >> expr_k: expr >>
+        (Source {
+            expr: expr_k,
+        }))
+);
+
+named!(pub args<Vec<Expr>>, separated_list!(char!(','), 
+    expr
+));
+
@@ -0,0 +1,119 @@
+#![allow(unused_variables)]
+#[allow(unused_imports)]
+use descr_common::cursor::*;
+use super::ast::*;
+
+/// Parses all of input, trailing
+/// whitespace is allowed
+pub fn parse<'a>(input: &'a [u8]) -> Result<Source<'a>, ParseError> {
+    ::descr_common::cursor::parse(input, start)
+}
+
+pub fn start<'a>(c: &mut Cursor<'a>) -> PResult<Source<'a>> {
+    source(c)
+}
+
+pub fn atom<'a>(c: &mut Cursor<'a>) -> PResult<Atom<'a>> {
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        c.chr('(')?;
+        c.sp();
+        let expr_k = expr(c)?;
+        c.sp();
+        c.chr(')')?;
+        Ok(Atom::ParenItem(Box::new(Paren {
+            expr: expr_k,
+        })))
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        let int_k = c.int()?;
+        Ok(Atom::NumItem(Num {
+            int: int_k,
+        }))
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        let ident_k = c.ident()?;
+        Ok(Atom::VarItem(Var {
+            ident: ident_k,
+        }))
+    }) {
+        return Ok(res);
+    }
+    c.fail()
+}
+
+pub fn expr<'a>(c: &mut Cursor<'a>) -> PResult<Expr<'a>> {
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        let atom_k = atom(c)?;
+        c.sp();
+        c.chr('(')?;
+        c.sp();
+        let args_k = args(c)?;
+        c.sp();
+        c.chr(')')?;
+        Ok(Expr::CallItem(Box::new(Call {
+            atom: atom_k,
+            args: args_k,
+        })))
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        let atom_k = atom(c)?;
+        c.sp();
+        c.chr('[')?;
+        c.sp();
+        let expr_k = expr(c)?;
+        c.sp();
+        c.chr(']')?;
+        Ok(Expr::IndexItem(Box::new(Index {
+            atom: atom_k,
+            expr: expr_k,
+        })))
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        let atom_k = atom(c)?;
+        c.sp();
+        c.chr('.')?;
+        c.sp();
+        let ident_k = c.ident()?;
+        Ok(Expr::FieldItem(Box::new(Field {
+            atom: atom_k,
+            ident: ident_k,
+        })))
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| atom(c).map(|node| Expr::AtomItem(Box::new(node)))) {
+        return Ok(res);
+    }
+    c.fail()
+}
+
+pub fn source<'a>(c: &mut Cursor<'a>) -> PResult<Source<'a>> {
+    c.sp();
+    let expr_k = expr(c)?;
+    Ok(Source {
+        expr: expr_k,
+    })
+}
+
+pub fn args<'a>(c: &mut Cursor<'a>) -> PResult<Vec<Expr<'a>>> {
+    c.separated_list(|c| c.chr(','), args_item)
+}
+
+fn args_item<'a>(c: &mut Cursor<'a>) -> PResult<Expr<'a>> {
+    expr(c)
+}
+
@@ -0,0 +1,90 @@
+#![allow(unused_variables)]
+#[allow(unused_imports)]
+use descr_common::cursor::*;
+use super::ast::*;
+
+/// Parses all of input, trailing
+/// whitespace is allowed
+pub fn parse<'a>(input: &'a [u8]) -> Result<JsObject, ParseError> {
+    ::descr_common::cursor::parse(input, start)
+}
+
+pub fn start<'a>(c: &mut Cursor<'a>) -> PResult<JsObject> {
+    js_object(c)
+}
+
+pub fn js_object<'a>(c: &mut Cursor<'a>) -> PResult<JsObject> {
+    c.sp();
+    c.chr('{')?;
+    c.sp();
+    let items_k = object_pairs(c)?;
+    c.sp();
+    c.chr('}')?;
+    Ok(JsObject {
+        items: items_k,
+    })
+}
+
+pub fn js_val<'a>(c: &mut Cursor<'a>) -> PResult<JsVal> {
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        let int_k = c.int()?;
+        Ok(JsVal::IntItem(Int {
+            int: int_k,
+        }))
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        let string_k = c.string()?;
+        Ok(JsVal::StringValItem(StringVal {
+            string: String::from(string_k),
+        }))
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| {
+        c.sp();
+        c.chr('[')?;
+        c.sp();
+        let items_k = array_vals(c)?;
+        c.sp();
+        c.chr(']')?;
+        Ok(JsVal::ArrayValItem(Box::new(ArrayVal {
+            items: items_k,
+        })))
+    }) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| js_object(c).map(|node| JsVal::JsObjectItem(Box::new(node)))) {
+        return Ok(res);
+    }
+    c.fail()
+}
+
+pub fn array_vals<'a>(c: &mut Cursor<'a>) -> PResult<Vec<JsVal>> {
+    c.separated_list(|c| c.chr(','), array_vals_item)
+}
+
+fn array_vals_item<'a>(c: &mut Cursor<'a>) -> PResult<JsVal> {
+    js_val(c)
+}
+
+pub fn object_pairs<'a>(c: &mut Cursor<'a>) -> PResult<Vec<ObjectPair>> {
+    c.separated_list(|c| c.chr(','), object_pairs_item)
+}
+
+fn object_pairs_item<'a>(c: &mut Cursor<'a>) -> PResult<ObjectPair> {
+    c.sp();
+    let key_k = c.string()?;
+    c.sp();
+    c.chr(':')?;
+    c.sp();
+    let val_k = js_val(c)?;
+    Ok(ObjectPair {
+        key: String::from(key_k),
+        val: val_k,
+    })
+}
+
@@ -0,0 +1,115 @@
+//! Compares the nom and recursive descent backends,
+//! with and without memoization, on samples of the
+//! json, rust, descr and expr grammars. The modules
+//! are generated without flags, with --rd and with
+//! --memo. In expr.lang, alternatives start with
+//! the same nested rule, which is parsed again for
+//! each alternative without memoization. The
+//! descr.lang row parses the grammar file itself,
+//! where AstItem alternatives reparse annotations
+//! and token lists.
+//!
+//!     cargo run --release --example parser_bench
+extern crate nom;
+#[macro_use]
+extern crate descr_common;
+extern crate descr_gen;
+
+#[allow(warnings, clippy::all)]
+mod json;
+#[allow(warnings, clippy::all)]
+mod rust;
+#[allow(warnings, clippy::all)]
+mod descr;
+#[allow(warnings, clippy::all)]
+mod expr;
+
+use std::fs;
+use std::time::Instant;
+use descr_gen::generator::{build_data, parse_grammar};
+use descr_gen::lang_data::data::LangData;
+use descr_gen::samples::{SampleOptions, Sampler};
+
+const ROUNDS: usize = 20;
+
+fn samples(path: &str, name: &str) -> Vec<String> {
+    let grammar = fs::read(path).unwrap();
+    let ast = parse_grammar(&grammar).unwrap();
+    let mut data = LangData::new(false, name.to_string());
+    build_data(&grammar, &ast, &mut data).unwrap();
+    let mut options = SampleOptions::new();
+    options.seed = 7;
+    let mut samples = Sampler::new(&data, options).samples(500);
+    // The grammar file is a sample of descr
+    if name == "Descr" {
+        samples.push(String::from_utf8(grammar).unwrap());
+    }
+    // Nested parens, where each level is
+    // parsed for each alternative
+    if name == "Expr" {
+        for depth in 1..9 {
+            samples.push(format!("{}f(x)[1].y{}", "(".repeat(depth), ")".repeat(depth)));
+        }
+    }
+    samples
+}
+
+fn mb_per_sec(bytes: usize, start: Instant) -> f64 {
+    let elapsed = start.elapsed();
+    let secs = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) / 1e9;
+    (bytes * ROUNDS) as f64 / secs / 1e6
+}
+
+macro_rules! time {
+    ($samples:expr, $bytes:expr, $parse:expr) => {{
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            for sample in $samples {
+                let _ = $parse(sample.as_bytes());
+            }
+        }
+        mb_per_sec($bytes, start)
+    }};
+}
+
+macro_rules! bench {
+    ($name:expr, $samples:expr, $lang:ident) => {{
+        let samples = $samples;
+        let bytes = samples.iter().map(|s| s.len()).sum::<usize>();
+        for sample in &samples {
+            let nom_res = match $lang::parsers::start(sample.as_bytes()) {
+                nom::IResult::Done(_, res) => Some(format!("{:?}", res)),
+                _ => None,
+            };
+            let rd_res = $lang::rd_parsers::parse(sample.as_bytes())
+                .ok()
+                .map(|res| format!("{:?}", res));
+            let memo_res = $lang::memo_parsers::parse(sample.as_bytes())
+                .ok()
+                .map(|res| format!("{:?}", res));
+            if nom_res != rd_res || rd_res != memo_res {
+                panic!("{}: backends differ on:\n{}", $name, sample);
+            }
+        }
+        let nom_speed = time!(&samples, bytes, $lang::parsers::start);
+        let rd_speed = time!(&samples, bytes, $lang::rd_parsers::parse);
+        let memo_speed = time!(&samples, bytes, $lang::memo_parsers::parse);
+        println!(
+            "{:<10} {:>4} samples {:>8} bytes  nom {:>7.2} MB/s  rd {:>7.2} MB/s  memo {:>7.2} MB/s",
+            $name,
+            samples.len(),
+            bytes,
+            nom_speed,
+            rd_speed,
+            memo_speed
+        );
+    }};
+}
+
+fn main() {
+    bench!("Json", samples("langs/json-descr/json.lang", "Json"), json);
+    bench!("Rust", samples("langs/rust-descr/rust.lang", "Rust"), rust);
+    bench!("Descr", samples("descr.lang", "Descr"), descr);
+    bench!("descr.lang", vec![fs::read_to_string("descr.lang").unwrap(); 100], descr);
+    bench!("Expr", samples("examples/parser_bench/expr.lang", "Expr"), expr);
+}
@@ -0,0 +1,293 @@
+#![allow(unused_variables)]
+#[allow(unused_imports)]
+use descr_common::cursor::*;
+use super::ast::*;
+
+/// Results of memoized rules by position
+#[derive(Default)]
+pub struct Memo<'a> {
+    pub generic: MemoTable<Generic<'a>>,
+    pub generic_item: MemoTable<GenericItem<'a>>,
+    pub life_time: MemoTable<LifeTime<'a>>,
+    pub rs_enum: MemoTable<RsEnum<'a>>,
+    pub rs_struct: MemoTable<RsStruct<'a>>,
+    pub rs_trait: MemoTable<RsTrait<'a>>,
+    pub source: MemoTable<Source<'a>>,
+    pub tpe: MemoTable<Tpe>,
+    pub tpe_spes: MemoTable<TpeSpes<'a>>,
+    pub enum_items: MemoTable<Vec<EnumItem<'a>>>,
+    pub generic_items: MemoTable<Vec<GenericItem<'a>>>,
+    pub source_items: MemoTable<Vec<SourceItem<'a>>>,
+    pub struct_members: MemoTable<Vec<StructMember<'a>>>,
+    input: ::std::marker::PhantomData<&'a [u8]>,
+}
+
+/// Results are kept on backtrack,
+/// they don't depend on the path
+impl<'a> State for Memo<'a> {
+    type Mark = ();
+    fn mark(&self) {}
+    fn reset(&mut self, _mark: ()) {}
+}
+
+/// Parses all of input, trailing
+/// whitespace is allowed
+pub fn parse<'a>(input: &'a [u8]) -> Result<Source<'a>, ParseError> {
+    ::descr_common::cursor::parse_with(input, Memo::default(), start)
+}
+
+pub fn start<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Source<'a>> {
+    source(c)
+}
+
+pub fn generic<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Generic<'a>> {
+    c.memo(|m| &mut m.generic, |c| {
+        c.sp();
+        c.tag("<")?;
+        c.sp();
+        let generic_items_k = generic_items(c)?;
+        c.sp();
+        c.tag(">")?;
+        Ok(Generic {
+            generic_items: generic_items_k,
+        })
+    })
+}
+
+pub fn generic_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<GenericItem<'a>> {
+    c.memo(|m| &mut m.generic_item, |c| {
+        if let Some(res) = c.attempt(|c| life_time(c).map(|node| GenericItem::LifeTimeItem(node))) {
+            return Ok(res);
+        }
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            let ident_k = c.ident()?;
+            Ok(GenericItem::GenTypeItem(GenType {
+                ident: ident_k,
+            }))
+        }) {
+            return Ok(res);
+        }
+        c.fail()
+    })
+}
+
+pub fn life_time<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<LifeTime<'a>> {
+    c.memo(|m| &mut m.life_time, |c| {
+        c.sp();
+        c.tag("'")?;
+        c.sp();
+        let ident_k = c.ident()?;
+        Ok(LifeTime {
+            ident: ident_k,
+        })
+    })
+}
+
+pub fn rs_enum<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<RsEnum<'a>> {
+    c.memo(|m| &mut m.rs_enum, |c| {
+        let public_k = c.opt(|c| {
+            c.sp();
+            c.tag("pub")
+        })?;
+        c.sp();
+        c.tag("enum")?;
+        c.sp();
+        let ident_k = c.ident()?;
+        c.sp();
+        c.chr('{')?;
+        c.sp();
+        let enum_items_k = enum_items(c)?;
+        c.sp();
+        c.chr('}')?;
+        Ok(RsEnum {
+            public: public_k.is_some(),
+            ident: ident_k,
+            enum_items: enum_items_k,
+        })
+    })
+}
+
+pub fn rs_struct<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<RsStruct<'a>> {
+    c.memo(|m| &mut m.rs_struct, |c| {
+        let public_k = c.opt(|c| {
+            c.sp();
+            c.tag("pub")
+        })?;
+        c.sp();
+        c.tag("struct")?;
+        c.sp();
+        let ident_k = c.ident()?;
+        let generic_k = c.opt(|c| {
+            c.sp();
+            generic(c)
+        })?;
+        c.sp();
+        c.chr('{')?;
+        c.sp();
+        let struct_members_k = struct_members(c)?;
+        c.sp();
+        c.chr('}')?;
+        Ok(RsStruct {
+            public: public_k.is_some(),
+            ident: ident_k,
+            generic: generic_k,
+            struct_members: struct_members_k,
+        })
+    })
+}
+
+pub fn rs_trait<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<RsTrait<'a>> {
+    c.memo(|m| &mut m.rs_trait, |c| {
+        let public_k = c.opt(|c| {
+            c.sp();
+            c.tag("pub")
+        })?;
+        c.sp();
+        c.tag("trait")?;
+        c.sp();
+        let ident_k = c.ident()?;
+        c.sp();
+        c.chr('{')?;
+        c.sp();
+        c.chr('}')?;
+        Ok(RsTrait {
+            public: public_k.is_some(),
+            ident: ident_k,
+        })
+    })
+}
+
+pub fn source<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Source<'a>> {
+    c.memo(|m| &mut m.source, |c| {
+        c.sp();
+        let source_items_k = source_items(c)?;
+        Ok(Source {
+            source_items: source_items_k,
+        })
+    })
+}
+
+pub fn tpe<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Tpe> {
+    c.memo(|m| &mut m.tpe, |c| {
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            c.tag("u32")?;
+            Ok(Tpe::RsU32)
+        }) {
+            return Ok(res);
+        }
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            c.tag("i32")?;
+            Ok(Tpe::RsI32)
+        }) {
+            return Ok(res);
+        }
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            c.tag("String")?;
+            Ok(Tpe::RsString)
+        }) {
+            return Ok(res);
+        }
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            c.tag("str")?;
+            Ok(Tpe::RsStr)
+        }) {
+            return Ok(res);
+        }
+        if let Some(res) = c.attempt(|c| {
+            c.sp();
+            c.tag("bool")?;
+            Ok(Tpe::Bool)
+        }) {
+            return Ok(res);
+        }
+        c.fail()
+    })
+}
+
+pub fn tpe_spes<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<TpeSpes<'a>> {
+    c.memo(|m| &mut m.tpe_spes, |c| {
+        c.sp();
+        let tpe_k = tpe(c)?;
+        let generic_item_k = c.opt(|c| {
+            c.sp();
+            generic_item(c)
+        })?;
+        Ok(TpeSpes {
+            tpe: tpe_k,
+            generic_item: generic_item_k,
+        })
+    })
+}
+
+pub fn enum_items<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Vec<EnumItem<'a>>> {
+    c.memo(|m| &mut m.enum_items, |c| {
+        c.separated_list(|c| c.chr(','), enum_items_item)
+    })
+}
+
+fn enum_items_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<EnumItem<'a>> {
+    c.sp();
+    let ident_k = c.ident()?;
+    Ok(EnumItem {
+        ident: ident_k,
+    })
+}
+
+pub fn generic_items<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Vec<GenericItem<'a>>> {
+    c.memo(|m| &mut m.generic_items, |c| {
+        c.separated_list(|c| c.chr(','), generic_items_item)
+    })
+}
+
+fn generic_items_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<GenericItem<'a>> {
+    generic_item(c)
+}
+
+pub fn source_items<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Vec<SourceItem<'a>>> {
+    c.memo(|m| &mut m.source_items, |c| {
+        c.many0(source_items_item)
+    })
+}
+
+fn source_items_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<SourceItem<'a>> {
+    if let Some(res) = c.attempt(|c| rs_struct(c).map(|node| SourceItem::RsStructItem(node))) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| rs_enum(c).map(|node| SourceItem::RsEnumItem(node))) {
+        return Ok(res);
+    }
+    if let Some(res) = c.attempt(|c| rs_trait(c).map(|node| SourceItem::RsTraitItem(node))) {
+        return Ok(res);
+    }
+    c.fail()
+}
+
+pub fn struct_members<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<Vec<StructMember<'a>>> {
+    c.memo(|m| &mut m.struct_members, |c| {
+        c.separated_list(|c| c.chr(','), struct_members_item)
+    })
+}
+
+fn struct_members_item<'a>(c: &mut Cursor<'a, Memo<'a>>) -> PResult<StructMember<'a>> {
+    let public_k = c.opt(|c| {
+        c.sp();
+        c.tag("pub")
+    })?;
+    c.sp();
+    let ident_k = c.ident()?;
+    c.sp();
+    c.chr(':')?;
+    c.sp();
+    let tpe_spes_k = tpe_spes(c)?;
+    Ok(StructMember {
+        public: public_k.is_some(),
+        ident: ident_k,
+        tpe_spes: tpe_spes_k,
+    })
+}
+
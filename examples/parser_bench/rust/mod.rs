@@ -0,0 +1,4 @@
+pub mod ast;
+pub mod parsers;
+pub mod rd_parsers;
+pub mod memo_parsers;
@@ -1,4 +0,0 @@
-pub mod ast;
-pub mod parsers;
-pub mod visitor;
-pub mod to_source;
\ No newline at end of file
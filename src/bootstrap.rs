@@ -0,0 +1,89 @@
+//! Checks that descr-lang is self-hosting. Stage 1
+//! is generated from descr.lang, then a generator
+//! built with stage 1 parses descr.lang and
+//! generates stage 2, which should be the same.
+//! Also checks that descr-lang/src/gen is stage 1,
+//! or with `--write`, writes stage 1 there.
+//! Runs from the root of the descr checkout.
+use std::fs;
+use std::path::Path;
+use std::process;
+use descr_gen::bootstrap::{self, descr_lang_outputs, diff_outputs};
+use descr_gen::process::write_file;
+
+pub fn usage() {
+    eprintln!("Usage: descr bootstrap-check [work-dir] [--write]");
+    eprintln!("    Stage 1 is built in work-dir, target/bootstrap by default");
+    eprintln!("    With --write, stage 1 is written to descr-lang/src/gen");
+}
+
+/// Writes the changed outputs of stage 1
+/// to descr-lang
+fn write_stage1(root: &Path) -> Result<(), String> {
+    let source = fs::read(root.join("descr.lang")).map_err(|err| format!("Could not read descr.lang: {}", err))?;
+    let outputs = descr_lang_outputs(&source).map_err(|err| format!("descr.lang: {}", err))?;
+    let gen_dir = root.join(bootstrap::GEN_DIR).to_string_lossy().into_owned();
+    for (name, content) in outputs {
+        if write_file(&gen_dir, &name, &content).map_err(|err| format!("Could not write {}: {}", name, err))? {
+            println!("Wrote {}/{}", bootstrap::GEN_DIR, name);
+        }
+    }
+    Ok(())
+}
+
+pub fn bootstrap_check(args: &[String]) {
+    let write = args.iter().any(|arg| arg == "--write");
+    let paths = args.iter().filter(|arg| *arg != "--write").collect::<Vec<_>>();
+    if paths.len() > 1 || paths.iter().any(|arg| arg.starts_with("--")) {
+        usage();
+        process::exit(2);
+    }
+    let root = Path::new(".");
+    if !root.join("descr.lang").exists() {
+        eprintln!("descr.lang not found, run from the descr checkout");
+        process::exit(2);
+    }
+    if write {
+        if let Err(err) = write_stage1(root) {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+        return;
+    }
+    let work_dir = Path::new(paths.first().map(|arg| arg.as_str()).unwrap_or("target/bootstrap"));
+    println!("Building stage 1 in {}..", work_dir.display());
+    let stages = match bootstrap::run(root, work_dir) {
+        Ok(stages) => stages,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
+    println!("Stage 1 parsed descr.lang and generated stage 2");
+    let mut failed = false;
+    match stages.diff() {
+        Some(diff) => {
+            print!("{}", diff);
+            eprintln!("Stage 2 differs from stage 1");
+            failed = true;
+        }
+        None => println!("Stage 2 is the same as stage 1"),
+    }
+    match bootstrap::checked_in(root) {
+        Ok(checked_in) => match diff_outputs(bootstrap::GEN_DIR, &checked_in, "stage1", &stages.stage1) {
+            Some(diff) => {
+                print!("{}", diff);
+                eprintln!("{} differs from stage 1, run descr bootstrap-check --write", bootstrap::GEN_DIR);
+                failed = true;
+            }
+            None => println!("{} is the same as stage 1", bootstrap::GEN_DIR),
+        },
+        Err(err) => {
+            eprintln!("Could not read {}: {}", bootstrap::GEN_DIR, err);
+            failed = true;
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
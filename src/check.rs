@@ -0,0 +1,63 @@
+//! Checks that grammars parse and that their
+//! rules and tokens resolve, without writing
+//! anything. Backends chosen with the lang
+//! flags are also generated, and their outputs
+//! listed. Exits with an error when any
+//! grammar has errors.
+use std::fs;
+use std::path::Path;
+use std::process;
+use descr_gen::generator::{build_data, parse_grammar};
+use descr_gen::lang_data::data::LangData;
+use options::{self, parse_args, LangFlags};
+
+pub fn usage() {
+    eprintln!("Usage: descr check grammar-file..");
+    eprintln!("{}", options::USAGE);
+}
+
+/// Rules and outputs of grammar,
+/// or the error message
+fn check_grammar(grammar_path: &Path, flags: &LangFlags) -> Result<(usize, Vec<String>), String> {
+    let grammar = fs::read(grammar_path).map_err(|err| format!("Could not read: {}", err))?;
+    let ast = parse_grammar(&grammar).map_err(|err| err.to_string())?;
+    let mut data = LangData::new(false, flags.lang_name(grammar_path));
+    build_data(&grammar, &ast, &mut data).map_err(|err| err.to_string())?;
+    let outputs = if flags.has_backends() {
+        flags.gen_backends(grammar_path, &grammar)?
+    } else {
+        Vec::new()
+    };
+    Ok((data.ast_data.len() + data.list_data.len(), outputs))
+}
+
+pub fn check(args: &[String]) {
+    let (flags, _, paths) = parse_args(args, &[], usage);
+    if paths.is_empty() {
+        usage();
+        process::exit(2);
+    }
+    if flags.name.is_some() && paths.len() > 1 {
+        eprintln!("--name is for a single grammar");
+        process::exit(2);
+    }
+    let mut failed = 0;
+    for path in &paths {
+        match check_grammar(Path::new(path), &flags) {
+            Ok((rules, outputs)) => {
+                println!("{}: ok, {} rules", path, rules);
+                for output in outputs {
+                    println!("    {}", output);
+                }
+            }
+            Err(err) => {
+                eprintln!("{}: {}", path, err);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        eprintln!("{} of {} grammars have errors", failed, paths.len());
+        process::exit(1);
+    }
+}
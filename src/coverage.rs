@@ -0,0 +1,86 @@
+//! Grammar coverage of a corpus. Files are
+//! parsed with the interpreter, and rules,
+//! alternatives, optional parts and separators
+//! that never matched are listed, as text or
+//! as json with `--json`.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use descr_gen::generator::{build_data, parse_grammar};
+use descr_gen::interpreter::{Coverage, Interpreter};
+use descr_gen::lang_data::data::LangData;
+use options::lang_name;
+
+pub fn usage() {
+    eprintln!("Usage: descr coverage grammar-file corpus-dir-or-file.. [--json]");
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+/// Files in dirs, recursively and sorted.
+/// Expected outputs from play are skipped.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        let mut entries = match fs::read_dir(path) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .collect::<Vec<_>>(),
+            Err(err) => exit_with(&format!("Could not read {}: {}", path.display(), err)),
+        };
+        entries.sort();
+        for entry in entries {
+            collect_files(&entry, files);
+        }
+    } else if path.extension().map(|ext| ext != "expected").unwrap_or(true) {
+        files.push(path.to_path_buf());
+    }
+}
+
+pub fn coverage(args: &[String]) {
+    let json = args.iter().any(|arg| arg == "--json");
+    let paths = args.iter().filter(|arg| *arg != "--json").collect::<Vec<_>>();
+    if paths.len() < 2 {
+        usage();
+        process::exit(2);
+    }
+    let grammar_path = Path::new(paths[0]);
+    let grammar = match fs::read(grammar_path) {
+        Ok(grammar) => grammar,
+        Err(err) => exit_with(&format!("Could not read {}: {}", grammar_path.display(), err)),
+    };
+    let ast = match parse_grammar(&grammar) {
+        Ok(ast) => ast,
+        Err(err) => exit_with(&format!("{}: {}", grammar_path.display(), err)),
+    };
+    let mut data = LangData::new(false, lang_name(grammar_path));
+    if let Err(err) = build_data(&grammar, &ast, &mut data) {
+        exit_with(&format!("{}: {}", grammar_path.display(), err));
+    }
+    let mut files = Vec::new();
+    for path in &paths[1..] {
+        collect_files(Path::new(path), &mut files);
+    }
+    let interpreter = Interpreter::new(&data).with_coverage();
+    let mut coverage = Coverage::new(&data);
+    for file in &files {
+        let name = file.display().to_string();
+        match fs::read(file) {
+            Ok(input) => match interpreter.parse_complete(&input) {
+                Ok(_) => coverage.add(&interpreter.hits()),
+                Err(err) => coverage.add_failure(name, err.to_string()),
+            },
+            Err(err) => coverage.add_failure(name, err.to_string()),
+        }
+    }
+    if json {
+        print!("{}", coverage.to_json());
+    } else {
+        print!("{}", coverage.to_text());
+    }
+    if !coverage.failures.is_empty() {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,71 @@
+//! Formats grammars with descr_gen::format,
+//! printing the result. With `--write` files
+//! are formatted in place, and with `--check`
+//! files that are not formatted are listed
+//! and the command fails. Backends chosen with
+//! the lang flags are generated from the
+//! formatted grammar, without writing them, to
+//! check that formatting kept it building.
+use std::fs;
+use std::path::Path;
+use std::process;
+use descr_gen::format::format_grammar;
+use options::{self, parse_args};
+
+pub fn usage() {
+    eprintln!("Usage: descr fmt grammar-file.. [--write | --check]");
+    eprintln!("{}", options::USAGE);
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+pub fn fmt(args: &[String]) {
+    let (flags, found, paths) = parse_args(args, &["--write", "--check"], usage);
+    let write = found.iter().any(|arg| arg == "--write");
+    let check = found.iter().any(|arg| arg == "--check");
+    if paths.is_empty() || (write && check) {
+        usage();
+        process::exit(2);
+    }
+    if flags.name.is_some() && paths.len() > 1 {
+        eprintln!("--name is for a single grammar");
+        process::exit(2);
+    }
+    let mut unformatted = 0;
+    for path in &paths {
+        let path = Path::new(path);
+        let grammar = match fs::read(path) {
+            Ok(grammar) => grammar,
+            Err(err) => exit_with(&format!("Could not read {}: {}", path.display(), err)),
+        };
+        let formatted = match format_grammar(&grammar) {
+            Ok(formatted) => formatted,
+            Err(err) => exit_with(&format!("{}: {}", path.display(), err)),
+        };
+        if flags.has_backends() {
+            if let Err(err) = flags.gen_backends(path, formatted.as_bytes()) {
+                exit_with(&format!("{}: {}", path.display(), err));
+            }
+        }
+        if check {
+            if formatted.as_bytes() != &grammar[..] {
+                println!("{}", path.display());
+                unformatted += 1;
+            }
+        } else if write {
+            if formatted.as_bytes() != &grammar[..] {
+                if let Err(err) = fs::write(path, formatted) {
+                    exit_with(&format!("Could not write {}: {}", path.display(), err));
+                }
+            }
+        } else {
+            print!("{}", formatted);
+        }
+    }
+    if unformatted > 0 {
+        exit_with(&format!("{} files are not formatted", unformatted));
+    }
+}
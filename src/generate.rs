@@ -0,0 +1,212 @@
+//! Generates modules from grammars into output
+//! dirs. The main outputs are chosen with
+//! `--outputs`, a comma separated list of
+//! ast, parsers, visitor, to-source and syntax,
+//! all by default. Other outputs have flags.
+//! With `--watch`, grammars are generated
+//! again when they change. With `--check` and
+//! `--diff` nothing is written, outputs that
+//! differ from the files are listed or diffed.
+//! Otherwise only changed outputs are written.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process;
+use elapsed::measure_time;
+use descr_gen::diff::unified_diff;
+use descr_gen::generator::{GenOptions, Generator};
+use descr_gen::process::write_file;
+use options::{self, value, LangFlags};
+use watch;
+
+pub fn usage() {
+    eprintln!("Usage: descr generate grammar-file output-dir [grammar-file output-dir..]");
+    eprintln!("    [--watch | --check | --diff]");
+    eprintln!("{}", options::USAGE);
+    eprintln!("    [--cst] [--highlight] [--tree-sitter] [--docs] [--lsp]");
+    eprintln!("    [--vscode] [--sublime] [--bench] [--descr-path dir]");
+}
+
+/// Flags of generate, with the lang flags
+/// shared with the other commands
+#[derive(Debug, Default)]
+pub struct Flags {
+    pub lang: LangFlags,
+    pub cst: bool,
+    pub highlight: bool,
+    pub tree_sitter: bool,
+    pub docs: bool,
+    pub lsp: bool,
+    pub vscode: bool,
+    pub sublime: bool,
+    pub bench: bool,
+    /// Descr checkout for the lsp and
+    /// bench crates to depend on by path
+    pub descr_path: Option<String>,
+    pub watch: bool,
+    /// List outputs that differ from
+    /// the files, exiting with 1
+    pub check: bool,
+    /// Print diffs of the files
+    /// to the outputs
+    pub diff: bool,
+    /// Arguments that are not flags
+    pub paths: Vec<String>,
+}
+impl Flags {
+    pub fn parse(args: &[String]) -> Result<Flags, String> {
+        let mut flags = Flags::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if flags.lang.take(arg, &mut args)? {
+                continue;
+            }
+            match arg.as_str() {
+                "--cst" => flags.cst = true,
+                "--highlight" => flags.highlight = true,
+                "--tree-sitter" => flags.tree_sitter = true,
+                "--docs" => flags.docs = true,
+                "--lsp" => flags.lsp = true,
+                "--vscode" => flags.vscode = true,
+                "--sublime" => flags.sublime = true,
+                "--bench" => flags.bench = true,
+                "--descr-path" => flags.descr_path = Some(value(arg, args.next())?),
+                "--watch" => flags.watch = true,
+                "--check" => flags.check = true,
+                "--diff" => flags.diff = true,
+                _ if arg.starts_with("--") => return Err(format!("Unknown flag: {}", arg)),
+                _ => flags.paths.push(arg.clone()),
+            }
+        }
+        if flags.watch && (flags.check || flags.diff) {
+            return Err("--watch can't be used with --check or --diff".to_string());
+        }
+        Ok(flags)
+    }
+
+    /// Whether outputs are compared
+    /// instead of written
+    pub fn dry_run(&self) -> bool {
+        self.check || self.diff
+    }
+
+    pub fn options(&self, grammar_path: &Path) -> GenOptions {
+        let mut options = self.lang.options(grammar_path);
+        options.cst = self.cst;
+        options.highlight = self.highlight;
+        options.tree_sitter = self.tree_sitter;
+        options.docs = self.docs;
+        options.lsp = self.lsp;
+        options.vscode = self.vscode;
+        options.sublime = self.sublime;
+        options.bench = self.bench;
+        options.descr_path = self.descr_path.clone();
+        options
+    }
+}
+
+/// Outputs of grammar by name
+pub fn gen_outputs(grammar_path: &Path, options: GenOptions) -> Result<BTreeMap<String, String>, String> {
+    let grammar = fs::read(grammar_path).map_err(|err| format!("Could not read {}: {}", grammar_path.display(), err))?;
+    Generator::new(options)
+        .generate(&grammar)
+        .map_err(|err| format!("{}: {}", grammar_path.display(), err))
+}
+
+/// Writes the changed outputs of grammar to output
+/// dir, or with --check and --diff, compares them
+/// to the files. Gives whether any output differed,
+/// exits with an error when it can't be generated.
+pub fn run(grammar_path: &Path, output_dir: &str, options: GenOptions, flags: &Flags) -> bool {
+    let (elapsed, res) = measure_time(|| gen_outputs(grammar_path, options));
+    match res {
+        Ok(outputs) => {
+            let changed = apply(output_dir, outputs, flags);
+            if !flags.dry_run() {
+                println!("Process: {}", elapsed);
+            }
+            changed
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Writes the changed outputs, or compares them
+/// with --check and --diff. Gives whether any
+/// output differed.
+pub fn apply(output_dir: &str, outputs: BTreeMap<String, String>, flags: &Flags) -> bool {
+    let mut changed = false;
+    for (name, content) in outputs {
+        let path = Path::new(output_dir).join(&name);
+        if flags.dry_run() {
+            let old = fs::read(&path).ok().map(|old| String::from_utf8_lossy(&old).into_owned());
+            let old_label = match old {
+                Some(_) => path.display().to_string(),
+                None => "/dev/null".to_string(),
+            };
+            let old = old.unwrap_or_default();
+            if old == content {
+                continue;
+            }
+            changed = true;
+            if flags.check {
+                println!("{} is out of date", path.display());
+            }
+            if flags.diff {
+                if let Some(diff) = unified_diff(&old_label, &path.display().to_string(), &old, &content) {
+                    print!("{}", diff);
+                }
+            }
+        } else {
+            match write_file(output_dir, &name, &content) {
+                Ok(written) => changed |= written,
+                Err(err) => {
+                    eprintln!("Could not write {}: {}", path.display(), err);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+    changed
+}
+
+pub fn generate(args: &[String]) {
+    let flags = match Flags::parse(args) {
+        Ok(flags) => flags,
+        Err(err) => {
+            eprintln!("{}", err);
+            usage();
+            process::exit(2);
+        }
+    };
+    if flags.paths.is_empty() || flags.paths.len() % 2 != 0 {
+        usage();
+        process::exit(2);
+    }
+    if flags.lang.name.is_some() && flags.paths.len() > 2 {
+        eprintln!("--name is for a single grammar");
+        process::exit(2);
+    }
+    let targets = flags
+        .paths
+        .chunks(2)
+        .map(|pair| (Path::new(&pair[0]).to_path_buf(), flags.lang.output_dir(&pair[1])))
+        .collect::<Vec<_>>();
+    if flags.watch {
+        watch::watch(&targets, &flags);
+        return;
+    }
+    let mut changed = false;
+    for target in &targets {
+        changed |= run(&target.0, &target.1, flags.options(&target.0), &flags);
+    }
+    if flags.check && changed {
+        process::exit(1);
+    }
+    if !flags.dry_run() {
+        println!("Langfile processed");
+    }
+}
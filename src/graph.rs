@@ -0,0 +1,56 @@
+//! Rule graph of a grammar, from each rule to
+//! the rules it uses, as text or as a graphviz
+//! dot graph with `--dot`. Tokens are included
+//! with `--tokens`. Backends chosen with the
+//! lang flags are generated without writing them.
+use std::fs;
+use std::path::Path;
+use std::process;
+use descr_gen::generator::{build_data, parse_grammar};
+use descr_gen::graph::RuleGraph;
+use descr_gen::lang_data::data::LangData;
+use options::{self, parse_args};
+
+pub fn usage() {
+    eprintln!("Usage: descr graph grammar-file [--dot] [--tokens]");
+    eprintln!("{}", options::USAGE);
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+pub fn graph(args: &[String]) {
+    let (flags, found, paths) = parse_args(args, &["--dot", "--tokens"], usage);
+    let dot = found.iter().any(|arg| arg == "--dot");
+    let tokens = found.iter().any(|arg| arg == "--tokens");
+    if paths.len() != 1 {
+        usage();
+        process::exit(2);
+    }
+    let grammar_path = Path::new(&paths[0]);
+    let grammar = match fs::read(grammar_path) {
+        Ok(grammar) => grammar,
+        Err(err) => exit_with(&format!("Could not read {}: {}", grammar_path.display(), err)),
+    };
+    let ast = match parse_grammar(&grammar) {
+        Ok(ast) => ast,
+        Err(err) => exit_with(&format!("{}: {}", grammar_path.display(), err)),
+    };
+    let mut data = LangData::new(false, flags.lang_name(grammar_path));
+    if let Err(err) = build_data(&grammar, &ast, &mut data) {
+        exit_with(&format!("{}: {}", grammar_path.display(), err));
+    }
+    if flags.has_backends() {
+        if let Err(err) = flags.gen_backends(grammar_path, &grammar) {
+            exit_with(&format!("{}: {}", grammar_path.display(), err));
+        }
+    }
+    let graph = RuleGraph::new(&data, tokens);
+    if dot {
+        print!("{}", graph.to_dot());
+    } else {
+        print!("{}", graph.to_text());
+    }
+}
@@ -0,0 +1,124 @@
+//! Highlights a file with a grammar, parsed
+//! with the interpreter, so any grammar can
+//! be used without generating code. Output
+//! is 24-bit ansi colored text, or html with
+//! `--html`. Styles are from the default
+//! theme, or a theme file given with `--theme`.
+//! Backends chosen with the lang flags are
+//! generated without writing them.
+use std::fs;
+use std::path::Path;
+use std::process;
+use descr_common::highlight::{self, HighlightToken, Theme};
+use descr_gen::generator::{build_data, parse_grammar};
+use descr_gen::interpreter::Interpreter;
+use descr_gen::lang_data::data::LangData;
+use options::{self, parse_args, LangFlags};
+
+pub fn usage() {
+    eprintln!("Usage: descr highlight grammar-file input-file [--html] [--theme theme-file]");
+    eprintln!("{}", options::USAGE);
+}
+
+pub fn highlight(args: &[String]) {
+    // The theme file is taken
+    // before the lang flags
+    let mut theme_path = None;
+    let mut rest = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--theme" {
+            match args.next() {
+                Some(path) => theme_path = Some(path.as_str()),
+                None => {
+                    usage();
+                    process::exit(2);
+                }
+            }
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+    let (flags, found, paths) = parse_args(&rest, &["--html"], usage);
+    let html = !found.is_empty();
+    if paths.len() != 2 {
+        usage();
+        process::exit(2);
+    }
+    let theme = match theme_path {
+        Some(theme_path) => {
+            let source = read(theme_path);
+            match Theme::parse(&source) {
+                Ok(theme) => theme,
+                Err(err) => exit_with(&format!("{}: {}", theme_path, err)),
+            }
+        }
+        None => Theme::default(),
+    };
+    let input = read(&paths[1]);
+    let tokens = match tokens(Path::new(&paths[0]), &flags, &input) {
+        Ok(tokens) => tokens,
+        Err(err) => exit_with(&err),
+    };
+    if html {
+        print!("{}", html_page(&input, &tokens, &theme));
+    } else {
+        print!("{}", highlight::to_ansi(&input, &tokens, &theme));
+    }
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+fn read(path: &str) -> String {
+    match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) => exit_with(&format!("Could not read {}: {}", path, err)),
+    }
+}
+
+/// Tokens with scopes from the interpreter,
+/// which are the scopes of the generated syntax
+fn tokens(grammar_path: &Path, flags: &LangFlags, input: &str) -> Result<Vec<HighlightToken>, String> {
+    let grammar = fs::read(grammar_path)
+        .map_err(|err| format!("Could not read {}: {}", grammar_path.display(), err))?;
+    let ast = parse_grammar(&grammar)
+        .map_err(|err| format!("{}: {}", grammar_path.display(), err))?;
+    let mut data = LangData::new(false, flags.lang_name(grammar_path));
+    build_data(&grammar, &ast, &mut data)
+        .map_err(|err| format!("{}: {}", grammar_path.display(), err))?;
+    if flags.has_backends() {
+        flags
+            .gen_backends(grammar_path, &grammar)
+            .map_err(|err| format!("{}: {}", grammar_path.display(), err))?;
+    }
+    let interpreter = Interpreter::new(&data);
+    interpreter
+        .parse_complete(input.as_bytes())
+        .map_err(|err| err.to_string())?;
+    Ok(interpreter
+        .tokens()
+        .into_iter()
+        .map(|token| HighlightToken {
+            start: token.span.start,
+            end: token.span.end,
+            scope: token.scope,
+        })
+        .collect())
+}
+
+/// Standalone page with the theme as
+/// stylesheet, on a dark background
+fn html_page(input: &str, tokens: &[HighlightToken], theme: &Theme) -> String {
+    let mut s = String::new();
+    s += "<!DOCTYPE html>\n";
+    s += "<html>\n<head>\n<meta charset=\"utf-8\">\n<style>\n";
+    s += ".highlight { background: #282c34; color: #abb2bf; padding: 1em; }\n";
+    s += &theme.to_css();
+    s += "</style>\n</head>\n<body>\n";
+    s += &highlight::to_html(input, tokens);
+    s += "</body>\n</html>\n";
+    s
+}
@@ -0,0 +1,76 @@
+//! Translates EBNF and ANTLR4 grammars to descr
+//! grammars, written to the output file if given,
+//! else to stdout. What can't be translated is
+//! reported, and exits with an error.
+use std::fs;
+use std::path::Path;
+use std::process;
+use descr_gen::import::{import as import_grammar, Format, Level};
+
+pub fn usage() {
+    eprintln!("Usage: descr import --from ebnf|antlr4 grammar-file [output-file]");
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+pub fn import(args: &[String]) {
+    let mut format = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--from" => match args.next().and_then(|name| Format::from_name(name)) {
+                Some(from) => format = Some(from),
+                None => {
+                    eprintln!("--from expects ebnf or antlr4");
+                    usage();
+                    process::exit(2);
+                }
+            },
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown flag: {}", arg);
+                usage();
+                process::exit(2);
+            }
+            _ => paths.push(arg),
+        }
+    }
+    let format = match format {
+        Some(format) if !paths.is_empty() && paths.len() <= 2 => format,
+        _ => {
+            usage();
+            process::exit(2);
+        }
+    };
+    let grammar_path = Path::new(paths[0]);
+    let source = match fs::read_to_string(grammar_path) {
+        Ok(source) => source,
+        Err(err) => exit_with(&format!("Could not read {}: {}", grammar_path.display(), err)),
+    };
+    let imported = match import_grammar(format, &source) {
+        Ok(imported) => imported,
+        Err(err) => exit_with(&format!("{}: {}", grammar_path.display(), err)),
+    };
+    for diagnostic in &imported.diagnostics {
+        if diagnostic.line > 0 {
+            eprintln!("{}:{}: {}", grammar_path.display(), diagnostic.line, diagnostic);
+        } else {
+            eprintln!("{}: {}", grammar_path.display(), diagnostic);
+        }
+    }
+    match paths.get(1) {
+        Some(output) => {
+            if let Err(err) = fs::write(output, &imported.grammar) {
+                exit_with(&format!("Could not write {}: {}", output, err));
+            }
+        }
+        None => print!("{}", imported.grammar),
+    }
+    let errors = imported.diagnostics.iter().filter(|d| d.level == Level::Error).count();
+    if errors > 0 {
+        exit_with(&format!("{} errors, the grammar needs changes to build", errors));
+    }
+}
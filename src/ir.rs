@@ -0,0 +1,48 @@
+//! Writes the grammar IR as JSON, to the output
+//! file if given, else to stdout. The IR is the
+//! analysed grammar, see descr_gen::ir.
+use std::fs;
+use std::path::Path;
+use std::process;
+use descr_gen::generator::{build_data, parse_grammar};
+use descr_gen::ir;
+use descr_gen::lang_data::data::LangData;
+use options::lang_name;
+
+pub fn usage() {
+    eprintln!("Usage: descr ir grammar-file [output-file]");
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+pub fn ir(args: &[String]) {
+    if args.is_empty() || args.len() > 2 || args.iter().any(|arg| arg.starts_with("--")) {
+        usage();
+        process::exit(2);
+    }
+    let grammar_path = Path::new(&args[0]);
+    let grammar = match fs::read(grammar_path) {
+        Ok(grammar) => grammar,
+        Err(err) => exit_with(&format!("Could not read {}: {}", grammar_path.display(), err)),
+    };
+    let ast = match parse_grammar(&grammar) {
+        Ok(ast) => ast,
+        Err(err) => exit_with(&format!("{}: {}", grammar_path.display(), err)),
+    };
+    let mut data = LangData::new(false, lang_name(grammar_path));
+    if let Err(err) = build_data(&grammar, &ast, &mut data) {
+        exit_with(&format!("{}: {}", grammar_path.display(), err));
+    }
+    let json = ir::to_json(&data);
+    match args.get(1) {
+        Some(output) => {
+            if let Err(err) = fs::write(output, json) {
+                exit_with(&format!("Could not write {}: {}", output, err));
+            }
+        }
+        None => print!("{}", json),
+    }
+}
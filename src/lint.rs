@@ -0,0 +1,58 @@
+//! Grammar lints from descr_gen::lint, printed
+//! one per line. Notes on overlapping alternatives
+//! are shown with `--notes`. Backends chosen with
+//! the lang flags are generated without writing
+//! them. Exits with an error when there are
+//! warnings.
+use std::fs;
+use std::path::Path;
+use std::process;
+use descr_gen::generator::{build_data, parse_grammar};
+use descr_gen::lang_data::data::LangData;
+use descr_gen::lint::Linter;
+use options::{self, parse_args};
+
+pub fn usage() {
+    eprintln!("Usage: descr lint grammar-file [--notes]");
+    eprintln!("{}", options::USAGE);
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+pub fn lint(args: &[String]) {
+    let (flags, found, paths) = parse_args(args, &["--notes"], usage);
+    let notes = !found.is_empty();
+    if paths.len() != 1 {
+        usage();
+        process::exit(2);
+    }
+    let grammar_path = Path::new(&paths[0]);
+    let grammar = match fs::read(grammar_path) {
+        Ok(grammar) => grammar,
+        Err(err) => exit_with(&format!("Could not read {}: {}", grammar_path.display(), err)),
+    };
+    let ast = match parse_grammar(&grammar) {
+        Ok(ast) => ast,
+        Err(err) => exit_with(&format!("{}: {}", grammar_path.display(), err)),
+    };
+    let mut data = LangData::new(false, flags.lang_name(grammar_path));
+    if let Err(err) = build_data(&grammar, &ast, &mut data) {
+        exit_with(&format!("{}: {}", grammar_path.display(), err));
+    }
+    if flags.has_backends() {
+        if let Err(err) = flags.gen_backends(grammar_path, &grammar) {
+            exit_with(&format!("{}: {}", grammar_path.display(), err));
+        }
+    }
+    let lints = Linter::new(&data).lint();
+    for lint in lints.iter().filter(|lint| notes || !lint.kind.is_note()) {
+        println!("{}: {}", grammar_path.display(), lint);
+    }
+    let warnings = lints.iter().filter(|lint| !lint.kind.is_note()).count();
+    if warnings > 0 {
+        exit_with(&format!("{} warnings", warnings));
+    }
+}
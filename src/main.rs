@@ -1,162 +1,144 @@
 extern crate elapsed;
-extern crate nom;
-use elapsed::measure_time;
 extern crate descr_common;
 extern crate descr_gen;
 extern crate descr_lang;
-use std::fs::File;
-use std::io::prelude::*;
 use std::env;
-use descr_gen::lang_data::data::LangData;
 use std::path::Path;
 use std::process;
 
+mod options;
+mod generate;
+mod watch;
+mod check;
+mod parse;
+mod fmt;
+mod graph;
+mod ir;
+mod import;
+mod play;
+mod highlight;
+mod samples;
+mod coverage;
+mod lint;
+mod trace;
+mod bootstrap;
+
 fn invalid_args() {
-    eprintln!("\n = Missing args ================================ ");
-    eprintln!("|                                               |");
-    eprintln!("|   Usage: <exe> input-file output-dir          |");
-    eprintln!("|          - Process lang-file to output dir    |");
-    eprintln!("|   or                                          |");
-    eprintln!("|          <exe> descr-lang                     |");
-    eprintln!("|          - Process descr lang                 |");
-    eprintln!("|   or                                          |");
-    eprintln!("|          <exe> pg-lang                        |");
-    eprintln!("|          - Process playground lang            |");
-    eprintln!("|   or                                          |");
-    eprintln!("|          <exe> pg                             |");
-    eprintln!("|          - Playground result, will check      |");
-    eprintln!("|            for changes                        |");
-    eprintln!("|                                               |");
+    eprintln!("\n = Usage ======================================= ");
+    eprintln!("|                                               |");
+    eprintln!("|   <exe> command [args]                        |");
+    eprintln!("|                                               |");
+    eprintln!("|   generate     Generate modules from grammar  |");
+    eprintln!("|   check        Check grammars for errors      |");
+    eprintln!("|   parse        Parse input, print the tree    |");
+    eprintln!("|   highlight    Highlight input as ansi/html   |");
+    eprintln!("|   fmt          Format grammars                |");
+    eprintln!("|   lint         Shadowed and unused rules      |");
+    eprintln!("|   graph        Rule graph as text or dot      |");
+    eprintln!("|   ir           Grammar IR as JSON             |");
+    eprintln!("|   import       Grammar from EBNF or ANTLR4    |");
+    eprintln!("|   trace        Rules tried parsing input      |");
+    eprintln!("|   coverage     Grammar coverage of corpus     |");
+    eprintln!("|   gen-samples  Random samples of grammar      |");
+    eprintln!("|   play         Interactive playground         |");
+    eprintln!("|   bootstrap-check  Descr lang self-hosting    |");
+    eprintln!("|                                               |");
+    eprintln!("|   <exe> command --help                        |");
+    eprintln!("|   - Arguments of command                      |");
+    eprintln!("|   <exe> input-file output-dir                 |");
+    eprintln!("|   - Same as generate                          |");
+    eprintln!("|                                               |");
     eprintln!(" =============================================== \n");
 }
 
-enum Command {
-    Pg,
-    PgRes,
-    DescrLang,
-    Custom,
-}
-
-fn compile_pg() {
-    let output = process::Command::new("cargo")
-        .current_dir("pg")
-        .arg("build")
-        .output()
-        .expect("Could not run pg build");
-    println!("{}", String::from_utf8_lossy(&output.stdout));
-    println!("{}", String::from_utf8_lossy(&output.stderr));
-}
-
-fn run_pg() {
-    let output = process::Command::new("cargo")
-        .current_dir("pg")
-        .arg("run")
-        .output()
-        .expect("Could not run pg");
-    println!("{}", String::from_utf8_lossy(&output.stdout));
-    println!("{}", String::from_utf8_lossy(&output.stderr));
+/// Exits after usage when args ask for help
+fn help(args: &[String], usage: fn()) {
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        usage();
+        process::exit(0);
+    }
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let (filename, output_dir, command, check_change) = match args.len() {
-        2 => match args[1].as_str() {
-            "pg-lang" => ("playground.lang", "pg/src/lang", Command::Pg, false),
-            "pg" => ("playground.lang", "pg/src/lang", Command::PgRes, true),
-            "descr-lang" => (
-                "descr.lang",
-                "descr-lang/src/gen",
-                Command::DescrLang,
-                false,
-            ),
-            _ => {
-                invalid_args();
-                return;
-            }
-        },
-        3 => (args[1].as_str(), args[2].as_str(), Command::Custom, false),
-        _ => {
-            invalid_args();
-            return;
+    if args.len() < 2 {
+        invalid_args();
+        process::exit(2);
+    }
+    let rest = &args[2..];
+    match args[1].as_str() {
+        "generate" => {
+            help(rest, generate::usage);
+            generate::generate(rest);
+        }
+        "check" => {
+            help(rest, check::usage);
+            check::check(rest);
+        }
+        "parse" => {
+            help(rest, parse::usage);
+            parse::parse(rest);
+        }
+        "highlight" => {
+            help(rest, highlight::usage);
+            highlight::highlight(rest);
+        }
+        "fmt" => {
+            help(rest, fmt::usage);
+            fmt::fmt(rest);
+        }
+        "lint" => {
+            help(rest, lint::usage);
+            lint::lint(rest);
         }
-    };
-    let file_path = Path::new(filename);
-    // Check modification of lang file vs ast.rs
-    let ast_path = Path::new(output_dir).join("ast.rs");
-    let is_changed = if !ast_path.exists() {
-        true
-    } else {
-        let file_meta = std::fs::metadata(file_path).unwrap();
-        let ast_meta = std::fs::metadata(ast_path).unwrap();
-        match file_meta.modified() {
-            Ok(file_time) => match ast_meta.modified() {
-                Ok(ast_time) => file_time > ast_time,
-                _ => true,
-            },
-            _ => true,
+        "graph" => {
+            help(rest, graph::usage);
+            graph::graph(rest);
         }
-    };
-    if check_change && !is_changed {
-        println!("Lang file not changes since last codegen");
-    } else {
-        let mut f = File::open(filename).expect(format!("Could not open {}", filename).as_str());
-        let mut buf = Vec::with_capacity(1024);
-        f.read_to_end(&mut buf)
-            .expect(format!("Could not open {}", filename).as_str());
-        let (elapsed, res) = measure_time(|| descr_lang::gen::parsers::source(&buf[..]));
-        println!("Parse: {}", elapsed);
-        //println!("{:#?}", res);
-        let lang_name = match file_path.file_name() {
-            Some(filename) => {
-                let filename = filename.to_str().unwrap();
-                match filename.find('.') {
-                    Some(pos) => {
-                        let mut chars = filename[0..pos].chars();
-                        match chars.next() {
-                            Some(c) => c.to_uppercase().chain(chars).collect(),
-                            _ => "No-name".to_string()
-                        }
-                    },
-                    _ => "No-name".to_string()
+        "ir" => {
+            help(rest, ir::usage);
+            ir::ir(rest);
+        }
+        "import" => {
+            help(rest, import::usage);
+            import::import(rest);
+        }
+        "trace" => {
+            help(rest, trace::usage);
+            trace::trace(rest);
+        }
+        "coverage" => {
+            help(rest, coverage::usage);
+            coverage::coverage(rest);
+        }
+        "gen-samples" => {
+            help(rest, samples::usage);
+            samples::gen_samples(rest);
+        }
+        "bootstrap-check" => {
+            help(rest, bootstrap::usage);
+            bootstrap::bootstrap_check(rest);
+        }
+        "play" => {
+            help(rest, play::usage);
+            if rest.is_empty() || rest.len() > 2 {
+                play::usage();
+                process::exit(2);
+            }
+            play::play(&rest[0], rest.get(1).map(|arg| arg.as_str()));
+        }
+        "--help" | "-h" | "help" => invalid_args(),
+        _ => {
+            // Shorthand for generate
+            match generate::Flags::parse(&args[1..]) {
+                Ok(ref flags) if flags.paths.len() == 2 && Path::new(&flags.paths[0]).is_file() => {
+                    generate::generate(&args[1..]);
                 }
-            },
-            _ => "No-name".to_string()
-        }; 
-        let mut data = LangData::new(false, lang_name);
-        {
-            match res {
-                nom::IResult::Done(_, ref source) => {
-                    descr_gen::process::process(source, &mut data, output_dir);
+                _ => {
+                    invalid_args();
+                    process::exit(2);
                 }
-                _ => (),
             }
         }
     }
-    match command {
-        Command::Pg => {
-            println!("Playground lang updated, compiling..");
-            compile_pg();
-        }
-        Command::PgRes => {
-            if is_changed {
-                println!("Playground lang updated, compiling..");
-                compile_pg();
-                run_pg();
-            } else {
-                run_pg();
-            }
-        }
-        Command::DescrLang => println!("Descr lang updated"),
-        Command::Custom => println!("Langfile processed"),
-    };
-
-    /*
-    println!("Ast keys: {:#?}", data.ast_data.keys());
-    println!("List keys: {:#?}", data.list_data.keys());
-    println!("Parts: {:#?}", data.typed_parts.values());
-    println!("Ast: {:#?}", data.ast_data.values());
-    println!("List: {:#?}", data.list_data.values());
-    println!("Structs: {:#?}", data.ast_structs);
-    println!("Enums: {:#?}", data.ast_enums);
-    */
 }
@@ -0,0 +1,178 @@
+//! Flags taken by the commands that build a
+//! language from a grammar: the backends with
+//! `--outputs`, `--rd` and `--memo`, the module
+//! with `--module`, the language name with
+//! `--name` and the debug mode with `--trace`.
+use std::path::Path;
+use std::process;
+use descr_gen::generator::{GenOptions, Generator};
+
+pub const USAGE: &str = "    [--outputs ast,parsers,visitor,to-source,syntax]\n    \
+                         [--name Name] [--module name] [--trace] [--rd] [--memo]";
+
+pub const OUTPUTS: [&str; 5] = ["ast", "parsers", "visitor", "to-source", "syntax"];
+
+#[derive(Debug, Default)]
+pub struct LangFlags {
+    /// Main outputs, all when not given
+    pub outputs: Option<Vec<String>>,
+    /// Language name, from the grammar
+    /// file name when not given
+    pub name: Option<String>,
+    /// Module dir in the output dir
+    pub module: Option<String>,
+    pub trace: bool,
+    pub rd: bool,
+    pub memo: bool,
+}
+impl LangFlags {
+    /// Takes arg, with its value from args,
+    /// giving whether it was a lang flag
+    pub fn take<'a, I>(&mut self, arg: &str, args: &mut I) -> Result<bool, String>
+    where
+        I: Iterator<Item = &'a String>,
+    {
+        match arg {
+            "--outputs" => {
+                let outputs = value(arg, args.next())?
+                    .split(',')
+                    .map(|output| output.trim().replace('_', "-"))
+                    .filter(|output| !output.is_empty())
+                    .collect::<Vec<_>>();
+                for output in &outputs {
+                    if !OUTPUTS.contains(&output.as_str()) {
+                        return Err(format!("Unknown output: {}, expected one of {}", output, OUTPUTS.join(", ")));
+                    }
+                }
+                self.outputs = Some(outputs);
+            }
+            "--name" => self.name = Some(value(arg, args.next())?),
+            "--module" => self.module = Some(value(arg, args.next())?),
+            "--trace" => self.trace = true,
+            "--rd" => self.rd = true,
+            "--memo" => self.memo = true,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Parses args with lang flags and the
+    /// flags in other, giving those found
+    /// and the arguments that are not flags
+    pub fn parse(args: &[String], other: &[&str]) -> Result<(LangFlags, Vec<String>, Vec<String>), String> {
+        let mut flags = LangFlags::default();
+        let mut found = Vec::new();
+        let mut paths = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if flags.take(arg, &mut args)? {
+                continue;
+            }
+            if other.contains(&arg.as_str()) {
+                found.push(arg.clone());
+            } else if arg.starts_with("--") {
+                return Err(format!("Unknown flag: {}", arg));
+            } else {
+                paths.push(arg.clone());
+            }
+        }
+        Ok((flags, found, paths))
+    }
+
+    /// Name given with --name, or from the
+    /// grammar file name
+    pub fn lang_name(&self, grammar_path: &Path) -> String {
+        match self.name {
+            Some(ref name) => name.clone(),
+            None => lang_name(grammar_path),
+        }
+    }
+
+    /// Whether backends or a module were chosen
+    pub fn has_backends(&self) -> bool {
+        self.outputs.is_some() || self.rd || self.memo || self.module.is_some()
+    }
+
+    /// Generates the chosen backends without writing
+    /// them, giving the paths of the outputs
+    pub fn gen_backends(&self, grammar_path: &Path, grammar: &[u8]) -> Result<Vec<String>, String> {
+        let outputs = Generator::new(self.options(grammar_path))
+            .generate(grammar)
+            .map_err(|err| err.to_string())?;
+        Ok(outputs
+            .keys()
+            .map(|name| match self.module {
+                Some(ref module) => format!("{}/{}", module, name),
+                None => name.clone(),
+            })
+            .collect())
+    }
+
+    /// Options with the main outputs,
+    /// backends and debug mode
+    pub fn options(&self, grammar_path: &Path) -> GenOptions {
+        let mut options = GenOptions::new(self.lang_name(grammar_path));
+        if let Some(ref outputs) = self.outputs {
+            let has = |output: &str| outputs.iter().any(|o| o == output);
+            options.ast = has("ast");
+            options.parsers = has("parsers");
+            options.visitor = has("visitor");
+            options.to_source = has("to-source");
+            options.syntax = has("syntax");
+        }
+        // Memoization is done by the rd parsers
+        options.recursive_descent = self.rd || self.memo;
+        options.memoize = self.memo;
+        options.trace = self.trace;
+        options
+    }
+
+    /// Output dir, with the module dir if given
+    pub fn output_dir(&self, output_dir: &str) -> String {
+        match self.module {
+            Some(ref module) => Path::new(output_dir).join(module).to_string_lossy().into_owned(),
+            None => output_dir.to_string(),
+        }
+    }
+}
+
+/// Lang flags, other flags found and paths of args,
+/// exits after usage when they don't parse
+pub fn parse_args(args: &[String], other: &[&str], usage: fn()) -> (LangFlags, Vec<String>, Vec<String>) {
+    match LangFlags::parse(args, other) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("{}", err);
+            usage();
+            process::exit(2);
+        }
+    }
+}
+
+pub fn value(flag: &str, value: Option<&String>) -> Result<String, String> {
+    match value {
+        Some(value) if !value.starts_with("--") => Ok(value.clone()),
+        _ => Err(format!("{} expects a value", flag)),
+    }
+}
+
+/// Language name from file name,
+/// capitalized up to the first dot
+pub fn lang_name(file_path: &Path) -> String {
+    match file_path.file_name() {
+        Some(filename) => {
+            let filename = filename.to_str().unwrap();
+            match filename.find('.') {
+                Some(pos) => {
+                    let mut chars = filename[0..pos].chars();
+                    match chars.next() {
+                        Some(c) => c.to_uppercase().chain(chars).collect(),
+                        _ => "No-name".to_string()
+                    }
+                },
+                _ => "No-name".to_string()
+            }
+        },
+        _ => "No-name".to_string()
+    }
+}
@@ -0,0 +1,77 @@
+//! Parses a file with a grammar, using the
+//! interpreter, and prints the tree. With
+//! `--source`, the tree is also printed back
+//! as source. With `--trace`, the rules tried
+//! are printed to stderr, and backends chosen
+//! with the lang flags are generated without
+//! writing them, to check the grammar builds.
+//! Exits with an error when the grammar has
+//! errors or the input doesn't parse.
+use std::fs;
+use std::path::Path;
+use std::process;
+use descr_gen::generator::{build_data, parse_grammar};
+use descr_gen::interpreter::{DynToSource, Interpreter};
+use descr_gen::lang_data::data::LangData;
+use options::{self, parse_args};
+
+pub fn usage() {
+    eprintln!("Usage: descr parse grammar-file input-file [--source]");
+    eprintln!("{}", options::USAGE);
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+fn read(path: &Path) -> Vec<u8> {
+    match fs::read(path) {
+        Ok(content) => content,
+        Err(err) => exit_with(&format!("Could not read {}: {}", path.display(), err)),
+    }
+}
+
+pub fn parse(args: &[String]) {
+    let (flags, found, paths) = parse_args(args, &["--source"], usage);
+    let source = !found.is_empty();
+    if paths.len() != 2 {
+        usage();
+        process::exit(2);
+    }
+    let grammar_path = Path::new(&paths[0]);
+    let grammar = read(grammar_path);
+    let ast = match parse_grammar(&grammar) {
+        Ok(ast) => ast,
+        Err(err) => exit_with(&format!("{}: {}", grammar_path.display(), err)),
+    };
+    let mut data = LangData::new(false, flags.lang_name(grammar_path));
+    if let Err(err) = build_data(&grammar, &ast, &mut data) {
+        exit_with(&format!("{}: {}", grammar_path.display(), err));
+    }
+    if flags.has_backends() {
+        if let Err(err) = flags.gen_backends(grammar_path, &grammar) {
+            exit_with(&format!("{}: {}", grammar_path.display(), err));
+        }
+    }
+    let input_path = Path::new(&paths[1]);
+    let input = read(input_path);
+    let interpreter = if flags.trace {
+        Interpreter::new(&data).with_trace()
+    } else {
+        Interpreter::new(&data)
+    };
+    let res = interpreter.parse_complete(&input);
+    if let Some(trace) = interpreter.trace(&input) {
+        eprint!("{}", trace.to_text());
+    }
+    match res {
+        Ok(node) => {
+            println!("{}", node);
+            if source {
+                println!("{}", DynToSource::new(&data).to_source(&node));
+            }
+        }
+        Err(err) => exit_with(&format!("{}: {}", input_path.display(), err)),
+    }
+}
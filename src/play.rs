@@ -0,0 +1,397 @@
+//! Playground, parses examples with the
+//! interpreter and shows the result again
+//! when the grammar or examples change.
+//!
+//! Examples are the input file if given,
+//! otherwise files in `<name>.examples/` next
+//! to `<name>.lang`. Expected output for
+//! an example is saved next to it, with
+//! the `.expected` extension.
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+use descr_gen::generator::{build_data, parse_grammar, SourcePos};
+use descr_gen::interpreter::{DynToSource, Interpreter};
+use descr_gen::lang_data::data::LangData;
+use options::lang_name;
+
+struct Example {
+    name: String,
+    path: PathBuf,
+}
+impl Example {
+    fn expected_path(&self) -> PathBuf {
+        self.path.with_extension("expected")
+    }
+}
+
+/// Result of parsing an example
+struct Output {
+    /// Tree or error, compared with expected
+    result: String,
+    source: Option<String>,
+    scopes: String,
+}
+
+pub fn usage() {
+    eprintln!("Usage: descr play grammar-file [input-file]");
+}
+
+pub fn play(grammar_path: &str, input_path: Option<&str>) {
+    let grammar_path = Path::new(grammar_path);
+    let examples = find_examples(grammar_path, input_path);
+    if examples.is_empty() {
+        eprintln!(
+            "No examples, give an input file or add files to {}",
+            examples_dir(grammar_path).display()
+        );
+        return;
+    }
+    let mut current = 0;
+    let mut modified = modified_times(grammar_path, &examples);
+    show(grammar_path, &examples[current]);
+    print_help();
+    let lines = read_lines();
+    loop {
+        prompt();
+        let line = loop {
+            match lines.recv_timeout(Duration::from_millis(300)) {
+                Ok(line) => break Some(line),
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    let now = modified_times(grammar_path, &examples);
+                    if now != modified {
+                        modified = now;
+                        println!();
+                        show(grammar_path, &examples[current]);
+                        prompt();
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => break None,
+            }
+        };
+        let line = match line {
+            Some(line) => line,
+            None => return,
+        };
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (None, _) => show(grammar_path, &examples[current]),
+            (Some("list"), _) => {
+                for (i, example) in examples.iter().enumerate() {
+                    let marker = if i == current { "*" } else { " " };
+                    println!("{} {}", marker, example.name);
+                }
+            }
+            (Some("use"), Some(name)) => match examples.iter().position(|e| e.name == name) {
+                Some(i) => {
+                    current = i;
+                    show(grammar_path, &examples[current]);
+                }
+                None => println!("No example named {}", name),
+            },
+            (Some("save"), _) => {
+                let example = &examples[current];
+                match run_example(grammar_path, example) {
+                    Ok(output) => match fs::write(example.expected_path(), output.result) {
+                        Ok(_) => println!("Saved {}", example.expected_path().display()),
+                        Err(err) => println!("Could not save expected: {}", err),
+                    },
+                    Err(err) => println!("{}", err),
+                }
+            }
+            (Some("check"), _) => check_all(grammar_path, &examples),
+            (Some("help"), _) => print_help(),
+            (Some("quit"), _) | (Some("q"), _) => return,
+            (Some(other), _) => println!("Unknown command: {}", other),
+        }
+    }
+}
+
+fn print_help() {
+    println!("Commands: <enter> reparse, list, use <example>, save, check, help, quit");
+}
+
+fn prompt() {
+    print!("play> ");
+    let _ = io::stdout().flush();
+}
+
+/// Stdin is read in a thread, so files
+/// can be polled while waiting for input
+fn read_lines() -> mpsc::Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+            match line {
+                Ok(line) => {
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    });
+    receiver
+}
+
+fn examples_dir(grammar_path: &Path) -> PathBuf {
+    grammar_path.with_extension("examples")
+}
+
+fn find_examples(grammar_path: &Path, input_path: Option<&str>) -> Vec<Example> {
+    let paths = match input_path {
+        Some(input_path) => vec![PathBuf::from(input_path)],
+        None => match fs::read_dir(examples_dir(grammar_path)) {
+            Ok(entries) => {
+                let mut paths = entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| {
+                        path.is_file()
+                            && path.extension().map(|ext| ext != "expected").unwrap_or(true)
+                    })
+                    .collect::<Vec<_>>();
+                paths.sort();
+                paths
+            }
+            Err(_) => Vec::new(),
+        },
+    };
+    paths
+        .into_iter()
+        .map(|path| Example {
+            name: path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path,
+        })
+        .collect()
+}
+
+fn modified_times(grammar_path: &Path, examples: &[Example]) -> Vec<Option<SystemTime>> {
+    let mut paths = vec![grammar_path.to_path_buf()];
+    for example in examples {
+        paths.push(example.path.clone());
+        paths.push(example.expected_path());
+    }
+    paths
+        .iter()
+        .map(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+        .collect()
+}
+
+fn run_example(grammar_path: &Path, example: &Example) -> Result<Output, String> {
+    let grammar = fs::read(grammar_path)
+        .map_err(|err| format!("Could not read {}: {}", grammar_path.display(), err))?;
+    let input = fs::read(&example.path)
+        .map_err(|err| format!("Could not read {}: {}", example.path.display(), err))?;
+    let ast = parse_grammar(&grammar)
+        .map_err(|err| format!("{}: {}", grammar_path.display(), err))?;
+    let mut data = LangData::new(false, lang_name(grammar_path));
+    build_data(&grammar, &ast, &mut data)
+        .map_err(|err| format!("{}: {}", grammar_path.display(), err))?;
+    let interpreter = Interpreter::new(&data);
+    let (result, source) = match interpreter.parse_complete(&input) {
+        Ok(node) => (node.to_string(), Some(DynToSource::new(&data).to_source(&node))),
+        Err(err) => (err.to_string(), None),
+    };
+    let mut scopes = String::new();
+    for token in interpreter.tokens() {
+        let pos = SourcePos::from_offset(&input, token.span.start);
+        let text = String::from_utf8_lossy(&input[token.span.start..token.span.end]);
+        scopes += &format!("{:<7} {:<22} {:?}\n", pos.to_string(), token.scope, text);
+    }
+    Ok(Output {
+        result,
+        source,
+        scopes,
+    })
+}
+
+fn show(grammar_path: &Path, example: &Example) {
+    println!("= {} ==========================", example.name);
+    let output = match run_example(grammar_path, example) {
+        Ok(output) => output,
+        Err(err) => {
+            println!("{}", err);
+            return;
+        }
+    };
+    println!("{}", side_by_side(&output.result, &output.scopes));
+    if let Some(ref source) = output.source {
+        println!("= Source ==========================");
+        println!("{}", source);
+    }
+    match fs::read_to_string(example.expected_path()) {
+        Ok(expected) => match first_difference(&expected, &output.result) {
+            None => println!("= Expected: ok"),
+            Some((line, expected, actual)) => {
+                println!("= Expected: differs at line {}", line);
+                println!("- {}", expected);
+                println!("+ {}", actual);
+            }
+        },
+        Err(_) => println!("= Expected: not saved"),
+    }
+}
+
+fn check_all(grammar_path: &Path, examples: &[Example]) {
+    let mut failed = 0;
+    for example in examples {
+        let status = match (
+            run_example(grammar_path, example),
+            fs::read_to_string(example.expected_path()),
+        ) {
+            (Ok(output), Ok(expected)) => match first_difference(&expected, &output.result) {
+                None => "ok".to_string(),
+                Some((line, ..)) => {
+                    failed += 1;
+                    format!("differs at line {}", line)
+                }
+            },
+            (Ok(_), Err(_)) => "not saved".to_string(),
+            (Err(err), _) => {
+                failed += 1;
+                err
+            }
+        };
+        println!("{:<20} {}", example.name, status);
+    }
+    println!("{} of {} examples differ", failed, examples.len());
+}
+
+/// Line number, expected and actual line
+fn first_difference(expected: &str, actual: &str) -> Option<(usize, String, String)> {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (None, None) => return None,
+            (e, a) => {
+                if e != a {
+                    return Some((
+                        line,
+                        e.unwrap_or("").to_string(),
+                        a.unwrap_or("").to_string(),
+                    ));
+                }
+            }
+        }
+        line += 1;
+    }
+}
+
+/// Left column is padded to the longest line
+fn side_by_side(left: &str, right: &str) -> String {
+    let width = left.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+    let mut left_lines = left.lines();
+    let mut right_lines = right.lines();
+    let mut s = String::new();
+    loop {
+        match (left_lines.next(), right_lines.next()) {
+            (None, None) => break,
+            (l, r) => {
+                let l = l.unwrap_or("");
+                match r {
+                    Some(r) => s += &format!("{:<2$} | {}\n", l, r, width),
+                    None => s += &format!("{}\n", l),
+                }
+            }
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    const GRAMMAR: &str = "Source(LBRACE items:items RBRACE)\nitems[] COMMA Int\nInt(int)\n";
+
+    /// Grammar with examples in a dir of the test
+    fn grammar(test: &str, examples: &[(&str, &str)]) -> PathBuf {
+        let dir = env::temp_dir().join("descr-play").join(test);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("lang.examples")).unwrap();
+        let grammar_path = dir.join("lang.lang");
+        fs::write(&grammar_path, GRAMMAR).unwrap();
+        for &(name, content) in examples {
+            fs::write(dir.join("lang.examples").join(name), content).unwrap();
+        }
+        grammar_path
+    }
+
+    #[test]
+    fn finds_examples_sorted_without_expected() {
+        let grammar_path = grammar("find", &[("b.in", "{1}"), ("a.in", "{2}"), ("a.expected", "")]);
+        let examples = find_examples(&grammar_path, None);
+        let names = examples.iter().map(|e| e.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(examples[0].expected_path(), grammar_path.with_extension("examples").join("a.expected"));
+    }
+
+    #[test]
+    fn input_file_is_the_only_example() {
+        let grammar_path = grammar("input", &[("a.in", "{1}")]);
+        let examples = find_examples(&grammar_path, Some("other.in"));
+        assert_eq!(examples.len(), 1);
+        assert_eq!(examples[0].name, "other");
+        assert!(find_examples(Path::new("missing.lang"), None).is_empty());
+    }
+
+    #[test]
+    fn runs_examples() {
+        let grammar_path = grammar("run", &[("ok.in", "{1, 2}"), ("err.in", "{1,")]);
+        let examples = find_examples(&grammar_path, None);
+        let output = run_example(&grammar_path, &examples[1]).unwrap();
+        assert!(output.result.starts_with("Source [0..6]"));
+        assert!(output.source.unwrap().contains("1, 2 }"));
+        assert!(output.scopes.contains("1:5     constant.numeric       \"2\""));
+        let output = run_example(&grammar_path, &examples[0]).unwrap();
+        assert!(output.result.contains("expected int"));
+        assert_eq!(output.source, None);
+    }
+
+    #[test]
+    fn matches_expected_output() {
+        let grammar_path = grammar("expected", &[("ok.in", "{1}")]);
+        let examples = find_examples(&grammar_path, None);
+        let output = run_example(&grammar_path, &examples[0]).unwrap();
+        assert_eq!(first_difference(&output.result, &output.result), None);
+        // Saved with a trailing newline by an editor
+        assert_eq!(first_difference(&format!("{}\n", output.result), &output.result), None);
+        let changed = output.result.replace("int: 1", "int: 2");
+        let (line, expected, actual) = first_difference(&changed, &output.result).unwrap();
+        assert!(line > 1);
+        assert!(expected.contains("int: 2"));
+        assert!(actual.contains("int: 1"));
+        assert_eq!(
+            first_difference("a\nb", "a"),
+            Some((2, "b".to_string(), "".to_string()))
+        );
+    }
+
+    #[test]
+    fn pads_left_column() {
+        assert_eq!(side_by_side("ab\nc", "1\n2\n3"), "ab | 1\nc  | 2\n   | 3\n");
+        assert_eq!(side_by_side("ab\nc", "1"), "ab | 1\nc\n");
+    }
+
+    #[test]
+    fn playground_examples_match() {
+        let grammar_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("playground.lang");
+        let examples = find_examples(&grammar_path, None);
+        assert!(!examples.is_empty());
+        for example in &examples {
+            let output = run_example(&grammar_path, example).unwrap();
+            let expected = fs::read_to_string(example.expected_path()).unwrap();
+            assert_eq!(first_difference(&expected, &output.result), None, "{}", example.name);
+        }
+    }
+}
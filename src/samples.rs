@@ -0,0 +1,105 @@
+//! Writes random valid inputs for a grammar,
+//! from descr_gen::samples. Samples are printed
+//! with a blank line between them, or written
+//! as numbered files to the `--out` dir.
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+use descr_gen::generator::{build_data, parse_grammar};
+use descr_gen::lang_data::data::LangData;
+use descr_gen::samples::{SampleOptions, Sampler};
+use options::lang_name;
+
+pub fn usage() {
+    eprintln!("Usage: descr gen-samples grammar-file [-n count] [--seed seed] [--max-depth depth] [--out dir]");
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+fn number<T: ::std::str::FromStr>(flag: &str, arg: Option<&String>) -> T {
+    match arg.and_then(|arg| arg.parse().ok()) {
+        Some(number) => number,
+        None => exit_with(&format!("{} expects a number", flag)),
+    }
+}
+
+pub fn gen_samples(args: &[String]) {
+    let mut count = 10;
+    let mut options = SampleOptions::new();
+    options.seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs() ^ u64::from(time.subsec_nanos()))
+        .unwrap_or(1);
+    let mut out_dir = None;
+    let mut grammar_path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-n" => count = number(arg, args.next()),
+            "--seed" => options.seed = number(arg, args.next()),
+            "--max-depth" => options.max_depth = number(arg, args.next()),
+            "--out" => match args.next() {
+                Some(dir) => out_dir = Some(dir.as_str()),
+                None => {
+                    usage();
+                    process::exit(2);
+                }
+            },
+            _ if grammar_path.is_none() => grammar_path = Some(arg.as_str()),
+            _ => {
+                usage();
+                process::exit(2);
+            }
+        }
+    }
+    let grammar_path = match grammar_path {
+        Some(grammar_path) => Path::new(grammar_path),
+        None => {
+            usage();
+            process::exit(2);
+        }
+    };
+    let grammar = match fs::read(grammar_path) {
+        Ok(grammar) => grammar,
+        Err(err) => exit_with(&format!("Could not read {}: {}", grammar_path.display(), err)),
+    };
+    let ast = match parse_grammar(&grammar) {
+        Ok(ast) => ast,
+        Err(err) => exit_with(&format!("{}: {}", grammar_path.display(), err)),
+    };
+    let mut data = LangData::new(false, lang_name(grammar_path));
+    if let Err(err) = build_data(&grammar, &ast, &mut data) {
+        exit_with(&format!("{}: {}", grammar_path.display(), err));
+    }
+    let samples = Sampler::new(&data, options).samples(count);
+    match out_dir {
+        Some(out_dir) => {
+            if let Err(err) = fs::create_dir_all(out_dir) {
+                exit_with(&format!("Could not create {}: {}", out_dir, err));
+            }
+            let width = count.to_string().len();
+            for (i, sample) in samples.iter().enumerate() {
+                let name = format!("sample-{:02$}.{}", i + 1, data.lang_id(), width);
+                let path = Path::new(out_dir).join(name);
+                if let Err(err) = fs::write(&path, sample) {
+                    exit_with(&format!("Could not write {}: {}", path.display(), err));
+                }
+            }
+        }
+        None => {
+            for (i, sample) in samples.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                print!("{}", sample);
+            }
+        }
+    }
+    if samples.len() < count {
+        exit_with(&format!("Only {} of {} samples parsed", samples.len(), count));
+    }
+}
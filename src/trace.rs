@@ -0,0 +1,66 @@
+//! Trace of parsing input with the interpreter,
+//! the rules, alternatives and tokens tried,
+//! as indented text, or json or html with
+//! `--json` and `--html`. Parse errors are
+//! given after the trace.
+use std::fs;
+use std::path::Path;
+use std::process;
+use descr_gen::generator::{build_data, parse_grammar};
+use descr_gen::interpreter::Interpreter;
+use descr_gen::lang_data::data::LangData;
+use options::lang_name;
+
+pub fn usage() {
+    eprintln!("Usage: descr trace grammar-file input-file [--json | --html]");
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+fn read(path: &Path) -> Vec<u8> {
+    match fs::read(path) {
+        Ok(content) => content,
+        Err(err) => exit_with(&format!("Could not read {}: {}", path.display(), err)),
+    }
+}
+
+pub fn trace(args: &[String]) {
+    let json = args.iter().any(|arg| arg == "--json");
+    let html = args.iter().any(|arg| arg == "--html");
+    let paths = args
+        .iter()
+        .filter(|arg| *arg != "--json" && *arg != "--html")
+        .collect::<Vec<_>>();
+    if paths.len() != 2 || (json && html) {
+        usage();
+        process::exit(2);
+    }
+    let grammar_path = Path::new(paths[0]);
+    let grammar = read(grammar_path);
+    let ast = match parse_grammar(&grammar) {
+        Ok(ast) => ast,
+        Err(err) => exit_with(&format!("{}: {}", grammar_path.display(), err)),
+    };
+    let mut data = LangData::new(false, lang_name(grammar_path));
+    if let Err(err) = build_data(&grammar, &ast, &mut data) {
+        exit_with(&format!("{}: {}", grammar_path.display(), err));
+    }
+    let input_path = Path::new(paths[1]);
+    let input = read(input_path);
+    let interpreter = Interpreter::new(&data).with_trace();
+    let res = interpreter.parse_complete(&input);
+    let trace = interpreter.trace(&input).unwrap();
+    if json {
+        print!("{}", trace.to_json());
+    } else if html {
+        print!("{}", trace.to_html());
+    } else {
+        print!("{}", trace.to_text());
+    }
+    if let Err(err) = res {
+        exit_with(&format!("{}: {}", input_path.display(), err));
+    }
+}
@@ -0,0 +1,175 @@
+//! Watches grammars, generating them again when
+//! they change. Changes are polled, and a grammar
+//! is generated once it has been unchanged for
+//! a moment, so rapid saves give one run. Only
+//! outputs with new content are written, and
+//! errors are shown without exiting.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use elapsed::measure_time;
+use descr_gen::process::write_file;
+use generate::{gen_outputs, Flags};
+
+const POLL: Duration = Duration::from_millis(100);
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Modified time and length, to
+/// tell when a grammar changed
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified().map(|modified| (modified, meta.len())))
+        .ok()
+}
+
+struct Target<'a> {
+    grammar_path: &'a Path,
+    output_dir: &'a str,
+    stamp: Stamp,
+    /// Time of a change not generated yet
+    changed: Option<Instant>,
+}
+
+impl<'a> Target<'a> {
+    /// Notes a change of the grammar, giving
+    /// whether to generate it at now
+    fn poll(&mut self, now: Instant) -> bool {
+        let stamp = stamp(self.grammar_path);
+        if stamp != self.stamp {
+            self.stamp = stamp;
+            self.changed = Some(now);
+        } else if self.changed.map(|at| now - at >= DEBOUNCE).unwrap_or(false) {
+            self.changed = None;
+            return true;
+        }
+        false
+    }
+}
+
+pub fn watch(targets: &[(PathBuf, String)], flags: &Flags) {
+    let mut targets = targets
+        .iter()
+        .map(|target| Target {
+            grammar_path: &target.0,
+            output_dir: &target.1,
+            stamp: stamp(&target.0),
+            changed: None,
+        })
+        .collect::<Vec<_>>();
+    for target in &targets {
+        regenerate(target, flags);
+    }
+    println!("Watching {} grammars, ctrl-c to stop", targets.len());
+    loop {
+        thread::sleep(POLL);
+        for target in &mut targets {
+            if target.poll(Instant::now()) {
+                regenerate(target, flags);
+            }
+        }
+    }
+}
+
+fn regenerate(target: &Target, flags: &Flags) {
+    let (elapsed, res) = measure_time(|| gen_outputs(target.grammar_path, flags.options(target.grammar_path)));
+    match res {
+        Ok(outputs) => {
+            let written = match write_changed(target.output_dir, outputs) {
+                Ok(written) => written,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return;
+                }
+            };
+            if written.is_empty() {
+                println!("{}: no changes ({})", target.grammar_path.display(), elapsed);
+            } else {
+                println!(
+                    "{}: wrote {} ({})",
+                    target.grammar_path.display(),
+                    written.join(", "),
+                    elapsed
+                );
+            }
+        }
+        Err(err) => eprintln!("{}", err),
+    }
+}
+
+/// Writes outputs with new content,
+/// giving their names
+pub fn write_changed(output_dir: &str, outputs: BTreeMap<String, String>) -> Result<Vec<String>, String> {
+    let mut written = Vec::new();
+    for (name, content) in outputs {
+        let changed = write_file(output_dir, &name, &content)
+            .map_err(|err| format!("Could not write {}: {}", Path::new(output_dir).join(&name).display(), err))?;
+        if changed {
+            written.push(name);
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// Empty dir of the test
+    fn dir(test: &str) -> PathBuf {
+        let dir = env::temp_dir().join("descr-watch").join(test);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn generates_once_a_change_settles() {
+        let grammar_path = dir("poll").join("lang.lang");
+        fs::write(&grammar_path, "Source(int)\n").unwrap();
+        let mut target = Target {
+            grammar_path: &grammar_path,
+            output_dir: "",
+            stamp: stamp(&grammar_path),
+            changed: None,
+        };
+        let start = Instant::now();
+        assert!(!target.poll(start + DEBOUNCE));
+        // Saves within the debounce time
+        // move the change along
+        fs::write(&grammar_path, "Source(ident)\n").unwrap();
+        assert!(!target.poll(start));
+        fs::write(&grammar_path, "Source(string)\n").unwrap();
+        assert!(!target.poll(start + POLL));
+        assert!(!target.poll(start + DEBOUNCE));
+        assert!(target.poll(start + POLL + DEBOUNCE));
+        assert!(!target.poll(start + POLL * 2 + DEBOUNCE));
+        // Removing the grammar is a change
+        fs::remove_file(&grammar_path).unwrap();
+        assert!(!target.poll(start));
+        assert!(target.poll(start + DEBOUNCE));
+    }
+
+    #[test]
+    fn writes_only_changed_outputs() {
+        let dir = dir("write");
+        let output_dir = dir.to_str().unwrap();
+        let outputs = |ast: &str| {
+            let mut outputs = BTreeMap::new();
+            outputs.insert("ast.rs".to_string(), ast.to_string());
+            outputs.insert("mod.rs".to_string(), "pub mod ast;\n".to_string());
+            outputs
+        };
+        assert_eq!(write_changed(output_dir, outputs("struct A;\n")), Ok(vec!["ast.rs".to_string(), "mod.rs".to_string()]));
+        assert_eq!(write_changed(output_dir, outputs("struct A;\n")), Ok(Vec::new()));
+        assert_eq!(write_changed(output_dir, outputs("struct B;\n")), Ok(vec!["ast.rs".to_string()]));
+        assert_eq!(fs::read_to_string(dir.join("ast.rs")).unwrap(), "struct B;\n");
+        // Errors name the output
+        let err = write_changed(dir.join("ast.rs").to_str().unwrap(), outputs("")).unwrap_err();
+        assert!(err.starts_with("Could not write"), "{}", err);
+    }
+}
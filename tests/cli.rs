@@ -0,0 +1,142 @@
+//! Runs the descr binary, checking exit codes:
+//! 0 on success, 1 for errors in grammars or
+//! inputs, 2 for invalid arguments.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const JSON: &str = "langs/json-descr/json.lang";
+
+fn descr(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_descr"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn code(args: &[&str]) -> i32 {
+    descr(args).status.code().unwrap()
+}
+
+fn stdout(args: &[&str]) -> String {
+    String::from_utf8(descr(args).stdout).unwrap()
+}
+
+/// File with content in a dir of the test
+fn file(test: &str, name: &str, content: &str) -> PathBuf {
+    let dir = env::temp_dir().join("descr-cli").join(test);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+fn arg(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+#[test]
+fn usage_exits_with_2() {
+    assert_eq!(code(&[]), 2);
+    assert_eq!(code(&["unknown"]), 2);
+    assert_eq!(code(&["check"]), 2);
+    assert_eq!(code(&["parse", JSON]), 2);
+    assert_eq!(code(&["lint", JSON, JSON]), 2);
+    assert_eq!(code(&["fmt", JSON, "--write", "--check"]), 2);
+}
+
+#[test]
+fn help_exits_with_0() {
+    let output = descr(&["parse", "--help"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--name Name"));
+    for command in &["highlight", "graph"] {
+        let output = descr(&[command, "--help"]);
+        assert_eq!(output.status.code(), Some(0));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--name Name"), "{}", command);
+    }
+}
+
+#[test]
+fn removed_project_commands_are_unknown() {
+    assert_eq!(code(&["pg"]), 2);
+    assert_eq!(code(&["pg-lang"]), 2);
+    assert_eq!(code(&["descr-lang"]), 2);
+}
+
+#[test]
+fn rejects_unknown_flags_and_missing_values() {
+    assert_eq!(code(&["check", JSON, "--bogus"]), 2);
+    assert_eq!(code(&["check", JSON, "--outputs", "ast,bogus"]), 2);
+    assert_eq!(code(&["check", JSON, "--name"]), 2);
+    assert_eq!(code(&["check", JSON, "--name", "--rd"]), 2);
+    assert_eq!(code(&["lint", JSON, "--source"]), 2);
+    assert_eq!(code(&["highlight", JSON, JSON, "--bogus"]), 2);
+    assert_eq!(code(&["highlight", JSON, JSON, "--theme"]), 2);
+    assert_eq!(code(&["graph", JSON, "--bogus"]), 2);
+    assert_eq!(code(&["graph", JSON, "--outputs", "bogus"]), 2);
+    assert_eq!(code(&["check", JSON, "descr.lang", "--name", "Json"]), 2);
+}
+
+#[test]
+fn check_lists_outputs_of_chosen_backends() {
+    assert_eq!(code(&["check", JSON]), 0);
+    let out = stdout(&["check", JSON, "--outputs", "ast,parsers", "--module", "lang"]);
+    assert!(out.contains("json.lang: ok"));
+    assert!(out.contains("    lang/ast.rs\n"));
+    assert!(out.contains("    lang/parsers.rs\n"));
+    assert!(!out.contains("visitor.rs"));
+}
+
+#[test]
+fn check_exits_with_1_for_grammar_errors() {
+    let grammar = file("check", "missing.lang", "Source(LBRACE value:Missing)\n");
+    assert_eq!(code(&["check", arg(&grammar)]), 1);
+    assert_eq!(code(&["check", "does-not-exist.lang"]), 1);
+}
+
+#[test]
+fn parse_takes_lang_flags() {
+    let input = file("parse", "input.json", "{\"a\": [1, 2]}");
+    let output = descr(&["parse", JSON, arg(&input), "--name", "Js", "--trace", "--rd"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("JsObject"));
+    // The trace is printed to stderr
+    assert!(String::from_utf8_lossy(&output.stderr).contains("LBRACE"));
+    let input = file("parse", "invalid.json", "{\"a\": }");
+    assert_eq!(code(&["parse", JSON, arg(&input)]), 1);
+}
+
+#[test]
+fn highlight_and_graph_take_lang_flags() {
+    let input = file("highlight", "input.json", "{\"a\": [1, 2]}");
+    let out = stdout(&["highlight", JSON, arg(&input), "--html", "--name", "Js", "--rd"]);
+    assert!(out.contains("<!DOCTYPE html>"));
+    assert_eq!(code(&["graph", JSON, "--dot", "--outputs", "ast", "--module", "lang"]), 0);
+}
+
+#[test]
+fn fmt_check_exits_with_1_when_unformatted() {
+    let grammar = file("fmt", "spaced.lang", "Source(LBRACE   items:items RBRACE)\n\n\nitems[] COMMA  Int\nInt(int)\n");
+    assert_eq!(code(&["fmt", arg(&grammar), "--check"]), 1);
+    let formatted = stdout(&["fmt", arg(&grammar)]);
+    let grammar = file("fmt", "formatted.lang", &formatted);
+    assert_eq!(code(&["fmt", arg(&grammar), "--check", "--outputs", "ast"]), 0);
+}
+
+#[test]
+fn generate_writes_module_dir() {
+    let dir = env::temp_dir().join("descr-cli").join("generate");
+    let _ = fs::remove_dir_all(&dir);
+    let args = ["generate", JSON, arg(&dir), "--outputs", "ast", "--module", "lang", "--name", "Js"];
+    assert_eq!(code(&args), 0);
+    let ast = fs::read_to_string(dir.join("lang").join("ast.rs")).unwrap();
+    assert!(ast.contains("pub struct JsObject"));
+    assert!(!dir.join("lang").join("parsers.rs").exists());
+    // Nothing differs after writing
+    let mut args = args.to_vec();
+    args.push("--check");
+    assert_eq!(code(&args), 0);
+}